@@ -30,3 +30,39 @@ pub struct DynamicParams {
     // true to return contract log
     pub return_log: bool,
 }
+
+// resource limits the TEE-Worker enforces when executing a dynamic assertion
+#[derive(Encode, Decode, Clone, Copy, Debug, PartialEq, Eq, MaxEncodedLen, TypeInfo)]
+pub struct AssertionLimits {
+    // gas available to the assertion, the deployment of its contract included
+    pub max_gas: u64,
+    // number of http calls the assertion may issue
+    pub max_http_calls: u32,
+    // size of a single http response body, in bytes
+    pub max_response_size: u32,
+    // wall-clock time of the whole execution, in milliseconds
+    pub max_execution_time: u64,
+}
+
+impl Default for AssertionLimits {
+    fn default() -> Self {
+        Self {
+            max_gas: 100_000_000,
+            max_http_calls: 32,
+            max_response_size: 1024 * 1024,
+            max_execution_time: 60_000,
+        }
+    }
+}
+
+// max length of a DNS name
+pub type AssertionDomain = BoundedVec<u8, ConstU32<253>>;
+pub type AssertionAllowedDomains = BoundedVec<AssertionDomain, ConstU32<32>>;
+
+// policy declared when an assertion is created, the allowlist also covers the subdomains
+// of every listed domain, an empty allowlist forbids any outbound http call
+#[derive(Encode, Decode, Clone, Default, Debug, PartialEq, Eq, MaxEncodedLen, TypeInfo)]
+pub struct AssertionPolicy {
+    pub limits: AssertionLimits,
+    pub allowed_domains: AssertionAllowedDomains,
+}
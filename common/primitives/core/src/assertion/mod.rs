@@ -220,4 +220,44 @@ impl Assertion {
             _ => false,
         }
     }
+
+    // How long (in seconds) an issued VC can be served from the enclave-side VC cache,
+    // `None` means the VC is always built from scratch.
+    //
+    // Assertions based on balances are only cached for a short time, while assertions
+    // based on past events or memberships can be cached longer.
+    pub fn vc_cache_ttl(&self) -> Option<u64> {
+        match self {
+            // the delegatee claim is checked (and consumed) while building the VC
+            Self::A13(..) => None,
+            // the assertion logic can be upgraded at any time
+            Self::Dynamic(..) => None,
+            Self::A4(..)
+            | Self::A7(..)
+            | Self::A10(..)
+            | Self::A11(..)
+            | Self::Achainable(..)
+            | Self::BnbDomainHolding
+            | Self::BnbDigitDomainClub(..)
+            | Self::WeirdoGhostGangHolder
+            | Self::LITStaking
+            | Self::EVMAmountHolding(..)
+            | Self::BRC20AmountHolder
+            | Self::CryptoSummary
+            | Self::TokenHoldingAmount(..)
+            | Self::NftHolder(..) => Some(10 * 60),
+            Self::A1
+            | Self::A2(..)
+            | Self::A3(..)
+            | Self::A6
+            | Self::A8(..)
+            | Self::A14
+            | Self::A20
+            | Self::OneBlock(..)
+            | Self::GenericDiscordRole(..)
+            | Self::VIP3MembershipCard(..)
+            | Self::PlatformUser(..)
+            | Self::LinkedIdentities => Some(60 * 60),
+        }
+    }
 }
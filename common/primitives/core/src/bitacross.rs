@@ -0,0 +1,116 @@
+// Copyright 2020-2024 Trust Computing GmbH.
+// This file is part of Litentry.
+//
+// Litentry is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// Litentry is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with Litentry.  If not, see <https://www.gnu.org/licenses/>.
+
+use parity_scale_codec::{Decode, Encode, MaxEncodedLen};
+use scale_info::TypeInfo;
+use sp_runtime::{traits::ConstU32, BoundedVec};
+
+pub const MAX_BTC_SCRIPT_LEN: u32 = 128;
+pub const MAX_BTC_DESTINATIONS: u32 = 64;
+
+pub type BtcScript = BoundedVec<u8, ConstU32<MAX_BTC_SCRIPT_LEN>>;
+
+/// Rules the bitacross TEEs check a PSBT against before signing it with the vault key.
+///
+/// Amounts are in satoshis and measure what leaves the vault, i.e. the value of the spent vault
+/// outputs minus the change going back to the vault, so fees are included.
+///
+/// Each TEE counts `max_amount_per_day` against the requests it was sent. Every TEE has to
+/// authorize a spend before it's signed, so the daily cap holds for the vault. PSBTs are not
+/// signed with a threshold key, where a subset of the TEEs could sign.
+#[derive(Encode, Decode, Debug, Clone, PartialEq, Eq, MaxEncodedLen, TypeInfo)]
+pub struct BtcSigningPolicy {
+    // scriptPubKeys which may receive funds, every other output must go back to the vault
+    pub allowed_destinations: BoundedVec<BtcScript, ConstU32<MAX_BTC_DESTINATIONS>>,
+    pub max_amount_per_tx: u64,
+    // per UTC day
+    pub max_amount_per_day: u64,
+}
+
+pub const MAX_ETH_CONTRACTS: u32 = 64;
+pub const MAX_ETH_SELECTORS: u32 = 64;
+
+#[derive(Encode, Decode, Debug, Clone, PartialEq, Eq, MaxEncodedLen, TypeInfo)]
+pub struct EthContract {
+    pub chain_id: u64,
+    pub address: [u8; 20],
+}
+
+/// What a relayer may have the bitacross TEEs sign with the Ethereum key.
+///
+/// Transactions have to go to one of `contracts` and either call one of `selectors` or carry no
+/// calldata at all. EIP-712 typed data has to name one of `contracts` as verifying contract.
+/// Prehashed messages are refused for relayers with an allowlist.
+#[derive(Encode, Decode, Debug, Clone, PartialEq, Eq, MaxEncodedLen, TypeInfo)]
+pub struct EthSigningAllowlist {
+    pub contracts: BoundedVec<EthContract, ConstU32<MAX_ETH_CONTRACTS>>,
+    pub selectors: BoundedVec<[u8; 4], ConstU32<MAX_ETH_SELECTORS>>,
+}
+
+pub const MAX_RELAYER_CHAINS: u32 = 8;
+
+/// Chains the bitacross TEEs sign for.
+#[derive(Encode, Decode, Debug, Clone, Copy, PartialEq, Eq, MaxEncodedLen, TypeInfo)]
+pub enum RelayerChain {
+    Bitcoin,
+    Ethereum,
+    Ton,
+    Solana,
+}
+
+#[derive(Encode, Decode, Debug, Clone, PartialEq, Eq, MaxEncodedLen, TypeInfo)]
+pub struct ChainPermission {
+    pub chain: RelayerChain,
+    // signing requests per UTC day, unlimited if not set
+    pub daily_quota: Option<u32>,
+}
+
+/// What a relayer may have the bitacross TEEs sign.
+///
+/// Requests for chains not in `chains` are rejected, and so is every request once the relayer
+/// expired. `expires_at` is a unix timestamp in seconds, checked against the clock of the TEEs.
+///
+/// Daily quotas are counted by each TEE on its own for the requests it receives, so a relayer
+/// sending its requests to all of them gets up to `daily_quota` times the number of TEEs a day.
+#[derive(Encode, Decode, Debug, Clone, PartialEq, Eq, MaxEncodedLen, TypeInfo)]
+pub struct RelayerPermissions {
+    pub chains: BoundedVec<ChainPermission, ConstU32<MAX_RELAYER_CHAINS>>,
+    pub expires_at: Option<u64>,
+}
+
+impl RelayerPermissions {
+    /// Every chain without quota or expiry, which is what relayers had before permissions existed
+    pub fn unrestricted() -> Self {
+        let chains = [
+            RelayerChain::Bitcoin,
+            RelayerChain::Ethereum,
+            RelayerChain::Ton,
+            RelayerChain::Solana,
+        ]
+        .map(|chain| ChainPermission {
+            chain,
+            daily_quota: None,
+        });
+        Self {
+            chains: BoundedVec::truncate_from(chains.to_vec()),
+            expires_at: None,
+        }
+    }
+
+    pub fn chain(&self, chain: RelayerChain) -> Option<&ChainPermission> {
+        self.chains.iter().find(|p| p.chain == chain)
+    }
+}
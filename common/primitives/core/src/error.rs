@@ -68,6 +68,12 @@ pub enum ErrorDetail {
     // error when trying to build vc but no eligible identity is found
     #[codec(index = 11)]
     NoEligibleIdentity,
+    // error when a dynamic assertion exceeds one of the resource limits it was declared with
+    #[codec(index = 12)]
+    AssertionLimitExceeded(ErrorString),
+    // error when a dynamic assertion calls a domain outside of its allowlist
+    #[codec(index = 13)]
+    AssertionDomainNotAllowed(ErrorString),
 }
 
 impl fmt::Debug for ErrorDetail {
@@ -90,6 +96,14 @@ impl fmt::Debug for ErrorDetail {
             ErrorDetail::UnexpectedMessage => write!(f, "UnexpectedMessage"),
             ErrorDetail::VerifyWeb3SignatureFailed => write!(f, "VerifyWeb3SignatureFailed"),
             ErrorDetail::NoEligibleIdentity => write!(f, "NoEligibleIdentity"),
+            ErrorDetail::AssertionLimitExceeded(error_string) => {
+                let text = String::from_utf8(error_string.to_vec()).map_err(|_| fmt::Error)?;
+                write!(f, "AssertionLimitExceeded({})", text)
+            }
+            ErrorDetail::AssertionDomainNotAllowed(error_string) => {
+                let text = String::from_utf8(error_string.to_vec()).map_err(|_| fmt::Error)?;
+                write!(f, "AssertionDomainNotAllowed({})", text)
+            }
         }
     }
 }
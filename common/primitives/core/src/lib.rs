@@ -38,6 +38,9 @@ pub use intent::*;
 pub mod omni_account;
 pub use omni_account::*;
 
+pub mod bitacross;
+pub use bitacross::*;
+
 extern crate alloc;
 extern crate core;
 use alloc::{format, str, str::FromStr, string::String, vec, vec::Vec};
@@ -19,6 +19,8 @@ use scale_info::TypeInfo;
 
 pub type PubKey33 = [u8; 33];
 pub type PubKey32 = [u8; 32];
+// Keccak-256 Merkle root
+pub type AuditRoot = [u8; 32];
 
 /// custodial wallet that each tee worker generates and holds
 #[derive(Encode, Decode, Clone, Default, Debug, PartialEq, Eq, TypeInfo)]
@@ -26,6 +28,7 @@ pub struct CustodialWallet {
 	pub btc: Option<PubKey33>,
 	pub eth: Option<PubKey33>,
 	pub ton: Option<PubKey32>,
+	pub sol: Option<PubKey32>,
 }
 
 impl CustodialWallet {
@@ -40,4 +43,8 @@ impl CustodialWallet {
 	pub fn has_ton(&self) -> bool {
 		self.ton.is_some()
 	}
+
+	pub fn has_sol(&self) -> bool {
+		self.sol.is_some()
+	}
 }
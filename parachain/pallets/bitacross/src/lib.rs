@@ -16,7 +16,7 @@
 
 #![cfg_attr(not(feature = "std"), no_std)]
 
-use core_primitives::Identity;
+use core_primitives::{BtcSigningPolicy, EthSigningAllowlist, Identity, RelayerPermissions};
 use frame_support::{
 	dispatch::{DispatchResult, DispatchResultWithPostInfo},
 	ensure,
@@ -30,11 +30,21 @@ pub use pallet::*;
 mod custodial_wallet;
 pub use custodial_wallet::*;
 
+pub mod migration;
+
+#[cfg(test)]
+mod mock;
+#[cfg(test)]
+mod tests;
+
 #[frame_support::pallet]
 pub mod pallet {
 	use super::*;
 
+	const STORAGE_VERSION: StorageVersion = StorageVersion::new(2);
+
 	#[pallet::pallet]
+	#[pallet::storage_version(STORAGE_VERSION)]
 	#[pallet::without_storage_info]
 	pub struct Pallet<T>(PhantomData<T>);
 
@@ -52,10 +62,11 @@ pub mod pallet {
 	pub type Admin<T: Config> = StorageValue<_, T::AccountId, OptionQuery>;
 
 	// use `Identity` as key to keep the flexibility, however we do further check its type when
-	// adding them
+	// adding them. The value is what the relayer may have the TEEs sign
 	#[pallet::storage]
 	#[pallet::getter(fn relayer)]
-	pub type Relayer<T: Config> = StorageMap<_, Blake2_128Concat, Identity, (), OptionQuery>;
+	pub type Relayer<T: Config> =
+		StorageMap<_, Blake2_128Concat, Identity, RelayerPermissions, OptionQuery>;
 
 	// `ValueQuery` is used as each field in CustodialWallet is optional already
 	// not using Option<CustodialWallet> either as each field is set separately
@@ -64,16 +75,55 @@ pub mod pallet {
 	pub type Vault<T: Config> =
 		StorageMap<_, Blake2_128Concat, T::AccountId, CustodialWallet, ValueQuery>;
 
+	// group public key of the threshold key together with its threshold
+	#[pallet::storage]
+	#[pallet::getter(fn threshold_key)]
+	pub type ThresholdKey<T: Config> = StorageValue<_, (PubKey33, u16), OptionQuery>;
+
+	// TEE accounts holding a share of the threshold key, the value is the group public key
+	#[pallet::storage]
+	#[pallet::getter(fn threshold_key_holder)]
+	pub type ThresholdKeyHolder<T: Config> =
+		StorageMap<_, Blake2_128Concat, T::AccountId, PubKey33, OptionQuery>;
+
+	// checked by the TEEs before they sign a PSBT spending from the vault, no PSBT is signed
+	// as long as it's not set
+	#[pallet::storage]
+	#[pallet::getter(fn btc_signing_policy)]
+	pub type SigningPolicy<T: Config> = StorageValue<_, BtcSigningPolicy, OptionQuery>;
+
+	// checked by the TEEs before they sign Ethereum transactions or typed data for a relayer,
+	// nothing is signed for relayers without an allowlist
+	#[pallet::storage]
+	#[pallet::getter(fn eth_signing_allowlist)]
+	pub type EthAllowlist<T: Config> =
+		StorageMap<_, Blake2_128Concat, Identity, EthSigningAllowlist, OptionQuery>;
+
+	// Merkle root of the signature audit log of each TEE together with the number of entries it
+	// covers, the log is append-only so the number never goes down
+	#[pallet::storage]
+	#[pallet::getter(fn signature_audit_root)]
+	pub type SignatureAuditRoot<T: Config> =
+		StorageMap<_, Blake2_128Concat, T::AccountId, (AuditRoot, u64), OptionQuery>;
+
 	#[pallet::event]
 	#[pallet::generate_deposit(pub(super) fn deposit_event)]
 	pub enum Event<T: Config> {
 		AdminSet { new_admin: Option<T::AccountId> },
 		RelayerAdded { who: Identity },
 		RelayerRemoved { who: Identity },
+		RelayerPermissionsSet { who: Identity, permissions: RelayerPermissions },
 		BtcWalletGenerated { pub_key: PubKey33, account_id: T::AccountId },
 		EthWalletGenerated { pub_key: PubKey33 },
 		TonWalletGenerated { pub_key: PubKey32 },
+		SolWalletGenerated { pub_key: PubKey32 },
 		VaultRemoved { who: T::AccountId },
+		ThresholdKeyGenerated { pub_key: PubKey33, threshold: u16, account_id: T::AccountId },
+		ThresholdKeyReshared { pub_key: PubKey33, threshold: u16, account_id: T::AccountId },
+		ThresholdKeyHolderRemoved { who: T::AccountId },
+		BtcSigningPolicySet { policy: BtcSigningPolicy },
+		EthSigningAllowlistSet { relayer: Identity, allowlist: Option<EthSigningAllowlist> },
+		SignatureAuditRootPosted { root: AuditRoot, size: u64, account_id: T::AccountId },
 	}
 
 	#[pallet::error]
@@ -84,7 +134,12 @@ pub mod pallet {
 		BtcWalletAlreadyExist,
 		EthWalletAlreadyExist,
 		TonWalletAlreadyExist,
+		SolWalletAlreadyExist,
 		VaultNotExist,
+		ThresholdKeyMismatch,
+		ThresholdKeyAlreadyExist,
+		ThresholdKeyHolderNotExist,
+		AuditLogNotGrown,
 	}
 
 	#[pallet::genesis_config]
@@ -129,8 +184,10 @@ pub mod pallet {
 		pub fn add_relayer(origin: OriginFor<T>, account: Identity) -> DispatchResultWithPostInfo {
 			Self::ensure_admin_or_root(origin)?;
 			ensure!(account.is_substrate() || account.is_evm(), Error::<T>::UnsupportedRelayerType);
-			// we don't care if `account` already exists
-			Relayer::<T>::insert(account.clone(), ());
+			// we don't care if `account` already exists, but its permissions are kept then
+			Relayer::<T>::mutate(&account, |permissions| {
+				permissions.get_or_insert_with(RelayerPermissions::unrestricted);
+			});
 			Self::deposit_event(Event::RelayerAdded { who: account });
 			Ok(Pays::No.into())
 		}
@@ -161,6 +218,67 @@ pub mod pallet {
 			Ok(Pays::No.into())
 		}
 
+		/// Set the policy PSBTs spending from the vault have to comply with
+		#[pallet::call_index(4)]
+		#[pallet::weight({195_000_000})]
+		pub fn set_btc_signing_policy(
+			origin: OriginFor<T>,
+			policy: BtcSigningPolicy,
+		) -> DispatchResultWithPostInfo {
+			Self::ensure_admin_or_root(origin)?;
+			SigningPolicy::<T>::put(policy.clone());
+			Self::deposit_event(Event::BtcSigningPolicySet { policy });
+			Ok(Pays::No.into())
+		}
+
+		/// Set or, with `None`, remove the Ethereum signing allowlist of a relayer
+		#[pallet::call_index(5)]
+		#[pallet::weight({195_000_000})]
+		pub fn set_eth_signing_allowlist(
+			origin: OriginFor<T>,
+			relayer: Identity,
+			allowlist: Option<EthSigningAllowlist>,
+		) -> DispatchResultWithPostInfo {
+			Self::ensure_admin_or_root(origin)?;
+			ensure!(Relayer::<T>::contains_key(&relayer), Error::<T>::RelayerNotExist);
+			EthAllowlist::<T>::set(relayer.clone(), allowlist.clone());
+			Self::deposit_event(Event::EthSigningAllowlistSet { relayer, allowlist });
+			Ok(Pays::No.into())
+		}
+
+		/// Set the chains a relayer may sign on, their daily quotas and when the relayer expires
+		#[pallet::call_index(6)]
+		#[pallet::weight({195_000_000})]
+		pub fn set_relayer_permissions(
+			origin: OriginFor<T>,
+			account: Identity,
+			permissions: RelayerPermissions,
+		) -> DispatchResultWithPostInfo {
+			Self::ensure_admin_or_root(origin)?;
+			ensure!(Relayer::<T>::contains_key(&account), Error::<T>::RelayerNotExist);
+			Relayer::<T>::insert(account.clone(), permissions.clone());
+			Self::deposit_event(Event::RelayerPermissionsSet { who: account, permissions });
+			Ok(Pays::No.into())
+		}
+
+		/// Drop an enclave from the holders of the threshold key, e.g. after it was removed from
+		/// teebag, the key itself is only replaced by a reshare among the remaining enclaves
+		#[pallet::call_index(7)]
+		#[pallet::weight({195_000_000})]
+		pub fn remove_threshold_key_holder(
+			origin: OriginFor<T>,
+			account: T::AccountId,
+		) -> DispatchResultWithPostInfo {
+			Self::ensure_admin_or_root(origin)?;
+			ensure!(
+				ThresholdKeyHolder::<T>::contains_key(&account),
+				Error::<T>::ThresholdKeyHolderNotExist
+			);
+			ThresholdKeyHolder::<T>::remove(account.clone());
+			Self::deposit_event(Event::ThresholdKeyHolderRemoved { who: account });
+			Ok(Pays::No.into())
+		}
+
 		/// ---------------------------------------------------
 		/// The following extrinsics are supposed to be called by TEE only
 		/// ---------------------------------------------------
@@ -216,6 +334,94 @@ pub mod pallet {
 			let _ = T::TEECallOrigin::ensure_origin(origin)?;
 			Ok(Pays::No.into())
 		}
+
+		/// Called by every TEE which took part in the generation of a threshold key, once a key
+		/// exists it can only be handed to other enclaves with a reshare
+		#[pallet::call_index(34)]
+		#[pallet::weight(({195_000_000}, DispatchClass::Normal, Pays::No))]
+		pub fn threshold_key_generated(
+			origin: OriginFor<T>,
+			pub_key: PubKey33,
+			threshold: u16,
+		) -> DispatchResultWithPostInfo {
+			let tee_account = T::TEECallOrigin::ensure_origin(origin)?;
+			// the other participants of the same generation report the same key
+			ensure!(
+				ThresholdKey::<T>::get().map_or(true, |(key, _)| key == pub_key),
+				Error::<T>::ThresholdKeyAlreadyExist
+			);
+			ThresholdKey::<T>::put((pub_key, threshold));
+			ThresholdKeyHolder::<T>::insert(tee_account.clone(), pub_key);
+			Self::deposit_event(Event::ThresholdKeyGenerated {
+				pub_key,
+				threshold,
+				account_id: tee_account,
+			});
+			Ok(Pays::No.into())
+		}
+
+		/// Called by every TEE which received shares of the threshold key after the set of
+		/// enclaves changed, the key itself stays the same
+		#[pallet::call_index(35)]
+		#[pallet::weight(({195_000_000}, DispatchClass::Normal, Pays::No))]
+		pub fn threshold_key_reshared(
+			origin: OriginFor<T>,
+			pub_key: PubKey33,
+			threshold: u16,
+		) -> DispatchResultWithPostInfo {
+			let tee_account = T::TEECallOrigin::ensure_origin(origin)?;
+			ensure!(
+				ThresholdKey::<T>::get().map(|(key, _)| key) == Some(pub_key),
+				Error::<T>::ThresholdKeyMismatch
+			);
+			ThresholdKey::<T>::put((pub_key, threshold));
+			ThresholdKeyHolder::<T>::insert(tee_account.clone(), pub_key);
+			Self::deposit_event(Event::ThresholdKeyReshared {
+				pub_key,
+				threshold,
+				account_id: tee_account,
+			});
+			Ok(Pays::No.into())
+		}
+
+		#[pallet::call_index(36)]
+		#[pallet::weight(({195_000_000}, DispatchClass::Normal, Pays::No))]
+		pub fn sol_wallet_generated(
+			origin: OriginFor<T>,
+			pub_key: PubKey32,
+		) -> DispatchResultWithPostInfo {
+			let tee_account = T::TEECallOrigin::ensure_origin(origin)?;
+			Vault::<T>::try_mutate(tee_account, |v| {
+				ensure!(!v.has_sol(), Error::<T>::SolWalletAlreadyExist);
+				v.sol = Some(pub_key);
+				Self::deposit_event(Event::SolWalletGenerated { pub_key });
+				Ok(Pays::No.into())
+			})
+		}
+
+		/// Called periodically by every TEE with the Merkle root of its signature audit log
+		#[pallet::call_index(37)]
+		#[pallet::weight(({195_000_000}, DispatchClass::Normal, Pays::No))]
+		pub fn signature_audit_root_posted(
+			origin: OriginFor<T>,
+			root: AuditRoot,
+			size: u64,
+		) -> DispatchResultWithPostInfo {
+			let tee_account = T::TEECallOrigin::ensure_origin(origin)?;
+			SignatureAuditRoot::<T>::try_mutate(tee_account.clone(), |posted| {
+				// a log which lost entries can't be anchored again
+				if let Some((_, posted_size)) = posted {
+					ensure!(size > *posted_size, Error::<T>::AuditLogNotGrown);
+				}
+				*posted = Some((root, size));
+				Self::deposit_event(Event::SignatureAuditRootPosted {
+					root,
+					size,
+					account_id: tee_account,
+				});
+				Ok(Pays::No.into())
+			})
+		}
 	}
 }
 
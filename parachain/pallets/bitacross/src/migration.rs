@@ -0,0 +1,71 @@
+// Copyright 2020-2024 Trust Computing GmbH.
+// This file is part of Litentry.
+//
+// Litentry is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// Litentry is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with Litentry.  If not, see <https://www.gnu.org/licenses/>.
+
+use crate::{Config, CustodialWallet, Pallet, PubKey32, PubKey33, Relayer, Vault};
+use core_primitives::RelayerPermissions;
+use frame_support::{
+	pallet_prelude::*,
+	traits::{GetStorageVersion, OnRuntimeUpgrade, StorageVersion},
+};
+
+// `CustodialWallet` before the `sol` slot was added
+#[derive(Encode, Decode)]
+struct CustodialWalletV0 {
+	btc: Option<PubKey33>,
+	eth: Option<PubKey33>,
+	ton: Option<PubKey32>,
+}
+
+/// Adds the `sol` slot to the custodial wallets in `Vault`, they wouldn't decode otherwise.
+pub struct MigrateToV1<T>(PhantomData<T>);
+
+impl<T: Config> OnRuntimeUpgrade for MigrateToV1<T> {
+	fn on_runtime_upgrade() -> Weight {
+		if Pallet::<T>::on_chain_storage_version() >= 1 {
+			return T::DbWeight::get().reads(1);
+		}
+
+		let mut translated = 0u64;
+		Vault::<T>::translate::<CustodialWalletV0, _>(|_, wallet| {
+			translated += 1;
+			Some(CustodialWallet { btc: wallet.btc, eth: wallet.eth, ton: wallet.ton, sol: None })
+		});
+		StorageVersion::new(1).put::<Pallet<T>>();
+
+		T::DbWeight::get().reads_writes(translated + 1, translated + 1)
+	}
+}
+
+/// Turns the relayers in `Relayer` into relayers which may sign on every chain, like they could
+/// before permissions were introduced.
+pub struct MigrateToV2<T>(PhantomData<T>);
+
+impl<T: Config> OnRuntimeUpgrade for MigrateToV2<T> {
+	fn on_runtime_upgrade() -> Weight {
+		if Pallet::<T>::on_chain_storage_version() >= 2 {
+			return T::DbWeight::get().reads(1);
+		}
+
+		let mut translated = 0u64;
+		Relayer::<T>::translate::<(), _>(|_, ()| {
+			translated += 1;
+			Some(RelayerPermissions::unrestricted())
+		});
+		StorageVersion::new(2).put::<Pallet<T>>();
+
+		T::DbWeight::get().reads_writes(translated + 1, translated + 1)
+	}
+}
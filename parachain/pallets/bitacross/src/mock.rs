@@ -0,0 +1,88 @@
+// Copyright 2020-2024 Trust Computing GmbH.
+// This file is part of Litentry.
+//
+// Litentry is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// Litentry is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with Litentry.  If not, see <https://www.gnu.org/licenses/>.
+
+#![cfg(test)]
+use crate as pallet_bitacross;
+use frame_support::parameter_types;
+use sp_runtime::{
+	testing::H256,
+	traits::{BlakeTwo256, IdentityLookup},
+	AccountId32, BuildStorage,
+};
+
+pub type AccountId = AccountId32;
+
+pub const ADMIN: AccountId = AccountId32::new([1u8; 32]);
+pub const ALICE: AccountId = AccountId32::new([2u8; 32]);
+pub const TEE_1: AccountId = AccountId32::new([11u8; 32]);
+pub const TEE_2: AccountId = AccountId32::new([12u8; 32]);
+
+// Configure a mock runtime to test the pallet.
+frame_support::construct_runtime!(
+	pub enum Test
+	{
+		System: frame_system,
+		Bitacross: pallet_bitacross,
+	}
+);
+
+parameter_types! {
+	pub const BlockHashCount: u64 = 250;
+}
+impl frame_system::Config for Test {
+	type BaseCallFilter = frame_support::traits::Everything;
+	type BlockWeights = ();
+	type BlockLength = ();
+	type Block = frame_system::mocking::MockBlock<Test>;
+	type DbWeight = ();
+	type RuntimeOrigin = RuntimeOrigin;
+	type Nonce = u64;
+	type RuntimeCall = RuntimeCall;
+	type Hash = H256;
+	type Hashing = BlakeTwo256;
+	type AccountId = AccountId;
+	type Lookup = IdentityLookup<Self::AccountId>;
+	type RuntimeEvent = RuntimeEvent;
+	type BlockHashCount = BlockHashCount;
+	type Version = ();
+	type PalletInfo = PalletInfo;
+	type AccountData = ();
+	type OnNewAccount = ();
+	type OnKilledAccount = ();
+	type SystemWeightInfo = ();
+	type SS58Prefix = ();
+	type OnSetCode = ();
+	type MaxConsumers = frame_support::traits::ConstU32<16>;
+}
+
+impl pallet_bitacross::Config for Test {
+	type RuntimeEvent = RuntimeEvent;
+	// any signed account plays an enclave here
+	type TEECallOrigin = frame_system::EnsureSigned<Self::AccountId>;
+	type SetAdminOrigin = frame_system::EnsureRoot<Self::AccountId>;
+}
+
+pub fn new_test_ext() -> sp_io::TestExternalities {
+	let mut test_storage = frame_system::GenesisConfig::<Test>::default().build_storage().unwrap();
+	pallet_bitacross::GenesisConfig::<Test> { admin: Some(ADMIN) }
+		.assimilate_storage(&mut test_storage)
+		.unwrap();
+	let mut ext = sp_io::TestExternalities::new(test_storage);
+	ext.execute_with(|| {
+		System::set_block_number(1);
+	});
+	ext
+}
@@ -0,0 +1,284 @@
+// Copyright 2020-2024 Trust Computing GmbH.
+// This file is part of Litentry.
+//
+// Litentry is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// Litentry is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with Litentry.  If not, see <https://www.gnu.org/licenses/>.
+
+use crate::{
+	mock::*, Error, EthAllowlist, Event, Relayer, SignatureAuditRoot, SigningPolicy, ThresholdKey,
+	ThresholdKeyHolder, Vault,
+};
+use core_primitives::{
+	BtcSigningPolicy, ChainPermission, EthContract, EthSigningAllowlist, Identity, RelayerChain,
+	RelayerPermissions,
+};
+use frame_support::{assert_noop, assert_ok, BoundedVec};
+use sp_runtime::DispatchError;
+
+fn relayer() -> Identity {
+	Identity::from(ALICE)
+}
+
+#[test]
+fn threshold_key_generated_works_for_every_participant() {
+	new_test_ext().execute_with(|| {
+		let pub_key = [3u8; 33];
+
+		assert_ok!(Bitacross::threshold_key_generated(RuntimeOrigin::signed(TEE_1), pub_key, 2));
+		assert_ok!(Bitacross::threshold_key_generated(RuntimeOrigin::signed(TEE_2), pub_key, 2));
+
+		assert_eq!(ThresholdKey::<Test>::get(), Some((pub_key, 2)));
+		assert_eq!(ThresholdKeyHolder::<Test>::get(TEE_1), Some(pub_key));
+		assert_eq!(ThresholdKeyHolder::<Test>::get(TEE_2), Some(pub_key));
+		System::assert_last_event(RuntimeEvent::Bitacross(Event::ThresholdKeyGenerated {
+			pub_key,
+			threshold: 2,
+			account_id: TEE_2,
+		}));
+	});
+}
+
+#[test]
+fn threshold_key_generated_fails_when_other_key_exists() {
+	new_test_ext().execute_with(|| {
+		assert_ok!(Bitacross::threshold_key_generated(RuntimeOrigin::signed(TEE_1), [3u8; 33], 2));
+
+		assert_noop!(
+			Bitacross::threshold_key_generated(RuntimeOrigin::signed(TEE_2), [4u8; 33], 2),
+			Error::<Test>::ThresholdKeyAlreadyExist
+		);
+		assert_eq!(ThresholdKey::<Test>::get(), Some(([3u8; 33], 2)));
+	});
+}
+
+#[test]
+fn threshold_key_reshared_works() {
+	new_test_ext().execute_with(|| {
+		let pub_key = [3u8; 33];
+		assert_ok!(Bitacross::threshold_key_generated(RuntimeOrigin::signed(TEE_1), pub_key, 1));
+
+		assert_ok!(Bitacross::threshold_key_reshared(RuntimeOrigin::signed(TEE_2), pub_key, 2));
+
+		assert_eq!(ThresholdKey::<Test>::get(), Some((pub_key, 2)));
+		assert_eq!(ThresholdKeyHolder::<Test>::get(TEE_2), Some(pub_key));
+		System::assert_last_event(RuntimeEvent::Bitacross(Event::ThresholdKeyReshared {
+			pub_key,
+			threshold: 2,
+			account_id: TEE_2,
+		}));
+	});
+}
+
+#[test]
+fn threshold_key_reshared_fails_with_other_key() {
+	new_test_ext().execute_with(|| {
+		assert_noop!(
+			Bitacross::threshold_key_reshared(RuntimeOrigin::signed(TEE_1), [3u8; 33], 2),
+			Error::<Test>::ThresholdKeyMismatch
+		);
+
+		assert_ok!(Bitacross::threshold_key_generated(RuntimeOrigin::signed(TEE_1), [3u8; 33], 1));
+		assert_noop!(
+			Bitacross::threshold_key_reshared(RuntimeOrigin::signed(TEE_2), [4u8; 33], 2),
+			Error::<Test>::ThresholdKeyMismatch
+		);
+	});
+}
+
+#[test]
+fn remove_threshold_key_holder_works() {
+	new_test_ext().execute_with(|| {
+		let pub_key = [3u8; 33];
+		assert_ok!(Bitacross::threshold_key_generated(RuntimeOrigin::signed(TEE_1), pub_key, 1));
+		assert_ok!(Bitacross::threshold_key_generated(RuntimeOrigin::signed(TEE_2), pub_key, 1));
+
+		assert_ok!(Bitacross::remove_threshold_key_holder(RuntimeOrigin::signed(ADMIN), TEE_1));
+
+		assert_eq!(ThresholdKeyHolder::<Test>::get(TEE_1), None);
+		assert_eq!(ThresholdKeyHolder::<Test>::get(TEE_2), Some(pub_key));
+		assert_eq!(ThresholdKey::<Test>::get(), Some((pub_key, 1)));
+		System::assert_last_event(RuntimeEvent::Bitacross(Event::ThresholdKeyHolderRemoved {
+			who: TEE_1,
+		}));
+	});
+}
+
+#[test]
+fn remove_threshold_key_holder_fails() {
+	new_test_ext().execute_with(|| {
+		assert_ok!(Bitacross::threshold_key_generated(RuntimeOrigin::signed(TEE_1), [3u8; 33], 1));
+
+		assert_noop!(
+			Bitacross::remove_threshold_key_holder(RuntimeOrigin::signed(ALICE), TEE_1),
+			Error::<Test>::RequireAdminOrRoot
+		);
+		assert_noop!(
+			Bitacross::remove_threshold_key_holder(RuntimeOrigin::root(), TEE_2),
+			Error::<Test>::ThresholdKeyHolderNotExist
+		);
+	});
+}
+
+#[test]
+fn set_btc_signing_policy_works() {
+	new_test_ext().execute_with(|| {
+		let policy = BtcSigningPolicy {
+			allowed_destinations: BoundedVec::truncate_from(vec![BoundedVec::truncate_from(vec![
+				0x00, 0x14,
+			])]),
+			max_amount_per_tx: 1_000,
+			max_amount_per_day: 10_000,
+		};
+
+		assert_noop!(
+			Bitacross::set_btc_signing_policy(RuntimeOrigin::signed(ALICE), policy.clone()),
+			Error::<Test>::RequireAdminOrRoot
+		);
+		assert_ok!(Bitacross::set_btc_signing_policy(RuntimeOrigin::signed(ADMIN), policy.clone()));
+
+		assert_eq!(SigningPolicy::<Test>::get(), Some(policy.clone()));
+		System::assert_last_event(RuntimeEvent::Bitacross(Event::BtcSigningPolicySet { policy }));
+	});
+}
+
+#[test]
+fn set_eth_signing_allowlist_works() {
+	new_test_ext().execute_with(|| {
+		let allowlist = EthSigningAllowlist {
+			contracts: BoundedVec::truncate_from(vec![EthContract {
+				chain_id: 1,
+				address: [5u8; 20],
+			}]),
+			selectors: BoundedVec::truncate_from(vec![[0xa9, 0x05, 0x9c, 0xbb]]),
+		};
+
+		assert_noop!(
+			Bitacross::set_eth_signing_allowlist(
+				RuntimeOrigin::root(),
+				relayer(),
+				Some(allowlist.clone())
+			),
+			Error::<Test>::RelayerNotExist
+		);
+		assert_ok!(Bitacross::add_relayer(RuntimeOrigin::root(), relayer()));
+
+		assert_ok!(Bitacross::set_eth_signing_allowlist(
+			RuntimeOrigin::root(),
+			relayer(),
+			Some(allowlist.clone())
+		));
+		assert_eq!(EthAllowlist::<Test>::get(relayer()), Some(allowlist));
+
+		assert_ok!(Bitacross::set_eth_signing_allowlist(RuntimeOrigin::root(), relayer(), None));
+		assert_eq!(EthAllowlist::<Test>::get(relayer()), None);
+	});
+}
+
+#[test]
+fn set_relayer_permissions_works() {
+	new_test_ext().execute_with(|| {
+		let permissions = RelayerPermissions {
+			chains: BoundedVec::truncate_from(vec![ChainPermission {
+				chain: RelayerChain::Bitcoin,
+				daily_quota: Some(10),
+			}]),
+			expires_at: Some(1_700_000_000),
+		};
+
+		assert_noop!(
+			Bitacross::set_relayer_permissions(
+				RuntimeOrigin::root(),
+				relayer(),
+				permissions.clone()
+			),
+			Error::<Test>::RelayerNotExist
+		);
+		assert_ok!(Bitacross::add_relayer(RuntimeOrigin::root(), relayer()));
+		assert_eq!(Relayer::<Test>::get(relayer()), Some(RelayerPermissions::unrestricted()));
+
+		assert_ok!(Bitacross::set_relayer_permissions(
+			RuntimeOrigin::root(),
+			relayer(),
+			permissions.clone()
+		));
+		assert_eq!(Relayer::<Test>::get(relayer()), Some(permissions.clone()));
+
+		// adding the relayer again keeps its permissions
+		assert_ok!(Bitacross::add_relayer(RuntimeOrigin::root(), relayer()));
+		assert_eq!(Relayer::<Test>::get(relayer()), Some(permissions));
+	});
+}
+
+#[test]
+fn signature_audit_root_posted_works() {
+	new_test_ext().execute_with(|| {
+		assert_ok!(Bitacross::signature_audit_root_posted(
+			RuntimeOrigin::signed(TEE_1),
+			[1u8; 32],
+			1
+		));
+		assert_ok!(Bitacross::signature_audit_root_posted(
+			RuntimeOrigin::signed(TEE_1),
+			[2u8; 32],
+			3
+		));
+
+		assert_eq!(SignatureAuditRoot::<Test>::get(TEE_1), Some(([2u8; 32], 3)));
+		System::assert_last_event(RuntimeEvent::Bitacross(Event::SignatureAuditRootPosted {
+			root: [2u8; 32],
+			size: 3,
+			account_id: TEE_1,
+		}));
+	});
+}
+
+#[test]
+fn signature_audit_root_posted_fails_when_log_did_not_grow() {
+	new_test_ext().execute_with(|| {
+		assert_ok!(Bitacross::signature_audit_root_posted(
+			RuntimeOrigin::signed(TEE_1),
+			[1u8; 32],
+			3
+		));
+
+		assert_noop!(
+			Bitacross::signature_audit_root_posted(RuntimeOrigin::signed(TEE_1), [2u8; 32], 3),
+			Error::<Test>::AuditLogNotGrown
+		);
+		assert_noop!(
+			Bitacross::signature_audit_root_posted(RuntimeOrigin::signed(TEE_1), [2u8; 32], 2),
+			Error::<Test>::AuditLogNotGrown
+		);
+	});
+}
+
+#[test]
+fn sol_wallet_generated_works() {
+	new_test_ext().execute_with(|| {
+		assert_noop!(
+			Bitacross::sol_wallet_generated(RuntimeOrigin::root(), [6u8; 32]),
+			DispatchError::BadOrigin
+		);
+
+		assert_ok!(Bitacross::sol_wallet_generated(RuntimeOrigin::signed(TEE_1), [6u8; 32]));
+		assert_eq!(Vault::<Test>::get(TEE_1).sol, Some([6u8; 32]));
+		System::assert_last_event(RuntimeEvent::Bitacross(Event::SolWalletGenerated {
+			pub_key: [6u8; 32],
+		}));
+
+		assert_noop!(
+			Bitacross::sol_wallet_generated(RuntimeOrigin::signed(TEE_1), [7u8; 32]),
+			Error::<Test>::SolWalletAlreadyExist
+		);
+	});
+}
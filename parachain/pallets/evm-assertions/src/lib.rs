@@ -15,11 +15,13 @@
 // along with Litentry.  If not, see <https://www.gnu.org/licenses/>.
 #![cfg_attr(not(feature = "std"), no_std)]
 
+pub mod migration;
 #[cfg(test)]
 mod mock;
 #[cfg(test)]
 mod tests;
 
+pub use core_primitives::assertion::dynamic::AssertionPolicy;
 pub use pallet::*;
 use sp_std::vec::Vec;
 
@@ -30,7 +32,7 @@ pub mod pallet {
 	use frame_system::pallet_prelude::OriginFor;
 	use scale_info::TypeInfo;
 	/// The current storage version.
-	const STORAGE_VERSION: StorageVersion = StorageVersion::new(0);
+	const STORAGE_VERSION: StorageVersion = StorageVersion::new(1);
 
 	#[pallet::pallet]
 	#[pallet::storage_version(STORAGE_VERSION)]
@@ -43,6 +45,9 @@ pub mod pallet {
 		pub secrets: Vec<Vec<u8>>,
 	}
 
+	/// Version of an assertion, `create_assertion` always stores version 0
+	pub type AssertionVersion = u32;
+
 	#[pallet::config]
 	pub trait Config: frame_system::Config {
 		/// The overarching event type.
@@ -61,6 +66,10 @@ pub mod pallet {
 
 		/// Only TEE-Workers can call some extrinsics
 		type TEECallOrigin: EnsureOrigin<Self::RuntimeOrigin>;
+
+		/// Maximum number of versions kept in the history of an assertion
+		#[pallet::constant]
+		type MaxAssertionVersions: Get<u32>;
 	}
 
 	/// Map for storing assertion smart contract bytecode alongside with additional secrets
@@ -70,23 +79,88 @@ pub mod pallet {
 	pub type Assertions<T: Config> =
 		StorageMap<_, Blake2_128Concat, T::AssertionId, Assertion, OptionQuery>;
 
+	/// Map for storing every retained version of an assertion, `Assertions` holds a copy of the
+	/// active one
+	#[pallet::storage]
+	#[pallet::getter(fn assertion_versions)]
+	pub type AssertionVersions<T: Config> = StorageDoubleMap<
+		_,
+		Blake2_128Concat,
+		T::AssertionId,
+		Twox64Concat,
+		AssertionVersion,
+		Assertion,
+		OptionQuery,
+	>;
+
+	/// Retained versions of an assertion, ordered from the oldest to the newest
+	#[pallet::storage]
+	#[pallet::getter(fn assertion_version_history)]
+	pub type AssertionVersionHistory<T: Config> = StorageMap<
+		_,
+		Blake2_128Concat,
+		T::AssertionId,
+		BoundedVec<AssertionVersion, T::MaxAssertionVersions>,
+		ValueQuery,
+	>;
+
+	/// Version of an assertion used by the TEE-Workers when issuing VCs
+	#[pallet::storage]
+	#[pallet::getter(fn active_assertion_version)]
+	pub type ActiveAssertionVersion<T: Config> =
+		StorageMap<_, Blake2_128Concat, T::AssertionId, AssertionVersion, ValueQuery>;
+
+	/// Resource limits and outbound domain allowlist an assertion was created with, enforced by
+	/// the TEE-Workers for every version of the assertion
+	#[pallet::storage]
+	#[pallet::getter(fn assertion_policies)]
+	pub type AssertionPolicies<T: Config> =
+		StorageMap<_, Blake2_128Concat, T::AssertionId, AssertionPolicy, OptionQuery>;
+
 	#[pallet::event]
 	#[pallet::generate_deposit(pub(super) fn deposit_event)]
 	pub enum Event<T: Config> {
-		AssertionCreated { id: T::AssertionId, byte_code: Vec<u8>, secrets: Vec<Vec<u8>> },
-		AssertionStored { id: T::AssertionId },
-		AssertionVoided { id: T::AssertionId },
+		AssertionCreated {
+			id: T::AssertionId,
+			byte_code: Vec<u8>,
+			secrets: Vec<Vec<u8>>,
+		},
+		AssertionStored {
+			id: T::AssertionId,
+		},
+		AssertionVoided {
+			id: T::AssertionId,
+		},
+		AssertionUpdated {
+			id: T::AssertionId,
+			version: AssertionVersion,
+			byte_code: Vec<u8>,
+			secrets: Vec<Vec<u8>>,
+		},
+		AssertionVersionActivated {
+			id: T::AssertionId,
+			version: AssertionVersion,
+		},
+		AssertionCreatedWithPolicy {
+			id: T::AssertionId,
+			byte_code: Vec<u8>,
+			secrets: Vec<Vec<u8>>,
+			policy: AssertionPolicy,
+		},
 	}
 
 	#[pallet::error]
 	pub enum Error<T> {
 		AssertionExists,
+		AssertionNotExists,
+		AssertionVersionNotExists,
+		AssertionVersionOverflow,
 	}
 
 	#[pallet::call]
 	impl<T: Config> Pallet<T> {
 		#[pallet::call_index(0)]
-		#[pallet::weight((T::DbWeight::get().write, DispatchClass::Normal, Pays::No))]
+		#[pallet::weight((T::DbWeight::get().writes(4), DispatchClass::Normal, Pays::No))]
 		pub fn create_assertion(
 			origin: OriginFor<T>,
 			id: T::AssertionId,
@@ -94,11 +168,7 @@ pub mod pallet {
 			secrets: Vec<Vec<u8>>,
 		) -> DispatchResultWithPostInfo {
 			let _ = T::ContractDevOrigin::ensure_origin(origin)?;
-			ensure!(!Assertions::<T>::contains_key(id), Error::<T>::AssertionExists);
-			Assertions::<T>::insert(
-				id,
-				Assertion { byte_code: byte_code.clone(), secrets: secrets.clone() },
-			);
+			Self::do_create_assertion(id, byte_code.clone(), secrets.clone())?;
 			Self::deposit_event(Event::AssertionCreated { id, byte_code, secrets });
 			Ok(Pays::No.into())
 		}
@@ -124,8 +194,104 @@ pub mod pallet {
 		) -> DispatchResultWithPostInfo {
 			let _ = T::TEECallOrigin::ensure_origin(origin)?;
 			Assertions::<T>::remove(id);
+			let _ = AssertionVersions::<T>::clear_prefix(id, u32::MAX, None);
+			AssertionVersionHistory::<T>::remove(id);
+			ActiveAssertionVersion::<T>::remove(id);
+			AssertionPolicies::<T>::remove(id);
 			Self::deposit_event(Event::AssertionVoided { id });
 			Ok(Pays::No.into())
 		}
+
+		/// Store a new version of an existing assertion and make it the active one.
+		/// The oldest version is dropped once `MaxAssertionVersions` is reached.
+		#[pallet::call_index(3)]
+		#[pallet::weight((T::DbWeight::get().reads_writes(3, 5), DispatchClass::Normal, Pays::No))]
+		pub fn update_assertion(
+			origin: OriginFor<T>,
+			id: T::AssertionId,
+			byte_code: Vec<u8>,
+			secrets: Vec<Vec<u8>>,
+		) -> DispatchResultWithPostInfo {
+			let _ = T::ContractDevOrigin::ensure_origin(origin)?;
+			ensure!(Assertions::<T>::contains_key(id), Error::<T>::AssertionNotExists);
+
+			let mut history = AssertionVersionHistory::<T>::get(id);
+			let version = history
+				.last()
+				.copied()
+				.unwrap_or_default()
+				.checked_add(1)
+				.ok_or(Error::<T>::AssertionVersionOverflow)?;
+			if history.is_full() {
+				let oldest = history.remove(0);
+				AssertionVersions::<T>::remove(id, oldest);
+			}
+			let _ = history.try_push(version);
+
+			let assertion = Assertion { byte_code: byte_code.clone(), secrets: secrets.clone() };
+			AssertionVersions::<T>::insert(id, version, assertion.clone());
+			AssertionVersionHistory::<T>::insert(id, history);
+			Assertions::<T>::insert(id, assertion);
+			ActiveAssertionVersion::<T>::insert(id, version);
+			Self::deposit_event(Event::AssertionUpdated { id, version, byte_code, secrets });
+			Ok(Pays::No.into())
+		}
+
+		/// Switch the active version of an assertion to one of the retained versions, e.g. to
+		/// roll back a faulty update
+		#[pallet::call_index(4)]
+		#[pallet::weight((T::DbWeight::get().reads_writes(1, 2), DispatchClass::Normal, Pays::No))]
+		pub fn set_active_assertion_version(
+			origin: OriginFor<T>,
+			id: T::AssertionId,
+			version: AssertionVersion,
+		) -> DispatchResultWithPostInfo {
+			let _ = T::ContractDevOrigin::ensure_origin(origin)?;
+			let assertion = AssertionVersions::<T>::get(id, version)
+				.ok_or(Error::<T>::AssertionVersionNotExists)?;
+			Assertions::<T>::insert(id, assertion);
+			ActiveAssertionVersion::<T>::insert(id, version);
+			Self::deposit_event(Event::AssertionVersionActivated { id, version });
+			Ok(Pays::No.into())
+		}
+
+		/// Same as `create_assertion`, the TEE-Workers additionally enforce the given resource
+		/// limits and outbound domain allowlist for every version of the assertion
+		#[pallet::call_index(5)]
+		#[pallet::weight((T::DbWeight::get().writes(5), DispatchClass::Normal, Pays::No))]
+		pub fn create_assertion_with_policy(
+			origin: OriginFor<T>,
+			id: T::AssertionId,
+			byte_code: Vec<u8>,
+			secrets: Vec<Vec<u8>>,
+			policy: AssertionPolicy,
+		) -> DispatchResultWithPostInfo {
+			let _ = T::ContractDevOrigin::ensure_origin(origin)?;
+			Self::do_create_assertion(id, byte_code.clone(), secrets.clone())?;
+			AssertionPolicies::<T>::insert(id, policy.clone());
+			Self::deposit_event(Event::AssertionCreatedWithPolicy {
+				id,
+				byte_code,
+				secrets,
+				policy,
+			});
+			Ok(Pays::No.into())
+		}
+	}
+
+	impl<T: Config> Pallet<T> {
+		fn do_create_assertion(
+			id: T::AssertionId,
+			byte_code: Vec<u8>,
+			secrets: Vec<Vec<u8>>,
+		) -> DispatchResult {
+			ensure!(!Assertions::<T>::contains_key(id), Error::<T>::AssertionExists);
+			let assertion = Assertion { byte_code, secrets };
+			Assertions::<T>::insert(id, assertion.clone());
+			AssertionVersions::<T>::insert(id, 0, assertion);
+			AssertionVersionHistory::<T>::insert(id, BoundedVec::truncate_from(sp_std::vec![0]));
+			ActiveAssertionVersion::<T>::insert(id, 0);
+			Ok(())
+		}
 	}
 }
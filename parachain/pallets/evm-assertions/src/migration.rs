@@ -0,0 +1,47 @@
+// Copyright 2020-2024 Trust Computing GmbH.
+// This file is part of Litentry.
+//
+// Litentry is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// Litentry is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with Litentry.  If not, see <https://www.gnu.org/licenses/>.
+
+use crate::{
+	ActiveAssertionVersion, Assertion, AssertionVersionHistory, AssertionVersions, Assertions,
+	Config, Pallet,
+};
+use frame_support::{
+	pallet_prelude::*,
+	traits::{GetStorageVersion, OnRuntimeUpgrade, StorageVersion},
+};
+
+/// Makes every assertion stored before versioning was introduced its own version 0, so that
+/// `update_assertion` and `set_active_assertion_version` find a history to work with.
+pub struct MigrateToV1<T>(PhantomData<T>);
+
+impl<T: Config> OnRuntimeUpgrade for MigrateToV1<T> {
+	fn on_runtime_upgrade() -> Weight {
+		if Pallet::<T>::on_chain_storage_version() >= 1 {
+			return T::DbWeight::get().reads(1);
+		}
+
+		let mut migrated = 0u64;
+		Assertions::<T>::iter().for_each(|(id, assertion): (T::AssertionId, Assertion)| {
+			migrated += 1;
+			AssertionVersions::<T>::insert(id, 0, assertion);
+			AssertionVersionHistory::<T>::insert(id, BoundedVec::truncate_from(sp_std::vec![0]));
+			ActiveAssertionVersion::<T>::insert(id, 0);
+		});
+		StorageVersion::new(1).put::<Pallet<T>>();
+
+		T::DbWeight::get().reads_writes(migrated + 1, migrated * 3 + 1)
+	}
+}
@@ -75,6 +75,7 @@ impl pallet_evm_assertions::Config for Test {
 	type AssertionId = H160;
 	type ContractDevOrigin = frame_system::EnsureRoot<Self::AccountId>;
 	type TEECallOrigin = frame_system::EnsureRoot<Self::AccountId>;
+	type MaxAssertionVersions = frame_support::traits::ConstU32<3>;
 }
 
 parameter_types! {
@@ -13,8 +13,13 @@
 //
 // You should have received a copy of the GNU General Public License
 // along with Litentry.  If not, see <https://www.gnu.org/licenses/>.
-use crate::{mock::*, Assertion, Error};
-use frame_support::{assert_noop, assert_ok};
+use crate::{migration::MigrateToV1, mock::*, Assertion, AssertionPolicy, Assertions, Error};
+use core_primitives::assertion::dynamic::AssertionLimits;
+use frame_support::{
+	assert_noop, assert_ok,
+	traits::{OnRuntimeUpgrade, StorageVersion},
+	BoundedVec,
+};
 use sp_core::H160;
 
 #[test]
@@ -30,6 +35,7 @@ fn should_create_new_assertion() {
 			byte_code.clone(),
 			secrets.clone()
 		));
+		assert_eq!(EvmAssertions::assertion_policies(assertion_id), None);
 		System::assert_last_event(RuntimeEvent::EvmAssertions(crate::Event::AssertionCreated {
 			id: assertion_id,
 			byte_code,
@@ -38,6 +44,38 @@ fn should_create_new_assertion() {
 	});
 }
 
+#[test]
+fn should_create_new_assertion_with_policy() {
+	new_test_ext().execute_with(|| {
+		let assertion_id: H160 = H160::from_slice(&[1u8; 20]);
+		let byte_code = [0u8; 256].to_vec();
+		let secrets = vec![[2u8; 13].to_vec(), [3u8; 32].to_vec()];
+		let policy = AssertionPolicy {
+			limits: AssertionLimits { max_http_calls: 2, ..Default::default() },
+			allowed_domains: BoundedVec::truncate_from(vec![BoundedVec::truncate_from(
+				b"litentry.com".to_vec(),
+			)]),
+		};
+
+		assert_ok!(EvmAssertions::create_assertion_with_policy(
+			RuntimeOrigin::root(),
+			assertion_id,
+			byte_code.clone(),
+			secrets.clone(),
+			policy.clone()
+		));
+		assert_eq!(EvmAssertions::assertion_policies(assertion_id), Some(policy.clone()));
+		System::assert_last_event(RuntimeEvent::EvmAssertions(
+			crate::Event::AssertionCreatedWithPolicy {
+				id: assertion_id,
+				byte_code,
+				secrets,
+				policy,
+			},
+		));
+	});
+}
+
 #[test]
 fn should_not_create_new_assertion_if_exists() {
 	new_test_ext().execute_with(|| {
@@ -83,5 +121,152 @@ fn should_remove_assertion_if_failed_to_store() {
 		assert_ok!(EvmAssertions::void_assertion(RuntimeOrigin::root(), assertion_id,));
 
 		assert_eq!(EvmAssertions::assertions(assertion_id), None);
+		assert_eq!(EvmAssertions::assertion_policies(assertion_id), None);
+	});
+}
+
+#[test]
+fn should_update_assertion_and_activate_new_version() {
+	new_test_ext().execute_with(|| {
+		let assertion_id: H160 = H160::from_slice(&[1u8; 20]);
+		let byte_code = [0u8; 256].to_vec();
+		let secrets = vec![[2u8; 13].to_vec(), [3u8; 32].to_vec()];
+		let new_byte_code = [1u8; 256].to_vec();
+
+		assert_ok!(EvmAssertions::create_assertion(
+			RuntimeOrigin::root(),
+			assertion_id,
+			byte_code.clone(),
+			secrets.clone()
+		));
+		assert_ok!(EvmAssertions::update_assertion(
+			RuntimeOrigin::root(),
+			assertion_id,
+			new_byte_code.clone(),
+			secrets.clone()
+		));
+		System::assert_last_event(RuntimeEvent::EvmAssertions(crate::Event::AssertionUpdated {
+			id: assertion_id,
+			version: 1,
+			byte_code: new_byte_code.clone(),
+			secrets: secrets.clone(),
+		}));
+
+		assert_eq!(EvmAssertions::active_assertion_version(assertion_id), 1);
+		assert_eq!(EvmAssertions::assertion_version_history(assertion_id).to_vec(), vec![0, 1]);
+		assert_eq!(
+			EvmAssertions::assertions(assertion_id),
+			Some(Assertion { byte_code: new_byte_code, secrets: secrets.clone() })
+		);
+		assert_eq!(
+			EvmAssertions::assertion_versions(assertion_id, 0),
+			Some(Assertion { byte_code, secrets })
+		);
+	});
+}
+
+#[test]
+fn should_not_update_assertion_if_not_exists() {
+	new_test_ext().execute_with(|| {
+		let assertion_id: H160 = H160::from_slice(&[1u8; 20]);
+
+		assert_noop!(
+			EvmAssertions::update_assertion(
+				RuntimeOrigin::root(),
+				assertion_id,
+				[0u8; 256].to_vec(),
+				vec![]
+			),
+			Error::<Test>::AssertionNotExists
+		);
+	});
+}
+
+#[test]
+fn should_drop_oldest_version_when_history_is_full() {
+	new_test_ext().execute_with(|| {
+		let assertion_id: H160 = H160::from_slice(&[1u8; 20]);
+
+		assert_ok!(EvmAssertions::create_assertion(
+			RuntimeOrigin::root(),
+			assertion_id,
+			[0u8; 256].to_vec(),
+			vec![]
+		));
+		for i in 1..=3u8 {
+			assert_ok!(EvmAssertions::update_assertion(
+				RuntimeOrigin::root(),
+				assertion_id,
+				[i; 256].to_vec(),
+				vec![]
+			));
+		}
+
+		assert_eq!(EvmAssertions::assertion_version_history(assertion_id).to_vec(), vec![1, 2, 3]);
+		assert_eq!(EvmAssertions::assertion_versions(assertion_id, 0), None);
+		assert_eq!(EvmAssertions::active_assertion_version(assertion_id), 3);
+	});
+}
+
+#[test]
+fn should_set_active_assertion_version() {
+	new_test_ext().execute_with(|| {
+		let assertion_id: H160 = H160::from_slice(&[1u8; 20]);
+		let byte_code = [0u8; 256].to_vec();
+
+		assert_ok!(EvmAssertions::create_assertion(
+			RuntimeOrigin::root(),
+			assertion_id,
+			byte_code.clone(),
+			vec![]
+		));
+		assert_ok!(EvmAssertions::update_assertion(
+			RuntimeOrigin::root(),
+			assertion_id,
+			[1u8; 256].to_vec(),
+			vec![]
+		));
+
+		assert_ok!(EvmAssertions::set_active_assertion_version(
+			RuntimeOrigin::root(),
+			assertion_id,
+			0
+		));
+		System::assert_last_event(RuntimeEvent::EvmAssertions(
+			crate::Event::AssertionVersionActivated { id: assertion_id, version: 0 },
+		));
+		assert_eq!(EvmAssertions::active_assertion_version(assertion_id), 0);
+		assert_eq!(
+			EvmAssertions::assertions(assertion_id),
+			Some(Assertion { byte_code, secrets: vec![] })
+		);
+
+		assert_noop!(
+			EvmAssertions::set_active_assertion_version(RuntimeOrigin::root(), assertion_id, 5),
+			Error::<Test>::AssertionVersionNotExists
+		);
+	});
+}
+
+#[test]
+fn should_migrate_unversioned_assertions_to_version_zero() {
+	new_test_ext().execute_with(|| {
+		let assertion_id: H160 = H160::from_slice(&[1u8; 20]);
+		let assertion = Assertion { byte_code: [0u8; 256].to_vec(), secrets: vec![] };
+		StorageVersion::new(0).put::<EvmAssertions>();
+		Assertions::<Test>::insert(assertion_id, assertion.clone());
+
+		MigrateToV1::<Test>::on_runtime_upgrade();
+
+		assert_eq!(EvmAssertions::assertion_versions(assertion_id, 0), Some(assertion));
+		assert_eq!(EvmAssertions::assertion_version_history(assertion_id).to_vec(), vec![0]);
+		assert_eq!(EvmAssertions::active_assertion_version(assertion_id), 0);
+		assert_ok!(EvmAssertions::update_assertion(
+			RuntimeOrigin::root(),
+			assertion_id,
+			[1u8; 256].to_vec(),
+			vec![]
+		));
+		assert_eq!(EvmAssertions::assertion_version_history(assertion_id).to_vec(), vec![0, 1]);
 	});
 }
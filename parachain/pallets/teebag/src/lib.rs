@@ -108,6 +108,14 @@ pub mod pallet {
 			who: T::AccountId,
 			sidechain_block_number: SidechainBlockNumber,
 		},
+		ShieldingKeyRotated {
+			who: T::AccountId,
+			shielding_pubkey: Vec<u8>,
+		},
+		ShieldingKeyRotationRequested {
+			worker_type: WorkerType,
+			grace_period_secs: u64,
+		},
 	}
 
 	#[pallet::error]
@@ -558,6 +566,56 @@ pub mod pallet {
 			Ok(Pays::No.into())
 		}
 
+		/// Ask all running enclaves of `worker_type` to rotate their shielding key
+		///
+		/// The enclaves keep accepting requests encrypted with the old key for `grace_period_secs`,
+		/// clients are expected to pick up the new key from the registry.
+		///
+		/// Weights should be 1 DB write for the event
+		#[pallet::call_index(13)]
+		#[pallet::weight((T::DbWeight::get().write, DispatchClass::Normal))]
+		pub fn request_shielding_key_rotation(
+			origin: OriginFor<T>,
+			worker_type: WorkerType,
+			grace_period_secs: u64,
+		) -> DispatchResultWithPostInfo {
+			Self::ensure_admin_or_root(origin)?;
+			Self::deposit_event(Event::ShieldingKeyRotationRequested {
+				worker_type,
+				grace_period_secs,
+			});
+			Ok(().into())
+		}
+
+		/// Publish the rotated shielding key of the calling enclave
+		///
+		/// The vc pubkey is only updated if `Some`, it's derived from the shielding key by the
+		/// identity worker.
+		///
+		/// Weights should be 1 DB read and 2 DB writes: 1 for the enclave and 1 for event
+		#[pallet::call_index(12)]
+		#[pallet::weight((
+			T::DbWeight::get().reads_writes(1, 2),
+			DispatchClass::Normal
+		))]
+		pub fn rotate_shielding_key(
+			origin: OriginFor<T>,
+			shielding_pubkey: Vec<u8>,
+			vc_pubkey: Option<Ed25519Public>,
+		) -> DispatchResultWithPostInfo {
+			let sender = ensure_signed(origin)?;
+			EnclaveRegistry::<T>::try_mutate(&sender, |maybe_enclave| {
+				let enclave = maybe_enclave.as_mut().ok_or(Error::<T>::EnclaveNotExist)?;
+				enclave.shielding_pubkey = Some(shielding_pubkey.clone());
+				if let Some(vc_pubkey) = vc_pubkey {
+					enclave.vc_pubkey = Some(vc_pubkey);
+				}
+				Ok::<(), Error<T>>(())
+			})?;
+			Self::deposit_event(Event::ShieldingKeyRotated { who: sender, shielding_pubkey });
+			Ok(Pays::No.into())
+		}
+
 		// ===============================================================================
 		// Following extrinsics are for runtime communication between parachain and worker
 		// ===============================================================================
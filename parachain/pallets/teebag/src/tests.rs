@@ -137,6 +137,104 @@ fn register_enclave_dev_works_with_sgx_build_mode_debug() {
 	})
 }
 
+#[test]
+fn rotate_shielding_key_works() {
+	new_test_ext(true).execute_with(|| {
+		assert_ok!(Teebag::register_enclave(
+			RuntimeOrigin::signed(alice()),
+			Default::default(),
+			Default::default(),
+			TEST4_MRENCLAVE.to_vec(),
+			URL.to_vec(),
+			Some(vec![1u8; 16]),
+			None,
+			AttestationType::Ignore,
+		));
+
+		assert_ok!(Teebag::rotate_shielding_key(
+			RuntimeOrigin::signed(alice()),
+			vec![2u8; 16],
+			Some([3u8; 32].into()),
+		));
+
+		let enclave = EnclaveRegistry::<Test>::get(alice()).unwrap();
+		assert_eq!(enclave.shielding_pubkey, Some(vec![2u8; 16]));
+		assert_eq!(enclave.vc_pubkey, Some([3u8; 32].into()));
+		let expected_event = RuntimeEvent::Teebag(TeebagEvent::ShieldingKeyRotated {
+			who: alice(),
+			shielding_pubkey: vec![2u8; 16],
+		});
+		assert!(System::events().iter().any(|a| a.event == expected_event));
+	})
+}
+
+#[test]
+fn rotate_shielding_key_fails_for_unregistered_enclave() {
+	new_test_ext(true).execute_with(|| {
+		assert_noop!(
+			Teebag::rotate_shielding_key(RuntimeOrigin::signed(alice()), vec![2u8; 16], None),
+			Error::<Test>::EnclaveNotExist
+		);
+	})
+}
+
+#[test]
+fn rotate_shielding_key_keeps_vc_pubkey_if_none() {
+	new_test_ext(true).execute_with(|| {
+		assert_ok!(Teebag::register_enclave(
+			RuntimeOrigin::signed(alice()),
+			Default::default(),
+			Default::default(),
+			TEST4_MRENCLAVE.to_vec(),
+			URL.to_vec(),
+			Some(vec![1u8; 16]),
+			Some([3u8; 32].into()),
+			AttestationType::Ignore,
+		));
+
+		assert_ok!(Teebag::rotate_shielding_key(
+			RuntimeOrigin::signed(alice()),
+			vec![2u8; 16],
+			None
+		));
+
+		let enclave = EnclaveRegistry::<Test>::get(alice()).unwrap();
+		assert_eq!(enclave.shielding_pubkey, Some(vec![2u8; 16]));
+		assert_eq!(enclave.vc_pubkey, Some([3u8; 32].into()));
+	})
+}
+
+#[test]
+fn request_shielding_key_rotation_works() {
+	new_test_ext(true).execute_with(|| {
+		assert_ok!(Teebag::request_shielding_key_rotation(
+			RuntimeOrigin::signed(alice()),
+			WorkerType::Identity,
+			600,
+		));
+
+		let expected_event = RuntimeEvent::Teebag(TeebagEvent::ShieldingKeyRotationRequested {
+			worker_type: WorkerType::Identity,
+			grace_period_secs: 600,
+		});
+		assert!(System::events().iter().any(|a| a.event == expected_event));
+	})
+}
+
+#[test]
+fn request_shielding_key_rotation_fails_for_non_admin() {
+	new_test_ext(true).execute_with(|| {
+		assert_noop!(
+			Teebag::request_shielding_key_rotation(
+				RuntimeOrigin::signed(AccountKeyring::Bob.to_account_id()),
+				WorkerType::Identity,
+				600,
+			),
+			Error::<Test>::RequireAdminOrRoot
+		);
+	})
+}
+
 #[test]
 fn parentchain_block_processed_works() {
 	new_test_ext(true).execute_with(|| {
@@ -151,6 +151,11 @@ pub type Executive = frame_executive::Executive<
 	// it was reverse order before.
 	// See the comment before collation related pallets too.
 	AllPalletsWithSystem,
+	(
+		pallet_bitacross::migration::MigrateToV1<Runtime>,
+		pallet_bitacross::migration::MigrateToV2<Runtime>,
+		pallet_evm_assertions::migration::MigrateToV1<Runtime>,
+	),
 >;
 
 impl fp_self_contained::SelfContainedCall for RuntimeCall {
@@ -1015,6 +1020,7 @@ impl pallet_evm_assertions::Config for Runtime {
 	type AssertionId = H160;
 	type ContractDevOrigin = pallet_collective::EnsureMember<AccountId, DeveloperCommitteeInstance>;
 	type TEECallOrigin = EnsureEnclaveSigner<Runtime>;
+	type MaxAssertionVersions = ConstU32<10>;
 }
 
 impl pallet_group::Config<IMPExtrinsicWhitelistInstance> for Runtime {
@@ -20,6 +20,7 @@ pub use ita_sgx_runtime::{Balance, Index};
 use bc_enclave_registry::{EnclaveRegistry, EnclaveRegistryUpdater};
 use bc_relayer_registry::{RelayerRegistry, RelayerRegistryUpdater};
 use bc_signer_registry::{SignerRegistry, SignerRegistryUpdater};
+use bc_task_sender::{BitAcrossRequest, BitAcrossRequestSender};
 use codec::Encode;
 use core::str::from_utf8;
 use ita_stf::TrustedCallSigned;
@@ -29,7 +30,9 @@ use itp_types::{
 	parentchain::{FilterEvents, HandleParentchainEvents, ParentchainEventProcessingError},
 	WorkerType,
 };
-use litentry_primitives::{Address32, Identity};
+use litentry_primitives::{
+	Address32, BtcSigningPolicy, EthSigningAllowlist, Identity, RelayerPermissions,
+};
 use log::*;
 use sp_core::{blake2_256, H256};
 use sp_std::vec::Vec;
@@ -58,6 +61,20 @@ impl ParentchainEventHandler {
 		Ok(())
 	}
 
+	fn set_relayer_permissions(
+		relayer_registry: &RelayerRegistry,
+		account: Identity,
+		permissions: RelayerPermissions,
+	) -> Result<(), Error> {
+		info!("Setting permissions of Relayer {:?}: {:?}", account, permissions);
+		relayer_registry.set_permissions(account, permissions).map_err(|e| {
+			error!("Error setting relayer permissions: {:?}", e);
+			Error::Other("Error setting relayer permissions".into())
+		})?;
+
+		Ok(())
+	}
+
 	fn add_enclave(
 		enclave_registry: &EnclaveRegistry,
 		account_id: Address32,
@@ -107,6 +124,38 @@ impl ParentchainEventHandler {
 
 		Ok(())
 	}
+
+	fn set_btc_signing_policy(policy: BtcSigningPolicy) -> Result<(), Error> {
+		info!("Setting BTC signing policy: {:?}", policy);
+		BitAcrossRequestSender::new()
+			.send(BitAcrossRequest::SetBtcSigningPolicy(policy))
+			.map_err(|e| {
+				error!("Error setting BTC signing policy: {:?}", e);
+				Error::Other("Error setting BTC signing policy".into())
+			})
+	}
+
+	fn set_eth_signing_allowlist(
+		relayer: Identity,
+		allowlist: Option<EthSigningAllowlist>,
+	) -> Result<(), Error> {
+		info!("Setting Ethereum signing allowlist of {:?}: {:?}", relayer, allowlist);
+		BitAcrossRequestSender::new()
+			.send(BitAcrossRequest::SetEthSigningAllowlist(relayer, allowlist))
+			.map_err(|e| {
+				error!("Error setting Ethereum signing allowlist: {:?}", e);
+				Error::Other("Error setting Ethereum signing allowlist".into())
+			})
+	}
+
+	// the threshold key has to follow the set of enclaves, otherwise the vault address would
+	// change, see `KeyGeneration` for how the key is handed over
+	fn reshare_threshold_key() {
+		info!("Set of enclaves changed, resharing threshold key");
+		if let Err(e) = BitAcrossRequestSender::new().send(BitAcrossRequest::ReshareThresholdKey) {
+			error!("Could not request resharing of threshold key: {:?}", e);
+		}
+	}
 }
 
 impl<Executor>
@@ -135,6 +184,7 @@ where
 		events: impl FilterEvents,
 	) -> Result<Vec<H256>, Error> {
 		let mut handled_events: Vec<H256> = Vec::new();
+		let mut enclaves_changed = false;
 
 		if let Ok(events) = events.get_relayer_added_events() {
 			debug!("Handling RelayerAdded events");
@@ -166,6 +216,25 @@ where
 				.map_err(|_| ParentchainEventProcessingError::RelayerRemoveFailure)?;
 		}
 
+		if let Ok(events) = events.get_relayer_permissions_set_events() {
+			debug!("Handling RelayerPermissionsSet events");
+			let relayer_registry = executor.get_relayer_registry_updater();
+			events
+				.iter()
+				.try_for_each(|event| {
+					debug!("found RelayerPermissionsSet event: {:?}", event);
+					let result = Self::set_relayer_permissions(
+						relayer_registry,
+						event.who.clone(),
+						event.permissions.clone(),
+					);
+					handled_events.push(hash_of(&event));
+
+					result
+				})
+				.map_err(|_| ParentchainEventProcessingError::RelayerPermissionsSetFailure)?;
+		}
+
 		if let Ok(events) = events.get_enclave_added_events() {
 			debug!("Handling EnclaveAdded events");
 			let enclave_registry = executor.get_enclave_registry_updater();
@@ -173,6 +242,7 @@ where
 				.iter()
 				.try_for_each(|event| {
 					debug!("found EnclaveAdded event: {:?}", event);
+					enclaves_changed |= event.worker_type == WorkerType::BitAcross;
 					let result = Self::add_enclave(
 						enclave_registry,
 						event.who,
@@ -193,6 +263,7 @@ where
 				.iter()
 				.try_for_each(|event| {
 					debug!("found EnclaveRemoved event: {:?}", event);
+					enclaves_changed = true;
 					let result = Self::remove_enclave(enclave_registry, event.who);
 					handled_events.push(hash_of(&event));
 
@@ -208,6 +279,8 @@ where
 				.iter()
 				.try_for_each(|event| {
 					debug!("found BtcWalletGenerated event: {:?}", event);
+					// signer keys of new enclaves are only known from here on
+					enclaves_changed = true;
 					let result = Self::save_signer(
 						signer_registry,
 						event.account_id.clone().into(),
@@ -220,6 +293,41 @@ where
 				.map_err(|_| ParentchainEventProcessingError::BtcWalletGeneratedFailure)?;
 		}
 
+		if let Ok(events) = events.get_btc_signing_policy_set_events() {
+			debug!("Handling BtcSigningPolicySet events");
+			events
+				.iter()
+				.try_for_each(|event| {
+					debug!("found BtcSigningPolicySet event: {:?}", event);
+					let result = Self::set_btc_signing_policy(event.policy.clone());
+					handled_events.push(hash_of(&event));
+
+					result
+				})
+				.map_err(|_| ParentchainEventProcessingError::BtcSigningPolicySetFailure)?;
+		}
+
+		if let Ok(events) = events.get_eth_signing_allowlist_set_events() {
+			debug!("Handling EthSigningAllowlistSet events");
+			events
+				.iter()
+				.try_for_each(|event| {
+					debug!("found EthSigningAllowlistSet event: {:?}", event);
+					let result = Self::set_eth_signing_allowlist(
+						event.relayer.clone(),
+						event.allowlist.clone(),
+					);
+					handled_events.push(hash_of(&event));
+
+					result
+				})
+				.map_err(|_| ParentchainEventProcessingError::EthSigningAllowlistSetFailure)?;
+		}
+
+		if enclaves_changed {
+			Self::reshare_threshold_key();
+		}
+
 		Ok(handled_events)
 	}
 }
@@ -0,0 +1,316 @@
+// Copyright 2020-2024 Trust Computing GmbH.
+// This file is part of Litentry.
+//
+// Litentry is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// Litentry is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with Litentry.  If not, see <https://www.gnu.org/licenses/>.
+
+//! In-process simulation of bitacross signers running ceremonies with each other, without SGX.
+//!
+//! Every simulated signer has its own key repository and ceremony registry and handles
+//! `CeremonyRoundCall`s through the `lc-direct-call` handlers and the ceremonies of
+//! `bc-musig2-ceremony`. It does not run `bc-task-processor`: the dispatch of commands and events
+//! in between is a copy of `handle_ceremony_command` and `process_command` of the task processor
+//! and has to be kept in sync with them. The calls are routed over channels by a simulated network
+//! which can lose, delay and tamper with them. Time is counted in ticks and routing is seeded, so
+//! a simulation goes the same way each time it runs.
+
+pub mod network;
+pub mod signer;
+
+pub use network::{
+	Envelope, Fault, MessageFilter, MessageKind, Network, Tick, FORGED_PARTIAL_SIGNATURE,
+};
+pub use signer::{CeremonyOutcome, SimulatedKeyRepository, SimulatedSigner};
+
+use bc_enclave_registry::{EnclaveRegistry, EnclaveRegistryUpdater};
+use bc_musig2_ceremony::{SignBitcoinPayload, SignerId, SignersWithKeys};
+use bc_signer_registry::{SignerRegistry, SignerRegistryUpdater};
+use codec::Encode;
+use itp_sgx_crypto::schnorr::Pair as SchnorrPair;
+use litentry_primitives::Address32;
+use sp_core::hashing::blake2_256;
+use std::sync::Arc;
+
+/// Ticks a ceremony is kept for before it times out, unless set otherwise.
+pub const DEFAULT_CEREMONY_TTL: Tick = 30;
+
+pub struct Simulation {
+	network: Network,
+	signers: Vec<SimulatedSigner>,
+	ceremony_ttl: Tick,
+}
+
+impl Simulation {
+	/// Creates `signers_count` signers registered as enclaves and signers with each other. Their
+	/// keys and the randomness of the network are derived from `seed`.
+	pub fn new(signers_count: usize, seed: u64) -> Self {
+		let mut network = Network::new(seed);
+		let enclave_registry = Arc::new(EnclaveRegistry::default());
+		let signer_registry = Arc::new(SignerRegistry::default());
+		let signers = (0..signers_count as u64)
+			.map(|index| {
+				let key = SchnorrPair::from_bytes(&blake2_256(
+					&(b"BitAcross/simulator", seed, index).encode(),
+				))
+				.expect("hash is a valid secret key");
+				let id: SignerId = blake2_256(&key.public_bytes());
+				enclave_registry
+					.update(Address32::from(id), format!("simulated-signer-{}", index))
+					.expect("registry isn't sealed with std");
+				signer_registry
+					.update(Address32::from(id), key.public_bytes())
+					.expect("registry isn't sealed with std");
+				let (outbox, inbox) = network.connect(id);
+				SimulatedSigner::new(
+					id,
+					key,
+					enclave_registry.clone(),
+					signer_registry.clone(),
+					outbox,
+					inbox,
+				)
+			})
+			.collect();
+		Self { network, signers, ceremony_ttl: DEFAULT_CEREMONY_TTL }
+	}
+
+	pub fn with_ceremony_ttl(mut self, ceremony_ttl: Tick) -> Self {
+		self.ceremony_ttl = ceremony_ttl;
+		self
+	}
+
+	pub fn add_fault(&mut self, fault: Fault) {
+		self.network.add_fault(fault);
+	}
+
+	pub fn network(&self) -> &Network {
+		&self.network
+	}
+
+	pub fn signer(&self, index: usize) -> &SimulatedSigner {
+		&self.signers[index]
+	}
+
+	pub fn signer_id(&self, index: usize) -> SignerId {
+		self.signers[index].id()
+	}
+
+	pub fn signers_with_keys(&self) -> SignersWithKeys {
+		self.signers.iter().map(|s| (s.id(), s.public_key())).collect()
+	}
+
+	/// Starts a ceremony with all signers, as if each of them received the signing request.
+	pub fn start_ceremony(&mut self, payload: SignBitcoinPayload) {
+		let signers: Vec<usize> = (0..self.signers.len()).collect();
+		self.start_ceremony_on(&signers, payload);
+	}
+
+	/// Starts a ceremony of all signers on the given ones only.
+	pub fn start_ceremony_on(&mut self, indexes: &[usize], payload: SignBitcoinPayload) {
+		let signers_with_keys = self.signers_with_keys();
+		let now = self.network.now();
+		for index in indexes {
+			self.signers[*index].start_ceremony(signers_with_keys.clone(), payload.clone(), now);
+		}
+	}
+
+	/// Lets every signer share its nonce pool, a batch signed with pre-shared nonces can be
+	/// started once the network delivered them.
+	pub fn share_nonce_pools(&mut self) {
+		self.signers.iter_mut().for_each(|signer| signer.share_nonce_pool());
+	}
+
+	/// Advances the simulation by one tick. Messages due are delivered and handled by the signers
+	/// in their order, ceremonies past their time to live are expired.
+	pub fn tick(&mut self) {
+		self.network.tick();
+		let now = self.network.now();
+		for signer in self.signers.iter_mut() {
+			signer.receive(now);
+		}
+		for signer in self.signers.iter_mut() {
+			signer.expire_ceremonies(now, self.ceremony_ttl);
+		}
+	}
+
+	/// Ticks until no message is in flight and no signer runs a ceremony, or `max_ticks` passed.
+	/// Returns the number of ticks run.
+	pub fn run(&mut self, max_ticks: Tick) -> Tick {
+		let start = self.network.now();
+		while self.network.now() - start < max_ticks {
+			self.tick();
+			if self.network.is_idle() && self.signers.iter().all(|s| s.is_idle()) {
+				break
+			}
+		}
+		self.network.now() - start
+	}
+}
+
+#[cfg(test)]
+pub mod test {
+	use crate::{CeremonyOutcome, Fault, MessageFilter, MessageKind, Simulation, Tick};
+	use bc_musig2_ceremony::{
+		CeremonyError, CeremonyErrorReason, CeremonyFailureReason, CeremonyRound,
+		SignBitcoinPayload,
+	};
+
+	const MAX_TICKS: Tick = 100;
+
+	fn payload() -> SignBitcoinPayload {
+		SignBitcoinPayload::Derived([1u8; 32].to_vec())
+	}
+
+	fn outcome(simulation: &Simulation, index: usize) -> Option<CeremonyOutcome> {
+		simulation.signer(index).outcome(&payload()).cloned()
+	}
+
+	#[test]
+	fn it_should_sign_with_all_signers() {
+		// given
+		let mut simulation = Simulation::new(3, 1);
+
+		// when
+		simulation.start_ceremony(payload());
+		let ticks = simulation.run(MAX_TICKS);
+
+		// then
+		assert_eq!(ticks, 2);
+		let signed = outcome(&simulation, 0);
+		assert!(matches!(signed, Some(CeremonyOutcome::Signed(_))));
+		assert_eq!(outcome(&simulation, 1), signed);
+		assert_eq!(outcome(&simulation, 2), signed);
+	}
+
+	#[test]
+	fn it_should_sign_batch_with_pre_shared_nonces() {
+		// given
+		let mut simulation = Simulation::new(3, 2);
+		simulation.share_nonce_pools();
+		simulation.run(MAX_TICKS);
+		let payload = SignBitcoinPayload::Batch(
+			vec![
+				SignBitcoinPayload::Derived([1u8; 32].to_vec()),
+				SignBitcoinPayload::Derived([2u8; 32].to_vec()),
+			],
+			vec![0, 1],
+		);
+
+		// when
+		simulation.start_ceremony(payload.clone());
+		let ticks = simulation.run(MAX_TICKS);
+
+		// then the first round is skipped
+		assert_eq!(ticks, 1);
+		assert!(matches!(
+			simulation.signer(0).outcome(&payload),
+			Some(CeremonyOutcome::Signed(signatures)) if signatures.len() == 2
+		));
+	}
+
+	#[test]
+	fn it_should_complete_with_delayed_messages() {
+		// given
+		let mut simulation = Simulation::new(3, 3);
+		simulation.add_fault(Fault::Delay(MessageFilter::any().from(simulation.signer_id(1)), 5));
+
+		// when
+		simulation.start_ceremony(payload());
+		let ticks = simulation.run(MAX_TICKS);
+
+		// then
+		assert_eq!(ticks, 7);
+		(0..3).for_each(|index| {
+			assert!(matches!(outcome(&simulation, index), Some(CeremonyOutcome::Signed(_))))
+		});
+	}
+
+	#[test]
+	fn it_should_blame_signer_whose_nonce_was_lost() {
+		// given
+		let mut simulation = Simulation::new(3, 4).with_ceremony_ttl(10);
+		simulation.add_fault(Fault::Drop(
+			MessageFilter::any()
+				.from(simulation.signer_id(2))
+				.to(simulation.signer_id(0))
+				.kind(MessageKind::Nonce),
+		));
+
+		// when
+		simulation.start_ceremony(payload());
+		simulation.run(MAX_TICKS);
+
+		// then
+		assert_eq!(simulation.network().dropped().len(), 1);
+		assert_eq!(outcome(&simulation, 0), Some(CeremonyOutcome::TimedOut));
+		let failures = simulation.signer(0).failures();
+		assert_eq!(failures.len(), 1);
+		assert_eq!(failures[0].reason, CeremonyFailureReason::TimedOut(CeremonyRound::Nonces));
+		assert_eq!(failures[0].offenders, vec![simulation.signer_id(2)]);
+
+		// the others wait for the partial signature of the signer which is stuck
+		let failures = simulation.signer(1).failures();
+		assert_eq!(
+			failures[0].reason,
+			CeremonyFailureReason::TimedOut(CeremonyRound::PartialSignatures)
+		);
+		assert_eq!(failures[0].offenders, vec![simulation.signer_id(0)]);
+	}
+
+	#[test]
+	fn it_should_blame_signer_forging_partial_signature() {
+		// given
+		let mut simulation = Simulation::new(3, 5);
+		let forger = simulation.signer_id(2);
+		simulation.add_fault(Fault::ForgePartialSignature(MessageFilter::any().from(forger)));
+
+		// when
+		simulation.start_ceremony(payload());
+		simulation.run(MAX_TICKS);
+
+		// then
+		(0..2).for_each(|index| {
+			assert_eq!(
+				outcome(&simulation, index),
+				Some(CeremonyOutcome::Failed(CeremonyError::PartialSignatureReceivingError(
+					CeremonyErrorReason::ContributionError
+				)))
+			);
+			assert_eq!(simulation.signer(index).failures()[0].offenders, vec![forger]);
+		});
+	}
+
+	#[test]
+	fn it_should_lose_same_messages_for_same_seed() {
+		// given
+		let lost_messages = |seed: u64| {
+			let mut simulation = Simulation::new(4, seed);
+			simulation.add_fault(Fault::DropRandomly(MessageFilter::any(), 50));
+			simulation.start_ceremony(payload());
+			simulation.run(MAX_TICKS);
+			simulation
+				.network()
+				.dropped()
+				.iter()
+				.map(|e| (e.from, e.to, MessageKind::of(&e.call)))
+				.collect::<Vec<_>>()
+		};
+
+		// when
+		let lost = lost_messages(6);
+
+		// then
+		assert!(!lost.is_empty());
+		assert_eq!(lost_messages(6), lost);
+	}
+}
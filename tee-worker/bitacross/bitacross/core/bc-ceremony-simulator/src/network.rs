@@ -0,0 +1,243 @@
+// Copyright 2020-2024 Trust Computing GmbH.
+// This file is part of Litentry.
+//
+// Litentry is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// Litentry is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with Litentry.  If not, see <https://www.gnu.org/licenses/>.
+
+use bc_musig2_ceremony::SignerId;
+use lc_direct_call::CeremonyRoundCall;
+use log::*;
+use rand::{rngs::StdRng, Rng, SeedableRng};
+use std::{
+	collections::BTreeMap,
+	sync::mpsc::{channel, Receiver, Sender},
+};
+
+/// Simulated time, the network delivers messages once per tick.
+pub type Tick = u64;
+
+/// Partial signature a forged message carries instead of the real one. It's a valid scalar, so
+/// the message is only rejected once the partial signature is verified.
+pub const FORGED_PARTIAL_SIGNATURE: [u8; 32] = [1u8; 32];
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum MessageKind {
+	Nonce,
+	PartialSignature,
+	KillCeremony,
+	NoncePool,
+	Other,
+}
+
+impl MessageKind {
+	pub fn of(call: &CeremonyRoundCall) -> Self {
+		match call {
+			CeremonyRoundCall::NonceShare(..) | CeremonyRoundCall::BatchNonceShare(..) =>
+				MessageKind::Nonce,
+			CeremonyRoundCall::PartialSignatureShare(..)
+			| CeremonyRoundCall::ThresholdPartialSignatureShare(..)
+			| CeremonyRoundCall::BatchPartialSignatureShare(..)
+			| CeremonyRoundCall::ThresholdBatchPartialSignatureShare(..) => MessageKind::PartialSignature,
+			CeremonyRoundCall::KillCeremony(..) => MessageKind::KillCeremony,
+			CeremonyRoundCall::NoncePoolShare(..) => MessageKind::NoncePool,
+			_ => MessageKind::Other,
+		}
+	}
+}
+
+/// Message sent from one simulated signer to another.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Envelope {
+	pub from: SignerId,
+	pub to: SignerId,
+	pub call: CeremonyRoundCall,
+}
+
+/// Messages a fault applies to, a criterion which isn't set matches any message.
+#[derive(Clone, Debug, Default)]
+pub struct MessageFilter {
+	pub from: Option<SignerId>,
+	pub to: Option<SignerId>,
+	pub kind: Option<MessageKind>,
+}
+
+impl MessageFilter {
+	pub fn any() -> Self {
+		Default::default()
+	}
+
+	pub fn from(mut self, signer: SignerId) -> Self {
+		self.from = Some(signer);
+		self
+	}
+
+	pub fn to(mut self, signer: SignerId) -> Self {
+		self.to = Some(signer);
+		self
+	}
+
+	pub fn kind(mut self, kind: MessageKind) -> Self {
+		self.kind = Some(kind);
+		self
+	}
+
+	pub fn matches(&self, envelope: &Envelope) -> bool {
+		self.from.map_or(true, |from| from == envelope.from)
+			&& self.to.map_or(true, |to| to == envelope.to)
+			&& self.kind.map_or(true, |kind| kind == MessageKind::of(&envelope.call))
+	}
+}
+
+#[derive(Clone, Debug)]
+pub enum Fault {
+	// matching messages are lost
+	Drop(MessageFilter),
+	// matching messages are lost with the given probability in percent, drawn from the seeded
+	// random number generator of the network
+	DropRandomly(MessageFilter, u8),
+	// matching messages are delivered the given number of ticks later
+	Delay(MessageFilter, Tick),
+	// partial signatures of matching messages are replaced by `FORGED_PARTIAL_SIGNATURE`
+	ForgePartialSignature(MessageFilter),
+}
+
+/// In-process network between simulated signers.
+///
+/// Signers send messages over a channel shared by all of them and receive over a channel of their
+/// own. Messages sent during a tick are routed on the next one, in the order they were sent, and
+/// every fault is applied to them on the way. The network is deterministic for a given seed.
+pub struct Network {
+	now: Tick,
+	faults: Vec<Fault>,
+	rng: StdRng,
+	outbox_sender: Sender<Envelope>,
+	outbox: Receiver<Envelope>,
+	// messages taken from the outbox which weren't routed yet
+	sent: Vec<Envelope>,
+	inboxes: BTreeMap<SignerId, Sender<Envelope>>,
+	// messages in flight by the tick they are delivered at
+	in_flight: BTreeMap<Tick, Vec<Envelope>>,
+	dropped: Vec<Envelope>,
+}
+
+impl Network {
+	pub fn new(seed: u64) -> Self {
+		let (outbox_sender, outbox) = channel();
+		Self {
+			now: 0,
+			faults: Vec::new(),
+			rng: StdRng::seed_from_u64(seed),
+			outbox_sender,
+			outbox,
+			sent: Vec::new(),
+			inboxes: BTreeMap::new(),
+			in_flight: BTreeMap::new(),
+			dropped: Vec::new(),
+		}
+	}
+
+	/// Connects a signer, returns the channel it sends messages to and the one it receives them
+	/// from.
+	pub fn connect(&mut self, signer: SignerId) -> (Sender<Envelope>, Receiver<Envelope>) {
+		let (inbox_sender, inbox) = channel();
+		self.inboxes.insert(signer, inbox_sender);
+		(self.outbox_sender.clone(), inbox)
+	}
+
+	pub fn add_fault(&mut self, fault: Fault) {
+		self.faults.push(fault);
+	}
+
+	pub fn now(&self) -> Tick {
+		self.now
+	}
+
+	/// Messages which were lost so far.
+	pub fn dropped(&self) -> &[Envelope] {
+		&self.dropped
+	}
+
+	/// Whether there's no message the network still has to deliver.
+	pub fn is_idle(&mut self) -> bool {
+		self.collect_sent();
+		self.sent.is_empty() && self.in_flight.is_empty()
+	}
+
+	/// Advances to the next tick, routes the messages sent so far and delivers the ones due,
+	/// returns the number of messages delivered.
+	pub fn tick(&mut self) -> usize {
+		self.now += 1;
+		self.collect_sent();
+		for envelope in core::mem::take(&mut self.sent) {
+			self.route(envelope);
+		}
+
+		let due: Vec<Tick> = self.in_flight.range(..=self.now).map(|(tick, _)| *tick).collect();
+		let mut delivered = 0;
+		for tick in due {
+			for envelope in self.in_flight.remove(&tick).unwrap_or_default() {
+				let sent = self
+					.inboxes
+					.get(&envelope.to)
+					.map_or(false, |inbox| inbox.send(envelope.clone()).is_ok());
+				if sent {
+					delivered += 1;
+				} else {
+					warn!("Signer {:?} is not connected, message lost", envelope.to);
+					self.dropped.push(envelope);
+				}
+			}
+		}
+		delivered
+	}
+
+	fn collect_sent(&mut self) {
+		self.sent.extend(self.outbox.try_iter());
+	}
+
+	fn route(&mut self, mut envelope: Envelope) {
+		let mut delay: Tick = 0;
+		for fault in self.faults.iter() {
+			match fault {
+				Fault::Drop(filter) if filter.matches(&envelope) => {
+					debug!("Dropping message from {:?} to {:?}", envelope.from, envelope.to);
+					self.dropped.push(envelope);
+					return
+				},
+				Fault::DropRandomly(filter, percent) if filter.matches(&envelope) =>
+					if self.rng.gen_range(0, 100u8) < *percent {
+						debug!("Losing message from {:?} to {:?}", envelope.from, envelope.to);
+						self.dropped.push(envelope);
+						return
+					},
+				Fault::Delay(filter, ticks) if filter.matches(&envelope) => delay += ticks,
+				Fault::ForgePartialSignature(filter) if filter.matches(&envelope) =>
+					forge_partial_signature(&mut envelope.call),
+				_ => {},
+			}
+		}
+		self.in_flight.entry(self.now + delay).or_default().push(envelope);
+	}
+}
+
+fn forge_partial_signature(call: &mut CeremonyRoundCall) {
+	match call {
+		CeremonyRoundCall::PartialSignatureShare(_, _, signature)
+		| CeremonyRoundCall::ThresholdPartialSignatureShare(_, _, _, signature) =>
+			*signature = FORGED_PARTIAL_SIGNATURE,
+		CeremonyRoundCall::BatchPartialSignatureShare(_, _, signatures)
+		| CeremonyRoundCall::ThresholdBatchPartialSignatureShare(_, _, _, signatures) =>
+			signatures.iter_mut().for_each(|s| *s = FORGED_PARTIAL_SIGNATURE),
+		_ => {},
+	}
+}
@@ -0,0 +1,479 @@
+// Copyright 2020-2024 Trust Computing GmbH.
+// This file is part of Litentry.
+//
+// Litentry is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// Litentry is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with Litentry.  If not, see <https://www.gnu.org/licenses/>.
+
+use crate::network::{Envelope, Tick};
+use bc_enclave_registry::{EnclaveRegistry, EnclaveRegistryLookup};
+use bc_musig2_ceremony::{
+	ceremony_statuses, Ceremony, CeremonyCommand, CeremonyError, CeremonyErrorReason,
+	CeremonyEvent, CeremonyFailure, CeremonyFailureHistory, CeremonyFailureReason, CeremonyId,
+	CeremonyRegistry, CeremonyStatus, CommandDisposition, MuSig2BatchCeremony, MuSig2Ceremony,
+	NoncePool, PublicKey, SignBitcoinPayload, SignerId, SignersWithKeys,
+};
+use bc_signer_registry::SignerRegistry;
+use itp_sgx_crypto::{
+	key_repository::AccessKey, mocks::KeyRepositoryMock, schnorr::Pair as SchnorrPair,
+};
+use lc_direct_call::{
+	handler::{
+		batch_nonce_share, batch_partial_signature_share, kill_ceremony, nonce_pool_share,
+		nonce_share, partial_signature_share,
+	},
+	CeremonyRoundCall,
+};
+use litentry_primitives::{Address32, Identity};
+use log::*;
+use std::{
+	collections::HashMap,
+	path::PathBuf,
+	sync::{
+		mpsc::{Receiver, Sender},
+		Arc, RwLock,
+	},
+};
+
+pub type SimulatedKeyRepository = KeyRepositoryMock<SchnorrPair>;
+
+/// How a ceremony ended for a signer.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum CeremonyOutcome {
+	// one signature per payload
+	Signed(Vec<[u8; 64]>),
+	Failed(CeremonyError),
+	TimedOut,
+	// killed on request of another signer
+	Killed,
+}
+
+/// Signer running ceremonies with the direct call handlers an enclave uses. Commands and events are
+/// dispatched like `bc-task-processor` does, without calling into it.
+pub struct SimulatedSigner {
+	id: SignerId,
+	key_repository: Arc<SimulatedKeyRepository>,
+	enclave_registry: Arc<EnclaveRegistry>,
+	signer_registry: Arc<SignerRegistry>,
+	ceremony_registry: CeremonyRegistry<SimulatedKeyRepository>,
+	// commands received before the ceremony got to their round
+	postponed: HashMap<CeremonyId, (Vec<CeremonyCommand>, Tick)>,
+	nonce_pool: NoncePool,
+	failures: CeremonyFailureHistory,
+	outcomes: HashMap<CeremonyId, CeremonyOutcome>,
+	outbox: Sender<Envelope>,
+	inbox: Receiver<Envelope>,
+}
+
+impl SimulatedSigner {
+	pub fn new(
+		id: SignerId,
+		key: SchnorrPair,
+		enclave_registry: Arc<EnclaveRegistry>,
+		signer_registry: Arc<SignerRegistry>,
+		outbox: Sender<Envelope>,
+		inbox: Receiver<Envelope>,
+	) -> Self {
+		Self {
+			id,
+			key_repository: Arc::new(KeyRepositoryMock::new(key)),
+			enclave_registry,
+			signer_registry,
+			ceremony_registry: HashMap::new(),
+			postponed: HashMap::new(),
+			nonce_pool: NoncePool::new(PathBuf::new()),
+			failures: CeremonyFailureHistory::default(),
+			outcomes: HashMap::new(),
+			outbox,
+			inbox,
+		}
+	}
+
+	pub fn id(&self) -> SignerId {
+		self.id
+	}
+
+	pub fn public_key(&self) -> PublicKey {
+		self.key_repository
+			.retrieve_key()
+			.expect("mocked key is always available")
+			.public
+	}
+
+	pub fn outcome(&self, ceremony_id: &CeremonyId) -> Option<&CeremonyOutcome> {
+		self.outcomes.get(ceremony_id)
+	}
+
+	/// Failures recorded by this signer, the most recent first.
+	pub fn failures(&self) -> Vec<CeremonyFailure> {
+		self.failures.recent()
+	}
+
+	pub fn ceremonies(&self, now: Tick) -> Vec<CeremonyStatus> {
+		ceremony_statuses(&self.ceremony_registry, &self.failures, now)
+	}
+
+	pub fn is_idle(&self) -> bool {
+		self.ceremony_registry.is_empty() && self.postponed.is_empty()
+	}
+
+	/// Starts a ceremony as if this signer received the signing request.
+	pub fn start_ceremony(
+		&mut self,
+		signers: SignersWithKeys,
+		payload: SignBitcoinPayload,
+		now: Tick,
+	) {
+		let command = CeremonyCommand::InitCeremony(signers, payload.clone(), false);
+		self.handle_command(payload, command, now);
+	}
+
+	/// Refills the nonce pool and shares its nonces with all other signers.
+	pub fn share_nonce_pool(&mut self) {
+		if let Err(e) = self.nonce_pool.refill() {
+			error!("Could not refill nonce pool, error: {:?}", e);
+			return
+		}
+		let call =
+			CeremonyRoundCall::NoncePoolShare(self.identity(), self.nonce_pool.public_nonces());
+		let peers: Vec<SignerId> = self
+			.enclave_registry
+			.get_all()
+			.iter()
+			.map(|(signer, _)| *signer.as_ref())
+			.filter(|signer| *signer != self.id)
+			.collect();
+		self.send(peers, call);
+	}
+
+	/// Handles all messages received so far.
+	pub fn receive(&mut self, now: Tick) {
+		let envelopes: Vec<Envelope> = self.inbox.try_iter().collect();
+		for envelope in envelopes {
+			self.handle_call(envelope.call, now);
+		}
+	}
+
+	/// Removes ceremonies which weren't completed within `time_to_live` ticks, the signers they
+	/// were waiting for are held responsible.
+	pub fn expire_ceremonies(&mut self, now: Tick, time_to_live: Tick) {
+		let expired: Vec<CeremonyId> = self
+			.ceremony_registry
+			.iter()
+			.filter(|(_, (_, create_time))| now - create_time >= time_to_live)
+			.map(|(ceremony_id, _)| ceremony_id.clone())
+			.collect();
+		for ceremony_id in expired {
+			if let Some((ceremony, _)) = self.ceremony_registry.remove(&ceremony_id) {
+				let contributions = ceremony.read().unwrap().contributions();
+				self.failures.record(
+					&ceremony_id,
+					now,
+					CeremonyFailureReason::TimedOut(contributions.round),
+					contributions.missing(),
+				);
+				self.outcomes.insert(ceremony_id.clone(), CeremonyOutcome::TimedOut);
+			}
+			self.postponed.remove(&ceremony_id);
+		}
+		self.postponed.retain(|_, (_, create_time)| now - *create_time < time_to_live);
+	}
+
+	fn identity(&self) -> Identity {
+		Identity::Substrate(Address32::from(self.id))
+	}
+
+	fn handle_call(&mut self, call: CeremonyRoundCall, now: Tick) {
+		let registry = self.enclave_registry.clone();
+		let command = match call {
+			CeremonyRoundCall::NonceShare(signer, message, nonce) =>
+				nonce_share::handle(signer, &message, nonce, registry)
+					.map(|command| (message, command))
+					.map_err(|e| format!("{:?}", e)),
+			CeremonyRoundCall::PartialSignatureShare(signer, message, signature) =>
+				partial_signature_share::handle(signer, &message, signature, registry)
+					.map(|command| (message, command))
+					.map_err(|e| format!("{:?}", e)),
+			CeremonyRoundCall::BatchNonceShare(signer, message, nonces) =>
+				batch_nonce_share::handle(signer, &message, nonces, registry)
+					.map(|command| (message, command))
+					.map_err(|e| format!("{:?}", e)),
+			CeremonyRoundCall::BatchPartialSignatureShare(signer, message, signatures) =>
+				batch_partial_signature_share::handle(signer, &message, signatures, registry)
+					.map(|command| (message, command))
+					.map_err(|e| format!("{:?}", e)),
+			CeremonyRoundCall::KillCeremony(signer, message) =>
+				kill_ceremony::handle(signer, registry.as_ref())
+					.map(|command| (message, command))
+					.map_err(|e| format!("{:?}", e)),
+			CeremonyRoundCall::NoncePoolShare(signer, nonces) => {
+				let result = nonce_pool_share::handle(
+					signer,
+					nonces,
+					registry.as_ref(),
+					self.signer_registry.as_ref(),
+				)
+				.map_err(|e| format!("{:?}", e))
+				.and_then(|(signer, nonces)| {
+					self.nonce_pool
+						.receive_peer_nonces(signer, nonces)
+						.map_err(|e| format!("{:?}", e))
+				});
+				if let Err(e) = result {
+					error!("Could not save pre-shared nonces, error: {}", e);
+				}
+				return
+			},
+			call => Err(format!("Call is not supported by simulated signers: {:?}", call)),
+		};
+		match command {
+			Ok((ceremony_id, command)) => self.handle_command(ceremony_id, command, now),
+			Err(e) => error!("Could not handle ceremony round call, error: {}", e),
+		}
+	}
+
+	fn handle_command(&mut self, ceremony_id: CeremonyId, command: CeremonyCommand, now: Tick) {
+		let is_first_round = self
+			.ceremony_registry
+			.get(&ceremony_id)
+			.map(|(ceremony, _)| ceremony.read().unwrap().is_first_round());
+		match command.disposition(is_first_round) {
+			CommandDisposition::Process => {},
+			CommandDisposition::Postpone => {
+				self.postponed.entry(ceremony_id).or_insert((vec![], now)).0.push(command);
+				return
+			},
+			CommandDisposition::Drop => {
+				debug!("Dropping command {:?}, is_first_round: {:?}", command, is_first_round);
+				return
+			},
+		}
+
+		let mut commands_to_process = vec![command];
+		while let Some(command) = commands_to_process.pop() {
+			let signer = command.signer();
+			for event in self.process_command(&ceremony_id, command, now) {
+				let outcome = match event {
+					CeremonyEvent::FirstRoundStarted(_, _, _)
+					| CeremonyEvent::SecondRoundStarted(_, _, _)
+					| CeremonyEvent::ThresholdSecondRoundStarted(_, _, _, _)
+					| CeremonyEvent::BatchFirstRoundStarted(_, _, _)
+					| CeremonyEvent::BatchSecondRoundStarted(_, _, _)
+					| CeremonyEvent::ThresholdBatchSecondRoundStarted(_, _, _, _) => {
+						if let Some((postponed, _)) = self.postponed.remove(&ceremony_id) {
+							commands_to_process.extend(postponed);
+						}
+						None
+					},
+					CeremonyEvent::CeremonyEnded(signature, _, _) =>
+						Some(CeremonyOutcome::Signed(vec![signature])),
+					CeremonyEvent::BatchCeremonyEnded(ref signatures) =>
+						Some(CeremonyOutcome::Signed(signatures.clone())),
+					CeremonyEvent::CeremonyError(_, ref error) => {
+						// the failure is attributed to the signer whose contribution caused it
+						self.failures.record(
+							&ceremony_id,
+							now,
+							CeremonyFailureReason::Error(error.clone()),
+							signer.into_iter().collect(),
+						);
+						Some(CeremonyOutcome::Failed(error.clone()))
+					},
+				};
+				if let Some(outcome) = outcome {
+					self.end_ceremony(&ceremony_id, outcome);
+				}
+				self.send_event(&ceremony_id, event);
+			}
+		}
+	}
+
+	// mirrors `process_command` of `bc-task-processor`, changes there need to be made here too
+	fn process_command(
+		&mut self,
+		ceremony_id: &CeremonyId,
+		command: CeremonyCommand,
+		now: Tick,
+	) -> Vec<CeremonyEvent> {
+		match command {
+			CeremonyCommand::InitCeremony(signers, payload, check_run) => {
+				if self.ceremony_registry.contains_key(ceremony_id) {
+					let error = CeremonyError::CeremonyInitError(CeremonyErrorReason::AlreadyExist);
+					return vec![CeremonyEvent::CeremonyError(vec![], error)]
+				}
+				let result = match payload {
+					SignBitcoinPayload::Batch(_, _) => MuSig2BatchCeremony::new(
+						self.id,
+						signers,
+						payload,
+						self.key_repository.clone(),
+						&mut self.nonce_pool,
+					)
+					.map(|(ceremony, event)| (Ceremony::Batch(ceremony), event)),
+					_ => MuSig2Ceremony::new(
+						self.id,
+						signers,
+						payload,
+						self.key_repository.clone(),
+						check_run,
+					)
+					.map(|(ceremony, event)| (Ceremony::MuSig2(ceremony), event)),
+				};
+				match result {
+					Ok((ceremony, event)) => {
+						self.ceremony_registry
+							.insert(ceremony_id.clone(), (Arc::new(RwLock::new(ceremony)), now));
+						vec![event]
+					},
+					Err(e) => {
+						error!("Could not start ceremony, error: {:?}", e);
+						let error = CeremonyError::CeremonyInitError(
+							CeremonyErrorReason::CreateCeremonyError,
+						);
+						vec![CeremonyEvent::CeremonyError(vec![], error)]
+					},
+				}
+			},
+			CeremonyCommand::SaveNonce(signer, nonce) =>
+				self.contribute(ceremony_id, |ceremony| ceremony.receive_nonce(signer, nonce)),
+			CeremonyCommand::SavePartialSignature(signer, partial_signature) => self
+				.contribute(ceremony_id, |ceremony| {
+					ceremony.receive_partial_sign(signer, partial_signature)
+				}),
+			CeremonyCommand::SaveThresholdPartialSignature(
+				signer,
+				signing_set,
+				partial_signature,
+			) => self.contribute(ceremony_id, |ceremony| {
+				ceremony.receive_threshold_partial_sign(signer, signing_set, partial_signature)
+			}),
+			CeremonyCommand::SaveBatchNonces(signer, nonces) => self
+				.contribute(ceremony_id, |ceremony| ceremony.receive_batch_nonces(signer, nonces)),
+			CeremonyCommand::SaveBatchPartialSignatures(signer, partial_signatures) => self
+				.contribute(ceremony_id, |ceremony| {
+					ceremony.receive_batch_partial_signs(signer, partial_signatures)
+				}),
+			CeremonyCommand::SaveThresholdBatchPartialSignatures(
+				signer,
+				signing_set,
+				partial_signatures,
+			) => self.contribute(ceremony_id, |ceremony| {
+				ceremony.receive_threshold_batch_partial_signs(
+					signer,
+					signing_set,
+					partial_signatures,
+				)
+			}),
+			CeremonyCommand::KillCeremony => {
+				if self.ceremony_registry.remove(ceremony_id).is_some() {
+					self.outcomes.insert(ceremony_id.clone(), CeremonyOutcome::Killed);
+				}
+				self.postponed.remove(ceremony_id);
+				vec![]
+			},
+		}
+	}
+
+	fn contribute(
+		&self,
+		ceremony_id: &CeremonyId,
+		receive: impl FnOnce(
+			&mut Ceremony<SimulatedKeyRepository>,
+		) -> Result<Vec<CeremonyEvent>, CeremonyError>,
+	) -> Vec<CeremonyEvent> {
+		match self.ceremony_registry.get(ceremony_id) {
+			Some((ceremony, _)) => {
+				let mut ceremony = ceremony.write().unwrap();
+				receive(&mut *ceremony).unwrap_or_else(|e| {
+					vec![CeremonyEvent::CeremonyError(ceremony.get_signers_except_self(), e)]
+				})
+			},
+			None => vec![],
+		}
+	}
+
+	fn end_ceremony(&mut self, ceremony_id: &CeremonyId, outcome: CeremonyOutcome) {
+		self.ceremony_registry.remove(ceremony_id);
+		self.postponed.remove(ceremony_id);
+		self.outcomes.insert(ceremony_id.clone(), outcome);
+	}
+
+	// shares the contribution of this signer, the way `bc_musig2_event::process_event` does
+	fn send_event(&self, ceremony_id: &CeremonyId, event: CeremonyEvent) {
+		let identity = self.identity();
+		let (signers, call) = match event {
+			CeremonyEvent::FirstRoundStarted(signers, message, nonce) =>
+				(signers, CeremonyRoundCall::NonceShare(identity, message, nonce.serialize())),
+			CeremonyEvent::SecondRoundStarted(signers, message, signature) => (
+				signers,
+				CeremonyRoundCall::PartialSignatureShare(identity, message, signature.serialize()),
+			),
+			CeremonyEvent::ThresholdSecondRoundStarted(
+				signers,
+				message,
+				signing_set,
+				signature,
+			) => (
+				signers,
+				CeremonyRoundCall::ThresholdPartialSignatureShare(
+					identity,
+					message,
+					signing_set,
+					signature,
+				),
+			),
+			CeremonyEvent::BatchFirstRoundStarted(signers, message, nonces) => (
+				signers,
+				CeremonyRoundCall::BatchNonceShare(
+					identity,
+					message,
+					nonces.iter().map(|n| n.serialize()).collect(),
+				),
+			),
+			CeremonyEvent::BatchSecondRoundStarted(signers, message, signatures) => (
+				signers,
+				CeremonyRoundCall::BatchPartialSignatureShare(
+					identity,
+					message,
+					signatures.iter().map(|s| s.serialize()).collect(),
+				),
+			),
+			CeremonyEvent::ThresholdBatchSecondRoundStarted(
+				signers,
+				message,
+				signing_set,
+				signatures,
+			) => (
+				signers,
+				CeremonyRoundCall::ThresholdBatchPartialSignatureShare(
+					identity,
+					message,
+					signing_set,
+					signatures,
+				),
+			),
+			CeremonyEvent::CeremonyError(signers, _) =>
+				(signers, CeremonyRoundCall::KillCeremony(identity, ceremony_id.clone())),
+			CeremonyEvent::CeremonyEnded(_, _, _) | CeremonyEvent::BatchCeremonyEnded(_) => return,
+		};
+		self.send(signers, call);
+	}
+
+	fn send(&self, signers: Vec<SignerId>, call: CeremonyRoundCall) {
+		for signer in signers {
+			let envelope = Envelope { from: self.id, to: signer, call: call.clone() };
+			if self.outbox.send(envelope).is_err() {
+				warn!("Network is gone, message to {:?} lost", signer);
+			}
+		}
+	}
+}
@@ -0,0 +1,528 @@
+// Copyright 2020-2024 Trust Computing GmbH.
+// This file is part of Litentry.
+//
+// Litentry is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// Litentry is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with Litentry.  If not, see <https://www.gnu.org/licenses/>.
+
+#[cfg(all(not(feature = "std"), feature = "sgx"))]
+use musig2_sgx as musig2;
+
+use crate::{
+	key_agg_context, nonce_pool::NoncePool, random_seed, CeremonyContributions, CeremonyError,
+	CeremonyErrorReason, CeremonyEvent, CeremonyId, CeremonyRound, SignBitcoinPayload, SignerId,
+	Signers, SignersWithKeys,
+};
+use itp_sgx_crypto::{key_repository::AccessKey, schnorr::Pair as SchnorrPair};
+use k256::{PublicKey, SecretKey};
+use log::*;
+use musig2::{
+	aggregate_partial_signatures, sign_partial, verify_partial, verify_single, AggNonce,
+	BinaryEncoding, CompactSignature, KeyAggContext, LiftedSignature, PartialSignature, PubNonce,
+	SecNonce,
+};
+use std::{collections::BTreeMap, format, string::String, sync::Arc, vec::Vec};
+
+/// MuSig2 ceremony signing every payload of a `SignBitcoinPayload::Batch`, so a withdrawal
+/// spending many inputs takes a single ceremony instead of one per input.
+///
+/// If the batch names nonce slots, the nonces pre-shared for them are used and the ceremony
+/// starts with the second round. Either way a secret nonce is taken out of where it's kept before
+/// a partial signature is made with it, so it can't sign a second message.
+pub struct MuSig2BatchCeremony<AK: AccessKey<KeyType = SchnorrPair>> {
+	id: CeremonyId,
+	me: SignerId,
+	// sorted by public key, the position of a signer is its index in the key aggregation
+	signers: SignersWithKeys,
+	signing_key_access: Arc<AK>,
+	payloads: Vec<SignBitcoinPayload>,
+	key_contexts: Vec<KeyAggContext>,
+	// secret nonces of this enclave, one per payload, they are taken once signing
+	secret_nonces: Vec<SecNonce>,
+	// public nonces by signer index, one per payload
+	nonces: BTreeMap<usize, Vec<PubNonce>>,
+	// set once the second round started
+	aggregated_nonces: Option<Vec<AggNonce>>,
+	partial_signatures: BTreeMap<usize, Vec<PartialSignature>>,
+}
+
+impl<AK: AccessKey<KeyType = SchnorrPair>> MuSig2BatchCeremony<AK> {
+	/// Creates a new batch ceremony, it starts with the first round unless the batch names the
+	/// slots of pre-shared nonces, which are then taken from `nonce_pool`.
+	pub fn new(
+		me: SignerId,
+		mut signers: SignersWithKeys,
+		id: CeremonyId,
+		signing_key_access: Arc<AK>,
+		nonce_pool: &mut NoncePool,
+	) -> Result<(Self, CeremonyEvent), String> {
+		info!("Creating new batch ceremony {:?}", id);
+		let (payloads, slots) = match &id {
+			SignBitcoinPayload::Batch(payloads, slots) => (payloads.clone(), slots.clone()),
+			_ => return Err("Not a batch payload".into()),
+		};
+		if payloads.is_empty() {
+			return Err("Empty batch".into())
+		}
+		if !slots.is_empty() && slots.len() != payloads.len() {
+			return Err(format!(
+				"Nonce slots don't match payloads, expected: {:?}, actual {:?}",
+				payloads.len(),
+				slots.len()
+			))
+		}
+		if signers.len() < 3 {
+			return Err(format!("Not enough signers, minimum: {:?}, actual {:?}", 3, signers.len()))
+		}
+		signers.sort_by_key(|k| k.1);
+		let my_index = signers.iter().position(|r| r.0 == me).ok_or("Could not determine index")?;
+		let key_contexts = payloads
+			.iter()
+			.map(|payload| key_agg_context(&signers, payload))
+			.collect::<Result<Vec<_>, _>>()?;
+
+		let mut nonces = BTreeMap::new();
+		for (index, signer) in signers.iter().enumerate().filter(|(i, _)| *i != my_index) {
+			// every slot needs a pre-shared nonce of every other signer
+			let signer_nonces = slots
+				.iter()
+				.map(|slot| {
+					nonce_pool
+						.peer_nonce(&signer.0, *slot)
+						.ok_or(format!("No nonce of signer {:?} for slot {}", signer.0, slot))
+				})
+				.collect::<Result<Vec<_>, _>>()?;
+			if !signer_nonces.is_empty() {
+				nonces.insert(index, signer_nonces);
+			}
+		}
+		let secret_nonces = if slots.is_empty() {
+			payloads.iter().map(|_| SecNonce::build(random_seed()).build()).collect()
+		} else {
+			nonce_pool
+				.take_nonces(&slots)
+				.map_err(|e| format!("Nonce pool error: {:?}", e))?
+		};
+		nonces.insert(my_index, secret_nonces.iter().map(|n| n.public_nonce()).collect());
+
+		let mut ceremony = Self {
+			id,
+			me,
+			signers,
+			signing_key_access,
+			payloads,
+			key_contexts,
+			secret_nonces,
+			nonces,
+			aggregated_nonces: None,
+			partial_signatures: BTreeMap::new(),
+		};
+		let event = if slots.is_empty() {
+			CeremonyEvent::BatchFirstRoundStarted(
+				ceremony.get_signers_except_self(),
+				ceremony.id.clone(),
+				ceremony.nonces[&my_index].clone(),
+			)
+		} else {
+			ceremony.start_second_round().map_err(|e| format!("{:?}", e))?
+		};
+		Ok((ceremony, event))
+	}
+
+	fn signer_index(&self, signer: &SignerId) -> Option<usize> {
+		self.signers.iter().position(|p| p.0 == *signer)
+	}
+
+	fn my_index(&self) -> usize {
+		// the ceremony isn't created unless we are one of the signers
+		self.signer_index(&self.me).unwrap_or_default()
+	}
+
+	// Saves signer's nonces
+	pub fn receive_nonces(
+		&mut self,
+		signer: SignerId,
+		nonces: Vec<PubNonce>,
+	) -> Result<Option<CeremonyEvent>, CeremonyError> {
+		info!("Saving batch nonces from signer: {:?}", signer);
+		let peer_index = self
+			.signer_index(&signer)
+			.ok_or(CeremonyError::NonceReceivingError(CeremonyErrorReason::SignerNotFound))?;
+		if self.aggregated_nonces.is_some() {
+			return Err(CeremonyError::NonceReceivingError(CeremonyErrorReason::IncorrectRound))
+		}
+		if nonces.len() != self.payloads.len() {
+			return Err(CeremonyError::NonceReceivingError(CeremonyErrorReason::ContributionError))
+		}
+		match self.nonces.get(&peer_index) {
+			Some(saved) if *saved != nonces =>
+				return Err(CeremonyError::NonceReceivingError(
+					CeremonyErrorReason::ContributionError,
+				)),
+			Some(_) => return Ok(None),
+			None => {},
+		}
+		self.nonces.insert(peer_index, nonces);
+		if self.nonces.len() == self.signers.len() {
+			self.start_second_round().map(Some)
+		} else {
+			Ok(None)
+		}
+	}
+
+	// Starts the second round, the secret nonces are taken before signing with them
+	fn start_second_round(&mut self) -> Result<CeremonyEvent, CeremonyError> {
+		let secret_nonces = core::mem::take(&mut self.secret_nonces);
+		if secret_nonces.len() != self.payloads.len() {
+			return Err(CeremonyError::NonceReceivingError(CeremonyErrorReason::IncorrectRound))
+		}
+		let secret_key = self
+			.signing_key_access
+			.retrieve_key()
+			.map_err(|e| format!("{:?}", e))
+			.and_then(|key| {
+				SecretKey::from_slice(&key.private_bytes()).map_err(|e| format!("{:?}", e))
+			})
+			.map_err(|e| {
+				error!("Could not start second round: {:?}", e);
+				CeremonyError::NonceReceivingError(CeremonyErrorReason::RoundFinalizationError)
+			})?;
+
+		let aggregated_nonces: Vec<AggNonce> = (0..self.payloads.len())
+			.map(|i| AggNonce::sum(self.nonces.values().map(|nonces| nonces[i].clone())))
+			.collect();
+		let mut partial_signatures = Vec::with_capacity(self.payloads.len());
+		for (i, secret_nonce) in secret_nonces.into_iter().enumerate() {
+			let partial_signature: PartialSignature = sign_partial(
+				&self.key_contexts[i],
+				secret_key.clone(),
+				secret_nonce,
+				&aggregated_nonces[i],
+				self.payloads[i].message(),
+			)
+			.map_err(|e| {
+				error!("Could not sign payload {}: {:?}", i, e);
+				CeremonyError::NonceReceivingError(CeremonyErrorReason::RoundFinalizationError)
+			})?;
+			partial_signatures.push(partial_signature);
+		}
+
+		self.aggregated_nonces = Some(aggregated_nonces);
+		self.partial_signatures.insert(self.my_index(), partial_signatures.clone());
+
+		Ok(CeremonyEvent::BatchSecondRoundStarted(
+			self.get_signers_except_self(),
+			self.id.clone(),
+			partial_signatures,
+		))
+	}
+
+	// Saves signer's partial signatures, every one of them is verified
+	pub fn receive_partial_signatures(
+		&mut self,
+		signer: SignerId,
+		partial_signatures: Vec<PartialSignature>,
+	) -> Result<Option<CeremonyEvent>, CeremonyError> {
+		info!("Saving batch partial signatures from signer: {:?}", signer);
+		let peer_index = self.signer_index(&signer).ok_or(
+			CeremonyError::PartialSignatureReceivingError(CeremonyErrorReason::SignerNotFound),
+		)?;
+		let aggregated_nonces = self.aggregated_nonces.as_ref().ok_or(
+			CeremonyError::PartialSignatureReceivingError(CeremonyErrorReason::IncorrectRound),
+		)?;
+		let contribution_error =
+			CeremonyError::PartialSignatureReceivingError(CeremonyErrorReason::ContributionError);
+		if partial_signatures.len() != self.payloads.len()
+			|| self.partial_signatures.contains_key(&peer_index)
+		{
+			return Err(contribution_error)
+		}
+		let public_key = self.signers[peer_index].1;
+		let nonces = &self.nonces[&peer_index];
+		for (i, partial_signature) in partial_signatures.iter().enumerate() {
+			verify_partial(
+				&self.key_contexts[i],
+				*partial_signature,
+				&aggregated_nonces[i],
+				public_key,
+				&nonces[i],
+				self.payloads[i].message(),
+			)
+			.map_err(|e| {
+				error!("Invalid partial signature for payload {}: {:?}", i, e);
+				CeremonyError::PartialSignatureReceivingError(
+					CeremonyErrorReason::ContributionError,
+				)
+			})?;
+		}
+		self.partial_signatures.insert(peer_index, partial_signatures);
+		if self.partial_signatures.len() < self.signers.len() {
+			return Ok(None)
+		}
+
+		let mut signatures = Vec::with_capacity(self.payloads.len());
+		for (i, payload) in self.payloads.iter().enumerate() {
+			let signature: CompactSignature = aggregate_partial_signatures::<LiftedSignature>(
+				&self.key_contexts[i],
+				&aggregated_nonces[i],
+				self.partial_signatures.values().map(|s| s[i]),
+				payload.message(),
+			)
+			.map_err(|e| {
+				error!("Could not aggregate signature of payload {}: {:?}", i, e);
+				CeremonyError::PartialSignatureReceivingError(
+					CeremonyErrorReason::RoundFinalizationError,
+				)
+			})?
+			.compact();
+			let agg_key: PublicKey = self.key_contexts[i].aggregated_pubkey();
+			verify_single(agg_key, signature, payload.message()).map_err(|e| {
+				error!("Invalid signature of payload {}: {:?}", i, e);
+				CeremonyError::PartialSignatureReceivingError(
+					CeremonyErrorReason::RoundFinalizationError,
+				)
+			})?;
+			signatures.push(signature.to_bytes());
+		}
+		info!("Batch ceremony {:?} `has ended`", self.id);
+		Ok(Some(CeremonyEvent::BatchCeremonyEnded(signatures)))
+	}
+
+	pub fn get_signers_except_self(&self) -> Signers {
+		self.signers.iter().filter(|e| e.0 != self.me).map(|s| s.0).collect()
+	}
+
+	pub fn get_id_ref(&self) -> &CeremonyId {
+		&self.id
+	}
+
+	pub fn is_first_round(&self) -> bool {
+		self.aggregated_nonces.is_none()
+	}
+
+	pub fn contributions(&self) -> CeremonyContributions {
+		let signers: Signers = self.signers.iter().map(|s| s.0).collect();
+		let received = |indexes: Vec<&usize>| -> Signers {
+			indexes.into_iter().map(|i| signers[*i]).collect()
+		};
+		CeremonyContributions {
+			round: if self.is_first_round() {
+				CeremonyRound::Nonces
+			} else {
+				CeremonyRound::PartialSignatures
+			},
+			nonces: received(self.nonces.keys().collect()),
+			partial_signatures: received(self.partial_signatures.keys().collect()),
+			signers,
+		}
+	}
+}
+
+#[cfg(test)]
+pub mod test {
+	use crate::{
+		batch::MuSig2BatchCeremony,
+		generate_aggregated_public_key,
+		nonce_pool::{NoncePool, NONCE_POOL_SIZE},
+		test::{
+			my_priv_key, signer1_priv_key, signer2_priv_key, signers_with_keys,
+			MockedSigningKeyAccess, MY_SIGNER_ID, SIGNER_1_ID, SIGNER_2_ID,
+		},
+		CeremonyEvent, SignBitcoinPayload, SignerId,
+	};
+	use alloc::sync::Arc;
+	use k256::schnorr::SigningKey;
+	use musig2::{verify_single, BinaryEncoding, CompactSignature};
+	use std::path::PathBuf;
+
+	type Ceremony = MuSig2BatchCeremony<MockedSigningKeyAccess>;
+
+	fn signers() -> Vec<(SignerId, SigningKey)> {
+		vec![
+			(MY_SIGNER_ID, my_priv_key()),
+			(SIGNER_1_ID, signer1_priv_key()),
+			(SIGNER_2_ID, signer2_priv_key()),
+		]
+	}
+
+	fn payloads() -> Vec<SignBitcoinPayload> {
+		vec![
+			SignBitcoinPayload::Derived([1u8; 32].to_vec()),
+			SignBitcoinPayload::Derived([2u8; 32].to_vec()),
+		]
+	}
+
+	fn new_ceremony(
+		signer: &(SignerId, SigningKey),
+		id: &SignBitcoinPayload,
+		pool: &mut NoncePool,
+	) -> Result<(Ceremony, CeremonyEvent), String> {
+		let signing_key_access = MockedSigningKeyAccess { signing_key: signer.1.clone() };
+		Ceremony::new(signer.0, signers_with_keys(), id.clone(), Arc::new(signing_key_access), pool)
+	}
+
+	// pools of all signers, each with the nonces the others shared
+	fn nonce_pools() -> Vec<NoncePool> {
+		let mut pools: Vec<NoncePool> = signers()
+			.iter()
+			.map(|_| {
+				let mut pool = NoncePool::new(PathBuf::new());
+				pool.refill().unwrap();
+				pool
+			})
+			.collect();
+		for i in 0..pools.len() {
+			let nonces = pools[i].public_nonces();
+			for j in (0..pools.len()).filter(|j| *j != i) {
+				pools[j].receive_peer_nonces(signers()[i].0, nonces.clone()).unwrap();
+			}
+		}
+		pools
+	}
+
+	// exchanges the partial signatures between the ceremonies and returns their signatures
+	fn finish(ceremonies: &mut [Ceremony], events: Vec<CeremonyEvent>) -> Vec<Vec<[u8; 64]>> {
+		let partial_signatures: Vec<_> = events
+			.into_iter()
+			.map(|event| match event {
+				CeremonyEvent::BatchSecondRoundStarted(_, _, partial_signatures) =>
+					partial_signatures,
+				event => panic!("expected second round but got: {:?}", event),
+			})
+			.collect();
+		(0..ceremonies.len())
+			.map(|i| {
+				let mut last_event = None;
+				for (j, signer) in signers().iter().enumerate().filter(|(j, _)| *j != i) {
+					last_event = ceremonies[i]
+						.receive_partial_signatures(signer.0, partial_signatures[j].clone())
+						.unwrap();
+				}
+				match last_event {
+					Some(CeremonyEvent::BatchCeremonyEnded(signatures)) => signatures,
+					event => panic!("expected end of ceremony but got: {:?}", event),
+				}
+			})
+			.collect()
+	}
+
+	fn assert_signatures_valid(signatures: &[[u8; 64]]) {
+		let agg_key =
+			generate_aggregated_public_key(signers_with_keys().iter().map(|s| s.1).collect());
+		assert_eq!(signatures.len(), payloads().len());
+		for (signature, payload) in signatures.iter().zip(payloads()) {
+			let signature = CompactSignature::from_bytes(signature).unwrap();
+			assert!(verify_single(agg_key, signature, payload.message()).is_ok());
+		}
+	}
+
+	#[test]
+	fn it_should_sign_batch_in_two_rounds() {
+		// given
+		let id = SignBitcoinPayload::Batch(payloads(), vec![]);
+		let mut pools = nonce_pools();
+		let (mut ceremonies, nonces): (Vec<_>, Vec<_>) = signers()
+			.iter()
+			.zip(pools.iter_mut())
+			.map(|(signer, pool)| match new_ceremony(signer, &id, pool).unwrap() {
+				(ceremony, CeremonyEvent::BatchFirstRoundStarted(_, _, nonces)) =>
+					(ceremony, nonces),
+				(_, event) => panic!("expected first round but got: {:?}", event),
+			})
+			.unzip();
+
+		// when
+		let events: Vec<_> = (0..ceremonies.len())
+			.map(|i| {
+				let mut last_event = None;
+				for (j, signer) in signers().iter().enumerate().filter(|(j, _)| *j != i) {
+					last_event = ceremonies[i].receive_nonces(signer.0, nonces[j].clone()).unwrap();
+				}
+				last_event.unwrap()
+			})
+			.collect();
+		let signatures = finish(&mut ceremonies, events);
+
+		// then
+		assert_eq!(signatures[0], signatures[1]);
+		assert_eq!(signatures[0], signatures[2]);
+		assert_signatures_valid(&signatures[0]);
+		// pooled nonces are left alone
+		assert_eq!(pools[0].ready_slots(&[SIGNER_1_ID, SIGNER_2_ID]).len(), NONCE_POOL_SIZE);
+	}
+
+	#[test]
+	fn it_should_sign_batch_with_pre_shared_nonces_in_one_round() {
+		// given
+		let id = SignBitcoinPayload::Batch(payloads(), vec![3, 7]);
+		let mut pools = nonce_pools();
+
+		// when
+		let (mut ceremonies, events): (Vec<_>, Vec<_>) = signers()
+			.iter()
+			.zip(pools.iter_mut())
+			.map(|(signer, pool)| new_ceremony(signer, &id, pool).unwrap())
+			.unzip();
+		let signatures = finish(&mut ceremonies, events);
+
+		// then
+		assert!(!ceremonies[0].is_first_round());
+		assert_eq!(signatures[0], signatures[1]);
+		assert_eq!(signatures[0], signatures[2]);
+		assert_signatures_valid(&signatures[0]);
+	}
+
+	#[test]
+	fn it_should_not_sign_twice_with_pre_shared_nonce() {
+		// given
+		let mut pools = nonce_pools();
+		let id = SignBitcoinPayload::Batch(payloads(), vec![3, 7]);
+		assert!(new_ceremony(&signers()[0], &id, &mut pools[0]).is_ok());
+
+		// when
+		let other_payloads = vec![
+			SignBitcoinPayload::Derived([3u8; 32].to_vec()),
+			SignBitcoinPayload::Derived([4u8; 32].to_vec()),
+		];
+		let other_id = SignBitcoinPayload::Batch(other_payloads, vec![8, 7]);
+		let result = new_ceremony(&signers()[0], &other_id, &mut pools[0]);
+
+		// then
+		assert!(result.is_err());
+		// the nonce of the other slot wasn't used up
+		assert!(pools[0].ready_slots(&[SIGNER_1_ID, SIGNER_2_ID]).contains(&8));
+	}
+
+	#[test]
+	fn it_should_reject_invalid_partial_signatures() {
+		// given
+		let id = SignBitcoinPayload::Batch(payloads(), vec![3, 7]);
+		let mut pools = nonce_pools();
+		let (mut ceremonies, events): (Vec<_>, Vec<_>) = signers()
+			.iter()
+			.zip(pools.iter_mut())
+			.map(|(signer, pool)| new_ceremony(signer, &id, pool).unwrap())
+			.unzip();
+		let mut partial_signatures = match &events[1] {
+			CeremonyEvent::BatchSecondRoundStarted(_, _, partial_signatures) =>
+				partial_signatures.clone(),
+			event => panic!("expected second round but got: {:?}", event),
+		};
+
+		// when
+		partial_signatures.swap(0, 1);
+		let result = ceremonies[0].receive_partial_signatures(SIGNER_1_ID, partial_signatures);
+
+		// then
+		assert!(result.is_err());
+	}
+}
@@ -0,0 +1,252 @@
+// Copyright 2020-2024 Trust Computing GmbH.
+// This file is part of Litentry.
+//
+// Litentry is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// Litentry is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with Litentry.  If not, see <https://www.gnu.org/licenses/>.
+
+use crate::{
+	random_seed, Ceremony, CeremonyError, CeremonyId, CeremonyRegistry, SignerId, Signers,
+};
+use codec::{Decode, Encode};
+use itp_sgx_crypto::{key_repository::AccessKey, schnorr::Pair as SchnorrPair};
+use sp_core::hashing::blake2_256;
+use std::{collections::VecDeque, vec::Vec};
+
+/// Number of failed ceremonies kept in the failure history.
+pub const CEREMONY_FAILURE_HISTORY_SIZE: usize = 64;
+
+#[derive(Encode, Decode, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum CeremonyKind {
+	MuSig2,
+	Threshold,
+	Batch,
+	ThresholdBatch,
+}
+
+/// Round a ceremony is in, i.e. the contributions it waits for.
+#[derive(Encode, Decode, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum CeremonyRound {
+	Nonces,
+	PartialSignatures,
+	Ended,
+}
+
+/// Contributions a ceremony received so far, this enclave's own ones included.
+#[derive(Encode, Decode, Clone, Debug, PartialEq, Eq)]
+pub struct CeremonyContributions {
+	pub round: CeremonyRound,
+	pub signers: Signers,
+	// signers whose nonces were received
+	pub nonces: Signers,
+	// signers whose partial signatures were received
+	pub partial_signatures: Signers,
+}
+
+impl CeremonyContributions {
+	/// Signers the ceremony still waits for in its current round. A threshold ceremony only
+	/// needs the signers of its signing set, so not all of them necessarily hold it up.
+	pub fn missing(&self) -> Signers {
+		let received = match self.round {
+			CeremonyRound::Nonces => &self.nonces,
+			CeremonyRound::PartialSignatures => &self.partial_signatures,
+			CeremonyRound::Ended => return Vec::new(),
+		};
+		self.signers.iter().filter(|s| !received.contains(s)).cloned().collect()
+	}
+}
+
+/// Status of an active ceremony, as returned by the diagnostics RPC.
+#[derive(Encode, Decode, Clone, Debug, PartialEq, Eq)]
+pub struct CeremonyStatus {
+	// see `CeremonyFailureHistory::ceremony_hash`
+	pub ceremony: [u8; 32],
+	pub kind: CeremonyKind,
+	// seconds since the ceremony was created
+	pub age: u64,
+	pub contributions: CeremonyContributions,
+}
+
+#[derive(Encode, Decode, Clone, Debug, PartialEq, Eq)]
+pub enum CeremonyFailureReason {
+	Error(CeremonyError),
+	// the ceremony didn't complete in time, it was waiting for the given round
+	TimedOut(CeremonyRound),
+}
+
+#[derive(Encode, Decode, Clone, Debug, PartialEq, Eq)]
+pub struct CeremonyFailure {
+	pub ceremony: [u8; 32],
+	pub timestamp: u64,
+	pub reason: CeremonyFailureReason,
+	// signers the failure is attributed to, i.e. the sender of a faulty contribution or the
+	// signers a timed out ceremony was still waiting for
+	pub offenders: Signers,
+}
+
+/// Recently failed ceremonies, the oldest one is dropped once the history is full.
+pub struct CeremonyFailureHistory {
+	failures: VecDeque<CeremonyFailure>,
+	// random key of this enclave the ceremony ids are hashed with
+	key: [u8; 32],
+}
+
+impl Default for CeremonyFailureHistory {
+	fn default() -> Self {
+		Self { failures: VecDeque::new(), key: random_seed() }
+	}
+}
+
+impl CeremonyFailureHistory {
+	pub fn record(
+		&mut self,
+		ceremony_id: &CeremonyId,
+		timestamp: u64,
+		reason: CeremonyFailureReason,
+		offenders: Signers,
+	) {
+		let failure = CeremonyFailure {
+			ceremony: self.ceremony_hash(ceremony_id),
+			timestamp,
+			reason,
+			offenders,
+		};
+		if self.failures.len() == CEREMONY_FAILURE_HISTORY_SIZE {
+			self.failures.pop_front();
+		}
+		self.failures.push_back(failure);
+	}
+
+	/// Hash the diagnostics identify a ceremony with. Unlike the hash a request is answered with
+	/// it can't be computed from the payload, so the diagnostics don't tell whoever reads them
+	/// which payloads are being signed.
+	pub fn ceremony_hash(&self, ceremony_id: &CeremonyId) -> [u8; 32] {
+		blake2_256(&(self.key, ceremony_id).encode())
+	}
+
+	/// Failures recorded, the most recent first.
+	pub fn recent(&self) -> Vec<CeremonyFailure> {
+		self.failures.iter().rev().cloned().collect()
+	}
+}
+
+/// Status of all ceremonies in the registry, the oldest first. They are identified the same way
+/// as in `failures`.
+pub fn ceremony_statuses<AK: AccessKey<KeyType = SchnorrPair>>(
+	registry: &CeremonyRegistry<AK>,
+	failures: &CeremonyFailureHistory,
+	now: u64,
+) -> Vec<CeremonyStatus> {
+	let mut statuses: Vec<CeremonyStatus> = registry
+		.values()
+		.map(|(ceremony, create_time)| {
+			let ceremony = ceremony.read().unwrap();
+			let kind = match *ceremony {
+				Ceremony::MuSig2(_) => CeremonyKind::MuSig2,
+				Ceremony::Threshold(_) => CeremonyKind::Threshold,
+				Ceremony::Batch(_) => CeremonyKind::Batch,
+				Ceremony::ThresholdBatch(_) => CeremonyKind::ThresholdBatch,
+			};
+			CeremonyStatus {
+				ceremony: failures.ceremony_hash(ceremony.get_id_ref()),
+				kind,
+				age: now.saturating_sub(*create_time),
+				contributions: ceremony.contributions(),
+			}
+		})
+		.collect();
+	statuses.sort_by(|a, b| b.age.cmp(&a.age));
+	statuses
+}
+
+// signers except the ones at the given positions
+pub(crate) fn signers_except(signers: &[SignerId], positions: &[usize]) -> Signers {
+	signers
+		.iter()
+		.enumerate()
+		.filter(|(i, _)| !positions.contains(i))
+		.map(|(_, s)| *s)
+		.collect()
+}
+
+#[cfg(test)]
+pub mod test {
+	use crate::{
+		diagnostics::{
+			CeremonyContributions, CeremonyFailureHistory, CeremonyFailureReason, CeremonyRound,
+			CEREMONY_FAILURE_HISTORY_SIZE,
+		},
+		CeremonyError, CeremonyErrorReason, SignBitcoinPayload,
+	};
+	use codec::Encode;
+	use sp_core::hashing::blake2_256;
+
+	fn record_failure(history: &mut CeremonyFailureHistory, timestamp: u64) {
+		history.record(
+			&SignBitcoinPayload::Derived(vec![]),
+			timestamp,
+			CeremonyFailureReason::Error(CeremonyError::NonceReceivingError(
+				CeremonyErrorReason::ContributionError,
+			)),
+			vec![[1u8; 32]],
+		)
+	}
+
+	#[test]
+	fn it_should_list_missing_signers_of_current_round() {
+		// given
+		let contributions = CeremonyContributions {
+			round: CeremonyRound::PartialSignatures,
+			signers: vec![[0u8; 32], [1u8; 32], [2u8; 32]],
+			nonces: vec![[0u8; 32], [1u8; 32], [2u8; 32]],
+			partial_signatures: vec![[0u8; 32], [2u8; 32]],
+		};
+
+		// when
+		let missing = contributions.missing();
+
+		// then
+		assert_eq!(missing, vec![[1u8; 32]]);
+	}
+
+	#[test]
+	fn it_should_keep_most_recent_failures() {
+		// given
+		let mut history = CeremonyFailureHistory::default();
+
+		// when
+		for timestamp in 0..(CEREMONY_FAILURE_HISTORY_SIZE as u64 + 2) {
+			record_failure(&mut history, timestamp);
+		}
+
+		// then
+		let recent = history.recent();
+		assert_eq!(recent.len(), CEREMONY_FAILURE_HISTORY_SIZE);
+		assert_eq!(recent[0].timestamp, CEREMONY_FAILURE_HISTORY_SIZE as u64 + 1);
+		assert_eq!(recent.last().unwrap().timestamp, 2);
+	}
+	#[test]
+	fn it_should_not_identify_ceremony_by_request_hash() {
+		// given
+		let ceremony_id = SignBitcoinPayload::Derived(vec![]);
+		let mut history = CeremonyFailureHistory::default();
+
+		// when
+		record_failure(&mut history, 0);
+
+		// then
+		let ceremony = history.recent()[0].ceremony;
+		assert_eq!(ceremony, history.ceremony_hash(&ceremony_id));
+		assert_ne!(ceremony, blake2_256(&ceremony_id.encode()));
+		assert_ne!(ceremony, CeremonyFailureHistory::default().ceremony_hash(&ceremony_id));
+	}
+}
@@ -0,0 +1,845 @@
+// Copyright 2020-2024 Trust Computing GmbH.
+// This file is part of Litentry.
+//
+// Litentry is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// Litentry is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with Litentry.  If not, see <https://www.gnu.org/licenses/>.
+
+//! FROST threshold Schnorr signatures over secp256k1.
+//!
+//! The group key is created with a Pedersen distributed key generation (every participant
+//! deals shares of a random polynomial and proves knowledge of its constant term), signatures
+//! are produced in two rounds by any `threshold` participants and are plain BIP340 signatures.
+//! A key can be reshared to another set of participants: enough of its holders deal their
+//! Lagrange weighted shares instead of random secrets, which keeps the group key unchanged.
+//! Tweaks are accumulated the same way MuSig2 (BIP327) does it, so taproot outputs of the group
+//! key can be spent with the existing `SignBitcoinPayload` variants.
+
+use crate::{random_seed, SignBitcoinPayload};
+use codec::{Decode, Encode};
+use k256::{
+	elliptic_curve::{
+		group::{Group, GroupEncoding},
+		ops::Reduce,
+		point::AffineCoordinates,
+		PrimeField,
+	},
+	CompressedPoint, FieldBytes, ProjectivePoint, Scalar, U256,
+};
+use sp_core::hashing::sha2_256;
+use std::{collections::BTreeMap, vec::Vec};
+
+pub type ParticipantIndex = u16;
+pub type SerializedPoint = [u8; 33];
+pub type SerializedScalar = [u8; 32];
+// hiding and binding commitments, laid out like a MuSig2 public nonce
+pub type SerializedCommitments = [u8; 66];
+
+#[derive(Debug, Eq, PartialEq, Clone, Copy, Encode)]
+pub enum FrostError {
+	InvalidThreshold,
+	InvalidPoint,
+	InvalidScalar,
+	InvalidProofOfKnowledge,
+	InvalidShare,
+	InvalidSigningSet,
+	InvalidPartialSignature,
+	UnknownParticipant,
+	UnsupportedPayload,
+}
+
+pub(crate) fn tagged_hash(tag: &[u8], data: &[&[u8]]) -> [u8; 32] {
+	let tag_hash = sha2_256(tag);
+	let mut preimage = Vec::with_capacity(64 + data.iter().map(|d| d.len()).sum::<usize>());
+	preimage.extend_from_slice(&tag_hash);
+	preimage.extend_from_slice(&tag_hash);
+	data.iter().for_each(|d| preimage.extend_from_slice(d));
+	sha2_256(&preimage)
+}
+
+fn hash_to_scalar(tag: &[u8], data: &[&[u8]]) -> Scalar {
+	<Scalar as Reduce<U256>>::reduce_bytes(&FieldBytes::clone_from_slice(&tagged_hash(tag, data)))
+}
+
+fn random_scalar() -> Scalar {
+	<Scalar as Reduce<U256>>::reduce_bytes(&FieldBytes::clone_from_slice(&random_seed()))
+}
+
+fn participant_scalar(index: ParticipantIndex) -> Scalar {
+	Scalar::from(index as u64)
+}
+
+fn has_odd_y(point: &ProjectivePoint) -> bool {
+	bool::from(point.to_affine().y_is_odd())
+}
+
+pub(crate) fn x_only(point: &ProjectivePoint) -> [u8; 32] {
+	let mut bytes = [0u8; 32];
+	bytes.copy_from_slice(&point.to_affine().x());
+	bytes
+}
+
+pub fn serialize_point(point: &ProjectivePoint) -> SerializedPoint {
+	let mut bytes = [0u8; 33];
+	bytes.copy_from_slice(&point.to_affine().to_bytes());
+	bytes
+}
+
+pub fn deserialize_point(bytes: &SerializedPoint) -> Result<ProjectivePoint, FrostError> {
+	let point: Option<ProjectivePoint> =
+		ProjectivePoint::from_bytes(CompressedPoint::from_slice(bytes)).into();
+	match point {
+		Some(point) if !bool::from(point.is_identity()) => Ok(point),
+		_ => Err(FrostError::InvalidPoint),
+	}
+}
+
+pub fn serialize_scalar(scalar: &Scalar) -> SerializedScalar {
+	let mut bytes = [0u8; 32];
+	bytes.copy_from_slice(&scalar.to_bytes());
+	bytes
+}
+
+pub fn deserialize_scalar(bytes: &SerializedScalar) -> Result<Scalar, FrostError> {
+	Option::<Scalar>::from(Scalar::from_repr(FieldBytes::clone_from_slice(bytes)))
+		.ok_or(FrostError::InvalidScalar)
+}
+
+/// Secret polynomial a participant deals shares of during the key generation.
+pub struct DkgSecret {
+	coefficients: Vec<Scalar>,
+}
+
+impl DkgSecret {
+	pub fn share_for(&self, index: ParticipantIndex) -> SerializedScalar {
+		let x = participant_scalar(index);
+		let share = self.coefficients.iter().rev().fold(Scalar::ZERO, |acc, c| acc * x + c);
+		serialize_scalar(&share)
+	}
+}
+
+/// Public part of a participant's contribution to the key generation, broadcast to everybody.
+#[derive(Encode, Decode, Clone, Debug, PartialEq, Eq)]
+pub struct DkgCommitment {
+	pub coefficient_commitments: Vec<SerializedPoint>,
+	// schnorr proof of knowledge of the constant term, binds the contribution to the sender
+	pub proof_of_knowledge: (SerializedPoint, SerializedScalar),
+}
+
+fn proof_of_knowledge_challenge(
+	index: ParticipantIndex,
+	context: &[u8],
+	constant_commitment: &SerializedPoint,
+	nonce_commitment: &SerializedPoint,
+) -> Scalar {
+	hash_to_scalar(
+		b"BitAcross/FROST/dkg-pok",
+		&[&index.to_be_bytes(), context, constant_commitment, nonce_commitment],
+	)
+}
+
+/// Samples a polynomial of degree `threshold - 1` and commits to it. `context` should be unique
+/// for every key generation, it prevents contributions from being replayed into another one.
+pub fn dkg_round_one(
+	index: ParticipantIndex,
+	threshold: u16,
+	context: &[u8],
+) -> Result<(DkgSecret, DkgCommitment), FrostError> {
+	dkg_deal(index, threshold, context, random_scalar())
+}
+
+/// Same as `dkg_round_one` but deals shares of a given secret instead of a random one.
+pub fn dkg_deal(
+	index: ParticipantIndex,
+	threshold: u16,
+	context: &[u8],
+	secret: Scalar,
+) -> Result<(DkgSecret, DkgCommitment), FrostError> {
+	if threshold < 2 || index == 0 {
+		return Err(FrostError::InvalidThreshold)
+	}
+	let coefficients: Vec<Scalar> = core::iter::once(secret)
+		.chain((1..threshold).map(|_| random_scalar()))
+		.collect();
+	let coefficient_commitments: Vec<SerializedPoint> = coefficients
+		.iter()
+		.map(|c| serialize_point(&(ProjectivePoint::GENERATOR * c)))
+		.collect();
+
+	let nonce = random_scalar();
+	let nonce_commitment = serialize_point(&(ProjectivePoint::GENERATOR * nonce));
+	let challenge = proof_of_knowledge_challenge(
+		index,
+		context,
+		&coefficient_commitments[0],
+		&nonce_commitment,
+	);
+	let response = nonce + coefficients[0] * challenge;
+
+	Ok((
+		DkgSecret { coefficients },
+		DkgCommitment {
+			coefficient_commitments,
+			proof_of_knowledge: (nonce_commitment, serialize_scalar(&response)),
+		},
+	))
+}
+
+/// Checks the proof of knowledge of a contribution and returns its coefficient commitments.
+pub fn verify_dkg_commitment(
+	sender: ParticipantIndex,
+	threshold: u16,
+	context: &[u8],
+	commitment: &DkgCommitment,
+) -> Result<Vec<ProjectivePoint>, FrostError> {
+	if commitment.coefficient_commitments.len() != threshold as usize || threshold < 2 {
+		return Err(FrostError::InvalidThreshold)
+	}
+	let points = commitment
+		.coefficient_commitments
+		.iter()
+		.map(deserialize_point)
+		.collect::<Result<Vec<_>, _>>()?;
+	let (nonce_commitment, response) = &commitment.proof_of_knowledge;
+	let challenge = proof_of_knowledge_challenge(
+		sender,
+		context,
+		&commitment.coefficient_commitments[0],
+		nonce_commitment,
+	);
+	if ProjectivePoint::GENERATOR * deserialize_scalar(response)?
+		!= deserialize_point(nonce_commitment)? + points[0] * challenge
+	{
+		return Err(FrostError::InvalidProofOfKnowledge)
+	}
+	Ok(points)
+}
+
+fn evaluate_commitments(points: &[ProjectivePoint], index: ParticipantIndex) -> ProjectivePoint {
+	let x = participant_scalar(index);
+	points.iter().rev().fold(ProjectivePoint::IDENTITY, |acc, p| acc * x + p)
+}
+
+/// Checks a share dealt to `receiver` against the dealer's coefficient commitments.
+pub fn verify_dkg_share(
+	receiver: ParticipantIndex,
+	share: &SerializedScalar,
+	commitments: &[ProjectivePoint],
+) -> Result<Scalar, FrostError> {
+	let share = deserialize_scalar(share)?;
+	if ProjectivePoint::GENERATOR * share != evaluate_commitments(commitments, receiver) {
+		return Err(FrostError::InvalidShare)
+	}
+	Ok(share)
+}
+
+pub struct DkgOutput {
+	pub secret_share: Scalar,
+	pub group_public_key: ProjectivePoint,
+	pub verification_shares: BTreeMap<ParticipantIndex, ProjectivePoint>,
+}
+
+/// Combines the verified contributions of all dealers, `shares` are the ones dealt to us.
+/// Verification shares are computed for every one of `participants`.
+pub fn dkg_finalize(
+	commitments: &BTreeMap<ParticipantIndex, Vec<ProjectivePoint>>,
+	shares: &BTreeMap<ParticipantIndex, Scalar>,
+	participants: &[ParticipantIndex],
+) -> Result<DkgOutput, FrostError> {
+	if commitments.len() != shares.len() || commitments.keys().ne(shares.keys()) {
+		return Err(FrostError::UnknownParticipant)
+	}
+	let secret_share = shares.values().fold(Scalar::ZERO, |acc, s| acc + s);
+	let group_public_key = commitments
+		.values()
+		.fold(ProjectivePoint::IDENTITY, |acc, points| acc + points[0]);
+	if bool::from(group_public_key.is_identity()) {
+		return Err(FrostError::InvalidPoint)
+	}
+	let verification_shares = participants
+		.iter()
+		.map(|index| {
+			let share = commitments.values().fold(ProjectivePoint::IDENTITY, |acc, points| {
+				acc + evaluate_commitments(points, *index)
+			});
+			(*index, share)
+		})
+		.collect();
+	Ok(DkgOutput { secret_share, group_public_key, verification_shares })
+}
+
+/// Part of the group secret a holder of `secret_share` deals when the key is reshared among a
+/// new set of participants. The parts dealt by all of `dealers` sum up to the group secret, so
+/// the group public key stays the same.
+pub fn resharing_secret(
+	index: ParticipantIndex,
+	dealers: &[ParticipantIndex],
+	secret_share: &Scalar,
+) -> Result<Scalar, FrostError> {
+	let lagrange_coefficient = lagrange_coefficients(dealers)?
+		.remove(&index)
+		.ok_or(FrostError::UnknownParticipant)?;
+	Ok(lagrange_coefficient * secret_share)
+}
+
+/// Checks that a resharing contribution deals exactly the part of the group secret its sender
+/// holds, `verification_share` is the sender's one from the key being reshared.
+pub fn verify_resharing_commitment(
+	sender: ParticipantIndex,
+	dealers: &[ParticipantIndex],
+	commitments: &[ProjectivePoint],
+	verification_share: &ProjectivePoint,
+) -> Result<(), FrostError> {
+	let lagrange_coefficient = lagrange_coefficients(dealers)?
+		.remove(&sender)
+		.ok_or(FrostError::UnknownParticipant)?;
+	match commitments.first() {
+		Some(point) if *point == *verification_share * lagrange_coefficient => Ok(()),
+		_ => Err(FrostError::InvalidShare),
+	}
+}
+
+/// Single use nonces of one signer, consumed when the partial signature is produced.
+pub struct SigningNonces {
+	hiding: Scalar,
+	binding: Scalar,
+}
+
+impl SigningNonces {
+	pub fn generate() -> Self {
+		Self { hiding: random_scalar(), binding: random_scalar() }
+	}
+
+	pub fn commitments(&self) -> SigningCommitments {
+		SigningCommitments {
+			hiding: ProjectivePoint::GENERATOR * self.hiding,
+			binding: ProjectivePoint::GENERATOR * self.binding,
+		}
+	}
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct SigningCommitments {
+	pub hiding: ProjectivePoint,
+	pub binding: ProjectivePoint,
+}
+
+impl SigningCommitments {
+	pub fn serialize(&self) -> SerializedCommitments {
+		let mut bytes = [0u8; 66];
+		bytes[..33].copy_from_slice(&serialize_point(&self.hiding));
+		bytes[33..].copy_from_slice(&serialize_point(&self.binding));
+		bytes
+	}
+
+	pub fn deserialize(bytes: &SerializedCommitments) -> Result<Self, FrostError> {
+		let mut hiding = [0u8; 33];
+		let mut binding = [0u8; 33];
+		hiding.copy_from_slice(&bytes[..33]);
+		binding.copy_from_slice(&bytes[33..]);
+		Ok(Self { hiding: deserialize_point(&hiding)?, binding: deserialize_point(&binding)? })
+	}
+}
+
+/// Group public key with the tweaks of a signing payload applied.
+#[derive(Clone, Debug)]
+pub struct TweakedGroupKey {
+	pub public_key: ProjectivePoint,
+	// product of the negations applied while tweaking
+	gacc: Scalar,
+	// accumulated tweak
+	tacc: Scalar,
+}
+
+impl TweakedGroupKey {
+	pub fn new(
+		group_public_key: ProjectivePoint,
+		payload: &SignBitcoinPayload,
+	) -> Result<Self, FrostError> {
+		let key = Self { public_key: group_public_key, gacc: Scalar::ONE, tacc: Scalar::ZERO };
+		match payload {
+			SignBitcoinPayload::Derived(_) => Ok(key),
+			SignBitcoinPayload::TaprootUnspendable(_) => {
+				let tweak = key.taproot_tweak(&[]);
+				key.with_tweak(tweak, true)
+			},
+			SignBitcoinPayload::TaprootSpendable(_, root_hash) => {
+				let tweak = key.taproot_tweak(root_hash);
+				key.with_tweak(tweak, true)
+			},
+			SignBitcoinPayload::WithTweaks(_, tweaks) =>
+				tweaks.iter().try_fold(key, |key, (tweak, is_x_only)| {
+					key.with_tweak(deserialize_scalar(tweak)?, *is_x_only)
+				}),
+			// batches are only signed by MuSig2 ceremonies
+			SignBitcoinPayload::Batch(_, _) => Err(FrostError::UnsupportedPayload),
+		}
+	}
+
+	// BIP341 tweak committing to the script tree `merkle_root`, empty for key path only outputs
+	fn taproot_tweak(&self, merkle_root: &[u8]) -> Scalar {
+		hash_to_scalar(b"TapTweak", &[&x_only(&self.public_key), merkle_root])
+	}
+
+	fn with_tweak(self, tweak: Scalar, is_x_only: bool) -> Result<Self, FrostError> {
+		let negation =
+			if is_x_only && has_odd_y(&self.public_key) { -Scalar::ONE } else { Scalar::ONE };
+		let public_key = self.public_key * negation + ProjectivePoint::GENERATOR * tweak;
+		if bool::from(public_key.is_identity()) {
+			return Err(FrostError::InvalidPoint)
+		}
+		Ok(Self { public_key, gacc: self.gacc * negation, tacc: tweak + self.tacc * negation })
+	}
+
+	// BIP340 signs for the even y representation of the key
+	fn parity(&self) -> Scalar {
+		if has_odd_y(&self.public_key) {
+			-Scalar::ONE
+		} else {
+			Scalar::ONE
+		}
+	}
+}
+
+fn lagrange_coefficients(
+	indices: &[ParticipantIndex],
+) -> Result<BTreeMap<ParticipantIndex, Scalar>, FrostError> {
+	let mut coefficients = BTreeMap::new();
+	for i in indices {
+		if *i == 0 {
+			return Err(FrostError::InvalidSigningSet)
+		}
+		let x_i = participant_scalar(*i);
+		let (numerator, denominator) = indices.iter().filter(|j| *j != i).fold(
+			(Scalar::ONE, Scalar::ONE),
+			|(numerator, denominator), j| {
+				let x_j = participant_scalar(*j);
+				(numerator * x_j, denominator * (x_j - x_i))
+			},
+		);
+		let inverted =
+			Option::<Scalar>::from(denominator.invert()).ok_or(FrostError::InvalidSigningSet)?;
+		coefficients.insert(*i, numerator * inverted);
+	}
+	Ok(coefficients)
+}
+
+/// Values all members of a signing set derive from the same commitments and message.
+pub struct SigningContext {
+	key: TweakedGroupKey,
+	commitments: BTreeMap<ParticipantIndex, SigningCommitments>,
+	binding_factors: BTreeMap<ParticipantIndex, Scalar>,
+	lagrange_coefficients: BTreeMap<ParticipantIndex, Scalar>,
+	// x coordinate of the group commitment
+	nonce_x: [u8; 32],
+	// -1 if the group commitment had odd y and the nonces have to be negated
+	nonce_parity: Scalar,
+	challenge: Scalar,
+}
+
+impl SigningContext {
+	pub fn new(
+		group_public_key: &ProjectivePoint,
+		key: &TweakedGroupKey,
+		message: &[u8],
+		commitments: BTreeMap<ParticipantIndex, SigningCommitments>,
+	) -> Result<Self, FrostError> {
+		if commitments.len() < 2 {
+			return Err(FrostError::InvalidSigningSet)
+		}
+		let indices: Vec<ParticipantIndex> = commitments.keys().copied().collect();
+		let lagrange_coefficients = lagrange_coefficients(&indices)?;
+
+		let group_key_bytes = serialize_point(group_public_key);
+		let message_hash = sha2_256(message);
+		let mut encoded_commitments = Vec::with_capacity(commitments.len() * 68);
+		commitments.iter().for_each(|(index, c)| {
+			encoded_commitments.extend_from_slice(&index.to_be_bytes());
+			encoded_commitments.extend_from_slice(&c.serialize());
+		});
+		let binding_factors: BTreeMap<ParticipantIndex, Scalar> = indices
+			.iter()
+			.map(|index| {
+				let factor = hash_to_scalar(
+					b"BitAcross/FROST/binding",
+					&[&group_key_bytes, &message_hash, &encoded_commitments, &index.to_be_bytes()],
+				);
+				(*index, factor)
+			})
+			.collect();
+
+		let group_commitment =
+			commitments.iter().fold(ProjectivePoint::IDENTITY, |acc, (index, c)| {
+				acc + c.hiding + c.binding * binding_factors[index]
+			});
+		if bool::from(group_commitment.is_identity()) {
+			return Err(FrostError::InvalidPoint)
+		}
+		let nonce_parity = if has_odd_y(&group_commitment) { -Scalar::ONE } else { Scalar::ONE };
+		let nonce_x = x_only(&group_commitment);
+		let challenge =
+			hash_to_scalar(b"BIP0340/challenge", &[&nonce_x, &x_only(&key.public_key), message]);
+
+		Ok(Self {
+			key: key.clone(),
+			commitments,
+			binding_factors,
+			lagrange_coefficients,
+			nonce_x,
+			nonce_parity,
+			challenge,
+		})
+	}
+
+	pub fn signers(&self) -> Vec<ParticipantIndex> {
+		self.commitments.keys().copied().collect()
+	}
+
+	// multiplier of a signer's secret share in its partial signature
+	fn share_factor(&self, index: ParticipantIndex) -> Result<Scalar, FrostError> {
+		let lagrange_coefficient =
+			self.lagrange_coefficients.get(&index).ok_or(FrostError::UnknownParticipant)?;
+		Ok(*lagrange_coefficient * self.challenge * self.key.parity() * self.key.gacc)
+	}
+
+	fn nonce_factor(&self, index: ParticipantIndex) -> Result<Scalar, FrostError> {
+		self.binding_factors.get(&index).copied().ok_or(FrostError::UnknownParticipant)
+	}
+
+	pub fn sign(
+		&self,
+		index: ParticipantIndex,
+		secret_share: &Scalar,
+		nonces: SigningNonces,
+	) -> Result<Scalar, FrostError> {
+		let nonce =
+			(nonces.hiding + nonces.binding * self.nonce_factor(index)?) * self.nonce_parity;
+		Ok(nonce + self.share_factor(index)? * secret_share)
+	}
+
+	pub fn verify_partial(
+		&self,
+		index: ParticipantIndex,
+		partial_signature: &Scalar,
+		verification_share: &ProjectivePoint,
+	) -> Result<(), FrostError> {
+		let commitments = self.commitments.get(&index).ok_or(FrostError::UnknownParticipant)?;
+		let nonce_commitment = (commitments.hiding
+			+ commitments.binding * self.nonce_factor(index)?)
+			* self.nonce_parity;
+		if ProjectivePoint::GENERATOR * partial_signature
+			!= nonce_commitment + *verification_share * self.share_factor(index)?
+		{
+			return Err(FrostError::InvalidPartialSignature)
+		}
+		Ok(())
+	}
+
+	/// Combines the partial signatures of the whole signing set into a BIP340 signature.
+	pub fn aggregate(
+		&self,
+		partial_signatures: &BTreeMap<ParticipantIndex, Scalar>,
+	) -> Result<[u8; 64], FrostError> {
+		if partial_signatures.keys().ne(self.commitments.keys()) {
+			return Err(FrostError::InvalidSigningSet)
+		}
+		let s = partial_signatures.values().fold(Scalar::ZERO, |acc, z| acc + z)
+			+ self.challenge * self.key.parity() * self.key.tacc;
+		let mut signature = [0u8; 64];
+		signature[..32].copy_from_slice(&self.nonce_x);
+		signature[32..].copy_from_slice(&serialize_scalar(&s));
+		Ok(signature)
+	}
+}
+
+#[cfg(test)]
+pub mod test {
+	use super::*;
+	use k256::PublicKey;
+	use musig2::{verify_single, BinaryEncoding, CompactSignature};
+	use std::{vec, vec::Vec};
+
+	const CONTEXT: &[u8] = b"key generation";
+
+	type KeyShares = BTreeMap<ParticipantIndex, (Scalar, ProjectivePoint)>;
+
+	fn generate_key(threshold: u16, participants: u16) -> (ProjectivePoint, KeyShares) {
+		let contributions: Vec<(ParticipantIndex, DkgSecret, DkgCommitment)> = (1..=participants)
+			.map(|index| {
+				let (secret, commitment) = dkg_round_one(index, threshold, CONTEXT).unwrap();
+				(index, secret, commitment)
+			})
+			.collect();
+
+		let indices: Vec<ParticipantIndex> = (1..=participants).collect();
+		let mut group_public_keys = vec![];
+		let mut key_shares = BTreeMap::new();
+		for receiver in 1..=participants {
+			let commitments: BTreeMap<ParticipantIndex, Vec<ProjectivePoint>> = contributions
+				.iter()
+				.map(|(index, _, commitment)| {
+					(*index, verify_dkg_commitment(*index, threshold, CONTEXT, commitment).unwrap())
+				})
+				.collect();
+			let shares: BTreeMap<ParticipantIndex, Scalar> = contributions
+				.iter()
+				.map(|(index, secret, _)| {
+					let share = secret.share_for(receiver);
+					(*index, verify_dkg_share(receiver, &share, &commitments[index]).unwrap())
+				})
+				.collect();
+			let output = dkg_finalize(&commitments, &shares, &indices).unwrap();
+			assert_eq!(
+				output.verification_shares[&receiver],
+				ProjectivePoint::GENERATOR * output.secret_share
+			);
+			group_public_keys.push(output.group_public_key);
+			key_shares
+				.insert(receiver, (output.secret_share, output.verification_shares[&receiver]));
+		}
+		assert!(group_public_keys.windows(2).all(|keys| keys[0] == keys[1]));
+
+		(group_public_keys[0], key_shares)
+	}
+
+	// `dealers` hand their shares over to participants `1..=participants` of the new key
+	fn reshare(
+		key_shares: &KeyShares,
+		dealers: &[ParticipantIndex],
+		threshold: u16,
+		participants: u16,
+	) -> (ProjectivePoint, KeyShares) {
+		let contributions: Vec<(ParticipantIndex, DkgSecret, Vec<ProjectivePoint>)> = dealers
+			.iter()
+			.map(|index| {
+				let (secret_share, verification_share) = key_shares[index];
+				let secret = resharing_secret(*index, dealers, &secret_share).unwrap();
+				let (secret, commitment) = dkg_deal(*index, threshold, CONTEXT, secret).unwrap();
+				let points =
+					verify_dkg_commitment(*index, threshold, CONTEXT, &commitment).unwrap();
+				verify_resharing_commitment(*index, dealers, &points, &verification_share).unwrap();
+				(*index, secret, points)
+			})
+			.collect();
+
+		let indices: Vec<ParticipantIndex> = (1..=participants).collect();
+		let commitments: BTreeMap<ParticipantIndex, Vec<ProjectivePoint>> = contributions
+			.iter()
+			.map(|(index, _, points)| (*index, points.clone()))
+			.collect();
+		let mut group_public_keys = vec![];
+		let mut new_key_shares = BTreeMap::new();
+		for receiver in 1..=participants {
+			let shares: BTreeMap<ParticipantIndex, Scalar> = contributions
+				.iter()
+				.map(|(index, secret, points)| {
+					let share = secret.share_for(receiver);
+					(*index, verify_dkg_share(receiver, &share, points).unwrap())
+				})
+				.collect();
+			let output = dkg_finalize(&commitments, &shares, &indices).unwrap();
+			group_public_keys.push(output.group_public_key);
+			new_key_shares
+				.insert(receiver, (output.secret_share, output.verification_shares[&receiver]));
+		}
+		assert!(group_public_keys.windows(2).all(|keys| keys[0] == keys[1]));
+
+		(group_public_keys[0], new_key_shares)
+	}
+
+	fn sign(
+		group_public_key: ProjectivePoint,
+		key_shares: &KeyShares,
+		signers: &[ParticipantIndex],
+		payload: &SignBitcoinPayload,
+	) -> (TweakedGroupKey, [u8; 64]) {
+		let key = TweakedGroupKey::new(group_public_key, payload).unwrap();
+		let nonces: BTreeMap<ParticipantIndex, SigningNonces> =
+			signers.iter().map(|index| (*index, SigningNonces::generate())).collect();
+		let commitments = nonces.iter().map(|(index, n)| (*index, n.commitments())).collect();
+		let context =
+			SigningContext::new(&group_public_key, &key, payload.message(), commitments).unwrap();
+		let partial_signatures: BTreeMap<ParticipantIndex, Scalar> = nonces
+			.into_iter()
+			.map(|(index, nonces)| {
+				let (secret_share, verification_share) = key_shares[&index];
+				let partial_signature = context.sign(index, &secret_share, nonces).unwrap();
+				context.verify_partial(index, &partial_signature, &verification_share).unwrap();
+				(index, partial_signature)
+			})
+			.collect();
+		let signature = context.aggregate(&partial_signatures).unwrap();
+		(key, signature)
+	}
+
+	fn verify(key: &TweakedGroupKey, payload: &SignBitcoinPayload, signature: [u8; 64]) -> bool {
+		let public_key = PublicKey::from_affine(key.public_key.to_affine()).unwrap();
+		let signature = CompactSignature::from_bytes(&signature).unwrap();
+		verify_single(public_key, signature, payload.message()).is_ok()
+	}
+
+	#[test]
+	fn it_should_sign_with_any_threshold_subset() {
+		// given
+		let (group_public_key, key_shares) = generate_key(3, 5);
+		let payload = SignBitcoinPayload::Derived([1u8; 32].to_vec());
+
+		for signers in [[1, 2, 3], [2, 4, 5], [1, 3, 5]] {
+			// when
+			let (key, signature) = sign(group_public_key, &key_shares, &signers, &payload);
+
+			// then
+			assert!(verify(&key, &payload, signature));
+		}
+	}
+
+	#[test]
+	fn it_should_sign_tweaked_payloads() {
+		// given
+		let (group_public_key, key_shares) = generate_key(2, 3);
+		let message = [2u8; 32].to_vec();
+		let payloads = vec![
+			SignBitcoinPayload::TaprootUnspendable(message.clone()),
+			SignBitcoinPayload::TaprootSpendable(message.clone(), [7u8; 32]),
+			SignBitcoinPayload::WithTweaks(message, vec![([1u8; 32], false), ([2u8; 32], true)]),
+		];
+
+		for payload in payloads {
+			// when
+			let (key, signature) = sign(group_public_key, &key_shares, &[1, 3], &payload);
+
+			// then
+			assert!(verify(&key, &payload, signature));
+			assert_ne!(key.public_key, group_public_key);
+		}
+	}
+
+	#[test]
+	fn it_should_apply_bip86_taproot_tweak() {
+		// given
+		let internal_key: SerializedPoint = [
+			&[2u8][..],
+			&hex::decode("cc8a4bc64d897bddc5fbc2f670f7a8ba0b386779106cf1223c6fc5d7cd6fc115")
+				.unwrap()[..],
+		]
+		.concat()
+		.try_into()
+		.unwrap();
+		let payload = SignBitcoinPayload::TaprootUnspendable(vec![]);
+
+		// when
+		let key =
+			TweakedGroupKey::new(deserialize_point(&internal_key).unwrap(), &payload).unwrap();
+
+		// then
+		assert_eq!(
+			hex::encode(x_only(&key.public_key)),
+			"a60869f0dbcf1dc659c9cecbaf8050135ea9e8cdc487053f1dc6880949dc684c"
+		);
+	}
+
+	#[test]
+	fn it_should_not_produce_valid_signature_below_threshold() {
+		// given
+		let (group_public_key, key_shares) = generate_key(3, 4);
+		let payload = SignBitcoinPayload::Derived([3u8; 32].to_vec());
+
+		// when
+		let (key, signature) = sign(group_public_key, &key_shares, &[2, 4], &payload);
+
+		// then
+		assert!(!verify(&key, &payload, signature));
+	}
+
+	#[test]
+	fn it_should_reject_invalid_partial_signature() {
+		// given
+		let (group_public_key, key_shares) = generate_key(2, 3);
+		let payload = SignBitcoinPayload::Derived([4u8; 32].to_vec());
+		let key = TweakedGroupKey::new(group_public_key, &payload).unwrap();
+		let nonces = SigningNonces::generate();
+		let commitments =
+			vec![(1, nonces.commitments()), (2, SigningNonces::generate().commitments())]
+				.into_iter()
+				.collect();
+		let context =
+			SigningContext::new(&group_public_key, &key, payload.message(), commitments).unwrap();
+		let (secret_share, verification_share) = key_shares[&1];
+
+		// when
+		let partial_signature = context.sign(1, &secret_share, nonces).unwrap() + Scalar::ONE;
+
+		// then
+		assert_eq!(
+			context.verify_partial(1, &partial_signature, &verification_share),
+			Err(FrostError::InvalidPartialSignature)
+		);
+	}
+
+	#[test]
+	fn it_should_reject_dkg_share_not_matching_commitment() {
+		// given
+		let (secret, commitment) = dkg_round_one(1, 2, CONTEXT).unwrap();
+		let commitments = verify_dkg_commitment(1, 2, CONTEXT, &commitment).unwrap();
+
+		// when
+		let result = verify_dkg_share(2, &secret.share_for(3), &commitments);
+
+		// then
+		assert_eq!(result, Err(FrostError::InvalidShare));
+	}
+
+	#[test]
+	fn it_should_reject_dkg_commitment_replayed_by_other_participant() {
+		// given
+		let (_, commitment) = dkg_round_one(1, 2, CONTEXT).unwrap();
+
+		// when
+		let result = verify_dkg_commitment(2, 2, CONTEXT, &commitment);
+
+		// then
+		assert_eq!(result, Err(FrostError::InvalidProofOfKnowledge));
+	}
+
+	#[test]
+	fn it_should_keep_group_key_when_resharing() {
+		// given
+		let (group_public_key, key_shares) = generate_key(2, 3);
+		let payload = SignBitcoinPayload::Derived([5u8; 32].to_vec());
+
+		// when
+		let (reshared_public_key, new_key_shares) = reshare(&key_shares, &[1, 3], 3, 4);
+
+		// then
+		assert_eq!(reshared_public_key, group_public_key);
+		for signers in [[1, 2, 3], [2, 3, 4]] {
+			let (key, signature) = sign(group_public_key, &new_key_shares, &signers, &payload);
+			assert!(verify(&key, &payload, signature));
+		}
+	}
+
+	#[test]
+	fn it_should_reject_resharing_of_foreign_share() {
+		// given
+		let (_, key_shares) = generate_key(2, 3);
+		let (_, other_key_shares) = generate_key(2, 3);
+		let secret = resharing_secret(1, &[1, 2], &other_key_shares[&1].0).unwrap();
+		let (_, commitment) = dkg_deal(1, 2, CONTEXT, secret).unwrap();
+		let points = verify_dkg_commitment(1, 2, CONTEXT, &commitment).unwrap();
+
+		// when
+		let result = verify_resharing_commitment(1, &[1, 2], &points, &key_shares[&1].1);
+
+		// then
+		assert_eq!(result, Err(FrostError::InvalidShare));
+	}
+}
@@ -0,0 +1,641 @@
+// Copyright 2020-2024 Trust Computing GmbH.
+// This file is part of Litentry.
+//
+// Litentry is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// Litentry is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with Litentry.  If not, see <https://www.gnu.org/licenses/>.
+
+use crate::{
+	frost::{
+		deserialize_point, deserialize_scalar, dkg_deal, dkg_finalize, dkg_round_one,
+		resharing_secret, serialize_point, serialize_scalar, tagged_hash, verify_dkg_commitment,
+		verify_dkg_share, verify_resharing_commitment, x_only, DkgCommitment, DkgSecret,
+		ParticipantIndex, SerializedPoint,
+	},
+	threshold::{ThresholdKeyShare, ThresholdPublicKey},
+	SignerId, Signers, SignersWithKeys,
+};
+use codec::{Decode, Encode};
+use itp_sgx_crypto::{
+	aes_decrypt, aes_encrypt_default, key_repository::AccessKey, schnorr::Pair as SchnorrPair,
+	Aes256Key, AesOutput,
+};
+use k256::{ProjectivePoint, PublicKey, Scalar};
+use log::*;
+use sp_core::hashing::blake2_256;
+use std::{
+	collections::{BTreeMap, HashMap},
+	format,
+	string::String,
+	sync::Arc,
+	vec,
+	vec::Vec,
+};
+
+pub type KeyGenerationId = [u8; 32];
+pub type KeyGenerationRegistry<AK> = HashMap<KeyGenerationId, (KeyGeneration<AK>, u64)>;
+pub type SerializedSigners = Vec<(SignerId, SerializedPoint)>;
+
+/// Identifies a key generation by its participants and threshold, so every enclave derives the
+/// same id from its own view of the signer registry. A resharing is also bound to the key it
+/// hands over.
+pub fn key_generation_id(
+	signers: &SignersWithKeys,
+	threshold: u16,
+	reshared_key: Option<&ThresholdPublicKey>,
+) -> KeyGenerationId {
+	let mut signers = serialize_signers(signers);
+	signers.sort();
+	match reshared_key {
+		None => blake2_256(&(b"BitAcross/FROST/keygen", signers, threshold).encode()),
+		Some(key) => blake2_256(&(b"BitAcross/FROST/reshare", signers, threshold, key).encode()),
+	}
+}
+
+pub fn serialize_signers(signers: &SignersWithKeys) -> SerializedSigners {
+	signers
+		.iter()
+		.map(|(id, key)| (*id, serialize_point(&key.to_projective())))
+		.collect()
+}
+
+pub fn deserialize_signers(signers: &[(SignerId, SerializedPoint)]) -> Option<SignersWithKeys> {
+	signers
+		.iter()
+		.map(|(id, key)| {
+			let point = deserialize_point(key).ok()?;
+			PublicKey::from_affine(point.to_affine()).ok().map(|key| (*id, key))
+		})
+		.collect()
+}
+
+/// Contribution of one participant to another one: the public commitment to its polynomial and
+/// the share dealt to the receiver, encrypted with a key both derive from their signer keys.
+#[derive(Encode, Decode, Clone, Debug, PartialEq, Eq)]
+pub struct KeyGenerationPackage {
+	pub commitment: DkgCommitment,
+	pub encrypted_share: AesOutput,
+}
+
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub enum KeyGenerationCommand {
+	Init(SignersWithKeys, u16),
+	// hands the key over to the signers, the threshold stays the same
+	Reshare(SignersWithKeys, ThresholdPublicKey),
+	SavePackage(SignersWithKeys, u16, Option<ThresholdPublicKey>, SignerId, KeyGenerationPackage),
+}
+
+impl KeyGenerationCommand {
+	pub fn signers(&self) -> &SignersWithKeys {
+		match self {
+			KeyGenerationCommand::Init(signers, _) => signers,
+			KeyGenerationCommand::Reshare(signers, _) => signers,
+			KeyGenerationCommand::SavePackage(signers, ..) => signers,
+		}
+	}
+
+	pub fn threshold(&self) -> u16 {
+		match self {
+			KeyGenerationCommand::Init(_, threshold) => *threshold,
+			KeyGenerationCommand::Reshare(_, key) => key.threshold,
+			KeyGenerationCommand::SavePackage(_, threshold, ..) => *threshold,
+		}
+	}
+
+	pub fn reshared_key(&self) -> Option<&ThresholdPublicKey> {
+		match self {
+			KeyGenerationCommand::Init(..) => None,
+			KeyGenerationCommand::Reshare(_, key) => Some(key),
+			KeyGenerationCommand::SavePackage(_, _, key, ..) => key.as_ref(),
+		}
+	}
+
+	pub fn key_generation_id(&self) -> KeyGenerationId {
+		key_generation_id(self.signers(), self.threshold(), self.reshared_key())
+	}
+}
+
+#[derive(Debug, Eq, PartialEq, Encode)]
+pub enum KeyGenerationError {
+	InitError,
+	SignerNotFound,
+	InvalidPackage,
+	FinalizationError,
+}
+
+#[derive(Debug, Eq, PartialEq)]
+pub enum KeyGenerationEvent {
+	// packages to send to every other participant
+	Started(KeyGenerationId, u16, Vec<(SignerId, KeyGenerationPackage)>),
+	// same as above, receivers need the signers and the reshared key to check the packages
+	ResharingStarted(
+		KeyGenerationId,
+		SerializedSigners,
+		ThresholdPublicKey,
+		Vec<(SignerId, KeyGenerationPackage)>,
+	),
+	Ended(ThresholdKeyShare),
+	Error(Signers, KeyGenerationError),
+}
+
+/// Distributed generation of a threshold key among all registered signers. Nobody ever learns
+/// the group secret, every participant ends up with its own share of it.
+///
+/// The same procedure reshares an existing key when the set of signers changes: holders of the
+/// key which stay signers deal their shares to the new set, so the group public key and the
+/// vault address don't change. Signers which are left out keep their shares of the old sharing,
+/// it remains usable by `threshold` of its holders.
+pub struct KeyGeneration<AK: AccessKey<KeyType = SchnorrPair>> {
+	id: KeyGenerationId,
+	me: SignerId,
+	threshold: u16,
+	// sorted by signer id, the participant index is the position + 1
+	signers: SignersWithKeys,
+	// key being reshared, `None` for a fresh key
+	reshared_key: Option<ThresholdPublicKey>,
+	// participants dealing shares, indexed the way the key they contribute to indexes them
+	dealers: Vec<(SignerId, ParticipantIndex)>,
+	signing_key_access: Arc<AK>,
+	commitments: BTreeMap<ParticipantIndex, Vec<ProjectivePoint>>,
+	shares: BTreeMap<ParticipantIndex, Scalar>,
+}
+
+impl<AK: AccessKey<KeyType = SchnorrPair>> KeyGeneration<AK> {
+	pub fn new(
+		me: SignerId,
+		signers: SignersWithKeys,
+		threshold: u16,
+		signing_key_access: Arc<AK>,
+	) -> Result<(Self, KeyGenerationEvent), String> {
+		let mut key_generation = Self::create(me, signers, threshold, None, signing_key_access)?;
+		let my_index = key_generation.index_of(&me).ok_or("Could not determine index")?;
+		let contribution = dkg_round_one(my_index, threshold, &key_generation.id)
+			.map_err(|e| format!("Could not create contribution: {:?}", e))?;
+		let packages = key_generation.deal(my_index, contribution)?;
+		let event = KeyGenerationEvent::Started(key_generation.id, threshold, packages);
+
+		Ok((key_generation, event))
+	}
+
+	/// Starts handing `reshared_key` over to `signers`. Holders of the key deal their
+	/// `key_share`, signers which don't hold the key yet only receive.
+	pub fn reshare(
+		me: SignerId,
+		signers: SignersWithKeys,
+		reshared_key: ThresholdPublicKey,
+		key_share: Option<ThresholdKeyShare>,
+		signing_key_access: Arc<AK>,
+	) -> Result<(Self, KeyGenerationEvent), String> {
+		let threshold = reshared_key.threshold;
+		let mut key_generation =
+			Self::create(me, signers, threshold, Some(reshared_key.clone()), signing_key_access)?;
+		if key_generation.dealers.len() < threshold as usize {
+			return Err(format!(
+				"Not enough key holders, minimum: {:?}, actual {:?}",
+				threshold,
+				key_generation.dealers.len()
+			))
+		}
+
+		let mut packages = vec![];
+		if let Some(my_index) = key_generation.dealer_index(&me) {
+			let key_share = key_share
+				.filter(|k| k.public_key() == reshared_key)
+				.ok_or("Key share does not belong to the reshared key")?;
+			let secret = key_share
+				.secret_share()
+				.and_then(|s| resharing_secret(my_index, &key_generation.dealer_indices(), &s))
+				.map_err(|e| format!("Could not create contribution: {:?}", e))?;
+			let contribution = dkg_deal(my_index, threshold, &key_generation.id, secret)
+				.map_err(|e| format!("Could not create contribution: {:?}", e))?;
+			packages = key_generation.deal(my_index, contribution)?;
+		}
+		let event = KeyGenerationEvent::ResharingStarted(
+			key_generation.id,
+			serialize_signers(&key_generation.signers),
+			reshared_key,
+			packages,
+		);
+
+		Ok((key_generation, event))
+	}
+
+	fn create(
+		me: SignerId,
+		mut signers: SignersWithKeys,
+		threshold: u16,
+		reshared_key: Option<ThresholdPublicKey>,
+		signing_key_access: Arc<AK>,
+	) -> Result<Self, String> {
+		if signers.len() < 3 {
+			return Err(format!("Not enough signers, minimum: {:?}, actual {:?}", 3, signers.len()))
+		}
+		if threshold < 2 || threshold as usize > signers.len() {
+			return Err(format!("Invalid threshold {:?} for {:?} signers", threshold, signers.len()))
+		}
+		signers.sort_by_key(|s| s.0);
+		signers.dedup_by_key(|s| s.0);
+		let id = key_generation_id(&signers, threshold, reshared_key.as_ref());
+		info!("Creating new key generation {:?}", id);
+
+		let dealers = match &reshared_key {
+			None => signers
+				.iter()
+				.enumerate()
+				.map(|(i, s)| (s.0, i as ParticipantIndex + 1))
+				.collect(),
+			Some(key) => key
+				.participants
+				.iter()
+				.filter(|p| signers.iter().any(|s| s.0 == p.0))
+				.map(|p| (p.0, p.1))
+				.collect(),
+		};
+
+		Ok(Self {
+			id,
+			me,
+			threshold,
+			signers,
+			reshared_key,
+			dealers,
+			signing_key_access,
+			commitments: BTreeMap::new(),
+			shares: BTreeMap::new(),
+		})
+	}
+
+	// keeps our own share of the contribution and encrypts the other ones for their receivers
+	fn deal(
+		&mut self,
+		dealer_index: ParticipantIndex,
+		(secret, commitment): (DkgSecret, DkgCommitment),
+	) -> Result<Vec<(SignerId, KeyGenerationPackage)>, String> {
+		let my_index = self.index_of(&self.me).ok_or("Could not determine index")?;
+		let points = verify_dkg_commitment(dealer_index, self.threshold, &self.id, &commitment)
+			.map_err(|e| format!("Could not create contribution: {:?}", e))?;
+		let my_share = verify_dkg_share(my_index, &secret.share_for(my_index), &points)
+			.map_err(|e| format!("Could not create contribution: {:?}", e))?;
+		self.commitments.insert(dealer_index, points);
+		self.shares.insert(dealer_index, my_share);
+
+		let mut packages = vec![];
+		for (index, (signer, public_key)) in self.participants() {
+			if signer == self.me {
+				continue
+			}
+			let key = self.share_encryption_key(dealer_index, index, &public_key)?;
+			packages.push((
+				signer,
+				KeyGenerationPackage {
+					commitment: commitment.clone(),
+					encrypted_share: aes_encrypt_default(&key, &secret.share_for(index)),
+				},
+			));
+		}
+		Ok(packages)
+	}
+
+	// Saves participant's contribution
+	pub fn receive_package(
+		&mut self,
+		signer: SignerId,
+		package: KeyGenerationPackage,
+	) -> Result<Vec<KeyGenerationEvent>, KeyGenerationError> {
+		info!("Saving key generation package from signer: {:?}", signer);
+		let index = self.dealer_index(&signer).ok_or(KeyGenerationError::SignerNotFound)?;
+		let my_index = self.index_of(&self.me).ok_or(KeyGenerationError::SignerNotFound)?;
+		if self.commitments.contains_key(&index) {
+			return Ok(vec![])
+		}
+		let public_key = self
+			.signers
+			.iter()
+			.find(|s| s.0 == signer)
+			.map(|s| s.1)
+			.ok_or(KeyGenerationError::SignerNotFound)?;
+
+		let points = verify_dkg_commitment(index, self.threshold, &self.id, &package.commitment)
+			.map_err(|e| {
+				error!("Invalid commitment from {:?}: {:?}", signer, e);
+				KeyGenerationError::InvalidPackage
+			})?;
+		if let Some(reshared_key) = &self.reshared_key {
+			let verification_share = reshared_key
+				.verification_share(index)
+				.ok_or(KeyGenerationError::InvalidPackage)?;
+			verify_resharing_commitment(
+				index,
+				&self.dealer_indices(),
+				&points,
+				&verification_share,
+			)
+			.map_err(|e| {
+				error!("Invalid resharing commitment from {:?}: {:?}", signer, e);
+				KeyGenerationError::InvalidPackage
+			})?;
+		}
+		let key = self
+			.share_encryption_key(index, my_index, &public_key)
+			.map_err(|_| KeyGenerationError::InvalidPackage)?;
+		let share: [u8; 32] = aes_decrypt(&key, &mut package.encrypted_share.clone())
+			.and_then(|share| share.try_into().ok())
+			.ok_or(KeyGenerationError::InvalidPackage)?;
+		let share = verify_dkg_share(my_index, &share, &points).map_err(|e| {
+			error!("Invalid share from {:?}: {:?}", signer, e);
+			KeyGenerationError::InvalidPackage
+		})?;
+		self.commitments.insert(index, points);
+		self.shares.insert(index, share);
+
+		if self.commitments.len() < self.dealers.len() {
+			return Ok(vec![])
+		}
+
+		let indices: Vec<ParticipantIndex> = self.participants().map(|(index, _)| index).collect();
+		let output = dkg_finalize(&self.commitments, &self.shares, &indices).map_err(|e| {
+			error!("Could not finalize key generation: {:?}", e);
+			KeyGenerationError::FinalizationError
+		})?;
+		let group_public_key = serialize_point(&output.group_public_key);
+		if let Some(reshared_key) = &self.reshared_key {
+			if reshared_key.group_public_key != group_public_key {
+				error!("Resharing {:?} changed the group public key", self.id);
+				return Err(KeyGenerationError::FinalizationError)
+			}
+		}
+		let participants = self
+			.participants()
+			.map(|(index, (signer, _))| {
+				output
+					.verification_shares
+					.get(&index)
+					.map(|share| (signer, index, serialize_point(share)))
+			})
+			.collect::<Option<Vec<_>>>()
+			.ok_or(KeyGenerationError::FinalizationError)?;
+		info!("Key generation {:?} has ended", self.id);
+
+		Ok(vec![KeyGenerationEvent::Ended(ThresholdKeyShare::new(
+			self.threshold,
+			my_index,
+			participants,
+			group_public_key,
+			serialize_scalar(&output.secret_share),
+		))])
+	}
+
+	pub fn get_signers_except_self(&self) -> Signers {
+		self.signers.iter().filter(|s| s.0 != self.me).map(|s| s.0).collect()
+	}
+
+	pub fn get_id_ref(&self) -> &KeyGenerationId {
+		&self.id
+	}
+
+	fn participants(&self) -> impl Iterator<Item = (ParticipantIndex, (SignerId, PublicKey))> + '_ {
+		self.signers.iter().enumerate().map(|(i, s)| (i as ParticipantIndex + 1, *s))
+	}
+
+	fn index_of(&self, signer: &SignerId) -> Option<ParticipantIndex> {
+		self.signers
+			.iter()
+			.position(|s| s.0 == *signer)
+			.map(|i| i as ParticipantIndex + 1)
+	}
+
+	fn dealer_index(&self, signer: &SignerId) -> Option<ParticipantIndex> {
+		self.dealers.iter().find(|d| d.0 == *signer).map(|d| d.1)
+	}
+
+	fn dealer_indices(&self) -> Vec<ParticipantIndex> {
+		self.dealers.iter().map(|d| d.1).collect()
+	}
+
+	// diffie-hellman between our signer key and the peer's one, bound to this key generation
+	// and to the direction of the share
+	fn share_encryption_key(
+		&self,
+		sender: ParticipantIndex,
+		receiver: ParticipantIndex,
+		peer_key: &PublicKey,
+	) -> Result<Aes256Key, String> {
+		let pair = self
+			.signing_key_access
+			.retrieve_key()
+			.map_err(|e| format!("Could not retrieve signer key: {:?}", e))?;
+		let secret = deserialize_scalar(&pair.private_bytes())
+			.map_err(|e| format!("Invalid signer key: {:?}", e))?;
+		let shared_point = peer_key.to_projective() * secret;
+		Ok(tagged_hash(
+			b"BitAcross/FROST/keygen-share",
+			&[&x_only(&shared_point), &self.id, &sender.to_be_bytes(), &receiver.to_be_bytes()],
+		))
+	}
+}
+
+#[cfg(test)]
+pub mod test {
+	use crate::{
+		key_generation::{KeyGeneration, KeyGenerationError, KeyGenerationEvent},
+		threshold::ThresholdKeyShare,
+		SignerId, SignersWithKeys,
+	};
+	use alloc::sync::Arc;
+	use itp_sgx_crypto::{key_repository::AccessKey, schnorr::Pair as SchnorrPair};
+	use k256::{elliptic_curve::PublicKey, schnorr::SigningKey};
+
+	struct MockedSigningKeyAccess {
+		signing_key: SigningKey,
+	}
+
+	impl AccessKey for MockedSigningKeyAccess {
+		type KeyType = SchnorrPair;
+
+		fn retrieve_key(&self) -> itp_sgx_crypto::Result<Self::KeyType> {
+			Ok(SchnorrPair::new(self.signing_key.clone()))
+		}
+	}
+
+	type Participant = (SignerId, KeyGeneration<MockedSigningKeyAccess>, KeyGenerationEvent);
+
+	fn signing_key(seed: u8) -> SigningKey {
+		SigningKey::from_bytes(&[seed; 32]).unwrap()
+	}
+
+	fn key_access(seed: u8) -> Arc<MockedSigningKeyAccess> {
+		Arc::new(MockedSigningKeyAccess { signing_key: signing_key(seed) })
+	}
+
+	fn signers_with_keys(seeds: &[u8]) -> SignersWithKeys {
+		seeds
+			.iter()
+			.map(|i| ([*i; 32], PublicKey::from(signing_key(*i).verifying_key())))
+			.collect()
+	}
+
+	fn start(count: u8, threshold: u16) -> Vec<Participant> {
+		let seeds: Vec<u8> = (1..=count).collect();
+		seeds
+			.iter()
+			.map(|i| {
+				let (key_generation, event) = KeyGeneration::new(
+					[*i; 32],
+					signers_with_keys(&seeds),
+					threshold,
+					key_access(*i),
+				)
+				.unwrap();
+				([*i; 32], key_generation, event)
+			})
+			.collect()
+	}
+
+	fn start_resharing(seeds: &[u8], key_shares: &[ThresholdKeyShare]) -> Vec<Participant> {
+		seeds
+			.iter()
+			.map(|i| {
+				let key_share =
+					key_shares.iter().find(|k| k.participant_index(&[*i; 32]) == Some(k.index));
+				let (key_generation, event) = KeyGeneration::reshare(
+					[*i; 32],
+					signers_with_keys(seeds),
+					key_shares[0].public_key(),
+					key_share.cloned(),
+					key_access(*i),
+				)
+				.unwrap();
+				([*i; 32], key_generation, event)
+			})
+			.collect()
+	}
+
+	// delivers every package to its receiver and collects the resulting key shares
+	fn exchange_packages(participants: &mut [Participant]) -> Vec<ThresholdKeyShare> {
+		let packages: Vec<_> = participants
+			.iter()
+			.flat_map(|(sender, _, event)| match event {
+				KeyGenerationEvent::Started(_, _, packages)
+				| KeyGenerationEvent::ResharingStarted(_, _, _, packages) =>
+					packages.iter().map(|(receiver, p)| (*sender, *receiver, p.clone())).collect(),
+				_ => vec![],
+			})
+			.collect();
+
+		let mut key_shares = vec![];
+		for (me, key_generation, _) in participants.iter_mut() {
+			for (sender, _, package) in packages.iter().filter(|(_, receiver, _)| *receiver == *me)
+			{
+				for event in key_generation.receive_package(*sender, package.clone()).unwrap() {
+					if let KeyGenerationEvent::Ended(key_share) = event {
+						key_shares.push(key_share);
+					}
+				}
+			}
+		}
+		key_shares
+	}
+
+	#[test]
+	fn it_should_generate_same_group_key_for_all_participants() {
+		// given
+		let mut participants = start(4, 3);
+
+		// when
+		let key_shares = exchange_packages(&mut participants);
+
+		// then
+		assert_eq!(key_shares.len(), 4);
+		assert!(key_shares.iter().all(|k| k.group_public_key == key_shares[0].group_public_key));
+		assert!(key_shares.iter().all(|k| k.participants == key_shares[0].participants));
+		assert!(key_shares.iter().all(|k| k.threshold == 3));
+	}
+
+	#[test]
+	fn it_should_reshare_key_to_new_signers() {
+		// given
+		let key_shares = exchange_packages(&mut start(4, 3));
+		let mut participants = start_resharing(&[1, 2, 3, 5, 6], &key_shares);
+
+		// when
+		let new_key_shares = exchange_packages(&mut participants);
+
+		// then
+		assert_eq!(new_key_shares.len(), 5);
+		assert!(new_key_shares
+			.iter()
+			.all(|k| k.group_public_key == key_shares[0].group_public_key && k.threshold == 3));
+		assert!(new_key_shares.iter().all(|k| k.participants == new_key_shares[0].participants));
+		assert!(new_key_shares[0].participant_index(&[4u8; 32]).is_none());
+		assert!(new_key_shares[0].participant_index(&[6u8; 32]).is_some());
+	}
+
+	#[test]
+	fn it_should_not_reshare_without_enough_key_holders() {
+		// given
+		let key_shares = exchange_packages(&mut start(4, 3));
+
+		// when
+		let result = KeyGeneration::reshare(
+			[1u8; 32],
+			signers_with_keys(&[1, 2, 5, 6]),
+			key_shares[0].public_key(),
+			Some(key_shares[0].clone()),
+			key_access(1),
+		);
+
+		// then
+		assert!(result.is_err())
+	}
+
+	#[test]
+	fn it_should_reject_package_addressed_to_other_participant() {
+		// given
+		let mut participants = start(3, 2);
+		let package_for_third = match &participants[0].2 {
+			KeyGenerationEvent::Started(_, _, packages) =>
+				packages.iter().find(|(receiver, _)| *receiver == [3u8; 32]).unwrap().1.clone(),
+			_ => panic!("key generation not started"),
+		};
+
+		// when
+		let result = participants[1].1.receive_package([1u8; 32], package_for_third);
+
+		// then
+		assert_eq!(result, Err(KeyGenerationError::InvalidPackage))
+	}
+
+	#[test]
+	fn it_should_reject_package_from_unknown_signer() {
+		// given
+		let mut participants = start(3, 2);
+		let package = match &participants[0].2 {
+			KeyGenerationEvent::Started(_, _, packages) => packages[0].1.clone(),
+			_ => panic!("key generation not started"),
+		};
+
+		// when
+		let result = participants[1].1.receive_package([9u8; 32], package);
+
+		// then
+		assert_eq!(result, Err(KeyGenerationError::SignerNotFound))
+	}
+
+	#[test]
+	fn it_should_not_start_with_invalid_threshold() {
+		// when
+		let result = KeyGeneration::new([1u8; 32], signers_with_keys(&[1, 2, 3]), 4, key_access(1));
+
+		// then
+		assert!(result.is_err())
+	}
+}
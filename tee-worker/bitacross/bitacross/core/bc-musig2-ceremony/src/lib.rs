@@ -52,23 +52,45 @@ use std::{
 	vec::Vec,
 };
 
+pub mod batch;
+pub mod diagnostics;
+pub mod frost;
+pub mod key_generation;
+pub mod nonce_pool;
+pub mod threshold;
+
+pub use batch::MuSig2BatchCeremony;
+pub use diagnostics::{
+	ceremony_statuses, CeremonyContributions, CeremonyFailure, CeremonyFailureHistory,
+	CeremonyFailureReason, CeremonyKind, CeremonyRound, CeremonyStatus,
+};
+pub use key_generation::{
+	deserialize_signers, key_generation_id, serialize_signers, KeyGeneration, KeyGenerationCommand,
+	KeyGenerationError, KeyGenerationEvent, KeyGenerationId, KeyGenerationPackage,
+	KeyGenerationRegistry, SerializedSigners,
+};
+pub use nonce_pool::{NoncePool, NoncePoolError, NonceSlot};
+pub use threshold::{
+	ThresholdBatchCeremony, ThresholdCeremony, ThresholdKeyShare, ThresholdPublicKey,
+};
+
 pub type CeremonyId = SignBitcoinPayload;
 pub type SignaturePayload = Vec<u8>;
 pub type Signers = Vec<SignerId>;
-pub type CeremonyRegistry<AK> = HashMap<CeremonyId, (Arc<RwLock<MuSig2Ceremony<AK>>>, u64)>;
+pub type CeremonyRegistry<AK> = HashMap<CeremonyId, (Arc<RwLock<Ceremony<AK>>>, u64)>;
 pub type CeremonyCommandTmp = HashMap<CeremonyId, (Arc<RwLock<Vec<CeremonyCommand>>>, u64)>;
 // enclave public key is used as signer identifier
 pub type SignerId = [u8; 32];
 pub type SignersWithKeys = Vec<(SignerId, PublicKey)>;
 
-#[derive(Debug, Eq, PartialEq, Encode)]
+#[derive(Clone, Debug, Eq, PartialEq, Encode, Decode)]
 pub enum CeremonyError {
 	CeremonyInitError(CeremonyErrorReason),
 	NonceReceivingError(CeremonyErrorReason),
 	PartialSignatureReceivingError(CeremonyErrorReason),
 }
 
-#[derive(Debug, Eq, PartialEq, Encode)]
+#[derive(Clone, Debug, Eq, PartialEq, Encode, Decode)]
 pub enum CeremonyErrorReason {
 	AlreadyExist,
 	CreateCeremonyError,
@@ -83,15 +105,74 @@ pub enum CeremonyCommand {
 	InitCeremony(SignersWithKeys, SignBitcoinPayload, bool),
 	SaveNonce(SignerId, PubNonce),
 	SavePartialSignature(SignerId, PartialSignature),
+	// partial signature of a threshold ceremony together with the signing set it was made for
+	SaveThresholdPartialSignature(SignerId, Signers, [u8; 32]),
+	// nonces and partial signatures of a batch ceremony, one per payload
+	SaveBatchNonces(SignerId, Vec<PubNonce>),
+	SaveBatchPartialSignatures(SignerId, Vec<PartialSignature>),
+	// partial signatures of a threshold batch ceremony, all made for the same signing set
+	SaveThresholdBatchPartialSignatures(SignerId, Signers, Vec<[u8; 32]>),
 	KillCeremony,
 }
 
+/// What to do with a command received for a ceremony.
+#[derive(Debug, PartialEq, Eq)]
+pub enum CommandDisposition {
+	Process,
+	// the command arrived before the ceremony got to its round, it's kept until then
+	Postpone,
+	Drop,
+}
+
+impl CeremonyCommand {
+	/// Disposition of the command given whether the ceremony is in its first round, or `None`
+	/// if the ceremony wasn't created yet.
+	pub fn disposition(&self, is_first_round: Option<bool>) -> CommandDisposition {
+		match (is_first_round, self) {
+			(None, CeremonyCommand::InitCeremony(_, _, _))
+			| (Some(true), CeremonyCommand::SaveNonce(_, _))
+			| (Some(false), CeremonyCommand::SavePartialSignature(_, _))
+			| (Some(_), CeremonyCommand::SaveThresholdPartialSignature(_, _, _))
+			| (Some(true), CeremonyCommand::SaveBatchNonces(_, _))
+			| (Some(false), CeremonyCommand::SaveBatchPartialSignatures(_, _))
+			| (Some(_), CeremonyCommand::SaveThresholdBatchPartialSignatures(_, _, _))
+			| (_, CeremonyCommand::KillCeremony) => CommandDisposition::Process,
+			(None, CeremonyCommand::SaveNonce(_, _))
+			| (Some(true), CeremonyCommand::SavePartialSignature(_, _))
+			| (None, CeremonyCommand::SaveThresholdPartialSignature(_, _, _))
+			| (None, CeremonyCommand::SaveThresholdBatchPartialSignatures(_, _, _))
+			| (None, CeremonyCommand::SaveBatchNonces(_, _))
+			| (None | Some(true), CeremonyCommand::SaveBatchPartialSignatures(_, _)) =>
+				CommandDisposition::Postpone,
+			_ => CommandDisposition::Drop,
+		}
+	}
+
+	/// Signer the command was received from, if it carries a contribution.
+	pub fn signer(&self) -> Option<SignerId> {
+		match self {
+			CeremonyCommand::SaveNonce(signer, _)
+			| CeremonyCommand::SavePartialSignature(signer, _)
+			| CeremonyCommand::SaveThresholdPartialSignature(signer, _, _)
+			| CeremonyCommand::SaveBatchNonces(signer, _)
+			| CeremonyCommand::SaveBatchPartialSignatures(signer, _)
+			| CeremonyCommand::SaveThresholdBatchPartialSignatures(signer, _, _) => Some(*signer),
+			CeremonyCommand::InitCeremony(_, _, _) | CeremonyCommand::KillCeremony => None,
+		}
+	}
+}
+
 // events are created by ceremony and executed by runner
 #[derive(Debug, Eq, PartialEq)]
 pub enum CeremonyEvent {
 	FirstRoundStarted(Signers, CeremonyId, PubNonce),
 	SecondRoundStarted(Signers, CeremonyId, PartialSignature),
+	ThresholdSecondRoundStarted(Signers, CeremonyId, Signers, [u8; 32]),
+	BatchFirstRoundStarted(Signers, CeremonyId, Vec<PubNonce>),
+	BatchSecondRoundStarted(Signers, CeremonyId, Vec<PartialSignature>),
+	ThresholdBatchSecondRoundStarted(Signers, CeremonyId, Signers, Vec<[u8; 32]>),
 	CeremonyEnded([u8; 64], bool, bool),
+	BatchCeremonyEnded(Vec<[u8; 64]>),
 	CeremonyError(Signers, CeremonyError),
 }
 
@@ -101,6 +182,22 @@ pub enum SignBitcoinPayload {
 	TaprootUnspendable(SignaturePayload),
 	TaprootSpendable(SignaturePayload, [u8; 32]),
 	WithTweaks(SignaturePayload, Vec<([u8; 32], bool)>),
+	// payloads signed together in one ceremony, one signature each. Payloads are signed with the
+	// pre-shared nonces of the given slots, one per payload, or with fresh nonces if none are given
+	Batch(Vec<SignBitcoinPayload>, Vec<NonceSlot>),
+}
+
+impl SignBitcoinPayload {
+	pub fn message(&self) -> &[u8] {
+		match self {
+			SignBitcoinPayload::Derived(p) => p,
+			SignBitcoinPayload::TaprootUnspendable(p) => p,
+			SignBitcoinPayload::TaprootSpendable(p, _) => p,
+			SignBitcoinPayload::WithTweaks(p, _) => p,
+			// every payload of a batch has its own message
+			SignBitcoinPayload::Batch(_, _) => &[],
+		}
+	}
 }
 
 pub fn generate_aggregated_public_key(mut public_keys: Vec<PublicKey>) -> PublicKey {
@@ -108,6 +205,77 @@ pub fn generate_aggregated_public_key(mut public_keys: Vec<PublicKey>) -> Public
 	KeyAggContext::new(public_keys).unwrap().aggregated_pubkey()
 }
 
+/// x-only output key of the vault, the key path only taproot output `TaprootUnspendable` payloads
+/// are signed for. It's derived from the threshold key once this enclave holds a share of it, as
+/// signing then happens in a `ThresholdCeremony`.
+pub fn vault_output_key(
+	signers: &SignersWithKeys,
+	key_share: Option<&ThresholdKeyShare>,
+) -> Result<[u8; 32], String> {
+	match key_share {
+		Some(key_share) => {
+			let group_public_key = frost::deserialize_point(&key_share.group_public_key)
+				.map_err(|e| format!("Invalid group public key: {:?}", e))?;
+			let payload = SignBitcoinPayload::TaprootUnspendable(Vec::new());
+			let tweaked_key = frost::TweakedGroupKey::new(group_public_key, &payload)
+				.map_err(|e| format!("Key tweaking error: {:?}", e))?;
+			Ok(frost::x_only(&tweaked_key.public_key))
+		},
+		None => {
+			let mut all_keys = signers.iter().map(|p| p.1).collect::<Vec<PublicKey>>();
+			all_keys.sort();
+			let agg_key: PublicKey = KeyAggContext::new(all_keys.iter().map(|p| Point::from(*p)))
+				.map_err(|e| format!("Key context creation error: {:?}", e))?
+				.with_unspendable_taproot_tweak()
+				.map_err(|e| format!("Key context creation error: {:?}", e))?
+				.aggregated_pubkey();
+			agg_key.to_sec1_bytes()[1..]
+				.try_into()
+				.map_err(|_| "Invalid aggregated key".into())
+		},
+	}
+}
+
+/// Key aggregation context of `signers`, tweaked as requested by `payload`. `signers` have to be
+/// sorted by their public keys.
+pub(crate) fn key_agg_context(
+	signers: &SignersWithKeys,
+	payload: &SignBitcoinPayload,
+) -> Result<KeyAggContext, String> {
+	let all_keys = signers.iter().map(|p| p.1).collect::<Vec<PublicKey>>();
+	let key_context = match payload {
+		SignBitcoinPayload::TaprootSpendable(_, root_hash) =>
+			KeyAggContext::new(all_keys.iter().map(|p| Point::from(*p)))
+				.map_err(|e| format!("Key context creation error: {:?}", e))?
+				.with_taproot_tweak(root_hash)
+				.map_err(|e| format!("Key context creation error: {:?}", e))?,
+		SignBitcoinPayload::TaprootUnspendable(_) =>
+			KeyAggContext::new(all_keys.iter().map(|p| Point::from(*p)))
+				.map_err(|e| format!("Key context creation error: {:?}", e))?
+				.with_unspendable_taproot_tweak()
+				.map_err(|e| format!("Key context creation error: {:?}", e))?,
+		SignBitcoinPayload::Derived(_) =>
+			KeyAggContext::new(all_keys.iter().map(|p| Point::from(*p)))
+				.map_err(|e| format!("Key context creation error: {:?}", e))?,
+		SignBitcoinPayload::WithTweaks(_, tweaks) => {
+			let mut prepared_tweaks = vec![];
+			for (tweak_bytes, is_x_only) in tweaks.iter() {
+				let scalar: Scalar = tweak_bytes.try_into().map_err(|e| {
+					format!("Key context creation error, could not parse scalar: {:?}", e)
+				})?;
+				prepared_tweaks.push((scalar, *is_x_only));
+			}
+			KeyAggContext::new(all_keys.iter().map(|p| Point::from(*p)))
+				.map_err(|e| format!("Key context creation error: {:?}", e))?
+				.with_tweaks(prepared_tweaks)
+				.map_err(|e| format!("Key context creation error: {:?}", e))?
+		},
+		SignBitcoinPayload::Batch(_, _) =>
+			return Err("Batch payload can't be signed as a single message".into()),
+	};
+	Ok(key_context)
+}
+
 pub struct MuSig2CeremonyData<AK: AccessKey<KeyType = SchnorrPair>> {
 	payload: SignBitcoinPayload,
 	me: SignerId,
@@ -145,35 +313,7 @@ impl<AK: AccessKey<KeyType = SchnorrPair>> MuSig2Ceremony<AK> {
 		signers.sort_by_key(|k| k.1);
 		// we are always the first key in the vector
 		let my_index = signers.iter().position(|r| r.0 == me).ok_or("Could not determine index")?;
-		let all_keys = signers.iter().map(|p| p.1).collect::<Vec<PublicKey>>();
-		let key_context = match &payload {
-			SignBitcoinPayload::TaprootSpendable(_, root_hash) =>
-				KeyAggContext::new(all_keys.iter().map(|p| Point::from(*p)))
-					.map_err(|e| format!("Key context creation error: {:?}", e))?
-					.with_taproot_tweak(root_hash)
-					.map_err(|e| format!("Key context creation error: {:?}", e))?,
-			SignBitcoinPayload::TaprootUnspendable(_) =>
-				KeyAggContext::new(all_keys.iter().map(|p| Point::from(*p)))
-					.map_err(|e| format!("Key context creation error: {:?}", e))?
-					.with_unspendable_taproot_tweak()
-					.map_err(|e| format!("Key context creation error: {:?}", e))?,
-			SignBitcoinPayload::Derived(_) =>
-				KeyAggContext::new(all_keys.iter().map(|p| Point::from(*p)))
-					.map_err(|e| format!("Key context creation error: {:?}", e))?,
-			SignBitcoinPayload::WithTweaks(_, tweaks) => {
-				let mut prepared_tweaks = vec![];
-				for (tweak_bytes, is_x_only) in tweaks.iter() {
-					let scalar: Scalar = tweak_bytes.try_into().map_err(|e| {
-						format!("Key context creation error, could not parse scalar: {:?}", e)
-					})?;
-					prepared_tweaks.push((scalar, *is_x_only));
-				}
-				KeyAggContext::new(all_keys.iter().map(|p| Point::from(*p)))
-					.map_err(|e| format!("Key context creation error: {:?}", e))?
-					.with_tweaks(prepared_tweaks)
-					.map_err(|e| format!("Key context creation error: {:?}", e))?
-			},
-		};
+		let key_context = key_agg_context(&signers, &payload)?;
 
 		info!(
 			"Ceremony aggregated public key: {:?}",
@@ -279,6 +419,10 @@ impl<AK: AccessKey<KeyType = SchnorrPair>> MuSig2Ceremony<AK> {
 			SignBitcoinPayload::Derived(message) => message.clone(),
 			SignBitcoinPayload::TaprootUnspendable(message) => message.clone(),
 			SignBitcoinPayload::WithTweaks(message, _) => message.clone(),
+			SignBitcoinPayload::Batch(_, _) =>
+				return Err(CeremonyError::NonceReceivingError(
+					CeremonyErrorReason::RoundFinalizationError,
+				)),
 		};
 		let second_round = first_round.finalize(private_key, message).map_err(|e| {
 			error!("Could not start second round: {:?}", e);
@@ -330,15 +474,12 @@ impl<AK: AccessKey<KeyType = SchnorrPair>> MuSig2Ceremony<AK> {
 					info!("Aggregated public key {:?}", self.ceremony_data.agg_key.to_sec1_bytes());
 					info!("Signature {:?}", signature.to_bytes());
 
-					let message = match &self.ceremony_data.payload {
-						SignBitcoinPayload::Derived(p) => p,
-						SignBitcoinPayload::TaprootUnspendable(p) => p,
-						SignBitcoinPayload::TaprootSpendable(p, _) => p,
-						SignBitcoinPayload::WithTweaks(p, _) => p,
-					};
-
-					let result =
-						verify_single(self.ceremony_data.agg_key, signature, message).is_ok();
+					let result = verify_single(
+						self.ceremony_data.agg_key,
+						signature,
+						self.ceremony_data.payload.message(),
+					)
+					.is_ok();
 					Ok(Some(CeremonyEvent::CeremonyEnded(
 						signature.to_bytes(),
 						self.ceremony_data.check_run,
@@ -373,6 +514,159 @@ impl<AK: AccessKey<KeyType = SchnorrPair>> MuSig2Ceremony<AK> {
 	pub fn is_first_round(&self) -> bool {
 		self.ceremony_state.first_round.is_some()
 	}
+
+	pub fn contributions(&self) -> CeremonyContributions {
+		let signers: Signers = self.ceremony_data.signers.iter().map(|s| s.0).collect();
+		let (round, nonces, partial_signatures) =
+			match (&self.ceremony_state.first_round, &self.ceremony_state.second_round) {
+				(Some(first_round), _) => (
+					CeremonyRound::Nonces,
+					diagnostics::signers_except(&signers, first_round.holdouts()),
+					Vec::new(),
+				),
+				(None, Some(second_round)) => (
+					CeremonyRound::PartialSignatures,
+					signers.clone(),
+					diagnostics::signers_except(&signers, second_round.holdouts()),
+				),
+				(None, None) => (CeremonyRound::Ended, signers.clone(), signers.clone()),
+			};
+		CeremonyContributions { round, signers, nonces, partial_signatures }
+	}
+}
+
+/// Signing ceremony, either MuSig2 with all signers or threshold signing when this enclave
+/// holds a threshold key share, batches are signed the same way.
+pub enum Ceremony<AK: AccessKey<KeyType = SchnorrPair>> {
+	MuSig2(MuSig2Ceremony<AK>),
+	Threshold(ThresholdCeremony),
+	Batch(MuSig2BatchCeremony<AK>),
+	ThresholdBatch(ThresholdBatchCeremony),
+}
+
+impl<AK: AccessKey<KeyType = SchnorrPair>> Ceremony<AK> {
+	pub fn receive_nonce(
+		&mut self,
+		signer: SignerId,
+		nonce: PubNonce,
+	) -> Result<Vec<CeremonyEvent>, CeremonyError> {
+		match self {
+			Ceremony::MuSig2(ceremony) =>
+				ceremony.receive_nonce(signer, nonce).map(|e| e.into_iter().collect()),
+			Ceremony::Threshold(ceremony) => ceremony.receive_nonce(signer, nonce),
+			Ceremony::Batch(_) | Ceremony::ThresholdBatch(_) =>
+				Err(CeremonyError::NonceReceivingError(CeremonyErrorReason::IncorrectRound)),
+		}
+	}
+
+	pub fn receive_batch_nonces(
+		&mut self,
+		signer: SignerId,
+		nonces: Vec<PubNonce>,
+	) -> Result<Vec<CeremonyEvent>, CeremonyError> {
+		match self {
+			Ceremony::Batch(ceremony) =>
+				ceremony.receive_nonces(signer, nonces).map(|e| e.into_iter().collect()),
+			Ceremony::ThresholdBatch(ceremony) => ceremony.receive_nonces(signer, nonces),
+			_ => Err(CeremonyError::NonceReceivingError(CeremonyErrorReason::IncorrectRound)),
+		}
+	}
+
+	pub fn receive_partial_sign(
+		&mut self,
+		signer: SignerId,
+		partial_signature: PartialSignature,
+	) -> Result<Vec<CeremonyEvent>, CeremonyError> {
+		match self {
+			Ceremony::MuSig2(ceremony) => ceremony
+				.receive_partial_sign(signer, partial_signature)
+				.map(|e| e.into_iter().collect()),
+			Ceremony::Threshold(_) | Ceremony::Batch(_) | Ceremony::ThresholdBatch(_) => Err(
+				CeremonyError::PartialSignatureReceivingError(CeremonyErrorReason::IncorrectRound),
+			),
+		}
+	}
+
+	pub fn receive_batch_partial_signs(
+		&mut self,
+		signer: SignerId,
+		partial_signatures: Vec<PartialSignature>,
+	) -> Result<Vec<CeremonyEvent>, CeremonyError> {
+		match self {
+			Ceremony::Batch(ceremony) => ceremony
+				.receive_partial_signatures(signer, partial_signatures)
+				.map(|e| e.into_iter().collect()),
+			_ => Err(CeremonyError::PartialSignatureReceivingError(
+				CeremonyErrorReason::IncorrectRound,
+			)),
+		}
+	}
+
+	pub fn receive_threshold_partial_sign(
+		&mut self,
+		signer: SignerId,
+		signing_set: Signers,
+		partial_signature: [u8; 32],
+	) -> Result<Vec<CeremonyEvent>, CeremonyError> {
+		match self {
+			Ceremony::Threshold(ceremony) =>
+				ceremony.receive_partial_sign(signer, signing_set, partial_signature),
+			_ => Err(CeremonyError::PartialSignatureReceivingError(
+				CeremonyErrorReason::IncorrectRound,
+			)),
+		}
+	}
+
+	pub fn receive_threshold_batch_partial_signs(
+		&mut self,
+		signer: SignerId,
+		signing_set: Signers,
+		partial_signatures: Vec<[u8; 32]>,
+	) -> Result<Vec<CeremonyEvent>, CeremonyError> {
+		match self {
+			Ceremony::ThresholdBatch(ceremony) =>
+				ceremony.receive_partial_signs(signer, signing_set, partial_signatures),
+			_ => Err(CeremonyError::PartialSignatureReceivingError(
+				CeremonyErrorReason::IncorrectRound,
+			)),
+		}
+	}
+
+	pub fn get_signers_except_self(&self) -> Signers {
+		match self {
+			Ceremony::MuSig2(ceremony) => ceremony.get_signers_except_self(),
+			Ceremony::Threshold(ceremony) => ceremony.get_signers_except_self(),
+			Ceremony::Batch(ceremony) => ceremony.get_signers_except_self(),
+			Ceremony::ThresholdBatch(ceremony) => ceremony.get_signers_except_self(),
+		}
+	}
+
+	pub fn get_id_ref(&self) -> &CeremonyId {
+		match self {
+			Ceremony::MuSig2(ceremony) => ceremony.get_id_ref(),
+			Ceremony::Threshold(ceremony) => ceremony.get_id_ref(),
+			Ceremony::Batch(ceremony) => ceremony.get_id_ref(),
+			Ceremony::ThresholdBatch(ceremony) => ceremony.get_id_ref(),
+		}
+	}
+
+	pub fn is_first_round(&self) -> bool {
+		match self {
+			Ceremony::MuSig2(ceremony) => ceremony.is_first_round(),
+			Ceremony::Threshold(ceremony) => ceremony.is_first_round(),
+			Ceremony::Batch(ceremony) => ceremony.is_first_round(),
+			Ceremony::ThresholdBatch(ceremony) => ceremony.is_first_round(),
+		}
+	}
+
+	pub fn contributions(&self) -> CeremonyContributions {
+		match self {
+			Ceremony::MuSig2(ceremony) => ceremony.contributions(),
+			Ceremony::Threshold(ceremony) => ceremony.contributions(),
+			Ceremony::Batch(ceremony) => ceremony.contributions(),
+			Ceremony::ThresholdBatch(ceremony) => ceremony.contributions(),
+		}
+	}
 }
 
 pub fn get_current_timestamp() -> u64 {
@@ -380,7 +674,7 @@ pub fn get_current_timestamp() -> u64 {
 }
 
 #[cfg(feature = "std")]
-fn random_seed() -> [u8; 32] {
+pub(crate) fn random_seed() -> [u8; 32] {
 	use rand::{thread_rng, RngCore};
 
 	let mut seed = [0u8; 32];
@@ -390,7 +684,7 @@ fn random_seed() -> [u8; 32] {
 }
 
 #[cfg(feature = "sgx")]
-fn random_seed() -> [u8; 32] {
+pub(crate) fn random_seed() -> [u8; 32] {
 	use sgx_rand::{Rng, StdRng};
 	let mut seed = [0u8; 32];
 	let mut rand = StdRng::new().unwrap();
@@ -401,8 +695,8 @@ fn random_seed() -> [u8; 32] {
 #[cfg(test)]
 pub mod test {
 	use crate::{
-		CeremonyError, CeremonyErrorReason, CeremonyEvent, MuSig2Ceremony, SignBitcoinPayload,
-		SignerId, SignersWithKeys,
+		CeremonyCommand, CeremonyError, CeremonyErrorReason, CeremonyEvent, CeremonyRound,
+		CommandDisposition, MuSig2Ceremony, SignBitcoinPayload, SignerId, SignersWithKeys,
 	};
 	use alloc::sync::Arc;
 	use itp_sgx_crypto::{key_repository::AccessKey, schnorr::Pair as SchnorrPair};
@@ -411,7 +705,7 @@ pub mod test {
 
 	pub const MY_SIGNER_ID: SignerId = [0u8; 32];
 
-	fn my_priv_key() -> SigningKey {
+	pub fn my_priv_key() -> SigningKey {
 		SigningKey::from_bytes(&[
 			252, 240, 35, 85, 243, 83, 129, 54, 7, 155, 24, 114, 254, 0, 134, 251, 207, 83, 177, 9,
 			92, 118, 222, 5, 202, 239, 188, 215, 132, 113, 127, 94,
@@ -419,7 +713,7 @@ pub mod test {
 		.unwrap()
 	}
 
-	fn signer1_priv_key() -> SigningKey {
+	pub fn signer1_priv_key() -> SigningKey {
 		SigningKey::from_bytes(&[
 			42, 82, 57, 169, 208, 130, 125, 141, 62, 185, 167, 41, 142, 217, 252, 135, 158, 128,
 			44, 129, 222, 71, 55, 86, 230, 183, 54, 111, 152, 83, 85, 155,
@@ -437,7 +731,7 @@ pub mod test {
 
 	pub const SIGNER_2_ID: SignerId = [2u8; 32];
 
-	fn signer2_priv_key() -> SigningKey {
+	pub fn signer2_priv_key() -> SigningKey {
 		SigningKey::from_bytes(&[
 			117, 130, 176, 36, 185, 53, 187, 61, 123, 86, 24, 38, 174, 143, 129, 73, 245, 210, 127,
 			148, 115, 136, 32, 98, 62, 47, 26, 196, 57, 211, 171, 185,
@@ -452,7 +746,7 @@ pub mod test {
 		255, 89, 34, 16, 10, 195, 107,
 	];
 
-	fn signers_with_keys() -> SignersWithKeys {
+	pub fn signers_with_keys() -> SignersWithKeys {
 		vec![
 			(MY_SIGNER_ID, PublicKey::from(my_priv_key().verifying_key())),
 			(SIGNER_1_ID, PublicKey::from(signer1_priv_key().verifying_key())),
@@ -462,8 +756,8 @@ pub mod test {
 
 	pub const SAMPLE_SIGNATURE_PAYLOAD: [u8; 32] = [0u8; 32];
 
-	struct MockedSigningKeyAccess {
-		signing_key: SigningKey,
+	pub struct MockedSigningKeyAccess {
+		pub signing_key: SigningKey,
 	}
 
 	impl AccessKey for MockedSigningKeyAccess {
@@ -541,6 +835,51 @@ pub mod test {
 		));
 	}
 
+	#[test]
+	fn it_should_postpone_partial_signatures_received_in_first_round() {
+		// given
+		let nonces = CeremonyCommand::SaveBatchNonces(SIGNER_1_ID, vec![]);
+		let partial_signatures = CeremonyCommand::SaveBatchPartialSignatures(SIGNER_1_ID, vec![]);
+
+		// when
+		let is_first_round = Some(true);
+
+		// then
+		assert_eq!(nonces.disposition(is_first_round), CommandDisposition::Process);
+		assert_eq!(partial_signatures.disposition(is_first_round), CommandDisposition::Postpone);
+		assert_eq!(nonces.disposition(Some(false)), CommandDisposition::Drop);
+		assert_eq!(CeremonyCommand::KillCeremony.disposition(None), CommandDisposition::Process);
+	}
+
+	#[test]
+	fn it_should_report_signers_missing_nonces() {
+		// given
+		let signing_key_access = MockedSigningKeyAccess { signing_key: my_priv_key() };
+		let mut ceremony = MuSig2Ceremony::new(
+			MY_SIGNER_ID,
+			signers_with_keys(),
+			SignBitcoinPayload::Derived(SAMPLE_SIGNATURE_PAYLOAD.to_vec()),
+			Arc::new(signing_key_access),
+			false,
+		)
+		.unwrap()
+		.0;
+
+		// when
+		ceremony
+			.receive_nonce(
+				SIGNER_1_ID,
+				SecNonce::from_bytes(&SIGNER_1_SEC_NONCE).unwrap().public_nonce(),
+			)
+			.unwrap();
+
+		// then
+		let contributions = ceremony.contributions();
+		assert_eq!(contributions.round, CeremonyRound::Nonces);
+		assert_eq!(contributions.nonces.len(), 2);
+		assert_eq!(contributions.missing(), vec![SIGNER_2_ID]);
+	}
+
 	#[test]
 	fn it_should_complete_successfully() {
 		// given
@@ -0,0 +1,382 @@
+// Copyright 2020-2024 Trust Computing GmbH.
+// This file is part of Litentry.
+//
+// Litentry is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// Litentry is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with Litentry.  If not, see <https://www.gnu.org/licenses/>.
+
+#[cfg(all(not(feature = "std"), feature = "sgx"))]
+use musig2_sgx as musig2;
+
+use crate::{random_seed, SignerId};
+use codec::{Decode, Encode};
+use log::*;
+use musig2::{PubNonce, SecNonce};
+use std::{
+	collections::{BTreeMap, BTreeSet},
+	path::PathBuf,
+	string::String,
+	vec::Vec,
+};
+
+/// Slot of a pre-shared nonce. Every signer has its own nonce for a slot, a ceremony signing
+/// with pre-shared nonces uses the nonces of all signers for the same slot.
+pub type NonceSlot = u64;
+pub type SerializedPubNonce = [u8; 66];
+
+/// File name of the sealed nonce pool.
+pub const SEALED_NONCE_POOL_FILE: &str = "nonce_pool_sealed.bin";
+
+/// Number of unused nonces an enclave keeps pre-shared with the other signers.
+pub const NONCE_POOL_SIZE: usize = 32;
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum NoncePoolError {
+	NonceNotAvailable(NonceSlot),
+	TooManyNonces,
+	InvalidNonce,
+	// a peer shared a nonce for a slot too far ahead of this enclave
+	SlotOutOfRange(NonceSlot),
+	SlotsExhausted,
+	SealingError(String),
+}
+
+/// Sealed part of the pool.
+#[derive(Encode, Decode, Default, Clone, Debug, PartialEq, Eq)]
+pub struct NoncePoolState {
+	// slot the next nonce of this enclave is generated for
+	pub next_slot: NonceSlot,
+	// nonces the other signers pre-shared with this enclave
+	pub peer_nonces: BTreeMap<SignerId, BTreeMap<NonceSlot, SerializedPubNonce>>,
+}
+
+/// Nonces pre-shared between the signers, so a ceremony using them can skip the first round.
+///
+/// A nonce must never sign two messages. This is guaranteed as:
+/// * a secret nonce is generated for a slot only once, `next_slot` only grows
+/// * secret nonces are kept in enclave memory only, they're never sealed as a stale copy of the
+///   sealed pool could bring back nonces which were used already. A restarted enclave starts with
+///   no secret nonces
+/// * a secret nonce is removed from the pool before a partial signature is made with it, see
+///   [`NoncePool::take_nonces`]
+pub struct NoncePool {
+	state: NoncePoolState,
+	secret_nonces: BTreeMap<NonceSlot, SecNonce>,
+	seal_path: PathBuf,
+}
+
+impl NoncePool {
+	pub fn new(base_dir: PathBuf) -> Self {
+		Self { state: Default::default(), secret_nonces: Default::default(), seal_path: base_dir }
+	}
+
+	#[cfg(feature = "std")]
+	pub fn init(&mut self) -> Result<(), NoncePoolError> {
+		Ok(())
+	}
+
+	#[cfg(feature = "sgx")]
+	pub fn init(&mut self) -> Result<(), NoncePoolError> {
+		use itp_sgx_io::SealedIO;
+		self.state = sgx::NoncePoolSeal::new(self.seal_path.clone())
+			.unseal()
+			.map_err(|e| NoncePoolError::SealingError(std::format!("{:?}", e)))?;
+		info!("Nonce pool unsealed, next slot: {}", self.state.next_slot);
+		Ok(())
+	}
+
+	#[cfg(feature = "std")]
+	fn seal(&self) -> Result<(), NoncePoolError> {
+		Ok(())
+	}
+
+	#[cfg(feature = "sgx")]
+	fn seal(&self) -> Result<(), NoncePoolError> {
+		use itp_sgx_io::SealedIO;
+		sgx::NoncePoolSeal::new(self.seal_path.clone())
+			.seal(&self.state)
+			.map_err(|e| NoncePoolError::SealingError(std::format!("{:?}", e)))
+	}
+
+	/// Generates nonces until the pool holds [`NONCE_POOL_SIZE`] unused ones, returns whether any
+	/// were generated. The new slots are sealed before nonces are generated for them, if sealing
+	/// fails the pool is left as it was.
+	pub fn refill(&mut self) -> Result<bool, NoncePoolError> {
+		let missing = NONCE_POOL_SIZE.saturating_sub(self.secret_nonces.len());
+		if missing == 0 {
+			return Ok(false)
+		}
+		let first_slot = self.state.next_slot;
+		let next_slot = first_slot
+			.checked_add(missing as NonceSlot)
+			.ok_or(NoncePoolError::SlotsExhausted)?;
+		self.state.next_slot = next_slot;
+		if let Err(e) = self.seal() {
+			self.state.next_slot = first_slot;
+			return Err(e)
+		}
+		for slot in first_slot..next_slot {
+			self.secret_nonces.insert(slot, SecNonce::build(random_seed()).build());
+		}
+		debug!("Nonce pool refilled up to slot {}", next_slot);
+		Ok(true)
+	}
+
+	/// Public nonces of this enclave which weren't used yet, they are shared with the other
+	/// signers.
+	pub fn public_nonces(&self) -> Vec<(NonceSlot, SerializedPubNonce)> {
+		self.secret_nonces
+			.iter()
+			.map(|(slot, nonce)| (*slot, nonce.public_nonce().serialize()))
+			.collect()
+	}
+
+	/// Saves the unused nonces shared by `signer`, they replace the ones it shared before.
+	///
+	/// Slots below the lowest one `signer` shared can't be signed for anymore, so this enclave
+	/// drops its own nonces for them and continues with the slots after them. Signers only use
+	/// up slots together, so nonces more than twice the pool size ahead of this enclave are
+	/// rejected rather than letting a peer skip its slots arbitrarily far.
+	pub fn receive_peer_nonces(
+		&mut self,
+		signer: SignerId,
+		nonces: Vec<(NonceSlot, SerializedPubNonce)>,
+	) -> Result<(), NoncePoolError> {
+		if nonces.len() > NONCE_POOL_SIZE {
+			return Err(NoncePoolError::TooManyNonces)
+		}
+		if nonces.iter().any(|(_, nonce)| PubNonce::from_bytes(nonce).is_err()) {
+			return Err(NoncePoolError::InvalidNonce)
+		}
+		let max_slot = self.state.next_slot.saturating_add(2 * NONCE_POOL_SIZE as NonceSlot);
+		if let Some((slot, _)) = nonces.iter().find(|(slot, _)| *slot > max_slot) {
+			return Err(NoncePoolError::SlotOutOfRange(*slot))
+		}
+		let nonces: BTreeMap<NonceSlot, SerializedPubNonce> = nonces.into_iter().collect();
+		if let Some(lowest_slot) = nonces.keys().next() {
+			self.secret_nonces.retain(|slot, _| slot >= lowest_slot);
+			self.state.next_slot = self.state.next_slot.max(*lowest_slot);
+		}
+		self.state.peer_nonces.insert(signer, nonces);
+		self.seal()
+	}
+
+	/// Pre-shared nonce of `signer` for `slot`.
+	pub fn peer_nonce(&self, signer: &SignerId, slot: NonceSlot) -> Option<PubNonce> {
+		self.state
+			.peer_nonces
+			.get(signer)
+			.and_then(|nonces| nonces.get(&slot))
+			.and_then(|nonce| PubNonce::from_bytes(nonce).ok())
+	}
+
+	/// Slots this enclave and all of `peers` have an unused nonce for.
+	pub fn ready_slots(&self, peers: &[SignerId]) -> Vec<NonceSlot> {
+		self.secret_nonces
+			.keys()
+			.filter(|slot| peers.iter().all(|peer| self.peer_nonce(peer, **slot).is_some()))
+			.cloned()
+			.collect()
+	}
+
+	/// Takes the secret nonces of `slots` out of the pool, together with the nonces the other
+	/// signers shared for them. The caller is the only one which ever gets to sign with them.
+	pub fn take_nonces(&mut self, slots: &[NonceSlot]) -> Result<Vec<SecNonce>, NoncePoolError> {
+		let mut unique_slots = BTreeSet::new();
+		for slot in slots {
+			if !unique_slots.insert(*slot) || !self.secret_nonces.contains_key(slot) {
+				return Err(NoncePoolError::NonceNotAvailable(*slot))
+			}
+		}
+		let nonces = slots
+			.iter()
+			.filter_map(|slot| self.secret_nonces.remove(slot))
+			.collect::<Vec<_>>();
+		self.state.peer_nonces.values_mut().for_each(|peer_nonces| {
+			slots.iter().for_each(|slot| {
+				peer_nonces.remove(slot);
+			})
+		});
+		self.seal()?;
+		Ok(nonces)
+	}
+}
+
+#[cfg(feature = "sgx")]
+pub mod sgx {
+	use super::{NoncePoolState, SEALED_NONCE_POOL_FILE};
+	use codec::{Decode, Encode};
+	use itp_sgx_crypto::error::{Error, Result};
+	use itp_sgx_io::{seal, unseal, SealedIO};
+	use log::*;
+	use std::path::PathBuf;
+
+	#[derive(Clone, Debug)]
+	pub struct NoncePoolSeal {
+		base_path: PathBuf,
+	}
+
+	impl NoncePoolSeal {
+		pub fn new(base_path: PathBuf) -> Self {
+			Self { base_path }
+		}
+
+		pub fn path(&self) -> PathBuf {
+			self.base_path.join(SEALED_NONCE_POOL_FILE)
+		}
+	}
+
+	impl SealedIO for NoncePoolSeal {
+		type Error = Error;
+		type Unsealed = NoncePoolState;
+
+		fn unseal(&self) -> Result<Self::Unsealed> {
+			if !self.path().exists() {
+				info!("Nonce pool not found: {}", self.path().display());
+				return Ok(Default::default())
+			}
+			let raw = unseal(self.path())?;
+			Ok(Decode::decode(&mut raw.as_slice())?)
+		}
+
+		fn seal(&self, unsealed: &Self::Unsealed) -> Result<()> {
+			debug!("Seal nonce pool to file: {}", self.path().display());
+			Ok(unsealed.using_encoded(|bytes| seal(bytes, self.path()))?)
+		}
+	}
+}
+
+#[cfg(test)]
+pub mod test {
+	use crate::{
+		nonce_pool::{NoncePool, NoncePoolError, NONCE_POOL_SIZE},
+		SignerId,
+	};
+	use std::path::PathBuf;
+
+	const PEER_1: SignerId = [1u8; 32];
+	const PEER_2: SignerId = [2u8; 32];
+
+	// pool of a peer sharing its nonces for the same slots
+	fn share_nonces(from: &NoncePool, to: &mut NoncePool, signer: SignerId) {
+		to.receive_peer_nonces(signer, from.public_nonces()).unwrap();
+	}
+
+	#[test]
+	fn it_should_refill_pool() {
+		// given
+		let mut pool = NoncePool::new(PathBuf::new());
+
+		// when
+		let refilled = pool.refill().unwrap();
+
+		// then
+		assert!(refilled);
+		assert_eq!(pool.public_nonces().len(), NONCE_POOL_SIZE);
+		assert!(!pool.refill().unwrap());
+	}
+
+	#[test]
+	fn it_should_report_slots_shared_by_all_peers() {
+		// given
+		let mut pool = NoncePool::new(PathBuf::new());
+		let mut peer_1 = NoncePool::new(PathBuf::new());
+		let mut peer_2 = NoncePool::new(PathBuf::new());
+		pool.refill().unwrap();
+		peer_1.refill().unwrap();
+		peer_2.refill().unwrap();
+
+		// when
+		share_nonces(&peer_1, &mut pool, PEER_1);
+
+		// then
+		assert!(pool.ready_slots(&[PEER_1, PEER_2]).is_empty());
+		share_nonces(&peer_2, &mut pool, PEER_2);
+		assert_eq!(pool.ready_slots(&[PEER_1, PEER_2]).len(), NONCE_POOL_SIZE);
+	}
+
+	#[test]
+	fn it_should_never_hand_out_nonce_twice() {
+		// given
+		let mut pool = NoncePool::new(PathBuf::new());
+		let mut peer = NoncePool::new(PathBuf::new());
+		pool.refill().unwrap();
+		peer.refill().unwrap();
+		share_nonces(&peer, &mut pool, PEER_1);
+
+		// when
+		let nonces = pool.take_nonces(&[3, 4]).unwrap();
+
+		// then
+		assert_eq!(nonces.len(), 2);
+		assert_eq!(pool.take_nonces(&[4]).unwrap_err(), NoncePoolError::NonceNotAvailable(4));
+		assert!(pool.peer_nonce(&PEER_1, 3).is_none());
+		// sharing the nonces again doesn't bring back the secret ones
+		share_nonces(&peer, &mut pool, PEER_1);
+		assert!(!pool.ready_slots(&[PEER_1]).contains(&3));
+		// refilling continues with new slots
+		pool.refill().unwrap();
+		assert_eq!(pool.take_nonces(&[3]).unwrap_err(), NoncePoolError::NonceNotAvailable(3));
+	}
+
+	#[test]
+	fn it_should_not_take_same_slot_twice_at_once() {
+		// given
+		let mut pool = NoncePool::new(PathBuf::new());
+		pool.refill().unwrap();
+
+		// when
+		let result = pool.take_nonces(&[5, 5]);
+
+		// then
+		assert_eq!(result.unwrap_err(), NoncePoolError::NonceNotAvailable(5));
+		// nothing was taken
+		assert_eq!(pool.take_nonces(&[5]).unwrap().len(), 1);
+	}
+
+	#[test]
+	fn it_should_catch_up_with_peer_slots() {
+		// given
+		let mut pool = NoncePool::new(PathBuf::new());
+		let mut peer = NoncePool::new(PathBuf::new());
+		pool.refill().unwrap();
+		peer.refill().unwrap();
+		peer.take_nonces(&(0..10).collect::<Vec<_>>()).unwrap();
+		peer.refill().unwrap();
+
+		// when
+		share_nonces(&peer, &mut pool, PEER_1);
+		pool.refill().unwrap();
+
+		// then
+		let ready_slots = pool.ready_slots(&[PEER_1]);
+		assert_eq!(ready_slots, (10..(10 + NONCE_POOL_SIZE as u64)).collect::<Vec<_>>());
+	}
+	#[test]
+	fn it_should_reject_nonces_far_ahead() {
+		// given
+		let mut pool = NoncePool::new(PathBuf::new());
+		let mut peer = NoncePool::new(PathBuf::new());
+		pool.refill().unwrap();
+		peer.refill().unwrap();
+		let far_slot = 4 * NONCE_POOL_SIZE as u64;
+		let nonces = vec![(far_slot, peer.public_nonces()[0].1)];
+
+		// when
+		let result = pool.receive_peer_nonces(PEER_1, nonces);
+
+		// then
+		assert_eq!(result, Err(NoncePoolError::SlotOutOfRange(far_slot)));
+		assert_eq!(pool.public_nonces().len(), NONCE_POOL_SIZE);
+		assert!(pool.peer_nonce(&PEER_1, far_slot).is_none());
+	}
+}
@@ -0,0 +1,914 @@
+// Copyright 2020-2024 Trust Computing GmbH.
+// This file is part of Litentry.
+//
+// Litentry is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// Litentry is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with Litentry.  If not, see <https://www.gnu.org/licenses/>.
+
+#[cfg(all(not(feature = "std"), feature = "sgx"))]
+use musig2_sgx as musig2;
+
+use crate::{
+	frost::{
+		deserialize_point, deserialize_scalar, serialize_scalar, FrostError, ParticipantIndex,
+		SerializedPoint, SerializedScalar, SigningCommitments, SigningContext, SigningNonces,
+		TweakedGroupKey,
+	},
+	CeremonyContributions, CeremonyError, CeremonyErrorReason, CeremonyEvent, CeremonyId,
+	CeremonyRound, SignBitcoinPayload, SignerId, Signers,
+};
+use codec::{Decode, Encode};
+use core::fmt;
+use k256::{ProjectivePoint, PublicKey, Scalar};
+use log::*;
+use musig2::{verify_single, BinaryEncoding, CompactSignature, PubNonce};
+use std::{collections::BTreeMap, format, string::String, vec, vec::Vec};
+
+/// File name of the sealed threshold key share.
+pub const SEALED_THRESHOLD_KEY_SHARE_FILE: &str = "threshold_key_share_sealed.bin";
+
+/// Share of the threshold signing key held by this enclave, produced by the key generation.
+#[derive(Encode, Decode, Clone, PartialEq, Eq)]
+pub struct ThresholdKeyShare {
+	pub threshold: u16,
+	pub index: ParticipantIndex,
+	// every participant with its verification share (public key of its secret share)
+	pub participants: Vec<(SignerId, ParticipantIndex, SerializedPoint)>,
+	pub group_public_key: SerializedPoint,
+	secret_share: SerializedScalar,
+}
+
+/// Public part of a threshold key, handed over to the participants of a resharing so they can
+/// check the contributions of the current holders.
+#[derive(Encode, Decode, Clone, Debug, PartialEq, Eq)]
+pub struct ThresholdPublicKey {
+	pub threshold: u16,
+	pub participants: Vec<(SignerId, ParticipantIndex, SerializedPoint)>,
+	pub group_public_key: SerializedPoint,
+}
+
+impl ThresholdPublicKey {
+	pub fn participant_index(&self, signer: &SignerId) -> Option<ParticipantIndex> {
+		self.participants.iter().find(|p| p.0 == *signer).map(|p| p.1)
+	}
+
+	pub fn verification_share(&self, index: ParticipantIndex) -> Option<ProjectivePoint> {
+		self.participants
+			.iter()
+			.find(|p| p.1 == index)
+			.and_then(|p| deserialize_point(&p.2).ok())
+	}
+}
+
+impl fmt::Debug for ThresholdKeyShare {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		f.debug_struct("ThresholdKeyShare")
+			.field("threshold", &self.threshold)
+			.field("index", &self.index)
+			.field("participants", &self.participants)
+			.field("group_public_key", &self.group_public_key)
+			.finish_non_exhaustive()
+	}
+}
+
+impl ThresholdKeyShare {
+	pub fn new(
+		threshold: u16,
+		index: ParticipantIndex,
+		participants: Vec<(SignerId, ParticipantIndex, SerializedPoint)>,
+		group_public_key: SerializedPoint,
+		secret_share: SerializedScalar,
+	) -> Self {
+		Self { threshold, index, participants, group_public_key, secret_share }
+	}
+
+	pub fn group_public_key(&self) -> Result<PublicKey, String> {
+		PublicKey::from_sec1_bytes(&self.group_public_key)
+			.map_err(|e| format!("Invalid group public key: {:?}", e))
+	}
+
+	pub fn participant_index(&self, signer: &SignerId) -> Option<ParticipantIndex> {
+		self.participants.iter().find(|p| p.0 == *signer).map(|p| p.1)
+	}
+
+	pub fn signers(&self) -> Signers {
+		self.participants.iter().map(|p| p.0).collect()
+	}
+
+	pub fn public_key(&self) -> ThresholdPublicKey {
+		ThresholdPublicKey {
+			threshold: self.threshold,
+			participants: self.participants.clone(),
+			group_public_key: self.group_public_key,
+		}
+	}
+
+	fn signer_id(&self, index: ParticipantIndex) -> Option<SignerId> {
+		self.participants.iter().find(|p| p.1 == index).map(|p| p.0)
+	}
+
+	fn verification_share(&self, index: ParticipantIndex) -> Option<ProjectivePoint> {
+		self.participants
+			.iter()
+			.find(|p| p.1 == index)
+			.and_then(|p| deserialize_point(&p.2).ok())
+	}
+
+	pub(crate) fn secret_share(&self) -> Result<Scalar, FrostError> {
+		deserialize_scalar(&self.secret_share)
+	}
+}
+
+#[cfg(feature = "sgx")]
+pub mod sgx {
+	use super::{ThresholdKeyShare, SEALED_THRESHOLD_KEY_SHARE_FILE};
+	use codec::{Decode, Encode};
+	use itp_sgx_crypto::{
+		error::{Error, Result},
+		key_repository::KeyRepository,
+	};
+	use itp_sgx_io::{seal, unseal, SealedIO};
+	use log::*;
+	use std::path::PathBuf;
+
+	pub type ThresholdKeyShareRepository =
+		KeyRepository<Option<ThresholdKeyShare>, ThresholdKeyShareSeal>;
+
+	/// Creates a repository for the threshold key share, it stays empty until
+	/// this enclave took part in a key generation.
+	pub fn create_threshold_key_share_repository(
+		path: PathBuf,
+	) -> Result<ThresholdKeyShareRepository> {
+		let seal = ThresholdKeyShareSeal::new(path);
+		Ok(KeyRepository::new(seal.unseal()?, seal.into()))
+	}
+
+	#[derive(Clone, Debug)]
+	pub struct ThresholdKeyShareSeal {
+		base_path: PathBuf,
+	}
+
+	impl ThresholdKeyShareSeal {
+		pub fn new(base_path: PathBuf) -> Self {
+			Self { base_path }
+		}
+
+		pub fn path(&self) -> PathBuf {
+			self.base_path.join(SEALED_THRESHOLD_KEY_SHARE_FILE)
+		}
+	}
+
+	impl SealedIO for ThresholdKeyShareSeal {
+		type Error = Error;
+		type Unsealed = Option<ThresholdKeyShare>;
+
+		fn unseal(&self) -> Result<Self::Unsealed> {
+			if !self.path().exists() {
+				info!("Threshold key share not found: {}", self.path().display());
+				return Ok(None)
+			}
+			let raw = unseal(self.path())?;
+			Ok(Decode::decode(&mut raw.as_slice())?)
+		}
+
+		fn seal(&self, unsealed: &Self::Unsealed) -> Result<()> {
+			info!("Seal threshold key share to file: {}", self.path().display());
+			Ok(unsealed.using_encoded(|bytes| seal(bytes, self.path()))?)
+		}
+	}
+}
+
+/// Signing ceremony for a threshold key, it completes as soon as `threshold` signers contributed,
+/// so signers which are offline don't stall it.
+///
+/// Every signer broadcasts its nonce commitments. The lowest indexed signer which collected
+/// `threshold` commitments proposes the signing set by broadcasting its partial signature for it,
+/// the other members of the set sign for the first proposal they receive. Nonces are never used
+/// twice, so if two signers happen to propose different sets at the same time, the ceremony may
+/// not complete and times out like any other ceremony.
+pub struct ThresholdCeremony {
+	payload: SignBitcoinPayload,
+	me: SignerId,
+	key_share: ThresholdKeyShare,
+	group_public_key: ProjectivePoint,
+	tweaked_key: TweakedGroupKey,
+	agg_key: PublicKey,
+	// taken when our partial signature is produced
+	nonces: Option<SigningNonces>,
+	commitments: BTreeMap<ParticipantIndex, SigningCommitments>,
+	signing_set: Option<Vec<ParticipantIndex>>,
+	partial_signatures: BTreeMap<ParticipantIndex, (Vec<ParticipantIndex>, Scalar)>,
+	// indicates whether it's check run - signature verification result is returned instead of signature
+	check_run: bool,
+	ended: bool,
+}
+
+impl ThresholdCeremony {
+	// Creates new ceremony
+	pub fn new(
+		me: SignerId,
+		key_share: ThresholdKeyShare,
+		payload: SignBitcoinPayload,
+		check_run: bool,
+	) -> Result<(Self, CeremonyEvent), String> {
+		info!("Creating new threshold ceremony {:?}", payload);
+		let my_index = key_share.index;
+		if key_share.participant_index(&me) != Some(my_index) {
+			return Err("Key share does not belong to this signer".into())
+		}
+		let group_public_key = deserialize_point(&key_share.group_public_key)
+			.map_err(|e| format!("Invalid group public key: {:?}", e))?;
+		let tweaked_key = TweakedGroupKey::new(group_public_key, &payload)
+			.map_err(|e| format!("Key tweaking error: {:?}", e))?;
+		let agg_key = PublicKey::from_affine(tweaked_key.public_key.to_affine())
+			.map_err(|e| format!("Key tweaking error: {:?}", e))?;
+		info!("Ceremony aggregated public key: {:?}", agg_key.to_sec1_bytes().to_vec());
+
+		let nonces = SigningNonces::generate();
+		let commitments = nonces.commitments();
+		let public_nonce = PubNonce::from_bytes(&commitments.serialize())
+			.map_err(|e| format!("Nonce commitment error: {:?}", e))?;
+
+		let ceremony = Self {
+			payload,
+			me,
+			key_share,
+			group_public_key,
+			tweaked_key,
+			agg_key,
+			nonces: Some(nonces),
+			commitments: vec![(my_index, commitments)].into_iter().collect(),
+			signing_set: None,
+			partial_signatures: BTreeMap::new(),
+			check_run,
+			ended: false,
+		};
+		let event = CeremonyEvent::FirstRoundStarted(
+			ceremony.get_signers_except_self(),
+			ceremony.payload.clone(),
+			public_nonce,
+		);
+		Ok((ceremony, event))
+	}
+
+	// Saves signer's nonce commitments
+	pub fn receive_nonce(
+		&mut self,
+		signer: SignerId,
+		nonce: PubNonce,
+	) -> Result<Vec<CeremonyEvent>, CeremonyError> {
+		info!("Saving nonce commitments from signer: {:?}", signer);
+		let index = self
+			.key_share
+			.participant_index(&signer)
+			.ok_or(CeremonyError::NonceReceivingError(CeremonyErrorReason::SignerNotFound))?;
+		let commitments = SigningCommitments::deserialize(&nonce.serialize()).map_err(|e| {
+			error!("Nonce receiving error: {:?}", e);
+			CeremonyError::NonceReceivingError(CeremonyErrorReason::ContributionError)
+		})?;
+
+		match self.commitments.get(&index) {
+			Some(saved) if *saved != commitments =>
+				return Err(CeremonyError::NonceReceivingError(
+					CeremonyErrorReason::ContributionError,
+				)),
+			Some(_) => return Ok(vec![]),
+			None => {
+				self.commitments.insert(index, commitments);
+			},
+		}
+
+		self.progress().map_err(CeremonyError::NonceReceivingError)
+	}
+
+	// Saves signer's partial signature made for `signing_set`
+	pub fn receive_partial_sign(
+		&mut self,
+		signer: SignerId,
+		signing_set: Signers,
+		partial_signature: [u8; 32],
+	) -> Result<Vec<CeremonyEvent>, CeremonyError> {
+		info!("Saving partial signature from signer: {:?}", signer);
+		let index = self.key_share.participant_index(&signer).ok_or(
+			CeremonyError::PartialSignatureReceivingError(CeremonyErrorReason::SignerNotFound),
+		)?;
+		let mut signing_set = signing_set
+			.iter()
+			.map(|s| self.key_share.participant_index(s))
+			.collect::<Option<Vec<ParticipantIndex>>>()
+			.ok_or(CeremonyError::PartialSignatureReceivingError(
+				CeremonyErrorReason::SignerNotFound,
+			))?;
+		signing_set.sort();
+		signing_set.dedup();
+		if signing_set.len() != self.key_share.threshold as usize || !signing_set.contains(&index) {
+			return Err(CeremonyError::PartialSignatureReceivingError(
+				CeremonyErrorReason::ContributionError,
+			))
+		}
+		let partial_signature = deserialize_scalar(&partial_signature).map_err(|e| {
+			error!("Signature receiving error: {:?}", e);
+			CeremonyError::PartialSignatureReceivingError(CeremonyErrorReason::ContributionError)
+		})?;
+
+		match self.partial_signatures.get(&index) {
+			Some(saved) if *saved != (signing_set.clone(), partial_signature) =>
+				return Err(CeremonyError::PartialSignatureReceivingError(
+					CeremonyErrorReason::ContributionError,
+				)),
+			Some(_) => return Ok(vec![]),
+			None => {},
+		}
+
+		// join the first proposal we are part of, unless we already signed
+		if self.nonces.is_some()
+			&& self.signing_set.is_none()
+			&& signing_set.contains(&self.key_share.index)
+		{
+			self.signing_set = Some(signing_set.clone());
+		}
+		self.partial_signatures.insert(index, (signing_set, partial_signature));
+
+		self.progress().map_err(CeremonyError::PartialSignatureReceivingError)
+	}
+
+	fn progress(&mut self) -> Result<Vec<CeremonyEvent>, CeremonyErrorReason> {
+		let mut events = vec![];
+		if self.ended {
+			return Ok(events)
+		}
+
+		if self.nonces.is_some() {
+			if self.signing_set.is_none() {
+				self.signing_set = self.propose_signing_set();
+			}
+			if let Some(signing_set) = self.signing_set.clone() {
+				if let Some(context) = self.signing_context(&signing_set)? {
+					events.push(self.sign(context, signing_set)?);
+				}
+			}
+		}
+
+		if let Some(event) = self.aggregate()? {
+			self.ended = true;
+			events.push(event);
+		}
+		Ok(events)
+	}
+
+	// only the lowest indexed signer we know of proposes, everybody else waits for its proposal
+	fn propose_signing_set(&self) -> Option<Vec<ParticipantIndex>> {
+		let threshold = self.key_share.threshold as usize;
+		if self.commitments.len() < threshold
+			|| self.commitments.keys().next() != Some(&self.key_share.index)
+		{
+			return None
+		}
+		Some(self.commitments.keys().take(threshold).copied().collect())
+	}
+
+	// returns `None` while commitments of some members of the signing set are missing
+	fn signing_context(
+		&self,
+		signing_set: &[ParticipantIndex],
+	) -> Result<Option<SigningContext>, CeremonyErrorReason> {
+		let commitments = signing_set
+			.iter()
+			.map(|index| self.commitments.get(index).map(|c| (*index, *c)))
+			.collect::<Option<BTreeMap<ParticipantIndex, SigningCommitments>>>();
+		match commitments {
+			Some(commitments) => SigningContext::new(
+				&self.group_public_key,
+				&self.tweaked_key,
+				self.payload.message(),
+				commitments,
+			)
+			.map(Some)
+			.map_err(|e| {
+				error!("Could not create signing context: {:?}", e);
+				CeremonyErrorReason::RoundFinalizationError
+			}),
+			None => Ok(None),
+		}
+	}
+
+	fn sign(
+		&mut self,
+		context: SigningContext,
+		signing_set: Vec<ParticipantIndex>,
+	) -> Result<CeremonyEvent, CeremonyErrorReason> {
+		let nonces = self.nonces.take().ok_or(CeremonyErrorReason::IncorrectRound)?;
+		let partial_signature = self
+			.key_share
+			.secret_share()
+			.and_then(|secret_share| context.sign(self.key_share.index, &secret_share, nonces))
+			.map_err(|e| {
+				error!("Could not produce partial signature: {:?}", e);
+				CeremonyErrorReason::RoundFinalizationError
+			})?;
+		let signers = signing_set.iter().filter_map(|i| self.key_share.signer_id(*i)).collect();
+		self.partial_signatures
+			.insert(self.key_share.index, (signing_set, partial_signature));
+
+		Ok(CeremonyEvent::ThresholdSecondRoundStarted(
+			self.get_signers_except_self(),
+			self.get_id_ref().clone(),
+			signers,
+			serialize_scalar(&partial_signature),
+		))
+	}
+
+	// aggregates the first signing set for which all partial signatures were received
+	fn aggregate(&self) -> Result<Option<CeremonyEvent>, CeremonyErrorReason> {
+		let mut signing_sets: Vec<&Vec<ParticipantIndex>> =
+			self.partial_signatures.values().map(|(signing_set, _)| signing_set).collect();
+		signing_sets.sort();
+		signing_sets.dedup();
+
+		for signing_set in signing_sets {
+			let partial_signatures: BTreeMap<ParticipantIndex, Scalar> = self
+				.partial_signatures
+				.iter()
+				.filter(|(_, (s, _))| s == signing_set)
+				.map(|(index, (_, partial_signature))| (*index, *partial_signature))
+				.collect();
+			if partial_signatures.len() < signing_set.len() {
+				continue
+			}
+			let context = match self.signing_context(signing_set)? {
+				Some(context) => context,
+				None => continue,
+			};
+			for (index, partial_signature) in partial_signatures.iter() {
+				let verification_share = self
+					.key_share
+					.verification_share(*index)
+					.ok_or(CeremonyErrorReason::SignerNotFound)?;
+				context.verify_partial(*index, partial_signature, &verification_share).map_err(
+					|e| {
+						error!("Invalid partial signature from {:?}: {:?}", index, e);
+						CeremonyErrorReason::ContributionError
+					},
+				)?;
+			}
+			let signature = context.aggregate(&partial_signatures).map_err(|e| {
+				error!("Could not aggregate signature: {:?}", e);
+				CeremonyErrorReason::RoundFinalizationError
+			})?;
+
+			info!("Ceremony {:?} `has ended`", self.get_id_ref());
+			info!("Aggregated public key {:?}", self.agg_key.to_sec1_bytes());
+			info!("Signature {:?}", signature);
+
+			let result = CompactSignature::from_bytes(&signature)
+				.map(|s| verify_single(self.agg_key, s, self.payload.message()).is_ok())
+				.unwrap_or(false);
+			return Ok(Some(CeremonyEvent::CeremonyEnded(signature, self.check_run, result)))
+		}
+		Ok(None)
+	}
+
+	pub fn get_signers_except_self(&self) -> Signers {
+		self.key_share.signers().into_iter().filter(|s| *s != self.me).collect()
+	}
+
+	pub fn get_id_ref(&self) -> &CeremonyId {
+		&self.payload
+	}
+
+	// commitments are accepted until the ceremony ends, as the signing set is not known upfront
+	pub fn is_first_round(&self) -> bool {
+		!self.ended
+	}
+
+	pub fn contributions(&self) -> CeremonyContributions {
+		let round = if self.ended {
+			CeremonyRound::Ended
+		} else if self.nonces.is_some() {
+			CeremonyRound::Nonces
+		} else {
+			CeremonyRound::PartialSignatures
+		};
+		CeremonyContributions {
+			round,
+			signers: self.key_share.signers(),
+			nonces: self.commitments.keys().filter_map(|i| self.key_share.signer_id(*i)).collect(),
+			partial_signatures: self
+				.partial_signatures
+				.keys()
+				.filter_map(|i| self.key_share.signer_id(*i))
+				.collect(),
+		}
+	}
+}
+
+/// Threshold ceremony signing every payload of a `SignBitcoinPayload::Batch`. It runs a
+/// `ThresholdCeremony` per payload and hands each of them the contributions of a signer at once,
+/// so they all settle on the same signing set. Pre-shared nonces are MuSig2 nonces, so batches
+/// signed with the threshold key always start with the first round.
+pub struct ThresholdBatchCeremony {
+	id: CeremonyId,
+	// one per payload, never empty
+	ceremonies: Vec<ThresholdCeremony>,
+}
+
+impl ThresholdBatchCeremony {
+	// Creates new ceremony
+	pub fn new(
+		me: SignerId,
+		key_share: ThresholdKeyShare,
+		id: CeremonyId,
+	) -> Result<(Self, CeremonyEvent), String> {
+		info!("Creating new threshold batch ceremony {:?}", id);
+		let payloads = match &id {
+			SignBitcoinPayload::Batch(payloads, slots) if slots.is_empty() => payloads.clone(),
+			SignBitcoinPayload::Batch(_, _) =>
+				return Err("Batches of a threshold key can't use pre-shared nonces".into()),
+			_ => return Err("Not a batch payload".into()),
+		};
+		if payloads.is_empty() {
+			return Err("Empty batch".into())
+		}
+
+		let mut ceremonies = Vec::with_capacity(payloads.len());
+		let mut nonces = Vec::with_capacity(payloads.len());
+		for payload in payloads {
+			match ThresholdCeremony::new(me, key_share.clone(), payload, false)? {
+				(ceremony, CeremonyEvent::FirstRoundStarted(_, _, nonce)) => {
+					ceremonies.push(ceremony);
+					nonces.push(nonce);
+				},
+				(_, event) => return Err(format!("Unexpected event: {:?}", event)),
+			}
+		}
+
+		let ceremony = Self { id, ceremonies };
+		let event = CeremonyEvent::BatchFirstRoundStarted(
+			ceremony.get_signers_except_self(),
+			ceremony.id.clone(),
+			nonces,
+		);
+		Ok((ceremony, event))
+	}
+
+	// Saves signer's nonce commitments, one per payload
+	pub fn receive_nonces(
+		&mut self,
+		signer: SignerId,
+		nonces: Vec<PubNonce>,
+	) -> Result<Vec<CeremonyEvent>, CeremonyError> {
+		if nonces.len() != self.ceremonies.len() {
+			return Err(CeremonyError::NonceReceivingError(CeremonyErrorReason::ContributionError))
+		}
+		let events = self
+			.ceremonies
+			.iter_mut()
+			.zip(nonces)
+			.map(|(ceremony, nonce)| ceremony.receive_nonce(signer, nonce))
+			.collect::<Result<Vec<_>, _>>()?;
+		self.combine(events).map_err(CeremonyError::NonceReceivingError)
+	}
+
+	// Saves signer's partial signatures made for `signing_set`, one per payload
+	pub fn receive_partial_signs(
+		&mut self,
+		signer: SignerId,
+		signing_set: Signers,
+		partial_signatures: Vec<[u8; 32]>,
+	) -> Result<Vec<CeremonyEvent>, CeremonyError> {
+		if partial_signatures.len() != self.ceremonies.len() {
+			return Err(CeremonyError::PartialSignatureReceivingError(
+				CeremonyErrorReason::ContributionError,
+			))
+		}
+		let events = self
+			.ceremonies
+			.iter_mut()
+			.zip(partial_signatures)
+			.map(|(ceremony, partial_signature)| {
+				ceremony.receive_partial_sign(signer, signing_set.clone(), partial_signature)
+			})
+			.collect::<Result<Vec<_>, _>>()?;
+		self.combine(events).map_err(CeremonyError::PartialSignatureReceivingError)
+	}
+
+	// merges the events of the ceremonies of all payloads, they are fed the same contributions
+	// so they have to move on together
+	fn combine(
+		&self,
+		events: Vec<Vec<CeremonyEvent>>,
+	) -> Result<Vec<CeremonyEvent>, CeremonyErrorReason> {
+		let mut signing_set = None;
+		let mut partial_signatures = vec![];
+		let mut signatures = vec![];
+		for event in events.into_iter().flatten() {
+			match event {
+				CeremonyEvent::ThresholdSecondRoundStarted(_, _, set, partial_signature) => {
+					if *signing_set.get_or_insert_with(|| set.clone()) != set {
+						error!("Payloads of batch {:?} signed for different sets", self.id);
+						return Err(CeremonyErrorReason::RoundFinalizationError)
+					}
+					partial_signatures.push(partial_signature);
+				},
+				CeremonyEvent::CeremonyEnded(signature, _, true) => signatures.push(signature),
+				event => {
+					error!("Unexpected event of batch {:?}: {:?}", self.id, event);
+					return Err(CeremonyErrorReason::RoundFinalizationError)
+				},
+			}
+		}
+
+		let mut combined = vec![];
+		if let Some(signing_set) = signing_set {
+			if partial_signatures.len() != self.ceremonies.len() {
+				return Err(CeremonyErrorReason::RoundFinalizationError)
+			}
+			combined.push(CeremonyEvent::ThresholdBatchSecondRoundStarted(
+				self.get_signers_except_self(),
+				self.id.clone(),
+				signing_set,
+				partial_signatures,
+			));
+		}
+		if !signatures.is_empty() {
+			if signatures.len() != self.ceremonies.len() {
+				return Err(CeremonyErrorReason::RoundFinalizationError)
+			}
+			info!("Threshold batch ceremony {:?} `has ended`", self.id);
+			combined.push(CeremonyEvent::BatchCeremonyEnded(signatures));
+		}
+		Ok(combined)
+	}
+
+	pub fn get_signers_except_self(&self) -> Signers {
+		self.ceremonies[0].get_signers_except_self()
+	}
+
+	pub fn get_id_ref(&self) -> &CeremonyId {
+		&self.id
+	}
+
+	pub fn is_first_round(&self) -> bool {
+		self.ceremonies.iter().any(|ceremony| ceremony.is_first_round())
+	}
+
+	pub fn contributions(&self) -> CeremonyContributions {
+		self.ceremonies[0].contributions()
+	}
+}
+
+#[cfg(test)]
+pub mod test {
+	use crate::{
+		frost::{
+			dkg_finalize, dkg_round_one, serialize_point, serialize_scalar, verify_dkg_commitment,
+			verify_dkg_share, ParticipantIndex,
+		},
+		threshold::{ThresholdBatchCeremony, ThresholdCeremony, ThresholdKeyShare},
+		CeremonyError, CeremonyErrorReason, CeremonyEvent, SignBitcoinPayload, SignerId,
+	};
+	use std::collections::BTreeMap;
+
+	fn signer_id(index: ParticipantIndex) -> SignerId {
+		[index as u8; 32]
+	}
+
+	fn key_shares(threshold: u16, count: u16) -> Vec<ThresholdKeyShare> {
+		let contributions: Vec<_> = (1..=count)
+			.map(|i| (i, dkg_round_one(i, threshold, b"test").unwrap()))
+			.collect();
+		let commitments: BTreeMap<_, _> = contributions
+			.iter()
+			.map(|(i, (_, c))| (*i, verify_dkg_commitment(*i, threshold, b"test", c).unwrap()))
+			.collect();
+		let indices: Vec<ParticipantIndex> = (1..=count).collect();
+		let outputs: Vec<_> = (1..=count)
+			.map(|receiver| {
+				let shares = contributions
+					.iter()
+					.map(|(i, (secret, _))| {
+						let share = secret.share_for(receiver);
+						(*i, verify_dkg_share(receiver, &share, &commitments[i]).unwrap())
+					})
+					.collect();
+				(receiver, dkg_finalize(&commitments, &shares, &indices).unwrap())
+			})
+			.collect();
+		let participants: Vec<_> = outputs[0]
+			.1
+			.verification_shares
+			.iter()
+			.map(|(i, share)| (signer_id(*i), *i, serialize_point(share)))
+			.collect();
+		outputs
+			.iter()
+			.map(|(i, output)| {
+				ThresholdKeyShare::new(
+					threshold,
+					*i,
+					participants.clone(),
+					serialize_point(&output.group_public_key),
+					serialize_scalar(&output.secret_share),
+				)
+			})
+			.collect()
+	}
+
+	// delivers every broadcast to the online ceremonies until nothing is left to deliver
+	fn run(
+		ceremonies: &mut [ThresholdCeremony],
+		events: Vec<(SignerId, CeremonyEvent)>,
+	) -> Vec<CeremonyEvent> {
+		let mut ended = vec![];
+		let mut queue = events;
+		while let Some((sender, event)) = queue.pop() {
+			for ceremony in ceremonies.iter_mut().filter(|c| c.me != sender) {
+				let result = match &event {
+					CeremonyEvent::FirstRoundStarted(_, _, nonce) =>
+						ceremony.receive_nonce(sender, nonce.clone()),
+					CeremonyEvent::ThresholdSecondRoundStarted(_, _, signing_set, signature) =>
+						ceremony.receive_partial_sign(sender, signing_set.clone(), *signature),
+					_ => continue,
+				};
+				for event in result.unwrap() {
+					match event {
+						CeremonyEvent::CeremonyEnded(_, _, _) => ended.push(event),
+						event => queue.push((ceremony.me, event)),
+					}
+				}
+			}
+		}
+		ended
+	}
+
+	#[test]
+	fn it_should_sign_with_offline_signer() {
+		// given
+		let payload = SignBitcoinPayload::TaprootUnspendable([1u8; 32].to_vec());
+		let (mut ceremonies, events): (Vec<_>, Vec<_>) = key_shares(3, 4)
+			.into_iter()
+			// signer 2 is offline
+			.filter(|k| k.index != 2)
+			.map(|k| {
+				let me = signer_id(k.index);
+				let (ceremony, event) =
+					ThresholdCeremony::new(me, k, payload.clone(), false).unwrap();
+				(ceremony, (me, event))
+			})
+			.unzip();
+
+		// when
+		let ended = run(&mut ceremonies, events);
+
+		// then
+		assert!(!ended.is_empty());
+		assert!(ended.iter().all(|e| matches!(e, CeremonyEvent::CeremonyEnded(_, false, true))));
+	}
+
+	#[test]
+	fn it_should_not_sign_below_threshold() {
+		// given
+		let payload = SignBitcoinPayload::Derived([1u8; 32].to_vec());
+		let (mut ceremonies, events): (Vec<_>, Vec<_>) = key_shares(3, 4)
+			.into_iter()
+			.filter(|k| k.index == 1 || k.index == 4)
+			.map(|k| {
+				let me = signer_id(k.index);
+				let (ceremony, event) =
+					ThresholdCeremony::new(me, k, payload.clone(), false).unwrap();
+				(ceremony, (me, event))
+			})
+			.unzip();
+
+		// when
+		let ended = run(&mut ceremonies, events);
+
+		// then
+		assert!(ended.is_empty());
+		assert!(ceremonies.iter().all(|c| c.is_first_round()));
+	}
+
+	#[test]
+	fn it_should_reject_partial_signature_from_signer_outside_of_signing_set() {
+		// given
+		let key_shares = key_shares(2, 3);
+		let (mut ceremony, _) = ThresholdCeremony::new(
+			signer_id(1),
+			key_shares[0].clone(),
+			SignBitcoinPayload::Derived([1u8; 32].to_vec()),
+			false,
+		)
+		.unwrap();
+
+		// when
+		let result = ceremony.receive_partial_sign(
+			signer_id(3),
+			vec![signer_id(1), signer_id(2)],
+			[1u8; 32],
+		);
+
+		// then
+		assert_eq!(
+			result,
+			Err(CeremonyError::PartialSignatureReceivingError(
+				CeremonyErrorReason::ContributionError
+			))
+		)
+	}
+
+	#[test]
+	fn it_should_sign_batch_with_offline_signer() {
+		// given
+		let id = SignBitcoinPayload::Batch(
+			vec![
+				SignBitcoinPayload::Derived([1u8; 32].to_vec()),
+				SignBitcoinPayload::TaprootUnspendable([2u8; 32].to_vec()),
+			],
+			vec![],
+		);
+		let (mut ceremonies, mut queue): (Vec<_>, Vec<_>) = key_shares(3, 4)
+			.into_iter()
+			// signer 2 is offline
+			.filter(|k| k.index != 2)
+			.map(|k| {
+				let me = signer_id(k.index);
+				let (ceremony, event) = ThresholdBatchCeremony::new(me, k, id.clone()).unwrap();
+				((me, ceremony), (me, event))
+			})
+			.unzip();
+
+		// when
+		let mut ended = vec![];
+		while let Some((sender, event)) = queue.pop() {
+			for (me, ceremony) in ceremonies.iter_mut().filter(|(me, _)| *me != sender) {
+				let result = match &event {
+					CeremonyEvent::BatchFirstRoundStarted(_, _, nonces) =>
+						ceremony.receive_nonces(sender, nonces.clone()),
+					CeremonyEvent::ThresholdBatchSecondRoundStarted(
+						_,
+						_,
+						signing_set,
+						signatures,
+					) => ceremony.receive_partial_signs(
+						sender,
+						signing_set.clone(),
+						signatures.clone(),
+					),
+					_ => continue,
+				};
+				for event in result.unwrap() {
+					match event {
+						CeremonyEvent::BatchCeremonyEnded(signatures) => ended.push(signatures),
+						event => queue.push((*me, event)),
+					}
+				}
+			}
+		}
+
+		// then
+		assert!(!ended.is_empty());
+		assert!(ended.iter().all(|signatures| signatures.len() == 2));
+	}
+
+	#[test]
+	fn it_should_not_create_batch_ceremony_with_nonce_slots() {
+		// given
+		let key_shares = key_shares(2, 3);
+		let id = SignBitcoinPayload::Batch(
+			vec![SignBitcoinPayload::Derived([1u8; 32].to_vec())],
+			vec![0],
+		);
+
+		// when
+		let result = ThresholdBatchCeremony::new(signer_id(1), key_shares[0].clone(), id);
+
+		// then
+		assert!(result.is_err())
+	}
+
+	#[test]
+	fn it_should_not_create_ceremony_with_foreign_key_share() {
+		// given
+		let key_shares = key_shares(2, 3);
+
+		// when
+		let result = ThresholdCeremony::new(
+			signer_id(1),
+			key_shares[1].clone(),
+			SignBitcoinPayload::Derived([1u8; 32].to_vec()),
+			false,
+		);
+
+		// then
+		assert!(result.is_err())
+	}
+}
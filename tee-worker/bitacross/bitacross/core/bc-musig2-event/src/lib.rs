@@ -43,7 +43,11 @@ use std::sync::RwLock;
 use std::sync::SgxRwLock as RwLock;
 
 use bc_enclave_registry::EnclaveRegistryLookup;
-use bc_musig2_ceremony::{CeremonyEvent, CeremonyId, CeremonyRegistry, SignerId};
+use bc_musig2_ceremony::{
+	get_current_timestamp, nonce_pool::SerializedPubNonce, CeremonyEvent, CeremonyId,
+	CeremonyRegistry, KeyGenerationEvent, KeyGenerationId, KeyGenerationRegistry, NonceSlot,
+	SignerId, Signers,
+};
 use codec::Encode;
 use itc_direct_rpc_client::{DirectRpcClient, DirectRpcClientFactory, RpcClient, RpcClientFactory};
 use itc_direct_rpc_server::SendRpcResponse;
@@ -56,7 +60,7 @@ use lc_direct_call::CeremonyRoundCall;
 use litentry_primitives::{Address32, Identity, PlainRequest, ShardIdentifier};
 use log::*;
 use sp_core::{blake2_256, ed25519, Pair as SpCorePair, H256};
-use std::{collections::HashMap, string::ToString, sync::Arc, thread::sleep, vec};
+use std::{collections::HashMap, string::ToString, sync::Arc, thread::sleep, vec, vec::Vec};
 
 #[allow(clippy::too_many_arguments)]
 pub fn process_event<OCallApi, SIGNINGAK, Responder, ECL, BKR>(
@@ -100,11 +104,16 @@ pub fn process_event<OCallApi, SIGNINGAK, Responder, ECL, BKR>(
 				event_threads_pool.execute(move || {
 					send_request(
 						signer_id,
-						&ceremony_id_cloned,
 						request,
 						peers_map_clone,
 						enclave_lookup_cloned,
-						ceremony_registry_cloned,
+						|| {
+							// check if ceremony still exists
+							ceremony_registry_cloned
+								.read()
+								.unwrap()
+								.contains_key(&ceremony_id_cloned)
+						},
 					);
 				});
 			});
@@ -129,11 +138,171 @@ pub fn process_event<OCallApi, SIGNINGAK, Responder, ECL, BKR>(
 				event_threads_pool.execute(move || {
 					send_request(
 						signer_id,
-						&ceremony_id_cloned,
 						request,
 						peers_map_clone,
 						enclave_lookup_cloned,
-						ceremony_registry_cloned,
+						|| {
+							// check if ceremony still exists
+							ceremony_registry_cloned
+								.read()
+								.unwrap()
+								.contains_key(&ceremony_id_cloned)
+						},
+					);
+				});
+			});
+		},
+		CeremonyEvent::ThresholdSecondRoundStarted(signers, message, signing_set, signature) => {
+			let direct_call = CeremonyRoundCall::ThresholdPartialSignatureShare(
+				identity,
+				message,
+				signing_set,
+				signature,
+			);
+			let request = prepare_request(signing_key_access.as_ref(), mr_enclave, direct_call);
+
+			signers.iter().for_each(|signer_id| {
+				debug!(
+					"Sharing threshold partial signature with signer: {:?} for ceremony: {:?}",
+					signer_id, ceremony_id
+				);
+
+				let signer_id = *signer_id;
+				let peers_map_clone = peers_map.clone();
+				let request = request.clone();
+				let enclave_lookup_cloned = enclave_registry_lookup.clone();
+				let ceremony_registry_cloned = ceremony_registry.clone();
+				let ceremony_id_cloned = ceremony_id.clone();
+				event_threads_pool.execute(move || {
+					send_request(
+						signer_id,
+						request,
+						peers_map_clone,
+						enclave_lookup_cloned,
+						|| {
+							// check if ceremony still exists
+							ceremony_registry_cloned
+								.read()
+								.unwrap()
+								.contains_key(&ceremony_id_cloned)
+						},
+					);
+				});
+			});
+		},
+		CeremonyEvent::BatchFirstRoundStarted(signers, message, nonces) => {
+			let direct_call = CeremonyRoundCall::BatchNonceShare(
+				identity,
+				message,
+				nonces.iter().map(|n| n.serialize()).collect(),
+			);
+			let request = prepare_request(signing_key_access.as_ref(), mr_enclave, direct_call);
+
+			signers.iter().for_each(|signer_id| {
+				debug!(
+					"Sharing batch nonces with signer: {:?} for ceremony: {:?}",
+					signer_id, ceremony_id
+				);
+
+				let signer_id = *signer_id;
+				let peers_map_clone = peers_map.clone();
+				let request = request.clone();
+				let enclave_lookup_cloned = enclave_registry_lookup.clone();
+				let ceremony_registry_cloned = ceremony_registry.clone();
+				let ceremony_id_cloned = ceremony_id.clone();
+				event_threads_pool.execute(move || {
+					send_request(
+						signer_id,
+						request,
+						peers_map_clone,
+						enclave_lookup_cloned,
+						|| {
+							// check if ceremony still exists
+							ceremony_registry_cloned
+								.read()
+								.unwrap()
+								.contains_key(&ceremony_id_cloned)
+						},
+					);
+				});
+			});
+		},
+		CeremonyEvent::BatchSecondRoundStarted(signers, message, signatures) => {
+			let direct_call = CeremonyRoundCall::BatchPartialSignatureShare(
+				identity,
+				message,
+				signatures.iter().map(|n| n.serialize()).collect(),
+			);
+			let request = prepare_request(signing_key_access.as_ref(), mr_enclave, direct_call);
+
+			signers.iter().for_each(|signer_id| {
+				debug!(
+					"Sharing batch partial signatures with signer: {:?} for ceremony: {:?}",
+					signer_id, ceremony_id
+				);
+
+				let signer_id = *signer_id;
+				let peers_map_clone = peers_map.clone();
+				let request = request.clone();
+				let enclave_lookup_cloned = enclave_registry_lookup.clone();
+				let ceremony_registry_cloned = ceremony_registry.clone();
+				let ceremony_id_cloned = ceremony_id.clone();
+				event_threads_pool.execute(move || {
+					send_request(
+						signer_id,
+						request,
+						peers_map_clone,
+						enclave_lookup_cloned,
+						|| {
+							// check if ceremony still exists
+							ceremony_registry_cloned
+								.read()
+								.unwrap()
+								.contains_key(&ceremony_id_cloned)
+						},
+					);
+				});
+			});
+		},
+		CeremonyEvent::ThresholdBatchSecondRoundStarted(
+			signers,
+			message,
+			signing_set,
+			signatures,
+		) => {
+			let direct_call = CeremonyRoundCall::ThresholdBatchPartialSignatureShare(
+				identity,
+				message,
+				signing_set,
+				signatures,
+			);
+			let request = prepare_request(signing_key_access.as_ref(), mr_enclave, direct_call);
+
+			signers.iter().for_each(|signer_id| {
+				debug!(
+					"Sharing threshold batch partial signatures with signer: {:?} for ceremony: {:?}",
+					signer_id, ceremony_id
+				);
+
+				let signer_id = *signer_id;
+				let peers_map_clone = peers_map.clone();
+				let request = request.clone();
+				let enclave_lookup_cloned = enclave_registry_lookup.clone();
+				let ceremony_registry_cloned = ceremony_registry.clone();
+				let ceremony_id_cloned = ceremony_id.clone();
+				event_threads_pool.execute(move || {
+					send_request(
+						signer_id,
+						request,
+						peers_map_clone,
+						enclave_lookup_cloned,
+						|| {
+							// check if ceremony still exists
+							ceremony_registry_cloned
+								.read()
+								.unwrap()
+								.contains_key(&ceremony_id_cloned)
+						},
 					);
 				});
 			});
@@ -157,6 +326,20 @@ pub fn process_event<OCallApi, SIGNINGAK, Responder, ECL, BKR>(
 				}
 			});
 		},
+		CeremonyEvent::BatchCeremonyEnded(signatures) => {
+			debug!("Batch ceremony {:?} ended, signatures {:?}", ceremony_id, signatures);
+			let hash = blake2_256(&ceremony_id.encode());
+			let result = signatures.encode();
+			event_threads_pool.execute(move || {
+				if let Err(e) = responder.send_state_with_status(
+					Hash::from_slice(&hash),
+					result,
+					DirectRequestStatus::Ok,
+				) {
+					error!("Could not send response to {:?}, reason: {:?}", &hash, e);
+				}
+			});
+		},
 		CeremonyEvent::CeremonyError(signers, error) => {
 			debug!("Ceremony {:?} error {:?}", ceremony_id, error);
 			let hash = blake2_256(&ceremony_id.encode());
@@ -190,29 +373,182 @@ pub fn process_event<OCallApi, SIGNINGAK, Responder, ECL, BKR>(
 				event_threads_pool.execute(move || {
 					send_request(
 						signer_id,
-						&ceremony_id_cloned,
 						request,
 						peers_map_clone,
 						enclave_lookup_cloned,
-						ceremony_registry_cloned,
+						|| {
+							// check if ceremony still exists
+							ceremony_registry_cloned
+								.read()
+								.unwrap()
+								.contains_key(&ceremony_id_cloned)
+						},
+					);
+				});
+			});
+		},
+	}
+}
+
+#[allow(clippy::too_many_arguments)]
+pub fn process_key_generation_event<OCallApi, SIGNINGAK, Responder, ECL, BKR>(
+	signing_key_access: Arc<SIGNINGAK>,
+	ocall_api: Arc<OCallApi>,
+	responder: Arc<Responder>,
+	enclave_registry_lookup: Arc<ECL>,
+	event: KeyGenerationEvent,
+	key_generation_id: KeyGenerationId,
+	event_threads_pool: ThreadPool,
+	peers_map: Arc<Mutex<HashMap<[u8; 32], DirectRpcClient>>>,
+	key_generation_registry: Arc<RwLock<KeyGenerationRegistry<BKR>>>,
+) where
+	OCallApi: EnclaveAttestationOCallApi + 'static,
+	SIGNINGAK: AccessKey<KeyType = ed25519::Pair> + Send + Sync + 'static,
+	Responder: SendRpcResponse<Hash = H256> + 'static,
+	ECL: EnclaveRegistryLookup + Send + Sync + 'static,
+	BKR: AccessKey<KeyType = SchnorrPair> + Send + Sync + 'static,
+{
+	let my_identity: Address32 = signing_key_access.retrieve_key().unwrap().public().0.into();
+	let identity = Identity::Substrate(my_identity);
+	let mr_enclave = ocall_api.get_mrenclave_of_self().unwrap().m;
+	let hash = blake2_256(&key_generation_id.encode());
+
+	match event {
+		KeyGenerationEvent::Started(id, threshold, packages) => {
+			// every participant gets its own share
+			packages.into_iter().for_each(|(signer_id, package)| {
+				debug!(
+					"Sharing key generation package with signer: {:?} for key generation: {:?}",
+					signer_id, id
+				);
+				let direct_call = CeremonyRoundCall::KeyGenerationPackageShare(
+					identity.clone(),
+					id,
+					threshold,
+					package,
+				);
+				let request = prepare_request(signing_key_access.as_ref(), mr_enclave, direct_call);
+
+				let peers_map_clone = peers_map.clone();
+				let enclave_lookup_cloned = enclave_registry_lookup.clone();
+				let key_generation_registry_cloned = key_generation_registry.clone();
+				event_threads_pool.execute(move || {
+					send_request(
+						signer_id,
+						request,
+						peers_map_clone,
+						enclave_lookup_cloned,
+						|| key_generation_registry_cloned.read().unwrap().contains_key(&id),
+					);
+				});
+			});
+		},
+		KeyGenerationEvent::ResharingStarted(id, signers, reshared_key, packages) => {
+			packages.into_iter().for_each(|(signer_id, package)| {
+				debug!(
+					"Sharing resharing package with signer: {:?} for resharing: {:?}",
+					signer_id, id
+				);
+				let direct_call = CeremonyRoundCall::ResharingPackageShare(
+					identity.clone(),
+					id,
+					signers.clone(),
+					reshared_key.clone(),
+					package,
+				);
+				let request = prepare_request(signing_key_access.as_ref(), mr_enclave, direct_call);
+
+				let peers_map_clone = peers_map.clone();
+				let enclave_lookup_cloned = enclave_registry_lookup.clone();
+				let key_generation_registry_cloned = key_generation_registry.clone();
+				event_threads_pool.execute(move || {
+					send_request(
+						signer_id,
+						request,
+						peers_map_clone,
+						enclave_lookup_cloned,
+						|| key_generation_registry_cloned.read().unwrap().contains_key(&id),
 					);
 				});
 			});
 		},
+		KeyGenerationEvent::Ended(key_share) => {
+			debug!("Key generation {:?} ended", key_generation_id);
+			let result = key_share.group_public_key.encode();
+			event_threads_pool.execute(move || {
+				if let Err(e) = responder.send_state_with_status(
+					Hash::from_slice(&hash),
+					result,
+					DirectRequestStatus::Ok,
+				) {
+					error!("Could not send response to {:?}, reason: {:?}", &hash, e);
+				}
+			});
+		},
+		KeyGenerationEvent::Error(_, error) => {
+			debug!("Key generation {:?} error {:?}", key_generation_id, error);
+			let encoded_result = error.encode();
+			event_threads_pool.execute(move || {
+				if let Err(e) = responder.send_state_with_status(
+					Hash::from_slice(&hash),
+					encoded_result,
+					DirectRequestStatus::Error,
+				) {
+					error!("Could not send response to {:?}, reason: {:?}", &hash, e);
+				}
+			});
+		},
 	}
 }
 
-// it will try to send request until it succeeds, the peer is removed from registry or ceremony is removed
-fn send_request<ECL, BKR>(
+/// Shares the unused nonces of this enclave with `signers`. Sending is given up after `ttl`
+/// seconds, by then the nonces are shared again anyway.
+#[allow(clippy::too_many_arguments)]
+pub fn share_nonce_pool<OCallApi, SIGNINGAK, ECL>(
+	signing_key_access: Arc<SIGNINGAK>,
+	ocall_api: Arc<OCallApi>,
+	enclave_registry_lookup: Arc<ECL>,
+	signers: Signers,
+	nonces: Vec<(NonceSlot, SerializedPubNonce)>,
+	ttl: u64,
+	event_threads_pool: ThreadPool,
+	peers_map: Arc<Mutex<HashMap<[u8; 32], DirectRpcClient>>>,
+) where
+	OCallApi: EnclaveAttestationOCallApi + 'static,
+	SIGNINGAK: AccessKey<KeyType = ed25519::Pair> + Send + Sync + 'static,
+	ECL: EnclaveRegistryLookup + Send + Sync + 'static,
+{
+	let my_identity: Address32 = signing_key_access.retrieve_key().unwrap().public().0.into();
+	let identity = Identity::Substrate(my_identity);
+	let mr_enclave = ocall_api.get_mrenclave_of_self().unwrap().m;
+	let direct_call = CeremonyRoundCall::NoncePoolShare(identity, nonces);
+	let request = prepare_request(signing_key_access.as_ref(), mr_enclave, direct_call);
+	let deadline = get_current_timestamp() + ttl;
+
+	signers.into_iter().for_each(|signer_id| {
+		debug!("Sharing nonce pool with signer: {:?}", signer_id);
+		let peers_map_clone = peers_map.clone();
+		let request = request.clone();
+		let enclave_lookup_cloned = enclave_registry_lookup.clone();
+		event_threads_pool.execute(move || {
+			send_request(signer_id, request, peers_map_clone, enclave_lookup_cloned, || {
+				get_current_timestamp() < deadline
+			});
+		});
+	});
+}
+
+// it will try to send request until it succeeds, the peer is removed from registry or the request
+// is no longer needed (e.g. ceremony is removed)
+fn send_request<ECL, F>(
 	signer_id: SignerId,
-	ceremony_id: &CeremonyId,
 	request: RpcRequest,
 	peers_map: Arc<Mutex<HashMap<[u8; 32], DirectRpcClient>>>,
 	enclave_registry_lookup: Arc<ECL>,
-	ceremony_registry: Arc<RwLock<CeremonyRegistry<BKR>>>,
+	still_needed: F,
 ) where
 	ECL: EnclaveRegistryLookup,
-	BKR: AccessKey<KeyType = SchnorrPair>,
+	F: Fn() -> bool,
 {
 	loop {
 		let client = peers_map.lock().unwrap().get(&signer_id).cloned();
@@ -227,8 +563,8 @@ fn send_request<ECL, BKR>(
 				break
 			}
 		} else {
-			// check if ceremony still exists, if not stop
-			if !ceremony_registry.read().unwrap().contains_key(ceremony_id) {
+			// check if request is still needed (e.g. ceremony still exists), if not stop
+			if !still_needed() {
 				break
 			}
 
@@ -25,16 +25,90 @@ compile_error!("feature \"std\" and feature \"sgx\" cannot be enabled at the sam
 
 use sp_std::{boxed::Box, fmt::Debug};
 
-use litentry_primitives::Identity;
+use codec::{Decode, Encode};
+use litentry_primitives::{Identity, RelayerChain, RelayerPermissions};
 use log::error;
-use std::{collections::BTreeMap, path::PathBuf};
+use std::{collections::BTreeMap, path::PathBuf, vec::Vec};
 
 #[cfg(feature = "std")]
 use std::sync::RwLock;
 #[cfg(feature = "sgx")]
 use std::sync::SgxRwLock as RwLock;
 
-pub type RelayerRegistryMap = BTreeMap<Identity, ()>;
+const SECONDS_PER_DAY: u64 = 86_400;
+
+#[derive(Encode, Debug, PartialEq, Eq)]
+pub enum PermissionError {
+	NotRelayer,
+	ChainNotAllowed(RelayerChain),
+	Expired,
+	// the daily quota of the chain is used up
+	QuotaExceeded(RelayerChain),
+	StateUnavailable,
+}
+
+/// Requests a relayer made on `day`. It's sealed on its own whenever a request is counted, so
+/// the registry isn't sealed again for every signing request.
+#[derive(Encode, Decode, Clone, Debug, Default, PartialEq, Eq)]
+pub struct RelayerUsage {
+	// days since the unix epoch
+	pub day: u64,
+	// only chains with a quota are counted
+	pub requests_today: Vec<(RelayerChain, u32)>,
+}
+
+/// Permissions of a relayer published on the parentchain together with what it already requested
+/// today.
+#[derive(Encode, Decode, Clone, Debug, PartialEq, Eq)]
+pub struct RelayerRecord {
+	pub permissions: RelayerPermissions,
+	pub usage: RelayerUsage,
+}
+
+impl RelayerRecord {
+	pub fn new(permissions: RelayerPermissions) -> Self {
+		Self { permissions, usage: Default::default() }
+	}
+
+	/// Checks whether the relayer may sign on `chain` at `now` and counts the request against the
+	/// daily quota of `chain`. Returns whether the record changed.
+	///
+	/// `now` is a unix timestamp in seconds coming from the host. Like for the signing policy the
+	/// clock is not allowed to go back, requests are counted on the latest day seen.
+	pub fn authorize(&mut self, chain: RelayerChain, now: u64) -> Result<bool, PermissionError> {
+		if matches!(self.permissions.expires_at, Some(expires_at) if now >= expires_at) {
+			return Err(PermissionError::Expired)
+		}
+		let permission =
+			self.permissions.chain(chain).ok_or(PermissionError::ChainNotAllowed(chain))?;
+		let quota = match permission.daily_quota {
+			Some(quota) => quota,
+			None => return Ok(false),
+		};
+
+		let usage = &mut self.usage;
+		let day = now / SECONDS_PER_DAY;
+		if day > usage.day {
+			usage.day = day;
+			usage.requests_today.clear();
+		}
+		let index = match usage.requests_today.iter().position(|(c, _)| *c == chain) {
+			Some(index) => index,
+			None => {
+				usage.requests_today.push((chain, 0));
+				usage.requests_today.len() - 1
+			},
+		};
+		let requests = &mut usage.requests_today[index].1;
+		if *requests >= quota {
+			return Err(PermissionError::QuotaExceeded(chain))
+		}
+		*requests += 1;
+		Ok(true)
+	}
+}
+
+pub type RelayerRegistryMap = BTreeMap<Identity, RelayerRecord>;
 
 #[derive(Default)]
 pub struct RelayerRegistry {
@@ -83,12 +157,17 @@ impl From<codec::Error> for RegistryError {
 
 #[cfg(feature = "sgx")]
 mod sgx {
-	use crate::{RegistryError as Error, RegistryResult as Result, RelayerRegistryMap};
-	pub use codec::{Decode, Encode};
-	pub use itp_settings::files::RELAYER_REGISTRY_FILE;
+	use crate::{
+		RegistryError as Error, RegistryResult as Result, RelayerRecord, RelayerRegistryMap,
+		RelayerUsage,
+	};
+	pub use codec::{Decode, DecodeAll, Encode};
+	pub use itp_settings::files::{RELAYER_REGISTRY_FILE, RELAYER_USAGE_PATH};
 	pub use itp_sgx_io::{seal, unseal, SealedIO};
+	use litentry_primitives::{Identity, RelayerPermissions};
 	pub use log::*;
-	pub use std::{boxed::Box, fs, path::PathBuf, sgxfs::SgxFile, sync::Arc};
+	use std::collections::BTreeMap;
+	pub use std::{boxed::Box, format, fs, path::PathBuf, sgxfs::SgxFile, sync::Arc};
 
 	#[derive(Clone, Debug)]
 	pub struct RelayerRegistrySeal {
@@ -110,7 +189,16 @@ mod sgx {
 		type Unsealed = RelayerRegistryMap;
 
 		fn unseal(&self) -> Result<Self::Unsealed> {
-			Ok(unseal(self.path()).map(|b| Decode::decode(&mut b.as_slice()))??)
+			let bytes = unseal(self.path())?;
+			if let Ok(registry) = RelayerRegistryMap::decode_all(&mut bytes.as_slice()) {
+				return Ok(registry)
+			}
+			// sealed before relayers had permissions, they could sign on every chain then
+			let legacy = BTreeMap::<Identity, ()>::decode_all(&mut bytes.as_slice())?;
+			Ok(legacy
+				.into_keys()
+				.map(|account| (account, RelayerRecord::new(RelayerPermissions::unrestricted())))
+				.collect())
 		}
 
 		fn seal(&self, unsealed: &Self::Unsealed) -> Result<()> {
@@ -118,6 +206,37 @@ mod sgx {
 			Ok(unsealed.using_encoded(|bytes| seal(bytes, self.path()))?)
 		}
 	}
+
+	#[derive(Clone, Debug)]
+	pub struct RelayerUsageSeal {
+		base_path: PathBuf,
+	}
+
+	impl RelayerUsageSeal {
+		pub fn new(base_path: PathBuf) -> Self {
+			Self { base_path }
+		}
+
+		pub fn path(&self, account: &Identity) -> PathBuf {
+			self.base_path
+				.join(RELAYER_USAGE_PATH)
+				.join(format!("{:x}.bin", account.hash()))
+		}
+
+		pub fn unseal(&self, account: &Identity) -> Result<Option<RelayerUsage>> {
+			let path = self.path(account);
+			if !path.exists() {
+				return Ok(None)
+			}
+			Ok(Some(RelayerUsage::decode_all(&mut unseal(path)?.as_slice())?))
+		}
+
+		pub fn seal(&self, account: &Identity, usage: &RelayerUsage) -> Result<()> {
+			fs::create_dir_all(self.base_path.join(RELAYER_USAGE_PATH))?;
+			debug!("Seal usage of relayer {:?}: {:?}", account, usage);
+			Ok(usage.using_encoded(|bytes| seal(bytes, self.path(account)))?)
+		}
+	}
 }
 
 #[cfg(feature = "sgx")]
@@ -125,29 +244,52 @@ use sgx::*;
 
 pub trait RelayerRegistryUpdater {
 	fn init(&self) -> RegistryResult<()>;
+	// adds `account` with unrestricted permissions, the permissions of known relayers are kept
 	fn update(&self, account: Identity) -> RegistryResult<()>;
+	fn set_permissions(
+		&self,
+		account: Identity,
+		permissions: RelayerPermissions,
+	) -> RegistryResult<()>;
 	fn remove(&self, account: Identity) -> RegistryResult<()>;
 }
 
 pub trait RelayerRegistryLookup {
 	fn contains_key(&self, account: &Identity) -> bool;
+	/// Authorizes `account` to sign on `chain`, see [`RelayerRecord::authorize`]
+	fn authorize(
+		&self,
+		account: &Identity,
+		chain: RelayerChain,
+		now: u64,
+	) -> Result<(), PermissionError>;
 }
 
-impl RelayerRegistryUpdater for RelayerRegistry {
+impl RelayerRegistry {
 	#[cfg(feature = "std")]
-	fn init(&self) -> RegistryResult<()> {
+	fn seal(&self, _registry: &RelayerRegistryMap) -> RegistryResult<()> {
 		Ok(())
 	}
 
+	#[cfg(feature = "sgx")]
+	fn seal(&self, registry: &RelayerRegistryMap) -> RegistryResult<()> {
+		RelayerRegistrySeal::new(self.seal_path.clone()).seal(registry)
+	}
+
 	#[cfg(feature = "std")]
-	fn update(&self, account: Identity) -> RegistryResult<()> {
-		let mut registry = self.registry.write().unwrap();
-		registry.insert(account, ());
+	fn seal_usage(&self, _account: &Identity, _usage: &RelayerUsage) -> RegistryResult<()> {
 		Ok(())
 	}
 
+	#[cfg(feature = "sgx")]
+	fn seal_usage(&self, account: &Identity, usage: &RelayerUsage) -> RegistryResult<()> {
+		RelayerUsageSeal::new(self.seal_path.clone()).seal(account, usage)
+	}
+}
+
+impl RelayerRegistryUpdater for RelayerRegistry {
 	#[cfg(feature = "std")]
-	fn remove(&self, _account: Identity) -> RegistryResult<()> {
+	fn init(&self) -> RegistryResult<()> {
 		Ok(())
 	}
 
@@ -164,7 +306,14 @@ impl RelayerRegistryUpdater for RelayerRegistry {
 			let registry = self.registry.write().map_err(|_| RegistryError::PoisonLock)?;
 			enclave_seal.seal(&*registry)
 		} else {
-			let m = enclave_seal.unseal()?;
+			let mut m = enclave_seal.unseal()?;
+			// the usage sealed with the registry may be behind the one sealed on its own
+			let usage_seal = RelayerUsageSeal::new(self.seal_path.clone());
+			for (account, record) in m.iter_mut() {
+				if let Some(usage) = usage_seal.unseal(account)? {
+					record.usage = usage;
+				}
+			}
 			info!("[Enclave] RelayerRegistry unsealed from file: {:?}", m);
 			let mut registry = self.registry.write().map_err(|_| RegistryError::PoisonLock)?;
 			*registry = m;
@@ -172,35 +321,157 @@ impl RelayerRegistryUpdater for RelayerRegistry {
 		}
 	}
 
-	#[cfg(feature = "sgx")]
 	fn update(&self, account: Identity) -> RegistryResult<()> {
 		let mut registry = self.registry.write().map_err(|_| RegistryError::PoisonLock)?;
-		registry.insert(account, ());
-		RelayerRegistrySeal::new(self.seal_path.clone()).seal(&*registry)
+		if registry.contains_key(&account) {
+			return Ok(())
+		}
+		registry.insert(account, RelayerRecord::new(RelayerPermissions::unrestricted()));
+		self.seal(&registry)
+	}
+
+	fn set_permissions(
+		&self,
+		account: Identity,
+		permissions: RelayerPermissions,
+	) -> RegistryResult<()> {
+		let mut registry = self.registry.write().map_err(|_| RegistryError::PoisonLock)?;
+		// what was requested today still counts against the new quotas
+		registry
+			.entry(account)
+			.and_modify(|record| record.permissions = permissions.clone())
+			.or_insert_with(|| RelayerRecord::new(permissions));
+		self.seal(&registry)
 	}
 
-	#[cfg(feature = "sgx")]
 	fn remove(&self, account: Identity) -> RegistryResult<()> {
 		let mut registry = self.registry.write().map_err(|_| RegistryError::PoisonLock)?;
 		let old_value = registry.remove(&account);
 		if old_value.is_some() {
-			return RelayerRegistrySeal::new(self.seal_path.clone()).seal(&*registry)
+			return self.seal(&registry)
 		}
 		Ok(())
 	}
 }
 
 impl RelayerRegistryLookup for RelayerRegistry {
-	#[cfg(feature = "std")]
 	fn contains_key(&self, account: &Identity) -> bool {
+		// Using unwrap becaused poisoned locks are unrecoverable errors
 		let registry = self.registry.read().unwrap();
 		registry.contains_key(account)
 	}
 
-	#[cfg(feature = "sgx")]
-	fn contains_key(&self, account: &Identity) -> bool {
-		// Using unwrap becaused poisoned locks are unrecoverable errors
-		let registry = self.registry.read().unwrap();
-		registry.contains_key(account)
+	fn authorize(
+		&self,
+		account: &Identity,
+		chain: RelayerChain,
+		now: u64,
+	) -> Result<(), PermissionError> {
+		let mut registry = self.registry.write().map_err(|_| PermissionError::StateUnavailable)?;
+		let record = registry.get_mut(account).ok_or(PermissionError::NotRelayer)?;
+		let mut updated = record.clone();
+		if !updated.authorize(chain, now)? {
+			return Ok(())
+		}
+		// nothing is signed if the request can't be counted
+		if let Err(e) = self.seal_usage(account, &updated.usage) {
+			error!("Could not seal relayer usage: {:?}", e);
+			return Err(PermissionError::StateUnavailable)
+		}
+		*record = updated;
+		Ok(())
+	}
+}
+
+#[cfg(test)]
+mod test {
+	use super::{
+		PermissionError, RelayerRegistry, RelayerRegistryLookup, RelayerRegistryUpdater,
+		SECONDS_PER_DAY,
+	};
+	use litentry_primitives::{
+		Address32, ChainPermission, Identity, RelayerChain, RelayerPermissions,
+	};
+
+	fn relayer() -> Identity {
+		Identity::Substrate(Address32::from([1u8; 32]))
+	}
+
+	fn permissions(daily_quota: Option<u32>, expires_at: Option<u64>) -> RelayerPermissions {
+		RelayerPermissions {
+			chains: vec![ChainPermission { chain: RelayerChain::Bitcoin, daily_quota }]
+				.try_into()
+				.unwrap(),
+			expires_at,
+		}
+	}
+
+	#[test]
+	pub fn it_should_authorize_new_relayer_on_every_chain() {
+		// given
+		let registry = RelayerRegistry::default();
+		registry.update(relayer()).unwrap();
+
+		// then
+		assert_eq!(registry.authorize(&relayer(), RelayerChain::Bitcoin, 0), Ok(()));
+		assert_eq!(registry.authorize(&relayer(), RelayerChain::Solana, 0), Ok(()));
+	}
+
+	#[test]
+	pub fn it_should_keep_permissions_when_relayer_is_added_again() {
+		// given
+		let registry = RelayerRegistry::default();
+		registry.set_permissions(relayer(), permissions(None, None)).unwrap();
+
+		// when
+		registry.update(relayer()).unwrap();
+
+		// then
+		assert_eq!(
+			registry.authorize(&relayer(), RelayerChain::Ethereum, 0),
+			Err(PermissionError::ChainNotAllowed(RelayerChain::Ethereum))
+		);
+	}
+
+	#[test]
+	pub fn it_should_reject_unknown_relayer() {
+		let registry = RelayerRegistry::default();
+
+		assert_eq!(
+			registry.authorize(&relayer(), RelayerChain::Bitcoin, 0),
+			Err(PermissionError::NotRelayer)
+		);
+	}
+
+	#[test]
+	pub fn it_should_reject_expired_relayer() {
+		// given
+		let registry = RelayerRegistry::default();
+		registry.set_permissions(relayer(), permissions(None, Some(100))).unwrap();
+
+		// then
+		assert_eq!(registry.authorize(&relayer(), RelayerChain::Bitcoin, 99), Ok(()));
+		assert_eq!(
+			registry.authorize(&relayer(), RelayerChain::Bitcoin, 100),
+			Err(PermissionError::Expired)
+		);
+	}
+
+	#[test]
+	pub fn it_should_enforce_daily_quota() {
+		// given
+		let registry = RelayerRegistry::default();
+		registry.set_permissions(relayer(), permissions(Some(2), None)).unwrap();
+
+		// when
+		registry.authorize(&relayer(), RelayerChain::Bitcoin, 0).unwrap();
+		registry.authorize(&relayer(), RelayerChain::Bitcoin, 1).unwrap();
+
+		// then
+		assert_eq!(
+			registry.authorize(&relayer(), RelayerChain::Bitcoin, 2),
+			Err(PermissionError::QuotaExceeded(RelayerChain::Bitcoin))
+		);
+		assert_eq!(registry.authorize(&relayer(), RelayerChain::Bitcoin, SECONDS_PER_DAY), Ok(()));
 	}
 }
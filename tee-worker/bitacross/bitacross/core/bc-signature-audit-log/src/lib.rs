@@ -0,0 +1,538 @@
+// Copyright 2020-2024 Trust Computing GmbH.
+// This file is part of Litentry.
+//
+// Litentry is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// Litentry is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with Litentry.  If not, see <https://www.gnu.org/licenses/>.
+
+#![cfg_attr(not(feature = "std"), no_std)]
+
+extern crate core;
+#[cfg(all(not(feature = "std"), feature = "sgx"))]
+extern crate sgx_tstd as std;
+
+#[cfg(all(feature = "std", feature = "sgx"))]
+compile_error!("feature \"std\" and feature \"sgx\" cannot be enabled at the same time");
+
+use binary_merkle_tree::{merkle_proof, merkle_root, verify_proof};
+use codec::{Decode, Encode};
+use itp_binary_merkle_tree::MerkleProofWithCodec;
+use litentry_primitives::Identity;
+use log::*;
+use sp_core::{blake2_256, H256};
+use sp_runtime::traits::{Hash, Keccak256};
+use sp_std::boxed::Box;
+use std::{path::PathBuf, vec::Vec};
+
+#[cfg(feature = "std")]
+use std::sync::RwLock;
+#[cfg(feature = "sgx")]
+use std::sync::SgxRwLock as RwLock;
+
+// ceremonies time out after 30 seconds, entries of ceremonies which never ended are dropped
+// after twice that long
+const PENDING_TIME_TO_LIVE: u64 = 60;
+
+/// Number of entries sealed together in one file, appending only reseals the last segment.
+pub const SEGMENT_LEN: u64 = 1024;
+
+/// Proof that an encoded [`AuditEntry`] is part of the log covered by `root`.
+pub type AuditProof = MerkleProofWithCodec<H256, Vec<u8>>;
+
+#[derive(Encode, Decode, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SigningChain {
+	Bitcoin,
+	Ethereum,
+	Ton,
+	Solana,
+}
+
+/// A signature this enclave handed out to a relayer.
+#[derive(Encode, Decode, Clone, Debug, PartialEq, Eq)]
+pub struct AuditEntry {
+	pub relayer: Identity,
+	// blake2_256 of the payload as the relayer submitted it
+	pub payload_hash: [u8; 32],
+	pub chain: SigningChain,
+	// blake2_256 of the ceremony id, for signatures produced together with other enclaves
+	pub ceremony_id: Option<[u8; 32]>,
+	// seconds since the unix epoch
+	pub timestamp: u64,
+}
+
+impl AuditEntry {
+	pub fn new(
+		relayer: Identity,
+		chain: SigningChain,
+		payload: &[u8],
+		ceremony_id: Option<[u8; 32]>,
+		timestamp: u64,
+	) -> Self {
+		Self { relayer, payload_hash: blake2_256(payload), chain, ceremony_id, timestamp }
+	}
+}
+
+/// Entries of the log, each leaf of the Merkle tree is an encoded entry.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct AuditLogState {
+	entries: Vec<AuditEntry>,
+	// number of entries covered by the root posted last
+	pub posted_size: u64,
+	// roots of the perfect subtrees the entries split into, indexed by height. The tree promotes
+	// odd nodes, so its root folds these from the lowest up
+	frontier: Vec<Option<H256>>,
+}
+
+impl AuditLogState {
+	pub fn new(entries: Vec<AuditEntry>, posted_size: u64) -> Self {
+		let mut state = Self { posted_size, ..Default::default() };
+		entries.into_iter().for_each(|entry| state.push(entry));
+		state
+	}
+
+	pub fn entries(&self) -> &[AuditEntry] {
+		&self.entries
+	}
+
+	/// Appends `entry`, the root of the whole log is kept up to date on the way.
+	pub fn push(&mut self, entry: AuditEntry) {
+		let mut node = Keccak256::hash(&entry.encode());
+		for subtree in self.frontier.iter_mut() {
+			match subtree.take() {
+				Some(left) => node = inner_node(&left, &node),
+				None => {
+					*subtree = Some(node);
+					self.entries.push(entry);
+					return
+				},
+			}
+		}
+		self.frontier.push(Some(node));
+		self.entries.push(entry);
+	}
+
+	/// Index of the segment the next entry goes to, with the entries already in it.
+	pub fn last_segment(&self) -> (u64, &[AuditEntry]) {
+		let index = self.entries.len() as u64 / SEGMENT_LEN;
+		(index, &self.entries[(index * SEGMENT_LEN) as usize..])
+	}
+
+	/// Root of the first `size` entries.
+	pub fn root(&self, size: u64) -> Option<H256> {
+		if size == self.entries.len() as u64 {
+			let root = self.frontier.iter().flatten().fold(None, |right, subtree| {
+				Some(right.map_or(*subtree, |right| inner_node(subtree, &right)))
+			});
+			return Some(root.unwrap_or_default())
+		}
+		let entries = self.entries.get(..size as usize)?;
+		Some(merkle_root::<Keccak256, _>(entries.iter().map(Encode::encode)))
+	}
+
+	/// Proof of the entry at `index` against the root of the first `size` entries.
+	pub fn proof(&self, index: u64, size: u64) -> Option<AuditProof> {
+		let entries = self.entries.get(..size as usize)?;
+		if index >= size {
+			return None
+		}
+		let leaves: Vec<Vec<u8>> = entries.iter().map(Encode::encode).collect();
+		Some(merkle_proof::<Keccak256, _, _>(leaves, index as usize).into())
+	}
+}
+
+// inner nodes of `binary_merkle_tree` hash the concatenation of their children
+fn inner_node(left: &H256, right: &H256) -> H256 {
+	Keccak256::hash(&[left.as_bytes(), right.as_bytes()].concat())
+}
+
+/// Checks `proof` the way an auditor would, against a root posted on the parentchain.
+pub fn verify_audit_proof(root: &H256, proof: &AuditProof) -> bool {
+	&proof.root == root
+		&& verify_proof::<Keccak256, _, _>(
+			root,
+			proof.proof.clone(),
+			proof.number_of_leaves as usize,
+			proof.leaf_index as usize,
+			proof.leaf.as_slice(),
+		)
+}
+
+pub struct SignatureAuditLog {
+	pub state: RwLock<AuditLogState>,
+	// entries waiting for their ceremony to end, they are not sealed
+	pub pending: RwLock<Vec<AuditEntry>>,
+	pub seal_path: PathBuf,
+}
+
+impl SignatureAuditLog {
+	pub fn new(base_dir: PathBuf) -> Self {
+		SignatureAuditLog {
+			state: Default::default(),
+			pending: Default::default(),
+			seal_path: base_dir,
+		}
+	}
+}
+
+pub type RegistryResult<T> = core::result::Result<T, RegistryError>;
+
+#[cfg(feature = "sgx")]
+use thiserror_sgx as thiserror;
+
+#[derive(Debug, thiserror::Error)]
+pub enum RegistryError {
+	#[error("poison lock")]
+	PoisonLock,
+	#[error(transparent)]
+	Other(#[from] Box<dyn std::error::Error + Sync + Send + 'static>),
+}
+
+impl From<std::io::Error> for RegistryError {
+	fn from(e: std::io::Error) -> Self {
+		Self::Other(e.into())
+	}
+}
+
+impl From<codec::Error> for RegistryError {
+	#[cfg(feature = "std")]
+	fn from(e: codec::Error) -> Self {
+		Self::Other(e.into())
+	}
+
+	#[cfg(feature = "sgx")]
+	fn from(e: codec::Error) -> Self {
+		Self::Other(std::format!("{:?}", e).into())
+	}
+}
+
+#[cfg(feature = "sgx")]
+mod sgx {
+	use crate::{AuditEntry, AuditLogState, RegistryError as Error, RegistryResult as Result};
+	pub use codec::{Decode, Encode};
+	pub use itp_settings::files::{SIGNATURE_AUDIT_LOG_FILE, SIGNATURE_AUDIT_LOG_SEGMENTS_PATH};
+	pub use itp_sgx_io::{seal, unseal};
+	pub use log::*;
+	pub use std::{format, path::PathBuf, vec::Vec};
+
+	/// Size of the log and what was posted of it, the entries are sealed in segments.
+	#[derive(Encode, Decode, Clone, Copy, Debug, Default)]
+	struct AuditLogHead {
+		size: u64,
+		posted_size: u64,
+	}
+
+	#[derive(Clone, Debug)]
+	pub struct SignatureAuditLogSeal {
+		base_path: PathBuf,
+	}
+
+	impl SignatureAuditLogSeal {
+		pub fn new(base_path: PathBuf) -> Self {
+			Self { base_path }
+		}
+
+		pub fn path(&self) -> PathBuf {
+			self.base_path.join(SIGNATURE_AUDIT_LOG_FILE)
+		}
+
+		fn segment_path(&self, index: u64) -> PathBuf {
+			self.base_path
+				.join(SIGNATURE_AUDIT_LOG_SEGMENTS_PATH)
+				.join(format!("{}.bin", index))
+		}
+
+		pub fn unseal(&self) -> Result<AuditLogState> {
+			if !self.path().exists() {
+				info!("Signature audit log not found: {}", self.path().display());
+				return Ok(Default::default())
+			}
+			let head: AuditLogHead = Decode::decode(&mut unseal(self.path())?.as_slice())?;
+			let mut entries = Vec::with_capacity(head.size as usize);
+			let mut index = 0;
+			while (entries.len() as u64) < head.size {
+				let segment: Vec<AuditEntry> =
+					Decode::decode(&mut unseal(self.segment_path(index))?.as_slice())?;
+				if segment.is_empty() {
+					break
+				}
+				entries.extend(segment);
+				index += 1;
+			}
+			if (entries.len() as u64) < head.size {
+				return Err(Error::Other(
+					format!("Signature audit log lost entries beyond {}", entries.len()).into(),
+				))
+			}
+			// the head is sealed after the segment, entries it doesn't count were never confirmed
+			entries.truncate(head.size as usize);
+			Ok(AuditLogState::new(entries, head.posted_size))
+		}
+
+		/// Seals the segment `entry` goes to and then the head counting it.
+		pub fn seal_appended(&self, state: &AuditLogState, entry: &AuditEntry) -> Result<()> {
+			let (index, segment) = state.last_segment();
+			std::fs::create_dir_all(self.base_path.join(SIGNATURE_AUDIT_LOG_SEGMENTS_PATH))?;
+			let mut segment = segment.to_vec();
+			segment.push(entry.clone());
+			debug!("Seal segment {} of signature audit log with {} entries", index, segment.len());
+			segment.using_encoded(|bytes| seal(bytes, self.segment_path(index)))?;
+			let size = state.entries().len() as u64 + 1;
+			self.seal_head(AuditLogHead { size, posted_size: state.posted_size })
+		}
+
+		pub fn seal_posted_size(&self, state: &AuditLogState) -> Result<()> {
+			let size = state.entries().len() as u64;
+			self.seal_head(AuditLogHead { size, posted_size: state.posted_size })
+		}
+
+		fn seal_head(&self, head: AuditLogHead) -> Result<()> {
+			Ok(head.using_encoded(|bytes| seal(bytes, self.path()))?)
+		}
+	}
+}
+
+#[cfg(feature = "sgx")]
+use sgx::*;
+
+pub trait SignatureAuditLogUpdater {
+	fn init(&self) -> RegistryResult<()>;
+	/// Appends `entry` to the log, it's sealed before this returns.
+	fn append(&self, entry: AuditEntry) -> RegistryResult<()>;
+	/// Holds `entry` back until its ceremony ended, see [`Self::ceremony_ended`].
+	fn await_ceremony(&self, entry: AuditEntry, now: u64) -> RegistryResult<()>;
+	/// Appends the entry waiting for the ceremony if a signature was produced, drops it otherwise.
+	fn ceremony_ended(&self, ceremony_id: &[u8; 32], signed: bool) -> RegistryResult<()>;
+	/// Records that the root of the first `size` entries was posted on the parentchain.
+	fn root_posted(&self, size: u64) -> RegistryResult<()>;
+}
+
+pub trait SignatureAuditLogLookup {
+	fn size(&self) -> u64;
+	fn posted_size(&self) -> u64;
+	/// See [`AuditLogState::root`]
+	fn root(&self, size: u64) -> Option<H256>;
+	/// See [`AuditLogState::proof`]
+	fn proof(&self, index: u64, size: u64) -> Option<AuditProof>;
+}
+
+impl SignatureAuditLog {
+	#[cfg(feature = "std")]
+	fn seal_appended(&self, _state: &AuditLogState, _entry: &AuditEntry) -> RegistryResult<()> {
+		Ok(())
+	}
+
+	#[cfg(feature = "sgx")]
+	fn seal_appended(&self, state: &AuditLogState, entry: &AuditEntry) -> RegistryResult<()> {
+		SignatureAuditLogSeal::new(self.seal_path.clone()).seal_appended(state, entry)
+	}
+
+	#[cfg(feature = "std")]
+	fn seal_posted_size(&self, _state: &AuditLogState) -> RegistryResult<()> {
+		Ok(())
+	}
+
+	#[cfg(feature = "sgx")]
+	fn seal_posted_size(&self, state: &AuditLogState) -> RegistryResult<()> {
+		SignatureAuditLogSeal::new(self.seal_path.clone()).seal_posted_size(state)
+	}
+}
+
+impl SignatureAuditLogUpdater for SignatureAuditLog {
+	#[cfg(feature = "std")]
+	fn init(&self) -> RegistryResult<()> {
+		Ok(())
+	}
+
+	#[cfg(feature = "sgx")]
+	fn init(&self) -> RegistryResult<()> {
+		let unsealed = SignatureAuditLogSeal::new(self.seal_path.clone()).unseal()?;
+		info!("Signature audit log unsealed with {} entries", unsealed.entries().len());
+		*self.state.write().map_err(|_| RegistryError::PoisonLock)? = unsealed;
+		Ok(())
+	}
+
+	fn append(&self, entry: AuditEntry) -> RegistryResult<()> {
+		let mut state = self.state.write().map_err(|_| RegistryError::PoisonLock)?;
+		// the entry is only kept if it made it into the sealed log
+		self.seal_appended(&state, &entry)?;
+		state.push(entry);
+		Ok(())
+	}
+
+	fn await_ceremony(&self, entry: AuditEntry, now: u64) -> RegistryResult<()> {
+		let mut pending = self.pending.write().map_err(|_| RegistryError::PoisonLock)?;
+		pending.retain(|e| now.saturating_sub(e.timestamp) < PENDING_TIME_TO_LIVE);
+		pending.push(entry);
+		Ok(())
+	}
+
+	fn ceremony_ended(&self, ceremony_id: &[u8; 32], signed: bool) -> RegistryResult<()> {
+		let entry = {
+			let mut pending = self.pending.write().map_err(|_| RegistryError::PoisonLock)?;
+			match pending.iter().position(|e| e.ceremony_id.as_ref() == Some(ceremony_id)) {
+				Some(index) => pending.remove(index),
+				None => return Ok(()),
+			}
+		};
+		if signed {
+			self.append(entry)?;
+		}
+		Ok(())
+	}
+
+	fn root_posted(&self, size: u64) -> RegistryResult<()> {
+		let mut state = self.state.write().map_err(|_| RegistryError::PoisonLock)?;
+		state.posted_size = state.posted_size.max(size);
+		self.seal_posted_size(&state)
+	}
+}
+
+impl SignatureAuditLogLookup for SignatureAuditLog {
+	fn size(&self) -> u64 {
+		self.state.read().map(|state| state.entries().len() as u64).unwrap_or_default()
+	}
+
+	fn posted_size(&self) -> u64 {
+		self.state.read().map(|state| state.posted_size).unwrap_or_default()
+	}
+
+	fn root(&self, size: u64) -> Option<H256> {
+		self.state.read().ok()?.root(size)
+	}
+
+	fn proof(&self, index: u64, size: u64) -> Option<AuditProof> {
+		self.state.read().ok()?.proof(index, size)
+	}
+}
+
+#[cfg(test)]
+mod test {
+	use super::{
+		verify_audit_proof, AuditEntry, AuditLogState, SignatureAuditLog, SignatureAuditLogLookup,
+		SignatureAuditLogUpdater, SigningChain, SEGMENT_LEN,
+	};
+	use binary_merkle_tree::merkle_root;
+	use codec::Encode;
+	use litentry_primitives::Identity;
+	use sp_runtime::traits::Keccak256;
+	use std::path::PathBuf;
+
+	fn entry(payload: u8, ceremony_id: Option<[u8; 32]>) -> AuditEntry {
+		let relayer = Identity::Substrate([1u8; 32].into());
+		AuditEntry::new(relayer, SigningChain::Bitcoin, &[payload], ceremony_id, 10)
+	}
+
+	#[test]
+	pub fn it_should_prove_entry_against_posted_root() {
+		// given
+		let log = SignatureAuditLog::new(PathBuf::new());
+		(0..5).for_each(|payload| log.append(entry(payload, None)).unwrap());
+		let root = log.root(3).unwrap();
+		log.append(entry(5, None)).unwrap();
+
+		// when
+		let proof = log.proof(1, 3).unwrap();
+
+		// then
+		assert!(verify_audit_proof(&root, &proof));
+		assert_eq!(proof.leaf, codec::Encode::encode(&entry(1, None)));
+		assert!(!verify_audit_proof(&log.root(6).unwrap(), &proof));
+	}
+
+	#[test]
+	pub fn it_should_not_prove_tampered_entry() {
+		// given
+		let log = SignatureAuditLog::new(PathBuf::new());
+		(0..4).for_each(|payload| log.append(entry(payload, None)).unwrap());
+		let root = log.root(4).unwrap();
+		let mut proof = log.proof(2, 4).unwrap();
+
+		// when
+		proof.leaf = codec::Encode::encode(&entry(9, None));
+
+		// then
+		assert!(!verify_audit_proof(&root, &proof));
+	}
+
+	#[test]
+	pub fn it_should_not_prove_beyond_log() {
+		let log = SignatureAuditLog::new(PathBuf::new());
+		log.append(entry(0, None)).unwrap();
+
+		assert!(log.root(2).is_none());
+		assert!(log.proof(1, 1).is_none());
+	}
+
+	#[test]
+	pub fn it_should_append_entry_once_ceremony_signed() {
+		// given
+		let log = SignatureAuditLog::new(PathBuf::new());
+		log.await_ceremony(entry(0, Some([2; 32])), 10).unwrap();
+		log.await_ceremony(entry(1, Some([3; 32])), 10).unwrap();
+
+		// when
+		log.ceremony_ended(&[2; 32], true).unwrap();
+		log.ceremony_ended(&[3; 32], false).unwrap();
+
+		// then
+		assert_eq!(log.size(), 1);
+		assert_eq!(log.state.read().unwrap().entries()[0], entry(0, Some([2; 32])));
+		assert!(log.pending.read().unwrap().is_empty());
+	}
+
+	#[test]
+	pub fn it_should_drop_entries_of_stale_ceremonies() {
+		// given
+		let log = SignatureAuditLog::new(PathBuf::new());
+		log.await_ceremony(entry(0, Some([2; 32])), 10).unwrap();
+
+		// when
+		log.await_ceremony(entry(1, Some([3; 32])), 100).unwrap();
+		log.ceremony_ended(&[2; 32], true).unwrap();
+
+		// then
+		assert_eq!(log.size(), 0);
+		assert_eq!(log.pending.read().unwrap().len(), 1);
+	}
+
+	#[test]
+	pub fn it_should_keep_root_of_merkle_tree_while_appending() {
+		// given
+		let mut state = AuditLogState::default();
+		assert_eq!(state.root(0), Some(Default::default()));
+
+		for payload in 0..=20 {
+			// when
+			state.push(entry(payload, None));
+
+			// then
+			let size = state.entries().len() as u64;
+			let expected = merkle_root::<Keccak256, _>(state.entries().iter().map(Encode::encode));
+			assert_eq!(state.root(size), Some(expected));
+		}
+	}
+
+	#[test]
+	pub fn it_should_start_next_segment_once_full() {
+		// given
+		let mut state =
+			AuditLogState::new((0..SEGMENT_LEN).map(|i| entry(i as u8, None)).collect(), 0);
+		assert_eq!(state.last_segment().0, 1);
+		assert!(state.last_segment().1.is_empty());
+
+		// when
+		state.push(entry(0, None));
+
+		// then
+		assert_eq!(state.last_segment(), (1, &[entry(0, None)][..]));
+	}
+}
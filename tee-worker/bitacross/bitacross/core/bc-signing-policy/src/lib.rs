@@ -0,0 +1,411 @@
+// Copyright 2020-2024 Trust Computing GmbH.
+// This file is part of Litentry.
+//
+// Litentry is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// Litentry is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with Litentry.  If not, see <https://www.gnu.org/licenses/>.
+
+#![cfg_attr(not(feature = "std"), no_std)]
+
+extern crate core;
+#[cfg(all(not(feature = "std"), feature = "sgx"))]
+extern crate sgx_tstd as std;
+
+#[cfg(all(feature = "std", feature = "sgx"))]
+compile_error!("feature \"std\" and feature \"sgx\" cannot be enabled at the same time");
+
+use codec::{Decode, Encode};
+use litentry_primitives::{BtcSigningPolicy, EthSigningAllowlist, Identity};
+use log::*;
+use sp_std::boxed::Box;
+use std::{path::PathBuf, vec::Vec};
+
+#[cfg(feature = "std")]
+use std::sync::RwLock;
+#[cfg(feature = "sgx")]
+use std::sync::SgxRwLock as RwLock;
+
+pub mod psbt;
+
+pub use psbt::{p2tr_script, parse_psbt_spend, PsbtError, PsbtSpend};
+
+const SECONDS_PER_DAY: u64 = 86_400;
+
+#[derive(Encode, Debug, PartialEq, Eq)]
+pub enum PolicyError {
+	NoPolicy,
+	DestinationNotAllowed(Vec<u8>),
+	// amount the transaction would move out of the vault
+	TxCapExceeded(u64),
+	// amount moved out of the vault today including the transaction
+	DailyCapExceeded(u64),
+	StateUnavailable,
+}
+
+/// Policies published on the parentchain together with what was already signed for today.
+#[derive(Encode, Decode, Clone, Debug, Default, PartialEq, Eq)]
+pub struct SigningPolicyState {
+	pub policy: Option<BtcSigningPolicy>,
+	// days since the unix epoch
+	pub day: u64,
+	pub spent_today: u64,
+	// each input of a transaction is signed separately, its amount is only booked once
+	pub booked_today: Vec<[u8; 32]>,
+	pub eth_allowlists: Vec<(Identity, EthSigningAllowlist)>,
+}
+
+impl SigningPolicyState {
+	/// Checks `spend` against the policy and books its amount on the day `now` falls on.
+	///
+	/// `now` comes from the host, the clock is not allowed to go back but a compromised host
+	/// can still skip ahead. The caps limit what a compromised relayer can do, not a compromised
+	/// relayer together with a compromised host.
+	///
+	/// Every enclave books the spends it was asked to sign in its own sealed state. A spend is
+	/// only signed once every signer of the ceremony authorized it, so the daily cap holds for
+	/// the vault, see `sign_bitcoin_psbt::handle` of `lc-direct-call`.
+	pub fn authorize(&mut self, spend: &PsbtSpend, now: u64) -> Result<(), PolicyError> {
+		let policy = self.policy.as_ref().ok_or(PolicyError::NoPolicy)?;
+		if let Some((script, _)) = spend
+			.destinations
+			.iter()
+			.find(|(script, _)| !policy.allowed_destinations.iter().any(|d| d.as_slice() == script))
+		{
+			return Err(PolicyError::DestinationNotAllowed(script.clone()))
+		}
+		if spend.outflow > policy.max_amount_per_tx {
+			return Err(PolicyError::TxCapExceeded(spend.outflow))
+		}
+
+		let day = now / SECONDS_PER_DAY;
+		let (spent_today, booked_today) = if day > self.day {
+			(0, Vec::new())
+		} else {
+			(self.spent_today, self.booked_today.clone())
+		};
+		if booked_today.contains(&spend.txid) {
+			return Ok(())
+		}
+		let spent = spent_today.saturating_add(spend.outflow);
+		if spent > policy.max_amount_per_day {
+			return Err(PolicyError::DailyCapExceeded(spent))
+		}
+
+		self.day = self.day.max(day);
+		self.spent_today = spent;
+		self.booked_today = booked_today;
+		self.booked_today.push(spend.txid);
+		Ok(())
+	}
+}
+
+pub struct SigningPolicyRegistry {
+	pub state: RwLock<SigningPolicyState>,
+	pub seal_path: PathBuf,
+}
+
+impl SigningPolicyRegistry {
+	pub fn new(base_dir: PathBuf) -> Self {
+		SigningPolicyRegistry { state: Default::default(), seal_path: base_dir }
+	}
+}
+
+pub type RegistryResult<T> = core::result::Result<T, RegistryError>;
+
+#[cfg(feature = "sgx")]
+use thiserror_sgx as thiserror;
+
+#[derive(Debug, thiserror::Error)]
+pub enum RegistryError {
+	#[error("poison lock")]
+	PoisonLock,
+	#[error(transparent)]
+	Other(#[from] Box<dyn std::error::Error + Sync + Send + 'static>),
+}
+
+impl From<std::io::Error> for RegistryError {
+	fn from(e: std::io::Error) -> Self {
+		Self::Other(e.into())
+	}
+}
+
+impl From<codec::Error> for RegistryError {
+	#[cfg(feature = "std")]
+	fn from(e: codec::Error) -> Self {
+		Self::Other(e.into())
+	}
+
+	#[cfg(feature = "sgx")]
+	fn from(e: codec::Error) -> Self {
+		Self::Other(std::format!("{:?}", e).into())
+	}
+}
+
+#[cfg(feature = "sgx")]
+mod sgx {
+	use crate::{RegistryError as Error, RegistryResult as Result, SigningPolicyState};
+	pub use codec::{Decode, Encode};
+	pub use itp_settings::files::BTC_SIGNING_POLICY_FILE;
+	pub use itp_sgx_io::{seal, unseal, SealedIO};
+	pub use log::*;
+	pub use std::path::PathBuf;
+
+	#[derive(Clone, Debug)]
+	pub struct SigningPolicySeal {
+		base_path: PathBuf,
+	}
+
+	impl SigningPolicySeal {
+		pub fn new(base_path: PathBuf) -> Self {
+			Self { base_path }
+		}
+
+		pub fn path(&self) -> PathBuf {
+			self.base_path.join(BTC_SIGNING_POLICY_FILE)
+		}
+	}
+
+	impl SealedIO for SigningPolicySeal {
+		type Error = Error;
+		type Unsealed = SigningPolicyState;
+
+		fn unseal(&self) -> Result<Self::Unsealed> {
+			if !self.path().exists() {
+				info!("Signing policy not found: {}", self.path().display());
+				return Ok(Default::default())
+			}
+			Ok(unseal(self.path()).map(|b| Decode::decode(&mut b.as_slice()))??)
+		}
+
+		fn seal(&self, unsealed: &Self::Unsealed) -> Result<()> {
+			info!("Seal signing policy to file: {:?}", unsealed);
+			Ok(unsealed.using_encoded(|bytes| seal(bytes, self.path()))?)
+		}
+	}
+}
+
+#[cfg(feature = "sgx")]
+use sgx::*;
+
+pub trait SigningPolicyUpdater {
+	fn init(&self) -> RegistryResult<()>;
+	fn set_policy(&self, policy: BtcSigningPolicy) -> RegistryResult<()>;
+	fn set_eth_allowlist(
+		&self,
+		relayer: Identity,
+		allowlist: Option<EthSigningAllowlist>,
+	) -> RegistryResult<()>;
+}
+
+pub trait SigningPolicyEnforcer {
+	/// Authorizes signing for `spend`, see [`SigningPolicyState::authorize`]
+	fn authorize(&self, spend: &PsbtSpend, now: u64) -> Result<(), PolicyError>;
+	/// Whether a policy is published, raw payloads can't be checked against it
+	fn is_enforced(&self) -> bool;
+}
+
+pub trait EthAllowlistLookup {
+	fn eth_allowlist(&self, relayer: &Identity) -> Option<EthSigningAllowlist>;
+}
+
+impl SigningPolicyRegistry {
+	#[cfg(feature = "std")]
+	fn seal(&self, _state: &SigningPolicyState) -> RegistryResult<()> {
+		Ok(())
+	}
+
+	#[cfg(feature = "sgx")]
+	fn seal(&self, state: &SigningPolicyState) -> RegistryResult<()> {
+		SigningPolicySeal::new(self.seal_path.clone()).seal(state)
+	}
+}
+
+impl SigningPolicyUpdater for SigningPolicyRegistry {
+	#[cfg(feature = "std")]
+	fn init(&self) -> RegistryResult<()> {
+		Ok(())
+	}
+
+	// unseal the policy and what was signed for today, so a restart doesn't reset the daily cap
+	#[cfg(feature = "sgx")]
+	fn init(&self) -> RegistryResult<()> {
+		let unsealed = SigningPolicySeal::new(self.seal_path.clone()).unseal()?;
+		info!("Signing policy unsealed from file: {:?}", unsealed);
+		*self.state.write().map_err(|_| RegistryError::PoisonLock)? = unsealed;
+		Ok(())
+	}
+
+	fn set_policy(&self, policy: BtcSigningPolicy) -> RegistryResult<()> {
+		let mut state = self.state.write().map_err(|_| RegistryError::PoisonLock)?;
+		state.policy = Some(policy);
+		self.seal(&state)
+	}
+
+	fn set_eth_allowlist(
+		&self,
+		relayer: Identity,
+		allowlist: Option<EthSigningAllowlist>,
+	) -> RegistryResult<()> {
+		let mut state = self.state.write().map_err(|_| RegistryError::PoisonLock)?;
+		state.eth_allowlists.retain(|(r, _)| r != &relayer);
+		if let Some(allowlist) = allowlist {
+			state.eth_allowlists.push((relayer, allowlist));
+		}
+		self.seal(&state)
+	}
+}
+
+impl SigningPolicyEnforcer for SigningPolicyRegistry {
+	fn authorize(&self, spend: &PsbtSpend, now: u64) -> Result<(), PolicyError> {
+		let mut state = self.state.write().map_err(|_| PolicyError::StateUnavailable)?;
+		let mut updated = state.clone();
+		updated.authorize(spend, now)?;
+		// nothing is signed for if the booking can't be persisted
+		self.seal(&updated).map_err(|e| {
+			error!("Could not seal signing policy: {:?}", e);
+			PolicyError::StateUnavailable
+		})?;
+		*state = updated;
+		Ok(())
+	}
+
+	fn is_enforced(&self) -> bool {
+		// a poisoned lock leaves it open whether a policy is published, so better assume it is
+		self.state.read().map(|state| state.policy.is_some()).unwrap_or(true)
+	}
+}
+
+impl EthAllowlistLookup for SigningPolicyRegistry {
+	fn eth_allowlist(&self, relayer: &Identity) -> Option<EthSigningAllowlist> {
+		let state = self.state.read().ok()?;
+		state.eth_allowlists.iter().find(|(r, _)| r == relayer).map(|(_, a)| a.clone())
+	}
+}
+
+#[cfg(test)]
+mod test {
+	use super::{
+		psbt::test::destination_script, EthAllowlistLookup, PolicyError, PsbtSpend,
+		SigningPolicyRegistry, SigningPolicyState, SigningPolicyUpdater,
+	};
+	use litentry_primitives::{
+		BtcScript, BtcSigningPolicy, EthContract, EthSigningAllowlist, Identity,
+	};
+	use std::path::PathBuf;
+
+	fn state() -> SigningPolicyState {
+		let destination: BtcScript = destination_script().try_into().unwrap();
+		let policy = BtcSigningPolicy {
+			allowed_destinations: vec![destination].try_into().unwrap(),
+			max_amount_per_tx: 1_000,
+			max_amount_per_day: 1_500,
+		};
+		SigningPolicyState { policy: Some(policy), ..Default::default() }
+	}
+
+	fn spend(txid: u8, outflow: u64) -> PsbtSpend {
+		PsbtSpend {
+			txid: [txid; 32],
+			sighash: [0; 32],
+			outflow,
+			destinations: vec![(destination_script(), outflow)],
+		}
+	}
+
+	#[test]
+	pub fn it_should_enforce_daily_cap() {
+		// given
+		let mut state = state();
+		state.authorize(&spend(1, 1_000), 10).unwrap();
+
+		// when
+		let result = state.authorize(&spend(2, 600), 20);
+
+		// then
+		assert_eq!(result, Err(PolicyError::DailyCapExceeded(1_600)));
+		assert_eq!(state.spent_today, 1_000);
+	}
+
+	#[test]
+	pub fn it_should_reset_daily_cap_on_next_day() {
+		// given
+		let mut state = state();
+		state.authorize(&spend(1, 1_000), 10).unwrap();
+
+		// when
+		let result = state.authorize(&spend(2, 600), 86_400 + 10);
+
+		// then
+		assert!(result.is_ok());
+		assert_eq!(state.spent_today, 600);
+	}
+
+	#[test]
+	pub fn it_should_book_each_transaction_once() {
+		// given
+		let mut state = state();
+		state.authorize(&spend(1, 1_000), 10).unwrap();
+
+		// when
+		let result = state.authorize(&spend(1, 1_000), 20);
+
+		// then
+		assert!(result.is_ok());
+		assert_eq!(state.spent_today, 1_000);
+	}
+
+	#[test]
+	pub fn it_should_enforce_tx_cap() {
+		let mut state = state();
+		assert_eq!(state.authorize(&spend(1, 1_001), 10), Err(PolicyError::TxCapExceeded(1_001)));
+	}
+
+	#[test]
+	pub fn it_should_reject_unknown_destination() {
+		// given
+		let mut state = state();
+		let mut spend = spend(1, 100);
+		spend.destinations.push((vec![0x00, 0x14], 1));
+
+		// when
+		let result = state.authorize(&spend, 10);
+
+		// then
+		assert_eq!(result, Err(PolicyError::DestinationNotAllowed(vec![0x00, 0x14])))
+	}
+
+	#[test]
+	pub fn it_should_not_sign_without_policy() {
+		let mut state = SigningPolicyState::default();
+		assert_eq!(state.authorize(&spend(1, 100), 10), Err(PolicyError::NoPolicy));
+	}
+
+	#[test]
+	pub fn it_should_replace_and_remove_eth_allowlist() {
+		// given
+		let registry = SigningPolicyRegistry::new(PathBuf::new());
+		let relayer = Identity::Substrate([1u8; 32].into());
+		let allowlist = |chain_id| EthSigningAllowlist {
+			contracts: vec![EthContract { chain_id, address: [2u8; 20] }].try_into().unwrap(),
+			selectors: Default::default(),
+		};
+		registry.set_eth_allowlist(relayer.clone(), Some(allowlist(1))).unwrap();
+
+		// when
+		registry.set_eth_allowlist(relayer.clone(), Some(allowlist(5))).unwrap();
+
+		// then
+		assert_eq!(registry.eth_allowlist(&relayer), Some(allowlist(5)));
+		registry.set_eth_allowlist(relayer.clone(), None).unwrap();
+		assert_eq!(registry.eth_allowlist(&relayer), None);
+	}
+}
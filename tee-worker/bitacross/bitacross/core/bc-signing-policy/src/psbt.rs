@@ -0,0 +1,237 @@
+// Copyright 2020-2024 Trust Computing GmbH.
+// This file is part of Litentry.
+//
+// Litentry is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// Litentry is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with Litentry.  If not, see <https://www.gnu.org/licenses/>.
+
+use bitcoin::{
+	hashes::Hash,
+	psbt::Psbt,
+	sighash::{Prevouts, SighashCache, TapSighashType},
+	TxOut,
+};
+use codec::Encode;
+use std::vec::Vec;
+
+/// Length of a P2TR scriptPubKey, `OP_1 OP_PUSHBYTES_32 <output key>`
+const P2TR_SCRIPT_LEN: usize = 34;
+
+#[derive(Encode, Debug, PartialEq, Eq)]
+pub enum PsbtError {
+	InvalidPsbt,
+	InputIndexOutOfRange,
+	// all prevouts are committed to by taproot sighashes, so every input needs its witness utxo
+	MissingWitnessUtxo,
+	InputNotFromVault,
+	UnsupportedSighashType,
+	SighashError,
+}
+
+/// What signing one input of a PSBT spending from the vault amounts to.
+#[derive(Debug, PartialEq, Eq)]
+pub struct PsbtSpend {
+	pub txid: [u8; 32],
+	// BIP341 key path sighash of the input, using `SIGHASH_DEFAULT`
+	pub sighash: [u8; 32],
+	// value of the spent vault outputs minus the change going back to the vault
+	pub outflow: u64,
+	// outputs not going back to the vault
+	pub destinations: Vec<(Vec<u8>, u64)>,
+}
+
+/// scriptPubKey of the key path only taproot output with the given x-only output key
+pub fn p2tr_script(output_key: &[u8; 32]) -> Vec<u8> {
+	let mut script = Vec::with_capacity(P2TR_SCRIPT_LEN);
+	// OP_1 OP_PUSHBYTES_32
+	script.extend_from_slice(&[0x51, 0x20]);
+	script.extend_from_slice(output_key);
+	script
+}
+
+/// Parses a serialized PSBT and computes what signing `input_index` with the vault key means.
+///
+/// The sighash is recomputed from the unsigned transaction and the witness utxos, nothing the
+/// PSBT claims about it is taken over.
+pub fn parse_psbt_spend(
+	psbt: &[u8],
+	input_index: u32,
+	vault_script: &[u8],
+) -> Result<PsbtSpend, PsbtError> {
+	let psbt = Psbt::deserialize(psbt).map_err(|_| PsbtError::InvalidPsbt)?;
+	let input_index = input_index as usize;
+	let input = psbt.inputs.get(input_index).ok_or(PsbtError::InputIndexOutOfRange)?;
+	match input.sighash_type.map(|t| t.taproot_hash_ty()) {
+		None | Some(Ok(TapSighashType::Default)) => {},
+		_ => return Err(PsbtError::UnsupportedSighashType),
+	}
+
+	let prevouts = psbt
+		.inputs
+		.iter()
+		.map(|i| i.witness_utxo.clone().ok_or(PsbtError::MissingWitnessUtxo))
+		.collect::<Result<Vec<TxOut>, PsbtError>>()?;
+	if prevouts[input_index].script_pubkey.as_bytes() != vault_script {
+		return Err(PsbtError::InputNotFromVault)
+	}
+
+	let sighash = SighashCache::new(&psbt.unsigned_tx)
+		.taproot_key_spend_signature_hash(
+			input_index,
+			&Prevouts::All(&prevouts),
+			TapSighashType::Default,
+		)
+		.map_err(|_| PsbtError::SighashError)?;
+
+	let spent: u64 = prevouts
+		.iter()
+		.filter(|p| p.script_pubkey.as_bytes() == vault_script)
+		.map(|p| p.value.to_sat())
+		.sum();
+	let mut change = 0u64;
+	let mut destinations = Vec::new();
+	for output in psbt.unsigned_tx.output.iter() {
+		if output.script_pubkey.as_bytes() == vault_script {
+			change = change.saturating_add(output.value.to_sat());
+		} else {
+			destinations.push((output.script_pubkey.to_bytes(), output.value.to_sat()));
+		}
+	}
+
+	Ok(PsbtSpend {
+		txid: psbt.unsigned_tx.txid().to_byte_array(),
+		sighash: sighash.to_byte_array(),
+		outflow: spent.saturating_sub(change),
+		destinations,
+	})
+}
+
+#[cfg(test)]
+pub mod test {
+	use super::{p2tr_script, parse_psbt_spend, PsbtError};
+	use bitcoin::{
+		absolute::LockTime,
+		hashes::Hash,
+		psbt::{Psbt, PsbtSighashType},
+		sighash::{Prevouts, SighashCache, TapSighashType},
+		transaction::Version,
+		Amount, OutPoint, ScriptBuf, Sequence, Transaction, TxIn, TxOut, Witness,
+	};
+
+	pub fn vault_script() -> Vec<u8> {
+		p2tr_script(&[1u8; 32])
+	}
+
+	pub fn destination_script() -> Vec<u8> {
+		p2tr_script(&[2u8; 32])
+	}
+
+	fn tx_out(script: Vec<u8>, value: u64) -> TxOut {
+		TxOut { value: Amount::from_sat(value), script_pubkey: ScriptBuf::from_bytes(script) }
+	}
+
+	// spends `inputs` and pays `outputs`, all prevouts are attached as witness utxos
+	pub fn psbt(inputs: Vec<TxOut>, outputs: Vec<TxOut>) -> Psbt {
+		let tx = Transaction {
+			version: Version::TWO,
+			lock_time: LockTime::ZERO,
+			input: (0..inputs.len())
+				.map(|vout| TxIn {
+					previous_output: OutPoint { vout: vout as u32, ..OutPoint::null() },
+					script_sig: ScriptBuf::new(),
+					sequence: Sequence::MAX,
+					witness: Witness::new(),
+				})
+				.collect(),
+			output: outputs,
+		};
+		let mut psbt = Psbt::from_unsigned_tx(tx).unwrap();
+		psbt.inputs
+			.iter_mut()
+			.zip(inputs)
+			.for_each(|(i, prevout)| i.witness_utxo = Some(prevout));
+		psbt
+	}
+
+	#[test]
+	pub fn it_should_compute_outflow_and_sighash() {
+		// given
+		let psbt = psbt(
+			vec![tx_out(vault_script(), 10_000), tx_out(vault_script(), 5_000)],
+			vec![tx_out(destination_script(), 6_000), tx_out(vault_script(), 8_500)],
+		);
+		let prevouts: Vec<TxOut> =
+			psbt.inputs.iter().map(|i| i.witness_utxo.clone().unwrap()).collect();
+		let expected_sighash = SighashCache::new(&psbt.unsigned_tx)
+			.taproot_key_spend_signature_hash(1, &Prevouts::All(&prevouts), TapSighashType::Default)
+			.unwrap();
+
+		// when
+		let spend = parse_psbt_spend(&psbt.serialize(), 1, &vault_script()).unwrap();
+
+		// then
+		assert_eq!(spend.outflow, 6_500);
+		assert_eq!(spend.destinations, vec![(destination_script(), 6_000)]);
+		assert_eq!(spend.sighash, expected_sighash.to_byte_array());
+		assert_eq!(spend.txid, psbt.unsigned_tx.txid().to_byte_array());
+	}
+
+	#[test]
+	pub fn it_should_not_sign_input_from_foreign_output() {
+		// given
+		let psbt = psbt(
+			vec![tx_out(vault_script(), 10_000), tx_out(destination_script(), 5_000)],
+			vec![tx_out(destination_script(), 14_000)],
+		);
+
+		// when
+		let result = parse_psbt_spend(&psbt.serialize(), 1, &vault_script());
+
+		// then
+		assert_eq!(result, Err(PsbtError::InputNotFromVault))
+	}
+
+	#[test]
+	pub fn it_should_require_all_witness_utxos() {
+		// given
+		let mut psbt = psbt(
+			vec![tx_out(vault_script(), 10_000), tx_out(vault_script(), 5_000)],
+			vec![tx_out(destination_script(), 14_000)],
+		);
+		psbt.inputs[1].witness_utxo = None;
+
+		// when
+		let result = parse_psbt_spend(&psbt.serialize(), 0, &vault_script());
+
+		// then
+		assert_eq!(result, Err(PsbtError::MissingWitnessUtxo))
+	}
+
+	#[test]
+	pub fn it_should_reject_non_default_sighash_type() {
+		// given
+		let mut psbt =
+			psbt(vec![tx_out(vault_script(), 10_000)], vec![tx_out(destination_script(), 9_000)]);
+		psbt.inputs[0].sighash_type = Some(PsbtSighashType::from(TapSighashType::None));
+
+		// when
+		let result = parse_psbt_spend(&psbt.serialize(), 0, &vault_script());
+
+		// then
+		assert_eq!(result, Err(PsbtError::UnsupportedSighashType))
+	}
+
+	#[test]
+	pub fn it_should_reject_garbage() {
+		assert_eq!(parse_psbt_spend(&[1, 2, 3], 0, &vault_script()), Err(PsbtError::InvalidPsbt))
+	}
+}
@@ -48,12 +48,22 @@ use std::sync::SgxMutex as Mutex;
 
 use bc_enclave_registry::EnclaveRegistryLookup;
 use bc_musig2_ceremony::{
-	get_current_timestamp, CeremonyCommand, CeremonyCommandTmp, CeremonyError, CeremonyErrorReason,
-	CeremonyEvent, CeremonyId, CeremonyRegistry, MuSig2Ceremony, SignBitcoinPayload,
+	get_current_timestamp, Ceremony, CeremonyCommand, CeremonyCommandTmp, CeremonyError,
+	CeremonyErrorReason, CeremonyEvent, CeremonyFailureHistory, CeremonyFailureReason, CeremonyId,
+	CeremonyRegistry, CommandDisposition, KeyGeneration, KeyGenerationCommand, KeyGenerationError,
+	KeyGenerationEvent, KeyGenerationId, KeyGenerationRegistry, MuSig2BatchCeremony,
+	MuSig2Ceremony, NoncePool, SignBitcoinPayload, ThresholdBatchCeremony, ThresholdCeremony,
+	ThresholdKeyShare,
+};
+use bc_musig2_event::{
+	process_event, process_key_generation_event, share_nonce_pool, DirectRequestStatus, Hash,
 };
-use bc_musig2_event::{process_event, DirectRequestStatus, Hash};
 use bc_relayer_registry::RelayerRegistryLookup;
+use bc_signature_audit_log::{
+	AuditEntry, SignatureAuditLogLookup, SignatureAuditLogUpdater, SigningChain,
+};
 use bc_signer_registry::SignerRegistryLookup;
+use bc_signing_policy::{EthAllowlistLookup, SigningPolicyEnforcer, SigningPolicyUpdater};
 use bc_task_sender::{
 	init_bit_across_task_sender_storage, BitAcrossProcessingResult, BitAcrossRequest,
 };
@@ -67,7 +77,7 @@ use itp_enclave_metrics::EnclaveMetric;
 use itp_ocall_api::{EnclaveAttestationOCallApi, EnclaveMetricsOCallApi, EnclaveOnChainOCallApi};
 use itp_sgx_crypto::{
 	ecdsa::Pair as EcdsaPair,
-	key_repository::{AccessKey, AccessPubkey},
+	key_repository::{AccessKey, AccessPubkey, MutateKey},
 	schnorr::Pair as SchnorrPair,
 	ShieldingCryptoDecrypt, ShieldingCryptoEncrypt,
 };
@@ -76,9 +86,14 @@ use itp_stf_executor::traits::StfEnclaveSigning;
 use itp_stf_state_handler::handle_state::HandleState;
 use lc_direct_call::{
 	handler::{
-		kill_ceremony, nonce_share, partial_signature_share,
+		batch_nonce_share, batch_partial_signature_share,
+		generate_threshold_key::{self, GenerateThresholdKeyError},
+		key_generation_package_share, kill_ceremony, nonce_pool_share, nonce_share,
+		partial_signature_share, reshare_threshold_key, resharing_package_share,
 		sign_bitcoin::{self, SignBitcoinError},
-		sign_ethereum, sign_ton,
+		sign_bitcoin_psbt::{self, SignBitcoinPsbtError},
+		sign_ethereum, sign_solana, sign_ton, threshold_batch_partial_signature_share,
+		threshold_partial_signature_share,
 	},
 	CeremonyRoundCall, CeremonyRoundCallSigned, DirectCall, DirectCallSigned,
 };
@@ -88,6 +103,7 @@ use sgx_crypto_helper::rsa3072::Rsa3072PubKey;
 use sp_core::{ed25519, Pair, H256};
 use std::{
 	collections::HashMap,
+	format,
 	string::{String, ToString},
 	sync::Arc,
 	vec,
@@ -95,6 +111,29 @@ use std::{
 };
 use threadpool::ThreadPool;
 
+// how often the root of the signature audit log is posted, if it has new entries
+const AUDIT_ROOT_POST_INTERVAL: Duration = Duration::from_secs(600);
+
+// how often the nonce pool is refilled and shared with the other signers
+const NONCE_POOL_SHARE_INTERVAL: Duration = Duration::from_secs(30);
+
+// commands created from incoming requests
+enum Command {
+	Ceremony(CeremonyId, CeremonyCommand),
+	KeyGeneration(KeyGenerationId, KeyGenerationCommand),
+}
+
+/// Records the threshold key this enclave holds a share of on the parentchain.
+pub trait PublishThresholdKey {
+	fn threshold_key_generated(&self, key_share: &ThresholdKeyShare) -> Result<(), String>;
+	fn threshold_key_reshared(&self, key_share: &ThresholdKeyShare) -> Result<(), String>;
+}
+
+/// Anchors the signature audit log on the parentchain.
+pub trait PublishAuditRoot {
+	fn signature_audit_root(&self, root: H256, size: u64) -> Result<(), String>;
+}
+
 #[derive(Debug, thiserror::Error, Clone)]
 pub enum Error {
 	#[error("Request error: {0}")]
@@ -110,12 +149,15 @@ pub struct BitAcrossTaskContext<
 	EKR,
 	BKR,
 	TKR,
+	KSR,
 	S: StfEnclaveSigning<TrustedCallSigned>,
 	H: HandleState,
 	O: EnclaveOnChainOCallApi,
 	RRL: RelayerRegistryLookup,
 	ERL: EnclaveRegistryLookup,
 	SRL: SignerRegistryLookup,
+	BSP: SigningPolicyEnforcer + SigningPolicyUpdater + EthAllowlistLookup,
+	SAL: SignatureAuditLogUpdater + SignatureAuditLogLookup,
 	Responder,
 > where
 	SKR: AccessKey + AccessPubkey<KeyType = Rsa3072PubKey>,
@@ -123,6 +165,7 @@ pub struct BitAcrossTaskContext<
 	EKR: AccessKey<KeyType = EcdsaPair>,
 	BKR: AccessKey<KeyType = SchnorrPair>,
 	TKR: AccessKey<KeyType = ed25519::Pair>,
+	KSR: AccessKey<KeyType = Option<ThresholdKeyShare>> + MutateKey<Option<ThresholdKeyShare>>,
 	<SKR as AccessKey>::KeyType: ShieldingCryptoEncrypt + 'static,
 	Responder: SendRpcResponse<Hash = H256>,
 {
@@ -131,16 +174,24 @@ pub struct BitAcrossTaskContext<
 	pub ethereum_key_repository: Arc<EKR>,
 	pub bitcoin_key_repository: Arc<BKR>,
 	pub ton_key_repository: Arc<TKR>,
+	// Solana uses Ed25519 keys as well
+	pub solana_key_repository: Arc<TKR>,
+	pub threshold_key_share_repository: Arc<KSR>,
 	pub enclave_signer: Arc<S>,
 	pub state_handler: Arc<H>,
 	pub ocall_api: Arc<O>,
 	pub relayer_registry_lookup: Arc<RRL>,
 	pub enclave_registry_lookup: Arc<ERL>,
 	pub signer_registry_lookup: Arc<SRL>,
+	pub signing_policy: Arc<BSP>,
+	pub signature_audit_log: Arc<SAL>,
 	pub signing_key_pub: [u8; 32],
 	pub responder: Arc<Responder>,
 	pub ceremony_registry: Arc<RwLock<CeremonyRegistry<BKR>>>,
 	pub ceremony_command_tmp: Arc<RwLock<CeremonyCommandTmp>>,
+	pub key_generation_registry: Arc<RwLock<KeyGenerationRegistry<BKR>>>,
+	pub nonce_pool: Arc<RwLock<NoncePool>>,
+	pub ceremony_failures: Arc<RwLock<CeremonyFailureHistory>>,
 }
 
 impl<
@@ -149,20 +200,40 @@ impl<
 		EKR,
 		BKR,
 		TKR,
+		KSR,
 		S: StfEnclaveSigning<TrustedCallSigned>,
 		H: HandleState,
 		O: EnclaveOnChainOCallApi,
 		RRL: RelayerRegistryLookup,
 		ERL: EnclaveRegistryLookup,
 		SRL: SignerRegistryLookup,
+		BSP: SigningPolicyEnforcer + SigningPolicyUpdater + EthAllowlistLookup,
+		SAL: SignatureAuditLogUpdater + SignatureAuditLogLookup,
 		Responder,
-	> BitAcrossTaskContext<SKR, SIGNINGAK, EKR, BKR, TKR, S, H, O, RRL, ERL, SRL, Responder>
-where
+	>
+	BitAcrossTaskContext<
+		SKR,
+		SIGNINGAK,
+		EKR,
+		BKR,
+		TKR,
+		KSR,
+		S,
+		H,
+		O,
+		RRL,
+		ERL,
+		SRL,
+		BSP,
+		SAL,
+		Responder,
+	> where
 	SKR: AccessKey + AccessPubkey<KeyType = Rsa3072PubKey>,
 	SIGNINGAK: AccessKey<KeyType = ed25519::Pair>,
 	EKR: AccessKey<KeyType = EcdsaPair>,
 	BKR: AccessKey<KeyType = SchnorrPair>,
 	TKR: AccessKey<KeyType = ed25519::Pair>,
+	KSR: AccessKey<KeyType = Option<ThresholdKeyShare>> + MutateKey<Option<ThresholdKeyShare>>,
 	<SKR as AccessKey>::KeyType: ShieldingCryptoEncrypt + 'static,
 	H::StateT: SgxExternalitiesTrait,
 	Responder: SendRpcResponse<Hash = H256>,
@@ -174,15 +245,22 @@ where
 		ethereum_key_repository: Arc<EKR>,
 		bitcoin_key_repository: Arc<BKR>,
 		ton_key_repository: Arc<TKR>,
+		solana_key_repository: Arc<TKR>,
+		threshold_key_share_repository: Arc<KSR>,
 		enclave_signer: Arc<S>,
 		state_handler: Arc<H>,
 		ocall_api: Arc<O>,
 		relayer_registry_lookup: Arc<RRL>,
 		enclave_registry_lookup: Arc<ERL>,
 		signer_registry_lookup: Arc<SRL>,
+		signing_policy: Arc<BSP>,
+		signature_audit_log: Arc<SAL>,
 		signing_key_pub: [u8; 32],
 		ceremony_registry: Arc<RwLock<CeremonyRegistry<BKR>>>,
 		ceremony_command_tmp: Arc<RwLock<CeremonyCommandTmp>>,
+		key_generation_registry: Arc<RwLock<KeyGenerationRegistry<BKR>>>,
+		nonce_pool: Arc<RwLock<NoncePool>>,
+		ceremony_failures: Arc<RwLock<CeremonyFailureHistory>>,
 		responder: Arc<Responder>,
 	) -> Self {
 		Self {
@@ -191,15 +269,22 @@ where
 			ethereum_key_repository,
 			bitcoin_key_repository,
 			ton_key_repository,
+			solana_key_repository,
+			threshold_key_share_repository,
 			enclave_signer,
 			state_handler,
 			ocall_api,
 			relayer_registry_lookup,
 			enclave_registry_lookup,
 			signer_registry_lookup,
+			signing_policy,
+			signature_audit_log,
 			signing_key_pub,
 			ceremony_registry,
 			ceremony_command_tmp,
+			key_generation_registry,
+			nonce_pool,
+			ceremony_failures,
 			responder,
 		}
 	}
@@ -212,17 +297,40 @@ pub fn run_bit_across_handler_runner<
 	EKR,
 	BKR,
 	TKR,
+	KSR,
 	S,
 	H,
 	O,
 	RRL,
 	ERL,
 	SRL,
+	BSP,
+	SAL,
 	Responder,
+	KP,
+	AP,
 >(
 	context: Arc<
-		BitAcrossTaskContext<SKR, SIGNINGAK, EKR, BKR, TKR, S, H, O, RRL, ERL, SRL, Responder>,
+		BitAcrossTaskContext<
+			SKR,
+			SIGNINGAK,
+			EKR,
+			BKR,
+			TKR,
+			KSR,
+			S,
+			H,
+			O,
+			RRL,
+			ERL,
+			SRL,
+			BSP,
+			SAL,
+			Responder,
+		>,
 	>,
+	threshold_key_publisher: Arc<KP>,
+	audit_root_publisher: Arc<AP>,
 	ceremony_commands_thread_count: u8,
 	ceremony_events_thread_count: u8,
 ) where
@@ -231,6 +339,11 @@ pub fn run_bit_across_handler_runner<
 	EKR: AccessKey<KeyType = EcdsaPair> + Send + Sync + 'static,
 	BKR: AccessKey<KeyType = SchnorrPair> + Send + Sync + 'static,
 	TKR: AccessKey<KeyType = ed25519::Pair> + Send + Sync + 'static,
+	KSR: AccessKey<KeyType = Option<ThresholdKeyShare>>
+		+ MutateKey<Option<ThresholdKeyShare>>
+		+ Send
+		+ Sync
+		+ 'static,
 	<SKR as AccessKey>::KeyType: ShieldingCryptoEncrypt + ShieldingCryptoDecrypt + 'static,
 	S: StfEnclaveSigning<TrustedCallSigned> + Send + Sync + 'static,
 	H: HandleState + Send + Sync + 'static,
@@ -239,14 +352,21 @@ pub fn run_bit_across_handler_runner<
 	RRL: RelayerRegistryLookup + Send + Sync + 'static,
 	ERL: EnclaveRegistryLookup + Send + Sync + 'static,
 	SRL: SignerRegistryLookup + Send + Sync + 'static,
+	BSP: SigningPolicyEnforcer + SigningPolicyUpdater + EthAllowlistLookup + Send + Sync + 'static,
+	SAL: SignatureAuditLogUpdater + SignatureAuditLogLookup + Send + Sync + 'static,
 	Responder: SendRpcResponse<Hash = H256> + Send + Sync + 'static,
+	KP: PublishThresholdKey + Send + Sync + 'static,
+	AP: PublishAuditRoot + Send + Sync + 'static,
 {
 	// timeout tick
 	let ceremony_registry = context.ceremony_registry.clone();
 	let ceremony_command_tmp = context.ceremony_command_tmp.clone();
+	let key_generation_registry = context.key_generation_registry.clone();
 	let responder = context.responder.clone();
 	let time_to_live = 30u64;
 	let cloned_ocall_api = context.ocall_api.clone();
+	let signature_audit_log = context.signature_audit_log.clone();
+	let ceremony_failures = context.ceremony_failures.clone();
 	std::thread::spawn(move || loop {
 		std::thread::sleep(Duration::from_secs(3));
 		let now = get_current_timestamp();
@@ -259,6 +379,22 @@ pub fn run_bit_across_handler_runner<
 					let ceremony_rwlock = ceremony.clone();
 					let ceremony = ceremony_rwlock.read().unwrap();
 					let hash = blake2_256(&ceremony.get_id_ref().encode());
+					// signers the ceremony was still waiting for are held responsible
+					let contributions = ceremony.contributions();
+					let missing = contributions.missing();
+					warn!(
+						"Ceremony {:?} timed out in round {:?}, missing signers: {:?}",
+						&hash, contributions.round, missing
+					);
+					ceremony_failures.write().unwrap().record(
+						ceremony.get_id_ref(),
+						now,
+						CeremonyFailureReason::TimedOut(contributions.round),
+						missing,
+					);
+					if let Err(e) = signature_audit_log.ceremony_ended(&hash, false) {
+						error!("Could not update signature audit log, error: {:?}", e);
+					}
 					let encrypted_result = SignBitcoinError::CeremonyError.encode();
 					if let Err(e) = responder.send_state_with_status(
 						Hash::from_slice(&hash),
@@ -276,31 +412,112 @@ pub fn run_bit_across_handler_runner<
 			let mut command_tmp_write = ceremony_command_tmp.write().unwrap();
 			command_tmp_write.retain(|_, &mut (_, create_time)| now - create_time < time_to_live);
 		}
+		{
+			let mut key_generation_registry_write = key_generation_registry.write().unwrap();
+			key_generation_registry_write.retain(|id, (_, create_time)| {
+				let if_retain = now - *create_time < time_to_live;
+				if !if_retain {
+					let hash = blake2_256(&id.encode());
+					let encrypted_result = GenerateThresholdKeyError::KeyGenerationError.encode();
+					if let Err(e) = responder.send_state_with_status(
+						Hash::from_slice(&hash),
+						encrypted_result,
+						DirectRequestStatus::Error,
+					) {
+						error!("Could not send response to {:?}, reason: {:?}", &hash, e);
+					}
+				}
+				if_retain
+			});
+		}
 		if timed_out_count > 0 {
 			let _ = cloned_ocall_api
 				.update_metric(EnclaveMetric::Musig2CeremonyTimedout(timed_out_count));
 		}
 	});
 
+	// audit root tick
+	let signature_audit_log = context.signature_audit_log.clone();
+	std::thread::spawn(move || loop {
+		std::thread::sleep(AUDIT_ROOT_POST_INTERVAL);
+		let size = signature_audit_log.size();
+		if size <= signature_audit_log.posted_size() {
+			continue
+		}
+		let root = match signature_audit_log.root(size) {
+			Some(root) => root,
+			None => continue,
+		};
+		match audit_root_publisher.signature_audit_root(root, size) {
+			Ok(()) =>
+				if let Err(e) = signature_audit_log.root_posted(size) {
+					error!("Could not update signature audit log, error: {:?}", e);
+				},
+			Err(e) => error!("Could not post signature audit root, error: {}", e),
+		}
+	});
+
 	let bit_across_task_receiver = init_bit_across_task_sender_storage();
 	let peers_map = Arc::new(Mutex::new(HashMap::<[u8; 32], DirectRpcClient>::new()));
 	let command_threads_pool = ThreadPool::new(ceremony_commands_thread_count.into());
 	let event_threads_pool = ThreadPool::new(ceremony_events_thread_count.into());
 
+	// nonce pool tick
+	let nonce_pool_context = context.clone();
+	let nonce_pool_event_threads_pool = event_threads_pool.clone();
+	let nonce_pool_peers_map = peers_map.clone();
+	std::thread::spawn(move || loop {
+		let context = &nonce_pool_context;
+		let nonces = {
+			let mut nonce_pool = context.nonce_pool.write().unwrap();
+			if let Err(e) = nonce_pool.refill() {
+				error!("Could not refill nonce pool, error: {:?}", e);
+			}
+			nonce_pool.public_nonces()
+		};
+		let signers = context
+			.signer_registry_lookup
+			.get_all()
+			.iter()
+			.map(|(address, _)| *address.as_ref())
+			.filter(|signer| *signer != context.signing_key_pub)
+			.collect();
+		share_nonce_pool(
+			context.signing_key_access.clone(),
+			context.ocall_api.clone(),
+			context.enclave_registry_lookup.clone(),
+			signers,
+			nonces,
+			NONCE_POOL_SHARE_INTERVAL.as_secs(),
+			nonce_pool_event_threads_pool.clone(),
+			nonce_pool_peers_map.clone(),
+		);
+		std::thread::sleep(NONCE_POOL_SHARE_INTERVAL);
+	});
+
 	while let Ok(req) = bit_across_task_receiver.recv() {
 		let context = context.clone();
 		let event_threads_pool = event_threads_pool.clone();
 		let peers_map = peers_map.clone();
-		command_threads_pool.execute(move || {
-			if let Some((ceremony_id, command)) = handle_request(req, context.clone()) {
-				handle_ceremony_command(
+		let threshold_key_publisher = threshold_key_publisher.clone();
+		command_threads_pool.execute(move || match handle_request(req, context.clone()) {
+			Some(Command::Ceremony(ceremony_id, command)) => handle_ceremony_command(
+				context,
+				ceremony_id,
+				command,
+				event_threads_pool,
+				peers_map,
+			),
+			Some(Command::KeyGeneration(key_generation_id, command)) =>
+				handle_key_generation_command(
 					context,
-					ceremony_id,
+					threshold_key_publisher,
+					key_generation_id,
 					command,
 					event_threads_pool,
 					peers_map,
-				);
-			}
+				),
+			None => {},
 		});
 	}
 
@@ -310,9 +527,41 @@ pub fn run_bit_across_handler_runner<
 }
 
 #[allow(clippy::type_complexity)]
-fn handle_ceremony_command<SKR, SIGNINGAK, EKR, BKR, TKR, S, H, O, RRL, ERL, SRL, Responder>(
+fn handle_ceremony_command<
+	SKR,
+	SIGNINGAK,
+	EKR,
+	BKR,
+	TKR,
+	KSR,
+	S,
+	H,
+	O,
+	RRL,
+	ERL,
+	SRL,
+	BSP,
+	SAL,
+	Responder,
+>(
 	context: Arc<
-		BitAcrossTaskContext<SKR, SIGNINGAK, EKR, BKR, TKR, S, H, O, RRL, ERL, SRL, Responder>,
+		BitAcrossTaskContext<
+			SKR,
+			SIGNINGAK,
+			EKR,
+			BKR,
+			TKR,
+			KSR,
+			S,
+			H,
+			O,
+			RRL,
+			ERL,
+			SRL,
+			BSP,
+			SAL,
+			Responder,
+		>,
 	>,
 	ceremony_id: CeremonyId,
 	command: CeremonyCommand,
@@ -324,6 +573,11 @@ fn handle_ceremony_command<SKR, SIGNINGAK, EKR, BKR, TKR, S, H, O, RRL, ERL, SRL
 	EKR: AccessKey<KeyType = EcdsaPair> + Send + Sync + 'static,
 	BKR: AccessKey<KeyType = SchnorrPair> + Send + Sync + 'static,
 	TKR: AccessKey<KeyType = ed25519::Pair> + Send + Sync + 'static,
+	KSR: AccessKey<KeyType = Option<ThresholdKeyShare>>
+		+ MutateKey<Option<ThresholdKeyShare>>
+		+ Send
+		+ Sync
+		+ 'static,
 	<SKR as AccessKey>::KeyType: ShieldingCryptoEncrypt + ShieldingCryptoDecrypt + 'static,
 	S: StfEnclaveSigning<TrustedCallSigned> + Send + Sync + 'static,
 	H: HandleState + Send + Sync + 'static,
@@ -332,6 +586,8 @@ fn handle_ceremony_command<SKR, SIGNINGAK, EKR, BKR, TKR, S, H, O, RRL, ERL, SRL
 	RRL: RelayerRegistryLookup + Send + Sync + 'static,
 	ERL: EnclaveRegistryLookup + Send + Sync + 'static,
 	SRL: SignerRegistryLookup + Send + Sync + 'static,
+	BSP: SigningPolicyEnforcer + SigningPolicyUpdater + EthAllowlistLookup + Send + Sync + 'static,
+	SAL: SignatureAuditLogUpdater + SignatureAuditLogLookup + Send + Sync + 'static,
 	Responder: SendRpcResponse<Hash = H256> + Send + Sync + 'static,
 {
 	// check whether to store command to tmp
@@ -343,13 +599,9 @@ fn handle_ceremony_command<SKR, SIGNINGAK, EKR, BKR, TKR, S, H, O, RRL, ERL, SRL
 			.get(&ceremony_id)
 			.map(|(c, _)| c.read().unwrap().is_first_round())
 	};
-	match (is_first_round, &command) {
-		(None, CeremonyCommand::InitCeremony(_, _, _))
-		| (Some(true), CeremonyCommand::SaveNonce(_, _))
-		| (Some(false), CeremonyCommand::SavePartialSignature(_, _))
-		| (_, CeremonyCommand::KillCeremony) => {},
-		(None, CeremonyCommand::SaveNonce(_, _))
-		| (Some(true), CeremonyCommand::SavePartialSignature(_, _)) => {
+	match command.disposition(is_first_round) {
+		CommandDisposition::Process => {},
+		CommandDisposition::Postpone => {
 			context
 				.ceremony_command_tmp
 				.write()
@@ -359,7 +611,7 @@ fn handle_ceremony_command<SKR, SIGNINGAK, EKR, BKR, TKR, S, H, O, RRL, ERL, SRL
 				.or_insert((Arc::new(RwLock::new(vec![command])), get_current_timestamp()));
 			return
 		},
-		(is_first_round, command) => {
+		CommandDisposition::Drop => {
 			error!(
 				"receive wrong command: is_first_round: {:?}, command: {:?}, drop it",
 				is_first_round, command
@@ -393,23 +645,33 @@ fn handle_ceremony_command<SKR, SIGNINGAK, EKR, BKR, TKR, S, H, O, RRL, ERL, SRL
 	let mut commands_to_process = vec![command];
 	while !commands_to_process.is_empty() {
 		let command = commands_to_process.pop().unwrap();
+		let signer = command.signer();
 
-		let event = process_command(context.clone(), ceremony_id.clone(), command);
+		let events = process_command(context.clone(), ceremony_id.clone(), command);
 
-		if let Some(event) = event {
+		for event in events {
 			// update metrics
 			match event {
-				CeremonyEvent::FirstRoundStarted(_, _, _) => {
+				CeremonyEvent::FirstRoundStarted(_, _, _)
+				| CeremonyEvent::BatchFirstRoundStarted(_, _, _) => {
 					let _ = context.ocall_api.update_metric(EnclaveMetric::Musig2CeremonyStarted);
 				},
-				CeremonyEvent::CeremonyError(_, _) => {
+				CeremonyEvent::CeremonyError(_, ref error) => {
 					let _ = context.ocall_api.update_metric(EnclaveMetric::Musig2CeremonyFailed);
+					// the failure is attributed to the signer whose contribution caused it
+					context.ceremony_failures.write().unwrap().record(
+						&ceremony_id,
+						get_current_timestamp(),
+						CeremonyFailureReason::Error(error.clone()),
+						signer.into_iter().collect(),
+					);
 				},
-				CeremonyEvent::CeremonyEnded(_, _, _) => {
+				CeremonyEvent::CeremonyEnded(_, _, _) | CeremonyEvent::BatchCeremonyEnded(_) => {
 					let ceremony_start_time =
 						context.ceremony_registry.read().unwrap().get(&ceremony_id).unwrap().1;
+					let _ = context.ocall_api.update_metric(EnclaveMetric::Musig2CeremonySucceeded);
 					let _ = context.ocall_api.update_metric(EnclaveMetric::Musig2CeremonyDuration(
-						Duration::from_millis(get_current_timestamp() - ceremony_start_time),
+						Duration::from_secs(get_current_timestamp() - ceremony_start_time),
 					));
 				},
 				_ => {},
@@ -417,17 +679,32 @@ fn handle_ceremony_command<SKR, SIGNINGAK, EKR, BKR, TKR, S, H, O, RRL, ERL, SRL
 
 			match event {
 				CeremonyEvent::FirstRoundStarted(_, _, _)
-				| CeremonyEvent::SecondRoundStarted(_, _, _) => {
+				| CeremonyEvent::SecondRoundStarted(_, _, _)
+				| CeremonyEvent::ThresholdSecondRoundStarted(_, _, _, _)
+				| CeremonyEvent::BatchFirstRoundStarted(_, _, _)
+				| CeremonyEvent::BatchSecondRoundStarted(_, _, _)
+				| CeremonyEvent::ThresholdBatchSecondRoundStarted(_, _, _, _) => {
 					// get all ceremony_command_tmp
 					let mut ceremony_command_tmp_write =
 						context.ceremony_command_tmp.write().unwrap();
 					if let Some((ceremony_command_tmp, _)) =
 						ceremony_command_tmp_write.remove(&ceremony_id)
 					{
-						commands_to_process = ceremony_command_tmp.read().unwrap().clone();
+						commands_to_process
+							.extend(ceremony_command_tmp.read().unwrap().iter().cloned());
 					}
 				},
-				CeremonyEvent::CeremonyEnded(_, _, _) | CeremonyEvent::CeremonyError(_, _) => {
+				CeremonyEvent::CeremonyEnded(_, _, _)
+				| CeremonyEvent::BatchCeremonyEnded(_)
+				| CeremonyEvent::CeremonyError(_, _) => {
+					// entries of requests this enclave received go into the audit log once signed
+					let signed = !matches!(event, CeremonyEvent::CeremonyError(_, _));
+					if let Err(e) = context
+						.signature_audit_log
+						.ceremony_ended(&blake2_256(&ceremony_id.encode()), signed)
+					{
+						error!("Could not update signature audit log, error: {:?}", e);
+					}
 					// remove ceremony
 					{
 						let mut registry_write = context.ceremony_registry.write().unwrap();
@@ -455,19 +732,217 @@ fn handle_ceremony_command<SKR, SIGNINGAK, EKR, BKR, TKR, S, H, O, RRL, ERL, SRL
 }
 
 #[allow(clippy::type_complexity)]
-fn process_command<SKR, SIGNINGAK, EKR, BKR, TKR, S, H, O, RRL, ERL, SRL, Responder>(
+fn handle_key_generation_command<
+	SKR,
+	SIGNINGAK,
+	EKR,
+	BKR,
+	TKR,
+	KSR,
+	S,
+	H,
+	O,
+	RRL,
+	ERL,
+	SRL,
+	BSP,
+	SAL,
+	Responder,
+	KP,
+>(
+	context: Arc<
+		BitAcrossTaskContext<
+			SKR,
+			SIGNINGAK,
+			EKR,
+			BKR,
+			TKR,
+			KSR,
+			S,
+			H,
+			O,
+			RRL,
+			ERL,
+			SRL,
+			BSP,
+			SAL,
+			Responder,
+		>,
+	>,
+	threshold_key_publisher: Arc<KP>,
+	key_generation_id: KeyGenerationId,
+	command: KeyGenerationCommand,
+	event_threads_pool: ThreadPool,
+	peers_map: Arc<Mutex<HashMap<[u8; 32], DirectRpcClient>>>,
+) where
+	SKR: AccessKey + AccessPubkey<KeyType = Rsa3072PubKey> + Send + Sync + 'static,
+	SIGNINGAK: AccessKey<KeyType = ed25519::Pair> + Send + Sync + 'static,
+	EKR: AccessKey<KeyType = EcdsaPair> + Send + Sync + 'static,
+	BKR: AccessKey<KeyType = SchnorrPair> + Send + Sync + 'static,
+	TKR: AccessKey<KeyType = ed25519::Pair> + Send + Sync + 'static,
+	KSR: AccessKey<KeyType = Option<ThresholdKeyShare>>
+		+ MutateKey<Option<ThresholdKeyShare>>
+		+ Send
+		+ Sync
+		+ 'static,
+	<SKR as AccessKey>::KeyType: ShieldingCryptoEncrypt + ShieldingCryptoDecrypt + 'static,
+	S: StfEnclaveSigning<TrustedCallSigned> + Send + Sync + 'static,
+	H: HandleState + Send + Sync + 'static,
+	H::StateT: SgxExternalitiesTrait,
+	O: EnclaveOnChainOCallApi + EnclaveMetricsOCallApi + EnclaveAttestationOCallApi + 'static,
+	RRL: RelayerRegistryLookup + Send + Sync + 'static,
+	ERL: EnclaveRegistryLookup + Send + Sync + 'static,
+	SRL: SignerRegistryLookup + Send + Sync + 'static,
+	BSP: SigningPolicyEnforcer + SigningPolicyUpdater + EthAllowlistLookup + Send + Sync + 'static,
+	SAL: SignatureAuditLogUpdater + SignatureAuditLogLookup + Send + Sync + 'static,
+	Responder: SendRpcResponse<Hash = H256> + Send + Sync + 'static,
+	KP: PublishThresholdKey + Send + Sync + 'static,
+{
+	let is_resharing = command.reshared_key().is_some();
+	let events = {
+		let mut registry_write = context.key_generation_registry.write().unwrap();
+		let mut events = vec![];
+		// packages of faster peers may arrive before we were asked to take part
+		if !registry_write.contains_key(&key_generation_id) {
+			let key_generation = match command.reshared_key() {
+				None => KeyGeneration::new(
+					context.signing_key_pub,
+					command.signers().clone(),
+					command.threshold(),
+					context.bitcoin_key_repository.clone(),
+				),
+				Some(reshared_key) => KeyGeneration::reshare(
+					context.signing_key_pub,
+					command.signers().clone(),
+					reshared_key.clone(),
+					context.threshold_key_share_repository.retrieve_key().ok().flatten(),
+					context.bitcoin_key_repository.clone(),
+				),
+			};
+			match key_generation {
+				Ok((key_generation, event)) => {
+					registry_write
+						.insert(key_generation_id, (key_generation, get_current_timestamp()));
+					events.push(event);
+				},
+				Err(e) => {
+					error!("Could not start key generation, error: {:?}", e);
+					events.push(KeyGenerationEvent::Error(vec![], KeyGenerationError::InitError));
+				},
+			}
+		}
+		if let (
+			KeyGenerationCommand::SavePackage(_, _, _, signer, package),
+			Some((key_generation, _)),
+		) = (command, registry_write.get_mut(&key_generation_id))
+		{
+			match key_generation.receive_package(signer, package) {
+				Ok(received_events) => events.extend(received_events),
+				Err(e) => events
+					.push(KeyGenerationEvent::Error(key_generation.get_signers_except_self(), e)),
+			}
+		}
+		events
+	};
+
+	for event in events {
+		let event = match event {
+			KeyGenerationEvent::Ended(key_share) => {
+				context.key_generation_registry.write().unwrap().remove(&key_generation_id);
+				match context.threshold_key_share_repository.update_key(Some(key_share.clone())) {
+					Ok(()) => {
+						info!(
+							"Saved threshold key share of key generation {:?}",
+							key_generation_id
+						);
+						let published = if is_resharing {
+							threshold_key_publisher.threshold_key_reshared(&key_share)
+						} else {
+							threshold_key_publisher.threshold_key_generated(&key_share)
+						};
+						if let Err(e) = published {
+							error!("Could not publish threshold key, error: {:?}", e);
+						}
+						KeyGenerationEvent::Ended(key_share)
+					},
+					Err(e) => {
+						error!("Could not save threshold key share, error: {:?}", e);
+						KeyGenerationEvent::Error(vec![], KeyGenerationError::FinalizationError)
+					},
+				}
+			},
+			KeyGenerationEvent::Error(signers, error) => {
+				context.key_generation_registry.write().unwrap().remove(&key_generation_id);
+				KeyGenerationEvent::Error(signers, error)
+			},
+			event => event,
+		};
+
+		process_key_generation_event(
+			context.signing_key_access.clone(),
+			context.ocall_api.clone(),
+			context.responder.clone(),
+			context.enclave_registry_lookup.clone(),
+			event,
+			key_generation_id,
+			event_threads_pool.clone(),
+			peers_map.clone(),
+			context.key_generation_registry.clone(),
+		);
+	}
+}
+
+#[allow(clippy::type_complexity)]
+fn process_command<
+	SKR,
+	SIGNINGAK,
+	EKR,
+	BKR,
+	TKR,
+	KSR,
+	S,
+	H,
+	O,
+	RRL,
+	ERL,
+	SRL,
+	BSP,
+	SAL,
+	Responder,
+>(
 	context: Arc<
-		BitAcrossTaskContext<SKR, SIGNINGAK, EKR, BKR, TKR, S, H, O, RRL, ERL, SRL, Responder>,
+		BitAcrossTaskContext<
+			SKR,
+			SIGNINGAK,
+			EKR,
+			BKR,
+			TKR,
+			KSR,
+			S,
+			H,
+			O,
+			RRL,
+			ERL,
+			SRL,
+			BSP,
+			SAL,
+			Responder,
+		>,
 	>,
 	ceremony_id: CeremonyId,
 	command: CeremonyCommand,
-) -> Option<CeremonyEvent>
+) -> Vec<CeremonyEvent>
 where
 	SKR: AccessKey + AccessPubkey<KeyType = Rsa3072PubKey> + Send + Sync + 'static,
 	SIGNINGAK: AccessKey<KeyType = ed25519::Pair> + Send + Sync + 'static,
 	EKR: AccessKey<KeyType = EcdsaPair> + Send + Sync + 'static,
 	BKR: AccessKey<KeyType = SchnorrPair> + Send + Sync + 'static,
 	TKR: AccessKey<KeyType = ed25519::Pair> + Send + Sync + 'static,
+	KSR: AccessKey<KeyType = Option<ThresholdKeyShare>>
+		+ MutateKey<Option<ThresholdKeyShare>>
+		+ Send
+		+ Sync
+		+ 'static,
 	<SKR as AccessKey>::KeyType: ShieldingCryptoEncrypt + ShieldingCryptoDecrypt + 'static,
 	S: StfEnclaveSigning<TrustedCallSigned> + Send + Sync + 'static,
 	H: HandleState + Send + Sync + 'static,
@@ -476,40 +951,62 @@ where
 	RRL: RelayerRegistryLookup + Send + Sync + 'static,
 	ERL: EnclaveRegistryLookup + Send + Sync + 'static,
 	SRL: SignerRegistryLookup + Send + Sync + 'static,
+	BSP: SigningPolicyEnforcer + SigningPolicyUpdater + EthAllowlistLookup + Send + Sync + 'static,
+	SAL: SignatureAuditLogUpdater + SignatureAuditLogLookup + Send + Sync + 'static,
 	Responder: SendRpcResponse<Hash = H256> + Send + Sync + 'static,
 {
 	match command {
 		CeremonyCommand::InitCeremony(signers, payload, check_run) => {
-			// InitCeremony should create ceremony first
-			let result = MuSig2Ceremony::new(
-				context.signing_key_pub,
-				signers,
-				payload,
-				context.bitcoin_key_repository.clone(),
-				check_run,
-			);
+			// the registry stays locked until the ceremony is in, so nonces are only taken out of
+			// the pool for a ceremony which doesn't exist yet
+			let mut registry_write = context.ceremony_registry.write().unwrap();
+			if registry_write.contains_key(&ceremony_id) {
+				let error = CeremonyError::CeremonyInitError(CeremonyErrorReason::AlreadyExist);
+				return vec![CeremonyEvent::CeremonyError(vec![], error)]
+			}
+
+			// InitCeremony should create ceremony first, threshold signing is used once this
+			// enclave holds a threshold key share
+			let result = match context.threshold_key_share_repository.retrieve_key() {
+				Ok(Some(key_share)) if matches!(payload, SignBitcoinPayload::Batch(_, _)) =>
+					ThresholdBatchCeremony::new(context.signing_key_pub, key_share, payload)
+						.map(|(ceremony, event)| (Ceremony::ThresholdBatch(ceremony), event)),
+				Ok(None) if matches!(payload, SignBitcoinPayload::Batch(_, _)) =>
+					MuSig2BatchCeremony::new(
+						context.signing_key_pub,
+						signers,
+						payload,
+						context.bitcoin_key_repository.clone(),
+						&mut context.nonce_pool.write().unwrap(),
+					)
+					.map(|(ceremony, event)| (Ceremony::Batch(ceremony), event)),
+				Ok(Some(key_share)) =>
+					ThresholdCeremony::new(context.signing_key_pub, key_share, payload, check_run)
+						.map(|(ceremony, event)| (Ceremony::Threshold(ceremony), event)),
+				Ok(None) => MuSig2Ceremony::new(
+					context.signing_key_pub,
+					signers,
+					payload,
+					context.bitcoin_key_repository.clone(),
+					check_run,
+				)
+				.map(|(ceremony, event)| (Ceremony::MuSig2(ceremony), event)),
+				Err(e) => Err(format!("Could not retrieve threshold key share: {:?}", e)),
+			};
 
 			match result {
 				Ok((ceremony, event)) => {
-					{
-						let mut registry_write = context.ceremony_registry.write().unwrap();
-						if registry_write.contains_key(&ceremony_id) {
-							let error =
-								CeremonyError::CeremonyInitError(CeremonyErrorReason::AlreadyExist);
-							return Some(CeremonyEvent::CeremonyError(vec![], error))
-						}
-						registry_write.insert(
-							ceremony_id,
-							(Arc::new(RwLock::new(ceremony)), get_current_timestamp()),
-						);
-					}
-					Some(event)
+					registry_write.insert(
+						ceremony_id,
+						(Arc::new(RwLock::new(ceremony)), get_current_timestamp()),
+					);
+					vec![event]
 				},
 				Err(e) => {
 					error!("Could not start ceremony, error: {:?}", e);
 					let error =
 						CeremonyError::CeremonyInitError(CeremonyErrorReason::CreateCeremonyError);
-					Some(CeremonyEvent::CeremonyError(vec![], error))
+					vec![CeremonyEvent::CeremonyError(vec![], error)]
 				},
 			}
 		},
@@ -520,14 +1017,14 @@ where
 				let mut ceremony_write_lock = ceremony_rwlock.0.write().unwrap();
 				let event_ret = ceremony_write_lock.receive_nonce(signer, nonce);
 				match event_ret {
-					Ok(event) => event,
-					Err(e) => Some(CeremonyEvent::CeremonyError(
+					Ok(events) => events,
+					Err(e) => vec![CeremonyEvent::CeremonyError(
 						ceremony_write_lock.get_signers_except_self(),
 						e,
-					)),
+					)],
 				}
 			} else {
-				None
+				vec![]
 			}
 		},
 		CeremonyCommand::SavePartialSignature(signer, partial_signature) => {
@@ -537,14 +1034,95 @@ where
 				let mut ceremony_write_lock = ceremony_rwlock.0.write().unwrap();
 				let event_ret = ceremony_write_lock.receive_partial_sign(signer, partial_signature);
 				match event_ret {
-					Ok(event) => event,
-					Err(e) => Some(CeremonyEvent::CeremonyError(
+					Ok(events) => events,
+					Err(e) => vec![CeremonyEvent::CeremonyError(
 						ceremony_write_lock.get_signers_except_self(),
 						e,
-					)),
+					)],
 				}
 			} else {
-				None
+				vec![]
+			}
+		},
+		CeremonyCommand::SaveThresholdPartialSignature(signer, signing_set, partial_signature) => {
+			let ceremony_rwlock =
+				context.ceremony_registry.read().unwrap().get(&ceremony_id).cloned();
+			if let Some(ceremony_rwlock) = ceremony_rwlock {
+				let mut ceremony_write_lock = ceremony_rwlock.0.write().unwrap();
+				let event_ret = ceremony_write_lock.receive_threshold_partial_sign(
+					signer,
+					signing_set,
+					partial_signature,
+				);
+				match event_ret {
+					Ok(events) => events,
+					Err(e) => vec![CeremonyEvent::CeremonyError(
+						ceremony_write_lock.get_signers_except_self(),
+						e,
+					)],
+				}
+			} else {
+				vec![]
+			}
+		},
+		CeremonyCommand::SaveBatchNonces(signer, nonces) => {
+			let ceremony_rwlock =
+				context.ceremony_registry.read().unwrap().get(&ceremony_id).cloned();
+			if let Some(ceremony_rwlock) = ceremony_rwlock {
+				let mut ceremony_write_lock = ceremony_rwlock.0.write().unwrap();
+				let event_ret = ceremony_write_lock.receive_batch_nonces(signer, nonces);
+				match event_ret {
+					Ok(events) => events,
+					Err(e) => vec![CeremonyEvent::CeremonyError(
+						ceremony_write_lock.get_signers_except_self(),
+						e,
+					)],
+				}
+			} else {
+				vec![]
+			}
+		},
+		CeremonyCommand::SaveBatchPartialSignatures(signer, partial_signatures) => {
+			let ceremony_rwlock =
+				context.ceremony_registry.read().unwrap().get(&ceremony_id).cloned();
+			if let Some(ceremony_rwlock) = ceremony_rwlock {
+				let mut ceremony_write_lock = ceremony_rwlock.0.write().unwrap();
+				let event_ret =
+					ceremony_write_lock.receive_batch_partial_signs(signer, partial_signatures);
+				match event_ret {
+					Ok(events) => events,
+					Err(e) => vec![CeremonyEvent::CeremonyError(
+						ceremony_write_lock.get_signers_except_self(),
+						e,
+					)],
+				}
+			} else {
+				vec![]
+			}
+		},
+		CeremonyCommand::SaveThresholdBatchPartialSignatures(
+			signer,
+			signing_set,
+			partial_signatures,
+		) => {
+			let ceremony_rwlock =
+				context.ceremony_registry.read().unwrap().get(&ceremony_id).cloned();
+			if let Some(ceremony_rwlock) = ceremony_rwlock {
+				let mut ceremony_write_lock = ceremony_rwlock.0.write().unwrap();
+				let event_ret = ceremony_write_lock.receive_threshold_batch_partial_signs(
+					signer,
+					signing_set,
+					partial_signatures,
+				);
+				match event_ret {
+					Ok(events) => events,
+					Err(e) => vec![CeremonyEvent::CeremonyError(
+						ceremony_write_lock.get_signers_except_self(),
+						e,
+					)],
+				}
+			} else {
+				vec![]
 			}
 		},
 		CeremonyCommand::KillCeremony => {
@@ -554,24 +1132,45 @@ where
 			{
 				context.ceremony_command_tmp.write().unwrap().remove(&ceremony_id);
 			}
-			None
+			vec![]
 		},
 	}
 }
 
 #[allow(clippy::type_complexity)]
-fn handle_request<SKR, SIGNINGAK, EKR, BKR, TKR, S, H, O, RRL, ERL, SRL, Responder>(
+fn handle_request<SKR, SIGNINGAK, EKR, BKR, TKR, KSR, S, H, O, RRL, ERL, SRL, BSP, SAL, Responder>(
 	request: BitAcrossRequest,
 	context: Arc<
-		BitAcrossTaskContext<SKR, SIGNINGAK, EKR, BKR, TKR, S, H, O, RRL, ERL, SRL, Responder>,
+		BitAcrossTaskContext<
+			SKR,
+			SIGNINGAK,
+			EKR,
+			BKR,
+			TKR,
+			KSR,
+			S,
+			H,
+			O,
+			RRL,
+			ERL,
+			SRL,
+			BSP,
+			SAL,
+			Responder,
+		>,
 	>,
-) -> Option<(CeremonyId, CeremonyCommand)>
+) -> Option<Command>
 where
 	SKR: AccessKey + AccessPubkey<KeyType = Rsa3072PubKey>,
 	SIGNINGAK: AccessKey<KeyType = ed25519::Pair>,
 	EKR: AccessKey<KeyType = EcdsaPair>,
 	BKR: AccessKey<KeyType = SchnorrPair>,
 	TKR: AccessKey<KeyType = ed25519::Pair> + Send + Sync + 'static,
+	KSR: AccessKey<KeyType = Option<ThresholdKeyShare>>
+		+ MutateKey<Option<ThresholdKeyShare>>
+		+ Send
+		+ Sync
+		+ 'static,
 	<SKR as AccessKey>::KeyType: ShieldingCryptoEncrypt + ShieldingCryptoDecrypt + 'static,
 	S: StfEnclaveSigning<TrustedCallSigned> + Send + Sync + 'static,
 	H: HandleState + Send + Sync + 'static,
@@ -579,6 +1178,8 @@ where
 	RRL: RelayerRegistryLookup + 'static,
 	ERL: EnclaveRegistryLookup + 'static,
 	SRL: SignerRegistryLookup + 'static,
+	BSP: SigningPolicyEnforcer + SigningPolicyUpdater + EthAllowlistLookup + 'static,
+	SAL: SignatureAuditLogUpdater + SignatureAuditLogLookup + 'static,
 	Responder: SendRpcResponse<Hash = H256> + Send + Sync + 'static,
 {
 	match request {
@@ -600,22 +1201,95 @@ where
 		},
 		BitAcrossRequest::ShareCeremonyData(request) =>
 			handle_ceremony_round_call(request, context).unwrap_or_default(),
+		BitAcrossRequest::SetBtcSigningPolicy(policy) => {
+			if let Err(e) = context.signing_policy.set_policy(policy) {
+				error!("Could not set BTC signing policy, error: {:?}", e);
+			}
+			None
+		},
+		BitAcrossRequest::SetEthSigningAllowlist(relayer, allowlist) => {
+			if let Err(e) = context.signing_policy.set_eth_allowlist(relayer, allowlist) {
+				error!("Could not set Ethereum signing allowlist, error: {:?}", e);
+			}
+			None
+		},
+		BitAcrossRequest::ReshareThresholdKey => {
+			let key_share = match context.threshold_key_share_repository.retrieve_key() {
+				Ok(key_share) => key_share,
+				Err(e) => {
+					error!("Could not retrieve threshold key share, error: {:?}", e);
+					return None
+				},
+			};
+			match reshare_threshold_key::handle(
+				key_share,
+				context.signer_registry_lookup.as_ref(),
+				context.enclave_registry_lookup.as_ref(),
+			) {
+				Ok(command) => command.map(|command| {
+					let key_generation_id = command.key_generation_id();
+					info!("Resharing threshold key, resharing: {:?}", key_generation_id);
+					Command::KeyGeneration(key_generation_id, command)
+				}),
+				Err(e) => {
+					error!("ReshareThresholdKey error: {:?}", e);
+					None
+				},
+			}
+		},
 	}
 }
 
 #[allow(clippy::type_complexity)]
-fn handle_direct_call<SKR, SIGNINGAK, EKR, BKR, TKR, S, H, O, RRL, ERL, SRL, Responder>(
+fn handle_direct_call<
+	SKR,
+	SIGNINGAK,
+	EKR,
+	BKR,
+	TKR,
+	KSR,
+	S,
+	H,
+	O,
+	RRL,
+	ERL,
+	SRL,
+	BSP,
+	SAL,
+	Responder,
+>(
 	request: PlainRequest,
 	context: Arc<
-		BitAcrossTaskContext<SKR, SIGNINGAK, EKR, BKR, TKR, S, H, O, RRL, ERL, SRL, Responder>,
+		BitAcrossTaskContext<
+			SKR,
+			SIGNINGAK,
+			EKR,
+			BKR,
+			TKR,
+			KSR,
+			S,
+			H,
+			O,
+			RRL,
+			ERL,
+			SRL,
+			BSP,
+			SAL,
+			Responder,
+		>,
 	>,
-) -> Result<(Option<BitAcrossProcessingResult>, Option<(CeremonyId, CeremonyCommand)>), Vec<u8>>
+) -> Result<(Option<BitAcrossProcessingResult>, Option<Command>), Vec<u8>>
 where
 	SKR: AccessKey + AccessPubkey<KeyType = Rsa3072PubKey>,
 	SIGNINGAK: AccessKey<KeyType = ed25519::Pair>,
 	EKR: AccessKey<KeyType = EcdsaPair>,
 	BKR: AccessKey<KeyType = SchnorrPair>,
 	TKR: AccessKey<KeyType = ed25519::Pair> + Send + Sync + 'static,
+	KSR: AccessKey<KeyType = Option<ThresholdKeyShare>>
+		+ MutateKey<Option<ThresholdKeyShare>>
+		+ Send
+		+ Sync
+		+ 'static,
 	<SKR as AccessKey>::KeyType: ShieldingCryptoEncrypt + ShieldingCryptoDecrypt + 'static,
 	S: StfEnclaveSigning<TrustedCallSigned> + Send + Sync + 'static,
 	H: HandleState + Send + Sync + 'static,
@@ -623,6 +1297,8 @@ where
 	RRL: RelayerRegistryLookup + 'static,
 	ERL: EnclaveRegistryLookup + 'static,
 	SRL: SignerRegistryLookup + 'static,
+	BSP: SigningPolicyEnforcer + SigningPolicyUpdater + EthAllowlistLookup + 'static,
+	SAL: SignatureAuditLogUpdater + SignatureAuditLogLookup + 'static,
 	Responder: SendRpcResponse<Hash = H256> + Send + Sync + 'static,
 {
 	let dc = DirectCallSigned::decode(&mut request.payload.as_slice()).map_err(|e| {
@@ -643,20 +1319,30 @@ where
 	match dc.call {
 		DirectCall::SignBitcoin(signer, payload) => {
 			let hash = blake2_256(&payload.encode());
+			let entry = AuditEntry::new(
+				signer.clone(),
+				SigningChain::Bitcoin,
+				&payload.encode(),
+				Some(hash),
+				get_current_timestamp(),
+			);
 			let command = sign_bitcoin::handle(
 				signer,
 				payload.clone(),
 				context.relayer_registry_lookup.deref(),
 				context.signer_registry_lookup.clone(),
 				context.enclave_registry_lookup.as_ref(),
+				context.signing_policy.deref(),
 				false,
+				get_current_timestamp(),
 			)
 			.map_err(|e| {
 				error!("SignBitcoin error: {:?}", e);
 				e.encode()
 			})?;
+			await_ceremony(context.signature_audit_log.deref(), entry)?;
 			let ret = BitAcrossProcessingResult::Submitted(hash);
-			Ok((Some(ret), Some((payload, command))))
+			Ok((Some(ret), Some(Command::Ceremony(payload, command))))
 		},
 		DirectCall::CheckSignBitcoin(signer) => {
 			let payload = SignBitcoinPayload::Derived([0u8; 32].to_vec());
@@ -667,53 +1353,251 @@ where
 				context.relayer_registry_lookup.deref(),
 				context.signer_registry_lookup.clone(),
 				context.enclave_registry_lookup.as_ref(),
+				context.signing_policy.deref(),
 				true,
+				get_current_timestamp(),
 			)
 			.map_err(|e| {
 				error!("SignBitcoinCheck error: {:?}", e);
 				e.encode()
 			})?;
 			let ret = BitAcrossProcessingResult::Submitted(hash);
-			Ok((Some(ret), Some((payload, command))))
+			Ok((Some(ret), Some(Command::Ceremony(payload, command))))
+		},
+		DirectCall::SignBitcoinPsbt(signer, psbt, input_index) => {
+			// PSBTs are refused once this enclave holds a threshold key share
+			let key_share = context.threshold_key_share_repository.retrieve_key().map_err(|e| {
+				error!("Could not retrieve threshold key share, error: {:?}", e);
+				SignBitcoinPsbtError::CeremonyError.encode()
+			})?;
+			let relayer = signer.clone();
+			let (payload, command) = sign_bitcoin_psbt::handle(
+				signer,
+				&psbt,
+				input_index,
+				context.relayer_registry_lookup.deref(),
+				context.signer_registry_lookup.deref(),
+				context.signing_policy.deref(),
+				key_share.as_ref(),
+				get_current_timestamp(),
+			)
+			.map_err(|e| {
+				error!("SignBitcoinPsbt error: {:?}", e);
+				e.encode()
+			})?;
+			let hash = blake2_256(&payload.encode());
+			let entry = AuditEntry::new(
+				relayer,
+				SigningChain::Bitcoin,
+				&psbt,
+				Some(hash),
+				get_current_timestamp(),
+			);
+			await_ceremony(context.signature_audit_log.deref(), entry)?;
+			let ret = BitAcrossProcessingResult::Submitted(hash);
+			Ok((Some(ret), Some(Command::Ceremony(payload, command))))
+		},
+		DirectCall::SignEthereum(signer, msg) => {
+			let entry = AuditEntry::new(
+				signer.clone(),
+				SigningChain::Ethereum,
+				&msg,
+				None,
+				get_current_timestamp(),
+			);
+			sign_ethereum::handle(
+				signer,
+				msg,
+				context.relayer_registry_lookup.deref(),
+				context.signing_policy.deref(),
+				context.ethereum_key_repository.deref(),
+				get_current_timestamp(),
+			)
+			.map_err(|e| {
+				error!("SignEthereum error: {:?}", e);
+				e.encode()
+			})
+			.and_then(|r| log_signature(context.signature_audit_log.deref(), entry, r))
+		},
+		DirectCall::SignEthereumTransaction(signer, raw) => {
+			let entry = AuditEntry::new(
+				signer.clone(),
+				SigningChain::Ethereum,
+				&raw,
+				None,
+				get_current_timestamp(),
+			);
+			sign_ethereum::handle_transaction(
+				signer,
+				&raw,
+				context.relayer_registry_lookup.deref(),
+				context.signing_policy.deref(),
+				context.ethereum_key_repository.deref(),
+				get_current_timestamp(),
+			)
+			.map_err(|e| {
+				error!("SignEthereumTransaction error: {:?}", e);
+				e.encode()
+			})
+			.and_then(|r| log_signature(context.signature_audit_log.deref(), entry, r))
+		},
+		DirectCall::SignEthereumTypedData(signer, json) => {
+			let entry = AuditEntry::new(
+				signer.clone(),
+				SigningChain::Ethereum,
+				&json,
+				None,
+				get_current_timestamp(),
+			);
+			sign_ethereum::handle_typed_data(
+				signer,
+				&json,
+				context.relayer_registry_lookup.deref(),
+				context.signing_policy.deref(),
+				context.ethereum_key_repository.deref(),
+				get_current_timestamp(),
+			)
+			.map_err(|e| {
+				error!("SignEthereumTypedData error: {:?}", e);
+				e.encode()
+			})
+			.and_then(|r| log_signature(context.signature_audit_log.deref(), entry, r))
+		},
+		DirectCall::SignTon(signer, payload) => {
+			let entry = AuditEntry::new(
+				signer.clone(),
+				SigningChain::Ton,
+				&payload,
+				None,
+				get_current_timestamp(),
+			);
+			sign_ton::handle(
+				signer,
+				payload,
+				context.relayer_registry_lookup.deref(),
+				context.ton_key_repository.deref(),
+				get_current_timestamp(),
+			)
+			.map_err(|e| {
+				error!("SignTon error: {:?}", e);
+				e.encode()
+			})
+			.and_then(|r| log_signature(context.signature_audit_log.deref(), entry, r))
+		},
+		DirectCall::SignSolana(signer, message) => {
+			let entry = AuditEntry::new(
+				signer.clone(),
+				SigningChain::Solana,
+				&message,
+				None,
+				get_current_timestamp(),
+			);
+			sign_solana::handle(
+				signer,
+				&message,
+				context.relayer_registry_lookup.deref(),
+				context.solana_key_repository.deref(),
+				get_current_timestamp(),
+			)
+			.map_err(|e| {
+				error!("SignSolana error: {:?}", e);
+				e.encode()
+			})
+			.and_then(|r| log_signature(context.signature_audit_log.deref(), entry, r))
+		},
+		DirectCall::GenerateThresholdKey(signer, threshold) => {
+			let command = generate_threshold_key::handle(
+				signer,
+				threshold,
+				context.relayer_registry_lookup.deref(),
+				context.signer_registry_lookup.deref(),
+			)
+			.map_err(|e| {
+				error!("GenerateThresholdKey error: {:?}", e);
+				e.encode()
+			})?;
+			let key_generation_id = command.key_generation_id();
+			let hash = blake2_256(&key_generation_id.encode());
+			let ret = BitAcrossProcessingResult::Submitted(hash);
+			Ok((Some(ret), Some(Command::KeyGeneration(key_generation_id, command))))
 		},
-		DirectCall::SignEthereum(signer, msg) => sign_ethereum::handle(
-			signer,
-			msg,
-			context.relayer_registry_lookup.deref(),
-			context.ethereum_key_repository.deref(),
-		)
-		.map_err(|e| {
-			error!("SignEthereum error: {:?}", e);
-			e.encode()
-		})
-		.map(|r| (Some(BitAcrossProcessingResult::Ok(r.encode())), None)),
-		DirectCall::SignTon(signer, payload) => sign_ton::handle(
-			signer,
-			payload,
-			context.relayer_registry_lookup.deref(),
-			context.ton_key_repository.deref(),
-		)
-		.map_err(|e| {
-			error!("SignTon error: {:?}", e);
-			e.encode()
-		})
-		.map(|r| (Some(BitAcrossProcessingResult::Ok(r.encode())), None)),
 	}
 }
 
+// a signature is only handed out once it's in the audit log
+fn log_signature<SAL: SignatureAuditLogUpdater, R: Encode>(
+	signature_audit_log: &SAL,
+	entry: AuditEntry,
+	signature: R,
+) -> Result<(Option<BitAcrossProcessingResult>, Option<Command>), Vec<u8>> {
+	signature_audit_log.append(entry).map_err(|e| {
+		error!("Could not append to signature audit log, error: {:?}", e);
+		"Failed to record signature".to_string()
+	})?;
+	Ok((Some(BitAcrossProcessingResult::Ok(signature.encode())), None))
+}
+
+fn await_ceremony<SAL: SignatureAuditLogUpdater>(
+	signature_audit_log: &SAL,
+	entry: AuditEntry,
+) -> Result<(), Vec<u8>> {
+	let now = entry.timestamp;
+	signature_audit_log.await_ceremony(entry, now).map_err(|e| {
+		error!("Could not append to signature audit log, error: {:?}", e);
+		"Failed to record signature".to_string().into()
+	})
+}
+
 #[allow(clippy::type_complexity)]
-fn handle_ceremony_round_call<SKR, SIGNINGAK, EKR, BKR, TKR, S, H, O, RRL, ERL, SRL, Responder>(
+fn handle_ceremony_round_call<
+	SKR,
+	SIGNINGAK,
+	EKR,
+	BKR,
+	TKR,
+	KSR,
+	S,
+	H,
+	O,
+	RRL,
+	ERL,
+	SRL,
+	BSP,
+	SAL,
+	Responder,
+>(
 	request: PlainRequest,
 	context: Arc<
-		BitAcrossTaskContext<SKR, SIGNINGAK, EKR, BKR, TKR, S, H, O, RRL, ERL, SRL, Responder>,
+		BitAcrossTaskContext<
+			SKR,
+			SIGNINGAK,
+			EKR,
+			BKR,
+			TKR,
+			KSR,
+			S,
+			H,
+			O,
+			RRL,
+			ERL,
+			SRL,
+			BSP,
+			SAL,
+			Responder,
+		>,
 	>,
-) -> Result<Option<(CeremonyId, CeremonyCommand)>, Vec<u8>>
+) -> Result<Option<Command>, Vec<u8>>
 where
 	SKR: AccessKey + AccessPubkey<KeyType = Rsa3072PubKey>,
 	SIGNINGAK: AccessKey<KeyType = ed25519::Pair>,
 	EKR: AccessKey<KeyType = EcdsaPair>,
 	BKR: AccessKey<KeyType = SchnorrPair>,
 	TKR: AccessKey<KeyType = ed25519::Pair> + Send + Sync + 'static,
+	KSR: AccessKey<KeyType = Option<ThresholdKeyShare>>
+		+ MutateKey<Option<ThresholdKeyShare>>
+		+ Send
+		+ Sync
+		+ 'static,
 	<SKR as AccessKey>::KeyType: ShieldingCryptoEncrypt + ShieldingCryptoDecrypt + 'static,
 	S: StfEnclaveSigning<TrustedCallSigned> + Send + Sync + 'static,
 	H: HandleState + Send + Sync + 'static,
@@ -721,6 +1605,8 @@ where
 	RRL: RelayerRegistryLookup + 'static,
 	ERL: EnclaveRegistryLookup + 'static,
 	SRL: SignerRegistryLookup + 'static,
+	BSP: SigningPolicyEnforcer + SigningPolicyUpdater + EthAllowlistLookup + 'static,
+	SAL: SignatureAuditLogUpdater + SignatureAuditLogLookup + 'static,
 	Responder: SendRpcResponse<Hash = H256> + Send + Sync + 'static,
 {
 	let crc = CeremonyRoundCallSigned::decode(&mut request.payload.as_slice()).map_err(|e| {
@@ -745,7 +1631,7 @@ where
 					error!("NonceShare error: {:?}", e);
 					e.encode()
 				})
-				.map(|command| Some((message, command))),
+				.map(|command| Some(Command::Ceremony(message, command))),
 		CeremonyRoundCall::PartialSignatureShare(signer, message, signature) =>
 			partial_signature_share::handle(
 				signer,
@@ -757,13 +1643,129 @@ where
 				error!("PartialSignatureShare error: {:?}", e);
 				e.encode()
 			})
-			.map(|command| Some((message, command))),
+			.map(|command| Some(Command::Ceremony(message, command))),
 		CeremonyRoundCall::KillCeremony(signer, message) =>
 			kill_ceremony::handle(signer, context.enclave_registry_lookup.as_ref())
 				.map_err(|e| {
 					error!("KillCeremony error: {:?}", e);
 					e.encode()
 				})
-				.map(|command| Some((message, command))),
+				.map(|command| Some(Command::Ceremony(message, command))),
+		CeremonyRoundCall::ThresholdPartialSignatureShare(
+			signer,
+			message,
+			signing_set,
+			signature,
+		) => threshold_partial_signature_share::handle(
+			signer,
+			&message,
+			signing_set,
+			signature,
+			context.enclave_registry_lookup.clone(),
+		)
+		.map_err(|e| {
+			error!("ThresholdPartialSignatureShare error: {:?}", e);
+			e.encode()
+		})
+		.map(|command| Some(Command::Ceremony(message, command))),
+		CeremonyRoundCall::KeyGenerationPackageShare(
+			signer,
+			key_generation_id,
+			threshold,
+			package,
+		) => key_generation_package_share::handle(
+			signer,
+			&key_generation_id,
+			threshold,
+			package,
+			context.enclave_registry_lookup.as_ref(),
+			context.signer_registry_lookup.as_ref(),
+		)
+		.map_err(|e| {
+			error!("KeyGenerationPackageShare error: {:?}", e);
+			e.encode()
+		})
+		.map(|command| Some(Command::KeyGeneration(key_generation_id, command))),
+		CeremonyRoundCall::ResharingPackageShare(
+			signer,
+			key_generation_id,
+			signers,
+			reshared_key,
+			package,
+		) => resharing_package_share::handle(
+			signer,
+			&key_generation_id,
+			signers,
+			reshared_key,
+			package,
+			context.enclave_registry_lookup.as_ref(),
+		)
+		.map_err(|e| {
+			error!("ResharingPackageShare error: {:?}", e);
+			e.encode()
+		})
+		.map(|command| Some(Command::KeyGeneration(key_generation_id, command))),
+		CeremonyRoundCall::BatchNonceShare(signer, message, nonces) => batch_nonce_share::handle(
+			signer,
+			&message,
+			nonces,
+			context.enclave_registry_lookup.clone(),
+		)
+		.map_err(|e| {
+			error!("BatchNonceShare error: {:?}", e);
+			e.encode()
+		})
+		.map(|command| Some(Command::Ceremony(message, command))),
+		CeremonyRoundCall::BatchPartialSignatureShare(signer, message, signatures) =>
+			batch_partial_signature_share::handle(
+				signer,
+				&message,
+				signatures,
+				context.enclave_registry_lookup.clone(),
+			)
+			.map_err(|e| {
+				error!("BatchPartialSignatureShare error: {:?}", e);
+				e.encode()
+			})
+			.map(|command| Some(Command::Ceremony(message, command))),
+		CeremonyRoundCall::ThresholdBatchPartialSignatureShare(
+			signer,
+			message,
+			signing_set,
+			signatures,
+		) => threshold_batch_partial_signature_share::handle(
+			signer,
+			&message,
+			signing_set,
+			signatures,
+			context.enclave_registry_lookup.clone(),
+		)
+		.map_err(|e| {
+			error!("ThresholdBatchPartialSignatureShare error: {:?}", e);
+			e.encode()
+		})
+		.map(|command| Some(Command::Ceremony(message, command))),
+		CeremonyRoundCall::NoncePoolShare(signer, nonces) => {
+			let (signer, nonces) = nonce_pool_share::handle(
+				signer,
+				nonces,
+				context.enclave_registry_lookup.as_ref(),
+				context.signer_registry_lookup.as_ref(),
+			)
+			.map_err(|e| {
+				error!("NoncePoolShare error: {:?}", e);
+				e.encode()
+			})?;
+			context
+				.nonce_pool
+				.write()
+				.unwrap()
+				.receive_peer_nonces(signer, nonces)
+				.map_err(|e| {
+					error!("Could not save pre-shared nonces, error: {:?}", e);
+					format!("{:?}", e).encode()
+				})?;
+			Ok(None)
+		},
 	}
 }
@@ -35,7 +35,7 @@ pub use crate::sgx_reexport_prelude::*;
 use codec::{Decode, Encode};
 use futures::channel::oneshot;
 use lazy_static::lazy_static;
-use litentry_primitives::PlainRequest;
+use litentry_primitives::{BtcSigningPolicy, EthSigningAllowlist, Identity, PlainRequest};
 #[cfg(feature = "std")]
 use std::sync::Mutex;
 #[cfg(feature = "sgx")]
@@ -54,6 +54,12 @@ use std::{
 pub enum BitAcrossRequest {
 	Request(PlainRequest, oneshot::Sender<Result<BitAcrossProcessingResult, Vec<u8>>>),
 	ShareCeremonyData(PlainRequest),
+	// the set of registered enclaves changed, their signers should hold the threshold key
+	ReshareThresholdKey,
+	// a new signing policy for PSBTs was published on the parentchain
+	SetBtcSigningPolicy(BtcSigningPolicy),
+	// the Ethereum signing allowlist of a relayer was set or removed on the parentchain
+	SetEthSigningAllowlist(Identity, Option<EthSigningAllowlist>),
 }
 
 #[derive(Encode, Decode, Clone, Debug)]
@@ -94,7 +100,8 @@ impl BitAcrossRequestSender {
 	pub fn send(&self, request: BitAcrossRequest) -> Result<(), String> {
 		// Acquire lock on extrinsic sender
 		let mutex_guard = GLOBAL_BIT_ACROSS_TASK_SENDER.lock().unwrap();
-		let bit_across_task_sender = mutex_guard.clone().unwrap();
+		let bit_across_task_sender =
+			mutex_guard.clone().ok_or("BitAcross task sender is not initialized")?;
 		// Release mutex lock, so we don't block the lock longer than necessary.
 		drop(mutex_guard);
 
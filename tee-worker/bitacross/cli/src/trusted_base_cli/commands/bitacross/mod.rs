@@ -15,6 +15,10 @@
 // along with Litentry.  If not, see <https://www.gnu.org/licenses/>.
 
 pub mod direct_call_sign_bitcoin;
+pub mod direct_call_sign_bitcoin_psbt;
 pub mod direct_call_sign_ethereum;
+pub mod direct_call_sign_ethereum_transaction;
+pub mod direct_call_sign_ethereum_typed_data;
+pub mod direct_call_sign_solana;
 
 pub mod utils;
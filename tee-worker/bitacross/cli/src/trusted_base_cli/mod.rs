@@ -20,7 +20,11 @@ use crate::{
 		balance::BalanceCommand,
 		bitacross::{
 			direct_call_sign_bitcoin::RequestDirectCallSignBitcoinCommand,
+			direct_call_sign_bitcoin_psbt::RequestDirectCallSignBitcoinPsbtCommand,
 			direct_call_sign_ethereum::RequestDirectCallSignEthereumCommand,
+			direct_call_sign_ethereum_transaction::RequestDirectCallSignEthereumTransactionCommand,
+			direct_call_sign_ethereum_typed_data::RequestDirectCallSignEthereumTypedDataCommand,
+			direct_call_sign_solana::RequestDirectCallSignSolanaCommand,
 		},
 		get_shard::GetShardCommand,
 		nonce::NonceCommand,
@@ -69,8 +73,20 @@ pub enum TrustedBaseCommand {
 	/// sign bitcoin transaction using custodian wallet
 	RequestDirectCallSignBitcoin(RequestDirectCallSignBitcoinCommand),
 
+	/// sign an input of a PSBT spending from the vault, subject to the signing policy
+	RequestDirectCallSignBitcoinPsbt(RequestDirectCallSignBitcoinPsbtCommand),
+
 	/// sign ethereum transaction using custodian wallet
 	RequestDirectCallSignEthereum(RequestDirectCallSignEthereumCommand),
+
+	/// sign an ethereum transaction, subject to the allowlist of the relayer
+	RequestDirectCallSignEthereumTransaction(RequestDirectCallSignEthereumTransactionCommand),
+
+	/// sign EIP-712 typed data, subject to the allowlist of the relayer
+	RequestDirectCallSignEthereumTypedData(RequestDirectCallSignEthereumTypedDataCommand),
+
+	/// sign a solana transaction message using custodian wallet
+	RequestDirectCallSignSolana(RequestDirectCallSignSolanaCommand),
 }
 
 impl TrustedBaseCommand {
@@ -85,7 +101,13 @@ impl TrustedBaseCommand {
 			TrustedBaseCommand::Nonce(cmd) => cmd.run(cli, trusted_cli),
 			TrustedBaseCommand::GetShard(cmd) => cmd.run(cli, trusted_cli),
 			TrustedBaseCommand::RequestDirectCallSignBitcoin(cmd) => cmd.run(cli, trusted_cli),
+			TrustedBaseCommand::RequestDirectCallSignBitcoinPsbt(cmd) => cmd.run(cli, trusted_cli),
 			TrustedBaseCommand::RequestDirectCallSignEthereum(cmd) => cmd.run(cli, trusted_cli),
+			TrustedBaseCommand::RequestDirectCallSignEthereumTransaction(cmd) =>
+				cmd.run(cli, trusted_cli),
+			TrustedBaseCommand::RequestDirectCallSignEthereumTypedData(cmd) =>
+				cmd.run(cli, trusted_cli),
+			TrustedBaseCommand::RequestDirectCallSignSolana(cmd) => cmd.run(cli, trusted_cli),
 		}
 	}
 }
@@ -161,6 +161,16 @@ impl FilterEvents for MockEvents {
 		Ok(Vec::new())
 	}
 
+	fn get_assertion_created_with_policy_events(
+		&self,
+	) -> Result<Vec<AssertionCreatedWithPolicy>, Self::Error> {
+		Ok(Vec::new())
+	}
+
+	fn get_assertion_version_events(&self) -> Result<Vec<AssertionVersionEvent>, Self::Error> {
+		Ok(Vec::new())
+	}
+
 	fn get_parentchain_block_proccessed_events(
 		&self,
 	) -> Result<Vec<ParentchainBlockProcessed>, Self::Error> {
@@ -196,6 +206,24 @@ impl FilterEvents for MockEvents {
 	) -> Result<Vec<itp_types::parentchain::events::BtcWalletGenerated>, Self::Error> {
 		Ok(Vec::new())
 	}
+
+	fn get_btc_signing_policy_set_events(
+		&self,
+	) -> Result<Vec<itp_types::parentchain::events::BtcSigningPolicySet>, Self::Error> {
+		Ok(Vec::new())
+	}
+
+	fn get_eth_signing_allowlist_set_events(
+		&self,
+	) -> Result<Vec<itp_types::parentchain::events::EthSigningAllowlistSet>, Self::Error> {
+		Ok(Vec::new())
+	}
+
+	fn get_relayer_permissions_set_events(
+		&self,
+	) -> Result<Vec<itp_types::parentchain::events::RelayerPermissionsSet>, Self::Error> {
+		Ok(Vec::new())
+	}
 }
 
 pub struct MockParentchainEventHandler {}
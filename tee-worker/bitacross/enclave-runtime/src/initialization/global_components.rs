@@ -31,8 +31,11 @@ use crate::{
 	tls_ra::seal_handler::SealHandler,
 };
 use bc_enclave_registry::EnclaveRegistry;
+use bc_musig2_ceremony::threshold::sgx::ThresholdKeyShareRepository;
 use bc_relayer_registry::RelayerRegistry;
+use bc_signature_audit_log::SignatureAuditLog;
 use bc_signer_registry::SignerRegistry;
+use bc_signing_policy::SigningPolicyRegistry;
 use ita_parentchain_interface::{integritee, target_a, target_b};
 use ita_sgx_runtime::Runtime;
 use ita_stf::{Getter, State as StfState, Stf, TrustedCallSigned};
@@ -102,8 +105,10 @@ pub type EnclaveStateKeyRepository = KeyRepository<Aes, AesSeal>;
 pub type EnclaveShieldingKeyRepository = KeyRepository<Rsa3072KeyPair, Rsa3072Seal>;
 pub type EnclaveSigningKeyRepository = KeyRepository<ed25519::Pair, Ed25519Seal>;
 pub type EnclaveBitcoinKeyRepository = KeyRepository<SchnorrPair, SchnorrSeal>;
+pub type EnclaveThresholdKeyShareRepository = ThresholdKeyShareRepository;
 pub type EnclaveEthereumKeyRepository = KeyRepository<EcdsaPair, EcdsaSeal>;
 pub type EnclaveTonKeyRepository = KeyRepository<ed25519::Pair, Ed25519Seal>;
+pub type EnclaveSolanaKeyRepository = KeyRepository<ed25519::Pair, Ed25519Seal>;
 pub type EnclaveStateFileIo = SgxStateFileIo<EnclaveStateKeyRepository, StfState>;
 pub type EnclaveStateSnapshotRepository = StateSnapshotRepository<EnclaveStateFileIo>;
 pub type EnclaveStateObserver = StateObserver<StfState>;
@@ -352,6 +357,11 @@ pub static GLOBAL_BITCOIN_KEY_REPOSITORY_COMPONENT: ComponentContainer<
 	EnclaveBitcoinKeyRepository,
 > = ComponentContainer::new("Bitcoin key repository");
 
+/// Threshold key share repository
+pub static GLOBAL_THRESHOLD_KEY_SHARE_REPOSITORY_COMPONENT: ComponentContainer<
+	EnclaveThresholdKeyShareRepository,
+> = ComponentContainer::new("Threshold key share repository");
+
 /// Ethereum key repository
 pub static GLOBAL_ETHEREUM_KEY_REPOSITORY_COMPONENT: ComponentContainer<
 	EnclaveEthereumKeyRepository,
@@ -361,6 +371,10 @@ pub static GLOBAL_ETHEREUM_KEY_REPOSITORY_COMPONENT: ComponentContainer<
 pub static GLOBAL_TON_KEY_REPOSITORY_COMPONENT: ComponentContainer<EnclaveTonKeyRepository> =
 	ComponentContainer::new("Ton key repository");
 
+/// Solana key repository
+pub static GLOBAL_SOLANA_KEY_REPOSITORY_COMPONENT: ComponentContainer<EnclaveSolanaKeyRepository> =
+	ComponentContainer::new("Solana key repository");
+
 /// Light client db seal for the Integritee parentchain
 pub static GLOBAL_INTEGRITEE_PARENTCHAIN_LIGHT_CLIENT_SEAL: ComponentContainer<
 	EnclaveLightClientSeal,
@@ -454,6 +468,14 @@ pub static GLOBAL_RELAYER_REGISTRY: ComponentContainer<RelayerRegistry> =
 pub static GLOBAL_SIGNER_REGISTRY: ComponentContainer<SignerRegistry> =
 	ComponentContainer::new("signer_registry");
 
+/// Signing policy for PSBTs
+pub static GLOBAL_SIGNING_POLICY_REGISTRY: ComponentContainer<SigningPolicyRegistry> =
+	ComponentContainer::new("signing_policy_registry");
+
+/// Log of the signatures handed out to relayers
+pub static GLOBAL_SIGNATURE_AUDIT_LOG: ComponentContainer<SignatureAuditLog> =
+	ComponentContainer::new("signature_audit_log");
+
 /// Enclave registry
 pub static GLOBAL_ENCLAVE_REGISTRY: ComponentContainer<EnclaveRegistry> =
 	ComponentContainer::new("enclave_registry");
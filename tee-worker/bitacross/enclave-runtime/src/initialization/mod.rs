@@ -32,10 +32,11 @@ use crate::{
 		GLOBAL_BITCOIN_KEY_REPOSITORY_COMPONENT, GLOBAL_ETHEREUM_KEY_REPOSITORY_COMPONENT,
 		GLOBAL_INTEGRITEE_PARENTCHAIN_LIGHT_CLIENT_SEAL, GLOBAL_OCALL_API_COMPONENT,
 		GLOBAL_RPC_WS_HANDLER_COMPONENT, GLOBAL_SHIELDING_KEY_REPOSITORY_COMPONENT,
-		GLOBAL_SIGNING_KEY_REPOSITORY_COMPONENT, GLOBAL_STATE_HANDLER_COMPONENT,
-		GLOBAL_STATE_KEY_REPOSITORY_COMPONENT, GLOBAL_STATE_OBSERVER_COMPONENT,
-		GLOBAL_TARGET_A_PARENTCHAIN_LIGHT_CLIENT_SEAL,
-		GLOBAL_TARGET_B_PARENTCHAIN_LIGHT_CLIENT_SEAL, GLOBAL_TON_KEY_REPOSITORY_COMPONENT,
+		GLOBAL_SIGNING_KEY_REPOSITORY_COMPONENT, GLOBAL_SOLANA_KEY_REPOSITORY_COMPONENT,
+		GLOBAL_STATE_HANDLER_COMPONENT, GLOBAL_STATE_KEY_REPOSITORY_COMPONENT,
+		GLOBAL_STATE_OBSERVER_COMPONENT, GLOBAL_TARGET_A_PARENTCHAIN_LIGHT_CLIENT_SEAL,
+		GLOBAL_TARGET_B_PARENTCHAIN_LIGHT_CLIENT_SEAL,
+		GLOBAL_THRESHOLD_KEY_SHARE_REPOSITORY_COMPONENT, GLOBAL_TON_KEY_REPOSITORY_COMPONENT,
 		GLOBAL_TOP_POOL_AUTHOR_COMPONENT, GLOBAL_WEB_SOCKET_SERVER_COMPONENT,
 	},
 	ocall::OcallApi,
@@ -45,10 +46,16 @@ use crate::{
 };
 use base58::ToBase58;
 use bc_enclave_registry::EnclaveRegistryUpdater;
-use bc_musig2_ceremony::{CeremonyCommandTmp, CeremonyId, CeremonyRegistry, MuSig2Ceremony};
+use bc_musig2_ceremony::{
+	threshold::sgx::create_threshold_key_share_repository, Ceremony, CeremonyCommandTmp,
+	CeremonyFailureHistory, CeremonyId, CeremonyRegistry, KeyGenerationRegistry, NoncePool,
+	ThresholdKeyShare,
+};
 use bc_relayer_registry::{RelayerRegistry, RelayerRegistryUpdater};
 use bc_signer_registry::SignerRegistryUpdater;
-use bc_task_processor::{run_bit_across_handler_runner, BitAcrossTaskContext};
+use bc_task_processor::{
+	run_bit_across_handler_runner, BitAcrossTaskContext, PublishAuditRoot, PublishThresholdKey,
+};
 use codec::Encode;
 use ita_stf::{Getter, TrustedCallSigned};
 use itc_direct_rpc_server::{
@@ -80,10 +87,13 @@ use itp_sgx_crypto::{
 };
 
 use crate::initialization::global_components::{
-	GLOBAL_ENCLAVE_REGISTRY, GLOBAL_RELAYER_REGISTRY, GLOBAL_SIGNER_REGISTRY,
+	GLOBAL_ENCLAVE_REGISTRY, GLOBAL_RELAYER_REGISTRY, GLOBAL_SIGNATURE_AUDIT_LOG,
+	GLOBAL_SIGNER_REGISTRY, GLOBAL_SIGNING_POLICY_REGISTRY,
 };
 use bc_enclave_registry::EnclaveRegistry;
+use bc_signature_audit_log::{SignatureAuditLog, SignatureAuditLogUpdater};
 use bc_signer_registry::SignerRegistry;
+use bc_signing_policy::{SigningPolicyRegistry, SigningPolicyUpdater};
 use itp_stf_state_handler::{
 	file_io::StateDir, handle_state::HandleState, query_shard_state::QueryShardState,
 	state_snapshot_repository::VersionedStateAccess,
@@ -126,6 +136,11 @@ pub(crate) fn init_enclave(
 	let bitcoin_key = bitcoin_key_repository.retrieve_key()?;
 	info!("[Enclave initialized] Bitcoin public key raw : {:?}", bitcoin_key.public_bytes());
 
+	let threshold_key_share_repository =
+		Arc::new(create_threshold_key_share_repository(base_dir.clone())?);
+	GLOBAL_THRESHOLD_KEY_SHARE_REPOSITORY_COMPONENT
+		.initialize(threshold_key_share_repository.clone());
+
 	let ethereum_key_repository =
 		Arc::new(create_ecdsa_repository(base_dir.clone(), "ethereum", None)?);
 	GLOBAL_ETHEREUM_KEY_REPOSITORY_COMPONENT.initialize(ethereum_key_repository.clone());
@@ -138,6 +153,12 @@ pub(crate) fn init_enclave(
 	let ton_key = ton_key_repository.retrieve_key()?;
 	info!("[Enclave initialized] Ton public key raw : {:?}", ton_key.public().0);
 
+	let solana_key_repository =
+		Arc::new(get_ed25519_repository(base_dir.clone(), Some("solana".to_string()), None)?);
+	GLOBAL_SOLANA_KEY_REPOSITORY_COMPONENT.initialize(solana_key_repository.clone());
+	let solana_key = solana_key_repository.retrieve_key()?;
+	info!("[Enclave initialized] Solana public key raw : {:?}", solana_key.public().0);
+
 	let shielding_key_repository = Arc::new(get_rsa3072_repository(base_dir.clone())?);
 	GLOBAL_SHIELDING_KEY_REPOSITORY_COMPONENT.initialize(shielding_key_repository.clone());
 
@@ -230,11 +251,20 @@ pub(crate) fn init_enclave(
 
 	let ceremony_registry = Arc::new(RwLock::new(HashMap::<
 		CeremonyId,
-		(Arc<RwLock<MuSig2Ceremony<KeyRepository<SchnorrPair, Seal>>>>, u64),
+		(Arc<RwLock<Ceremony<KeyRepository<SchnorrPair, Seal>>>>, u64),
 	>::new()));
 
 	let ceremony_command_tmp = Arc::new(RwLock::new(CeremonyCommandTmp::new()));
 
+	let key_generation_registry =
+		Arc::new(RwLock::new(KeyGenerationRegistry::<KeyRepository<SchnorrPair, Seal>>::new()));
+
+	let mut nonce_pool = NoncePool::new(base_dir.clone());
+	nonce_pool.init().map_err(|e| Error::Other(format!("{:?}", e).into()))?;
+	let nonce_pool = Arc::new(RwLock::new(nonce_pool));
+
+	let ceremony_failures = Arc::new(RwLock::new(CeremonyFailureHistory::default()));
+
 	let attestation_handler =
 		Arc::new(IntelAttestationHandler::new(ocall_api.clone(), signing_key_repository.clone()));
 	GLOBAL_ATTESTATION_HANDLER_COMPONENT.initialize(attestation_handler);
@@ -247,6 +277,14 @@ pub(crate) fn init_enclave(
 	signer_registry.init().map_err(|e| Error::Other(e.into()))?;
 	GLOBAL_SIGNER_REGISTRY.initialize(signer_registry.clone());
 
+	let signing_policy_registry = Arc::new(SigningPolicyRegistry::new(base_dir.clone()));
+	signing_policy_registry.init().map_err(|e| Error::Other(e.into()))?;
+	GLOBAL_SIGNING_POLICY_REGISTRY.initialize(signing_policy_registry);
+
+	let signature_audit_log = Arc::new(SignatureAuditLog::new(base_dir.clone()));
+	signature_audit_log.init().map_err(|e| Error::Other(e.into()))?;
+	GLOBAL_SIGNATURE_AUDIT_LOG.initialize(signature_audit_log.clone());
+
 	let enclave_registry = Arc::new(EnclaveRegistry::new(base_dir));
 	enclave_registry.init().map_err(|e| Error::Other(e.into()))?;
 	GLOBAL_ENCLAVE_REGISTRY.initialize(enclave_registry);
@@ -260,7 +298,13 @@ pub(crate) fn init_enclave(
 		bitcoin_key_repository,
 		ethereum_key_repository,
 		ton_key_repository,
+		solana_key_repository,
+		threshold_key_share_repository,
 		signer_registry,
+		signature_audit_log,
+		nonce_pool.clone(),
+		ceremony_registry.clone(),
+		ceremony_failures.clone(),
 	);
 	let rpc_handler = Arc::new(RpcWsHandler::new(io_handler, watch_extractor, connection_registry));
 	GLOBAL_RPC_WS_HANDLER_COMPONENT.initialize(rpc_handler);
@@ -269,6 +313,9 @@ pub(crate) fn init_enclave(
 		run_bit_across_handler(
 			ceremony_registry,
 			ceremony_command_tmp,
+			key_generation_registry,
+			nonce_pool,
+			ceremony_failures,
 			signer.public().0,
 			rpc_responder,
 			ceremony_commands_thread_count,
@@ -290,7 +337,9 @@ pub(crate) fn finish_enclave_init() -> EnclaveResult<()> {
 pub(crate) fn init_wallets(base_dir: PathBuf) -> EnclaveResult<()> {
 	if_development_or!(
 		{
-			println!("Initializing wallets from BTC_KEY, ETH_KEY and TON_KEY env variables");
+			println!(
+				"Initializing wallets from BTC_KEY, ETH_KEY, TON_KEY and SOL_KEY env variables"
+			);
 			let btc_key: Option<[u8; 32]> = read_key_from_env("BTC_KEY")?;
 			if btc_key.is_some() {
 				create_schnorr_repository(base_dir.clone(), "bitcoin", btc_key)?;
@@ -303,7 +352,12 @@ pub(crate) fn init_wallets(base_dir: PathBuf) -> EnclaveResult<()> {
 
 			let ton_key: Option<[u8; 32]> = read_key_from_env("TON_KEY")?;
 			if ton_key.is_some() {
-				get_ed25519_repository(base_dir, Some("ton".to_string()), ton_key)?;
+				get_ed25519_repository(base_dir.clone(), Some("ton".to_string()), ton_key)?;
+			}
+
+			let sol_key: Option<[u8; 32]> = read_key_from_env("SOL_KEY")?;
+			if sol_key.is_some() {
+				get_ed25519_repository(base_dir, Some("solana".to_string()), sol_key)?;
 			}
 		},
 		{
@@ -366,8 +420,97 @@ pub(crate) fn publish_wallets() -> EnclaveResult<()> {
 
 	let ton_opaque_call = OpaqueCall::from_tuple(&(ton_call, ton_key.public().0));
 
+	let solana_key_repository = GLOBAL_SOLANA_KEY_REPOSITORY_COMPONENT.get()?;
+	let solana_key = solana_key_repository.retrieve_key()?;
+
+	let solana_call = metadata_repository
+		.get_from_metadata(|m| m.sol_wallet_generated_indexes())
+		.map_err(|e| Error::Other(e.into()))?
+		.map_err(|e| Error::Other(format!("{:?}", e).into()))?;
+
+	let solana_opaque_call = OpaqueCall::from_tuple(&(solana_call, solana_key.public().0));
+
+	let xts = extrinsics_factory
+		.create_extrinsics(
+			&[bitcoin_opaque_call, ethereum_opaque_call, ton_opaque_call, solana_opaque_call],
+			None,
+		)
+		.map_err(|e| Error::Other(e.into()))?;
+	validator_accessor
+		.execute_mut_on_validator(|v| v.send_extrinsics(xts))
+		.map_err(|e| Error::Other(e.into()))?;
+
+	Ok(())
+}
+
+/// Publishes the group key of the threshold key share to the parentchain.
+///
+/// Parentchain components are initialized after the bitacross handler is started, so they are
+/// looked up on each call.
+pub(crate) struct EnclaveThresholdKeyPublisher;
+
+impl PublishThresholdKey for EnclaveThresholdKeyPublisher {
+	fn threshold_key_generated(&self, key_share: &ThresholdKeyShare) -> Result<(), String> {
+		publish_threshold_key(key_share, false).map_err(|e| format!("{:?}", e))
+	}
+
+	fn threshold_key_reshared(&self, key_share: &ThresholdKeyShare) -> Result<(), String> {
+		publish_threshold_key(key_share, true).map_err(|e| format!("{:?}", e))
+	}
+}
+
+fn publish_threshold_key(key_share: &ThresholdKeyShare, reshared: bool) -> EnclaveResult<()> {
+	let metadata_repository = get_node_metadata_repository_from_integritee_solo_or_parachain()?;
+	let extrinsics_factory = get_extrinsic_factory_from_integritee_solo_or_parachain()?;
+	let validator_accessor = get_validator_accessor_from_integritee_solo_or_parachain()?;
+
+	let call = metadata_repository
+		.get_from_metadata(|m| {
+			if reshared {
+				m.threshold_key_reshared_indexes()
+			} else {
+				m.threshold_key_generated_indexes()
+			}
+		})
+		.map_err(|e| Error::Other(e.into()))?
+		.map_err(|e| Error::Other(format!("{:?}", e).into()))?;
+
+	let opaque_call =
+		OpaqueCall::from_tuple(&(call, key_share.group_public_key, key_share.threshold));
+
+	let xts = extrinsics_factory
+		.create_extrinsics(&[opaque_call], None)
+		.map_err(|e| Error::Other(e.into()))?;
+	validator_accessor
+		.execute_mut_on_validator(|v| v.send_extrinsics(xts))
+		.map_err(|e| Error::Other(e.into()))?;
+
+	Ok(())
+}
+
+/// Posts the root of the signature audit log to the parentchain.
+pub(crate) struct EnclaveAuditRootPublisher;
+
+impl PublishAuditRoot for EnclaveAuditRootPublisher {
+	fn signature_audit_root(&self, root: H256, size: u64) -> Result<(), String> {
+		publish_signature_audit_root(root, size).map_err(|e| format!("{:?}", e))
+	}
+}
+
+fn publish_signature_audit_root(root: H256, size: u64) -> EnclaveResult<()> {
+	let metadata_repository = get_node_metadata_repository_from_integritee_solo_or_parachain()?;
+	let extrinsics_factory = get_extrinsic_factory_from_integritee_solo_or_parachain()?;
+	let validator_accessor = get_validator_accessor_from_integritee_solo_or_parachain()?;
+
+	let call = metadata_repository
+		.get_from_metadata(|m| m.signature_audit_root_posted_indexes())
+		.map_err(|e| Error::Other(e.into()))?
+		.map_err(|e| Error::Other(format!("{:?}", e).into()))?;
+
+	let opaque_call = OpaqueCall::from_tuple(&(call, root.0, size));
+
 	let xts = extrinsics_factory
-		.create_extrinsics(&[bitcoin_opaque_call, ethereum_opaque_call, ton_opaque_call], None)
+		.create_extrinsics(&[opaque_call], None)
 		.map_err(|e| Error::Other(e.into()))?;
 	validator_accessor
 		.execute_mut_on_validator(|v| v.send_extrinsics(xts))
@@ -394,6 +537,9 @@ fn initialize_state_observer(
 fn run_bit_across_handler(
 	ceremony_registry: Arc<RwLock<CeremonyRegistry<KeyRepository<SchnorrPair, Seal>>>>,
 	musig2_ceremony_pending_commands: Arc<RwLock<CeremonyCommandTmp>>,
+	key_generation_registry: Arc<RwLock<KeyGenerationRegistry<KeyRepository<SchnorrPair, Seal>>>>,
+	nonce_pool: Arc<RwLock<NoncePool>>,
+	ceremony_failures: Arc<RwLock<CeremonyFailureHistory>>,
 	signing_key_pub: [u8; 32],
 	responder: Arc<
 		RpcResponder<ConnectionRegistry<H256, ConnectionToken>, H256, RpcResponseChannel>,
@@ -408,11 +554,15 @@ fn run_bit_across_handler(
 	let relayer_registry_lookup = GLOBAL_RELAYER_REGISTRY.get()?;
 	let enclave_registry_lookup = GLOBAL_ENCLAVE_REGISTRY.get()?;
 	let signer_registry_lookup = GLOBAL_SIGNER_REGISTRY.get()?;
+	let signing_policy = GLOBAL_SIGNING_POLICY_REGISTRY.get()?;
+	let signature_audit_log = GLOBAL_SIGNATURE_AUDIT_LOG.get()?;
 
 	let shielding_key_repository = GLOBAL_SHIELDING_KEY_REPOSITORY_COMPONENT.get()?;
 	let ethereum_key_repository = GLOBAL_ETHEREUM_KEY_REPOSITORY_COMPONENT.get()?;
 	let bitcoin_key_repository = GLOBAL_BITCOIN_KEY_REPOSITORY_COMPONENT.get()?;
 	let ton_key_repository = GLOBAL_TON_KEY_REPOSITORY_COMPONENT.get()?;
+	let solana_key_repository = GLOBAL_SOLANA_KEY_REPOSITORY_COMPONENT.get()?;
+	let threshold_key_share_repository = GLOBAL_THRESHOLD_KEY_SHARE_REPOSITORY_COMPONENT.get()?;
 
 	#[allow(clippy::unwrap_used)]
 	let ocall_api = GLOBAL_OCALL_API_COMPONENT.get()?;
@@ -429,19 +579,28 @@ fn run_bit_across_handler(
 		ethereum_key_repository,
 		bitcoin_key_repository,
 		ton_key_repository,
+		solana_key_repository,
+		threshold_key_share_repository,
 		stf_enclave_signer,
 		state_handler,
 		ocall_api,
 		relayer_registry_lookup,
 		enclave_registry_lookup,
 		signer_registry_lookup,
+		signing_policy,
+		signature_audit_log,
 		signing_key_pub,
 		ceremony_registry,
 		musig2_ceremony_pending_commands,
+		key_generation_registry,
+		nonce_pool,
+		ceremony_failures,
 		responder,
 	);
 	run_bit_across_handler_runner(
 		Arc::new(task_context),
+		Arc::new(EnclaveThresholdKeyPublisher),
+		Arc::new(EnclaveAuditRootPublisher),
 		ceremony_commands_thread_count,
 		ceremony_events_thread_count,
 	);
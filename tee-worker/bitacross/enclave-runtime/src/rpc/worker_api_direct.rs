@@ -22,12 +22,16 @@ use crate::{
 	},
 	initialization::global_components::{
 		EnclaveBitcoinKeyRepository, EnclaveEthereumKeyRepository, EnclaveSigningKeyRepository,
-		EnclaveTonKeyRepository,
+		EnclaveSolanaKeyRepository, EnclaveThresholdKeyShareRepository, EnclaveTonKeyRepository,
 	},
 	std::string::ToString,
 	utils::get_validator_accessor_from_integritee_solo_or_parachain,
 };
-use bc_musig2_ceremony::{generate_aggregated_public_key, PublicKey};
+use bc_musig2_ceremony::{
+	ceremony_statuses, generate_aggregated_public_key, get_current_timestamp,
+	CeremonyFailureHistory, CeremonyRegistry, NoncePool, PublicKey,
+};
+use bc_signature_audit_log::SignatureAuditLogLookup;
 use bc_signer_registry::SignerRegistryLookup;
 use bc_task_sender::{BitAcrossProcessingResult, BitAcrossRequest, BitAcrossRequestSender};
 use codec::Encode;
@@ -56,7 +60,14 @@ use log::{debug, error};
 use sgx_crypto_helper::rsa3072::Rsa3072PubKey;
 use sp_core::crypto::Pair;
 use sp_runtime::OpaqueExtrinsic;
-use std::{borrow::ToOwned, boxed::Box, format, str, string::String, sync::Arc, vec::Vec};
+use std::{
+	borrow::ToOwned,
+	boxed::Box,
+	format, str,
+	string::String,
+	sync::{Arc, SgxRwLock as RwLock},
+	vec::Vec,
+};
 
 fn compute_hex_encoded_return_error(error_msg: &str) -> String {
 	RpcReturnValue::from_error_message(error_msg).to_hex()
@@ -73,7 +84,7 @@ fn get_all_rpc_methods_string(io_handler: &IoHandler) -> String {
 }
 
 #[allow(clippy::too_many_arguments)]
-pub fn public_api_rpc_handler<Author, GetterExecutor, AccessShieldingKey, OcallApi, SR>(
+pub fn public_api_rpc_handler<Author, GetterExecutor, AccessShieldingKey, OcallApi, SR, SAL>(
 	top_pool_author: Arc<Author>,
 	getter_executor: Arc<GetterExecutor>,
 	shielding_key: Arc<AccessShieldingKey>,
@@ -82,7 +93,13 @@ pub fn public_api_rpc_handler<Author, GetterExecutor, AccessShieldingKey, OcallA
 	bitcoin_key_repository: Arc<EnclaveBitcoinKeyRepository>,
 	ethereum_key_repository: Arc<EnclaveEthereumKeyRepository>,
 	ton_key_repository: Arc<EnclaveTonKeyRepository>,
+	solana_key_repository: Arc<EnclaveSolanaKeyRepository>,
+	threshold_key_share_repository: Arc<EnclaveThresholdKeyShareRepository>,
 	signer_lookup: Arc<SR>,
+	signature_audit_log: Arc<SAL>,
+	nonce_pool: Arc<RwLock<NoncePool>>,
+	ceremony_registry: Arc<RwLock<CeremonyRegistry<EnclaveBitcoinKeyRepository>>>,
+	ceremony_failures: Arc<RwLock<CeremonyFailureHistory>>,
 ) -> IoHandler
 where
 	Author: AuthorApi<H256, H256, TrustedCallSigned, Getter> + Send + Sync + 'static,
@@ -92,10 +109,13 @@ where
 		ShieldingCryptoDecrypt + ShieldingCryptoEncrypt + DeriveEd25519 + Send + Sync + 'static,
 	OcallApi: EnclaveAttestationOCallApi + Send + Sync + 'static,
 	SR: SignerRegistryLookup + Send + Sync + 'static,
+	SAL: SignatureAuditLogLookup + Send + Sync + 'static,
 {
 	let mut io = IoHandler::new();
 
 	let signer_lookup_cloned = signer_lookup.clone();
+	let nonce_pool_signer_lookup = signer_lookup.clone();
+	let nonce_pool_signing_key_repository = signing_key_repository.clone();
 	let shielding_key_cloned = shielding_key.clone();
 	let signing_key_repository_cloned = signing_key_repository.clone();
 	let ocall_api_cloned = ocall_api.clone();
@@ -207,6 +227,22 @@ where
 
 	io.add_sync_method("bitacross_aggregatedPublicKey", move |_: Params| {
 		debug!("worker_api_direct rpc was called: bitacross_aggregatedPublicKey");
+		// once a threshold key was generated, ceremonies are signed with it
+		match threshold_key_share_repository.retrieve_key() {
+			Ok(Some(key_share)) => {
+				let json_value = RpcReturnValue::new(
+					key_share.group_public_key.to_vec(),
+					false,
+					DirectRequestStatus::Ok,
+				);
+				return Ok(json!(json_value.to_hex()))
+			},
+			Ok(None) => {},
+			Err(_e) =>
+				return Ok(json!(compute_hex_encoded_return_error(
+					"Could not obtain threshold key share"
+				))),
+		}
 		if let Ok(keys) = signer_lookup
 			.get_all()
 			.iter()
@@ -244,11 +280,17 @@ where
 			Err(_e) => compute_hex_encoded_return_error("Can not obtain ton key"),
 		};
 
+		let solana_key = match solana_key_repository.retrieve_key() {
+			Ok(pair) => pair.public().0.to_hex(),
+			Err(_e) => compute_hex_encoded_return_error("Can not obtain solana key"),
+		};
+
 		Ok(json!({
 			"signer": signer,
 			"bitcoin_key": bitcoin_key,
 			"ethereum_key": ethereum_key,
-			"ton_key": ton_key
+			"ton_key": ton_key,
+			"solana_key": solana_key
 		}))
 	});
 
@@ -268,6 +310,83 @@ where
 		Ok(json!(keys))
 	});
 
+	// params are the index of the entry and the size of the log the posted root covers
+	io.add_sync_method("bitacross_getSignatureAuditProof", move |params: Params| {
+		debug!("worker_api_direct rpc was called: bitacross_getSignatureAuditProof");
+		let (index, size) = match params.parse::<(u64, u64)>() {
+			Ok(params) => params,
+			Err(e) => {
+				let error_msg: String = format!("Could not parse params due to: {}", e);
+				return Ok(json!(compute_hex_encoded_return_error(error_msg.as_str())))
+			},
+		};
+		match signature_audit_log.proof(index, size) {
+			Some(proof) => {
+				let json_value =
+					RpcReturnValue::new(proof.encode(), false, DirectRequestStatus::Ok);
+				Ok(json!(json_value.to_hex()))
+			},
+			None => Ok(json!(compute_hex_encoded_return_error("No such entry in audit log"))),
+		}
+	});
+
+	// slots with pre-shared nonces of all signers, a batch signed with them skips the first round
+	io.add_sync_method("bitacross_getNoncePoolSlots", move |_: Params| {
+		debug!("worker_api_direct rpc was called: bitacross_getNoncePoolSlots");
+		let me = match nonce_pool_signing_key_repository.retrieve_key() {
+			Ok(key) => key.public().0,
+			Err(e) => {
+				let error_msg: String = format!("Could not get signing key due to: {:?}", e);
+				return Ok(json!(compute_hex_encoded_return_error(error_msg.as_str())))
+			},
+		};
+		let peers: Vec<[u8; 32]> = nonce_pool_signer_lookup
+			.get_all()
+			.iter()
+			.map(|(signer, _)| *signer.as_ref())
+			.filter(|signer| *signer != me)
+			.collect();
+		match nonce_pool.read() {
+			Ok(nonce_pool) => {
+				let slots = nonce_pool.ready_slots(&peers);
+				let json_value =
+					RpcReturnValue::new(slots.encode(), false, DirectRequestStatus::Ok);
+				Ok(json!(json_value.to_hex()))
+			},
+			Err(_) => Ok(json!(compute_hex_encoded_return_error("Could not read nonce pool"))),
+		}
+	});
+
+	// active ceremonies with the signers each of them still waits for, ceremonies are identified
+	// by hashes only this enclave can compute
+	let statuses_ceremony_failures = ceremony_failures.clone();
+	io.add_sync_method("bitacross_getCeremonies", move |_: Params| {
+		debug!("worker_api_direct rpc was called: bitacross_getCeremonies");
+		match (ceremony_registry.read(), statuses_ceremony_failures.read()) {
+			(Ok(registry), Ok(failures)) => {
+				let statuses = ceremony_statuses(&registry, &failures, get_current_timestamp());
+				let json_value =
+					RpcReturnValue::new(statuses.encode(), false, DirectRequestStatus::Ok);
+				Ok(json!(json_value.to_hex()))
+			},
+			_ => Ok(json!(compute_hex_encoded_return_error("Could not read ceremony registry"))),
+		}
+	});
+
+	// recently failed ceremonies together with the signers the failures are attributed to
+	io.add_sync_method("bitacross_getCeremonyFailures", move |_: Params| {
+		debug!("worker_api_direct rpc was called: bitacross_getCeremonyFailures");
+		match ceremony_failures.read() {
+			Ok(failures) => {
+				let json_value =
+					RpcReturnValue::new(failures.recent().encode(), false, DirectRequestStatus::Ok);
+				Ok(json!(json_value.to_hex()))
+			},
+			Err(_) =>
+				Ok(json!(compute_hex_encoded_return_error("Could not read ceremony failures"))),
+		}
+	});
+
 	io.add_sync_method("author_getShard", move |_: Params| {
 		debug!("worker_api_direct rpc was called: author_getShard");
 		let shard = top_pool_author.list_handled_shards().first().copied().unwrap_or_default();
@@ -19,7 +19,8 @@
 use crate::{
 	initialization::global_components::{
 		GLOBAL_BITCOIN_KEY_REPOSITORY_COMPONENT, GLOBAL_ETHEREUM_KEY_REPOSITORY_COMPONENT,
-		GLOBAL_SIGNING_KEY_REPOSITORY_COMPONENT, GLOBAL_TON_KEY_REPOSITORY_COMPONENT,
+		GLOBAL_SIGNING_KEY_REPOSITORY_COMPONENT, GLOBAL_SOLANA_KEY_REPOSITORY_COMPONENT,
+		GLOBAL_THRESHOLD_KEY_SHARE_REPOSITORY_COMPONENT, GLOBAL_TON_KEY_REPOSITORY_COMPONENT,
 	},
 	rpc::worker_api_direct::public_api_rpc_handler,
 	test::{
@@ -28,6 +29,8 @@ use crate::{
 	},
 	Hash,
 };
+use bc_musig2_ceremony::{CeremonyFailureHistory, CeremonyRegistry, NoncePool};
+use bc_signature_audit_log::SignatureAuditLog;
 use bc_signer_registry::{PubKey, SignerRegistryLookup};
 use codec::{Decode, Encode};
 use ita_stf::{Getter, PublicGetter};
@@ -48,7 +51,11 @@ use itp_types::{DirectRequestStatus, RsaRequest, ShardIdentifier};
 use itp_utils::{FromHexPrefixed, ToHexPrefixed};
 use litentry_primitives::{Address32, Identity};
 use sp_core::Pair;
-use std::{string::ToString, sync::Arc, vec::Vec};
+use std::{
+	string::ToString,
+	sync::{Arc, SgxRwLock as RwLock},
+	vec::Vec,
+};
 
 struct SignerRegistryMock {}
 
@@ -90,7 +97,13 @@ pub fn state_get_mrenclave_works() {
 		GLOBAL_BITCOIN_KEY_REPOSITORY_COMPONENT.get().unwrap(),
 		GLOBAL_ETHEREUM_KEY_REPOSITORY_COMPONENT.get().unwrap(),
 		GLOBAL_TON_KEY_REPOSITORY_COMPONENT.get().unwrap(),
+		GLOBAL_SOLANA_KEY_REPOSITORY_COMPONENT.get().unwrap(),
+		GLOBAL_THRESHOLD_KEY_SHARE_REPOSITORY_COMPONENT.get().unwrap(),
 		signer_lookup,
+		Arc::new(SignatureAuditLog::new(temp_dir.path().to_path_buf())),
+		Arc::new(RwLock::new(NoncePool::new(temp_dir.path().to_path_buf()))),
+		Arc::new(RwLock::new(CeremonyRegistry::new())),
+		Arc::new(RwLock::new(CeremonyFailureHistory::default())),
 	);
 	let rpc_handler = Arc::new(RpcWsHandler::new(io_handler, watch_extractor, connection_registry));
 
@@ -145,7 +158,13 @@ pub fn get_state_request_works() {
 		GLOBAL_BITCOIN_KEY_REPOSITORY_COMPONENT.get().unwrap(),
 		GLOBAL_ETHEREUM_KEY_REPOSITORY_COMPONENT.get().unwrap(),
 		GLOBAL_TON_KEY_REPOSITORY_COMPONENT.get().unwrap(),
+		GLOBAL_SOLANA_KEY_REPOSITORY_COMPONENT.get().unwrap(),
+		GLOBAL_THRESHOLD_KEY_SHARE_REPOSITORY_COMPONENT.get().unwrap(),
 		signer_lookup,
+		Arc::new(SignatureAuditLog::new(temp_dir.path().to_path_buf())),
+		Arc::new(RwLock::new(NoncePool::new(temp_dir.path().to_path_buf()))),
+		Arc::new(RwLock::new(CeremonyRegistry::new())),
+		Arc::new(RwLock::new(CeremonyFailureHistory::default())),
 	);
 	let rpc_handler = Arc::new(RpcWsHandler::new(io_handler, watch_extractor, connection_registry));
 
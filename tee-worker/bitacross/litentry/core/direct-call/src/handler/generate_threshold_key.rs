@@ -0,0 +1,132 @@
+// Copyright 2020-2024 Trust Computing GmbH.
+// This file is part of Litentry.
+//
+// Litentry is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// Litentry is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with Litentry.  If not, see <https://www.gnu.org/licenses/>.
+
+use bc_musig2_ceremony::{KeyGenerationCommand, PublicKey, SignersWithKeys};
+use bc_relayer_registry::RelayerRegistryLookup;
+use bc_signer_registry::SignerRegistryLookup;
+use codec::Encode;
+use litentry_primitives::Identity;
+
+#[derive(Encode, Debug)]
+pub enum GenerateThresholdKeyError {
+	InvalidSigner,
+	InvalidThreshold,
+	KeyGenerationError,
+}
+
+pub fn handle<RRL: RelayerRegistryLookup, SR: SignerRegistryLookup>(
+	signer: Identity,
+	threshold: u16,
+	relayer_registry: &RRL,
+	signer_registry: &SR,
+) -> Result<KeyGenerationCommand, GenerateThresholdKeyError> {
+	if !relayer_registry.contains_key(&signer) {
+		return Err(GenerateThresholdKeyError::InvalidSigner)
+	}
+	let signers =
+		signers_with_keys(signer_registry).ok_or(GenerateThresholdKeyError::KeyGenerationError)?;
+	if threshold < 2 || threshold as usize > signers.len() {
+		return Err(GenerateThresholdKeyError::InvalidThreshold)
+	}
+
+	Ok(KeyGenerationCommand::Init(signers, threshold))
+}
+
+// all registered signers take part in the key generation
+pub fn signers_with_keys<SR: SignerRegistryLookup>(
+	signer_registry: &SR,
+) -> Option<SignersWithKeys> {
+	signer_registry
+		.get_all()
+		.iter()
+		.map(|(address, pub_key)| {
+			PublicKey::from_sec1_bytes(pub_key).ok().map(|key| (*address.as_ref(), key))
+		})
+		.collect()
+}
+
+#[cfg(test)]
+pub mod test {
+	use crate::handler::generate_threshold_key::{handle, GenerateThresholdKeyError};
+	use bc_musig2_ceremony::KeyGenerationCommand;
+	use bc_relayer_registry::{RelayerRegistry, RelayerRegistryUpdater};
+	use bc_signer_registry::{PubKey, SignerRegistryLookup};
+	use k256::{elliptic_curve::PublicKey, schnorr::SigningKey};
+	use litentry_primitives::{Address32, Identity};
+	use sp_core::{sr25519, Pair};
+
+	struct SignersRegistryMock {}
+
+	impl SignerRegistryLookup for SignersRegistryMock {
+		fn contains_key(&self, _account: &Address32) -> bool {
+			true
+		}
+
+		fn get_all(&self) -> Vec<(Address32, PubKey)> {
+			(1..=3u8)
+				.map(|i| {
+					let key =
+						PublicKey::from(SigningKey::from_bytes(&[i; 32]).unwrap().verifying_key());
+					(Address32::from([i; 32]), key.to_sec1_bytes().as_ref().try_into().unwrap())
+				})
+				.collect()
+		}
+	}
+
+	#[test]
+	pub fn it_should_return_ok_for_relayer_signer() {
+		// given
+		let relayer_registry = RelayerRegistry::default();
+		let alice_key_pair = sr25519::Pair::from_string("//Alice", None).unwrap();
+		let relayer_account = Identity::Substrate(alice_key_pair.public().into());
+		relayer_registry.update(relayer_account.clone()).unwrap();
+
+		// when
+		let result = handle(relayer_account, 2, &relayer_registry, &SignersRegistryMock {});
+
+		// then
+		assert!(matches!(result, Ok(KeyGenerationCommand::Init(signers, 2)) if signers.len() == 3))
+	}
+
+	#[test]
+	pub fn it_should_return_err_for_threshold_above_signers_count() {
+		// given
+		let relayer_registry = RelayerRegistry::default();
+		let alice_key_pair = sr25519::Pair::from_string("//Alice", None).unwrap();
+		let relayer_account = Identity::Substrate(alice_key_pair.public().into());
+		relayer_registry.update(relayer_account.clone()).unwrap();
+
+		// when
+		let result = handle(relayer_account, 4, &relayer_registry, &SignersRegistryMock {});
+
+		// then
+		assert!(matches!(result, Err(GenerateThresholdKeyError::InvalidThreshold)))
+	}
+
+	#[test]
+	pub fn it_should_return_err_for_non_relayer_signer() {
+		// given
+		let relayer_registry = RelayerRegistry::default();
+		let alice_key_pair = sr25519::Pair::from_string("//Alice", None).unwrap();
+		let non_relayer_account = Identity::Substrate(alice_key_pair.public().into());
+
+		// when
+		let result = handle(non_relayer_account, 2, &relayer_registry, &SignersRegistryMock {});
+
+		// then
+		assert!(matches!(result, Err(GenerateThresholdKeyError::InvalidSigner)))
+	}
+}
@@ -0,0 +1,151 @@
+// Copyright 2020-2024 Trust Computing GmbH.
+// This file is part of Litentry.
+//
+// Litentry is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// Litentry is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with Litentry.  If not, see <https://www.gnu.org/licenses/>.
+
+use crate::handler::generate_threshold_key::signers_with_keys;
+use bc_enclave_registry::EnclaveRegistryLookup;
+use bc_musig2_ceremony::{KeyGenerationCommand, KeyGenerationId, KeyGenerationPackage};
+use bc_signer_registry::SignerRegistryLookup;
+use codec::Encode;
+use litentry_primitives::Identity;
+use log::debug;
+
+#[derive(Encode, Debug)]
+pub enum KeyGenerationPackageShareError {
+	InvalidSigner,
+	UnknownKeyGeneration,
+}
+
+pub fn handle<ER: EnclaveRegistryLookup, SR: SignerRegistryLookup>(
+	signer: Identity,
+	key_generation_id: &KeyGenerationId,
+	threshold: u16,
+	package: KeyGenerationPackage,
+	enclave_registry: &ER,
+	signer_registry: &SR,
+) -> Result<KeyGenerationCommand, KeyGenerationPackageShareError> {
+	debug!(
+		"Received key generation package from: {:?} for key generation {:?}",
+		signer, key_generation_id
+	);
+	let address = match signer {
+		Identity::Substrate(address) if enclave_registry.contains_key(&address) => address,
+		_ => return Err(KeyGenerationPackageShareError::InvalidSigner),
+	};
+
+	// the sender has to see the same set of signers as we do
+	let signers = signers_with_keys(signer_registry)
+		.ok_or(KeyGenerationPackageShareError::UnknownKeyGeneration)?;
+	if bc_musig2_ceremony::key_generation_id(&signers, threshold, None) != *key_generation_id {
+		return Err(KeyGenerationPackageShareError::UnknownKeyGeneration)
+	}
+
+	Ok(KeyGenerationCommand::SavePackage(signers, threshold, None, *address.as_ref(), package))
+}
+
+#[cfg(test)]
+pub mod test {
+	use crate::handler::key_generation_package_share::{handle, KeyGenerationPackageShareError};
+	use bc_enclave_registry::{EnclaveRegistry, EnclaveRegistryUpdater};
+	use bc_musig2_ceremony::{
+		frost::dkg_round_one, key_generation_id, KeyGenerationCommand, KeyGenerationPackage,
+	};
+	use bc_signer_registry::{PubKey, SignerRegistryLookup};
+	use itp_sgx_crypto::AesOutput;
+	use k256::{elliptic_curve::PublicKey, schnorr::SigningKey};
+	use litentry_primitives::{Address32, Identity};
+	use sp_core::{sr25519, Pair};
+
+	struct SignersRegistryMock {}
+
+	impl SignerRegistryLookup for SignersRegistryMock {
+		fn contains_key(&self, _account: &Address32) -> bool {
+			true
+		}
+
+		fn get_all(&self) -> Vec<(Address32, PubKey)> {
+			(1..=3u8)
+				.map(|i| {
+					let key =
+						PublicKey::from(SigningKey::from_bytes(&[i; 32]).unwrap().verifying_key());
+					(Address32::from([i; 32]), key.to_sec1_bytes().as_ref().try_into().unwrap())
+				})
+				.collect()
+		}
+	}
+
+	fn package() -> KeyGenerationPackage {
+		KeyGenerationPackage {
+			commitment: dkg_round_one(1, 2, b"test").unwrap().1,
+			encrypted_share: AesOutput::default(),
+		}
+	}
+
+	fn id() -> [u8; 32] {
+		let signers =
+			crate::handler::generate_threshold_key::signers_with_keys(&SignersRegistryMock {})
+				.unwrap();
+		key_generation_id(&signers, 2, None)
+	}
+
+	#[test]
+	pub fn it_should_return_ok_for_enclave_signer() {
+		// given
+		let alice_key_pair = sr25519::Pair::from_string("//Alice", None).unwrap();
+		let signer_account = Identity::Substrate(alice_key_pair.public().into());
+		let enclave_registry = EnclaveRegistry::default();
+		let _ =
+			enclave_registry.update(alice_key_pair.public().into(), "localhost:2000".to_string());
+
+		// when
+		let result =
+			handle(signer_account, &id(), 2, package(), &enclave_registry, &SignersRegistryMock {});
+
+		// then
+		assert!(matches!(result, Ok(KeyGenerationCommand::SavePackage(_, 2, None, _, _))))
+	}
+
+	#[test]
+	pub fn it_should_return_err_for_unknown_key_generation() {
+		// given
+		let alice_key_pair = sr25519::Pair::from_string("//Alice", None).unwrap();
+		let signer_account = Identity::Substrate(alice_key_pair.public().into());
+		let enclave_registry = EnclaveRegistry::default();
+		let _ =
+			enclave_registry.update(alice_key_pair.public().into(), "localhost:2000".to_string());
+
+		// when
+		let result =
+			handle(signer_account, &id(), 3, package(), &enclave_registry, &SignersRegistryMock {});
+
+		// then
+		assert!(matches!(result, Err(KeyGenerationPackageShareError::UnknownKeyGeneration)))
+	}
+
+	#[test]
+	pub fn it_should_return_err_for_non_enclave_signer() {
+		// given
+		let alice_key_pair = sr25519::Pair::from_string("//Alice", None).unwrap();
+		let signer_account = Identity::Substrate(alice_key_pair.public().into());
+		let enclave_registry = EnclaveRegistry::default();
+
+		// when
+		let result =
+			handle(signer_account, &id(), 2, package(), &enclave_registry, &SignersRegistryMock {});
+
+		// then
+		assert!(matches!(result, Err(KeyGenerationPackageShareError::InvalidSigner)))
+	}
+}
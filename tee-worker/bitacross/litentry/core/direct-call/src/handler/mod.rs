@@ -14,9 +14,20 @@
 // You should have received a copy of the GNU General Public License
 // along with Litentry.  If not, see <https://www.gnu.org/licenses/>.
 
+pub mod batch_nonce_share;
+pub mod batch_partial_signature_share;
+pub mod generate_threshold_key;
+pub mod key_generation_package_share;
 pub mod kill_ceremony;
+pub mod nonce_pool_share;
 pub mod nonce_share;
 pub mod partial_signature_share;
+pub mod reshare_threshold_key;
+pub mod resharing_package_share;
 pub mod sign_bitcoin;
+pub mod sign_bitcoin_psbt;
 pub mod sign_ethereum;
+pub mod sign_solana;
 pub mod sign_ton;
+pub mod threshold_batch_partial_signature_share;
+pub mod threshold_partial_signature_share;
@@ -0,0 +1,108 @@
+// Copyright 2020-2024 Trust Computing GmbH.
+// This file is part of Litentry.
+//
+// Litentry is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// Litentry is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with Litentry.  If not, see <https://www.gnu.org/licenses/>.
+
+use bc_enclave_registry::EnclaveRegistryLookup;
+use bc_musig2_ceremony::{nonce_pool::SerializedPubNonce, NonceSlot, SignerId};
+use bc_signer_registry::SignerRegistryLookup;
+use codec::Encode;
+use litentry_primitives::Identity;
+use log::debug;
+use std::vec::Vec;
+
+#[derive(Encode, Debug)]
+pub enum NoncePoolShareError {
+	InvalidSigner,
+}
+
+/// Checks that the pre-shared nonces come from a registered enclave which is also a signer, they
+/// are saved to the nonce pool by the caller.
+pub fn handle<ER: EnclaveRegistryLookup, SR: SignerRegistryLookup>(
+	signer: Identity,
+	nonces: Vec<(NonceSlot, SerializedPubNonce)>,
+	enclave_registry: &ER,
+	signer_registry: &SR,
+) -> Result<(SignerId, Vec<(NonceSlot, SerializedPubNonce)>), NoncePoolShareError> {
+	debug!("Received {} pre-shared nonces from: {:?}", nonces.len(), signer);
+	match signer {
+		Identity::Substrate(address)
+			if enclave_registry.contains_key(&address)
+				&& signer_registry.contains_key(&address) =>
+			Ok((*address.as_ref(), nonces)),
+		_ => Err(NoncePoolShareError::InvalidSigner),
+	}
+}
+
+#[cfg(test)]
+pub mod test {
+	use crate::handler::nonce_pool_share::{handle, NoncePoolShareError};
+	use bc_enclave_registry::{EnclaveRegistry, EnclaveRegistryUpdater};
+	use bc_signer_registry::{SignerRegistry, SignerRegistryUpdater};
+	use litentry_primitives::Identity;
+	use sp_core::{sr25519, Pair};
+
+	#[test]
+	pub fn it_should_return_err_for_non_enclave_signer() {
+		// given
+		let alice_key_pair = sr25519::Pair::from_string("//Alice", None).unwrap();
+		let signer_account = Identity::Substrate(alice_key_pair.public().into());
+		let enclave_registry = EnclaveRegistry::default();
+		let signer_registry = SignerRegistry::default();
+
+		// when
+		let result =
+			handle(signer_account, vec![(0, [0u8; 66])], &enclave_registry, &signer_registry);
+
+		// then
+		assert!(matches!(result, Err(NoncePoolShareError::InvalidSigner)))
+	}
+
+	#[test]
+	pub fn it_should_return_err_for_enclave_which_is_not_a_signer() {
+		// given
+		let alice_key_pair = sr25519::Pair::from_string("//Alice", None).unwrap();
+		let signer_account = Identity::Substrate(alice_key_pair.public().into());
+		let enclave_registry = EnclaveRegistry::default();
+		let _ =
+			enclave_registry.update(alice_key_pair.public().into(), "localhost:2000".to_string());
+		let signer_registry = SignerRegistry::default();
+
+		// when
+		let result =
+			handle(signer_account, vec![(0, [0u8; 66])], &enclave_registry, &signer_registry);
+
+		// then
+		assert!(matches!(result, Err(NoncePoolShareError::InvalidSigner)))
+	}
+
+	#[test]
+	pub fn it_should_return_nonces_of_enclave_signer() {
+		// given
+		let alice_key_pair = sr25519::Pair::from_string("//Alice", None).unwrap();
+		let signer_account = Identity::Substrate(alice_key_pair.public().into());
+		let enclave_registry = EnclaveRegistry::default();
+		let _ =
+			enclave_registry.update(alice_key_pair.public().into(), "localhost:2000".to_string());
+		let signer_registry = SignerRegistry::default();
+		let _ = signer_registry.update(alice_key_pair.public().into(), [2u8; 33]);
+
+		// when
+		let result =
+			handle(signer_account, vec![(0, [0u8; 66])], &enclave_registry, &signer_registry);
+
+		// then
+		assert_eq!(result.unwrap(), (alice_key_pair.public().0, vec![(0, [0u8; 66])]))
+	}
+}
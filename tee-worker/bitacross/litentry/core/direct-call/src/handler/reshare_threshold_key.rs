@@ -0,0 +1,128 @@
+// Copyright 2020-2024 Trust Computing GmbH.
+// This file is part of Litentry.
+//
+// Litentry is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// Litentry is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with Litentry.  If not, see <https://www.gnu.org/licenses/>.
+
+use crate::handler::generate_threshold_key::signers_with_keys;
+use bc_enclave_registry::EnclaveRegistryLookup;
+use bc_musig2_ceremony::{KeyGenerationCommand, Signers, ThresholdKeyShare};
+use bc_signer_registry::SignerRegistryLookup;
+use codec::Encode;
+use litentry_primitives::Address32;
+use std::vec::Vec;
+
+#[derive(Encode, Debug)]
+pub enum ReshareThresholdKeyError {
+	InvalidSigners,
+}
+
+// hands the threshold key over to the signers of the currently registered enclaves, nothing to do
+// if they already hold it or if we don't hold a share of it
+pub fn handle<SR: SignerRegistryLookup, ER: EnclaveRegistryLookup>(
+	key_share: Option<ThresholdKeyShare>,
+	signer_registry: &SR,
+	enclave_registry: &ER,
+) -> Result<Option<KeyGenerationCommand>, ReshareThresholdKeyError> {
+	let key_share = match key_share {
+		Some(key_share) => key_share,
+		None => return Ok(None),
+	};
+	let signers: Vec<_> = signers_with_keys(signer_registry)
+		.ok_or(ReshareThresholdKeyError::InvalidSigners)?
+		.into_iter()
+		.filter(|(id, _)| enclave_registry.contains_key(&Address32::from(*id)))
+		.collect();
+
+	let mut holders = key_share.signers();
+	holders.sort();
+	let mut signer_ids: Signers = signers.iter().map(|s| s.0).collect();
+	signer_ids.sort();
+	if holders == signer_ids {
+		return Ok(None)
+	}
+
+	Ok(Some(KeyGenerationCommand::Reshare(signers, key_share.public_key())))
+}
+
+#[cfg(test)]
+pub mod test {
+	use crate::handler::reshare_threshold_key::handle;
+	use bc_enclave_registry::{EnclaveRegistry, EnclaveRegistryUpdater};
+	use bc_musig2_ceremony::{KeyGenerationCommand, ThresholdKeyShare};
+	use bc_signer_registry::{PubKey, SignerRegistryLookup};
+	use k256::{elliptic_curve::PublicKey, schnorr::SigningKey};
+	use litentry_primitives::Address32;
+
+	struct SignersRegistryMock {}
+
+	impl SignerRegistryLookup for SignersRegistryMock {
+		fn contains_key(&self, _account: &Address32) -> bool {
+			true
+		}
+
+		fn get_all(&self) -> Vec<(Address32, PubKey)> {
+			(1..=4u8)
+				.map(|i| {
+					let key =
+						PublicKey::from(SigningKey::from_bytes(&[i; 32]).unwrap().verifying_key());
+					(Address32::from([i; 32]), key.to_sec1_bytes().as_ref().try_into().unwrap())
+				})
+				.collect()
+		}
+	}
+
+	fn enclave_registry(count: u8) -> EnclaveRegistry {
+		let enclave_registry = EnclaveRegistry::default();
+		for i in 1..=count {
+			let _ = enclave_registry.update(Address32::from([i; 32]), "localhost:2000".to_string());
+		}
+		enclave_registry
+	}
+
+	fn key_share() -> ThresholdKeyShare {
+		let participants = (1..=3u8).map(|i| ([i; 32], i as u16, [2u8; 33])).collect();
+		ThresholdKeyShare::new(2, 1, participants, [2u8; 33], [1u8; 32])
+	}
+
+	#[test]
+	pub fn it_should_reshare_to_newly_registered_enclave() {
+		// when
+		let result = handle(Some(key_share()), &SignersRegistryMock {}, &enclave_registry(4));
+
+		// then
+		assert!(matches!(
+			result,
+			Ok(Some(KeyGenerationCommand::Reshare(signers, key)))
+				if signers.len() == 4 && key == key_share().public_key()
+		))
+	}
+
+	#[test]
+	pub fn it_should_not_reshare_to_current_holders() {
+		// when
+		let result = handle(Some(key_share()), &SignersRegistryMock {}, &enclave_registry(3));
+
+		// then
+		assert!(matches!(result, Ok(None)))
+	}
+
+	#[test]
+	pub fn it_should_not_reshare_without_key_share() {
+		// when
+		let result = handle(None, &SignersRegistryMock {}, &enclave_registry(4));
+
+		// then
+		assert!(matches!(result, Ok(None)))
+	}
+}
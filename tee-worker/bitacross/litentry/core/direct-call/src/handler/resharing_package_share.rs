@@ -0,0 +1,169 @@
+// Copyright 2020-2024 Trust Computing GmbH.
+// This file is part of Litentry.
+//
+// Litentry is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// Litentry is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with Litentry.  If not, see <https://www.gnu.org/licenses/>.
+
+use bc_enclave_registry::EnclaveRegistryLookup;
+use bc_musig2_ceremony::{
+	deserialize_signers, key_generation_id, KeyGenerationCommand, KeyGenerationId,
+	KeyGenerationPackage, SerializedSigners, ThresholdPublicKey,
+};
+use codec::Encode;
+use litentry_primitives::Identity;
+use log::debug;
+
+#[derive(Encode, Debug)]
+pub enum ResharingPackageShareError {
+	InvalidSigner,
+	InvalidSigners,
+	UnknownKeyGeneration,
+}
+
+pub fn handle<ER: EnclaveRegistryLookup>(
+	signer: Identity,
+	key_generation_id: &KeyGenerationId,
+	signers: SerializedSigners,
+	reshared_key: ThresholdPublicKey,
+	package: KeyGenerationPackage,
+	enclave_registry: &ER,
+) -> Result<KeyGenerationCommand, ResharingPackageShareError> {
+	debug!("Received resharing package from: {:?} for resharing {:?}", signer, key_generation_id);
+	let address = match signer {
+		Identity::Substrate(address) if enclave_registry.contains_key(&address) => address,
+		_ => return Err(ResharingPackageShareError::InvalidSigner),
+	};
+
+	// the resharing is triggered by parentchain events and our registries may still lag behind,
+	// so the new signers are taken from the sender
+	let signers =
+		deserialize_signers(&signers).ok_or(ResharingPackageShareError::InvalidSigners)?;
+	let threshold = reshared_key.threshold;
+	if key_generation_id(&signers, threshold, Some(&reshared_key)) != *key_generation_id {
+		return Err(ResharingPackageShareError::UnknownKeyGeneration)
+	}
+
+	Ok(KeyGenerationCommand::SavePackage(
+		signers,
+		threshold,
+		Some(reshared_key),
+		*address.as_ref(),
+		package,
+	))
+}
+
+#[cfg(test)]
+pub mod test {
+	use crate::handler::resharing_package_share::{handle, ResharingPackageShareError};
+	use bc_enclave_registry::{EnclaveRegistry, EnclaveRegistryUpdater};
+	use bc_musig2_ceremony::{
+		frost::dkg_round_one, key_generation_id, serialize_signers, KeyGenerationCommand,
+		KeyGenerationPackage, SerializedSigners, ThresholdPublicKey,
+	};
+	use itp_sgx_crypto::AesOutput;
+	use k256::{elliptic_curve::PublicKey, schnorr::SigningKey};
+	use litentry_primitives::Identity;
+	use sp_core::{sr25519, Pair};
+
+	fn signers() -> SerializedSigners {
+		let signers = (1..=3u8)
+			.map(|i| {
+				(
+					[i; 32],
+					PublicKey::from(SigningKey::from_bytes(&[i; 32]).unwrap().verifying_key()),
+				)
+			})
+			.collect();
+		serialize_signers(&signers)
+	}
+
+	fn reshared_key() -> ThresholdPublicKey {
+		ThresholdPublicKey {
+			threshold: 2,
+			participants: vec![([1u8; 32], 1, [2u8; 33]), ([4u8; 32], 2, [3u8; 33])],
+			group_public_key: [2u8; 33],
+		}
+	}
+
+	fn package() -> KeyGenerationPackage {
+		KeyGenerationPackage {
+			commitment: dkg_round_one(1, 2, b"test").unwrap().1,
+			encrypted_share: AesOutput::default(),
+		}
+	}
+
+	fn id() -> [u8; 32] {
+		let signers = bc_musig2_ceremony::deserialize_signers(&signers()).unwrap();
+		key_generation_id(&signers, 2, Some(&reshared_key()))
+	}
+
+	#[test]
+	pub fn it_should_return_ok_for_enclave_signer() {
+		// given
+		let alice_key_pair = sr25519::Pair::from_string("//Alice", None).unwrap();
+		let signer_account = Identity::Substrate(alice_key_pair.public().into());
+		let enclave_registry = EnclaveRegistry::default();
+		let _ =
+			enclave_registry.update(alice_key_pair.public().into(), "localhost:2000".to_string());
+
+		// when
+		let result =
+			handle(signer_account, &id(), signers(), reshared_key(), package(), &enclave_registry);
+
+		// then
+		assert!(matches!(
+			result,
+			Ok(KeyGenerationCommand::SavePackage(signers, 2, Some(_), _, _)) if signers.len() == 3
+		))
+	}
+
+	#[test]
+	pub fn it_should_return_err_for_unknown_resharing() {
+		// given
+		let alice_key_pair = sr25519::Pair::from_string("//Alice", None).unwrap();
+		let signer_account = Identity::Substrate(alice_key_pair.public().into());
+		let enclave_registry = EnclaveRegistry::default();
+		let _ =
+			enclave_registry.update(alice_key_pair.public().into(), "localhost:2000".to_string());
+		let mut other_signers = signers();
+		other_signers.pop();
+
+		// when
+		let result = handle(
+			signer_account,
+			&id(),
+			other_signers,
+			reshared_key(),
+			package(),
+			&enclave_registry,
+		);
+
+		// then
+		assert!(matches!(result, Err(ResharingPackageShareError::UnknownKeyGeneration)))
+	}
+
+	#[test]
+	pub fn it_should_return_err_for_non_enclave_signer() {
+		// given
+		let alice_key_pair = sr25519::Pair::from_string("//Alice", None).unwrap();
+		let signer_account = Identity::Substrate(alice_key_pair.public().into());
+		let enclave_registry = EnclaveRegistry::default();
+
+		// when
+		let result =
+			handle(signer_account, &id(), signers(), reshared_key(), package(), &enclave_registry);
+
+		// then
+		assert!(matches!(result, Err(ResharingPackageShareError::InvalidSigner)))
+	}
+}
@@ -16,46 +16,70 @@
 
 use bc_enclave_registry::EnclaveRegistryLookup;
 use bc_musig2_ceremony::{CeremonyCommand, PublicKey, SignBitcoinPayload, SignersWithKeys};
-use bc_relayer_registry::RelayerRegistryLookup;
+use bc_relayer_registry::{PermissionError, RelayerRegistryLookup};
 use bc_signer_registry::SignerRegistryLookup;
+use bc_signing_policy::SigningPolicyEnforcer;
 use codec::Encode;
-use litentry_primitives::Identity;
+use litentry_primitives::{Identity, RelayerChain};
 use std::sync::Arc;
 
 #[derive(Encode, Debug)]
 pub enum SignBitcoinError {
 	InvalidSigner,
 	CeremonyError,
+	PermissionDenied(PermissionError),
+	// the vault has to be spent from with `SignBitcoinPsbt` once a signing policy is published
+	PolicyEnforced,
+}
+
+impl From<PermissionError> for SignBitcoinError {
+	fn from(e: PermissionError) -> Self {
+		match e {
+			PermissionError::NotRelayer => Self::InvalidSigner,
+			e => Self::PermissionDenied(e),
+		}
+	}
 }
 
 #[allow(clippy::too_many_arguments)]
-pub fn handle<RRL: RelayerRegistryLookup, SR: SignerRegistryLookup, ER: EnclaveRegistryLookup>(
+pub fn handle<
+	RRL: RelayerRegistryLookup,
+	SR: SignerRegistryLookup,
+	ER: EnclaveRegistryLookup,
+	SP: SigningPolicyEnforcer,
+>(
 	signer: Identity,
 	payload: SignBitcoinPayload,
 	relayer_registry: &RRL,
 	signer_registry: Arc<SR>,
 	enclave_registry: &ER,
+	signing_policy: &SP,
 	check_run: bool,
+	now: u64,
 ) -> Result<CeremonyCommand, SignBitcoinError> {
-	if relayer_registry.contains_key(&signer)
-		|| match &signer {
-			Identity::Substrate(address) => enclave_registry.contains_key(address),
-			_ => false,
-		} {
-		let signers: Result<SignersWithKeys, SignBitcoinError> = signer_registry
-			.get_all()
-			.iter()
-			.map(|(address, pub_key)| {
-				let public_key = PublicKey::from_sec1_bytes(pub_key)
-					.map_err(|_| SignBitcoinError::CeremonyError)?;
-				Ok((*address.as_ref(), public_key))
-			})
-			.collect();
-
-		Ok(CeremonyCommand::InitCeremony(signers?, payload, check_run))
-	} else {
-		Err(SignBitcoinError::InvalidSigner)
+	// raw payloads would sign past the policy, check runs don't produce a signature
+	if !check_run && signing_policy.is_enforced() {
+		return Err(SignBitcoinError::PolicyEnforced)
+	}
+	let is_enclave = match &signer {
+		Identity::Substrate(address) => enclave_registry.contains_key(address),
+		_ => false,
+	};
+	// enclaves aren't bound to the permissions of relayers
+	if !is_enclave {
+		relayer_registry.authorize(&signer, RelayerChain::Bitcoin, now)?;
 	}
+	let signers: Result<SignersWithKeys, SignBitcoinError> = signer_registry
+		.get_all()
+		.iter()
+		.map(|(address, pub_key)| {
+			let public_key =
+				PublicKey::from_sec1_bytes(pub_key).map_err(|_| SignBitcoinError::CeremonyError)?;
+			Ok((*address.as_ref(), public_key))
+		})
+		.collect();
+
+	Ok(CeremonyCommand::InitCeremony(signers?, payload, check_run))
 }
 
 #[cfg(test)]
@@ -64,10 +88,13 @@ pub mod test {
 	use alloc::sync::Arc;
 	use bc_enclave_registry::{EnclaveRegistry, EnclaveRegistryUpdater};
 	use bc_musig2_ceremony::SignBitcoinPayload;
-	use bc_relayer_registry::{RelayerRegistry, RelayerRegistryUpdater};
+	use bc_relayer_registry::{PermissionError, RelayerRegistry, RelayerRegistryUpdater};
 	use bc_signer_registry::{PubKey, SignerRegistryLookup};
+	use bc_signing_policy::{SigningPolicyRegistry, SigningPolicyUpdater};
 	use itp_sgx_crypto::{key_repository::AccessKey, schnorr::Pair as SchnorrPair, Error};
-	use litentry_primitives::{Address32, Identity};
+	use litentry_primitives::{
+		Address32, BtcSigningPolicy, ChainPermission, Identity, RelayerChain, RelayerPermissions,
+	};
 	use sp_core::{sr25519, Pair};
 
 	struct SignersRegistryMock {}
@@ -119,6 +146,7 @@ pub mod test {
 		// given
 		let relayer_registry = RelayerRegistry::default();
 		let enclave_registry = EnclaveRegistry::default();
+		let signing_policy = SigningPolicyRegistry::new(Default::default());
 		let alice_key_pair = sr25519::Pair::from_string("//Alice", None).unwrap();
 		let relayer_account = Identity::Substrate(alice_key_pair.public().into());
 		relayer_registry.update(relayer_account.clone()).unwrap();
@@ -131,7 +159,9 @@ pub mod test {
 			&relayer_registry,
 			signers_registry,
 			&enclave_registry,
+			&signing_policy,
 			false,
+			0,
 		);
 
 		// then
@@ -143,6 +173,7 @@ pub mod test {
 		// given
 		let relayer_registry = RelayerRegistry::default();
 		let enclave_registry = EnclaveRegistry::default();
+		let signing_policy = SigningPolicyRegistry::new(Default::default());
 		let alice_key_pair = sr25519::Pair::from_string("//Alice", None).unwrap();
 		let enclave_account = Identity::Substrate(alice_key_pair.public().into());
 		enclave_registry.update(alice_key_pair.public().into(), "".to_string()).unwrap();
@@ -155,7 +186,9 @@ pub mod test {
 			&relayer_registry,
 			signers_registry,
 			&enclave_registry,
+			&signing_policy,
 			false,
+			0,
 		);
 
 		// then
@@ -167,6 +200,7 @@ pub mod test {
 		//given
 		let relayer_registry = RelayerRegistry::default();
 		let enclave_registry = EnclaveRegistry::default();
+		let signing_policy = SigningPolicyRegistry::new(Default::default());
 
 		let alice_key_pair = sr25519::Pair::from_string("//Alice", None).unwrap();
 		let non_relayer_account = Identity::Substrate(alice_key_pair.public().into());
@@ -179,10 +213,95 @@ pub mod test {
 			&relayer_registry,
 			signers_registry,
 			&enclave_registry,
+			&signing_policy,
 			false,
+			0,
 		);
 
 		//then
 		assert!(matches!(result, Err(SignBitcoinError::InvalidSigner)))
 	}
+
+	#[test]
+	pub fn it_should_return_err_for_relayer_without_bitcoin_permission() {
+		// given
+		let relayer_registry = RelayerRegistry::default();
+		let enclave_registry = EnclaveRegistry::default();
+		let signing_policy = SigningPolicyRegistry::new(Default::default());
+		let alice_key_pair = sr25519::Pair::from_string("//Alice", None).unwrap();
+		let relayer_account = Identity::Substrate(alice_key_pair.public().into());
+		let permissions = RelayerPermissions {
+			chains: vec![ChainPermission { chain: RelayerChain::Ton, daily_quota: None }]
+				.try_into()
+				.unwrap(),
+			expires_at: None,
+		};
+		relayer_registry.set_permissions(relayer_account.clone(), permissions).unwrap();
+		let signers_registry = Arc::new(SignersRegistryMock {});
+
+		// when
+		let result = handle(
+			relayer_account,
+			SignBitcoinPayload::Derived(vec![]),
+			&relayer_registry,
+			signers_registry,
+			&enclave_registry,
+			&signing_policy,
+			false,
+			0,
+		);
+
+		// then
+		assert!(matches!(
+			result,
+			Err(SignBitcoinError::PermissionDenied(PermissionError::ChainNotAllowed(
+				RelayerChain::Bitcoin
+			)))
+		))
+	}
+
+	#[test]
+	pub fn it_should_return_err_for_raw_payload_once_policy_is_set() {
+		// given
+		let relayer_registry = RelayerRegistry::default();
+		let enclave_registry = EnclaveRegistry::default();
+		let signing_policy = SigningPolicyRegistry::new(Default::default());
+		signing_policy
+			.set_policy(BtcSigningPolicy {
+				allowed_destinations: Default::default(),
+				max_amount_per_tx: 1_000,
+				max_amount_per_day: 1_000,
+			})
+			.unwrap();
+		let alice_key_pair = sr25519::Pair::from_string("//Alice", None).unwrap();
+		let relayer_account = Identity::Substrate(alice_key_pair.public().into());
+		relayer_registry.update(relayer_account.clone()).unwrap();
+		let signers_registry = Arc::new(SignersRegistryMock {});
+
+		// when
+		let result = handle(
+			relayer_account.clone(),
+			SignBitcoinPayload::Derived(vec![]),
+			&relayer_registry,
+			signers_registry.clone(),
+			&enclave_registry,
+			&signing_policy,
+			false,
+			0,
+		);
+
+		// then
+		assert!(matches!(result, Err(SignBitcoinError::PolicyEnforced)));
+		assert!(handle(
+			relayer_account,
+			SignBitcoinPayload::Derived(vec![]),
+			&relayer_registry,
+			signers_registry,
+			&enclave_registry,
+			&signing_policy,
+			true,
+			0,
+		)
+		.is_ok())
+	}
 }
@@ -0,0 +1,317 @@
+// Copyright 2020-2024 Trust Computing GmbH.
+// This file is part of Litentry.
+//
+// Litentry is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// Litentry is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with Litentry.  If not, see <https://www.gnu.org/licenses/>.
+
+use crate::handler::generate_threshold_key::signers_with_keys;
+use bc_musig2_ceremony::{
+	vault_output_key, CeremonyCommand, SignBitcoinPayload, ThresholdKeyShare,
+};
+use bc_relayer_registry::{PermissionError, RelayerRegistryLookup};
+use bc_signer_registry::SignerRegistryLookup;
+use bc_signing_policy::{
+	p2tr_script, parse_psbt_spend, PolicyError, PsbtError, SigningPolicyEnforcer,
+};
+use codec::Encode;
+use litentry_primitives::{Identity, RelayerChain};
+
+#[derive(Encode, Debug)]
+pub enum SignBitcoinPsbtError {
+	InvalidSigner,
+	InvalidPsbt(PsbtError),
+	PolicyViolation(PolicyError),
+	CeremonyError,
+	PermissionDenied(PermissionError),
+	ThresholdKeyNotSupported,
+}
+
+impl From<PermissionError> for SignBitcoinPsbtError {
+	fn from(e: PermissionError) -> Self {
+		match e {
+			PermissionError::NotRelayer => Self::InvalidSigner,
+			e => Self::PermissionDenied(e),
+		}
+	}
+}
+
+/// Starts the ceremony for one input of a PSBT spending from the vault.
+///
+/// Unlike `SignBitcoin` the relayer doesn't get to choose what is signed, the sighash is computed
+/// here from the PSBT once it passed the signing policy published on the parentchain.
+///
+/// The MuSig2 ceremony needs the nonce of every signer, and a signer only starts the ceremony and
+/// contributes its nonce for a request it authorized itself. So every signer books the spend
+/// against its daily cap before it can be signed.
+///
+/// A threshold ceremony completes with `threshold` of the signers, the bookings of a signer
+/// don't cover the spends signed without it. PSBTs are not signed once this enclave holds a
+/// threshold key share, so the daily cap can't be bypassed by spreading the requests over
+/// signing sets.
+#[allow(clippy::too_many_arguments)]
+pub fn handle<RRL: RelayerRegistryLookup, SR: SignerRegistryLookup, SP: SigningPolicyEnforcer>(
+	signer: Identity,
+	psbt: &[u8],
+	input_index: u32,
+	relayer_registry: &RRL,
+	signer_registry: &SR,
+	signing_policy: &SP,
+	key_share: Option<&ThresholdKeyShare>,
+	now: u64,
+) -> Result<(SignBitcoinPayload, CeremonyCommand), SignBitcoinPsbtError> {
+	relayer_registry.authorize(&signer, RelayerChain::Bitcoin, now)?;
+	if key_share.is_some() {
+		return Err(SignBitcoinPsbtError::ThresholdKeyNotSupported)
+	}
+	let signers = signers_with_keys(signer_registry).ok_or(SignBitcoinPsbtError::CeremonyError)?;
+	let vault_key =
+		vault_output_key(&signers, None).map_err(|_| SignBitcoinPsbtError::CeremonyError)?;
+
+	let spend = parse_psbt_spend(psbt, input_index, &p2tr_script(&vault_key))
+		.map_err(SignBitcoinPsbtError::InvalidPsbt)?;
+	signing_policy
+		.authorize(&spend, now)
+		.map_err(SignBitcoinPsbtError::PolicyViolation)?;
+
+	let payload = SignBitcoinPayload::TaprootUnspendable(spend.sighash.to_vec());
+	Ok((payload.clone(), CeremonyCommand::InitCeremony(signers, payload, false)))
+}
+
+#[cfg(test)]
+pub mod test {
+	use super::{handle, SignBitcoinPsbtError};
+	use bc_musig2_ceremony::{vault_output_key, PublicKey, SignBitcoinPayload, ThresholdKeyShare};
+	use bc_relayer_registry::{RelayerRegistry, RelayerRegistryUpdater};
+	use bc_signer_registry::{PubKey, SignerRegistryLookup};
+	use bc_signing_policy::{
+		p2tr_script, PolicyError, SigningPolicyRegistry, SigningPolicyUpdater,
+	};
+	use bitcoin::{
+		absolute::LockTime, psbt::Psbt, transaction::Version, Amount, OutPoint, ScriptBuf,
+		Sequence, Transaction, TxIn, TxOut, Witness,
+	};
+	use litentry_primitives::{Address32, BtcScript, BtcSigningPolicy, Identity};
+	use sp_core::{sr25519, Pair};
+
+	struct SignersRegistryMock {}
+
+	impl SignerRegistryLookup for SignersRegistryMock {
+		fn contains_key(&self, _account: &Address32) -> bool {
+			true
+		}
+
+		fn get_all(&self) -> Vec<(Address32, PubKey)> {
+			vec![
+				(
+					Address32::from([0u8; 32]),
+					[
+						2, 58, 165, 169, 140, 84, 151, 130, 21, 185, 32, 243, 101, 89, 29, 51, 56,
+						38, 233, 110, 219, 75, 23, 37, 81, 20, 189, 129, 185, 104, 46, 113, 33,
+					],
+				),
+				(
+					Address32::from([1u8; 32]),
+					[
+						2, 33, 158, 56, 188, 136, 36, 56, 255, 109, 228, 17, 179, 63, 196, 98, 40,
+						57, 207, 209, 184, 120, 220, 9, 54, 115, 189, 207, 56, 230, 136, 48, 51,
+					],
+				),
+				(
+					Address32::from([2u8; 32]),
+					[
+						2, 167, 108, 241, 140, 166, 89, 112, 114, 58, 251, 60, 114, 93, 85, 16,
+						221, 20, 31, 40, 78, 234, 124, 2, 156, 166, 18, 246, 230, 29, 49, 229, 58,
+					],
+				),
+			]
+		}
+	}
+
+	fn vault_script() -> Vec<u8> {
+		let signers: Vec<([u8; 32], PublicKey)> = SignersRegistryMock {}
+			.get_all()
+			.iter()
+			.map(|(address, key)| (*address.as_ref(), PublicKey::from_sec1_bytes(key).unwrap()))
+			.collect();
+		p2tr_script(&vault_output_key(&signers, None).unwrap())
+	}
+
+	fn destination_script() -> Vec<u8> {
+		p2tr_script(&[2u8; 32])
+	}
+
+	fn psbt(to: Vec<u8>, amount: u64) -> Vec<u8> {
+		let tx = Transaction {
+			version: Version::TWO,
+			lock_time: LockTime::ZERO,
+			input: vec![TxIn {
+				previous_output: OutPoint::null(),
+				script_sig: ScriptBuf::new(),
+				sequence: Sequence::MAX,
+				witness: Witness::new(),
+			}],
+			output: vec![
+				TxOut { value: Amount::from_sat(amount), script_pubkey: ScriptBuf::from_bytes(to) },
+				TxOut {
+					value: Amount::from_sat(10_000 - amount - 500),
+					script_pubkey: ScriptBuf::from_bytes(vault_script()),
+				},
+			],
+		};
+		let mut psbt = Psbt::from_unsigned_tx(tx).unwrap();
+		psbt.inputs[0].witness_utxo = Some(TxOut {
+			value: Amount::from_sat(10_000),
+			script_pubkey: ScriptBuf::from_bytes(vault_script()),
+		});
+		psbt.serialize()
+	}
+
+	fn signing_policy() -> SigningPolicyRegistry {
+		let registry = SigningPolicyRegistry::new(Default::default());
+		let destination: BtcScript = destination_script().try_into().unwrap();
+		registry
+			.set_policy(BtcSigningPolicy {
+				allowed_destinations: vec![destination].try_into().unwrap(),
+				max_amount_per_tx: 5_000,
+				max_amount_per_day: 5_000,
+			})
+			.unwrap();
+		registry
+	}
+
+	fn relayer(relayer_registry: &RelayerRegistry) -> Identity {
+		let alice_key_pair = sr25519::Pair::from_string("//Alice", None).unwrap();
+		let relayer_account = Identity::Substrate(alice_key_pair.public().into());
+		relayer_registry.update(relayer_account.clone()).unwrap();
+		relayer_account
+	}
+
+	#[test]
+	pub fn it_should_start_ceremony_for_compliant_psbt() {
+		// given
+		let relayer_registry = RelayerRegistry::default();
+		let relayer = relayer(&relayer_registry);
+
+		// when
+		let result = handle(
+			relayer,
+			&psbt(destination_script(), 4_000),
+			0,
+			&relayer_registry,
+			&SignersRegistryMock {},
+			&signing_policy(),
+			None,
+			0,
+		);
+
+		// then
+		let (payload, _) = result.unwrap();
+		assert!(matches!(payload, SignBitcoinPayload::TaprootUnspendable(s) if s.len() == 32))
+	}
+
+	#[test]
+	pub fn it_should_not_sign_above_tx_cap() {
+		// given
+		let relayer_registry = RelayerRegistry::default();
+		let relayer = relayer(&relayer_registry);
+
+		// when
+		let result = handle(
+			relayer,
+			&psbt(destination_script(), 6_000),
+			0,
+			&relayer_registry,
+			&SignersRegistryMock {},
+			&signing_policy(),
+			None,
+			0,
+		);
+
+		// then
+		assert!(matches!(
+			result,
+			Err(SignBitcoinPsbtError::PolicyViolation(PolicyError::TxCapExceeded(6_500)))
+		))
+	}
+
+	#[test]
+	pub fn it_should_not_sign_for_unknown_destination() {
+		// given
+		let relayer_registry = RelayerRegistry::default();
+		let relayer = relayer(&relayer_registry);
+
+		// when
+		let result = handle(
+			relayer,
+			&psbt(p2tr_script(&[3u8; 32]), 1_000),
+			0,
+			&relayer_registry,
+			&SignersRegistryMock {},
+			&signing_policy(),
+			None,
+			0,
+		);
+
+		// then
+		assert!(matches!(
+			result,
+			Err(SignBitcoinPsbtError::PolicyViolation(PolicyError::DestinationNotAllowed(_)))
+		))
+	}
+
+	#[test]
+	pub fn it_should_not_sign_with_threshold_key() {
+		// given
+		let relayer_registry = RelayerRegistry::default();
+		let relayer = relayer(&relayer_registry);
+		let key_share = ThresholdKeyShare::new(2, 1, vec![], [2u8; 33], [1u8; 32]);
+
+		// when
+		let result = handle(
+			relayer,
+			&psbt(destination_script(), 1_000),
+			0,
+			&relayer_registry,
+			&SignersRegistryMock {},
+			&signing_policy(),
+			Some(&key_share),
+			0,
+		);
+
+		// then
+		assert!(matches!(result, Err(SignBitcoinPsbtError::ThresholdKeyNotSupported)))
+	}
+
+	#[test]
+	pub fn it_should_return_err_for_non_relayer_signer() {
+		// given
+		let relayer_registry = RelayerRegistry::default();
+		let alice_key_pair = sr25519::Pair::from_string("//Alice", None).unwrap();
+		let non_relayer_account = Identity::Substrate(alice_key_pair.public().into());
+
+		// when
+		let result = handle(
+			non_relayer_account,
+			&psbt(destination_script(), 1_000),
+			0,
+			&relayer_registry,
+			&SignersRegistryMock {},
+			&signing_policy(),
+			None,
+			0,
+		);
+
+		// then
+		assert!(matches!(result, Err(SignBitcoinPsbtError::InvalidSigner)))
+	}
+}
@@ -15,47 +15,188 @@
 // along with Litentry.  If not, see <https://www.gnu.org/licenses/>.
 
 use crate::PrehashedEthereumMessage;
-use bc_relayer_registry::RelayerRegistryLookup;
+use bc_relayer_registry::{PermissionError, RelayerRegistryLookup};
+use bc_signing_policy::EthAllowlistLookup;
 use codec::Encode;
 use itp_sgx_crypto::{ecdsa::Pair, key_repository::AccessKey};
-use litentry_primitives::Identity;
+use litentry_primitives::{EthSigningAllowlist, Identity, RelayerChain};
 use log::error;
+use std::vec::Vec;
 
-#[derive(Encode, Debug)]
+pub mod transaction;
+pub mod typed_data;
+
+pub use transaction::{decode_transaction, EthereumTransaction, TransactionError};
+pub use typed_data::{decode_typed_data, TypedData, TypedDataError};
+
+#[derive(Encode, Debug, PartialEq, Eq)]
 pub enum SignEthereumError {
 	InvalidSigner,
 	SigningError,
+	InvalidTransaction(TransactionError),
+	InvalidTypedData(TypedDataError),
+	NoAllowlist,
+	// relayers with an allowlist have to send transactions or typed data the allowlist applies to
+	PrehashedNotAllowed,
+	// chain id and address of the contract
+	ContractNotAllowed(u64, [u8; 20]),
+	// first bytes of the calldata
+	SelectorNotAllowed(Vec<u8>),
+	PermissionDenied(PermissionError),
+}
+
+impl From<PermissionError> for SignEthereumError {
+	fn from(e: PermissionError) -> Self {
+		match e {
+			PermissionError::NotRelayer => Self::InvalidSigner,
+			e => Self::PermissionDenied(e),
+		}
+	}
 }
 
-pub fn handle<RRL: RelayerRegistryLookup, EKR: AccessKey<KeyType = Pair>>(
+/// Signs a prehashed message, which is only possible for relayers without an allowlist as the hash
+/// can't be checked against it.
+pub fn handle<
+	RRL: RelayerRegistryLookup,
+	AL: EthAllowlistLookup,
+	EKR: AccessKey<KeyType = Pair>,
+>(
 	signer: Identity,
 	msg: PrehashedEthereumMessage,
 	relayer_registry: &RRL,
+	allowlists: &AL,
+	key_repository: &EKR,
+	now: u64,
+) -> Result<[u8; 65], SignEthereumError> {
+	relayer_registry.authorize(&signer, RelayerChain::Ethereum, now)?;
+	if allowlists.eth_allowlist(&signer).is_some() {
+		return Err(SignEthereumError::PrehashedNotAllowed)
+	}
+	sign(&msg, key_repository)
+}
+
+/// Signs an unsigned EIP-1559 or legacy transaction once it passed the allowlist of `signer`.
+pub fn handle_transaction<
+	RRL: RelayerRegistryLookup,
+	AL: EthAllowlistLookup,
+	EKR: AccessKey<KeyType = Pair>,
+>(
+	signer: Identity,
+	raw: &[u8],
+	relayer_registry: &RRL,
+	allowlists: &AL,
+	key_repository: &EKR,
+	now: u64,
+) -> Result<[u8; 65], SignEthereumError> {
+	relayer_registry.authorize(&signer, RelayerChain::Ethereum, now)?;
+	let tx = decode_transaction(raw).map_err(SignEthereumError::InvalidTransaction)?;
+	let allowlist = allowlists.eth_allowlist(&signer).ok_or(SignEthereumError::NoAllowlist)?;
+	ensure_contract_allowed(&allowlist, tx.chain_id, tx.to)?;
+	// plain transfers don't call anything
+	if !tx.data.is_empty() {
+		let selector: Option<[u8; 4]> = tx.data.get(..4).and_then(|s| s.try_into().ok());
+		if !matches!(selector, Some(s) if allowlist.selectors.contains(&s)) {
+			return Err(SignEthereumError::SelectorNotAllowed(
+				tx.data.iter().take(4).copied().collect(),
+			))
+		}
+	}
+
+	sign(&tx.sighash, key_repository)
+}
+
+/// Signs EIP-712 typed data once its verifying contract passed the allowlist of `signer`.
+pub fn handle_typed_data<
+	RRL: RelayerRegistryLookup,
+	AL: EthAllowlistLookup,
+	EKR: AccessKey<KeyType = Pair>,
+>(
+	signer: Identity,
+	json: &[u8],
+	relayer_registry: &RRL,
+	allowlists: &AL,
 	key_repository: &EKR,
+	now: u64,
 ) -> Result<[u8; 65], SignEthereumError> {
-	if relayer_registry.contains_key(&signer) {
-		let key = key_repository.retrieve_key().map_err(|e| {
-			error!("Could not retrieve ethereum signing key: {}", e);
-			SignEthereumError::SigningError
-		})?;
-		let sig = key.sign_prehash_recoverable(&msg).map_err(|e| {
-			error!("Could not sign: {}", e);
-			SignEthereumError::SigningError
-		})?;
-		Ok(sig)
+	relayer_registry.authorize(&signer, RelayerChain::Ethereum, now)?;
+	let typed_data = decode_typed_data(json).map_err(SignEthereumError::InvalidTypedData)?;
+	let allowlist = allowlists.eth_allowlist(&signer).ok_or(SignEthereumError::NoAllowlist)?;
+	ensure_contract_allowed(&allowlist, typed_data.chain_id, typed_data.verifying_contract)?;
+
+	sign(&typed_data.sighash, key_repository)
+}
+
+fn ensure_contract_allowed(
+	allowlist: &EthSigningAllowlist,
+	chain_id: u64,
+	address: [u8; 20],
+) -> Result<(), SignEthereumError> {
+	if allowlist
+		.contracts
+		.iter()
+		.any(|c| c.chain_id == chain_id && c.address == address)
+	{
+		Ok(())
 	} else {
-		Err(SignEthereumError::InvalidSigner)
+		Err(SignEthereumError::ContractNotAllowed(chain_id, address))
 	}
 }
 
+fn sign<EKR: AccessKey<KeyType = Pair>>(
+	msg: &[u8; 32],
+	key_repository: &EKR,
+) -> Result<[u8; 65], SignEthereumError> {
+	let key = key_repository.retrieve_key().map_err(|e| {
+		error!("Could not retrieve ethereum signing key: {}", e);
+		SignEthereumError::SigningError
+	})?;
+	key.sign_prehash_recoverable(msg).map_err(|e| {
+		error!("Could not sign: {}", e);
+		SignEthereumError::SigningError
+	})
+}
+
 #[cfg(test)]
 pub mod test {
-	use crate::handler::sign_ethereum::handle;
+	use crate::handler::sign_ethereum::{
+		handle, handle_transaction, handle_typed_data,
+		transaction::test::eip1559_transaction,
+		typed_data::test::{mail, MAIL_DOMAIN, MAIL_DOMAIN_TYPES},
+		SignEthereumError,
+	};
 	use bc_relayer_registry::{RelayerRegistry, RelayerRegistryUpdater};
+	use bc_signing_policy::{SigningPolicyRegistry, SigningPolicyUpdater};
 	use itp_sgx_crypto::{ecdsa::Pair as EcdsaPair, mocks::KeyRepositoryMock};
 	use k256::{ecdsa::SigningKey, elliptic_curve::rand_core};
-	use litentry_primitives::Identity;
+	use litentry_primitives::{EthContract, EthSigningAllowlist, Identity};
 	use sp_core::{sr25519, Pair};
+	use std::path::PathBuf;
+
+	const TRANSFER_SELECTOR: [u8; 4] = [0xa9, 0x05, 0x9c, 0xbb];
+
+	fn setup() -> (Identity, RelayerRegistry, SigningPolicyRegistry, KeyRepositoryMock<EcdsaPair>) {
+		let relayer_registry = RelayerRegistry::default();
+		let alice_key_pair = sr25519::Pair::from_string("//Alice", None).unwrap();
+		let relayer_account = Identity::Substrate(alice_key_pair.public().into());
+		relayer_registry.update(relayer_account.clone()).unwrap();
+
+		let allowlists = SigningPolicyRegistry::new(PathBuf::new());
+		let allowlist = EthSigningAllowlist {
+			contracts: vec![
+				EthContract { chain_id: 1, address: [0x11; 20] },
+				EthContract { chain_id: 1, address: [0xcc; 20] },
+			]
+			.try_into()
+			.unwrap(),
+			selectors: vec![TRANSFER_SELECTOR].try_into().unwrap(),
+		};
+		allowlists.set_eth_allowlist(relayer_account.clone(), Some(allowlist)).unwrap();
+
+		let private = SigningKey::random(&mut rand_core::OsRng);
+		let key_repository = KeyRepositoryMock::new(EcdsaPair::new(private));
+
+		(relayer_account, relayer_registry, allowlists, key_repository)
+	}
 
 	#[test]
 	pub fn it_should_return_ok_for_relayer_signer() {
@@ -69,10 +210,17 @@ pub mod test {
 		let signing_key = EcdsaPair::new(private);
 
 		let key_repository = KeyRepositoryMock::new(signing_key);
+		let allowlists = SigningPolicyRegistry::new(PathBuf::new());
 
 		//when
-		let result =
-			handle(relayer_account, Default::default(), &relayer_registry, &key_repository);
+		let result = handle(
+			relayer_account,
+			Default::default(),
+			&relayer_registry,
+			&allowlists,
+			&key_repository,
+			0,
+		);
 
 		//then
 		assert!(result.is_ok())
@@ -89,15 +237,35 @@ pub mod test {
 		let signing_key = EcdsaPair::new(private);
 
 		let key_repository = KeyRepositoryMock::new(signing_key);
+		let allowlists = SigningPolicyRegistry::new(PathBuf::new());
 
 		//when
-		let result =
-			handle(non_relayer_account, Default::default(), &relayer_registry, &key_repository);
+		let result = handle(
+			non_relayer_account,
+			Default::default(),
+			&relayer_registry,
+			&allowlists,
+			&key_repository,
+			0,
+		);
 
 		//then
 		assert!(result.is_err())
 	}
 
+	#[test]
+	pub fn it_should_not_sign_prehashed_message_for_relayer_with_allowlist() {
+		// given
+		let (relayer, relayer_registry, allowlists, key_repository) = setup();
+
+		// when
+		let result =
+			handle(relayer, Default::default(), &relayer_registry, &allowlists, &key_repository, 0);
+
+		// then
+		assert_eq!(result, Err(SignEthereumError::PrehashedNotAllowed))
+	}
+
 	#[test]
 	pub fn sign_ethereum_works() {
 		// test vector from bc team, verified with sp_core::ecdsa::Pair::sign_prehashed
@@ -113,4 +281,88 @@ pub mod test {
 
 		assert_eq!(&result, expected_result.as_slice())
 	}
+
+	#[test]
+	pub fn it_should_sign_allowed_transaction() {
+		// given
+		let (relayer, relayer_registry, allowlists, key_repository) = setup();
+		let calldata = [&TRANSFER_SELECTOR[..], &[0u8; 64][..]].concat();
+		let raw = eip1559_transaction([0x11; 20], &calldata);
+
+		// when
+		let result =
+			handle_transaction(relayer, &raw, &relayer_registry, &allowlists, &key_repository, 0);
+
+		// then
+		assert!(result.is_ok())
+	}
+
+	#[test]
+	pub fn it_should_not_sign_transaction_calling_other_selector() {
+		// given
+		let (relayer, relayer_registry, allowlists, key_repository) = setup();
+		let raw = eip1559_transaction([0x11; 20], &[0x09, 0x5e, 0xa7, 0xb3]);
+
+		// when
+		let result =
+			handle_transaction(relayer, &raw, &relayer_registry, &allowlists, &key_repository, 0);
+
+		// then
+		assert_eq!(result, Err(SignEthereumError::SelectorNotAllowed(vec![0x09, 0x5e, 0xa7, 0xb3])))
+	}
+
+	#[test]
+	pub fn it_should_not_sign_transaction_to_other_contract() {
+		// given
+		let (relayer, relayer_registry, allowlists, key_repository) = setup();
+		let raw = eip1559_transaction([0x22; 20], &[]);
+
+		// when
+		let result =
+			handle_transaction(relayer, &raw, &relayer_registry, &allowlists, &key_repository, 0);
+
+		// then
+		assert_eq!(result, Err(SignEthereumError::ContractNotAllowed(1, [0x22; 20])))
+	}
+
+	#[test]
+	pub fn it_should_sign_typed_data_for_allowed_contract() {
+		// given
+		let (relayer, relayer_registry, allowlists, key_repository) = setup();
+		let json = mail(MAIL_DOMAIN_TYPES, MAIL_DOMAIN);
+
+		// when
+		let result = handle_typed_data(
+			relayer,
+			json.as_bytes(),
+			&relayer_registry,
+			&allowlists,
+			&key_repository,
+			0,
+		);
+
+		// then
+		assert!(result.is_ok())
+	}
+
+	#[test]
+	pub fn it_should_not_sign_without_allowlist() {
+		// given
+		let (relayer, relayer_registry, allowlists, key_repository) = setup();
+		allowlists.set_eth_allowlist(relayer.clone(), None).unwrap();
+		let json = mail(MAIL_DOMAIN_TYPES, MAIL_DOMAIN);
+
+		// when
+		let result = handle_typed_data(
+			relayer,
+			json.as_bytes(),
+			&relayer_registry,
+			&allowlists,
+			&key_repository,
+			0,
+		);
+
+		// then
+		assert_eq!(result, Err(SignEthereumError::NoAllowlist))
+	}
 }
@@ -0,0 +1,171 @@
+// Copyright 2020-2024 Trust Computing GmbH.
+// This file is part of Litentry.
+//
+// Litentry is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// Litentry is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with Litentry.  If not, see <https://www.gnu.org/licenses/>.
+
+use codec::Encode;
+use rlp::{DecoderError, Rlp};
+use sp_io::hashing::keccak_256;
+use std::vec::Vec;
+
+const EIP1559_TX_TYPE: u8 = 0x02;
+
+#[derive(Encode, Debug, PartialEq, Eq)]
+pub enum TransactionError {
+	InvalidRlp,
+	UnsupportedTransactionType(u8),
+	// legacy transaction without EIP-155 replay protection
+	MissingChainId,
+	ContractCreation,
+}
+
+impl From<DecoderError> for TransactionError {
+	fn from(_: DecoderError) -> Self {
+		Self::InvalidRlp
+	}
+}
+
+/// Fields of an unsigned transaction which are checked before it is signed.
+#[derive(Debug, PartialEq, Eq)]
+pub struct EthereumTransaction {
+	pub chain_id: u64,
+	pub to: [u8; 20],
+	pub data: Vec<u8>,
+	pub sighash: [u8; 32],
+}
+
+/// Decodes an unsigned EIP-1559 transaction or a legacy transaction in its EIP-155 signing form.
+///
+/// The sighash is the hash of `raw` itself, so everything that ends up signed must have been
+/// decoded here.
+pub fn decode_transaction(raw: &[u8]) -> Result<EthereumTransaction, TransactionError> {
+	let (chain_id, to, data) = match raw.first() {
+		// 0x02 || rlp([chain_id, nonce, max_priority_fee_per_gas, max_fee_per_gas, gas_limit, to,
+		// value, data, access_list])
+		Some(&EIP1559_TX_TYPE) => {
+			let tx = list(&raw[1..])?;
+			if tx.item_count()? != 9 {
+				return Err(TransactionError::InvalidRlp)
+			}
+			(tx.val_at::<u64>(0)?, tx.at(5)?, tx.at(7)?)
+		},
+		// rlp([nonce, gas_price, gas_limit, to, value, data, chain_id, 0, 0])
+		Some(&prefix) if prefix >= 0xc0 => {
+			let tx = list(raw)?;
+			match tx.item_count()? {
+				9 => {},
+				6 => return Err(TransactionError::MissingChainId),
+				_ => return Err(TransactionError::InvalidRlp),
+			}
+			if tx.val_at::<u64>(7)? != 0 || tx.val_at::<u64>(8)? != 0 {
+				return Err(TransactionError::InvalidRlp)
+			}
+			(tx.val_at::<u64>(6)?, tx.at(3)?, tx.at(5)?)
+		},
+		Some(&tx_type) => return Err(TransactionError::UnsupportedTransactionType(tx_type)),
+		None => return Err(TransactionError::InvalidRlp),
+	};
+	if chain_id == 0 {
+		return Err(TransactionError::MissingChainId)
+	}
+	let to = to.data()?;
+	if to.is_empty() {
+		return Err(TransactionError::ContractCreation)
+	}
+
+	Ok(EthereumTransaction {
+		chain_id,
+		to: to.try_into().map_err(|_| TransactionError::InvalidRlp)?,
+		data: data.data()?.to_vec(),
+		sighash: keccak_256(raw),
+	})
+}
+
+// trailing bytes would be signed without being looked at
+fn list(raw: &[u8]) -> Result<Rlp<'_>, TransactionError> {
+	let rlp = Rlp::new(raw);
+	if !rlp.is_list() || rlp.payload_info()?.total() != raw.len() {
+		return Err(TransactionError::InvalidRlp)
+	}
+	Ok(rlp)
+}
+
+#[cfg(test)]
+pub mod test {
+	use super::{decode_transaction, TransactionError};
+	use rlp::RlpStream;
+
+	pub fn eip1559_transaction(to: [u8; 20], data: &[u8]) -> Vec<u8> {
+		let mut stream = RlpStream::new_list(9);
+		stream.append(&1u64);
+		stream.append(&0u64);
+		stream.append(&1_000_000_000u64);
+		stream.append(&20_000_000_000u64);
+		stream.append(&100_000u64);
+		stream.append(&to.to_vec());
+		stream.append(&0u64);
+		stream.append(&data.to_vec());
+		stream.begin_list(0);
+		[&[0x02u8][..], &stream.out()[..]].concat()
+	}
+
+	#[test]
+	pub fn it_should_decode_eip155_transaction() {
+		// test vector from EIP-155
+		let raw = hex::decode("ec098504a817c800825208943535353535353535353535353535353535353535880de0b6b3a764000080018080").unwrap();
+
+		let tx = decode_transaction(&raw).unwrap();
+
+		assert_eq!(tx.chain_id, 1);
+		assert_eq!(tx.to, [0x35; 20]);
+		assert!(tx.data.is_empty());
+		assert_eq!(
+			tx.sighash.to_vec(),
+			hex::decode("daf5a779ae972f972197303d7b574746c7ef83eadac0f2791ad23db92e4c8e53")
+				.unwrap()
+		);
+	}
+
+	#[test]
+	pub fn it_should_decode_eip1559_transaction() {
+		let raw = eip1559_transaction([0x11; 20], &[0xa9, 0x05, 0x9c, 0xbb, 0x01]);
+
+		let tx = decode_transaction(&raw).unwrap();
+
+		assert_eq!(tx.chain_id, 1);
+		assert_eq!(tx.to, [0x11; 20]);
+		assert_eq!(tx.data, vec![0xa9, 0x05, 0x9c, 0xbb, 0x01]);
+	}
+
+	#[test]
+	pub fn it_should_reject_trailing_bytes() {
+		let mut raw = eip1559_transaction([0x11; 20], &[]);
+		raw.push(0x00);
+
+		assert_eq!(decode_transaction(&raw), Err(TransactionError::InvalidRlp));
+	}
+
+	#[test]
+	pub fn it_should_reject_legacy_transaction_without_chain_id() {
+		let mut stream = RlpStream::new_list(6);
+		stream.append(&0u64);
+		stream.append(&1u64);
+		stream.append(&21_000u64);
+		stream.append(&[0x11u8; 20].to_vec());
+		stream.append(&0u64);
+		stream.append(&Vec::<u8>::new());
+
+		assert_eq!(decode_transaction(&stream.out()), Err(TransactionError::MissingChainId));
+	}
+}
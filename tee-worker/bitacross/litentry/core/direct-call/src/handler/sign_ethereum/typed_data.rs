@@ -0,0 +1,353 @@
+// Copyright 2020-2024 Trust Computing GmbH.
+// This file is part of Litentry.
+//
+// Litentry is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// Litentry is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with Litentry.  If not, see <https://www.gnu.org/licenses/>.
+
+use alloc::collections::BTreeSet;
+use codec::Encode;
+use serde_json::{Map, Value};
+use sp_core::U256;
+use sp_io::hashing::keccak_256;
+use std::{string::String, vec::Vec};
+
+const DOMAIN_TYPE: &str = "EIP712Domain";
+
+#[derive(Encode, Debug, PartialEq, Eq)]
+pub enum TypedDataError {
+	InvalidJson,
+	// name of the type which is unknown or malformed
+	InvalidType(String),
+	// name of the type or field whose value doesn't match its type
+	InvalidValue(String),
+	// `chainId` or `verifyingContract` isn't part of the domain
+	MissingDomainField(String),
+}
+
+/// Fields of EIP-712 typed data which are checked before it is signed.
+#[derive(Debug, PartialEq, Eq)]
+pub struct TypedData {
+	pub chain_id: u64,
+	pub verifying_contract: [u8; 20],
+	pub sighash: [u8; 32],
+}
+
+/// Decodes typed data in the JSON format of `eth_signTypedData_v4` and computes its EIP-712 hash.
+pub fn decode_typed_data(json: &[u8]) -> Result<TypedData, TypedDataError> {
+	let typed_data: Value =
+		serde_json::from_slice(json).map_err(|_| TypedDataError::InvalidJson)?;
+	let types = typed_data
+		.get("types")
+		.and_then(Value::as_object)
+		.ok_or(TypedDataError::InvalidJson)?;
+	let primary_type = typed_data
+		.get("primaryType")
+		.and_then(Value::as_str)
+		.ok_or(TypedDataError::InvalidJson)?;
+	let domain = typed_data.get("domain").ok_or(TypedDataError::InvalidJson)?;
+	let message = typed_data.get("message").ok_or(TypedDataError::InvalidJson)?;
+	if primary_type == DOMAIN_TYPE {
+		return Err(TypedDataError::InvalidType(primary_type.into()))
+	}
+
+	let encoder = Encoder { types };
+	let chain_id = encode_integer(encoder.domain_field(domain, "chainId", "uint256")?, 256, false)
+		.map(|word| U256::from_big_endian(&word))
+		.filter(|chain_id| *chain_id <= U256::from(u64::MAX))
+		.ok_or_else(|| TypedDataError::InvalidValue("chainId".into()))?;
+	let verifying_contract =
+		address(encoder.domain_field(domain, "verifyingContract", "address")?)
+			.ok_or_else(|| TypedDataError::InvalidValue("verifyingContract".into()))?;
+
+	let mut encoded = Vec::from([0x19, 0x01]);
+	encoded.extend(encoder.hash_struct(DOMAIN_TYPE, domain)?);
+	encoded.extend(encoder.hash_struct(primary_type, message)?);
+
+	Ok(TypedData {
+		chain_id: chain_id.low_u64(),
+		verifying_contract,
+		sighash: keccak_256(&encoded),
+	})
+}
+
+struct Encoder<'a> {
+	types: &'a Map<String, Value>,
+}
+
+impl<'a> Encoder<'a> {
+	// (name, type) of each field of `ty`
+	fn fields(&self, ty: &str) -> Result<Vec<(&'a str, &'a str)>, TypedDataError> {
+		let invalid = || TypedDataError::InvalidType(ty.into());
+		self.types
+			.get(ty)
+			.and_then(Value::as_array)
+			.ok_or_else(invalid)?
+			.iter()
+			.map(|field| {
+				let name = field.get("name").and_then(Value::as_str);
+				let field_type = field.get("type").and_then(Value::as_str);
+				name.zip(field_type).ok_or_else(invalid)
+			})
+			.collect()
+	}
+
+	// a field only ends up in the domain separator if it's declared
+	fn domain_field<'v>(
+		&self,
+		domain: &'v Value,
+		name: &str,
+		field_type: &str,
+	) -> Result<&'v Value, TypedDataError> {
+		if !self.fields(DOMAIN_TYPE)?.contains(&(name, field_type)) {
+			return Err(TypedDataError::MissingDomainField(name.into()))
+		}
+		domain.get(name).ok_or_else(|| TypedDataError::MissingDomainField(name.into()))
+	}
+
+	// `ty` followed by all struct types it references, sorted by name
+	fn encode_type(&self, ty: &'a str) -> Result<String, TypedDataError> {
+		let mut dependencies = BTreeSet::new();
+		self.collect_dependencies(ty, &mut dependencies)?;
+		dependencies.remove(ty);
+
+		let mut encoded = String::new();
+		for dependency in core::iter::once(ty).chain(dependencies) {
+			let fields: Vec<String> = self
+				.fields(dependency)?
+				.into_iter()
+				.map(|(name, field_type)| [field_type, name].join(" "))
+				.collect();
+			encoded.push_str(dependency);
+			encoded.push('(');
+			encoded.push_str(&fields.join(","));
+			encoded.push(')');
+		}
+		Ok(encoded)
+	}
+
+	fn collect_dependencies(
+		&self,
+		ty: &'a str,
+		dependencies: &mut BTreeSet<&'a str>,
+	) -> Result<(), TypedDataError> {
+		if !dependencies.insert(ty) {
+			return Ok(())
+		}
+		for (_, field_type) in self.fields(ty)? {
+			let base_type = field_type.split('[').next().unwrap_or(field_type);
+			if self.types.contains_key(base_type) {
+				self.collect_dependencies(base_type, dependencies)?;
+			}
+		}
+		Ok(())
+	}
+
+	fn hash_struct(&self, ty: &'a str, value: &Value) -> Result<[u8; 32], TypedDataError> {
+		let object = value.as_object().ok_or_else(|| TypedDataError::InvalidValue(ty.into()))?;
+		let mut encoded = keccak_256(self.encode_type(ty)?.as_bytes()).to_vec();
+		for (name, field_type) in self.fields(ty)? {
+			let field =
+				object.get(name).ok_or_else(|| TypedDataError::InvalidValue(name.into()))?;
+			encoded.extend(self.encode_value(field_type, field)?);
+		}
+		Ok(keccak_256(&encoded))
+	}
+
+	fn encode_value(&self, ty: &'a str, value: &Value) -> Result<[u8; 32], TypedDataError> {
+		let invalid_type = || TypedDataError::InvalidType(ty.into());
+		let invalid_value = || TypedDataError::InvalidValue(ty.into());
+
+		if let Some(array_type) = ty.strip_suffix(']') {
+			let (item_type, len) = array_type.rsplit_once('[').ok_or_else(invalid_type)?;
+			let items = value.as_array().ok_or_else(invalid_value)?;
+			if !len.is_empty() && len.parse::<usize>().ok() != Some(items.len()) {
+				return Err(invalid_value())
+			}
+			let mut encoded = Vec::new();
+			for item in items {
+				encoded.extend(self.encode_value(item_type, item)?);
+			}
+			return Ok(keccak_256(&encoded))
+		}
+		if self.types.contains_key(ty) {
+			return self.hash_struct(ty, value)
+		}
+
+		let mut word = [0u8; 32];
+		match ty {
+			"string" => word = keccak_256(value.as_str().ok_or_else(invalid_value)?.as_bytes()),
+			"bytes" => word = keccak_256(&hex_bytes(value).ok_or_else(invalid_value)?),
+			"bool" => word[31] = value.as_bool().ok_or_else(invalid_value)? as u8,
+			"address" => word[12..].copy_from_slice(&address(value).ok_or_else(invalid_value)?),
+			_ =>
+				if let Some(len) = ty.strip_prefix("bytes") {
+					let len = len.parse::<usize>().ok().filter(|len| (1..=32).contains(len));
+					let bytes = hex_bytes(value).ok_or_else(invalid_value)?;
+					if len.ok_or_else(invalid_type)? != bytes.len() {
+						return Err(invalid_value())
+					}
+					word[..bytes.len()].copy_from_slice(&bytes);
+				} else {
+					let (bits, signed) = match ty.strip_prefix("uint") {
+						Some(bits) => (bits, false),
+						None => (ty.strip_prefix("int").ok_or_else(invalid_type)?, true),
+					};
+					let bits = bits
+						.parse::<usize>()
+						.ok()
+						.filter(|bits| bits % 8 == 0 && (8..=256).contains(bits))
+						.ok_or_else(invalid_type)?;
+					word = encode_integer(value, bits, signed).ok_or_else(invalid_value)?;
+				},
+		}
+		Ok(word)
+	}
+}
+
+// integers are given as JSON numbers or as decimal or 0x prefixed hex strings
+fn encode_integer(value: &Value, bits: usize, signed: bool) -> Option<[u8; 32]> {
+	let (negative, magnitude) = match value {
+		Value::Number(number) => match number.as_u64() {
+			Some(number) => (false, U256::from(number)),
+			None => (true, U256::from(number.as_i64()?.unsigned_abs())),
+		},
+		Value::String(number) => {
+			let (negative, number) = match number.strip_prefix('-') {
+				Some(number) => (true, number),
+				None => (false, number.as_str()),
+			};
+			let magnitude = match number.strip_prefix("0x") {
+				Some(hex) => U256::from_str_radix(hex, 16).ok()?,
+				None => U256::from_dec_str(number).ok()?,
+			};
+			(negative, magnitude)
+		},
+		_ => return None,
+	};
+
+	let value = match (signed, negative) {
+		// two's complement
+		(true, true) if magnitude <= U256::one() << (bits - 1) =>
+			(!magnitude).overflowing_add(U256::one()).0,
+		(true, false) if magnitude < U256::one() << (bits - 1) => magnitude,
+		(false, _)
+			if (!negative || magnitude.is_zero())
+				&& (bits == 256 || (magnitude >> bits).is_zero()) =>
+			magnitude,
+		_ => return None,
+	};
+
+	let mut word = [0u8; 32];
+	value.to_big_endian(&mut word);
+	Some(word)
+}
+
+fn hex_bytes(value: &Value) -> Option<Vec<u8>> {
+	hex::decode(value.as_str()?.strip_prefix("0x")?).ok()
+}
+
+fn address(value: &Value) -> Option<[u8; 20]> {
+	hex_bytes(value)?.try_into().ok()
+}
+
+#[cfg(test)]
+pub mod test {
+	use super::{decode_typed_data, TypedDataError};
+
+	pub fn mail(domain_types: &str, domain: &str) -> String {
+		format!(
+			r#"{{
+				"types": {{
+					"EIP712Domain": [{}],
+					"Person": [
+						{{ "name": "name", "type": "string" }},
+						{{ "name": "wallet", "type": "address" }}
+					],
+					"Mail": [
+						{{ "name": "from", "type": "Person" }},
+						{{ "name": "to", "type": "Person" }},
+						{{ "name": "contents", "type": "string" }}
+					]
+				}},
+				"primaryType": "Mail",
+				"domain": {{ {} }},
+				"message": {{
+					"from": {{
+						"name": "Cow",
+						"wallet": "0xCD2a3d9F938E13CD947Ec05AbC7FE734Df8DD826"
+					}},
+					"to": {{
+						"name": "Bob",
+						"wallet": "0xbBbBBBBbbBBBbbbBbbBbbbbBBbBbbbbBbBbbBBbB"
+					}},
+					"contents": "Hello, Bob!"
+				}}
+			}}"#,
+			domain_types, domain
+		)
+	}
+
+	pub const MAIL_DOMAIN_TYPES: &str = r#"
+		{ "name": "name", "type": "string" },
+		{ "name": "version", "type": "string" },
+		{ "name": "chainId", "type": "uint256" },
+		{ "name": "verifyingContract", "type": "address" }"#;
+
+	pub const MAIL_DOMAIN: &str = r#"
+		"name": "Ether Mail",
+		"version": "1",
+		"chainId": 1,
+		"verifyingContract": "0xCcCCccccCCCCcCCCCCCcCcCccCcCCCcCcccccccC""#;
+
+	#[test]
+	pub fn it_should_hash_typed_data() {
+		// example from EIP-712
+		let typed_data =
+			decode_typed_data(mail(MAIL_DOMAIN_TYPES, MAIL_DOMAIN).as_bytes()).unwrap();
+
+		assert_eq!(typed_data.chain_id, 1);
+		assert_eq!(typed_data.verifying_contract, [0xcc; 20]);
+		assert_eq!(
+			typed_data.sighash.to_vec(),
+			hex::decode("be609aee343fb3c4b28e1df9e632fca64fcfaede20f02e86244efddf30957bd2")
+				.unwrap()
+		);
+	}
+
+	#[test]
+	pub fn it_should_reject_undeclared_verifying_contract() {
+		// the verifying contract wouldn't be part of the domain separator
+		let domain_types =
+			r#"{ "name": "name", "type": "string" }, { "name": "chainId", "type": "uint256" }"#;
+
+		let result = decode_typed_data(mail(domain_types, MAIL_DOMAIN).as_bytes());
+
+		assert_eq!(result, Err(TypedDataError::MissingDomainField("verifyingContract".into())));
+	}
+
+	#[test]
+	pub fn it_should_reject_out_of_range_integer() {
+		let domain_types = r#"
+			{ "name": "chainId", "type": "uint256" },
+			{ "name": "verifyingContract", "type": "address" },
+			{ "name": "salt", "type": "uint8" }"#;
+		let domain = r#"
+			"chainId": 1,
+			"verifyingContract": "0xCcCCccccCCCCcCCCCCCcCcCccCcCCCcCcccccccC",
+			"salt": "256""#;
+
+		let result = decode_typed_data(mail(domain_types, domain).as_bytes());
+
+		assert_eq!(result, Err(TypedDataError::InvalidValue("uint8".into())));
+	}
+}
@@ -0,0 +1,132 @@
+// Copyright 2020-2024 Trust Computing GmbH.
+// This file is part of Litentry.
+//
+// Litentry is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// Litentry is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with Litentry.  If not, see <https://www.gnu.org/licenses/>.
+
+use bc_relayer_registry::{PermissionError, RelayerRegistryLookup};
+use codec::Encode;
+use itp_sgx_crypto::key_repository::AccessKey;
+use litentry_primitives::{Identity, RelayerChain};
+use log::error;
+use sp_core::{ed25519::Pair as Ed25519Pair, Pair};
+
+pub mod message;
+
+pub use message::{decode_message, MessageError, SolanaMessage};
+
+#[derive(Encode, Debug, PartialEq, Eq)]
+pub enum SignSolanaError {
+	InvalidSigner,
+	SigningError,
+	InvalidMessage(MessageError),
+	// the message doesn't require a signature of the enclave's key
+	NotARequiredSigner,
+	PermissionDenied(PermissionError),
+}
+
+impl From<PermissionError> for SignSolanaError {
+	fn from(e: PermissionError) -> Self {
+		match e {
+			PermissionError::NotRelayer => Self::InvalidSigner,
+			e => Self::PermissionDenied(e),
+		}
+	}
+}
+
+/// Signs a Solana transaction message, it's only signed if the enclave's key is one of its signers.
+pub fn handle<RRL: RelayerRegistryLookup, EKR: AccessKey<KeyType = Ed25519Pair>>(
+	signer: Identity,
+	message: &[u8],
+	relayer_registry: &RRL,
+	key_repository: &EKR,
+	now: u64,
+) -> Result<[u8; 64], SignSolanaError> {
+	relayer_registry.authorize(&signer, RelayerChain::Solana, now)?;
+	let decoded = decode_message(message).map_err(SignSolanaError::InvalidMessage)?;
+	let key = key_repository.retrieve_key().map_err(|e| {
+		error!("Could not retrieve solana signing key: {}", e);
+		SignSolanaError::SigningError
+	})?;
+	if !decoded.signers().contains(&key.public().0) {
+		return Err(SignSolanaError::NotARequiredSigner)
+	}
+	Ok(key.sign(message).into())
+}
+
+#[cfg(test)]
+pub mod test {
+	use super::{handle, message::test::transfer_message, SignSolanaError};
+	use bc_relayer_registry::{RelayerRegistry, RelayerRegistryUpdater};
+	use itp_sgx_crypto::mocks::KeyRepositoryMock;
+	use litentry_primitives::Identity;
+	use sp_core::{ed25519, sr25519, Pair};
+
+	fn relayer() -> (RelayerRegistry, Identity) {
+		let relayer_registry = RelayerRegistry::default();
+		let alice_key_pair = sr25519::Pair::from_string("//Alice", None).unwrap();
+		let relayer_account = Identity::Substrate(alice_key_pair.public().into());
+		relayer_registry.update(relayer_account.clone()).unwrap();
+		(relayer_registry, relayer_account)
+	}
+
+	#[test]
+	pub fn it_should_sign_message_of_enclave_key() {
+		// given
+		let (relayer_registry, relayer_account) = relayer();
+		let signing_key = ed25519::Pair::from_seed(&[3; 32]);
+		let public = signing_key.public();
+		let message = transfer_message(public.0, [2; 32]);
+		let key_repository = KeyRepositoryMock::new(signing_key);
+
+		// when
+		let signature =
+			handle(relayer_account, &message, &relayer_registry, &key_repository, 0).unwrap();
+
+		// then
+		assert!(ed25519::Pair::verify(&ed25519::Signature::from_raw(signature), &message, &public));
+	}
+
+	#[test]
+	pub fn it_should_return_err_for_non_relayer_signer() {
+		// given
+		let relayer_registry = RelayerRegistry::default();
+		let alice_key_pair = sr25519::Pair::from_string("//Alice", None).unwrap();
+		let non_relayer_account = Identity::Substrate(alice_key_pair.public().into());
+		let signing_key = ed25519::Pair::from_seed(&[3; 32]);
+		let message = transfer_message(signing_key.public().0, [2; 32]);
+		let key_repository = KeyRepositoryMock::new(signing_key);
+
+		// when
+		let result = handle(non_relayer_account, &message, &relayer_registry, &key_repository, 0);
+
+		// then
+		assert_eq!(result, Err(SignSolanaError::InvalidSigner))
+	}
+
+	#[test]
+	pub fn it_should_return_err_if_enclave_key_is_not_a_signer() {
+		// given
+		let (relayer_registry, relayer_account) = relayer();
+		let signing_key = ed25519::Pair::from_seed(&[3; 32]);
+		// the enclave's key only receives the transfer
+		let message = transfer_message([1; 32], signing_key.public().0);
+		let key_repository = KeyRepositoryMock::new(signing_key);
+
+		// when
+		let result = handle(relayer_account, &message, &relayer_registry, &key_repository, 0);
+
+		// then
+		assert_eq!(result, Err(SignSolanaError::NotARequiredSigner))
+	}
+}
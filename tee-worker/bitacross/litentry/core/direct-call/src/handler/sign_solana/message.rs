@@ -0,0 +1,260 @@
+// Copyright 2020-2024 Trust Computing GmbH.
+// This file is part of Litentry.
+//
+// Litentry is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// Litentry is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with Litentry.  If not, see <https://www.gnu.org/licenses/>.
+
+use codec::Encode;
+use std::vec::Vec;
+
+const VERSION_PREFIX: u8 = 0x80;
+
+#[derive(Encode, Debug, PartialEq, Eq)]
+pub enum MessageError {
+	InvalidMessage,
+	UnsupportedVersion(u8),
+	// index which doesn't refer to any account of the message
+	InvalidAccountIndex(u8),
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub struct CompiledInstruction {
+	pub program_id_index: u8,
+	pub accounts: Vec<u8>,
+	pub data: Vec<u8>,
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub struct AddressTableLookup {
+	pub account_key: [u8; 32],
+	pub writable_indexes: Vec<u8>,
+	pub readonly_indexes: Vec<u8>,
+}
+
+/// Legacy or v0 transaction message, which is what a Solana transaction signature is made for.
+#[derive(Debug, PartialEq, Eq)]
+pub struct SolanaMessage {
+	// `None` for legacy messages
+	pub version: Option<u8>,
+	pub num_required_signatures: u8,
+	pub num_readonly_signed_accounts: u8,
+	pub num_readonly_unsigned_accounts: u8,
+	pub account_keys: Vec<[u8; 32]>,
+	pub recent_blockhash: [u8; 32],
+	pub instructions: Vec<CompiledInstruction>,
+	pub address_table_lookups: Vec<AddressTableLookup>,
+}
+
+impl SolanaMessage {
+	/// Accounts whose signature the transaction requires, the first one pays the fees.
+	pub fn signers(&self) -> &[[u8; 32]] {
+		&self.account_keys[..self.num_required_signatures as usize]
+	}
+}
+
+/// Decodes a serialized message, the whole of `raw` has to be part of it.
+pub fn decode_message(raw: &[u8]) -> Result<SolanaMessage, MessageError> {
+	let mut reader = Reader(raw);
+	let version = match reader.peek()? {
+		prefix if prefix & VERSION_PREFIX != 0 => {
+			reader.byte()?;
+			match prefix & !VERSION_PREFIX {
+				0 => Some(0),
+				version => return Err(MessageError::UnsupportedVersion(version)),
+			}
+		},
+		_ => None,
+	};
+	let num_required_signatures = reader.byte()?;
+	let num_readonly_signed_accounts = reader.byte()?;
+	let num_readonly_unsigned_accounts = reader.byte()?;
+	let account_keys = reader.vec(|r| r.array())?;
+	let recent_blockhash = reader.array()?;
+	let instructions = reader.vec(|r| {
+		Ok(CompiledInstruction {
+			program_id_index: r.byte()?,
+			accounts: r.bytes()?,
+			data: r.bytes()?,
+		})
+	})?;
+	let address_table_lookups = match version {
+		Some(_) => reader.vec(|r| {
+			Ok(AddressTableLookup {
+				account_key: r.array()?,
+				writable_indexes: r.bytes()?,
+				readonly_indexes: r.bytes()?,
+			})
+		})?,
+		None => Vec::new(),
+	};
+	if !reader.0.is_empty() {
+		return Err(MessageError::InvalidMessage)
+	}
+
+	let num_signers = num_required_signatures as usize;
+	if num_signers == 0
+		|| num_readonly_signed_accounts >= num_required_signatures
+		|| num_signers + num_readonly_unsigned_accounts as usize > account_keys.len()
+	{
+		return Err(MessageError::InvalidMessage)
+	}
+	// program ids can't be loaded from lookup tables
+	let num_accounts = account_keys.len()
+		+ address_table_lookups
+			.iter()
+			.map(|lookup| lookup.writable_indexes.len() + lookup.readonly_indexes.len())
+			.sum::<usize>();
+	for instruction in instructions.iter() {
+		if instruction.program_id_index as usize >= account_keys.len() {
+			return Err(MessageError::InvalidAccountIndex(instruction.program_id_index))
+		}
+		if let Some(index) = instruction.accounts.iter().find(|i| **i as usize >= num_accounts) {
+			return Err(MessageError::InvalidAccountIndex(*index))
+		}
+	}
+
+	Ok(SolanaMessage {
+		version,
+		num_required_signatures,
+		num_readonly_signed_accounts,
+		num_readonly_unsigned_accounts,
+		account_keys,
+		recent_blockhash,
+		instructions,
+		address_table_lookups,
+	})
+}
+
+struct Reader<'a>(&'a [u8]);
+
+impl<'a> Reader<'a> {
+	fn take(&mut self, len: usize) -> Result<&'a [u8], MessageError> {
+		if self.0.len() < len {
+			return Err(MessageError::InvalidMessage)
+		}
+		let (head, tail) = self.0.split_at(len);
+		self.0 = tail;
+		Ok(head)
+	}
+
+	fn peek(&self) -> Result<u8, MessageError> {
+		self.0.first().copied().ok_or(MessageError::InvalidMessage)
+	}
+
+	fn byte(&mut self) -> Result<u8, MessageError> {
+		Ok(self.take(1)?[0])
+	}
+
+	fn array(&mut self) -> Result<[u8; 32], MessageError> {
+		self.take(32)?.try_into().map_err(|_| MessageError::InvalidMessage)
+	}
+
+	// compact-u16, 7 bits per byte with the high bit set on all but the last one
+	fn len(&mut self) -> Result<usize, MessageError> {
+		let mut len = 0usize;
+		for i in 0..3 {
+			let byte = self.byte()?;
+			len |= ((byte & 0x7f) as usize) << (7 * i);
+			if byte & 0x80 == 0 {
+				if (i > 0 && byte == 0) || len > u16::MAX as usize {
+					return Err(MessageError::InvalidMessage)
+				}
+				return Ok(len)
+			}
+		}
+		Err(MessageError::InvalidMessage)
+	}
+
+	fn bytes(&mut self) -> Result<Vec<u8>, MessageError> {
+		let len = self.len()?;
+		Ok(self.take(len)?.to_vec())
+	}
+
+	fn vec<T>(
+		&mut self,
+		item: impl Fn(&mut Self) -> Result<T, MessageError>,
+	) -> Result<Vec<T>, MessageError> {
+		let len = self.len()?;
+		(0..len).map(|_| item(self)).collect()
+	}
+}
+
+#[cfg(test)]
+pub mod test {
+	use super::{decode_message, MessageError};
+
+	pub const BLOCKHASH: [u8; 32] = [7; 32];
+
+	// system program transfer of 1_000_000 lamports from `from` to `to`
+	pub fn transfer_message(from: [u8; 32], to: [u8; 32]) -> Vec<u8> {
+		let mut message = vec![1, 0, 1, 3];
+		message.extend(from);
+		message.extend(to);
+		message.extend([0u8; 32]);
+		message.extend(BLOCKHASH);
+		message.extend([1, 2, 2, 0, 1]);
+		let data = [[2u8, 0, 0, 0].as_slice(), &1_000_000u64.to_le_bytes()].concat();
+		message.push(data.len() as u8);
+		message.extend(data);
+		message
+	}
+
+	#[test]
+	pub fn it_should_decode_legacy_message() {
+		let message = decode_message(&transfer_message([1; 32], [2; 32])).unwrap();
+
+		assert_eq!(message.version, None);
+		assert_eq!(message.signers(), &[[1; 32]]);
+		assert_eq!(message.recent_blockhash, BLOCKHASH);
+		assert_eq!(message.instructions.len(), 1);
+		assert_eq!(message.instructions[0].accounts, vec![0, 1]);
+	}
+
+	#[test]
+	pub fn it_should_decode_v0_message_with_lookup() {
+		// given
+		let mut raw = vec![0x80];
+		raw.extend(transfer_message([1; 32], [2; 32]));
+		// the transfer goes to the first writable account of the lookup table
+		let accounts = raw.len() - 12 - 3;
+		raw[accounts + 1] = 3;
+		raw.extend([1]);
+		raw.extend([9u8; 32]);
+		raw.extend([1, 5, 0]);
+
+		// when
+		let message = decode_message(&raw).unwrap();
+
+		// then
+		assert_eq!(message.version, Some(0));
+		assert_eq!(message.instructions[0].accounts, vec![0, 3]);
+		assert_eq!(message.address_table_lookups[0].writable_indexes, vec![5]);
+	}
+
+	#[test]
+	pub fn it_should_reject_trailing_bytes() {
+		let mut raw = transfer_message([1; 32], [2; 32]);
+		raw.push(0);
+
+		assert_eq!(decode_message(&raw), Err(MessageError::InvalidMessage));
+	}
+
+	#[test]
+	pub fn it_should_reject_unknown_account_index() {
+		let mut raw = transfer_message([1; 32], [2; 32]);
+		let accounts = raw.len() - 12 - 3;
+		raw[accounts + 1] = 3;
+
+		assert_eq!(decode_message(&raw), Err(MessageError::InvalidAccountIndex(3)));
+	}
+}
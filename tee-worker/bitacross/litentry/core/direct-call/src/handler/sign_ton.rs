@@ -14,10 +14,10 @@
 // You should have received a copy of the GNU General Public License
 // along with Litentry.  If not, see <https://www.gnu.org/licenses/>.
 
-use bc_relayer_registry::RelayerRegistryLookup;
+use bc_relayer_registry::{PermissionError, RelayerRegistryLookup};
 use codec::Encode;
 use itp_sgx_crypto::key_repository::AccessKey;
-use litentry_primitives::Identity;
+use litentry_primitives::{Identity, RelayerChain};
 use log::error;
 use sp_core::{ed25519::Pair as Ed25519Pair, Pair};
 use std::vec::Vec;
@@ -26,6 +26,16 @@ use std::vec::Vec;
 pub enum SignTonError {
 	InvalidSigner,
 	SigningError,
+	PermissionDenied(PermissionError),
+}
+
+impl From<PermissionError> for SignTonError {
+	fn from(e: PermissionError) -> Self {
+		match e {
+			PermissionError::NotRelayer => Self::InvalidSigner,
+			e => Self::PermissionDenied(e),
+		}
+	}
 }
 
 pub fn handle<RRL: RelayerRegistryLookup, EKR: AccessKey<KeyType = Ed25519Pair>>(
@@ -33,17 +43,15 @@ pub fn handle<RRL: RelayerRegistryLookup, EKR: AccessKey<KeyType = Ed25519Pair>>
 	msg: Vec<u8>,
 	relayer_registry: &RRL,
 	key_repository: &EKR,
+	now: u64,
 ) -> Result<[u8; 64], SignTonError> {
-	if relayer_registry.contains_key(&signer) {
-		let key = key_repository.retrieve_key().map_err(|e| {
-			error!("Could not retrieve ton signing key: {}", e);
-			SignTonError::SigningError
-		})?;
-		let sig = key.sign(&msg);
-		Ok(sig.into())
-	} else {
-		Err(SignTonError::InvalidSigner)
-	}
+	relayer_registry.authorize(&signer, RelayerChain::Ton, now)?;
+	let key = key_repository.retrieve_key().map_err(|e| {
+		error!("Could not retrieve ton signing key: {}", e);
+		SignTonError::SigningError
+	})?;
+	let sig = key.sign(&msg);
+	Ok(sig.into())
 }
 
 #[cfg(test)]
@@ -72,7 +80,7 @@ pub mod test {
 
 		//when
 		let result =
-			handle(relayer_account, Default::default(), &relayer_registry, &key_repository);
+			handle(relayer_account, Default::default(), &relayer_registry, &key_repository, 0);
 
 		//then
 		assert!(result.is_ok())
@@ -95,7 +103,7 @@ pub mod test {
 
 		//when
 		let result =
-			handle(non_relayer_account, Default::default(), &relayer_registry, &key_repository);
+			handle(non_relayer_account, Default::default(), &relayer_registry, &key_repository, 0);
 
 		//then
 		assert!(result.is_err())
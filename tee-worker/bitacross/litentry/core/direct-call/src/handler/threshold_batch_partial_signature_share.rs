@@ -0,0 +1,109 @@
+// Copyright 2020-2024 Trust Computing GmbH.
+// This file is part of Litentry.
+//
+// Litentry is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// Litentry is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with Litentry.  If not, see <https://www.gnu.org/licenses/>.
+
+use bc_enclave_registry::EnclaveRegistryLookup;
+use bc_musig2_ceremony::{CeremonyCommand, CeremonyId, Signers};
+use codec::Encode;
+use litentry_primitives::Identity;
+use log::debug;
+use std::{sync::Arc, vec::Vec};
+
+#[derive(Encode, Debug)]
+pub enum ThresholdBatchPartialSignatureShareError {
+	InvalidSigner,
+}
+
+pub fn handle<ER: EnclaveRegistryLookup>(
+	signer: Identity,
+	ceremony_id: &CeremonyId,
+	signing_set: Signers,
+	signatures: Vec<[u8; 32]>,
+	enclave_registry: Arc<ER>,
+) -> Result<CeremonyCommand, ThresholdBatchPartialSignatureShareError> {
+	debug!(
+		"Received threshold batch partial signature share from: {:?} for ceremony {:?}",
+		signer, ceremony_id
+	);
+	match signer {
+		Identity::Substrate(address) if enclave_registry.contains_key(&address) =>
+			Ok(CeremonyCommand::SaveThresholdBatchPartialSignatures(
+				*address.as_ref(),
+				signing_set,
+				signatures,
+			)),
+		_ => Err(ThresholdBatchPartialSignatureShareError::InvalidSigner),
+	}
+}
+
+#[cfg(test)]
+pub mod test {
+	use crate::handler::threshold_batch_partial_signature_share::{
+		handle, ThresholdBatchPartialSignatureShareError,
+	};
+	use alloc::sync::Arc;
+	use bc_enclave_registry::{EnclaveRegistry, EnclaveRegistryUpdater};
+	use bc_musig2_ceremony::{CeremonyCommand, SignBitcoinPayload};
+	use litentry_primitives::Identity;
+	use sp_core::{sr25519, Pair};
+
+	#[test]
+	pub fn it_should_return_ok_for_enclave_signer() {
+		// given
+		let alice_key_pair = sr25519::Pair::from_string("//Alice", None).unwrap();
+		let signer_account = Identity::Substrate(alice_key_pair.public().into());
+		let ceremony_id = SignBitcoinPayload::Batch(vec![], vec![]);
+		let enclave_registry = Arc::new(EnclaveRegistry::default());
+		let _ =
+			enclave_registry.update(alice_key_pair.public().into(), "localhost:2000".to_string());
+
+		// when
+		let result = handle(
+			signer_account,
+			&ceremony_id,
+			vec![[1u8; 32], [2u8; 32]],
+			vec![[1u8; 32], [2u8; 32]],
+			enclave_registry,
+		);
+
+		// then
+		assert!(matches!(
+			result,
+			Ok(CeremonyCommand::SaveThresholdBatchPartialSignatures(_, _, signatures))
+				if signatures.len() == 2
+		))
+	}
+
+	#[test]
+	pub fn it_should_return_err_for_non_enclave_signer() {
+		// given
+		let alice_key_pair = sr25519::Pair::from_string("//Alice", None).unwrap();
+		let signer_account = Identity::Substrate(alice_key_pair.public().into());
+		let ceremony_id = SignBitcoinPayload::Batch(vec![], vec![]);
+		let enclave_registry = Arc::new(EnclaveRegistry::default());
+
+		// when
+		let result = handle(
+			signer_account,
+			&ceremony_id,
+			vec![[1u8; 32], [2u8; 32]],
+			vec![[1u8; 32]],
+			enclave_registry,
+		);
+
+		// then
+		assert!(matches!(result, Err(ThresholdBatchPartialSignatureShareError::InvalidSigner)))
+	}
+}
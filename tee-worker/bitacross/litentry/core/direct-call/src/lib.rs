@@ -20,7 +20,10 @@ extern crate alloc;
 #[cfg(all(not(feature = "std"), feature = "sgx"))]
 extern crate sgx_tstd as std;
 
-use bc_musig2_ceremony::SignBitcoinPayload;
+use bc_musig2_ceremony::{
+	nonce_pool::SerializedPubNonce, KeyGenerationId, KeyGenerationPackage, NonceSlot,
+	SerializedSigners, SignBitcoinPayload, Signers, ThresholdPublicKey,
+};
 use codec::{Decode, Encode};
 use itp_stf_primitives::types::KeyPair;
 use litentry_primitives::{Identity, LitentryMultiSignature, ShardIdentifier};
@@ -53,6 +56,16 @@ pub enum DirectCall {
 	SignEthereum(Identity, PrehashedEthereumMessage),
 	SignTon(Identity, Vec<u8>),
 	CheckSignBitcoin(Identity),
+	// threshold of the key
+	GenerateThresholdKey(Identity, u16),
+	// serialized PSBT and the index of the input to sign
+	SignBitcoinPsbt(Identity, Vec<u8>, u32),
+	// unsigned EIP-1559 or EIP-155 legacy transaction, RLP encoded
+	SignEthereumTransaction(Identity, Vec<u8>),
+	// EIP-712 typed data as JSON
+	SignEthereumTypedData(Identity, Vec<u8>),
+	// serialized Solana transaction message, legacy or v0
+	SignSolana(Identity, Vec<u8>),
 }
 
 impl DirectCall {
@@ -62,6 +75,11 @@ impl DirectCall {
 			Self::SignEthereum(signer, ..) => signer,
 			Self::SignTon(signer, ..) => signer,
 			Self::CheckSignBitcoin(signer) => signer,
+			Self::GenerateThresholdKey(signer, ..) => signer,
+			Self::SignBitcoinPsbt(signer, ..) => signer,
+			Self::SignEthereumTransaction(signer, ..) => signer,
+			Self::SignEthereumTypedData(signer, ..) => signer,
+			Self::SignSolana(signer, ..) => signer,
 		}
 	}
 
@@ -103,6 +121,24 @@ pub enum CeremonyRoundCall {
 	NonceShare(Identity, SignBitcoinPayload, [u8; 66]),
 	PartialSignatureShare(Identity, SignBitcoinPayload, [u8; 32]),
 	KillCeremony(Identity, SignBitcoinPayload),
+	// partial signature together with the signing set it was made for
+	ThresholdPartialSignatureShare(Identity, SignBitcoinPayload, Signers, [u8; 32]),
+	KeyGenerationPackageShare(Identity, KeyGenerationId, u16, KeyGenerationPackage),
+	// package of a resharing together with the new signers and the key handed over to them
+	ResharingPackageShare(
+		Identity,
+		KeyGenerationId,
+		SerializedSigners,
+		ThresholdPublicKey,
+		KeyGenerationPackage,
+	),
+	// nonces and partial signatures of a batch ceremony, one per payload
+	BatchNonceShare(Identity, SignBitcoinPayload, Vec<[u8; 66]>),
+	BatchPartialSignatureShare(Identity, SignBitcoinPayload, Vec<[u8; 32]>),
+	// partial signatures of a threshold batch ceremony together with their signing set
+	ThresholdBatchPartialSignatureShare(Identity, SignBitcoinPayload, Signers, Vec<[u8; 32]>),
+	// unused nonces of the sender, for ceremonies to skip the first round
+	NoncePoolShare(Identity, Vec<(NonceSlot, SerializedPubNonce)>),
 }
 
 impl CeremonyRoundCall {
@@ -111,6 +147,13 @@ impl CeremonyRoundCall {
 			Self::NonceShare(signer, ..) => signer,
 			Self::PartialSignatureShare(signer, ..) => signer,
 			Self::KillCeremony(signer, ..) => signer,
+			Self::ThresholdPartialSignatureShare(signer, ..) => signer,
+			Self::KeyGenerationPackageShare(signer, ..) => signer,
+			Self::ResharingPackageShare(signer, ..) => signer,
+			Self::BatchNonceShare(signer, ..) => signer,
+			Self::BatchPartialSignatureShare(signer, ..) => signer,
+			Self::ThresholdBatchPartialSignatureShare(signer, ..) => signer,
+			Self::NoncePoolShare(signer, ..) => signer,
 		}
 	}
 
@@ -52,6 +52,9 @@ lazy_static! {
 	static ref MUSIG2_CEREMONIES_STARTED: Counter =
 		register_counter!("bitacross_worker_ceremonies_started", "Musig2 ceremonies started")
 			.unwrap();
+	static ref MUSIG2_CEREMONIES_SUCCEEDED: Counter =
+		register_counter!("bitacross_worker_ceremonies_succeeded", "Musig2 ceremonies succeeded")
+			.unwrap();
 	static ref MUSIG2_CEREMONIES_FAILED: Counter =
 		register_counter!("bitacross_worker_ceremonies_failed", "Musig2 ceremonies failed")
 			.unwrap();
@@ -59,7 +62,7 @@ lazy_static! {
 		register_counter!("bitacross_worker_ceremonies_timed_out", "Musig2 ceremonies timed out")
 			.unwrap();
 	static ref MUSIG2_CEREMONY_DURATION: Histogram =
-		register_histogram!("bitacross_worker_ceremony_duration", "Time taken to perform musig2 ceremony", vec![0.0005, 0.005, 0.01, 0.025, 0.05, 0.1])
+		register_histogram!("bitacross_worker_ceremony_duration", "Time taken to perform musig2 ceremony", vec![1.0, 2.0, 5.0, 10.0, 20.0, 30.0])
 			.unwrap();
 
 
@@ -143,6 +146,7 @@ impl ReceiveEnclaveMetrics for EnclaveMetricsReceiver {
 			EnclaveMetric::ParentchainBlockImportTime(time) =>
 				ENCLAVE_PARENTCHAIN_BLOCK_IMPORT_TIME.observe(time.as_secs_f64()),
 			EnclaveMetric::Musig2CeremonyStarted => MUSIG2_CEREMONIES_STARTED.inc(),
+			EnclaveMetric::Musig2CeremonySucceeded => MUSIG2_CEREMONIES_SUCCEEDED.inc(),
 			EnclaveMetric::Musig2CeremonyFailed => MUSIG2_CEREMONIES_FAILED.inc(),
 			EnclaveMetric::Musig2CeremonyTimedout(count) =>
 				for i in 0..count {
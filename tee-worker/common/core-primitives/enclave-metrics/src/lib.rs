@@ -56,4 +56,5 @@ pub enum EnclaveMetric {
 	Musig2CeremonyFailed,
 	Musig2CeremonyTimedout(u8),
 	Musig2CeremonyDuration(Duration),
+	Musig2CeremonySucceeded,
 }
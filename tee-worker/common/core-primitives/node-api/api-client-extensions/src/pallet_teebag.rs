@@ -16,7 +16,9 @@
 
 use crate::ApiResult;
 use itp_api_client_types::{traits::GetStorage, Api, Config, Request};
-use itp_types::{AccountId, Enclave, ShardIdentifier, WorkerType};
+use itp_types::{
+	AccountId, Enclave, MrEnclave, ShardIdentifier, SidechainBlockConfirmation, WorkerType,
+};
 
 pub const TEEBAG: &str = "Teebag";
 
@@ -51,6 +53,22 @@ pub trait PalletTeebagApi {
 		worker_type: WorkerType,
 		at_block: Option<Self::Hash>,
 	) -> ApiResult<Vec<Enclave>>;
+	fn authorized_enclaves(
+		&self,
+		worker_type: WorkerType,
+		at_block: Option<Self::Hash>,
+	) -> ApiResult<Vec<MrEnclave>>;
+	fn authorized_enclaves_for_shard(
+		&self,
+		worker_type: WorkerType,
+		shard: &ShardIdentifier,
+		at_block: Option<Self::Hash>,
+	) -> ApiResult<Vec<(AccountId, Enclave)>>;
+	fn latest_sidechain_block_confirmation(
+		&self,
+		shard: &ShardIdentifier,
+		at_block: Option<Self::Hash>,
+	) -> ApiResult<Option<SidechainBlockConfirmation>>;
 }
 
 impl<RuntimeConfig, Client> PalletTeebagApi for Api<RuntimeConfig, Client>
@@ -131,4 +149,52 @@ where
 			.collect();
 		Ok(enclaves)
 	}
+
+	fn authorized_enclaves(
+		&self,
+		worker_type: WorkerType,
+		at_block: Option<Self::Hash>,
+	) -> ApiResult<Vec<MrEnclave>> {
+		Ok(self
+			.get_storage_map(TEEBAG, "AuthorizedEnclave", worker_type, at_block)?
+			.unwrap_or_default())
+	}
+
+	// all registered enclaves running the mrenclave of the shard, if it is still authorized
+	fn authorized_enclaves_for_shard(
+		&self,
+		worker_type: WorkerType,
+		shard: &ShardIdentifier,
+		at_block: Option<Self::Hash>,
+	) -> ApiResult<Vec<(AccountId, Enclave)>> {
+		if !self
+			.authorized_enclaves(worker_type, at_block)?
+			.iter()
+			.any(|mrenclave| mrenclave == shard.as_ref())
+		{
+			return Ok(Vec::new())
+		}
+
+		let identifiers: Vec<AccountId> = self
+			.get_storage_map(TEEBAG, "EnclaveIdentifier", worker_type, at_block)?
+			.unwrap_or_default();
+
+		let mut enclaves = Vec::new();
+		for account in identifiers {
+			if let Some(e) = self.enclave(&account, at_block)? {
+				if e.mrenclave == shard.as_ref() {
+					enclaves.push((account, e));
+				}
+			}
+		}
+		Ok(enclaves)
+	}
+
+	fn latest_sidechain_block_confirmation(
+		&self,
+		shard: &ShardIdentifier,
+		at_block: Option<Self::Hash>,
+	) -> ApiResult<Option<SidechainBlockConfirmation>> {
+		self.get_storage_map(TEEBAG, "LatestSidechainBlockConfirmation", shard, at_block)
+	}
 }
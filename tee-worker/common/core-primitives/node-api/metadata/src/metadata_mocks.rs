@@ -50,6 +50,7 @@ pub struct NodeMetadataMock {
 	post_opaque_task: u8,
 	parentchain_block_processed: u8,
 	sidechain_block_imported: u8,
+	rotate_shielding_key: u8,
 	// IMP
 	imp_module: u8,
 	imp_link_identity: u8,
@@ -96,6 +97,10 @@ pub struct NodeMetadataMock {
 	btc_wallet_generated: u8,
 	eth_wallet_generated: u8,
 	ton_wallet_generated: u8,
+	threshold_key_generated: u8,
+	threshold_key_reshared: u8,
+	sol_wallet_generated: u8,
+	signature_audit_root_posted: u8,
 
 	omni_account_module: u8,
 	dispatch_as_omni_account: u8,
@@ -118,6 +123,7 @@ impl NodeMetadataMock {
 			post_opaque_task: 6u8,
 			parentchain_block_processed: 7u8,
 			sidechain_block_imported: 8u8,
+			rotate_shielding_key: 9u8,
 
 			imp_module: 64u8,
 			imp_link_identity: 1u8,
@@ -164,6 +170,10 @@ impl NodeMetadataMock {
 			btc_wallet_generated: 2u8,
 			eth_wallet_generated: 3u8,
 			ton_wallet_generated: 4u8,
+			threshold_key_generated: 5u8,
+			threshold_key_reshared: 6u8,
+			sol_wallet_generated: 7u8,
+			signature_audit_root_posted: 8u8,
 
 			omni_account_module: 70u8,
 			dispatch_as_omni_account: 0u8,
@@ -193,6 +203,9 @@ impl TeebagCallIndexes for NodeMetadataMock {
 	fn register_tcb_info_call_indexes(&self) -> Result<[u8; 2]> {
 		Ok([self.teebag_module, self.register_tcb_info])
 	}
+	fn rotate_shielding_key_call_indexes(&self) -> Result<[u8; 2]> {
+		Ok([self.teebag_module, self.rotate_shielding_key])
+	}
 	fn post_opaque_task_call_indexes(&self) -> Result<[u8; 2]> {
 		Ok([self.teebag_module, self.post_opaque_task])
 	}
@@ -342,6 +355,22 @@ impl BitAcrossCallIndexes for NodeMetadataMock {
 	fn ton_wallet_generated_indexes(&self) -> Result<[u8; 2]> {
 		Ok([self.bitacross_module, self.ton_wallet_generated])
 	}
+
+	fn sol_wallet_generated_indexes(&self) -> Result<[u8; 2]> {
+		Ok([self.bitacross_module, self.sol_wallet_generated])
+	}
+
+	fn threshold_key_generated_indexes(&self) -> Result<[u8; 2]> {
+		Ok([self.bitacross_module, self.threshold_key_generated])
+	}
+
+	fn threshold_key_reshared_indexes(&self) -> Result<[u8; 2]> {
+		Ok([self.bitacross_module, self.threshold_key_reshared])
+	}
+
+	fn signature_audit_root_posted_indexes(&self) -> Result<[u8; 2]> {
+		Ok([self.bitacross_module, self.signature_audit_root_posted])
+	}
 }
 
 impl TimestampCallIndexes for NodeMetadataMock {
@@ -25,6 +25,10 @@ pub trait BitAcrossCallIndexes {
 	fn btc_wallet_generated_indexes(&self) -> Result<[u8; 2]>;
 	fn eth_wallet_generated_indexes(&self) -> Result<[u8; 2]>;
 	fn ton_wallet_generated_indexes(&self) -> Result<[u8; 2]>;
+	fn sol_wallet_generated_indexes(&self) -> Result<[u8; 2]>;
+	fn threshold_key_generated_indexes(&self) -> Result<[u8; 2]>;
+	fn threshold_key_reshared_indexes(&self) -> Result<[u8; 2]>;
+	fn signature_audit_root_posted_indexes(&self) -> Result<[u8; 2]>;
 }
 
 impl BitAcrossCallIndexes for NodeMetadata {
@@ -47,4 +51,20 @@ impl BitAcrossCallIndexes for NodeMetadata {
 	fn ton_wallet_generated_indexes(&self) -> Result<[u8; 2]> {
 		self.call_indexes(BITACROSS, "ton_wallet_generated")
 	}
+
+	fn sol_wallet_generated_indexes(&self) -> Result<[u8; 2]> {
+		self.call_indexes(BITACROSS, "sol_wallet_generated")
+	}
+
+	fn threshold_key_generated_indexes(&self) -> Result<[u8; 2]> {
+		self.call_indexes(BITACROSS, "threshold_key_generated")
+	}
+
+	fn threshold_key_reshared_indexes(&self) -> Result<[u8; 2]> {
+		self.call_indexes(BITACROSS, "threshold_key_reshared")
+	}
+
+	fn signature_audit_root_posted_indexes(&self) -> Result<[u8; 2]> {
+		self.call_indexes(BITACROSS, "signature_audit_root_posted")
+	}
 }
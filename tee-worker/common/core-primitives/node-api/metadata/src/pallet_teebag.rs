@@ -33,6 +33,8 @@ pub trait TeebagCallIndexes {
 
 	fn register_tcb_info_call_indexes(&self) -> Result<[u8; 2]>;
 
+	fn rotate_shielding_key_call_indexes(&self) -> Result<[u8; 2]>;
+
 	fn post_opaque_task_call_indexes(&self) -> Result<[u8; 2]>;
 
 	fn parentchain_block_processed_call_indexes(&self) -> Result<[u8; 2]>;
@@ -59,6 +61,9 @@ impl TeebagCallIndexes for NodeMetadata {
 	fn register_tcb_info_call_indexes(&self) -> Result<[u8; 2]> {
 		self.call_indexes(TEEBAG, "register_tcb_info")
 	}
+	fn rotate_shielding_key_call_indexes(&self) -> Result<[u8; 2]> {
+		self.call_indexes(TEEBAG, "rotate_shielding_key")
+	}
 	fn post_opaque_task_call_indexes(&self) -> Result<[u8; 2]> {
 		self.call_indexes(TEEBAG, "post_opaque_task")
 	}
@@ -48,15 +48,28 @@ pub mod files {
 	// identity
 	pub const ASSERTIONS_FILE: &str = "assertions_sealed.bin";
 
+	/// Directory of the sealed pending trusted operations, one file per shard.
+	pub const TOP_POOL_PATH: &str = "top_pool";
+
 	pub const RA_DUMP_CERT_DER_FILE: &str = "ra_dump_cert.der";
 
 	// bitacross
 	pub const RELAYER_REGISTRY_FILE: &str = "relayer_registry_sealed.bin";
 
+	/// Directory of the sealed daily request counters of relayers, one file per relayer.
+	pub const RELAYER_USAGE_PATH: &str = "relayer_usage";
+
 	pub const ENCLAVE_REGISTRY_FILE: &str = "enclave_registry_sealed.bin";
 
 	pub const SIGNER_REGISTRY_FILE: &str = "signer_registry_sealed.bin";
 
+	pub const BTC_SIGNING_POLICY_FILE: &str = "btc_signing_policy_sealed.bin";
+
+	pub const SIGNATURE_AUDIT_LOG_FILE: &str = "signature_audit_log_sealed.bin";
+
+	/// Directory of the sealed entries of the signature audit log, one file per segment.
+	pub const SIGNATURE_AUDIT_LOG_SEGMENTS_PATH: &str = "signature_audit_log";
+
 	// used by worker and enclave
 	pub const SHARDS_PATH: &str = "shards";
 
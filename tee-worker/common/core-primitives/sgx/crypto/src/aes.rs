@@ -118,6 +118,20 @@ pub mod sgx {
 		Ok(KeyRepository::new(aes_key, aes_seal.into()))
 	}
 
+	impl Aes {
+		/// Generate a fresh random key and initialization vector.
+		pub fn random() -> Result<Self> {
+			let mut key = [0u8; 16];
+			let mut iv = [0u8; 16];
+			let mut rand = StdRng::new()?;
+
+			rand.fill_bytes(&mut key);
+			rand.fill_bytes(&mut iv);
+
+			Ok(Aes::new(key, iv))
+		}
+	}
+
 	impl AesSealing for AesSeal {
 		fn unseal_key(&self) -> Result<Aes> {
 			self.unseal()
@@ -136,14 +150,7 @@ pub mod sgx {
 		}
 
 		fn create_sealed(&self) -> Result<()> {
-			let mut key = [0u8; 16];
-			let mut iv = [0u8; 16];
-			let mut rand = StdRng::new()?;
-
-			rand.fill_bytes(&mut key);
-			rand.fill_bytes(&mut iv);
-
-			Ok(self.seal(&Aes::new(key, iv))?)
+			Ok(self.seal(&Aes::random()?)?)
 		}
 	}
 
@@ -23,16 +23,28 @@ use std::sync::RwLock;
 
 use crate::{
 	error::{Error, Result},
-	ToPubkey,
+	ShieldingCryptoDecrypt, ToPubkey,
 };
 use itp_sgx_io::SealedIO;
-use std::sync::Arc;
+use itp_time_utils::now_as_millis;
+use std::{sync::Arc, vec::Vec};
 
 /// Access a cryptographic key.
 pub trait AccessKey {
 	type KeyType;
 
 	fn retrieve_key(&self) -> Result<Self::KeyType>;
+
+	/// Retrieve the key which was replaced by the latest rotation, as long as its
+	/// grace period hasn't ended.
+	fn retrieve_previous_key(&self) -> Result<Option<Self::KeyType>> {
+		Ok(self.retrieve_previous_key_with_expiry()?.map(|(key, _)| key))
+	}
+
+	/// Same as `retrieve_previous_key`, together with the end of the grace period.
+	fn retrieve_previous_key_with_expiry(&self) -> Result<Option<(Self::KeyType, u64)>> {
+		Ok(None)
+	}
 }
 
 /// Access a cryptographic public key.
@@ -47,16 +59,46 @@ pub trait MutateKey<KeyType> {
 	fn update_key(&self, key: KeyType) -> Result<()>;
 }
 
+/// Rotate a cryptographic key.
+///
+/// In contrast to `MutateKey`, the replaced key remains accessible through
+/// `AccessKey::retrieve_previous_key` until `expires_at` (unix epoch in milliseconds).
+pub trait RotateKey<KeyType> {
+	fn rotate_key(&self, key: KeyType, expires_at: u64) -> Result<()>;
+
+	/// Replace the previous key, e.g. with the one provisioned by a peer.
+	fn update_previous_key(&self, key: KeyType, expires_at: u64) -> Result<()>;
+}
+
+/// Sealing of the key replaced by the latest rotation together with the end of its grace period.
+pub trait SealedRotatedKeyIO {
+	type Unsealed;
+
+	fn unseal_previous(&self) -> Result<Option<(Self::Unsealed, u64)>>;
+
+	fn seal_previous(&self, unsealed: &Self::Unsealed, expires_at: u64) -> Result<()>;
+}
+
 /// Repository implementation. Stores a cryptographic key in-memory and in a file backed.
 /// Uses the SealedIO trait for the file backend.
 pub struct KeyRepository<KeyType, SealedIo> {
 	key_lock: RwLock<KeyType>,
+	// the key replaced by the latest rotation and the end of its grace period
+	previous_key_lock: RwLock<Option<(KeyType, u64)>>,
 	sealed_io: Arc<SealedIo>,
 }
 
 impl<KeyType, SealedIo> KeyRepository<KeyType, SealedIo> {
 	pub fn new(key: KeyType, sealed_io: Arc<SealedIo>) -> Self {
-		KeyRepository { key_lock: RwLock::new(key), sealed_io }
+		KeyRepository {
+			key_lock: RwLock::new(key),
+			previous_key_lock: RwLock::new(None),
+			sealed_io,
+		}
+	}
+
+	pub fn with_previous_key(self, previous_key: Option<(KeyType, u64)>) -> Self {
+		KeyRepository { previous_key_lock: RwLock::new(previous_key), ..self }
 	}
 }
 
@@ -70,6 +112,14 @@ where
 	fn retrieve_key(&self) -> Result<Self::KeyType> {
 		self.key_lock.read().map_err(|_| Error::LockPoisoning).map(|l| l.clone())
 	}
+
+	fn retrieve_previous_key_with_expiry(&self) -> Result<Option<(Self::KeyType, u64)>> {
+		let previous_key_lock = self.previous_key_lock.read().map_err(|_| Error::LockPoisoning)?;
+		Ok(previous_key_lock
+			.as_ref()
+			.filter(|(_, expires_at)| now_as_millis() < *expires_at)
+			.cloned())
+	}
 }
 
 impl<Pair, SealedIo> AccessPubkey for KeyRepository<Pair, SealedIo>
@@ -99,6 +149,67 @@ where
 	}
 }
 
+impl<KeyType, SealedIo> RotateKey<KeyType> for KeyRepository<KeyType, SealedIo>
+where
+	KeyType: Clone,
+	SealedIo: SealedIO<Unsealed = KeyType, Error = crate::error::Error>
+		+ SealedRotatedKeyIO<Unsealed = KeyType>,
+{
+	fn rotate_key(&self, key: KeyType, expires_at: u64) -> Result<()> {
+		let mut key_lock = self.key_lock.write().map_err(|_| Error::LockPoisoning)?;
+		let mut previous_key_lock =
+			self.previous_key_lock.write().map_err(|_| Error::LockPoisoning)?;
+
+		// seal the current key first, so that it isn't lost if sealing the new key fails
+		self.sealed_io.seal_previous(&key_lock, expires_at)?;
+		self.sealed_io.seal(&key)?;
+		let new_key = self.sealed_io.unseal()?;
+
+		*previous_key_lock = Some((core::mem::replace(&mut *key_lock, new_key), expires_at));
+
+		Ok(())
+	}
+
+	fn update_previous_key(&self, key: KeyType, expires_at: u64) -> Result<()> {
+		let mut previous_key_lock =
+			self.previous_key_lock.write().map_err(|_| Error::LockPoisoning)?;
+
+		self.sealed_io.seal_previous(&key, expires_at)?;
+		*previous_key_lock = self.sealed_io.unseal_previous()?;
+
+		Ok(())
+	}
+}
+
+/// Shielding key that falls back to the key replaced by the latest rotation if the
+/// decryption fails, so that requests encrypted right before a rotation still go through.
+pub struct RotatedShieldingKey<KeyType> {
+	current: KeyType,
+	previous: Option<KeyType>,
+}
+
+impl<KeyType> RotatedShieldingKey<KeyType> {
+	pub fn retrieve<Repository: AccessKey<KeyType = KeyType>>(
+		repository: &Repository,
+	) -> Result<Self> {
+		Ok(Self {
+			current: repository.retrieve_key()?,
+			previous: repository.retrieve_previous_key()?,
+		})
+	}
+}
+
+impl<KeyType: ShieldingCryptoDecrypt> ShieldingCryptoDecrypt for RotatedShieldingKey<KeyType> {
+	type Error = KeyType::Error;
+
+	fn decrypt(&self, data: &[u8]) -> core::result::Result<Vec<u8>, Self::Error> {
+		self.current.decrypt(data).or_else(|e| match self.previous {
+			Some(ref previous) => previous.decrypt(data).map_err(|_| e),
+			None => Err(e),
+		})
+	}
+}
+
 #[cfg(test)]
 mod tests {
 	use super::*;
@@ -119,4 +230,22 @@ mod tests {
 		assert_eq!(updated_key, key_repository.retrieve_key().unwrap());
 		assert_eq!(updated_key, seal_mock.unseal().unwrap());
 	}
+
+	#[test]
+	fn rotate_key_keeps_previous_key_until_expired() {
+		let seal_mock = Arc::new(AesSealMock::default());
+		let key_repository = TestKeyRepository::new(seal_mock.unseal().unwrap(), seal_mock.clone());
+		let initial_key = key_repository.retrieve_key().unwrap();
+		let rotated_key = Aes::new([3u8; 16], [1u8; 16]);
+
+		key_repository.rotate_key(rotated_key, now_as_millis() + 60_000).unwrap();
+
+		assert_eq!(rotated_key, key_repository.retrieve_key().unwrap());
+		assert_eq!(Some(initial_key), key_repository.retrieve_previous_key().unwrap());
+		assert_eq!(initial_key, seal_mock.unseal_previous().unwrap().unwrap().0);
+
+		key_repository.rotate_key(Aes::new([4u8; 16], [1u8; 16]), 0).unwrap();
+
+		assert_eq!(None, key_repository.retrieve_previous_key().unwrap());
+	}
 }
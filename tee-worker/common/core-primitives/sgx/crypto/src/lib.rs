@@ -58,7 +58,8 @@ pub mod tests {
 	};
 
 	pub use super::rsa3072::sgx_tests::{
-		rsa3072_sealing_works, using_get_rsa3072_repository_twice_initializes_key_only_once,
+		rotated_rsa3072_key_is_kept_as_previous_key_after_restart, rsa3072_sealing_works,
+		using_get_rsa3072_repository_twice_initializes_key_only_once,
 	};
 
 	pub use super::aes::sgx_tests::{
@@ -24,7 +24,7 @@ use std::sync::RwLock;
 use crate::{
 	aes::Aes,
 	error::{Error, Result},
-	key_repository::{AccessKey, MutateKey},
+	key_repository::{AccessKey, MutateKey, RotateKey, SealedRotatedKeyIO},
 };
 use itp_sgx_io::{SealedIO, StaticSealedIO};
 use sgx_crypto_helper::rsa3072::Rsa3072KeyPair;
@@ -35,6 +35,7 @@ where
 	KeyType: Clone,
 {
 	key: RwLock<KeyType>,
+	previous_key: RwLock<Option<(KeyType, u64)>>,
 }
 
 impl<KeyType> KeyRepositoryMock<KeyType>
@@ -42,7 +43,7 @@ where
 	KeyType: Clone,
 {
 	pub fn new(key: KeyType) -> Self {
-		KeyRepositoryMock { key: RwLock::new(key) }
+		KeyRepositoryMock { key: RwLock::new(key), previous_key: RwLock::new(None) }
 	}
 }
 
@@ -55,6 +56,10 @@ where
 	fn retrieve_key(&self) -> Result<Self::KeyType> {
 		Ok(self.key.read().unwrap().clone())
 	}
+
+	fn retrieve_previous_key_with_expiry(&self) -> Result<Option<(Self::KeyType, u64)>> {
+		Ok(self.previous_key.read().unwrap().clone())
+	}
 }
 
 impl<KeyType> MutateKey<KeyType> for KeyRepositoryMock<KeyType>
@@ -68,9 +73,27 @@ where
 	}
 }
 
+impl<KeyType> RotateKey<KeyType> for KeyRepositoryMock<KeyType>
+where
+	KeyType: Clone,
+{
+	fn rotate_key(&self, key: KeyType, expires_at: u64) -> Result<()> {
+		let mut lock = self.key.write().unwrap();
+		let previous_key = core::mem::replace(&mut *lock, key);
+		*self.previous_key.write().unwrap() = Some((previous_key, expires_at));
+		Ok(())
+	}
+
+	fn update_previous_key(&self, key: KeyType, expires_at: u64) -> Result<()> {
+		*self.previous_key.write().unwrap() = Some((key, expires_at));
+		Ok(())
+	}
+}
+
 #[derive(Default)]
 pub struct AesSealMock {
 	aes: RwLock<Aes>,
+	previous_aes: RwLock<Option<(Aes, u64)>>,
 }
 
 impl StaticSealedIO for AesSealMock {
@@ -101,6 +124,24 @@ impl SealedIO for AesSealMock {
 	}
 }
 
+impl SealedRotatedKeyIO for AesSealMock {
+	type Unsealed = Aes;
+
+	fn unseal_previous(&self) -> Result<Option<(Self::Unsealed, u64)>> {
+		self.previous_aes
+			.read()
+			.map_err(|e| Error::Other(format!("{:?}", e).into()))
+			.map(|k| *k)
+	}
+
+	fn seal_previous(&self, unsealed: &Self::Unsealed, expires_at: u64) -> Result<()> {
+		let mut previous_aes_lock =
+			self.previous_aes.write().map_err(|e| Error::Other(format!("{:?}", e).into()))?;
+		*previous_aes_lock = Some((*unsealed, expires_at));
+		Ok(())
+	}
+}
+
 #[derive(Default)]
 pub struct Rsa3072SealMock {}
 
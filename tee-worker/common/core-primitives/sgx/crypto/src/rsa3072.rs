@@ -35,6 +35,9 @@ pub use sgx::*;
 /// File name of the sealed RSA key file.
 pub const RSA3072_SEALED_KEY_FILE: &str = "rsa3072_key_sealed.bin";
 
+/// File name of the sealed RSA key replaced by the latest key rotation.
+pub const RSA3072_SEALED_PREVIOUS_KEY_FILE: &str = "rsa3072_previous_key_sealed.bin";
+
 impl ShieldingCryptoEncrypt for Rsa3072KeyPair {
 	type Error = Error;
 
@@ -92,7 +95,7 @@ pub trait RsaSealing {
 #[cfg(feature = "sgx")]
 pub mod sgx {
 	use super::*;
-	use crate::key_repository::KeyRepository;
+	use crate::key_repository::{KeyRepository, SealedRotatedKeyIO};
 	use itp_sgx_io::{seal, unseal, SealedIO};
 	use log::*;
 	use std::path::PathBuf;
@@ -105,7 +108,9 @@ pub mod sgx {
 		let rsa_seal = Rsa3072Seal::new(path);
 		rsa_seal.create_sealed_if_absent()?;
 		let shielding_key = rsa_seal.unseal_pair()?;
-		Ok(KeyRepository::new(shielding_key, rsa_seal.into()))
+		let previous_shielding_key = rsa_seal.unseal_previous()?;
+		Ok(KeyRepository::new(shielding_key, rsa_seal.into())
+			.with_previous_key(previous_shielding_key))
 	}
 
 	#[derive(Clone, Debug)]
@@ -121,6 +126,10 @@ pub mod sgx {
 		pub fn path(&self) -> PathBuf {
 			self.base_path.join(RSA3072_SEALED_KEY_FILE)
 		}
+
+		pub fn previous_path(&self) -> PathBuf {
+			self.base_path.join(RSA3072_SEALED_PREVIOUS_KEY_FILE)
+		}
 	}
 
 	impl RsaSealing for Rsa3072Seal {
@@ -169,14 +178,41 @@ pub mod sgx {
 			Ok(seal(&key_json, self.path())?)
 		}
 	}
+
+	impl SealedRotatedKeyIO for Rsa3072Seal {
+		type Unsealed = Rsa3072KeyPair;
+
+		fn unseal_previous(&self) -> Result<Option<(Self::Unsealed, u64)>> {
+			if !self.previous_path().exists() {
+				return Ok(None)
+			}
+			let raw = unseal(self.previous_path())?;
+			let previous: (Rsa3072KeyPair, u64) = serde_json::from_slice(&raw)
+				.map_err(|e| Error::Other(format!("{:?}", e).into()))?;
+			Ok(Some(previous))
+		}
+
+		fn seal_previous(&self, unsealed: &Self::Unsealed, expires_at: u64) -> Result<()> {
+			let previous_json = serde_json::to_vec(&(unsealed, expires_at))
+				.map_err(|e| Error::Other(format!("{:?}", e).into()))?;
+			Ok(seal(&previous_json, self.previous_path())?)
+		}
+	}
 }
 
 #[cfg(feature = "test")]
 pub mod sgx_tests {
 	use super::{serde_json, sgx::*};
-	use crate::{key_repository::AccessKey, RsaSealing, ToPubkey};
+	use crate::{
+		key_repository::{AccessKey, RotateKey},
+		RsaSealing, ShieldingCryptoDecrypt, ShieldingCryptoEncrypt, ToPubkey,
+	};
 	use itp_sgx_temp_dir::TempDir;
-	use sgx_crypto_helper::rsa3072::Rsa3072PubKey;
+	use itp_time_utils::now_as_millis;
+	use sgx_crypto_helper::{
+		rsa3072::{Rsa3072KeyPair, Rsa3072PubKey},
+		RsaKeyPair,
+	};
 
 	/// Helper method because Rsa3072 does not implement `Eq`.
 	pub fn equal(pubkey1: &Rsa3072PubKey, pubkey2: &Rsa3072PubKey) -> bool {
@@ -218,4 +254,28 @@ pub mod sgx_tests {
 
 		assert!(!equal(&pair_different.pubkey().unwrap(), &pair.pubkey().unwrap()));
 	}
+
+	pub fn rotated_rsa3072_key_is_kept_as_previous_key_after_restart() {
+		let temp_dir =
+			TempDir::with_prefix("rotated_rsa3072_key_is_kept_as_previous_key_after_restart")
+				.unwrap();
+		let temp_path = temp_dir.path().to_path_buf();
+		let repository = get_rsa3072_repository(temp_path.clone()).unwrap();
+		let initial_key = repository.retrieve_key().unwrap();
+		let cipher = initial_key.encrypt(b"hello").unwrap();
+
+		repository
+			.rotate_key(Rsa3072KeyPair::new().unwrap(), now_as_millis() + 60_000)
+			.unwrap();
+
+		// a restarted enclave still knows the replaced key
+		let repository = get_rsa3072_repository(temp_path).unwrap();
+		let previous_key = repository.retrieve_previous_key().unwrap().unwrap();
+		assert!(!equal(
+			&repository.retrieve_key().unwrap().pubkey().unwrap(),
+			&initial_key.pubkey().unwrap()
+		));
+		assert!(equal(&previous_key.pubkey().unwrap(), &initial_key.pubkey().unwrap()));
+		assert_eq!(previous_key.decrypt(&cipher).unwrap(), b"hello".to_vec());
+	}
 }
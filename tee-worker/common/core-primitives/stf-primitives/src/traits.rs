@@ -14,7 +14,7 @@
 	limitations under the License.
 
 */
-use crate::types::{AccountId, KeyPair, ShardIdentifier};
+use crate::types::{AccountId, KeyPair, PriorityClass, ShardIdentifier};
 use alloc::vec::Vec;
 use codec::{Decode, Encode};
 use core::fmt::Debug;
@@ -48,6 +48,12 @@ pub trait TrustedCallVerification {
 
 	// Litentry: extend the trait for metric statistic purpose
 	fn metric_name(&self) -> &'static str;
+
+	/// Class that decides the priority of the call in the top pool and whether it
+	/// is subject to admission control.
+	fn priority_class(&self) -> PriorityClass {
+		PriorityClass::User
+	}
 }
 
 /// validation for top pool
@@ -23,7 +23,7 @@ use litentry_primitives::LitentryMultiSignature;
 use sp_core::{blake2_256, crypto::AccountId32, ed25519, sr25519, Pair, H256};
 use sp_runtime::{
 	traits::Verify,
-	transaction_validity::{TransactionValidityError, ValidTransaction},
+	transaction_validity::{TransactionPriority, TransactionValidityError, ValidTransaction},
 	MultiSignature,
 };
 use sp_std::{vec, vec::Vec};
@@ -69,6 +69,35 @@ impl From<sr25519::Pair> for KeyPair {
 	}
 }
 
+/// Priority classes of trusted calls, ordered from the lowest to the highest priority.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub enum PriorityClass {
+	/// Requests that can cause a lot of work, e.g. VC issuance.
+	Bulk,
+	/// Regular calls of users.
+	User,
+	/// Callbacks which complete a request that is already in progress.
+	Callback,
+	/// Calls created by the enclave itself.
+	Internal,
+}
+
+impl PriorityClass {
+	pub fn priority(&self) -> TransactionPriority {
+		match self {
+			PriorityClass::Bulk => 1 << 19,
+			PriorityClass::User => 1 << 20,
+			PriorityClass::Callback => 1 << 21,
+			PriorityClass::Internal => 1 << 22,
+		}
+	}
+
+	/// Whether calls of this class are subject to the per-sender admission control.
+	pub fn is_rate_limited(&self) -> bool {
+		matches!(self, PriorityClass::Bulk | PriorityClass::User)
+	}
+}
+
 #[derive(Encode, Decode, Clone, Debug, PartialEq, Eq)]
 #[allow(non_camel_case_types)]
 pub enum TrustedOperation<TCS, G>
@@ -120,7 +149,13 @@ where
 		let requires = vec![];
 		let provides = vec![(from, trusted_call_signed.nonce()).encode()];
 
-		ValidTransaction { priority: 1 << 20, requires, provides, longevity: 64, propagate: true }
+		ValidTransaction {
+			priority: trusted_call_signed.priority_class().priority(),
+			requires,
+			provides,
+			longevity: 64,
+			propagate: true,
+		}
 	}
 
 	pub fn hash(&self) -> H256 {
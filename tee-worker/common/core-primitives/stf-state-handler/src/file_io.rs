@@ -44,6 +44,13 @@ use std::{
 /// It is also the suffix of all past snapshots.
 pub const ENCRYPTED_STATE_FILE: &str = "state.bin";
 
+/// Suffix of the state snapshots re-encrypted with a new state key, which
+/// are not yet moved over the original snapshots.
+pub const ROTATED_STATE_FILE_SUFFIX: &str = "rotated";
+
+/// File name of the sealed marker of a pending state key rotation.
+pub const STATE_KEY_ROTATION_MARKER_FILE: &str = "state_key_rotation_sealed.bin";
+
 /// Helps with file system operations of all files relevant for the State.
 #[derive(Debug, Clone, Eq, PartialEq)]
 pub struct StateDir {
@@ -98,6 +105,18 @@ impl StateDir {
 		self.shard_path(shard).join(to_file_name(state_id))
 	}
 
+	pub fn rotated_state_file_path(&self, shard: &ShardIdentifier, state_id: StateId) -> PathBuf {
+		self.shard_path(shard).join(format!(
+			"{}.{}",
+			to_file_name(state_id),
+			ROTATED_STATE_FILE_SUFFIX
+		))
+	}
+
+	pub fn state_key_rotation_marker_path(&self) -> PathBuf {
+		self.base_path.join(STATE_KEY_ROTATION_MARKER_FILE)
+	}
+
 	pub fn file_for_state_exists(&self, shard: &ShardIdentifier, state_id: StateId) -> bool {
 		self.state_file_path(shard, state_id).exists()
 	}
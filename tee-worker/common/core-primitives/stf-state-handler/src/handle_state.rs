@@ -75,4 +75,12 @@ pub trait HandleState {
 
 	/// Force migrate state to new shard
 	fn migrate_shard(&self, new_shard: ShardIdentifier) -> Result<Self::HashType>;
+
+	/// Execute a function while no state snapshots are written.
+	///
+	/// Writing a state blocks until the function has returned, e.g. to re-encrypt
+	/// the state snapshots with a new state key.
+	fn execute_without_snapshot_writes<E, R>(&self, executing_function: E) -> Result<R>
+	where
+		E: FnOnce() -> R;
 }
@@ -27,7 +27,7 @@ use crate::{
 	state_snapshot_primitives::StateId,
 };
 use codec::Encode;
-use itp_sgx_externalities::{SgxExternalities, SgxExternalitiesType};
+use itp_sgx_externalities::{SgxExternalities, SgxExternalitiesTrait, SgxExternalitiesType};
 use itp_types::{ShardIdentifier, H256};
 use sp_core::blake2_256;
 use std::{boxed::Box, collections::HashMap, sync::Arc, vec::Vec};
@@ -231,7 +231,7 @@ fn sgx_externalities_selector() -> InnerStateSelector<SgxExternalitiesType, SgxE
 }
 
 fn sgx_externalities_wrapper() -> ExternalStateGenerator<SgxExternalitiesType, SgxExternalities> {
-	Box::new(|s| SgxExternalities { state: s, state_diff: Default::default() })
+	Box::new(SgxExternalities::new)
 }
 
 #[cfg(feature = "sgx")]
@@ -38,6 +38,8 @@ pub mod in_memory_state_file_io;
 pub mod query_shard_state;
 pub mod state_handler;
 pub mod state_initializer;
+#[cfg(feature = "sgx")]
+pub mod state_key_rotation;
 mod state_snapshot_primitives;
 pub mod state_snapshot_repository;
 pub mod state_snapshot_repository_loader;
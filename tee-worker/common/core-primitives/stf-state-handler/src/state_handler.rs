@@ -260,6 +260,15 @@ where
 
 		Ok(new_shard_state_hash)
 	}
+
+	fn execute_without_snapshot_writes<E, R>(&self, executing_function: E) -> Result<R>
+	where
+		E: FnOnce() -> R,
+	{
+		let _state_snapshots_lock =
+			self.state_snapshot_repository.write().map_err(|_| Error::LockPoisoning)?;
+		Ok(executing_function())
+	}
 }
 
 impl<Repository, StateObserver, StateInitializer> QueryShardState
@@ -0,0 +1,122 @@
+// Copyright 2020-2024 Trust Computing GmbH.
+// This file is part of Litentry.
+//
+// Litentry is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// Litentry is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with Litentry.  If not, see <https://www.gnu.org/licenses/>.
+
+//! Rotation of the key the state snapshots are encrypted with.
+//!
+//! The rotation is crash-safe: all snapshots are first re-encrypted next to the
+//! originals, then a sealed marker with the fingerprint of the new key is written
+//! and the new key is sealed. Sealing the new key is the commit point, the
+//! re-encrypted snapshots are moved over the originals afterwards.
+//! `finish_state_key_rotation` completes or reverts an interrupted rotation and must
+//! be called before the state snapshots are loaded.
+//!
+//! Must not run concurrently with state mutations.
+
+use crate::{
+	error::{Error, Result},
+	file_io::StateDir,
+};
+use codec::Encode;
+use itp_sgx_crypto::{
+	key_repository::{AccessKey, MutateKey},
+	StateCrypto,
+};
+use itp_sgx_io::{read as io_read, seal, unseal, write as io_write};
+use log::*;
+use sp_core::blake2_256;
+use std::{format, fs, vec::Vec};
+
+/// Re-encrypt all state snapshots in `state_dir` with `new_key` and make it the
+/// state key of `key_repository`.
+pub fn rotate_state_key<KeyRepository>(
+	state_dir: &StateDir,
+	key_repository: &KeyRepository,
+	new_key: KeyRepository::KeyType,
+) -> Result<()>
+where
+	KeyRepository: AccessKey + MutateKey<<KeyRepository as AccessKey>::KeyType>,
+	<KeyRepository as AccessKey>::KeyType: StateCrypto + Encode,
+{
+	// leftovers of an interrupted rotation must not be mixed with this one
+	finish_state_key_rotation(state_dir, key_repository)?;
+
+	let current_key = key_repository.retrieve_key()?;
+
+	for shard in state_dir.list_shards()? {
+		for state_id in state_dir.list_state_ids_for_shard(&shard)? {
+			let mut state = io_read(state_dir.state_file_path(&shard, state_id))?;
+
+			// empty files are stored unencrypted, see `SgxStateFileIo`
+			if !state.is_empty() {
+				current_key
+					.decrypt(&mut state)
+					.map_err(|e| Error::Other(format!("{:?}", e).into()))?;
+				new_key
+					.encrypt(&mut state)
+					.map_err(|e| Error::Other(format!("{:?}", e).into()))?;
+			}
+
+			io_write(&state, state_dir.rotated_state_file_path(&shard, state_id))?;
+		}
+	}
+
+	seal(&key_fingerprint(&new_key), state_dir.state_key_rotation_marker_path())?;
+	key_repository.update_key(new_key)?;
+	info!("Sealed new state key, replacing the state snapshots");
+
+	finish_state_key_rotation(state_dir, key_repository)
+}
+
+/// Complete an interrupted state key rotation if the new key has been sealed,
+/// otherwise discard the re-encrypted state snapshots.
+pub fn finish_state_key_rotation<KeyRepository>(
+	state_dir: &StateDir,
+	key_repository: &KeyRepository,
+) -> Result<()>
+where
+	KeyRepository: AccessKey,
+	<KeyRepository as AccessKey>::KeyType: Encode,
+{
+	let marker_path = state_dir.state_key_rotation_marker_path();
+	if !marker_path.exists() {
+		return Ok(())
+	}
+
+	let is_committed = unseal(&marker_path)? == key_fingerprint(&key_repository.retrieve_key()?);
+	if !is_committed {
+		warn!("Found interrupted state key rotation, discarding re-encrypted state snapshots");
+	}
+
+	for shard in state_dir.list_shards()? {
+		for state_id in state_dir.list_state_ids_for_shard(&shard)? {
+			let rotated_path = state_dir.rotated_state_file_path(&shard, state_id);
+			if !rotated_path.exists() {
+				continue
+			}
+			if is_committed {
+				fs::rename(&rotated_path, state_dir.state_file_path(&shard, state_id))?;
+			} else {
+				fs::remove_file(&rotated_path)?;
+			}
+		}
+	}
+
+	Ok(fs::remove_file(&marker_path)?)
+}
+
+fn key_fingerprint<Key: Encode>(key: &Key) -> Vec<u8> {
+	blake2_256(&key.encode()).to_vec()
+}
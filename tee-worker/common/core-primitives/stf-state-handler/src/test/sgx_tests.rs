@@ -21,6 +21,7 @@ use crate::{
 	in_memory_state_file_io::sgx::create_in_memory_state_io_from_shards_directories,
 	query_shard_state::QueryShardState,
 	state_handler::StateHandler,
+	state_key_rotation::{finish_state_key_rotation, rotate_state_key},
 	state_snapshot_repository::{StateSnapshotRepository, VersionedStateAccess},
 	state_snapshot_repository_loader::StateSnapshotRepositoryLoader,
 	test::mocks::initialize_state_mock::InitializeStateMock,
@@ -33,7 +34,7 @@ use itp_sgx_crypto::{
 	Aes, AesSeal, StateCrypto,
 };
 use itp_sgx_externalities::{SgxExternalities, SgxExternalitiesTrait, SgxExternalitiesType};
-use itp_sgx_io::write;
+use itp_sgx_io::{seal, write};
 use itp_sgx_temp_dir::TempDir;
 use itp_stf_state_observer::state_observer::StateObserver;
 use itp_types::{ShardIdentifier, H256};
@@ -309,6 +310,48 @@ pub fn test_in_memory_state_initializes_from_shard_directory() {
 	assert!(state_snapshot_repository.shard_exists(&shard));
 }
 
+pub fn test_rotate_state_key_re_encrypts_state_snapshots() {
+	// given
+	let shard: ShardIdentifier = [67u8; 32].into();
+	let (_temp_dir, state_key_access, state_dir) =
+		test_setup("test_rotate_state_key_re_encrypts_state_snapshots", &shard);
+	let file_io = TestStateFileIo::new(state_key_access.clone(), state_dir.clone());
+	let state = given_hello_world_state();
+	file_io.initialize_shard(&shard, 1234, &state).unwrap();
+	let new_key = Aes::new([3u8; 16], [5u8; 16]);
+
+	// when
+	rotate_state_key(&state_dir, state_key_access.as_ref(), new_key).unwrap();
+
+	// then
+	assert_eq!(new_key, state_key_access.retrieve_key().unwrap());
+	assert_eq!(state.state, file_io.load(&shard, 1234).unwrap().state);
+	assert!(!state_dir.rotated_state_file_path(&shard, 1234).exists());
+	assert!(!state_dir.state_key_rotation_marker_path().exists());
+}
+
+pub fn test_interrupted_state_key_rotation_is_reverted_if_new_key_was_not_sealed() {
+	// given
+	let shard: ShardIdentifier = [68u8; 32].into();
+	let (_temp_dir, state_key_access, state_dir) = test_setup(
+		"test_interrupted_state_key_rotation_is_reverted_if_new_key_was_not_sealed",
+		&shard,
+	);
+	let file_io = TestStateFileIo::new(state_key_access.clone(), state_dir.clone());
+	let state = given_hello_world_state();
+	file_io.initialize_shard(&shard, 1234, &state).unwrap();
+	write(&[0, 1, 2, 3], state_dir.rotated_state_file_path(&shard, 1234)).unwrap();
+	seal(&[0u8; 32], state_dir.state_key_rotation_marker_path()).unwrap();
+
+	// when
+	finish_state_key_rotation(&state_dir, state_key_access.as_ref()).unwrap();
+
+	// then
+	assert_eq!(state.state, file_io.load(&shard, 1234).unwrap().state);
+	assert!(!state_dir.rotated_state_file_path(&shard, 1234).exists());
+	assert!(!state_dir.state_key_rotation_marker_path().exists());
+}
+
 fn initialize_state_handler(
 	state_key_access: Arc<StateKeyRepository>,
 	state_dir: StateDir,
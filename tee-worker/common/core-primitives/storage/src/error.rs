@@ -13,6 +13,10 @@ pub enum Error {
 	StorageRootMismatch,
 	#[error("Storage value unavailable")]
 	StorageValueUnavailable,
+	#[error("Invalid sidechain state proof")]
+	InvalidStateProof,
+	#[error("Sidechain header is not linked to the confirmed sidechain block")]
+	UnconfirmedStateRoot,
 	#[error(transparent)]
 	#[cfg(feature = "std")]
 	Codec(#[from] codec::Error),
@@ -39,5 +43,9 @@ pub enum Error {
 	/// InvalidStorageProof,
 	StorageRootMismatch,
 	StorageValueUnavailable,
+	/// Malformed sidechain state proof
+	InvalidStateProof,
+	/// Sidechain header is not linked to the confirmed sidechain block
+	UnconfirmedStateRoot,
 	Codec(codec::Error),
 }
@@ -29,9 +29,11 @@ pub use error::Error;
 pub use frame_metadata::v14::StorageHasher;
 pub use keys::*;
 pub use proof::*;
+pub use sidechain_state_proof::*;
 pub use verify_storage_proof::*;
 
 pub mod error;
 pub mod keys;
 pub mod proof;
+pub mod sidechain_state_proof;
 pub mod verify_storage_proof;
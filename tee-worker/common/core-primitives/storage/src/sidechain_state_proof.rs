@@ -0,0 +1,683 @@
+// Copyright 2020-2024 Trust Computing GmbH.
+// This file is part of Litentry.
+//
+// Litentry is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// Litentry is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with Litentry.  If not, see <https://www.gnu.org/licenses/>.
+
+//! Merkle proofs over the sidechain state.
+//!
+//! The sidechain state is a flat key-value map ordered by key. It is committed to by a binary
+//! Merkle tree whose leaves are `blake2_256((key, blake2_256(value)))` in key order. A node
+//! without a sibling at the end of a level is promoted to the next level unchanged, the root of
+//! an empty state is `H256::zero()`.
+//!
+//! Proving values only reveals the requested leaves. As the leaves are ordered by key, revealing
+//! a contiguous run of leaves together with its direct neighbours additionally proves that no
+//! other key is present in that range, see [`verify_state_absence`] and [`verify_state_prefix`].
+
+use crate::error::Error;
+use codec::{Decode, Encode};
+use core::cmp::Ordering;
+use frame_support::ensure;
+use sp_core::{hashing::blake2_256, H256};
+use sp_std::{vec, vec::Vec};
+
+/// A leaf revealed by a [`StateProof`].
+#[derive(Clone, Debug, PartialEq, Eq, Encode, Decode)]
+pub struct StateProofLeaf {
+	/// Position of the leaf in the ordered state.
+	pub index: u32,
+	pub key: Vec<u8>,
+	/// `blake2_256` hash of the value stored under `key`.
+	pub value_hash: H256,
+}
+
+/// Proof for a contiguous run of leaves of the sidechain state tree.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Encode, Decode)]
+pub struct StateProof {
+	/// Number of entries in the proven state.
+	pub leaf_count: u32,
+	/// Revealed leaves, ordered by index.
+	pub leaves: Vec<StateProofLeaf>,
+	/// Hashes of the unrevealed subtrees, in the order they are needed to compute the root.
+	pub nodes: Vec<H256>,
+}
+
+/// Merkle tree over the sidechain state.
+#[derive(Clone, Debug)]
+pub struct StateMerkleTree {
+	leaves: Vec<(Vec<u8>, H256)>,
+	// `levels[0]` holds the leaf hashes, the last level holds the root.
+	levels: Vec<Vec<H256>>,
+}
+
+impl StateMerkleTree {
+	/// Build the tree from state entries, which must be ordered by key.
+	pub fn new<K: AsRef<[u8]>, V: AsRef<[u8]>>(entries: impl IntoIterator<Item = (K, V)>) -> Self {
+		let leaves: Vec<(Vec<u8>, H256)> = entries
+			.into_iter()
+			.map(|(k, v)| (k.as_ref().to_vec(), blake2_256(v.as_ref()).into()))
+			.collect();
+
+		let leaf_hashes = leaves.iter().map(|(k, h)| state_leaf_hash(k, h)).collect();
+
+		StateMerkleTree { leaves, levels: build_levels(leaf_hashes) }
+	}
+
+	pub fn root(&self) -> H256 {
+		self.levels.last().and_then(|l| l.first()).copied().unwrap_or_default()
+	}
+
+	/// Apply a state diff to the tree, a `None` value removes the entry.
+	///
+	/// Only the changed values are hashed. Updating a value recomputes the path from its leaf to
+	/// the root. Inserting or removing entries shifts the positions of the following leaves, so
+	/// the inner nodes are then rebuilt from the cached leaf hashes.
+	pub fn apply_diff<K: AsRef<[u8]>, V: AsRef<[u8]>>(
+		&mut self,
+		diff: impl IntoIterator<Item = (K, Option<V>)>,
+	) {
+		let mut changed = Vec::new();
+		let mut reshaped = false;
+
+		for (key, value) in diff {
+			let key = key.as_ref();
+			let position = self.leaves.binary_search_by(|(k, _)| k.as_slice().cmp(key));
+			let value_hash = value.map(|v| H256::from(blake2_256(v.as_ref())));
+			let leaf_hashes = match self.levels.first_mut() {
+				Some(leaf_hashes) => leaf_hashes,
+				None => return,
+			};
+
+			match (position, value_hash) {
+				(Ok(i), Some(value_hash)) =>
+					if let (Some(leaf), Some(leaf_hash)) =
+						(self.leaves.get_mut(i), leaf_hashes.get_mut(i))
+					{
+						leaf.1 = value_hash;
+						*leaf_hash = state_leaf_hash(key, &value_hash);
+						changed.push(i);
+					},
+				(Err(i), Some(value_hash)) => {
+					self.leaves.insert(i, (key.to_vec(), value_hash));
+					leaf_hashes.insert(i, state_leaf_hash(key, &value_hash));
+					reshaped = true;
+				},
+				(Ok(i), None) => {
+					self.leaves.remove(i);
+					leaf_hashes.remove(i);
+					reshaped = true;
+				},
+				(Err(_), None) => {},
+			}
+		}
+
+		if reshaped {
+			let leaf_hashes = self.levels.drain(..).next().unwrap_or_default();
+			self.levels = build_levels(leaf_hashes);
+		} else {
+			self.update_paths(changed);
+		}
+	}
+
+	/// Recompute the inner nodes above the given leaves.
+	fn update_paths(&mut self, mut dirty: Vec<usize>) {
+		dirty.sort_unstable();
+		dirty.dedup();
+
+		for level in 1..self.levels.len() {
+			dirty = dirty.iter().map(|i| i / 2).collect();
+			dirty.dedup();
+
+			let (lower, upper) = self.levels.split_at_mut(level);
+			if let (Some(children), Some(nodes)) = (lower.last(), upper.first_mut()) {
+				for i in dirty.iter() {
+					if let (Some(pair), Some(node)) =
+						(children.chunks(2).nth(*i), nodes.get_mut(*i))
+					{
+						*node = parent_hash(pair);
+					}
+				}
+			}
+		}
+	}
+
+	/// Prove the values stored under `keys`.
+	///
+	/// Only the requested leaves are revealed. Returns `None` if any of the keys is absent.
+	pub fn prove_values<K: AsRef<[u8]>>(&self, keys: &[K]) -> Option<StateProof> {
+		let mut indices = keys
+			.iter()
+			.map(|key| self.leaves.binary_search_by(|(k, _)| k.as_slice().cmp(key.as_ref())).ok())
+			.collect::<Option<Vec<usize>>>()?;
+		indices.sort_unstable();
+		indices.dedup();
+
+		Some(self.proof_for(indices))
+	}
+
+	/// Prove the result of reading `keys`, whether they are present or not.
+	///
+	/// Reveals the leaf of a present key and the neighbouring leaves of an absent one.
+	pub fn prove_reads<K: AsRef<[u8]>>(&self, keys: &[K]) -> StateProof {
+		let mut indices = Vec::new();
+		for key in keys {
+			match self.leaves.binary_search_by(|(k, _)| k.as_slice().cmp(key.as_ref())) {
+				Ok(i) => indices.push(i),
+				Err(i) => {
+					indices.extend(i.checked_sub(1));
+					indices.extend(Some(i).filter(|i| *i < self.leaves.len()));
+				},
+			}
+		}
+		indices.sort_unstable();
+		indices.dedup();
+
+		self.proof_for(indices)
+	}
+
+	/// Prove that no value is stored under `key`.
+	///
+	/// Reveals the keys of the neighbouring leaves.
+	pub fn prove_absence(&self, key: &[u8]) -> StateProof {
+		self.prove_range(|k| k.cmp(key))
+	}
+
+	/// Prove the set of all entries whose key starts with `prefix`.
+	pub fn prove_prefix(&self, prefix: &[u8]) -> StateProof {
+		self.prove_range(|k| prefix_ordering(k, prefix))
+	}
+
+	fn prove_range(&self, ordering: impl Fn(&[u8]) -> Ordering) -> StateProof {
+		let last = match self.leaves.len().checked_sub(1) {
+			Some(last) => last,
+			None => return StateProof::default(),
+		};
+		let start = self.leaves.partition_point(|(k, _)| ordering(k.as_slice()) == Ordering::Less);
+		let end = self
+			.leaves
+			.partition_point(|(k, _)| ordering(k.as_slice()) != Ordering::Greater);
+
+		// include the direct neighbours of the range to prove that nothing is left out
+		self.proof_for((start.saturating_sub(1)..=end.min(last)).collect())
+	}
+
+	fn proof_for(&self, indices: Vec<usize>) -> StateProof {
+		let leaves = indices
+			.iter()
+			.filter_map(|i| self.leaves.get(*i).map(|leaf| (*i, leaf)))
+			.map(|(i, (key, value_hash))| StateProofLeaf {
+				index: i as u32,
+				key: key.clone(),
+				value_hash: *value_hash,
+			})
+			.collect();
+
+		StateProof {
+			leaf_count: self.leaves.len() as u32,
+			leaves,
+			nodes: self.proof_nodes(indices),
+		}
+	}
+
+	fn proof_nodes(&self, mut known: Vec<usize>) -> Vec<H256> {
+		let mut nodes = Vec::new();
+		for level in self.levels.iter() {
+			for i in known.iter() {
+				let sibling = i ^ 1;
+				if known.binary_search(&sibling).is_err() {
+					if let Some(node) = level.get(sibling) {
+						nodes.push(*node);
+					}
+				}
+			}
+			known = known.iter().map(|i| i / 2).collect();
+			known.dedup();
+		}
+		nodes
+	}
+}
+
+/// Verify that `entries` are stored in the state with the given `root`.
+pub fn verify_state_values(
+	root: H256,
+	entries: &[(Vec<u8>, Vec<u8>)],
+	proof: &StateProof,
+) -> Result<(), Error> {
+	ensure!(compute_root(proof)? == root, Error::StorageRootMismatch);
+
+	for (key, value) in entries {
+		let leaf = proof.leaves.iter().find(|l| &l.key == key).ok_or(Error::WrongValue)?;
+		ensure!(leaf.value_hash == H256::from(blake2_256(value)), Error::WrongValue);
+	}
+	Ok(())
+}
+
+/// Verify the result of reading keys from the state with the given `root`.
+///
+/// A `None` value states that the key is absent, which is proven by its neighbouring leaves.
+pub fn verify_state_reads(
+	root: H256,
+	reads: &[(Vec<u8>, Option<Vec<u8>>)],
+	proof: &StateProof,
+) -> Result<(), Error> {
+	ensure!(compute_root(proof)? == root, Error::StorageRootMismatch);
+
+	// the root commits to the position of every leaf, so the revealed leaves are ordered by key
+	for (key, value) in reads {
+		match (proof.leaves.binary_search_by(|l| l.key.as_slice().cmp(key)), value) {
+			(Ok(i), Some(value)) => {
+				let leaf = proof.leaves.get(i).ok_or(Error::WrongValue)?;
+				ensure!(leaf.value_hash == H256::from(blake2_256(value)), Error::WrongValue);
+			},
+			(Err(i), None) => {
+				let prev = i.checked_sub(1).and_then(|p| proof.leaves.get(p));
+				let next = proof.leaves.get(i);
+				let adjacent = match (prev, next) {
+					(Some(prev), Some(next)) => next.index == prev.index.saturating_add(1),
+					(Some(prev), None) => prev.index.saturating_add(1) == proof.leaf_count,
+					(None, Some(next)) => next.index == 0,
+					(None, None) => proof.leaf_count == 0,
+				};
+				ensure!(adjacent, Error::InvalidStateProof);
+			},
+			_ => return Err(Error::WrongValue),
+		}
+	}
+	Ok(())
+}
+
+/// Verify that no value is stored under `key` in the state with the given `root`.
+pub fn verify_state_absence(root: H256, key: &[u8], proof: &StateProof) -> Result<(), Error> {
+	let matched = verify_range(root, proof, |k| k.cmp(key))?;
+	ensure!(matched.is_empty(), Error::WrongValue);
+	Ok(())
+}
+
+/// Verify that `entries` are exactly the entries whose key starts with `prefix` in the state
+/// with the given `root`.
+///
+/// `entries` must be ordered by key.
+pub fn verify_state_prefix(
+	root: H256,
+	prefix: &[u8],
+	entries: &[(Vec<u8>, Vec<u8>)],
+	proof: &StateProof,
+) -> Result<(), Error> {
+	let matched = verify_range(root, proof, |k| prefix_ordering(k, prefix))?;
+
+	ensure!(matched.len() == entries.len(), Error::WrongValue);
+	for (leaf, (key, value)) in matched.iter().zip(entries) {
+		ensure!(&leaf.key == key, Error::WrongValue);
+		ensure!(leaf.value_hash == H256::from(blake2_256(value)), Error::WrongValue);
+	}
+	Ok(())
+}
+
+fn build_levels(leaf_hashes: Vec<H256>) -> Vec<Vec<H256>> {
+	let mut levels = vec![leaf_hashes];
+	while let Some(level) = levels.last().filter(|l| l.len() > 1) {
+		let next = level.chunks(2).map(parent_hash).collect();
+		levels.push(next);
+	}
+	levels
+}
+
+fn state_leaf_hash(key: &[u8], value_hash: &H256) -> H256 {
+	(key, value_hash).using_encoded(blake2_256).into()
+}
+
+fn parent_hash(children: &[H256]) -> H256 {
+	match children {
+		[left, right] => (left, right).using_encoded(blake2_256).into(),
+		[single] => *single,
+		_ => H256::zero(),
+	}
+}
+
+fn prefix_ordering(key: &[u8], prefix: &[u8]) -> Ordering {
+	if key.starts_with(prefix) {
+		Ordering::Equal
+	} else {
+		key.cmp(prefix)
+	}
+}
+
+/// Check the proof against `root` and return the revealed leaves that fall into the range
+/// described by `ordering`.
+fn verify_range(
+	root: H256,
+	proof: &StateProof,
+	ordering: impl Fn(&[u8]) -> Ordering,
+) -> Result<Vec<&StateProofLeaf>, Error> {
+	ensure!(compute_root(proof)? == root, Error::StorageRootMismatch);
+
+	for (prev, next) in proof.leaves.iter().zip(proof.leaves.iter().skip(1)) {
+		ensure!(next.index == prev.index.saturating_add(1), Error::InvalidStateProof);
+		ensure!(next.key > prev.key, Error::InvalidStateProof);
+	}
+
+	if let (Some(first), Some(last)) = (proof.leaves.first(), proof.leaves.last()) {
+		ensure!(
+			first.index == 0 || ordering(first.key.as_slice()) == Ordering::Less,
+			Error::InvalidStateProof
+		);
+		ensure!(
+			last.index.saturating_add(1) == proof.leaf_count
+				|| ordering(last.key.as_slice()) == Ordering::Greater,
+			Error::InvalidStateProof
+		);
+	}
+
+	Ok(proof
+		.leaves
+		.iter()
+		.filter(|l| ordering(l.key.as_slice()) == Ordering::Equal)
+		.collect())
+}
+
+fn compute_root(proof: &StateProof) -> Result<H256, Error> {
+	if proof.leaf_count == 0 {
+		ensure!(proof.leaves.is_empty() && proof.nodes.is_empty(), Error::InvalidStateProof);
+		return Ok(H256::zero())
+	}
+
+	let mut known: Vec<(usize, H256)> = proof
+		.leaves
+		.iter()
+		.map(|l| (l.index as usize, state_leaf_hash(&l.key, &l.value_hash)))
+		.collect();
+	ensure!(!known.is_empty(), Error::InvalidStateProof);
+	for (prev, next) in known.iter().zip(known.iter().skip(1)) {
+		ensure!(next.0 > prev.0, Error::InvalidStateProof);
+	}
+	ensure!(
+		known.last().map(|(i, _)| *i < proof.leaf_count as usize).unwrap_or_default(),
+		Error::InvalidStateProof
+	);
+
+	let mut nodes = proof.nodes.iter();
+	let mut level_len = proof.leaf_count as usize;
+	while level_len > 1 {
+		let mut next = Vec::with_capacity(known.len());
+		let mut iter = known.into_iter().peekable();
+		while let Some((i, hash)) = iter.next() {
+			let sibling = i ^ 1;
+			let parent = if sibling >= level_len {
+				hash
+			} else {
+				let sibling_hash = match iter.peek() {
+					Some((j, h)) if *j == sibling => {
+						let h = *h;
+						iter.next();
+						h
+					},
+					_ => *nodes.next().ok_or(Error::InvalidStateProof)?,
+				};
+				if i % 2 == 0 {
+					parent_hash(&[hash, sibling_hash])
+				} else {
+					parent_hash(&[sibling_hash, hash])
+				}
+			};
+			next.push((i / 2, parent));
+		}
+		known = next;
+		level_len = (level_len + 1) / 2;
+	}
+	ensure!(nodes.next().is_none(), Error::InvalidStateProof);
+
+	known.first().map(|(_, root)| *root).ok_or(Error::InvalidStateProof)
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	fn entries(count: u8) -> Vec<(Vec<u8>, Vec<u8>)> {
+		(0..count).map(|i| (vec![i / 4, i], vec![i; 3])).collect()
+	}
+
+	#[test]
+	fn value_proof_verifies_for_every_key() {
+		for count in 1..12u8 {
+			// given
+			let entries = entries(count);
+			let tree = StateMerkleTree::new(entries.clone());
+
+			for entry in entries.iter() {
+				// when
+				let proof = tree.prove_values(&[&entry.0]).unwrap();
+
+				// then
+				assert_eq!(proof.leaves.len(), 1);
+				assert_eq!(verify_state_values(tree.root(), &[entry.clone()], &proof), Ok(()));
+				assert_eq!(
+					verify_state_values(tree.root(), &[(entry.0.clone(), vec![42])], &proof),
+					Err(Error::WrongValue)
+				);
+			}
+		}
+	}
+
+	#[test]
+	fn multi_value_proof_verifies() {
+		// given
+		let entries = entries(13);
+		let tree = StateMerkleTree::new(entries.clone());
+		let proven = vec![entries[2].clone(), entries[7].clone(), entries[12].clone()];
+
+		// when
+		let proof = tree.prove_values(&[&entries[12].0, &entries[2].0, &entries[7].0]).unwrap();
+
+		// then
+		assert_eq!(verify_state_values(tree.root(), &proven, &proof), Ok(()));
+		assert_eq!(
+			verify_state_values(tree.root(), &entries[3..4], &proof),
+			Err(Error::WrongValue)
+		);
+	}
+
+	#[test]
+	fn value_proof_for_absent_key_is_not_created() {
+		let tree = StateMerkleTree::new(entries(5));
+
+		assert!(tree.prove_values(&[vec![7u8]]).is_none());
+	}
+
+	#[test]
+	fn absence_proof_verifies() {
+		// given
+		let tree = StateMerkleTree::new(entries(9));
+
+		for key in [vec![0u8], vec![1, 4, 0], vec![9]] {
+			// when
+			let proof = tree.prove_absence(&key);
+
+			// then
+			assert_eq!(verify_state_absence(tree.root(), &key, &proof), Ok(()));
+		}
+	}
+
+	#[test]
+	fn absence_proof_for_present_key_is_rejected() {
+		// given
+		let tree = StateMerkleTree::new(entries(9));
+		let (key, _) = entries(9).remove(4);
+
+		// when
+		let proof = tree.prove_absence(&key);
+
+		// then
+		assert_eq!(verify_state_absence(tree.root(), &key, &proof), Err(Error::WrongValue));
+	}
+
+	#[test]
+	fn prefix_proof_verifies_all_entries() {
+		// given
+		let entries = entries(11);
+		let tree = StateMerkleTree::new(entries.clone());
+		let expected: Vec<_> = entries.iter().filter(|(k, _)| k[0] == 1).cloned().collect();
+
+		// when
+		let proof = tree.prove_prefix(&[1]);
+
+		// then
+		assert_eq!(verify_state_prefix(tree.root(), &[1], &expected, &proof), Ok(()));
+		assert_eq!(
+			verify_state_prefix(tree.root(), &[1], &expected[1..], &proof),
+			Err(Error::WrongValue)
+		);
+	}
+
+	#[test]
+	fn proof_without_neighbours_is_rejected() {
+		// given
+		let entries = entries(8);
+		let tree = StateMerkleTree::new(entries.clone());
+		let mut proof = tree.prove_prefix(&[1]);
+
+		// when
+		proof.leaves.remove(0);
+		proof.nodes = tree.proof_nodes((4..8).collect());
+
+		// then
+		assert_eq!(
+			verify_state_prefix(tree.root(), &[1], &entries[4..8], &proof),
+			Err(Error::InvalidStateProof)
+		);
+	}
+
+	#[test]
+	fn proof_against_other_root_is_rejected() {
+		// given
+		let tree = StateMerkleTree::new(entries(5));
+		let entry = entries(5).remove(2);
+		let proof = tree.prove_values(&[&entry.0]).unwrap();
+
+		// then
+		assert_eq!(
+			verify_state_values(H256::random(), &[entry], &proof),
+			Err(Error::StorageRootMismatch)
+		);
+	}
+
+	#[test]
+	fn read_proof_verifies_present_and_absent_keys() {
+		// given
+		let entries = entries(11);
+		let tree = StateMerkleTree::new(entries.clone());
+		let reads = vec![
+			(vec![0u8], None),
+			(entries[2].0.clone(), Some(entries[2].1.clone())),
+			(vec![1u8, 5, 0], None),
+			(entries[10].0.clone(), Some(entries[10].1.clone())),
+			(vec![9u8], None),
+		];
+		let keys: Vec<_> = reads.iter().map(|(k, _)| k.clone()).collect();
+
+		// when
+		let proof = tree.prove_reads(&keys);
+
+		// then
+		assert_eq!(verify_state_reads(tree.root(), &reads, &proof), Ok(()));
+	}
+
+	#[test]
+	fn read_proof_claiming_present_key_absent_is_rejected() {
+		// given
+		let entries = entries(11);
+		let tree = StateMerkleTree::new(entries.clone());
+		let proof = tree.prove_reads(&[vec![1u8, 5, 0]]);
+
+		// then
+		assert_eq!(
+			verify_state_reads(tree.root(), &[(entries[4].0.clone(), None)], &proof),
+			Err(Error::InvalidStateProof)
+		);
+		assert_eq!(
+			verify_state_reads(tree.root(), &[(vec![1u8, 5, 0], Some(vec![5u8]))], &proof),
+			Err(Error::WrongValue)
+		);
+	}
+
+	#[test]
+	fn read_proof_on_empty_state_verifies() {
+		let tree = StateMerkleTree::new(Vec::<(Vec<u8>, Vec<u8>)>::new());
+
+		assert_eq!(
+			verify_state_reads(H256::zero(), &[(vec![1u8], None)], &tree.prove_reads(&[[1u8]])),
+			Ok(())
+		);
+	}
+
+	#[test]
+	fn applying_value_updates_matches_rebuilt_tree() {
+		// given
+		let mut entries = entries(13);
+		let mut tree = StateMerkleTree::new(entries.clone());
+		entries[3].1 = vec![42];
+		entries[12].1 = vec![43];
+
+		// when
+		tree.apply_diff(vec![
+			(entries[3].0.clone(), Some(vec![42u8])),
+			(entries[12].0.clone(), Some(vec![43u8])),
+		]);
+
+		// then
+		assert_eq!(tree.root(), StateMerkleTree::new(entries).root());
+	}
+
+	#[test]
+	fn applying_inserts_and_removals_matches_rebuilt_tree() {
+		// given
+		let mut entries = entries(10);
+		let mut tree = StateMerkleTree::new(entries.clone());
+		let removed = entries.remove(4);
+		entries.insert(0, (vec![0u8], vec![7u8]));
+		entries.push((vec![9u8], vec![8u8]));
+
+		// when
+		tree.apply_diff(vec![
+			(removed.0, None),
+			(vec![0u8], Some(vec![7u8])),
+			(vec![9u8], Some(vec![8u8])),
+			(vec![5u8], None),
+		]);
+
+		// then
+		assert_eq!(tree.root(), StateMerkleTree::new(entries).root());
+	}
+
+	#[test]
+	fn applying_diff_to_empty_tree_matches_rebuilt_tree() {
+		// given
+		let entries = entries(3);
+		let mut tree = StateMerkleTree::new(Vec::<(Vec<u8>, Vec<u8>)>::new());
+
+		// when
+		tree.apply_diff(entries.iter().map(|(k, v)| (k.clone(), Some(v.clone()))));
+
+		// then
+		assert_eq!(tree.root(), StateMerkleTree::new(entries).root());
+	}
+
+	#[test]
+	fn empty_state_has_zero_root() {
+		let tree = StateMerkleTree::new(Vec::<(Vec<u8>, Vec<u8>)>::new());
+
+		assert_eq!(tree.root(), H256::zero());
+		assert_eq!(verify_state_absence(H256::zero(), &[1], &tree.prove_absence(&[1])), Ok(()));
+	}
+}
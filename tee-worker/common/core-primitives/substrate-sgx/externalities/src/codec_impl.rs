@@ -97,6 +97,7 @@ mod tests {
 		let externalities = SgxExternalities {
 			state: create_default_state(),
 			state_diff: create_default_state_diff(),
+			..Default::default()
 		};
 
 		ensure_serialize_roundtrip_succeeds(externalities);
@@ -27,9 +27,14 @@ use derive_more::{Deref, DerefMut, From, IntoIterator};
 use itp_hashing::Hash;
 use serde::{Deserialize, Serialize};
 use sp_core::{hashing::blake2_256, H256};
-use std::{collections::BTreeMap, fmt::Debug, vec, vec::Vec};
+use std::{
+	collections::{BTreeMap, BTreeSet},
+	fmt::Debug,
+	vec,
+	vec::Vec,
+};
 
-pub use scope_limited::{set_and_run_with_externalities, with_externalities};
+pub use scope_limited::{record_reads, set_and_run_with_externalities, with_externalities};
 
 // Unfortunately we cannot use `serde_with::serde_as` to serialize our map (which would be very convenient)
 // because it has pulls in the serde and serde_json dependency with `std`, not `default-features=no`.
@@ -63,10 +68,20 @@ pub struct SgxExternalitiesType(#[serde(with = "vectorize")] InternalMap<Vec<u8>
 )]
 pub struct SgxExternalitiesDiffType(#[serde(with = "vectorize")] InternalMap<Option<Vec<u8>>>);
 
-#[derive(Clone, Debug, Default, PartialEq, Eq, Encode, Decode, Serialize, Deserialize)]
+#[derive(Clone, Debug, Default, Eq, Encode, Decode, Serialize, Deserialize)]
 pub struct SgxExternalities {
 	pub state: SgxExternalitiesType,
 	pub state_diff: SgxExternalitiesDiffType,
+	/// Keys changed since tracking was (re)started, kept in memory only.
+	#[codec(skip)]
+	#[serde(skip)]
+	changed_keys: Option<BTreeSet<Vec<u8>>>,
+}
+
+impl PartialEq for SgxExternalities {
+	fn eq(&self, other: &Self) -> bool {
+		self.state == other.state && self.state_diff == other.state_diff
+	}
 }
 
 pub trait StateHash {
@@ -122,6 +137,15 @@ pub trait SgxExternalitiesTrait {
 	/// Prunes the state diff.
 	fn prune_state_diff(&mut self);
 
+	/// Keys changed since [`Self::take_changed_keys`] was last called.
+	///
+	/// Unlike the state diff, they survive pruning. `None` if they are not known, i.e. if
+	/// tracking was never started on this state, e.g. because it was just decoded.
+	fn changed_keys(&self) -> Option<&BTreeSet<Vec<u8>>>;
+
+	/// Return the keys changed so far and restart tracking them.
+	fn take_changed_keys(&mut self) -> Option<BTreeSet<Vec<u8>>>;
+
 	/// Execute the given closure while `self` is set as externalities.
 	///
 	/// Returns the result of the given closure.
@@ -137,7 +161,7 @@ where
 	type SgxExternalitiesDiffType = SgxExternalitiesDiffType;
 
 	fn new(state: Self::SgxExternalitiesType) -> Self {
-		Self { state, state_diff: Default::default() }
+		Self { state, state_diff: Default::default(), changed_keys: None }
 	}
 
 	fn state(&self) -> &Self::SgxExternalitiesType {
@@ -149,26 +173,31 @@ where
 	}
 
 	fn insert(&mut self, key: Vec<u8>, value: Vec<u8>) -> Option<Vec<u8>> {
+		self.note_change(&key);
 		self.state_diff.insert(key.clone(), Some(value.clone()));
 		self.state.insert(key, value)
 	}
 
 	fn append(&mut self, key: Vec<u8>, value: Vec<u8>) {
+		self.note_change(&key);
 		let current = self.state.entry(key.clone()).or_default();
 		let updated_value = StorageAppend::new(current).append(value);
 		self.state_diff.insert(key, Some(updated_value));
 	}
 
 	fn remove(&mut self, key: &[u8]) -> Option<Vec<u8>> {
+		self.note_change(key);
 		self.state_diff.insert(key.to_vec(), None);
 		self.state.remove(key)
 	}
 
 	fn get(&self, key: &[u8]) -> Option<&Vec<u8>> {
+		scope_limited::note_read(key);
 		self.state.get(key)
 	}
 
 	fn contains_key(&self, key: &[u8]) -> bool {
+		scope_limited::note_read(key);
 		self.state.contains_key(key)
 	}
 
@@ -181,6 +210,14 @@ where
 		self.state_diff.clear();
 	}
 
+	fn changed_keys(&self) -> Option<&BTreeSet<Vec<u8>>> {
+		self.changed_keys.as_ref()
+	}
+
+	fn take_changed_keys(&mut self) -> Option<BTreeSet<Vec<u8>>> {
+		self.changed_keys.replace(BTreeSet::new())
+	}
+
 	// Note: This implementation only works for keys encoded with Blake2_128Concat
 	fn iter_prefix<K: Decode + Debug, V: Decode + Debug>(
 		&self,
@@ -238,6 +275,14 @@ where
 	}
 }
 
+impl SgxExternalities {
+	fn note_change(&mut self, key: &[u8]) {
+		if let Some(keys) = self.changed_keys.as_mut() {
+			keys.insert(key.to_vec());
+		}
+	}
+}
+
 /// Results concerning an operation to remove many keys.
 #[derive(codec::Encode, codec::Decode)]
 #[must_use]
@@ -393,6 +438,47 @@ pub mod tests {
 		assert_eq!(ext.get(&world), None);
 	}
 
+	#[test]
+	fn changed_keys_are_tracked_once_started() {
+		let mut ext = SgxExternalities::default();
+		ext.insert(b"untracked".to_vec(), vec![1]);
+		assert_eq!(ext.take_changed_keys(), None);
+
+		ext.insert(b"hello".to_vec(), vec![1]);
+		ext.remove(b"untracked");
+		ext.prune_state_diff();
+
+		assert_eq!(
+			ext.take_changed_keys().unwrap().into_iter().collect::<Vec<_>>(),
+			vec![b"hello".to_vec(), b"untracked".to_vec()]
+		);
+		assert_eq!(ext.changed_keys().map(|k| k.len()), Some(0));
+		assert_eq!(SgxExternalities::new(ext.state.clone()).changed_keys(), None);
+	}
+
+	#[test]
+	fn reads_are_recorded_within_scope() {
+		let mut ext = SgxExternalities::default();
+		ext.insert(b"hello".to_vec(), b"world".to_vec());
+
+		let (value, keys) = record_reads(|| {
+			ext.execute_with(|| {
+				with_externalities(|e| {
+					e.contains_key(b"absent");
+					e.get(b"hello").cloned()
+				})
+				.unwrap()
+			})
+		});
+
+		assert_eq!(value, Some(b"world".to_vec()));
+		assert_eq!(
+			keys.into_iter().collect::<Vec<_>>(),
+			vec![b"absent".to_vec(), b"hello".to_vec()]
+		);
+		assert_eq!(record_reads(|| ()).1.len(), 0);
+	}
+
 	#[test]
 	fn clear_prefix_works() {
 		let mut externalities = SgxExternalities::default();
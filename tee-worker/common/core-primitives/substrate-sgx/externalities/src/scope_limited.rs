@@ -18,8 +18,10 @@
 //! Stores the externalities in an `environmental` value to make it scope limited available.
 
 use crate::SgxExternalities;
+use std::{collections::BTreeSet, vec::Vec};
 
 environmental::environmental!(ext: SgxExternalities);
+environmental::environmental!(reads: BTreeSet<Vec<u8>>);
 
 /// Set the given externalities while executing the given closure. To get access to the
 /// externalities while executing the given closure [`with_externalities`] grants access to them.
@@ -36,3 +38,16 @@ pub fn set_and_run_with_externalities<F: FnOnce() -> R, R>(ext: &mut SgxExternal
 pub fn with_externalities<F: FnOnce(&mut SgxExternalities) -> R, R>(f: F) -> Option<R> {
 	ext::with(f)
 }
+
+/// Execute the given closure and return the keys it read from the externalities.
+///
+/// Only point reads are recorded, keys visited while iterating the state are not.
+pub fn record_reads<F: FnOnce() -> R, R>(f: F) -> (R, BTreeSet<Vec<u8>>) {
+	let mut keys = BTreeSet::new();
+	let result = reads::using(&mut keys, f);
+	(result, keys)
+}
+
+pub(crate) fn note_read(key: &[u8]) {
+	reads::with(|keys| keys.insert(key.to_vec()));
+}
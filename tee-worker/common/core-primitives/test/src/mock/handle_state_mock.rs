@@ -110,6 +110,14 @@ impl HandleState for HandleStateMock {
 		let write_lock = self.state_map.write().unwrap();
 		self.write_after_mutation(state, write_lock, shard)
 	}
+
+	fn execute_without_snapshot_writes<E, R>(&self, executing_function: E) -> Result<R>
+	where
+		E: FnOnce() -> R,
+	{
+		let _write_lock = self.state_map.write().unwrap();
+		Ok(executing_function())
+	}
 }
 
 impl QueryShardState for HandleStateMock {
@@ -0,0 +1,42 @@
+// Copyright 2020-2024 Trust Computing GmbH.
+// This file is part of Litentry.
+//
+// Litentry is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// Litentry is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with Litentry.  If not, see <https://www.gnu.org/licenses/>.
+
+// Passed from the worker config to the admission control of the enclave's top pool author
+use codec::{Decode, Encode};
+use core::time::Duration;
+
+#[derive(Encode, Decode, Clone, Debug, PartialEq, Eq)]
+pub struct AdmissionConfig {
+	/// Length of the window in which the calls of an identity are counted.
+	pub window: Duration,
+	/// Calls an identity may submit per window.
+	pub max_calls_per_window: u32,
+	/// Assertions an identity may have in issuance at the same time.
+	pub max_concurrent_vc_requests_per_identity: u32,
+	/// Assertions that may be in issuance at the same time, over all identities.
+	pub max_concurrent_vc_requests: u32,
+}
+
+impl Default for AdmissionConfig {
+	fn default() -> Self {
+		Self {
+			window: Duration::from_secs(60),
+			max_calls_per_window: 60,
+			max_concurrent_vc_requests_per_identity: 10,
+			max_concurrent_vc_requests: 500,
+		}
+	}
+}
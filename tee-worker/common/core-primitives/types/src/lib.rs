@@ -23,15 +23,18 @@ use codec::{Decode, Encode};
 use itp_sgx_crypto::ShieldingCryptoDecrypt;
 use sp_std::{boxed::Box, fmt::Debug, vec::Vec};
 
+pub mod admission;
 pub mod parentchain;
 pub mod sidechain;
 pub mod storage;
+pub use admission::AdmissionConfig;
 pub use sidechain::SidechainBlockHash;
 
 pub use itp_sgx_runtime_primitives::types::*;
 pub use litentry_primitives::{
 	decl_rsa_request, Assertion, AttestationType, DcapProvider, DecryptableRequest, Enclave,
-	EnclaveFingerprint, Fmspc, MrEnclave, SidechainBlockNumber, WorkerType,
+	EnclaveFingerprint, Fmspc, MrEnclave, SidechainBlockConfirmation, SidechainBlockNumber,
+	WorkerType,
 };
 pub use sp_core::{crypto::AccountId32 as AccountId, H256};
 
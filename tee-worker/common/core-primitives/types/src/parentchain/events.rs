@@ -6,7 +6,9 @@ use crate::{
 use codec::{Decode, Encode};
 use core::fmt::Debug;
 use itp_utils::{hex::ToHexPrefixed, stringify::account_id_to_string};
-use litentry_primitives::{Address32, Identity};
+use litentry_primitives::{
+	Address32, AssertionPolicy, BtcSigningPolicy, EthSigningAllowlist, Identity, RelayerPermissions,
+};
 use sp_core::H160;
 use substrate_api_client::ac_node_api::StaticEvent;
 
@@ -139,6 +141,46 @@ impl StaticEvent for EnclaveRemoved {
 	const EVENT: &'static str = "EnclaveRemoved";
 }
 
+#[derive(Encode, Decode, Debug)]
+pub struct ShieldingKeyRotated {
+	pub who: AccountId,
+	pub shielding_pubkey: Vec<u8>,
+}
+
+impl core::fmt::Display for ShieldingKeyRotated {
+	fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+		let message =
+			format!("ShieldingKeyRotated :: who: {}", account_id_to_string::<AccountId>(&self.who));
+		write!(f, "{}", message)
+	}
+}
+
+impl StaticEvent for ShieldingKeyRotated {
+	const PALLET: &'static str = "Teebag";
+	const EVENT: &'static str = "ShieldingKeyRotated";
+}
+
+#[derive(Encode, Decode, Debug)]
+pub struct ShieldingKeyRotationRequested {
+	pub worker_type: WorkerType,
+	pub grace_period_secs: u64,
+}
+
+impl core::fmt::Display for ShieldingKeyRotationRequested {
+	fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+		let message = format!(
+			"ShieldingKeyRotationRequested :: worker_type: {:?}, grace_period_secs: {}",
+			self.worker_type, self.grace_period_secs
+		);
+		write!(f, "{}", message)
+	}
+}
+
+impl StaticEvent for ShieldingKeyRotationRequested {
+	const PALLET: &'static str = "Teebag";
+	const EVENT: &'static str = "ShieldingKeyRotationRequested";
+}
+
 // Identity-worker events
 
 #[derive(Encode, Decode, Debug)]
@@ -284,6 +326,89 @@ impl StaticEvent for AssertionCreated {
 	const EVENT: &'static str = "AssertionCreated";
 }
 
+#[derive(Encode, Decode, Debug)]
+pub struct AssertionCreatedWithPolicy {
+	pub id: H160,
+	pub byte_code: Vec<u8>,
+	pub secrets: Vec<Vec<u8>>,
+	pub policy: AssertionPolicy,
+}
+
+impl core::fmt::Display for AssertionCreatedWithPolicy {
+	fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+		let message = format!(
+			"{:?} :: byte_code: {:?}, secrets: {:?}, policy: {:?}",
+			AssertionCreatedWithPolicy::EVENT,
+			self.byte_code,
+			self.secrets,
+			self.policy
+		);
+		write!(f, "{}", message)
+	}
+}
+
+impl StaticEvent for AssertionCreatedWithPolicy {
+	const PALLET: &'static str = "EvmAssertions";
+	const EVENT: &'static str = "AssertionCreatedWithPolicy";
+}
+
+#[derive(Encode, Decode, Debug)]
+pub struct AssertionUpdated {
+	pub id: H160,
+	pub version: u32,
+	pub byte_code: Vec<u8>,
+	pub secrets: Vec<Vec<u8>>,
+}
+
+impl core::fmt::Display for AssertionUpdated {
+	fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+		let message = format!(
+			"{:?} :: version: {}, byte_code: {:?}, secrets: {:?}",
+			AssertionUpdated::EVENT,
+			self.version,
+			self.byte_code,
+			self.secrets
+		);
+		write!(f, "{}", message)
+	}
+}
+
+impl StaticEvent for AssertionUpdated {
+	const PALLET: &'static str = "EvmAssertions";
+	const EVENT: &'static str = "AssertionUpdated";
+}
+
+#[derive(Encode, Decode, Debug)]
+pub struct AssertionVersionActivated {
+	pub id: H160,
+	pub version: u32,
+}
+
+impl core::fmt::Display for AssertionVersionActivated {
+	fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+		let message = format!(
+			"{:?} :: id: {:?}, version: {}",
+			AssertionVersionActivated::EVENT,
+			self.id,
+			self.version
+		);
+		write!(f, "{}", message)
+	}
+}
+
+impl StaticEvent for AssertionVersionActivated {
+	const PALLET: &'static str = "EvmAssertions";
+	const EVENT: &'static str = "AssertionVersionActivated";
+}
+
+/// `AssertionUpdated` and `AssertionVersionActivated` events, kept apart from the other events so
+/// they can be applied in the order they were emitted
+#[derive(Debug)]
+pub enum AssertionVersionEvent {
+	Updated(AssertionUpdated),
+	Activated(AssertionVersionActivated),
+}
+
 //  Bitacross pallet events
 
 #[derive(Encode, Decode, Debug)]
@@ -346,3 +471,62 @@ impl StaticEvent for BtcWalletGenerated {
 	const PALLET: &'static str = "Bitacross";
 	const EVENT: &'static str = "BtcWalletGenerated";
 }
+
+#[derive(Encode, Decode, Debug)]
+pub struct BtcSigningPolicySet {
+	pub policy: BtcSigningPolicy,
+}
+
+impl core::fmt::Display for BtcSigningPolicySet {
+	fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+		let message = format!("BtcSigningPolicySet :: policy: {:?}", self.policy);
+		write!(f, "{}", message)
+	}
+}
+
+impl StaticEvent for BtcSigningPolicySet {
+	const PALLET: &'static str = "Bitacross";
+	const EVENT: &'static str = "BtcSigningPolicySet";
+}
+
+#[derive(Encode, Decode, Debug)]
+pub struct EthSigningAllowlistSet {
+	pub relayer: Identity,
+	pub allowlist: Option<EthSigningAllowlist>,
+}
+
+impl core::fmt::Display for EthSigningAllowlistSet {
+	fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+		let message = format!(
+			"EthSigningAllowlistSet :: relayer: {:?}, allowlist: {:?}",
+			self.relayer, self.allowlist
+		);
+		write!(f, "{}", message)
+	}
+}
+
+impl StaticEvent for EthSigningAllowlistSet {
+	const PALLET: &'static str = "Bitacross";
+	const EVENT: &'static str = "EthSigningAllowlistSet";
+}
+
+#[derive(Encode, Decode, Debug)]
+pub struct RelayerPermissionsSet {
+	pub who: Identity,
+	pub permissions: RelayerPermissions,
+}
+
+impl core::fmt::Display for RelayerPermissionsSet {
+	fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+		let message = format!(
+			"RelayerPermissionsSet :: who: {:?}, permissions: {:?}",
+			self.who, self.permissions
+		);
+		write!(f, "{}", message)
+	}
+}
+
+impl StaticEvent for RelayerPermissionsSet {
+	const PALLET: &'static str = "Bitacross";
+	const EVENT: &'static str = "RelayerPermissionsSet";
+}
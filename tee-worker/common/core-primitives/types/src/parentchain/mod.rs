@@ -110,6 +110,13 @@ pub trait FilterEvents {
 
 	fn get_assertion_created_events(&self) -> Result<Vec<AssertionCreated>, Self::Error>;
 
+	fn get_assertion_created_with_policy_events(
+		&self,
+	) -> Result<Vec<AssertionCreatedWithPolicy>, Self::Error>;
+
+	/// `AssertionUpdated` and `AssertionVersionActivated` events in chain order
+	fn get_assertion_version_events(&self) -> Result<Vec<AssertionVersionEvent>, Self::Error>;
+
 	fn get_parentchain_block_proccessed_events(
 		&self,
 	) -> Result<Vec<ParentchainBlockProcessed>, Self::Error>;
@@ -123,6 +130,15 @@ pub trait FilterEvents {
 	fn get_enclave_removed_events(&self) -> Result<Vec<EnclaveRemoved>, Self::Error>;
 
 	fn get_btc_wallet_generated_events(&self) -> Result<Vec<BtcWalletGenerated>, Self::Error>;
+
+	fn get_btc_signing_policy_set_events(&self) -> Result<Vec<BtcSigningPolicySet>, Self::Error>;
+
+	fn get_eth_signing_allowlist_set_events(
+		&self,
+	) -> Result<Vec<EthSigningAllowlistSet>, Self::Error>;
+
+	fn get_relayer_permissions_set_events(&self)
+		-> Result<Vec<RelayerPermissionsSet>, Self::Error>;
 }
 
 #[derive(Debug)]
@@ -163,6 +179,9 @@ pub enum ParentchainEventProcessingError {
 	EnclaveAddFailure,
 	EnclaveRemoveFailure,
 	BtcWalletGeneratedFailure,
+	BtcSigningPolicySetFailure,
+	EthSigningAllowlistSetFailure,
+	RelayerPermissionsSetFailure,
 }
 
 impl core::fmt::Display for ParentchainEventProcessingError {
@@ -196,6 +215,12 @@ impl core::fmt::Display for ParentchainEventProcessingError {
 				"Parentchain Event Processing Error: EnclaveRemoveFailure",
 			ParentchainEventProcessingError::BtcWalletGeneratedFailure =>
 				"Parentchain Event Processing Error: BtcWalletGeneratedFailure",
+			ParentchainEventProcessingError::BtcSigningPolicySetFailure =>
+				"Parentchain Event Processing Error: BtcSigningPolicySetFailure",
+			ParentchainEventProcessingError::EthSigningAllowlistSetFailure =>
+				"Parentchain Event Processing Error: EthSigningAllowlistSetFailure",
+			ParentchainEventProcessingError::RelayerPermissionsSetFailure =>
+				"Parentchain Event Processing Error: RelayerPermissionsSetFailure",
 		};
 		write!(f, "{}", message)
 	}
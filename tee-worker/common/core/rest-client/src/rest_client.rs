@@ -18,7 +18,10 @@
 #[cfg(all(not(feature = "std"), feature = "sgx"))]
 use crate::sgx_reexport_prelude::*;
 
-pub use http_req::{request::Method, response::Headers};
+pub use http_req::{
+	request::{Method, Request},
+	response::{Headers, Response},
+};
 pub use url::{form_urlencoded, Url};
 
 use crate::{
@@ -124,6 +124,7 @@ where
 				trace!("Connection {:?} is closed, removing", token);
 				connection.deregister(poll)?;
 				connections_lock.remove(&token);
+				self.connection_handler.handle_connection_closed(token.into());
 				trace!(
 					"Closed {:?}, {} active connections remaining",
 					token,
@@ -37,6 +37,8 @@ pub mod migrations;
 pub use pallet::*;
 pub mod identity_context;
 pub use identity_context::*;
+pub mod vc_issuance;
+pub use vc_issuance::*;
 
 use frame_support::{pallet_prelude::*, sp_runtime::traits::One, traits::StorageVersion};
 use frame_system::pallet_prelude::*;
@@ -72,6 +74,9 @@ pub mod pallet {
 		/// maximum number of identities an account can have, if you change this value to lower some accounts may exceed this limit
 		#[pallet::constant]
 		type MaxIDGraphLength: Get<u32>;
+		/// maximum number of VC issuance records kept per identity, older ones are dropped
+		#[pallet::constant]
+		type MaxVcIssuanceHistoryLength: Get<u32>;
 	}
 
 	#[pallet::event]
@@ -85,6 +90,8 @@ pub mod pallet {
 		IdentityActivated { who: Identity, identity: Identity },
 		/// an identity was removed
 		IdentityRemoved { who: Identity, identity: Identity },
+		/// a VC issuance was recorded
+		VcIssuanceRecorded { who: Identity, vc_id: H256 },
 	}
 
 	#[pallet::error]
@@ -101,6 +108,8 @@ pub mod pallet {
 		WrongWeb3NetworkTypes,
 		/// identity cannot be used to build prime identity
 		NotSupportedIdentity,
+		/// the VC issuance history of the identity is full
+		VcIssuanceHistoryFull,
 	}
 
 	#[pallet::storage]
@@ -123,6 +132,25 @@ pub mod pallet {
 	#[pallet::getter(fn id_graph_lens)]
 	pub type IDGraphLens<T: Config> = StorageMap<_, Blake2_128Concat, Identity, u32, ValueQuery>;
 
+	// the issued VCs of an identity, keyed by their position in the history,
+	// only the newest `MaxVcIssuanceHistoryLength` positions are kept
+	#[pallet::storage]
+	pub type VcIssuanceHistory<T: Config> = StorageDoubleMap<
+		_,
+		Blake2_128Concat,
+		Identity,
+		Twox64Concat,
+		u32,
+		VcIssuanceRecord,
+		OptionQuery,
+	>;
+
+	// number of VCs ever recorded for an identity, the position of the next record
+	#[pallet::storage]
+	#[pallet::getter(fn vc_issuance_count)]
+	pub type VcIssuanceCount<T: Config> =
+		StorageMap<_, Blake2_128Concat, Identity, u32, ValueQuery>;
+
 	#[pallet::call]
 	impl<T: Config> Pallet<T> {
 		#[pallet::call_index(1)]
@@ -268,6 +296,29 @@ pub mod pallet {
 
 			Ok(())
 		}
+
+		#[pallet::call_index(7)]
+		#[pallet::weight({15_000_000})]
+		pub fn record_vc_issuance(
+			origin: OriginFor<T>,
+			who: Identity,
+			record: VcIssuanceRecord,
+		) -> DispatchResult {
+			T::ManageOrigin::ensure_origin(origin)?;
+
+			let index = VcIssuanceCount::<T>::try_mutate(&who, |count| {
+				let index = *count;
+				*count = count.checked_add(1).ok_or(Error::<T>::VcIssuanceHistoryFull)?;
+				Result::<u32, Error<T>>::Ok(index)
+			})?;
+			let vc_id = record.vc_id;
+			VcIssuanceHistory::<T>::insert(&who, index, record);
+			if let Some(dropped) = index.checked_sub(T::MaxVcIssuanceHistoryLength::get()) {
+				VcIssuanceHistory::<T>::remove(&who, dropped);
+			}
+			Self::deposit_event(Event::VcIssuanceRecorded { who, vc_id });
+			Ok(())
+		}
 	}
 
 	impl<T: Config> Pallet<T> {
@@ -331,6 +382,23 @@ pub mod pallet {
 			}
 		}
 
+		// get up to `limit` records of the kept VC issuance history of `who`, starting at `start`,
+		// which counts from the oldest kept record
+		pub fn vc_issuance_history(
+			who: &Identity,
+			start: u32,
+			limit: u32,
+		) -> VcIssuanceHistoryPage {
+			let count = VcIssuanceCount::<T>::get(who);
+			let total = count.min(T::MaxVcIssuanceHistoryLength::get());
+			let first = count - total;
+			let end = start.saturating_add(limit.min(MAX_VC_ISSUANCE_HISTORY_PAGE_SIZE)).min(total);
+			let records = (start..end)
+				.filter_map(|index| VcIssuanceHistory::<T>::get(who, first + index))
+				.collect();
+			VcIssuanceHistoryPage { records, total }
+		}
+
 		// get count of all keys account + identity in the IDGraphs
 		pub fn id_graph_stats() -> Option<Vec<(Identity, u32)>> {
 			let stats = IDGraphLens::<T>::iter().collect();
@@ -16,10 +16,12 @@
 
 use crate::{
 	all_evm_web3networks, get_eligible_identities, mock::*, Error, IDGraph, Identity,
-	IdentityContext, IdentityStatus, Web3Network,
+	IdentityContext, IdentityStatus, VcIssuanceHistoryPage, VcIssuanceRecord, Web3Network,
+	MAX_VC_ISSUANCE_HISTORY_PAGE_SIZE,
 };
 use frame_support::{assert_err, assert_noop, assert_ok, traits::Get};
-use litentry_primitives::all_substrate_web3networks;
+use litentry_primitives::{all_substrate_web3networks, Assertion};
+use sp_core::H256;
 use sp_runtime::AccountId32;
 pub const ALICE: AccountId32 = AccountId32::new([1u8; 32]);
 pub const BOB: AccountId32 = AccountId32::new([2u8; 32]);
@@ -636,3 +638,95 @@ fn remove_identity_graph_of_other_account_fails() {
 		);
 	});
 }
+
+fn vc_issuance_record(n: u8) -> VcIssuanceRecord {
+	VcIssuanceRecord {
+		vc_id: H256::repeat_byte(n),
+		assertion: Assertion::A1,
+		issued_at: n as u64,
+		id_graph_hash: H256::zero(),
+		expires_at: None,
+	}
+}
+
+#[test]
+fn record_vc_issuance_works() {
+	new_test_ext().execute_with(|| {
+		let who: Identity = BOB.into();
+
+		for n in 0..3 {
+			assert_ok!(IMT::record_vc_issuance(
+				RuntimeOrigin::signed(ALICE),
+				who.clone(),
+				vc_issuance_record(n),
+			));
+		}
+
+		assert_eq!(IMT::vc_issuance_count(&who), 3);
+		assert_eq!(
+			IMT::vc_issuance_history(&who, 1, 10),
+			VcIssuanceHistoryPage {
+				records: vec![vc_issuance_record(1), vc_issuance_record(2)],
+				total: 3
+			}
+		);
+		assert_eq!(IMT::vc_issuance_history(&who, 3, 10).records, vec![]);
+		assert_eq!(
+			IMT::vc_issuance_history(&ALICE.into(), 0, 10),
+			VcIssuanceHistoryPage::default()
+		);
+	});
+}
+
+#[test]
+fn record_vc_issuance_requires_manage_origin() {
+	new_test_ext().execute_with(|| {
+		assert_noop!(
+			IMT::record_vc_issuance(RuntimeOrigin::signed(BOB), BOB.into(), vc_issuance_record(0)),
+			sp_runtime::DispatchError::BadOrigin
+		);
+	});
+}
+
+#[test]
+fn vc_issuance_history_page_size_is_capped() {
+	new_test_ext().execute_with(|| {
+		let who: Identity = BOB.into();
+
+		for n in 0..=MAX_VC_ISSUANCE_HISTORY_PAGE_SIZE {
+			assert_ok!(IMT::record_vc_issuance(
+				RuntimeOrigin::signed(ALICE),
+				who.clone(),
+				vc_issuance_record(n as u8),
+			));
+		}
+
+		let page = IMT::vc_issuance_history(&who, 0, u32::MAX);
+		assert_eq!(page.records.len() as u32, MAX_VC_ISSUANCE_HISTORY_PAGE_SIZE);
+		assert_eq!(page.total, MAX_VC_ISSUANCE_HISTORY_PAGE_SIZE + 1);
+	});
+}
+
+#[test]
+fn vc_issuance_history_drops_oldest_records_beyond_max_length() {
+	new_test_ext().execute_with(|| {
+		let who: Identity = BOB.into();
+		let max_len = <<Test as crate::Config>::MaxVcIssuanceHistoryLength as Get<u32>>::get();
+
+		for n in 0..max_len + 2 {
+			assert_ok!(IMT::record_vc_issuance(
+				RuntimeOrigin::signed(ALICE),
+				who.clone(),
+				vc_issuance_record(n as u8),
+			));
+		}
+
+		assert_eq!(IMT::vc_issuance_count(&who), max_len + 2);
+		assert_eq!(crate::VcIssuanceHistory::<Test>::iter_prefix(&who).count() as u32, max_len);
+		assert!(crate::VcIssuanceHistory::<Test>::get(&who, 1).is_none());
+
+		let page = IMT::vc_issuance_history(&who, 0, 1);
+		assert_eq!(page.total, max_len);
+		assert_eq!(page.records, vec![vc_issuance_record(2)]);
+	});
+}
@@ -0,0 +1,47 @@
+// Copyright 2020-2024 Trust Computing GmbH.
+// This file is part of Litentry.
+//
+// Litentry is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// Litentry is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with Litentry.  If not, see <https://www.gnu.org/licenses/>.
+
+use codec::{Decode, Encode};
+use litentry_primitives::Assertion;
+use scale_info::TypeInfo;
+use sp_core::H256;
+use sp_std::vec::Vec;
+
+/// Maximum number of records returned by a single history query.
+pub const MAX_VC_ISSUANCE_HISTORY_PAGE_SIZE: u32 = 100;
+
+// A VC issued to an identity, kept so that the holder can discover and re-request it
+// and so that all VCs of an identity can be found, e.g. for revocation
+#[derive(Clone, Eq, PartialEq, Debug, Encode, Decode, TypeInfo)]
+pub struct VcIssuanceRecord {
+	// the `id` of the credential
+	pub vc_id: H256,
+	pub assertion: Assertion,
+	// unix timestamp in milliseconds
+	pub issued_at: u64,
+	// hash of the IDGraph the VC was built upon
+	pub id_graph_hash: H256,
+	// unix timestamp in milliseconds, `None` if the VC doesn't expire
+	pub expires_at: Option<u64>,
+}
+
+// A page of the VC issuance history of an identity, in issuance order
+#[derive(Clone, Eq, PartialEq, Debug, Default, Encode, Decode, TypeInfo)]
+pub struct VcIssuanceHistoryPage {
+	pub records: Vec<VcIssuanceRecord>,
+	// number of records in the kept history
+	pub total: u32,
+}
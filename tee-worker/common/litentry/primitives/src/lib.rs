@@ -54,7 +54,10 @@ pub use parentchain_primitives::{
 		},
 		bnb_domain::BnbDigitDomainType,
 		contest::ContestType,
-		dynamic::{DynamicContractParams, DynamicParams},
+		dynamic::{
+			AssertionAllowedDomains, AssertionDomain, AssertionLimits, AssertionPolicy,
+			DynamicContractParams, DynamicParams,
+		},
 		evm_amount_holding::EVMTokenType,
 		generic_discord_role::GenericDiscordRoleType,
 		network::{
@@ -69,6 +72,7 @@ pub use parentchain_primitives::{
 		web3_token::Web3TokenType,
 		Assertion,
 	},
+	bitacross::*,
 	decl_rsa_request,
 	identity::*,
 	omni_account::*,
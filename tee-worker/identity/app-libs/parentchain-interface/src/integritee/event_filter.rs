@@ -92,6 +92,34 @@ impl FilterEvents for FilterableEvents {
 		self.filter()
 	}
 
+	fn get_assertion_created_with_policy_events(
+		&self,
+	) -> Result<Vec<AssertionCreatedWithPolicy>, Self::Error> {
+		self.filter()
+	}
+
+	fn get_assertion_version_events(&self) -> Result<Vec<AssertionVersionEvent>, Self::Error> {
+		Ok(self
+			.to_events()
+			.iter()
+			.flatten()
+			.filter_map(|ev| {
+				match (
+					ev.as_event::<AssertionUpdated>(),
+					ev.as_event::<AssertionVersionActivated>(),
+				) {
+					(Ok(Some(event)), _) => Some(AssertionVersionEvent::Updated(event)),
+					(_, Ok(Some(event))) => Some(AssertionVersionEvent::Activated(event)),
+					(Err(e), _) | (_, Err(e)) => {
+						log::error!("Could not decode event: {:?}", e);
+						None
+					},
+					_ => None,
+				}
+			})
+			.collect())
+	}
+
 	fn get_parentchain_block_proccessed_events(
 		&self,
 	) -> Result<Vec<ParentchainBlockProcessed>, Self::Error> {
@@ -117,4 +145,20 @@ impl FilterEvents for FilterableEvents {
 	fn get_btc_wallet_generated_events(&self) -> Result<Vec<BtcWalletGenerated>, Self::Error> {
 		self.filter()
 	}
+
+	fn get_btc_signing_policy_set_events(&self) -> Result<Vec<BtcSigningPolicySet>, Self::Error> {
+		self.filter()
+	}
+
+	fn get_eth_signing_allowlist_set_events(
+		&self,
+	) -> Result<Vec<EthSigningAllowlistSet>, Self::Error> {
+		self.filter()
+	}
+
+	fn get_relayer_permissions_set_events(
+		&self,
+	) -> Result<Vec<RelayerPermissionsSet>, Self::Error> {
+		self.filter()
+	}
 }
@@ -25,14 +25,15 @@ use itp_ocall_api::EnclaveMetricsOCallApi;
 use itp_stf_primitives::{traits::IndirectExecutor, types::TrustedOperation};
 use itp_types::{
 	parentchain::{
-		events::ParentchainBlockProcessed, AccountId, FilterEvents, HandleParentchainEvents,
-		ParentchainEventProcessingError, ProcessedEventsArtifacts,
+		events::{AssertionVersionEvent, ParentchainBlockProcessed},
+		AccountId, FilterEvents, HandleParentchainEvents, ParentchainEventProcessingError,
+		ProcessedEventsArtifacts,
 	},
 	RsaRequest, H256,
 };
-use lc_dynamic_assertion::AssertionLogicRepository;
+use lc_dynamic_assertion::{AssertionLogicRepository, AssertionVersion};
 use lc_evm_dynamic_assertions::repository::EvmAssertionRepository;
-use litentry_primitives::{Assertion, Identity, ValidationData, Web3Network};
+use litentry_primitives::{Assertion, AssertionPolicy, Identity, ValidationData, Web3Network};
 use log::*;
 use sp_core::{blake2_256, H160};
 use sp_std::vec::Vec;
@@ -182,6 +183,7 @@ where
 		&self,
 		executor: &Executor,
 		id: H160,
+		version: AssertionVersion,
 		byte_code: Vec<u8>,
 		secrets: Vec<Vec<u8>>,
 	) -> Result<(), Error> {
@@ -207,7 +209,7 @@ where
 		}
 		let start_time = Instant::now();
 		self.assertion_repository
-			.save(id, (byte_code, decrypted_secrets))
+			.save(id, version, (byte_code, decrypted_secrets))
 			.map_err(Error::AssertionCreatedHandling)?;
 		let duration = start_time.elapsed();
 		if let Err(e) = self
@@ -219,6 +221,20 @@ where
 
 		Ok(())
 	}
+
+	fn store_assertion_policy(&self, id: H160, policy: AssertionPolicy) -> Result<(), Error> {
+		debug!("store assertion policy: {:?}, policy: {:?}", id, policy);
+		self.assertion_repository
+			.save_policy(id, policy)
+			.map_err(Error::AssertionCreatedHandling)
+	}
+
+	fn activate_assertion_version(&self, id: H160, version: AssertionVersion) -> Result<(), Error> {
+		debug!("activate assertion: {:?}, version: {}", id, version);
+		self.assertion_repository
+			.activate(&id, version)
+			.map_err(Error::AssertionCreatedHandling)
+	}
 }
 
 impl<Executor, MetricsApi> HandleParentchainEvents<Executor, TrustedCallSigned, Error, (), (), ()>
@@ -328,8 +344,9 @@ where
 				.try_for_each(|event| {
 					debug!("found AssertionCreated event: {:?}", event);
 					let event_hash = hash_of(&event);
+					// newly created assertions always start with version 0
 					let result =
-						self.store_assertion(executor, event.id, event.byte_code, event.secrets);
+						self.store_assertion(executor, event.id, 0, event.byte_code, event.secrets);
 					handled_events.push(event_hash);
 					if result.is_ok() {
 						successful_assertion_ids.push(event.id);
@@ -341,6 +358,62 @@ where
 				.map_err(|_| ParentchainEventProcessingError::AssertionCreatedFailure)?;
 		}
 
+		if let Ok(events) = events.get_assertion_created_with_policy_events() {
+			debug!("Handling AssertionCreatedWithPolicy events");
+			events
+				.into_iter()
+				.try_for_each(|event| {
+					debug!("found AssertionCreatedWithPolicy event: {:?}", event);
+					let event_hash = hash_of(&event);
+					let result = self
+						.store_assertion(executor, event.id, 0, event.byte_code, event.secrets)
+						.and_then(|_| self.store_assertion_policy(event.id, event.policy));
+					handled_events.push(event_hash);
+					if result.is_ok() {
+						successful_assertion_ids.push(event.id);
+					} else {
+						failed_assertion_ids.push(event.id)
+					}
+					result
+				})
+				.map_err(|_| ParentchainEventProcessingError::AssertionCreatedFailure)?;
+		}
+
+		if let Ok(events) = events.get_assertion_version_events() {
+			debug!("Handling AssertionUpdated and AssertionVersionActivated events");
+			// applied in chain order, a failed update or activation is logged and skipped so that it
+			// neither voids the assertion nor holds back the other events of the block
+			events.into_iter().for_each(|event| match event {
+				AssertionVersionEvent::Updated(event) => {
+					debug!("found AssertionUpdated event: {:?}", event);
+					handled_events.push(hash_of(&event));
+					match self.store_assertion(
+						executor,
+						event.id,
+						event.version,
+						event.byte_code,
+						event.secrets,
+					) {
+						Ok(()) => successful_assertion_ids.push(event.id),
+						Err(e) => error!(
+							"Could not store version {} of assertion {:?}, previous version stays active: {:?}",
+							event.version, event.id, e
+						),
+					}
+				},
+				AssertionVersionEvent::Activated(event) => {
+					debug!("found AssertionVersionActivated event: {}", event);
+					handled_events.push(hash_of(&event));
+					if let Err(e) = self.activate_assertion_version(event.id, event.version) {
+						error!(
+							"Could not activate version {} of assertion {:?}: {:?}",
+							event.version, event.id, e
+						);
+					}
+				},
+			});
+		}
+
 		if let Ok(events) = events.get_parentchain_block_proccessed_events() {
 			debug!("Handling ParentchainBlockProcessed events");
 			events.iter().for_each(|event| {
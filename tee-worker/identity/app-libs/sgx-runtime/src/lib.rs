@@ -83,7 +83,8 @@ pub use sp_runtime::{Perbill, Permill};
 
 // litentry
 pub use pallet_identity_management_tee::{
-	self, Call as IdentityManagementCall, IDGraph, IdentityStatus,
+	self, Call as IdentityManagementCall, IDGraph, IdentityStatus, VcIssuanceHistoryPage,
+	VcIssuanceRecord,
 };
 
 /// Block type as expected by this sgx-runtime.
@@ -289,6 +290,7 @@ impl pallet_identity_management_tee::Config for Runtime {
 	type RuntimeEvent = RuntimeEvent;
 	type ManageOrigin = EnsureRoot<AccountId>;
 	type MaxIDGraphLength = ConstU32<64>;
+	type MaxVcIssuanceHistoryLength = ConstU32<1000>;
 }
 
 // The plain sgx-runtime without the `evm-pallet`
@@ -124,6 +124,9 @@ pub enum TrustedGetter {
 	// litentry
 	#[codec(index = 5)]
 	id_graph(Identity),
+	// (who, start, limit)
+	#[codec(index = 6)]
+	vc_issuance_history(Identity, u32, u32),
 }
 
 impl TrustedGetter {
@@ -139,6 +142,7 @@ impl TrustedGetter {
 			TrustedGetter::evm_account_storages(sender_identity, ..) => sender_identity,
 			// litentry
 			TrustedGetter::id_graph(sender_identity) => sender_identity,
+			TrustedGetter::vc_issuance_history(sender_identity, ..) => sender_identity,
 		}
 	}
 
@@ -266,6 +270,8 @@ impl ExecuteGetter for TrustedGetterSigned {
 				},
 			// litentry
 			TrustedGetter::id_graph(who) => Some(IdentityManagement::id_graph(&who).encode()),
+			TrustedGetter::vc_issuance_history(who, start, limit) =>
+				Some(IdentityManagement::vc_issuance_history(&who, start, limit).encode()),
 		}
 	}
 
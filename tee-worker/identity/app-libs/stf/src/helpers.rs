@@ -93,6 +93,11 @@ pub fn enclave_signer_account<AccountId: Decode>() -> AccountId {
 	get_storage_value("Sudo", ENCLAVE_ACCOUNT_KEY).expect("No enclave account")
 }
 
+/// Replaces the registered enclave account.
+pub fn set_enclave_signer_account<AccountId: Encode>(account: &AccountId) {
+	sp_io::storage::set(&storage_value_key("Sudo", ENCLAVE_ACCOUNT_KEY), &account.encode());
+}
+
 /// Ensures an account is a registered enclave account.
 pub fn ensure_enclave_signer_account<AccountId: Encode + Decode + PartialEq>(
 	account: &AccountId,
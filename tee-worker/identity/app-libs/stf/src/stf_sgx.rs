@@ -401,7 +401,7 @@ where
 
 /// Creates valid enclave account with a balance that is above the existential deposit.
 /// !! Requires a root to be set.
-fn create_enclave_self_account<Runtime, AccountId>(
+pub(crate) fn create_enclave_self_account<Runtime, AccountId>(
 	enclave_account: AccountId,
 ) -> Result<(), StfError>
 where
@@ -22,7 +22,11 @@ use sp_core::{H160, U256};
 use crate::evm_helpers::{create_code_hash, evm_create2_address, evm_create_address};
 use crate::{
 	format,
-	helpers::{enclave_signer_account, ensure_enclave_signer_account, ensure_self},
+	helpers::{
+		enclave_signer_account, ensure_enclave_signer_account, ensure_self,
+		set_enclave_signer_account,
+	},
+	stf_sgx::create_enclave_self_account,
 	trusted_call_result::{
 		ActivateIdentityResult, DeactivateIdentityResult, SetIdentityNetworksResult,
 		TrustedCallResult,
@@ -35,7 +39,8 @@ use frame_support::{ensure, traits::UnfilteredDispatchable};
 use ita_sgx_runtime::{AddressMapping, HashedAddressMapping};
 pub use ita_sgx_runtime::{
 	Balance, IDGraph, Index, ParentchainInstanceLitentry, ParentchainInstanceTargetA,
-	ParentchainInstanceTargetB, ParentchainLitentry, Runtime, System, VERSION as SIDECHAIN_VERSION,
+	ParentchainInstanceTargetB, ParentchainLitentry, Runtime, System, VcIssuanceRecord,
+	VERSION as SIDECHAIN_VERSION,
 };
 use itp_node_api::metadata::{provider::AccessNodeMetadata, NodeMetadataTrait};
 use itp_node_api_metadata::{pallet_imp::IMPCallIndexes, pallet_vcmp::VCMPCallIndexes};
@@ -43,7 +48,7 @@ use itp_stf_interface::ExecuteCall;
 use itp_stf_primitives::{
 	error::StfError,
 	traits::{TrustedCallSigning, TrustedCallVerification},
-	types::{AccountId, KeyPair, ShardIdentifier, TrustedOperation},
+	types::{AccountId, KeyPair, PriorityClass, ShardIdentifier, TrustedOperation},
 };
 use itp_types::{
 	parentchain::{ParentchainCall, ParentchainId},
@@ -139,6 +144,10 @@ pub enum TrustedCall {
 	#[cfg(feature = "development")]
 	#[codec(index = 25)]
 	clean_id_graphs(Identity),
+	#[codec(index = 26)]
+	record_vc_issuance(Identity, Identity, VcIssuanceRecord),
+	#[codec(index = 27)]
+	rotate_enclave_signer_account(Identity, AccountId),
 
 	// original integritee trusted calls, starting from index 50
 	#[codec(index = 50)]
@@ -224,6 +233,8 @@ impl TrustedCall {
 			Self::handle_vcmp_error(sender_identity, ..) => sender_identity,
 			Self::send_erroneous_parentchain_call(sender_identity) => sender_identity,
 			Self::maybe_create_id_graph(sender_identity, ..) => sender_identity,
+			Self::record_vc_issuance(sender_identity, ..) => sender_identity,
+			Self::rotate_enclave_signer_account(sender_identity, ..) => sender_identity,
 			#[cfg(feature = "development")]
 			Self::remove_identity(sender_identity, ..) => sender_identity,
 			Self::request_batch_vc(sender_identity, ..) => sender_identity,
@@ -246,6 +257,19 @@ impl TrustedCall {
 		}
 	}
 
+	pub fn priority_class(&self) -> PriorityClass {
+		match self {
+			Self::handle_imp_error(..)
+			| Self::handle_vcmp_error(..)
+			| Self::maybe_create_id_graph(..)
+			| Self::record_vc_issuance(..)
+			| Self::rotate_enclave_signer_account(..) => PriorityClass::Internal,
+			Self::link_identity_callback(..) => PriorityClass::Callback,
+			Self::request_vc(..) | Self::request_batch_vc(..) => PriorityClass::Bulk,
+			_ => PriorityClass::User,
+		}
+	}
+
 	pub fn signature_message_prefix(&self) -> String {
 		match self {
 			Self::link_identity(..) => "By linking your identity to our platform, you're taking a step towards a more integrated experience. Please be assured, this process is safe and involves no transactions of your assets. Token: ".to_string(),
@@ -342,6 +366,10 @@ impl TrustedCallVerification for TrustedCallSigned {
 	fn metric_name(&self) -> &'static str {
 		self.call.metric_name()
 	}
+
+	fn priority_class(&self) -> PriorityClass {
+		self.call.priority_class()
+	}
 }
 
 impl<NodeMetadataRepository> ExecuteCall<NodeMetadataRepository> for TrustedCallSigned
@@ -879,6 +907,36 @@ where
 
 				Ok(TrustedCallResult::Empty)
 			},
+			TrustedCall::record_vc_issuance(signer, who, record) => {
+				debug!("record_vc_issuance, who: {:?}, vc_id: {:?}", who, record.vc_id);
+				let signer_account: AccountId32 =
+					signer.to_native_account().ok_or(Self::Error::InvalidAccount)?;
+				ensure_enclave_signer_account(&signer_account)?;
+
+				// we only log the error, the VC was already issued
+				let call = IMTCall::record_vc_issuance { who, record };
+				if let Err(e) = call.dispatch_bypass_filter(ita_sgx_runtime::RuntimeOrigin::root())
+				{
+					warn!("record_vc_issuance NOK: {:?}", e);
+				}
+
+				Ok(TrustedCallResult::Empty)
+			},
+			TrustedCall::rotate_enclave_signer_account(signer, account) => {
+				debug!(
+					"rotate_enclave_signer_account, account: {}",
+					account_id_to_string(&account)
+				);
+				let signer_account: AccountId32 =
+					signer.to_native_account().ok_or(Self::Error::InvalidAccount)?;
+				ensure_enclave_signer_account(&signer_account)?;
+
+				// the enclave account is derived from the shielding key, which was rotated
+				set_enclave_signer_account(&account);
+				create_enclave_self_account::<Runtime, AccountId>(account)?;
+
+				Ok(TrustedCallResult::Empty)
+			},
 			#[cfg(feature = "development")]
 			TrustedCall::clean_id_graphs(signer) => {
 				debug!("clean_id_graphs");
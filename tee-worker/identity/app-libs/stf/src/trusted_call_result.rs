@@ -128,6 +128,9 @@ pub enum RequestVcErrorDetail {
 	CallSendingFailed(String),
 	ExtrinsicConstructionFailed(String), // Stringified itp_extrinsics_factory::Error
 	ExtrinsicSendingFailed(String),      // Stringified sgx_status_t
+	AssertionLimitExceeded(String),
+	AssertionDomainNotAllowed(String),
+	RateLimited(String),
 }
 
 #[derive(Debug, Encode, Decode, Clone)]
@@ -24,3 +24,4 @@ pub mod remove_identity;
 pub mod request_vc;
 pub mod request_vc_subcommands;
 pub mod send_erroneous_parentchain_call;
+pub mod vc_issuance_history;
@@ -0,0 +1,61 @@
+// Copyright 2020-2024 Trust Computing GmbH.
+// This file is part of Litentry.
+//
+// Litentry is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// Litentry is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with Litentry.  If not, see <https://www.gnu.org/licenses/>.
+
+use crate::{
+	trusted_cli::TrustedCli, trusted_command_utils::get_pair_from_str,
+	trusted_operation::perform_trusted_operation, Cli, CliResult, CliResultOk,
+};
+use ita_sgx_runtime::VcIssuanceHistoryPage;
+use ita_stf::{Getter, TrustedGetter};
+use itp_stf_primitives::types::KeyPair;
+use litentry_primitives::Identity;
+
+// usage example:
+//
+// ./bin/litentry-cli trusted -d vc-issuance-history did:litentry:substrate:0x8eaf04151687736326c9fea17e25fc5287613693c912909cb226aa4794f26a48 --start 0 --limit 10
+//
+// returns:
+//
+// VcIssuanceHistoryPage { records: [VcIssuanceRecord { vc_id: 0x1f2c..., assertion: A1, issued_at: 1718000000000, id_graph_hash: 0x7a3e..., expires_at: None }], total: 1 }
+
+#[derive(Parser)]
+pub struct VcIssuanceHistoryCommand {
+	// did format - will be converted to `Identity`
+	did: String,
+	/// position of the first record in the history
+	#[clap(long, default_value_t = 0)]
+	start: u32,
+	/// maximum number of records to return
+	#[clap(long, default_value_t = 10)]
+	limit: u32,
+}
+
+impl VcIssuanceHistoryCommand {
+	pub(crate) fn run(&self, cli: &Cli, trusted_cli: &TrustedCli) -> CliResult {
+		let alice = get_pair_from_str(trusted_cli, "//Alice", cli);
+		let id: Identity = Identity::from_did(self.did.as_str()).unwrap();
+
+		let top = Getter::trusted(
+			TrustedGetter::vc_issuance_history(id, self.start, self.limit)
+				.sign(&KeyPair::Sr25519(Box::new(alice))),
+		)
+		.into();
+		let history = perform_trusted_operation::<VcIssuanceHistoryPage>(cli, trusted_cli, &top);
+		println!("{:?}", history.unwrap());
+
+		Ok(CliResultOk::None)
+	}
+}
@@ -27,6 +27,7 @@ use crate::{
 			get_storage::GetStorageCommand, link_identity::LinkIdentityCommand,
 			request_vc::RequestVcCommand,
 			send_erroneous_parentchain_call::SendErroneousParentchainCallCommand,
+			vc_issuance_history::VcIssuanceHistoryCommand,
 		},
 		nonce::NonceCommand,
 		set_balance::SetBalanceCommand,
@@ -85,6 +86,9 @@ pub enum TrustedBaseCommand {
 	/// Request VC
 	RequestVc(RequestVcCommand),
 
+	/// The VCs issued to the given identity
+	VcIssuanceHistory(VcIssuanceHistoryCommand),
+
 	/// Remove Identity from the prime identity
 	#[cfg(feature = "development")]
 	RemoveIdentity(RemoveIdentityCommand),
@@ -110,6 +114,7 @@ impl TrustedBaseCommand {
 			TrustedBaseCommand::LinkIdentity(cmd) => cmd.run(cli, trusted_cli),
 			TrustedBaseCommand::IDGraph(cmd) => cmd.run(cli, trusted_cli),
 			TrustedBaseCommand::RequestVc(cmd) => cmd.run(cli, trusted_cli),
+			TrustedBaseCommand::VcIssuanceHistory(cmd) => cmd.run(cli, trusted_cli),
 			#[cfg(feature = "development")]
 			TrustedBaseCommand::RemoveIdentity(cmd) => cmd.run(cli, trusted_cli),
 			#[cfg(feature = "development")]
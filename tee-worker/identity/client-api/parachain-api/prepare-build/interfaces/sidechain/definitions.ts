@@ -87,6 +87,8 @@ export default {
                 __Unused_WrongSignatureType: "Null",
                 VerifyWeb3SignatureFailed: "Null",
                 NoEligibleIdentity: "Null",
+                AssertionLimitExceeded: "(Bytes)",
+                AssertionDomainNotAllowed: "(Bytes)",
             },
         },
         ShardIdentifier: "H256",
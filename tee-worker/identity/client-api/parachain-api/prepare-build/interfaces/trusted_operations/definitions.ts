@@ -26,6 +26,7 @@ export default {
                 __Unused_evm_account_codes: "Null",
                 __Unused_evm_account_storages: "Null",
                 id_graph: "(LitentryIdentity)",
+                vc_issuance_history: "(LitentryIdentity, u32, u32)",
             },
         },
         //important
@@ -61,6 +61,17 @@ export default {
             pre_mutated_id_graph: "AesOutput",
             pre_id_graph_hash: "H256",
         },
+        VcIssuanceRecord: {
+            vc_id: "H256",
+            assertion: "Assertion",
+            issued_at: "u64",
+            id_graph_hash: "H256",
+            expires_at: "Option<u64>",
+        },
+        VcIssuanceHistoryPage: {
+            records: "Vec<VcIssuanceRecord>",
+            total: "u32",
+        },
         VCMPError: {
             _enum: {
                 RequestVCFailed: "(Assertion, ErrorDetail)",
@@ -90,6 +101,9 @@ export default {
                 CallSendingFailed: "String",
                 ExtrinsicConstructionFailed: "String", // Stringified itp_extrinsics_factory::Error
                 ExtrinsicSendingFailed: "String", // Stringified sgx_status_t
+                AssertionLimitExceeded: "String",
+                AssertionDomainNotAllowed: "String",
+                RateLimited: "String",
             },
         },
         RequestVcResultOrError: {
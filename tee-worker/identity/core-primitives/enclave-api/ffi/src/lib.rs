@@ -28,6 +28,8 @@ extern "C" {
 		untrusted_worker_addr_size: u32,
 		encoded_base_dir_str: *const u8,
 		encoded_base_dir_size: u32,
+		admission_config: *const u8,
+		admission_config_size: u32,
 	) -> sgx_status_t;
 
 	pub fn init_enclave_sidechain_components(
@@ -227,6 +229,9 @@ extern "C" {
 		quote_size: Option<&u32>,
 		shard: *const u8,
 		shard_size: u32,
+		confirmed_block: *const u8,
+		confirmed_block_size: u32,
+		provisioned_payloads: *mut u8,
 		skip_ra: c_int,
 	) -> sgx_status_t;
 
@@ -240,6 +245,30 @@ extern "C" {
 
 	pub fn upload_id_graph(eid: sgx_enclave_id_t, retval: *mut sgx_status_t) -> sgx_status_t;
 
+	pub fn rotate_shielding_key(
+		eid: sgx_enclave_id_t,
+		retval: *mut sgx_status_t,
+		grace_period_secs: *const u64,
+	) -> sgx_status_t;
+
+	pub fn export_state_backup(
+		eid: sgx_enclave_id_t,
+		retval: *mut sgx_status_t,
+		shard: *const u8,
+		shard_size: u32,
+		sidechain_blocks: *const u8,
+		sidechain_blocks_size: u32,
+		backup_path: *const u8,
+		backup_path_size: u32,
+	) -> sgx_status_t;
+
+	pub fn import_state_backup(
+		eid: sgx_enclave_id_t,
+		retval: *mut sgx_status_t,
+		backup_path: *const u8,
+		backup_path_size: u32,
+	) -> sgx_status_t;
+
 	pub fn ignore_parentchain_block_import_validation_until(
 		eid: sgx_enclave_id_t,
 		retval: *mut sgx_status_t,
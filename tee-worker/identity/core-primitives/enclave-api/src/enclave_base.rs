@@ -17,12 +17,12 @@
 */
 
 use crate::EnclaveResult;
-use codec::Decode;
+use codec::{Decode, Encode};
 use core::fmt::Debug;
 use itp_stf_interface::ShardCreationInfo;
 use itp_types::{
 	parentchain::{Header, ParentchainId, ParentchainInitParams},
-	EnclaveFingerprint, ShardIdentifier,
+	AdmissionConfig, EnclaveFingerprint, ShardIdentifier,
 };
 use sgx_crypto_helper::rsa3072::Rsa3072PubKey;
 use sp_core::ed25519;
@@ -35,6 +35,7 @@ pub trait EnclaveBase: Send + Sync + 'static {
 		mu_ra_addr: &str,
 		untrusted_worker_addr: &str,
 		base_dir: &str,
+		admission_config: &AdmissionConfig,
 	) -> EnclaveResult<()>;
 
 	/// Initialize the enclave sidechain components.
@@ -84,6 +85,20 @@ pub trait EnclaveBase: Send + Sync + 'static {
 	fn migrate_shard(&self, new_shard: Vec<u8>) -> EnclaveResult<()>;
 
 	fn upload_id_graph(&self) -> EnclaveResult<()>;
+
+	fn rotate_shielding_key(&self, grace_period_secs: u64) -> EnclaveResult<()>;
+
+	/// Write an encrypted backup of the shard state, the parentchain light client
+	/// and the given sidechain blocks to `backup_path`.
+	fn export_state_backup<SidechainBlock: Encode>(
+		&self,
+		shard: &ShardIdentifier,
+		sidechain_blocks: &[SidechainBlock],
+		backup_path: &str,
+	) -> EnclaveResult<()>;
+
+	/// Restore the shard state and the parentchain light client from a backup.
+	fn import_state_backup(&self, backup_path: &str) -> EnclaveResult<()>;
 }
 
 /// EnclaveApi implementation for Enclave struct
@@ -101,7 +116,7 @@ mod impl_ffi {
 	use itp_stf_interface::ShardCreationInfo;
 	use itp_types::{
 		parentchain::{Header, ParentchainId, ParentchainInitParams},
-		EnclaveFingerprint, ShardIdentifier,
+		AdmissionConfig, EnclaveFingerprint, ShardIdentifier,
 	};
 	use log::*;
 	use sgx_crypto_helper::rsa3072::Rsa3072PubKey;
@@ -114,12 +129,14 @@ mod impl_ffi {
 			mu_ra_addr: &str,
 			untrusted_worker_addr: &str,
 			base_dir: &str,
+			admission_config: &AdmissionConfig,
 		) -> EnclaveResult<()> {
 			let mut retval = sgx_status_t::SGX_SUCCESS;
 
 			let encoded_mu_ra_addr = mu_ra_addr.encode();
 			let encoded_untrusted_worker_addr = untrusted_worker_addr.encode();
 			let encoded_base_dir = base_dir.encode();
+			let encoded_admission_config = admission_config.encode();
 
 			let result = unsafe {
 				ffi::init(
@@ -131,6 +148,8 @@ mod impl_ffi {
 					encoded_untrusted_worker_addr.len() as u32,
 					encoded_base_dir.as_ptr(),
 					encoded_base_dir.len() as u32,
+					encoded_admission_config.as_ptr(),
+					encoded_admission_config.len() as u32,
 				)
 			};
 
@@ -401,6 +420,69 @@ mod impl_ffi {
 
 			Ok(())
 		}
+
+		fn rotate_shielding_key(&self, grace_period_secs: u64) -> EnclaveResult<()> {
+			let mut retval = sgx_status_t::SGX_SUCCESS;
+
+			let result =
+				unsafe { ffi::rotate_shielding_key(self.eid, &mut retval, &grace_period_secs) };
+
+			ensure!(result == sgx_status_t::SGX_SUCCESS, Error::Sgx(result));
+			ensure!(retval == sgx_status_t::SGX_SUCCESS, Error::Sgx(retval));
+
+			Ok(())
+		}
+
+		fn export_state_backup<SidechainBlock: Encode>(
+			&self,
+			shard: &ShardIdentifier,
+			sidechain_blocks: &[SidechainBlock],
+			backup_path: &str,
+		) -> EnclaveResult<()> {
+			let mut retval = sgx_status_t::SGX_SUCCESS;
+
+			let shard_bytes = shard.encode();
+			let encoded_blocks = sidechain_blocks.encode();
+			let encoded_path = backup_path.encode();
+
+			let result = unsafe {
+				ffi::export_state_backup(
+					self.eid,
+					&mut retval,
+					shard_bytes.as_ptr(),
+					shard_bytes.len() as u32,
+					encoded_blocks.as_ptr(),
+					encoded_blocks.len() as u32,
+					encoded_path.as_ptr(),
+					encoded_path.len() as u32,
+				)
+			};
+
+			ensure!(result == sgx_status_t::SGX_SUCCESS, Error::Sgx(result));
+			ensure!(retval == sgx_status_t::SGX_SUCCESS, Error::Sgx(retval));
+
+			Ok(())
+		}
+
+		fn import_state_backup(&self, backup_path: &str) -> EnclaveResult<()> {
+			let mut retval = sgx_status_t::SGX_SUCCESS;
+
+			let encoded_path = backup_path.encode();
+
+			let result = unsafe {
+				ffi::import_state_backup(
+					self.eid,
+					&mut retval,
+					encoded_path.as_ptr(),
+					encoded_path.len() as u32,
+				)
+			};
+
+			ensure!(result == sgx_status_t::SGX_SUCCESS, Error::Sgx(result));
+			ensure!(retval == sgx_status_t::SGX_SUCCESS, Error::Sgx(retval));
+
+			Ok(())
+		}
 	}
 
 	fn init_parentchain_components_ffi(
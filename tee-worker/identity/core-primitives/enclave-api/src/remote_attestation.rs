@@ -17,7 +17,7 @@
 */
 
 use crate::EnclaveResult;
-use itp_types::{Fmspc, ShardIdentifier};
+use itp_types::{Fmspc, ShardIdentifier, SidechainBlockConfirmation};
 use sgx_types::*;
 
 /// Struct that unites all relevant data reported by the QVE
@@ -106,6 +106,11 @@ pub trait TlsRemoteAttestation {
 		skip_ra: bool,
 	) -> EnclaveResult<()>;
 
+	/// Request keys and state from a peer, the state must not be older than `confirmed_block`.
+	///
+	/// `provisioned_payloads` is a bit mask of the payloads sealed so far, it is updated
+	/// even if the provisioning fails and can be passed on to resume with another peer.
+	#[allow(clippy::too_many_arguments)]
 	fn request_state_provisioning(
 		&self,
 		socket_fd: c_int,
@@ -113,6 +118,8 @@ pub trait TlsRemoteAttestation {
 		quoting_enclave_target_info: Option<&sgx_target_info_t>,
 		quote_size: Option<&u32>,
 		shard: &ShardIdentifier,
+		confirmed_block: Option<SidechainBlockConfirmation>,
+		provisioned_payloads: &mut u8,
 		skip_ra: bool,
 	) -> EnclaveResult<()>;
 }
@@ -125,7 +132,7 @@ mod impl_ffi {
 	use frame_support::ensure;
 	use itp_enclave_api_ffi as ffi;
 	use itp_settings::worker::EXTRINSIC_MAX_SIZE;
-	use itp_types::{Fmspc, ShardIdentifier};
+	use itp_types::{Fmspc, ShardIdentifier, SidechainBlockConfirmation};
 	use log::*;
 	use sgx_types::*;
 
@@ -779,11 +786,14 @@ mod impl_ffi {
 			quoting_enclave_target_info: Option<&sgx_target_info_t>,
 			quote_size: Option<&u32>,
 			shard: &ShardIdentifier,
+			confirmed_block: Option<SidechainBlockConfirmation>,
+			provisioned_payloads: &mut u8,
 			skip_ra: bool,
 		) -> EnclaveResult<()> {
 			let mut retval = sgx_status_t::SGX_SUCCESS;
 
 			let encoded_shard = shard.encode();
+			let encoded_confirmed_block = confirmed_block.encode();
 
 			let result = unsafe {
 				ffi::request_state_provisioning(
@@ -795,6 +805,9 @@ mod impl_ffi {
 					quote_size,
 					encoded_shard.as_ptr(),
 					encoded_shard.len() as u32,
+					encoded_confirmed_block.as_ptr(),
+					encoded_confirmed_block.len() as u32,
+					provisioned_payloads,
 					skip_ra.into(),
 				)
 			};
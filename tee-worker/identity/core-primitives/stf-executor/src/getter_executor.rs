@@ -28,11 +28,20 @@ use std::{marker::PhantomData, sync::Arc, time::Instant, vec::Vec};
 
 /// Trait to execute a getter for a specific shard.
 pub trait ExecuteGetter {
+	type StateType;
+
 	fn execute_getter(
 		&self,
 		shard: &ShardIdentifier,
 		encoded_signed_getter: Vec<u8>,
 	) -> Result<Option<Vec<u8>>>;
+
+	/// Execute a getter on the given state instead of the most recent state of the shard.
+	fn execute_getter_on_state(
+		&self,
+		state: &mut Self::StateType,
+		encoded_signed_getter: Vec<u8>,
+	) -> Result<Option<Vec<u8>>>;
 }
 
 pub struct GetterExecutor<StateObserver, StateGetter, G>
@@ -59,6 +68,8 @@ where
 	StateGetter: GetState<StateObserver::StateType, G>,
 	G: PartialEq + Decode + GetterAuthorization,
 {
+	type StateType = StateObserver::StateType;
+
 	fn execute_getter(
 		&self,
 		shard: &ShardIdentifier,
@@ -76,6 +87,15 @@ where
 
 		Ok(state_result)
 	}
+
+	fn execute_getter_on_state(
+		&self,
+		state: &mut Self::StateType,
+		encoded_signed_getter: Vec<u8>,
+	) -> Result<Option<Vec<u8>>> {
+		let getter = G::decode(&mut encoded_signed_getter.as_slice())?;
+		StateGetter::get_state(getter, state)
+	}
 }
 
 #[cfg(test)]
@@ -115,6 +135,20 @@ mod tests {
 		assert_eq!(decoded_state, test_state);
 	}
 
+	#[test]
+	fn executing_getter_on_given_state_works() {
+		let state_observer = Arc::new(TestStateObserver::new(23489u64));
+		let getter_executor = TestGetterExecutor::new(state_observer);
+		let getter = GetterMock::trusted(dummy_trusted_getter());
+
+		let state_result = getter_executor
+			.execute_getter_on_state(&mut 42u64, getter.encode())
+			.unwrap()
+			.unwrap();
+		let decoded_state: TestState = Decode::decode(&mut state_result.as_slice()).unwrap();
+		assert_eq!(decoded_state, 42u64);
+	}
+
 	#[test]
 	fn executing_public_getter_works() {
 		let test_state = 23489u64;
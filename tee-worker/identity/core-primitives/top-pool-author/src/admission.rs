@@ -0,0 +1,335 @@
+// Copyright 2020-2024 Trust Computing GmbH.
+// This file is part of Litentry.
+//
+// Litentry is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// Litentry is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with Litentry.  If not, see <https://www.gnu.org/licenses/>.
+
+//! Admission control of trusted calls, so a single identity cannot flood the enclave.
+//!
+//! Every sender identity gets a quota of calls per time window. VC requests are additionally
+//! capped by the number of requests that are processed concurrently, per identity and in total.
+//! Calls of the enclave itself and callbacks are not subject to admission control.
+
+#[cfg(all(not(feature = "std"), feature = "sgx"))]
+use crate::sgx_reexport_prelude::*;
+
+#[cfg(feature = "sgx")]
+use std::sync::SgxMutex as Mutex;
+
+#[cfg(feature = "std")]
+use std::sync::Mutex;
+
+use derive_more::Display;
+use itp_stf_primitives::types::PriorityClass;
+pub use itp_types::AdmissionConfig;
+use litentry_primitives::Identity;
+use log::*;
+use std::{collections::BTreeMap, sync::Arc, time::Instant, vec::Vec};
+
+/// Number of identities whose quota windows are tracked at most. Above it, expired windows are
+/// pruned and then the oldest window is evicted, which resets the quota of its identity.
+const MAX_TRACKED_IDENTITIES: usize = 10_000;
+
+#[derive(Clone, Debug, Display, PartialEq, Eq)]
+pub enum AdmissionError {
+	#[display(fmt = "Quota of {} calls per {} seconds exceeded", _0, _1)]
+	QuotaExceeded(u32, u64),
+	#[display(fmt = "Too many concurrent VC requests of this identity (max {})", _0)]
+	TooManyVcRequestsOfIdentity(u32),
+	#[display(fmt = "Too many concurrent VC requests (max {})", _0)]
+	TooManyVcRequests(u32),
+	#[display(fmt = "Lock poisoning")]
+	LockPoisoning,
+}
+
+#[derive(Default)]
+struct AdmissionState {
+	windows: BTreeMap<Identity, (Instant, u32)>,
+	vc_requests: BTreeMap<Identity, u32>,
+	vc_requests_total: u32,
+}
+
+/// Keeps track of the calls and VC requests per sender identity.
+pub struct AdmissionControl {
+	config: AdmissionConfig,
+	state: Arc<Mutex<AdmissionState>>,
+}
+
+impl Default for AdmissionControl {
+	fn default() -> Self {
+		Self::new(AdmissionConfig::default())
+	}
+}
+
+impl AdmissionControl {
+	pub fn new(config: AdmissionConfig) -> Self {
+		Self { config, state: Default::default() }
+	}
+
+	/// Counts a call of `sender` against its quota, fails if the quota is exhausted.
+	pub fn admit_call(
+		&self,
+		sender: &Identity,
+		class: PriorityClass,
+	) -> Result<(), AdmissionError> {
+		self.admit_call_at(sender, class, Instant::now())
+	}
+
+	pub(crate) fn admit_call_at(
+		&self,
+		sender: &Identity,
+		class: PriorityClass,
+		now: Instant,
+	) -> Result<(), AdmissionError> {
+		if !class.is_rate_limited() {
+			return Ok(())
+		}
+
+		let window = self.config.window;
+		let mut state = self.state.lock().map_err(|_| AdmissionError::LockPoisoning)?;
+		if state.windows.len() >= MAX_TRACKED_IDENTITIES && !state.windows.contains_key(sender) {
+			state
+				.windows
+				.retain(|_, (start, _)| now.saturating_duration_since(*start) < window);
+			if state.windows.len() >= MAX_TRACKED_IDENTITIES {
+				let oldest = state
+					.windows
+					.iter()
+					.min_by_key(|(_, (start, _))| *start)
+					.map(|(identity, _)| identity.clone());
+				if let Some(oldest) = oldest {
+					state.windows.remove(&oldest);
+				}
+			}
+		}
+
+		let (start, calls) = state.windows.entry(sender.clone()).or_insert((now, 0));
+		if now.saturating_duration_since(*start) >= window {
+			*start = now;
+			*calls = 0;
+		}
+		if *calls >= self.config.max_calls_per_window {
+			debug!("Rejecting call of {:?}, quota exhausted", sender);
+			return Err(AdmissionError::QuotaExceeded(
+				self.config.max_calls_per_window,
+				window.as_secs(),
+			))
+		}
+		*calls += 1;
+		Ok(())
+	}
+
+	/// Reserves `assertions` VC issuance slots for `sender`.
+	///
+	/// The slots are released when the returned permit is dropped.
+	pub fn admit_vc_request(
+		&self,
+		sender: &Identity,
+		assertions: u32,
+	) -> Result<VcRequestPermit, AdmissionError> {
+		let mut state = self.state.lock().map_err(|_| AdmissionError::LockPoisoning)?;
+
+		let in_progress = state.vc_requests.get(sender).copied().unwrap_or_default();
+		if in_progress.saturating_add(assertions)
+			> self.config.max_concurrent_vc_requests_per_identity
+		{
+			return Err(AdmissionError::TooManyVcRequestsOfIdentity(
+				self.config.max_concurrent_vc_requests_per_identity,
+			))
+		}
+		if state.vc_requests_total.saturating_add(assertions)
+			> self.config.max_concurrent_vc_requests
+		{
+			return Err(AdmissionError::TooManyVcRequests(self.config.max_concurrent_vc_requests))
+		}
+
+		state.vc_requests.insert(sender.clone(), in_progress + assertions);
+		state.vc_requests_total += assertions;
+
+		Ok(VcRequestPermit { sender: sender.clone(), assertions, state: self.state.clone() })
+	}
+
+	#[cfg(test)]
+	fn vc_requests_in_progress(&self) -> u32 {
+		self.state.lock().unwrap().vc_requests_total
+	}
+
+	#[cfg(test)]
+	fn tracked_identities(&self) -> usize {
+		self.state.lock().unwrap().windows.len()
+	}
+}
+
+/// Releases the reserved VC issuance slots when dropped.
+pub struct VcRequestPermit {
+	sender: Identity,
+	assertions: u32,
+	state: Arc<Mutex<AdmissionState>>,
+}
+
+impl VcRequestPermit {
+	/// Splits the permit into one permit per reserved slot, so each slot can be released
+	/// as soon as its assertion is issued.
+	pub fn split(mut self) -> Vec<VcRequestPermit> {
+		let permits = (0..self.assertions)
+			.map(|_| VcRequestPermit {
+				sender: self.sender.clone(),
+				assertions: 1,
+				state: self.state.clone(),
+			})
+			.collect();
+		// the slots are released by the split permits
+		self.assertions = 0;
+		permits
+	}
+}
+
+impl Drop for VcRequestPermit {
+	fn drop(&mut self) {
+		if self.assertions == 0 {
+			return
+		}
+		let mut state = match self.state.lock() {
+			Ok(state) => state,
+			Err(e) => {
+				error!("Failed to release VC request slots: {:?}", e);
+				return
+			},
+		};
+		state.vc_requests_total = state.vc_requests_total.saturating_sub(self.assertions);
+		let remaining = state
+			.vc_requests
+			.get(&self.sender)
+			.copied()
+			.unwrap_or_default()
+			.saturating_sub(self.assertions);
+		if remaining == 0 {
+			state.vc_requests.remove(&self.sender);
+		} else {
+			state.vc_requests.insert(self.sender.clone(), remaining);
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use sp_core::{sr25519, Pair};
+	use std::time::Duration;
+
+	fn identity(seed: u8) -> Identity {
+		Identity::from(sr25519::Pair::from_seed(&[seed; 32]).public())
+	}
+
+	fn config() -> AdmissionConfig {
+		AdmissionConfig {
+			window: Duration::from_secs(10),
+			max_calls_per_window: 2,
+			max_concurrent_vc_requests_per_identity: 3,
+			max_concurrent_vc_requests: 4,
+		}
+	}
+
+	#[test]
+	fn calls_above_quota_are_rejected_until_window_expires() {
+		let admission = AdmissionControl::new(config());
+		let now = Instant::now();
+
+		assert!(admission.admit_call_at(&identity(1), PriorityClass::User, now).is_ok());
+		assert!(admission.admit_call_at(&identity(1), PriorityClass::Bulk, now).is_ok());
+		assert_eq!(
+			admission.admit_call_at(&identity(1), PriorityClass::User, now),
+			Err(AdmissionError::QuotaExceeded(2, 10))
+		);
+		// other identities have their own quota
+		assert!(admission.admit_call_at(&identity(2), PriorityClass::User, now).is_ok());
+
+		let later = now + Duration::from_secs(10);
+		assert!(admission.admit_call_at(&identity(1), PriorityClass::User, later).is_ok());
+	}
+
+	#[test]
+	fn tracked_identities_are_capped_by_evicting_the_oldest_window() {
+		let admission = AdmissionControl::new(config());
+		let now = Instant::now();
+		let oldest = Identity::from(sr25519::Public::from_raw([0; 32]));
+		assert!(admission.admit_call_at(&oldest, PriorityClass::User, now).is_ok());
+		assert!(admission.admit_call_at(&oldest, PriorityClass::User, now).is_ok());
+
+		let later = now + Duration::from_secs(1);
+		for i in 1..MAX_TRACKED_IDENTITIES as u32 {
+			let mut account = [0u8; 32];
+			account[..4].copy_from_slice(&i.to_le_bytes());
+			let sender = Identity::from(sr25519::Public::from_raw(account));
+			assert!(admission.admit_call_at(&sender, PriorityClass::User, later).is_ok());
+		}
+		assert_eq!(MAX_TRACKED_IDENTITIES, admission.tracked_identities());
+
+		assert!(admission.admit_call_at(&identity(1), PriorityClass::User, later).is_ok());
+
+		assert_eq!(MAX_TRACKED_IDENTITIES, admission.tracked_identities());
+		// the quota of the evicted identity starts over
+		assert!(admission.admit_call_at(&oldest, PriorityClass::User, later).is_ok());
+	}
+
+	#[test]
+	fn internal_calls_and_callbacks_are_not_rate_limited() {
+		let admission = AdmissionControl::new(config());
+		let now = Instant::now();
+
+		for _ in 0..10 {
+			assert!(admission.admit_call_at(&identity(1), PriorityClass::Internal, now).is_ok());
+			assert!(admission.admit_call_at(&identity(1), PriorityClass::Callback, now).is_ok());
+		}
+		assert!(admission.admit_call_at(&identity(1), PriorityClass::User, now).is_ok());
+	}
+
+	#[test]
+	fn vc_requests_are_capped_per_identity_and_in_total() {
+		let admission = AdmissionControl::new(config());
+
+		let permit = admission.admit_vc_request(&identity(1), 3).unwrap();
+		assert_eq!(
+			admission.admit_vc_request(&identity(1), 1).err(),
+			Some(AdmissionError::TooManyVcRequestsOfIdentity(3))
+		);
+		assert_eq!(
+			admission.admit_vc_request(&identity(2), 2).err(),
+			Some(AdmissionError::TooManyVcRequests(4))
+		);
+		let _other_permit = admission.admit_vc_request(&identity(2), 1).unwrap();
+		assert_eq!(4, admission.vc_requests_in_progress());
+
+		drop(permit);
+
+		assert_eq!(1, admission.vc_requests_in_progress());
+		assert!(admission.admit_vc_request(&identity(1), 3).is_ok());
+	}
+
+	#[test]
+	fn split_permits_release_their_slot_each() {
+		let admission = AdmissionControl::new(config());
+		let mut permits = admission.admit_vc_request(&identity(1), 3).unwrap().split();
+		assert_eq!(3, permits.len());
+		assert_eq!(3, admission.vc_requests_in_progress());
+
+		permits.pop();
+
+		assert_eq!(2, admission.vc_requests_in_progress());
+		assert!(admission.admit_vc_request(&identity(1), 1).is_ok());
+
+		drop(permits);
+
+		assert_eq!(0, admission.vc_requests_in_progress());
+	}
+}
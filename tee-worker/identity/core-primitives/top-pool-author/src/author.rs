@@ -19,16 +19,27 @@
 use crate::sgx_reexport_prelude::*;
 use core::fmt::Debug;
 
+#[cfg(feature = "sgx")]
+use std::sync::SgxRwLock as RwLock;
+
+#[cfg(feature = "std")]
+use std::sync::RwLock;
+
 use crate::{
+	admission::{AdmissionConfig, AdmissionControl, AdmissionError, VcRequestPermit},
 	client_error::Error as ClientError,
 	error::{Error as StateRpcError, Result},
 	top_filter::Filter,
+	top_pool_seal::{PendingOperations, PersistTopPool, RestoreOutcomes, TopPoolSealer},
 	traits::{AuthorApi, OnBlockImported},
 };
 use codec::{Decode, Encode};
 use itp_enclave_metrics::EnclaveMetric;
 use itp_ocall_api::EnclaveMetricsOCallApi;
-use itp_sgx_crypto::{key_repository::AccessKey, ShieldingCryptoDecrypt};
+use itp_sgx_crypto::{
+	key_repository::{AccessKey, RotatedShieldingKey},
+	ShieldingCryptoDecrypt,
+};
 use itp_stf_primitives::{
 	traits::{PoolTransactionValidation, TrustedCallVerification},
 	types::{AccountId, Hash, TrustedOperation as StfTrustedOperation, TrustedOperationOrHash},
@@ -41,13 +52,18 @@ use itp_top_pool::{
 		TrustedOperationSource, TxHash,
 	},
 };
-use itp_types::{BlockHash as SidechainBlockHash, DecryptableRequest, ShardIdentifier};
+use itp_types::{
+	BlockHash as SidechainBlockHash, DecryptableRequest, ShardIdentifier, TrustedOperationStatus,
+};
 use itp_utils::hex::ToHexPrefixed;
 use jsonrpc_core::{
-	futures::future::{ready, TryFutureExt},
+	futures::{
+		executor,
+		future::{ready, TryFutureExt},
+	},
 	Error as RpcError,
 };
-use litentry_primitives::BroadcastedRequest;
+use litentry_primitives::{BroadcastedRequest, Identity};
 use log::*;
 use sp_runtime::generic;
 use std::{
@@ -113,6 +129,14 @@ pub struct Author<
 	shielding_key_repo: Arc<ShieldingKeyRepository>,
 	ocall_api: Arc<OCallApi>,
 	request_sink: Arc<SyncSender<BroadcastedRequest>>,
+	top_pool_sealer: Option<Arc<TopPoolSealer>>,
+	/// Final status of the sealed operations that could not be restored after a restart.
+	restore_outcomes: RwLock<RestoreOutcomes>,
+	admission_control: AdmissionControl,
+	/// MRENCLAVE and signer of this enclave. Signatures of trusted calls are verified against
+	/// the MRENCLAVE before admission, and only calls of the signer get the priority classes
+	/// of the enclave.
+	enclave_identity: RwLock<Option<([u8; 32], Identity)>>,
 }
 
 impl<
@@ -163,8 +187,46 @@ impl<
 			shielding_key_repo: encryption_key,
 			ocall_api,
 			request_sink,
+			top_pool_sealer: None,
+			restore_outcomes: RwLock::new(RestoreOutcomes::default()),
+			admission_control: AdmissionControl::default(),
+			enclave_identity: RwLock::new(None),
 		}
 	}
+
+	/// Verify the signatures of trusted calls against `mrenclave` and accept calls of the
+	/// `Internal` and `Callback` classes only from `enclave_signer`.
+	///
+	/// Without it, signatures are left to the executor and such calls are rejected.
+	pub fn with_enclave_identity(mut self, mrenclave: [u8; 32], enclave_signer: Identity) -> Self {
+		self.enclave_identity = RwLock::new(Some((mrenclave, enclave_signer)));
+		self
+	}
+
+	/// Replace the signer of this enclave, after the shielding key it's derived from was rotated.
+	pub fn update_enclave_signer(&self, enclave_signer: Identity) -> Result<()> {
+		let mut enclave_identity =
+			self.enclave_identity.write().map_err(|_| StateRpcError::LockPoisoning)?;
+		if let Some((_, signer)) = enclave_identity.as_mut() {
+			*signer = enclave_signer;
+		}
+		Ok(())
+	}
+
+	/// Replace the default quotas of the admission control.
+	pub fn with_admission_config(mut self, config: AdmissionConfig) -> Self {
+		self.admission_control = AdmissionControl::new(config);
+		self
+	}
+
+	/// Seal the pending trusted calls, so they can be restored after a restart.
+	pub fn with_top_pool_seal(
+		mut self,
+		top_pool_seal: Arc<dyn PersistTopPool + Send + Sync>,
+	) -> Self {
+		self.top_pool_sealer = Some(Arc::new(TopPoolSealer::new(top_pool_seal)));
+		self
+	}
 }
 
 enum TopSubmissionMode {
@@ -238,7 +300,7 @@ impl<
 		};
 
 		// decrypt call
-		let shielding_key = match self.shielding_key_repo.retrieve_key() {
+		let shielding_key = match RotatedShieldingKey::retrieve(&*self.shielding_key_repo) {
 			Ok(k) => k,
 			Err(_) => return Box::pin(ready(Err(ClientError::BadFormatDecipher.into()))),
 		};
@@ -262,6 +324,13 @@ impl<
 			return Box::pin(ready(Err(ClientError::UnsupportedOperation.into())))
 		}
 
+		if let Some(trusted_call_signed) = trusted_operation.to_call() {
+			if let Err(e) = self.admit_trusted_call(trusted_call_signed, &shard) {
+				warn!("Rejecting trusted call: {}", e);
+				return Box::pin(ready(Err(e.into())))
+			}
+		}
+
 		//let best_block_hash = self.client.info().best_hash;
 		// dummy block hash
 		let best_block_hash = Default::default();
@@ -285,7 +354,9 @@ impl<
 			);
 		}
 
-		match submission_mode {
+		let is_trusted_call = trusted_operation.to_call().is_some();
+
+		let submission: PoolFuture<TxHash, RpcError> = match submission_mode {
 			TopSubmissionMode::Submit => Box::pin(
 				self.top_pool
 					.submit_one(
@@ -335,6 +406,167 @@ impl<
 				}
 				result
 			},
+		};
+
+		if is_trusted_call {
+			self.seal_on_success(submission, shard)
+		} else {
+			submission
+		}
+	}
+
+	/// Verifies the signature of `call` and counts it against the quota of its sender.
+	fn admit_trusted_call(
+		&self,
+		call: &TCS,
+		shard: &ShardIdentifier,
+	) -> core::result::Result<(), ClientError> {
+		let class = call.priority_class();
+		let enclave_identity = self
+			.enclave_identity
+			.read()
+			.map_err(|_| ClientError::Client("Lock poisoning".into()))?;
+		match enclave_identity.as_ref() {
+			Some((mrenclave, enclave_signer)) => {
+				if !call.verify_signature(mrenclave, shard) {
+					return Err(ClientError::Verification)
+				}
+				if !class.is_rate_limited() && call.sender_identity() != enclave_signer {
+					return Err(ClientError::UnsupportedOperation)
+				}
+			},
+			None =>
+				if !class.is_rate_limited() {
+					return Err(ClientError::UnsupportedOperation)
+				},
+		}
+		self.admission_control
+			.admit_call(call.sender_identity(), class)
+			.map_err(ClientError::RateLimited)
+	}
+
+	/// Seals the pending trusted calls of `shard` once `submission` made it into the pool.
+	fn seal_on_success(
+		&self,
+		submission: PoolFuture<TxHash, RpcError>,
+		shard: ShardIdentifier,
+	) -> PoolFuture<TxHash, RpcError> {
+		let top_pool_sealer = match self.top_pool_sealer.clone() {
+			Some(sealer) => sealer,
+			None => return submission,
+		};
+		let top_pool = self.top_pool.clone();
+		Box::pin(submission.map_ok(move |hash| {
+			top_pool_sealer.note_change(&shard);
+			seal_pending_trusted_calls::<TopPool, TCS, G>(
+				&*top_pool,
+				&top_pool_sealer,
+				shard,
+				false,
+			);
+			hash
+		}))
+	}
+
+	/// Seals the pending trusted calls of `shard` that were not sealed yet because of the
+	/// debounce of the seals.
+	pub fn seal_pending_tops(&self, shard: ShardIdentifier) {
+		if let Some(top_pool_sealer) = self.top_pool_sealer.as_ref() {
+			seal_pending_trusted_calls::<TopPool, TCS, G>(
+				&*self.top_pool,
+				top_pool_sealer,
+				shard,
+				true,
+			);
+		}
+	}
+
+	/// Re-submits the trusted calls that were sealed for `shard` before the last shutdown.
+	///
+	/// Has to be called once the state of the shard is synced, calls are validated against it.
+	/// Until then, changes of the pool of the shard are not sealed. Calls which are no longer
+	/// accepted by the filter or the pool are not restored. Their final status can be queried
+	/// with [`AuthorApi::get_trusted_operation_status`].
+	/// Returns the number of restored calls, 0 if the shard was already restored.
+	pub fn restore_pending_tops(&self, shard: ShardIdentifier) -> Result<usize> {
+		let top_pool_sealer = match self.top_pool_sealer.as_ref() {
+			Some(sealer) => sealer,
+			None => return Ok(0),
+		};
+		let sealed_operations = match top_pool_sealer.restore(&shard)? {
+			Some(operations) => operations,
+			None => return Ok(0),
+		};
+
+		let mut restored = 0;
+		for encoded_operation in sealed_operations {
+			let trusted_operation =
+				match StfTrustedOperation::<TCS, G>::decode(&mut encoded_operation.as_slice()) {
+					Ok(op) => op,
+					Err(e) => {
+						warn!("Failed to decode sealed trusted operation, dropping it: {:?}", e);
+						continue
+					},
+				};
+			let hash = self.hash_of(&trusted_operation);
+
+			if !self.top_filter.filter(&trusted_operation) {
+				warn!("Sealed trusted operation {:?} is no longer supported, dropping it", hash);
+				self.record_restore_outcome(hash, TrustedOperationStatus::Dropped);
+				continue
+			}
+
+			let submission = self.top_pool.submit_one(
+				&generic::BlockId::hash(Default::default()),
+				TX_SOURCE,
+				trusted_operation,
+				shard,
+			);
+			let result = executor::block_on(submission)
+				.map_err(|e| e.into_pool_error().unwrap_or_else(|_| PoolError::Verification));
+			match result {
+				Ok(_) | Err(PoolError::AlreadyImported) => {
+					if let Err(e) =
+						self.ocall_api.update_metric(EnclaveMetric::TopPoolSizeIncrement)
+					{
+						warn!("Failed to update metric for top pool size: {:?}", e);
+					}
+					restored += 1;
+				},
+				Err(
+					PoolError::ImmediatelyDropped
+					| PoolError::TemporarilyBanned
+					| PoolError::TooLowPriority(_),
+				) => {
+					warn!("Sealed trusted operation {:?} was dropped by the pool", hash);
+					self.record_restore_outcome(hash, TrustedOperationStatus::Dropped);
+				},
+				Err(e) => {
+					warn!("Sealed trusted operation {:?} is no longer valid: {:?}", hash, e);
+					self.record_restore_outcome(hash, TrustedOperationStatus::Invalid);
+				},
+			}
+		}
+
+		// re-seal what actually made it into the pool, so dropped calls are not restored again
+		top_pool_sealer.note_change(&shard);
+		seal_pending_trusted_calls::<TopPool, TCS, G>(
+			&*self.top_pool,
+			top_pool_sealer,
+			shard,
+			true,
+		);
+		info!("Restored {} pending trusted calls of shard {:?}", restored, shard);
+
+		Ok(restored)
+	}
+
+	fn record_restore_outcome(&self, hash: TxHash, status: TrustedOperationStatus) {
+		match self.restore_outcomes.write() {
+			Ok(mut outcomes) => {
+				outcomes.insert(hash, status);
+			},
+			Err(e) => error!("Failed to record status of trusted operation {:?}: {:?}", hash, e),
 		}
 	}
 
@@ -377,6 +609,28 @@ impl<
 	}
 }
 
+/// Seals the trusted calls of `shard` that are currently pending in the pool, if they changed
+/// since the last seal. Getters are short-lived and are not sealed.
+fn seal_pending_trusted_calls<P, TCS, G>(
+	top_pool: &P,
+	top_pool_sealer: &TopPoolSealer,
+	shard: ShardIdentifier,
+	force: bool,
+) where
+	P: TrustedOperationPool<StfTrustedOperation<TCS, G>>,
+	TCS: PartialEq + Encode + Debug,
+	G: PartialEq + Encode + Debug,
+{
+	top_pool_sealer.seal_if_due(&shard, force, || -> PendingOperations {
+		top_pool
+			.ready(shard)
+			.chain(top_pool.futures(shard))
+			.filter(|o| !matches!(o.data(), StfTrustedOperation::<TCS, G>::get(_)))
+			.map(|o| o.data().encode())
+			.collect()
+	});
+}
+
 fn map_top_error<P: TrustedOperationPool<StfTrustedOperation<TCS, G>>, TCS, G>(
 	error: P::Error,
 ) -> RpcError
@@ -514,6 +768,15 @@ impl<
 				failed_to_remove.push(executed_call);
 			}
 		}
+		if let Some(top_pool_sealer) = self.top_pool_sealer.as_ref() {
+			top_pool_sealer.note_change(&shard);
+			seal_pending_trusted_calls::<TopPool, TCS, G>(
+				&*self.top_pool,
+				top_pool_sealer,
+				shard,
+				false,
+			);
+		}
 		failed_to_remove
 	}
 
@@ -543,6 +806,31 @@ impl<
 	fn swap_rpc_connection_hash(&self, old_hash: TxHash, new_hash: TxHash) {
 		self.top_pool.swap_rpc_connection_hash(old_hash, new_hash)
 	}
+
+	fn get_trusted_operation_status(
+		&self,
+		shard: ShardIdentifier,
+		hash: &TxHash,
+	) -> Option<TrustedOperationStatus> {
+		if self.top_pool.ready_transaction(hash, shard).is_some() {
+			return Some(TrustedOperationStatus::Ready)
+		}
+		if self.top_pool.futures(shard).iter().any(|o| o.hash() == *hash) {
+			return Some(TrustedOperationStatus::Future)
+		}
+		self.restore_outcomes
+			.read()
+			.ok()
+			.and_then(|outcomes| outcomes.get(hash).cloned())
+	}
+
+	fn admit_vc_request(
+		&self,
+		sender: &Identity,
+		assertions: u32,
+	) -> core::result::Result<VcRequestPermit, AdmissionError> {
+		self.admission_control.admit_vc_request(sender, assertions)
+	}
 }
 
 impl<
@@ -16,10 +16,12 @@
 */
 
 use crate::{
+	admission::AdmissionConfig,
 	author::Author,
 	test_fixtures::shard_id,
 	test_utils::submit_operation_to_top_pool,
 	top_filter::{AllowAllTopsFilter, DirectCallsOnlyFilter, Filter, GettersOnlyFilter},
+	top_pool_seal::{PendingOperations, PersistTopPool, SealedOperations},
 	traits::AuthorApi,
 };
 use codec::{Decode, Encode};
@@ -36,10 +38,14 @@ use itp_test::mock::{
 	},
 };
 use itp_top_pool::mocks::trusted_operation_pool_mock::TrustedOperationPoolMock;
+use itp_types::{ShardIdentifier, TrustedOperationStatus};
 use itp_utils::ToHexPrefixed;
 use litentry_primitives::BroadcastedRequest;
 use sgx_crypto_helper::{rsa3072::Rsa3072KeyPair, RsaKeyPair};
-use std::sync::Arc;
+use std::{
+	collections::HashMap,
+	sync::{Arc, RwLock},
+};
 
 type TestAuthor<Filter, BroadcastedFilter> = Author<
 	TrustedOperationPoolMock<TrustedOperationMock>,
@@ -160,6 +166,160 @@ fn submitting_indirect_call_works() {
 	assert_eq!(1, author.get_pending_trusted_calls(shard_id()).len());
 }
 
+#[test]
+fn submitting_trusted_call_seals_pending_calls() {
+	let top_pool_seal = Arc::new(TopPoolSealMock::default());
+	let (author, _top_pool, shielding_key, _) =
+		create_author_with_filter(AllowAllTopsFilter::new(), DirectCallsOnlyFilter::new());
+	let author = author.with_top_pool_seal(top_pool_seal.clone());
+	author.restore_pending_tops(shard_id()).unwrap();
+	let top_call = mock_top_direct_trusted_call_signed();
+
+	let _ = submit_operation_to_top_pool(&author, &top_call, &shielding_key, shard_id(), false)
+		.unwrap();
+	author.seal_pending_tops(shard_id());
+
+	assert_eq!(top_pool_seal.restore(&shard_id()).unwrap().operations, vec![top_call.encode()]);
+}
+
+#[test]
+fn submitting_trusted_call_before_restoring_does_not_overwrite_sealed_calls() {
+	// given
+	let sealed_call = mock_top_indirect_trusted_call_signed();
+	let top_pool_seal = Arc::new(TopPoolSealMock::default());
+	top_pool_seal.persist(&shard_id(), &sealed(vec![sealed_call.encode()])).unwrap();
+	let (author, _top_pool, shielding_key, _) =
+		create_author_with_filter(AllowAllTopsFilter::new(), DirectCallsOnlyFilter::new());
+	let author = author.with_top_pool_seal(top_pool_seal.clone());
+	let top_call = mock_top_direct_trusted_call_signed();
+
+	// when
+	let _ = submit_operation_to_top_pool(&author, &top_call, &shielding_key, shard_id(), false)
+		.unwrap();
+	author.seal_pending_tops(shard_id());
+
+	// then
+	assert_eq!(top_pool_seal.restore(&shard_id()).unwrap().operations, vec![sealed_call.encode()]);
+}
+
+#[test]
+fn submitting_getter_does_not_seal_pending_calls() {
+	let top_pool_seal = Arc::new(TopPoolSealMock::default());
+	let (author, _top_pool, shielding_key, _) =
+		create_author_with_filter(AllowAllTopsFilter::new(), DirectCallsOnlyFilter::new());
+	let author = author.with_top_pool_seal(top_pool_seal.clone());
+	author.restore_pending_tops(shard_id()).unwrap();
+	let top_getter = mock_top_trusted_getter_signed();
+
+	let _ = submit_operation_to_top_pool(&author, &top_getter, &shielding_key, shard_id(), false)
+		.unwrap();
+	author.seal_pending_tops(shard_id());
+
+	assert!(top_pool_seal.restore(&shard_id()).unwrap().operations.is_empty());
+}
+
+#[test]
+fn restoring_resubmits_sealed_trusted_calls() {
+	// given
+	let top_call = mock_top_direct_trusted_call_signed();
+	let top_pool_seal = Arc::new(TopPoolSealMock::default());
+	top_pool_seal.persist(&shard_id(), &sealed(vec![top_call.encode()])).unwrap();
+	let (author, top_pool, _, _) =
+		create_author_with_filter(AllowAllTopsFilter::new(), DirectCallsOnlyFilter::new());
+	let author = author.with_top_pool_seal(top_pool_seal.clone());
+
+	// when
+	let restored = author.restore_pending_tops(shard_id()).unwrap();
+
+	// then
+	assert_eq!(1, restored);
+	assert_eq!(1, top_pool.get_last_submitted_transactions().len());
+	assert_eq!(
+		Some(TrustedOperationStatus::Ready),
+		author.get_trusted_operation_status(shard_id(), &author.hash_of(&top_call))
+	);
+	assert_eq!(top_pool_seal.restore(&shard_id()).unwrap().operations, vec![top_call.encode()]);
+	// a shard is only restored once
+	assert_eq!(0, author.restore_pending_tops(shard_id()).unwrap());
+}
+
+#[test]
+fn restoring_drops_sealed_calls_rejected_by_filter() {
+	// given
+	let top_call = mock_top_direct_trusted_call_signed();
+	let top_pool_seal = Arc::new(TopPoolSealMock::default());
+	top_pool_seal.persist(&shard_id(), &sealed(vec![top_call.encode()])).unwrap();
+	let (author, top_pool, _, _) =
+		create_author_with_filter(GettersOnlyFilter::new(), DirectCallsOnlyFilter::new());
+	let author = author.with_top_pool_seal(top_pool_seal.clone());
+
+	// when
+	let restored = author.restore_pending_tops(shard_id()).unwrap();
+
+	// then
+	assert_eq!(0, restored);
+	assert!(top_pool.get_last_submitted_transactions().is_empty());
+	assert_eq!(
+		Some(TrustedOperationStatus::Dropped),
+		author.get_trusted_operation_status(shard_id(), &author.hash_of(&top_call))
+	);
+	assert!(top_pool_seal.restore(&shard_id()).unwrap().operations.is_empty());
+}
+
+#[test]
+fn restoring_without_seal_does_nothing() {
+	let (author, top_pool, _, _) =
+		create_author_with_filter(AllowAllTopsFilter::new(), DirectCallsOnlyFilter::new());
+
+	assert_eq!(0, author.restore_pending_tops(shard_id()).unwrap());
+	assert!(top_pool.get_last_submitted_transactions().is_empty());
+}
+
+#[test]
+fn submitting_calls_above_quota_returns_error() {
+	let (author, _top_pool, shielding_key, _) =
+		create_author_with_filter(AllowAllTopsFilter::new(), DirectCallsOnlyFilter::new());
+	let author = author
+		.with_admission_config(AdmissionConfig { max_calls_per_window: 1, ..Default::default() });
+	let top_call = mock_top_direct_trusted_call_signed();
+	let top_getter = mock_top_trusted_getter_signed();
+
+	assert!(
+		submit_operation_to_top_pool(&author, &top_call, &shielding_key, shard_id(), false).is_ok()
+	);
+	let rejected =
+		submit_operation_to_top_pool(&author, &top_call, &shielding_key, shard_id(), false);
+	// getters are not counted against the quota
+	assert!(submit_operation_to_top_pool(&author, &top_getter, &shielding_key, shard_id(), false)
+		.is_ok());
+
+	assert_eq!(rejected.unwrap_err().message, "Rate limit exceeded");
+}
+
+fn sealed(operations: PendingOperations) -> SealedOperations {
+	SealedOperations { sequence: 1, operations }
+}
+
+#[derive(Default)]
+struct TopPoolSealMock {
+	operations: RwLock<HashMap<ShardIdentifier, SealedOperations>>,
+}
+
+impl PersistTopPool for TopPoolSealMock {
+	fn persist(
+		&self,
+		shard: &ShardIdentifier,
+		sealed: &SealedOperations,
+	) -> crate::error::Result<()> {
+		self.operations.write().unwrap().insert(*shard, sealed.clone());
+		Ok(())
+	}
+
+	fn restore(&self, shard: &ShardIdentifier) -> crate::error::Result<SealedOperations> {
+		Ok(self.operations.read().unwrap().get(shard).cloned().unwrap_or_default())
+	}
+}
+
 fn create_author_with_filter<
 	F: Filter<Value = TrustedOperationMock>,
 	BF: Filter<Value = TrustedOperationMock>,
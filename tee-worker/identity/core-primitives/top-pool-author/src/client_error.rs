@@ -68,6 +68,9 @@ pub enum Error {
 	/// Unsupported trusted operation (in case we allow only certain types of operations, using filters)
 	#[display(fmt = "Unsupported operation type")]
 	UnsupportedOperation,
+	/// The sender exceeded its admission quota.
+	#[display(fmt = "Rate limited: {}", _0)]
+	RateLimited(crate::admission::AdmissionError),
 }
 
 impl std::error::Error for Error {
@@ -104,6 +107,8 @@ const POOL_CYCLE_DETECTED: i64 = POOL_INVALID_TX + 5;
 const POOL_IMMEDIATELY_DROPPED: i64 = POOL_INVALID_TX + 6;
 /// The key type crypto is not known.
 const UNSUPPORTED_KEY_TYPE: i64 = POOL_INVALID_TX + 7;
+/// The sender exceeded its admission quota.
+const RATE_LIMITED: i64 = BASE_ERROR + 20;
 
 impl From<Error> for rpc_core::Error {
 	fn from(e: Error) -> Self {
@@ -173,6 +178,11 @@ impl From<Error> for rpc_core::Error {
 					request to insert the key successfully.".into()
 				),
 			},
+			Error::RateLimited(reason) => rpc_core::Error {
+				code: rpc_core::ErrorCode::ServerError(RATE_LIMITED),
+				message: "Rate limit exceeded".into(),
+				data: Some(format!("{}", reason).into()),
+			},
 			e => rpc_core::Error {
 				code: rpc_core::ErrorCode::InternalError,
 				message: "Unknown error occurred".into(),
@@ -65,6 +65,12 @@ pub enum Error {
 
 	#[display(fmt = "Codec error: {}", _0)]
 	CodecError(codec::Error),
+
+	#[display(fmt = "IO error: {}", _0)]
+	IoError(std::io::Error),
+
+	#[display(fmt = "Lock poisoning")]
+	LockPoisoning,
 }
 
 impl error::Error for Error {
@@ -31,11 +31,13 @@ pub mod sgx_reexport_prelude {
 	pub use jsonrpc_core_sgx as jsonrpc_core;
 }
 
+pub mod admission;
 pub mod api;
 pub mod author;
 pub mod client_error;
 pub mod error;
 pub mod top_filter;
+pub mod top_pool_seal;
 pub mod traits;
 
 #[cfg(test)]
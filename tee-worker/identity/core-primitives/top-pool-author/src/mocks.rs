@@ -26,6 +26,7 @@ use std::sync::SgxRwLock as RwLock;
 use std::sync::RwLock;
 
 use crate::{
+	admission::{AdmissionControl, AdmissionError, VcRequestPermit},
 	error::Result,
 	traits::{AuthorApi, OnBlockImported},
 };
@@ -35,9 +36,10 @@ use itp_stf_primitives::{
 	types::{AccountId, TrustedOperation as StfTrustedOperation, TrustedOperationOrHash},
 };
 use itp_top_pool::primitives::{PoolFuture, PoolStatus};
-use itp_types::{DecryptableRequest, ShardIdentifier};
+use itp_types::{DecryptableRequest, ShardIdentifier, TrustedOperationStatus};
 use jsonrpc_core::{futures::future::ready, Error as RpcError};
 use lazy_static::lazy_static;
+use litentry_primitives::Identity;
 use sp_core::{blake2_256, H256};
 #[cfg(feature = "sgx")]
 use std::sync::SgxMutex as Mutex;
@@ -71,6 +73,7 @@ where
 	tops: RwLock<HashMap<ShardIdentifier, Vec<Vec<u8>>>>,
 	_phantom: PhantomData<(Hash, BlockHash, TCS, G)>,
 	pub remove_attempts: RwLock<usize>,
+	admission_control: AdmissionControl,
 }
 
 impl<Hash, BlockHash, TCS, G> AuthorApiMock<Hash, BlockHash, TCS, G>
@@ -267,6 +270,22 @@ where
 	fn send_rpc_response(&self, _hash: H256, _encoded_value: Vec<u8>, _do_watch: bool) {}
 
 	fn swap_rpc_connection_hash(&self, _old_hash: H256, _new_hash: H256) {}
+
+	fn get_trusted_operation_status(
+		&self,
+		_shard: ShardIdentifier,
+		_hash: &H256,
+	) -> Option<TrustedOperationStatus> {
+		None
+	}
+
+	fn admit_vc_request(
+		&self,
+		sender: &Identity,
+		assertions: u32,
+	) -> core::result::Result<VcRequestPermit, AdmissionError> {
+		self.admission_control.admit_vc_request(sender, assertions)
+	}
 }
 
 impl<TCS, G> OnBlockImported for AuthorApiMock<H256, H256, TCS, G>
@@ -0,0 +1,398 @@
+// Copyright 2020-2024 Trust Computing GmbH.
+// This file is part of Litentry.
+//
+// Litentry is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// Litentry is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with Litentry.  If not, see <https://www.gnu.org/licenses/>.
+
+//! Sealing of the pending trusted operations, so that they survive a restart of the worker.
+//!
+//! All seals go through the [`TopPoolSealer`], the single writer of the sealed files. Every
+//! change of the pool of a shard gets a sequence number, and a snapshot of the pool is only
+//! sealed if it reflects changes that are not sealed yet. Seals triggered by submissions are
+//! debounced, the block production flushes the remaining changes once per slot.
+
+#[cfg(feature = "sgx")]
+use std::sync::SgxMutex as Mutex;
+
+#[cfg(feature = "std")]
+use std::sync::Mutex;
+
+use crate::error::Result;
+use codec::{Decode, Encode};
+use itp_settings::files::TOP_POOL_PATH;
+use itp_top_pool::primitives::TxHash;
+use itp_types::{ShardIdentifier, TrustedOperationStatus};
+use itp_utils::ToHexPrefixed;
+use log::*;
+use std::{
+	collections::{HashMap, VecDeque},
+	format,
+	io::ErrorKind,
+	path::PathBuf,
+	sync::Arc,
+	time::{Duration, Instant},
+	vec::Vec,
+};
+
+#[cfg(feature = "sgx")]
+use itp_sgx_io::{seal, unseal};
+
+#[cfg(feature = "std")]
+use itp_sgx_io::{read as unseal, write as seal};
+
+/// Minimum time between two debounced seals of the same shard.
+pub const SEAL_DEBOUNCE: Duration = Duration::from_secs(1);
+
+/// Maximum number of final statuses of operations that could not be restored, the oldest are
+/// dropped first.
+pub const MAX_RESTORE_OUTCOMES: usize = 10_000;
+
+/// Encoded trusted operations that were pending in the pool of one shard.
+pub type PendingOperations = Vec<Vec<u8>>;
+
+/// Pending operations of a shard, with the sequence number of the last pool change they reflect.
+#[derive(Encode, Decode, Clone, Debug, Default, PartialEq, Eq)]
+pub struct SealedOperations {
+	pub sequence: u64,
+	pub operations: PendingOperations,
+}
+
+/// Persists the pending trusted operations of a shard across restarts.
+pub trait PersistTopPool {
+	/// Replaces the persisted operations of `shard` with `sealed`.
+	fn persist(&self, shard: &ShardIdentifier, sealed: &SealedOperations) -> Result<()>;
+
+	/// Returns the persisted operations of `shard`, empty if nothing was persisted yet.
+	fn restore(&self, shard: &ShardIdentifier) -> Result<SealedOperations>;
+}
+
+/// Seals the pending operations of each shard to its own file in the `TOP_POOL_PATH` directory.
+#[derive(Clone, Debug)]
+pub struct TopPoolSeal {
+	base_path: PathBuf,
+}
+
+impl TopPoolSeal {
+	pub fn new(base_path: PathBuf) -> Self {
+		Self { base_path }
+	}
+
+	fn directory(&self) -> PathBuf {
+		self.base_path.join(TOP_POOL_PATH)
+	}
+
+	fn file_path(&self, shard: &ShardIdentifier) -> PathBuf {
+		self.directory().join(format!("{}.bin", shard.encode().to_hex()))
+	}
+}
+
+impl PersistTopPool for TopPoolSeal {
+	fn persist(&self, shard: &ShardIdentifier, sealed: &SealedOperations) -> Result<()> {
+		std::fs::create_dir_all(self.directory())?;
+		Ok(sealed.using_encoded(|bytes| seal(bytes, self.file_path(shard)))?)
+	}
+
+	fn restore(&self, shard: &ShardIdentifier) -> Result<SealedOperations> {
+		let bytes = match unseal(self.file_path(shard)) {
+			Ok(bytes) => bytes,
+			Err(e) if e.kind() == ErrorKind::NotFound => return Ok(Default::default()),
+			Err(e) => return Err(e.into()),
+		};
+		Ok(Decode::decode(&mut bytes.as_slice())?)
+	}
+}
+
+#[derive(Default)]
+struct ShardSealState {
+	changed: u64,
+	sealed: u64,
+	sealed_at: Option<Instant>,
+}
+
+/// Single writer of the sealed pending operations.
+pub struct TopPoolSealer {
+	seal: Arc<dyn PersistTopPool + Send + Sync>,
+	/// Only shards that were restored are sealed, so that their sealed operations are not
+	/// overwritten before they are restored.
+	shards: Mutex<HashMap<ShardIdentifier, ShardSealState>>,
+}
+
+impl TopPoolSealer {
+	pub fn new(seal: Arc<dyn PersistTopPool + Send + Sync>) -> Self {
+		Self { seal, shards: Default::default() }
+	}
+
+	/// Returns the sealed operations of `shard` and starts to seal its changes.
+	///
+	/// Returns `None` if the shard was already restored.
+	pub fn restore(&self, shard: &ShardIdentifier) -> Result<Option<PendingOperations>> {
+		let mut shards = self.shards.lock().map_err(|_| crate::error::Error::LockPoisoning)?;
+		if shards.contains_key(shard) {
+			return Ok(None)
+		}
+		let sealed = self.seal.restore(shard)?;
+		shards.insert(
+			*shard,
+			ShardSealState { changed: sealed.sequence, sealed: sealed.sequence, sealed_at: None },
+		);
+		Ok(Some(sealed.operations))
+	}
+
+	/// Notes a change of the pool of `shard`.
+	pub fn note_change(&self, shard: &ShardIdentifier) {
+		if let Ok(mut shards) = self.shards.lock() {
+			if let Some(state) = shards.get_mut(shard) {
+				state.changed = state.changed.saturating_add(1);
+			}
+		}
+	}
+
+	/// Seals the operations returned by `snapshot` if `shard` changed since its last seal and,
+	/// unless `force` is set, the last seal is at least [`SEAL_DEBOUNCE`] old.
+	pub fn seal_if_due(
+		&self,
+		shard: &ShardIdentifier,
+		force: bool,
+		snapshot: impl FnOnce() -> PendingOperations,
+	) {
+		self.seal_if_due_at(shard, force, Instant::now(), snapshot)
+	}
+
+	fn seal_if_due_at(
+		&self,
+		shard: &ShardIdentifier,
+		force: bool,
+		now: Instant,
+		snapshot: impl FnOnce() -> PendingOperations,
+	) {
+		// the lock is held while sealing, so a snapshot is never overwritten by an older one
+		let mut shards = match self.shards.lock() {
+			Ok(shards) => shards,
+			Err(e) => {
+				error!("Failed to lock the top pool sealer: {:?}", e);
+				return
+			},
+		};
+		let state = match shards.get_mut(shard) {
+			Some(state) => state,
+			None => return,
+		};
+		if state.changed == state.sealed {
+			return
+		}
+		let debounced = state
+			.sealed_at
+			.map_or(false, |at| now.saturating_duration_since(at) < SEAL_DEBOUNCE);
+		if debounced && !force {
+			return
+		}
+
+		let sealed = SealedOperations { sequence: state.changed, operations: snapshot() };
+		match self.seal.persist(shard, &sealed) {
+			Ok(()) => {
+				state.sealed = sealed.sequence;
+				state.sealed_at = Some(now);
+			},
+			Err(e) => error!("Failed to seal pending trusted calls of shard {:?}: {:?}", shard, e),
+		}
+	}
+}
+
+/// Final statuses of the sealed operations that could not be restored, bounded by
+/// [`MAX_RESTORE_OUTCOMES`].
+#[derive(Default)]
+pub struct RestoreOutcomes {
+	statuses: HashMap<TxHash, TrustedOperationStatus>,
+	order: VecDeque<TxHash>,
+}
+
+impl RestoreOutcomes {
+	pub fn insert(&mut self, hash: TxHash, status: TrustedOperationStatus) {
+		if self.statuses.insert(hash, status).is_none() {
+			self.order.push_back(hash);
+		}
+		while self.order.len() > MAX_RESTORE_OUTCOMES {
+			if let Some(oldest) = self.order.pop_front() {
+				self.statuses.remove(&oldest);
+			}
+		}
+	}
+
+	pub fn get(&self, hash: &TxHash) -> Option<&TrustedOperationStatus> {
+		self.statuses.get(hash)
+	}
+
+	pub fn len(&self) -> usize {
+		self.order.len()
+	}
+
+	pub fn is_empty(&self) -> bool {
+		self.order.is_empty()
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::test_fixtures::shard_id;
+	use std::{
+		env::temp_dir,
+		sync::RwLock,
+		time::{SystemTime, UNIX_EPOCH},
+	};
+
+	fn unique_base_path() -> PathBuf {
+		let nanos = SystemTime::now().duration_since(UNIX_EPOCH).unwrap().as_nanos();
+		temp_dir().join(format!("top_pool_seal_test_{}", nanos))
+	}
+
+	fn sealed(sequence: u64, operations: PendingOperations) -> SealedOperations {
+		SealedOperations { sequence, operations }
+	}
+
+	#[derive(Default)]
+	struct SealMock {
+		sealed: RwLock<HashMap<ShardIdentifier, SealedOperations>>,
+	}
+
+	impl PersistTopPool for SealMock {
+		fn persist(&self, shard: &ShardIdentifier, sealed: &SealedOperations) -> Result<()> {
+			self.sealed.write().unwrap().insert(*shard, sealed.clone());
+			Ok(())
+		}
+
+		fn restore(&self, shard: &ShardIdentifier) -> Result<SealedOperations> {
+			Ok(self.sealed.read().unwrap().get(shard).cloned().unwrap_or_default())
+		}
+	}
+
+	#[test]
+	fn restore_without_sealed_file_returns_empty_list() {
+		let seal = TopPoolSeal::new(unique_base_path());
+
+		assert_eq!(seal.restore(&shard_id()).unwrap(), SealedOperations::default());
+	}
+
+	#[test]
+	fn persisted_operations_are_restored_per_shard() {
+		let base_path = unique_base_path();
+		let seal = TopPoolSeal::new(base_path.clone());
+		let other_shard = ShardIdentifier::repeat_byte(7);
+		let operations = sealed(3, vec![vec![1u8, 2, 3], vec![4u8]]);
+
+		seal.persist(&shard_id(), &operations).unwrap();
+
+		assert_eq!(seal.restore(&shard_id()).unwrap(), operations);
+		assert!(seal.restore(&other_shard).unwrap().operations.is_empty());
+
+		std::fs::remove_dir_all(base_path).unwrap();
+	}
+
+	#[test]
+	fn persisting_replaces_previous_operations() {
+		let base_path = unique_base_path();
+		let seal = TopPoolSeal::new(base_path.clone());
+
+		seal.persist(&shard_id(), &sealed(1, vec![vec![1u8], vec![2u8]])).unwrap();
+		seal.persist(&shard_id(), &sealed(2, vec![vec![2u8]])).unwrap();
+
+		assert_eq!(seal.restore(&shard_id()).unwrap(), sealed(2, vec![vec![2u8]]));
+
+		std::fs::remove_dir_all(base_path).unwrap();
+	}
+
+	#[test]
+	fn changes_are_not_sealed_before_the_shard_is_restored() {
+		// given
+		let seal = Arc::new(SealMock::default());
+		seal.persist(&shard_id(), &sealed(5, vec![vec![1u8]])).unwrap();
+		let sealer = TopPoolSealer::new(seal.clone());
+
+		// when
+		sealer.note_change(&shard_id());
+		sealer.seal_if_due(&shard_id(), true, || vec![vec![2u8]]);
+
+		// then
+		assert_eq!(seal.restore(&shard_id()).unwrap(), sealed(5, vec![vec![1u8]]));
+		assert_eq!(sealer.restore(&shard_id()).unwrap(), Some(vec![vec![1u8]]));
+		assert_eq!(sealer.restore(&shard_id()).unwrap(), None);
+	}
+
+	#[test]
+	fn seals_continue_the_restored_sequence() {
+		// given
+		let seal = Arc::new(SealMock::default());
+		seal.persist(&shard_id(), &sealed(5, vec![vec![1u8]])).unwrap();
+		let sealer = TopPoolSealer::new(seal.clone());
+		sealer.restore(&shard_id()).unwrap();
+
+		// when
+		sealer.note_change(&shard_id());
+		sealer.seal_if_due(&shard_id(), false, || vec![vec![2u8]]);
+
+		// then
+		assert_eq!(seal.restore(&shard_id()).unwrap(), sealed(6, vec![vec![2u8]]));
+	}
+
+	#[test]
+	fn unchanged_shard_is_not_sealed_again() {
+		let seal = Arc::new(SealMock::default());
+		let sealer = TopPoolSealer::new(seal.clone());
+		sealer.restore(&shard_id()).unwrap();
+
+		sealer.seal_if_due(&shard_id(), true, || vec![vec![2u8]]);
+
+		assert_eq!(seal.restore(&shard_id()).unwrap(), SealedOperations::default());
+	}
+
+	#[test]
+	fn seals_are_debounced_unless_forced() {
+		// given
+		let seal = Arc::new(SealMock::default());
+		let sealer = TopPoolSealer::new(seal.clone());
+		sealer.restore(&shard_id()).unwrap();
+		let now = Instant::now();
+		sealer.note_change(&shard_id());
+		sealer.seal_if_due_at(&shard_id(), false, now, || vec![vec![1u8]]);
+
+		// when
+		sealer.note_change(&shard_id());
+		sealer.seal_if_due_at(&shard_id(), false, now, || vec![vec![2u8]]);
+
+		// then
+		assert_eq!(seal.restore(&shard_id()).unwrap(), sealed(1, vec![vec![1u8]]));
+
+		sealer.seal_if_due_at(&shard_id(), false, now + SEAL_DEBOUNCE, || vec![vec![2u8]]);
+		assert_eq!(seal.restore(&shard_id()).unwrap(), sealed(2, vec![vec![2u8]]));
+
+		sealer.note_change(&shard_id());
+		sealer.seal_if_due_at(&shard_id(), true, now + SEAL_DEBOUNCE, || vec![vec![3u8]]);
+		assert_eq!(seal.restore(&shard_id()).unwrap(), sealed(3, vec![vec![3u8]]));
+	}
+
+	#[test]
+	fn restore_outcomes_drop_the_oldest_above_limit() {
+		let mut outcomes = RestoreOutcomes::default();
+
+		for i in 0..=MAX_RESTORE_OUTCOMES as u64 {
+			outcomes.insert(TxHash::from_low_u64_be(i), TrustedOperationStatus::Dropped);
+		}
+
+		assert_eq!(outcomes.len(), MAX_RESTORE_OUTCOMES);
+		assert!(outcomes.get(&TxHash::from_low_u64_be(0)).is_none());
+		assert_eq!(
+			outcomes.get(&TxHash::from_low_u64_be(MAX_RESTORE_OUTCOMES as u64)),
+			Some(&TrustedOperationStatus::Dropped)
+		);
+	}
+}
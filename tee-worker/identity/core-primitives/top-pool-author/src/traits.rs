@@ -20,13 +20,20 @@ use crate::sgx_reexport_prelude::*;
 use codec::Encode;
 use core::fmt::Debug;
 
-use crate::error::Result;
+use crate::{
+	admission::{AdmissionError, VcRequestPermit},
+	error::Result,
+};
 use itp_stf_primitives::types::{
 	AccountId, TrustedOperation as StfTrustedOperation, TrustedOperationOrHash,
 };
 use itp_top_pool::primitives::{PoolFuture, PoolStatus};
-use itp_types::{BlockHash as SidechainBlockHash, DecryptableRequest, ShardIdentifier, H256};
+use itp_types::{
+	BlockHash as SidechainBlockHash, DecryptableRequest, ShardIdentifier, TrustedOperationStatus,
+	H256,
+};
 use jsonrpc_core::Error as RpcError;
+use litentry_primitives::Identity;
 use std::{string::String, vec::Vec};
 
 /// Trait alias for a full STF author API
@@ -101,6 +108,23 @@ where
 
 	/// Litentry: swap the old hash with the new one in rpc connection registry
 	fn swap_rpc_connection_hash(&self, old_hash: Hash, new_hash: Hash);
+
+	/// Returns the status of a trusted operation that is pending in the pool, or the final
+	/// status of an operation that could not be restored after a restart.
+	fn get_trusted_operation_status(
+		&self,
+		shard: ShardIdentifier,
+		hash: &Hash,
+	) -> Option<TrustedOperationStatus>;
+
+	/// Reserves VC issuance slots for `assertions` requested by `sender`.
+	///
+	/// The slots are released once the returned permit is dropped.
+	fn admit_vc_request(
+		&self,
+		sender: &Identity,
+		assertions: u32,
+	) -> core::result::Result<VcRequestPermit, AdmissionError>;
 }
 
 /// Trait to notify listeners/observer of a newly created block
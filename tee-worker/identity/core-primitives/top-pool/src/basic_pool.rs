@@ -207,6 +207,10 @@ where
 		Box::new(self.pool.validated_pool().ready(shard))
 	}
 
+	fn futures(&self, shard: ShardIdentifier) -> Vec<Arc<Self::InPoolOperation>> {
+		self.pool.validated_pool().futures(shard)
+	}
+
 	fn shards(&self) -> Vec<ShardIdentifier> {
 		self.pool.validated_pool().shards()
 	}
@@ -164,6 +164,10 @@ where
 		Box::new(ready_transactions.into_iter())
 	}
 
+	fn futures(&self, _shard: ShardIdentifier) -> Vec<Arc<Self::InPoolOperation>> {
+		Vec::new()
+	}
+
 	fn shards(&self) -> Vec<ShardIdentifier> {
 		let transactions = self.submitted_transactions.read().unwrap();
 		transactions.iter().map(|(shard, _)| *shard).collect()
@@ -205,10 +209,10 @@ where
 
 	fn ready_transaction(
 		&self,
-		_hash: &TxHash,
-		_shard: ShardIdentifier,
+		hash: &TxHash,
+		shard: ShardIdentifier,
 	) -> Option<Arc<Self::InPoolOperation>> {
-		unimplemented!()
+		self.ready(shard).find(|top| top.hash == *hash)
 	}
 
 	fn on_block_imported(&self, _hashes: &[TxHash], _block_hash: SidechainBlockHash) {}
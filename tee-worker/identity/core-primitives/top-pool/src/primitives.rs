@@ -216,6 +216,9 @@ pub trait TrustedOperationPool<TOP>: Send + Sync {
 		shard: ShardIdentifier,
 	) -> Box<dyn Iterator<Item = Arc<Self::InPoolOperation>> + Send>;
 
+	/// Get all operations of a shard that are waiting in the future queue.
+	fn futures(&self, shard: ShardIdentifier) -> Vec<Arc<Self::InPoolOperation>>;
+
 	/// Get an iterator over all shards.
 	fn shards(&self) -> Vec<ShardIdentifier>;
 
@@ -658,6 +658,11 @@ where
 		self.pool.read().unwrap().ready(shard)
 	}
 
+	/// Get all operations waiting in the future queue
+	pub fn futures(&self, shard: ShardIdentifier) -> Vec<TransactionFor<TOP>> {
+		self.pool.read().unwrap().futures(shard).cloned().map(Arc::new).collect()
+	}
+
 	/// Get an iterator for all shards
 	pub fn shards(&self) -> Vec<ShardIdentifier> {
 		let mut shards = vec![];
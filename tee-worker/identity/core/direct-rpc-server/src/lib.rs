@@ -98,7 +98,7 @@ pub type ForceWait = bool;
 /// Registry for RPC connections (i.e. connections that are kept alive to send updates).
 pub trait RpcConnectionRegistry: Send + Sync {
 	type Hash: RpcHash;
-	type Connection: Copy + Debug;
+	type Connection: Copy + Debug + PartialEq;
 
 	fn store(
 		&self,
@@ -111,6 +111,9 @@ pub trait RpcConnectionRegistry: Send + Sync {
 	fn withdraw(&self, hash: &Self::Hash) -> Option<(Self::Connection, RpcResponse, ForceWait)>;
 
 	fn is_force_wait(&self, hash: &Self::Hash) -> bool;
+
+	/// Withdraw all hashes watched by `connection`, e.g. once it is closed.
+	fn withdraw_connection(&self, connection: &Self::Connection) -> Vec<Self::Hash>;
 }
 
 /// Sends an RPC response back to the client.
@@ -23,14 +23,14 @@ use std::sync::RwLock;
 
 use crate::{ForceWait, RpcConnectionRegistry, RpcHash};
 use itp_rpc::RpcResponse;
-use std::{collections::HashMap, fmt::Debug};
+use std::{collections::HashMap, fmt::Debug, vec::Vec};
 
 type HashMapLock<K, V> = RwLock<HashMap<K, V>>;
 
 pub struct ConnectionRegistry<Hash, Token>
 where
 	Hash: RpcHash,
-	Token: Copy + Send + Sync + Debug,
+	Token: Copy + Send + Sync + Debug + PartialEq,
 {
 	connection_map:
 		HashMapLock<<Self as RpcConnectionRegistry>::Hash, (Token, RpcResponse, ForceWait)>,
@@ -39,7 +39,7 @@ where
 impl<Hash, Token> ConnectionRegistry<Hash, Token>
 where
 	Hash: RpcHash,
-	Token: Copy + Send + Sync + Debug,
+	Token: Copy + Send + Sync + Debug + PartialEq,
 {
 	pub fn new() -> Self {
 		Self::default()
@@ -54,7 +54,7 @@ where
 impl<Hash, Token> Default for ConnectionRegistry<Hash, Token>
 where
 	Hash: RpcHash,
-	Token: Copy + Send + Sync + Debug,
+	Token: Copy + Send + Sync + Debug + PartialEq,
 {
 	fn default() -> Self {
 		ConnectionRegistry { connection_map: RwLock::new(HashMap::default()) }
@@ -64,7 +64,7 @@ where
 impl<Hash, Token> RpcConnectionRegistry for ConnectionRegistry<Hash, Token>
 where
 	Hash: RpcHash,
-	Token: Copy + Send + Sync + Debug,
+	Token: Copy + Send + Sync + Debug + PartialEq,
 {
 	type Hash = Hash;
 	type Connection = Token;
@@ -93,6 +93,19 @@ where
 			false
 		}
 	}
+
+	fn withdraw_connection(&self, connection: &Self::Connection) -> Vec<Self::Hash> {
+		let mut map = self.connection_map.write().expect("Lock poisoning");
+		let hashes: Vec<Self::Hash> = map
+			.iter()
+			.filter(|(_, (c, _, _))| c == connection)
+			.map(|(hash, _)| hash.clone())
+			.collect();
+		for hash in hashes.iter() {
+			map.remove(hash);
+		}
+		hashes
+	}
 }
 
 #[cfg(test)]
@@ -135,6 +148,22 @@ pub mod tests {
 		assert!(registry.is_empty());
 	}
 
+	#[test]
+	pub fn withdrawing_connection_removes_all_its_hashes() {
+		let registry = TestRegistry::new();
+
+		registry.store("first".to_string(), 1, dummy_rpc_response(), false);
+		registry.store("second".to_string(), 1, dummy_rpc_response(), false);
+		registry.store("third".to_string(), 2, dummy_rpc_response(), false);
+
+		let mut hashes = registry.withdraw_connection(&1);
+		hashes.sort();
+
+		assert_eq!(hashes, vec!["first".to_string(), "second".to_string()]);
+		assert!(registry.withdraw(&"third".to_string()).is_some());
+		assert!(registry.is_empty());
+	}
+
 	fn dummy_rpc_response() -> RpcResponse {
 		RpcResponse { jsonrpc: String::new(), result: Default::default(), id: Id::Number(1u32) }
 	}
@@ -22,7 +22,10 @@ use crate::{DetermineWatch, RpcConnectionRegistry, RpcHash};
 use itc_tls_websocket_server::{error::WebSocketResult, ConnectionToken, WebSocketMessageHandler};
 use jsonrpc_core::IoHandler;
 use log::*;
-use std::{string::String, sync::Arc};
+use std::{boxed::Box, string::String, sync::Arc};
+
+/// Called with the watched hashes of a connection once it is closed.
+pub type ConnectionClosedCallback<Hash> = Box<dyn Fn(&[Hash]) + Send + Sync>;
 
 pub struct RpcWsHandler<Watcher, Registry, Hash>
 where
@@ -33,6 +36,7 @@ where
 	rpc_io_handler: IoHandler,
 	connection_watcher: Arc<Watcher>,
 	connection_registry: Arc<Registry>,
+	connection_closed_callback: Option<ConnectionClosedCallback<Hash>>,
 }
 
 impl<Watcher, Registry, Hash> RpcWsHandler<Watcher, Registry, Hash>
@@ -46,7 +50,20 @@ where
 		connection_watcher: Arc<Watcher>,
 		connection_registry: Arc<Registry>,
 	) -> Self {
-		RpcWsHandler { rpc_io_handler, connection_watcher, connection_registry }
+		RpcWsHandler {
+			rpc_io_handler,
+			connection_watcher,
+			connection_registry,
+			connection_closed_callback: None,
+		}
+	}
+
+	pub fn with_connection_closed_callback(
+		mut self,
+		callback: ConnectionClosedCallback<Hash>,
+	) -> Self {
+		self.connection_closed_callback = Some(callback);
+		self
 	}
 }
 
@@ -83,6 +100,17 @@ where
 
 		Ok(maybe_rpc_response)
 	}
+
+	fn handle_connection_closed(&self, connection_token: ConnectionToken) {
+		let hashes = self.connection_registry.withdraw_connection(&connection_token.into());
+		if hashes.is_empty() {
+			return
+		}
+		debug!("Withdrew {} watched hashes of closed connection", hashes.len());
+		if let Some(callback) = self.connection_closed_callback.as_ref() {
+			callback(&hashes);
+		}
+	}
 }
 
 #[cfg(test)]
@@ -166,6 +194,28 @@ pub mod tests {
 		assert!(connection_registry.is_empty());
 	}
 
+	#[test]
+	fn closing_connection_withdraws_its_hashes_and_calls_back() {
+		let io_handler = create_io_handler_with_method(RPC_METHOD_NAME);
+
+		let connection_hash = String::from("connection_hash");
+		let (connection_token, message) = create_message_to_handle(RPC_METHOD_NAME);
+
+		let (ws_handler, connection_registry) =
+			create_ws_handler(io_handler, Some(connection_hash.clone()));
+		let closed_hashes = Arc::new(std::sync::Mutex::new(Vec::new()));
+		let closed_hashes_cloned = closed_hashes.clone();
+		let ws_handler = ws_handler.with_connection_closed_callback(Box::new(move |hashes| {
+			closed_hashes_cloned.lock().unwrap().extend_from_slice(hashes)
+		}));
+
+		ws_handler.handle_message(connection_token, message).unwrap();
+		ws_handler.handle_connection_closed(connection_token);
+
+		assert!(connection_registry.is_empty());
+		assert_eq!(*closed_hashes.lock().unwrap(), vec![connection_hash]);
+	}
+
 	fn create_message_to_handle(method_name: &str) -> (ConnectionToken, String) {
 		let json_rpc_pre_method = r#"{"jsonrpc": "2.0", "method": ""#;
 		let json_rpc_post_method = r#"", "params": {}, "id": 1}"#;
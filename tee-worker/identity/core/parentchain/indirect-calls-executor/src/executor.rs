@@ -34,7 +34,10 @@ use itp_node_api::metadata::{
 	provider::AccessNodeMetadata, NodeMetadataTrait,
 };
 use itp_ocall_api::EnclaveMetricsOCallApi;
-use itp_sgx_crypto::{key_repository::AccessKey, ShieldingCryptoDecrypt, ShieldingCryptoEncrypt};
+use itp_sgx_crypto::{
+	key_repository::{AccessKey, RotatedShieldingKey},
+	ShieldingCryptoDecrypt, ShieldingCryptoEncrypt,
+};
 use itp_stf_executor::traits::StfEnclaveSigning;
 use itp_stf_primitives::{
 	traits::{IndirectExecutor, TrustedCallSigning, TrustedCallVerification},
@@ -306,7 +309,7 @@ impl<
 	}
 
 	fn decrypt(&self, encrypted: &[u8]) -> Result<Vec<u8>> {
-		let key = self.shielding_key_repo.retrieve_key()?;
+		let key = RotatedShieldingKey::retrieve(&*self.shielding_key_repo)?;
 		Ok(key.decrypt(encrypted)?)
 	}
 
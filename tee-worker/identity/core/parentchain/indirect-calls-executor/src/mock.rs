@@ -59,6 +59,16 @@ impl FilterEvents for MockEvents {
 		Ok(Vec::new())
 	}
 
+	fn get_assertion_created_with_policy_events(
+		&self,
+	) -> Result<Vec<AssertionCreatedWithPolicy>, Self::Error> {
+		Ok(Vec::new())
+	}
+
+	fn get_assertion_version_events(&self) -> Result<Vec<AssertionVersionEvent>, Self::Error> {
+		Ok(Vec::new())
+	}
+
 	fn get_parentchain_block_proccessed_events(
 		&self,
 	) -> Result<Vec<ParentchainBlockProcessed>, Self::Error> {
@@ -69,6 +79,22 @@ impl FilterEvents for MockEvents {
 		Ok(Vec::new())
 	}
 
+	fn get_btc_signing_policy_set_events(&self) -> Result<Vec<BtcSigningPolicySet>, Self::Error> {
+		Ok(Vec::new())
+	}
+
+	fn get_eth_signing_allowlist_set_events(
+		&self,
+	) -> Result<Vec<EthSigningAllowlistSet>, Self::Error> {
+		Ok(Vec::new())
+	}
+
+	fn get_relayer_permissions_set_events(
+		&self,
+	) -> Result<Vec<RelayerPermissionsSet>, Self::Error> {
+		Ok(Vec::new())
+	}
+
 	fn get_enclave_added_events(&self) -> Result<Vec<EnclaveAdded>, Self::Error> {
 		Ok(Vec::new())
 	}
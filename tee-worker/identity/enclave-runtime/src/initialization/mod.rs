@@ -19,10 +19,11 @@ pub mod global_components;
 pub mod parentchain;
 use crate::{
 	error::{Error, Result as EnclaveResult},
+	get_base_path,
 	initialization::global_components::{
 		EnclaveBlockImportConfirmationHandler, EnclaveGetterExecutor, EnclaveLightClientSeal,
-		EnclaveOCallApi, EnclaveRpcResponder, EnclaveShieldingKeyRepository, EnclaveSidechainApi,
-		EnclaveSidechainBlockImportQueueWorker, EnclaveSidechainBlockImporter,
+		EnclaveOCallApi, EnclaveRpcResponder, EnclaveSealHandler, EnclaveShieldingKeyRepository,
+		EnclaveSidechainApi, EnclaveSidechainBlockImportQueueWorker, EnclaveSidechainBlockImporter,
 		EnclaveSidechainBlockSyncer, EnclaveStateFileIo, EnclaveStateHandler,
 		EnclaveStateInitializer, EnclaveStateObserver, EnclaveStateSnapshotRepository,
 		EnclaveStfEnclaveSigner, EnclaveTopPool, EnclaveTopPoolAuthor,
@@ -41,6 +42,7 @@ use crate::{
 	},
 	ocall::OcallApi,
 	rpc::{common_api::add_common_api, rpc_response_channel::RpcResponseChannel},
+	state_backup::{create_state_backup, restore_state_backup},
 	utils::{
 		get_extrinsic_factory_from_integritee_solo_or_parachain,
 		get_node_metadata_repository_from_integritee_solo_or_parachain,
@@ -50,14 +52,15 @@ use crate::{
 	Hash,
 };
 use base58::ToBase58;
-use codec::Encode;
+use codec::{Decode, Encode};
 use core::str::FromStr;
 use ita_sgx_runtime::Runtime;
-use ita_stf::{aes_encrypt_default, Getter, TrustedCallSigned};
+use ita_stf::{aes_encrypt_default, Getter, TrustedCall, TrustedCallSigned};
 use itc_direct_rpc_server::{
 	create_determine_watch, rpc_connection_registry::ConnectionRegistry,
 	rpc_ws_handler::RpcWsHandler,
 };
+use itc_parentchain::light_client::{LightClientState, LightValidationState};
 use itc_peer_top_broadcaster::init;
 use itc_tls_websocket_server::{
 	certificate_generation::ed25519_self_signed_certificate,
@@ -68,33 +71,55 @@ use itp_attestation_handler::IntelAttestationHandler;
 use itp_component_container::{ComponentGetter, ComponentInitializer};
 use itp_extrinsics_factory::CreateExtrinsics;
 use itp_node_api::metadata::{
-	pallet_omni_account::OmniAccountCallIndexes, provider::AccessNodeMetadata,
+	pallet_omni_account::OmniAccountCallIndexes, pallet_teebag::TeebagCallIndexes,
+	provider::AccessNodeMetadata,
 };
-use itp_ocall_api::EnclaveOnChainOCallApi;
+use itp_ocall_api::{EnclaveAttestationOCallApi, EnclaveOnChainOCallApi};
 use itp_primitives_cache::GLOBAL_PRIMITIVES_CACHE;
 use itp_settings::files::{
 	ASSERTIONS_FILE, LITENTRY_PARENTCHAIN_LIGHT_CLIENT_DB_PATH, STATE_SNAPSHOTS_CACHE_SIZE,
 	TARGET_A_PARENTCHAIN_LIGHT_CLIENT_DB_PATH, TARGET_B_PARENTCHAIN_LIGHT_CLIENT_DB_PATH,
 };
 use itp_sgx_crypto::{
-	get_aes_repository, get_ed25519_repository, get_rsa3072_repository, key_repository::AccessKey,
+	ed25519_derivation::DeriveEd25519,
+	get_aes_repository, get_ed25519_repository, get_rsa3072_repository,
+	key_repository::{AccessKey, AccessPubkey, RotateKey},
+	Aes, ShieldingCryptoEncrypt,
 };
 use itp_sgx_externalities::SgxExternalitiesTrait;
+use itp_sgx_io::{read as io_read, write as io_write};
+use itp_stf_executor::traits::StfEnclaveSigning;
+use itp_stf_primitives::types::TrustedOperation;
 use itp_stf_state_handler::{
-	file_io::StateDir, handle_state::HandleState, query_shard_state::QueryShardState,
+	file_io::StateDir,
+	handle_state::HandleState,
+	query_shard_state::QueryShardState,
+	state_key_rotation::{finish_state_key_rotation, rotate_state_key},
 	state_snapshot_repository::VersionedStateAccess,
-	state_snapshot_repository_loader::StateSnapshotRepositoryLoader, StateHandler,
+	state_snapshot_repository_loader::StateSnapshotRepositoryLoader,
+	StateHandler,
 };
+use itp_time_utils::now_as_millis;
 use itp_top_pool::pool::Options as PoolOptions;
-use itp_top_pool_author::author::{AuthorTopFilter, BroadcastedTopFilter};
-use itp_types::{parentchain::ParentchainId, OpaqueCall, ShardIdentifier};
+use itp_top_pool_author::{
+	author::{AuthorTopFilter, BroadcastedTopFilter},
+	top_pool_seal::TopPoolSeal,
+	traits::AuthorApi,
+};
+use itp_types::{
+	parentchain::ParentchainId, AccountId, AdmissionConfig, Block as ParentchainBlock, OpaqueCall,
+	RsaRequest, ShardIdentifier,
+};
+use its_primitives::types::{state_backup::StateBackupBundle, SignedBlock as SignedSidechainBlock};
 use its_sidechain::{
 	block_composer::BlockComposer,
 	slots::{FailSlotMode, FailSlotOnDemand},
+	validateer_fetch::ValidateerFetch,
 };
-use jsonrpc_core::IoHandler;
+use jsonrpc_core::{futures::executor, IoHandler};
 use lc_data_providers::DataProviderConfig;
-use lc_evm_dynamic_assertions::repository::EvmAssertionRepository;
+use lc_evm_dynamic_assertions::{repository::EvmAssertionRepository, sealing::io::AssertionsSeal};
+use lc_id_graph_subscription::id_graph_subscriptions;
 use lc_native_task_receiver::{run_native_task_receiver, NativeTaskContext};
 use lc_parachain_extrinsic_task_receiver::run_parachain_extrinsic_task_receiver;
 use lc_stf_task_receiver::{run_stf_task_receiver, StfTaskContext};
@@ -103,14 +128,23 @@ use litentry_primitives::{
 	sgx::create_aes256_repository, BroadcastedRequest, Identity, MemberAccount,
 };
 use log::*;
+use sgx_crypto_helper::{rsa3072::Rsa3072KeyPair, RsaKeyPair};
 use sgx_types::sgx_status_t;
-use sp_core::crypto::Pair;
-use std::{collections::HashMap, path::PathBuf, string::String, sync::Arc, vec::Vec};
+use sp_core::{crypto::Pair, ed25519::Public as Ed25519Public};
+use std::{
+	boxed::Box,
+	collections::HashMap,
+	path::PathBuf,
+	string::{String, ToString},
+	sync::Arc,
+	vec::Vec,
+};
 
 pub(crate) fn init_enclave(
 	mu_ra_url: String,
 	untrusted_worker_url: String,
 	base_dir: PathBuf,
+	admission_config: AdmissionConfig,
 ) -> EnclaveResult<()> {
 	let signing_key_repository = Arc::new(get_ed25519_repository(base_dir.clone(), None, None)?);
 	GLOBAL_SIGNING_KEY_REPOSITORY_COMPONENT.initialize(signing_key_repository.clone());
@@ -125,6 +159,9 @@ pub(crate) fn init_enclave(
 	let state_key_repository = Arc::new(get_aes_repository(base_dir.clone())?);
 	GLOBAL_STATE_KEY_REPOSITORY_COMPONENT.initialize(state_key_repository.clone());
 
+	// complete or revert a state key rotation which was interrupted, before any state is loaded
+	finish_state_key_rotation(&StateDir::new(base_dir.clone()), state_key_repository.as_ref())?;
+
 	let account_store_key_repository =
 		Arc::new(create_aes256_repository(base_dir.clone(), "account_store", None)?);
 	GLOBAL_ACCOUNT_STORE_KEY_REPOSITORY_COMPONENT.initialize(account_store_key_repository);
@@ -148,7 +185,7 @@ pub(crate) fn init_enclave(
 	GLOBAL_TARGET_B_PARENTCHAIN_LIGHT_CLIENT_SEAL.initialize(target_b_light_client_seal);
 
 	let state_file_io =
-		Arc::new(EnclaveStateFileIo::new(state_key_repository, StateDir::new(base_dir)));
+		Arc::new(EnclaveStateFileIo::new(state_key_repository, StateDir::new(base_dir.clone())));
 	let state_initializer =
 		Arc::new(EnclaveStateInitializer::new(shielding_key_repository.clone()));
 	let state_snapshot_repository_loader = StateSnapshotRepositoryLoader::<
@@ -211,7 +248,9 @@ pub(crate) fn init_enclave(
 		ocall_api.clone(),
 		shielding_key_repository.clone(),
 		request_sink_cloned,
-	);
+		base_dir,
+		admission_config,
+	)?;
 	GLOBAL_TOP_POOL_AUTHOR_COMPONENT.initialize(top_pool_author.clone());
 
 	GLOBAL_DIRECT_RPC_BROADCASTER_COMPONENT.initialize(broadcaster);
@@ -248,7 +287,13 @@ pub(crate) fn init_enclave(
 		add_sidechain_api(&mut io_handler, sidechain_import_queue);
 	}
 
-	let rpc_handler = Arc::new(RpcWsHandler::new(io_handler, watch_extractor, connection_registry));
+	// ID graph subscriptions end with the connection they are watched on
+	let rpc_handler = Arc::new(
+		RpcWsHandler::new(io_handler, watch_extractor, connection_registry)
+			.with_connection_closed_callback(Box::new(|hashes| {
+				id_graph_subscriptions().connection_closed(hashes)
+			})),
+	);
 	GLOBAL_RPC_WS_HANDLER_COMPONENT.initialize(rpc_handler);
 
 	let attestation_handler =
@@ -400,6 +445,15 @@ pub(crate) fn init_enclave_sidechain_components(
 
 	let signer = GLOBAL_SIGNING_KEY_REPOSITORY_COMPONENT.get()?.retrieve_key()?;
 
+	// the last block stored in the state of an older worker has a header without state root
+	for shard in state_handler.list_shards()? {
+		let (state_lock, mut state) = state_handler.load_for_mutation(&shard)?;
+		if its_sidechain::state::migrate_last_block(&mut state) {
+			info!("Migrated the last sidechain block of shard {:?}", shard);
+			state_handler.write_after_mutation(state, state_lock, &shard)?;
+		}
+	}
+
 	let sidechain_block_importer = Arc::new(EnclaveSidechainBlockImporter::new(
 		state_handler,
 		state_key_repository.clone(),
@@ -595,6 +649,148 @@ pub(crate) fn upload_id_graph() -> EnclaveResult<()> {
 	Ok(())
 }
 
+/// Rotate the shielding key and the state key of the running enclave and publish the new
+/// shielding key to the parentchain.
+///
+/// Requests encrypted with the previous shielding key are accepted for `grace_period_secs`.
+/// The state snapshots are re-encrypted with the new state key.
+///
+/// The enclave account and the vc signing key are derived from the shielding key and change
+/// with it: the enclave account is replaced in the state of every shard with a call signed
+/// by the current enclave account, and the new vc signing key is published along with the
+/// shielding key. VCs issued before the rotation are verified with the previous vc pubkey.
+pub(crate) fn rotate_shielding_key(grace_period_secs: u64) -> EnclaveResult<()> {
+	let ocall_api = GLOBAL_OCALL_API_COMPONENT.get()?;
+	let extrinsic_factory = get_extrinsic_factory_from_integritee_solo_or_parachain()?;
+	let node_metadata_repo = get_node_metadata_repository_from_integritee_solo_or_parachain()?;
+	let shielding_key_repository = GLOBAL_SHIELDING_KEY_REPOSITORY_COMPONENT.get()?;
+	let state_key_repository = GLOBAL_STATE_KEY_REPOSITORY_COMPONENT.get()?;
+	let state_handler = GLOBAL_STATE_HANDLER_COMPONENT.get()?;
+	let top_pool_author = GLOBAL_TOP_POOL_AUTHOR_COMPONENT.get()?;
+
+	let call_index =
+		node_metadata_repo.get_from_metadata(|m| m.rotate_shielding_key_call_indexes())??;
+
+	let shielding_key =
+		Rsa3072KeyPair::new().map_err(|e| Error::Other(format!("{:?}", e).into()))?;
+	let enclave_signer = shielding_key.derive_ed25519()?;
+	let enclave_account: AccountId = enclave_signer.public().into();
+
+	// must be signed before the rotation, the state only knows the current enclave account
+	let stf_enclave_signer = EnclaveStfEnclaveSigner::new(
+		GLOBAL_STATE_OBSERVER_COMPONENT.get()?,
+		ocall_api.clone(),
+		shielding_key_repository.clone(),
+		top_pool_author.clone(),
+	);
+	let current_enclave_account = stf_enclave_signer.get_enclave_account()?;
+	for shard in state_handler.list_shards()? {
+		let call = TrustedCall::rotate_enclave_signer_account(
+			current_enclave_account.clone().into(),
+			enclave_account.clone(),
+		);
+		let signed_call = stf_enclave_signer.sign_call_with_self(&call, &shard)?;
+		let top = TrustedOperation::<TrustedCallSigned, Getter>::direct_call(signed_call);
+		let encrypted_top = shielding_key_repository.retrieve_key()?.encrypt(&top.encode())?;
+		executor::block_on(top_pool_author.watch_and_broadcast_top(
+			RsaRequest::new(shard, encrypted_top),
+			"author_submitAndWatchBroadcastedRsaRequest".to_string(),
+		))
+		.map_err(|e| Error::Other(format!("{:?}", e).into()))?;
+	}
+
+	let expires_at = now_as_millis().saturating_add(grace_period_secs.saturating_mul(1000));
+	shielding_key_repository.rotate_key(shielding_key, expires_at)?;
+	info!("Rotated shielding key, the previous key expires at {}", expires_at);
+	refresh_enclave_signer()?;
+
+	let state_key = Aes::random()?;
+	let state_dir = StateDir::new(get_base_path()?);
+	state_handler.execute_without_snapshot_writes(|| {
+		rotate_state_key(&state_dir, state_key_repository.as_ref(), state_key)
+	})??;
+	info!("Rotated state key");
+
+	let shielding_pubkey = serde_json::to_vec(&shielding_key_repository.retrieve_pubkey()?)
+		.map_err(|e| Error::Other(e.into()))?;
+	let vc_pubkey: Ed25519Public = enclave_signer.public();
+
+	let call = OpaqueCall::from_tuple(&(call_index, shielding_pubkey, Some(vc_pubkey)));
+	extrinsic_factory
+		.create_extrinsics(&[call], None)
+		.map_err(|_| Error::Other("failed to create extrinsic".into()))
+		.and_then(|ext| {
+			ocall_api
+				.send_to_parentchain(ext, &ParentchainId::Litentry, true)
+				.map_err(|_| Error::Other("failed to send extrinsic".into()))
+		})
+}
+
+/// Update the enclave signer of the TOP pool author to the one derived from the current
+/// shielding key.
+pub(crate) fn refresh_enclave_signer() -> EnclaveResult<()> {
+	let enclave_signer: AccountId = GLOBAL_SHIELDING_KEY_REPOSITORY_COMPONENT
+		.get()?
+		.retrieve_key()?
+		.derive_ed25519()?
+		.public()
+		.into();
+	GLOBAL_TOP_POOL_AUTHOR_COMPONENT
+		.get()?
+		.update_enclave_signer(Identity::from(enclave_signer))?;
+	Ok(())
+}
+
+pub(crate) fn export_state_backup(
+	shard: ShardIdentifier,
+	sidechain_blocks: Vec<SignedSidechainBlock>,
+	backup_path: PathBuf,
+) -> EnclaveResult<()> {
+	let bundle = create_state_backup(
+		&create_seal_handler()?,
+		GLOBAL_TARGET_A_PARENTCHAIN_LIGHT_CLIENT_SEAL.get()?.as_ref(),
+		GLOBAL_TARGET_B_PARENTCHAIN_LIGHT_CLIENT_SEAL.get()?.as_ref(),
+		shard,
+		sidechain_blocks,
+	)?;
+	io_write(&bundle.encode(), &backup_path)?;
+	info!("Exported state backup of shard {:?} to {}", shard, backup_path.display());
+	Ok(())
+}
+
+pub(crate) fn import_state_backup(backup_path: PathBuf) -> EnclaveResult<()> {
+	let ocall_api = GLOBAL_OCALL_API_COMPONENT.get()?;
+	let bundle = StateBackupBundle::decode(&mut io_read(&backup_path)?.as_slice())?;
+	restore_state_backup(
+		&create_seal_handler()?,
+		GLOBAL_TARGET_A_PARENTCHAIN_LIGHT_CLIENT_SEAL.get()?.as_ref(),
+		GLOBAL_TARGET_B_PARENTCHAIN_LIGHT_CLIENT_SEAL.get()?.as_ref(),
+		bundle,
+		|mut light_client_state| {
+			// The registered enclaves are read at the finalized header of the backed up light client.
+			let light_client_state =
+				LightValidationState::<ParentchainBlock>::decode(&mut light_client_state)?;
+			let header = light_client_state
+				.latest_finalized_header()
+				.map_err(|e| Error::Other(format!("{:?}", e).into()))?;
+			ocall_api
+				.current_validateers(&header)
+				.map_err(|e| Error::Other(format!("{:?}", e).into()))
+		},
+	)
+}
+
+fn create_seal_handler() -> EnclaveResult<EnclaveSealHandler> {
+	Ok(EnclaveSealHandler::new(
+		GLOBAL_STATE_HANDLER_COMPONENT.get()?,
+		GLOBAL_STATE_KEY_REPOSITORY_COMPONENT.get()?,
+		GLOBAL_SHIELDING_KEY_REPOSITORY_COMPONENT.get()?,
+		GLOBAL_INTEGRITEE_PARENTCHAIN_LIGHT_CLIENT_SEAL.get()?,
+		Arc::new(AssertionsSeal::new(ASSERTIONS_FILE.into())),
+	)
+	.with_state_dir(StateDir::new(get_base_path()?)))
+}
+
 /// Initialize the TOP pool author component.
 pub fn create_top_pool_author(
 	rpc_responder: Arc<EnclaveRpcResponder>,
@@ -602,18 +798,30 @@ pub fn create_top_pool_author(
 	ocall_api: Arc<EnclaveOCallApi>,
 	shielding_key_repository: Arc<EnclaveShieldingKeyRepository>,
 	requests_sink: Arc<std::sync::mpsc::SyncSender<BroadcastedRequest>>,
-) -> Arc<EnclaveTopPoolAuthor> {
+	base_dir: PathBuf,
+	admission_config: AdmissionConfig,
+) -> EnclaveResult<Arc<EnclaveTopPoolAuthor>> {
 	let side_chain_api = Arc::new(EnclaveSidechainApi::new());
 	let top_pool =
 		Arc::new(EnclaveTopPool::create(PoolOptions::default(), side_chain_api, rpc_responder));
 
-	Arc::new(EnclaveTopPoolAuthor::new(
-		top_pool,
-		AuthorTopFilter::<TrustedCallSigned, Getter>::new(),
-		BroadcastedTopFilter::<TrustedCallSigned, Getter>::new(),
-		state_handler,
-		shielding_key_repository,
-		ocall_api,
-		requests_sink,
+	// the enclave signs its own calls with the key derived from the shielding key
+	let mrenclave = ocall_api.get_mrenclave_of_self()?.m;
+	let enclave_signer: AccountId =
+		shielding_key_repository.retrieve_key()?.derive_ed25519()?.public().into();
+
+	Ok(Arc::new(
+		EnclaveTopPoolAuthor::new(
+			top_pool,
+			AuthorTopFilter::<TrustedCallSigned, Getter>::new(),
+			BroadcastedTopFilter::<TrustedCallSigned, Getter>::new(),
+			state_handler,
+			shielding_key_repository,
+			ocall_api,
+			requests_sink,
+		)
+		.with_top_pool_seal(Arc::new(TopPoolSeal::new(base_dir)))
+		.with_admission_config(admission_config)
+		.with_enclave_identity(mrenclave, Identity::from(enclave_signer)),
 	))
 }
@@ -72,8 +72,9 @@ use itp_nonce_cache::{MutateNonce, Nonce};
 use itp_settings::worker_mode::{ProvideWorkerMode, WorkerModeProvider};
 use itp_sgx_crypto::key_repository::AccessPubkey;
 use itp_storage::{StorageProof, StorageProofChecker};
-use itp_types::{parentchain::ParentchainId, ShardIdentifier, SignedBlock};
+use itp_types::{parentchain::ParentchainId, AdmissionConfig, ShardIdentifier, SignedBlock};
 use itp_utils::write_slice_and_whitespace_pad;
+use its_primitives::types::SignedBlock as SignedSidechainBlock;
 use litentry_macros::if_development_or;
 use log::*;
 use once_cell::sync::OnceCell;
@@ -92,6 +93,7 @@ mod initialization;
 mod ipfs;
 mod ocall;
 mod shard_creation_info;
+mod state_backup;
 mod stf_task_handler;
 mod utils;
 
@@ -126,6 +128,8 @@ pub unsafe extern "C" fn init(
 	untrusted_worker_addr_size: u32,
 	encoded_base_dir_str: *const u8,
 	encoded_base_dir_size: u32,
+	admission_config: *const u8,
+	admission_config_size: u32,
 ) -> sgx_status_t {
 	// Initialize the logging environment in the enclave.
 	if_development_or!(
@@ -181,12 +185,22 @@ pub unsafe extern "C" fn init(
 		Err(e) => return e.into(),
 	};
 
+	let admission_config = match AdmissionConfig::decode(&mut slice::from_raw_parts(
+		admission_config,
+		admission_config_size as usize,
+	))
+	.map_err(Error::Codec)
+	{
+		Ok(config) => config,
+		Err(e) => return e.into(),
+	};
+
 	info!("Setting base_dir to {}", base_dir);
 	let path = PathBuf::from(base_dir);
 	// Litentry: the default value here is only for clippy checking
 	BASE_PATH.set(path.clone()).unwrap_or(());
 
-	match initialization::init_enclave(mu_ra_url, untrusted_worker_url, path) {
+	match initialization::init_enclave(mu_ra_url, untrusted_worker_url, path, admission_config) {
 		Err(e) => e.into(),
 		Ok(()) => sgx_status_t::SGX_SUCCESS,
 	}
@@ -455,6 +469,68 @@ pub unsafe extern "C" fn upload_id_graph() -> sgx_status_t {
 	sgx_status_t::SGX_SUCCESS
 }
 
+#[no_mangle]
+pub unsafe extern "C" fn rotate_shielding_key(grace_period_secs: *const u64) -> sgx_status_t {
+	if let Err(e) = initialization::rotate_shielding_key(*grace_period_secs) {
+		error!("Failed to rotate shielding key: {:?}", e);
+		return sgx_status_t::SGX_ERROR_UNEXPECTED
+	}
+
+	sgx_status_t::SGX_SUCCESS
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn export_state_backup(
+	shard: *const u8,
+	shard_size: u32,
+	sidechain_blocks: *const u8,
+	sidechain_blocks_size: u32,
+	backup_path: *const u8,
+	backup_path_size: u32,
+) -> sgx_status_t {
+	let shard = ShardIdentifier::from_slice(slice::from_raw_parts(shard, shard_size as usize));
+
+	let sidechain_blocks = match Vec::<SignedSidechainBlock>::decode(&mut slice::from_raw_parts(
+		sidechain_blocks,
+		sidechain_blocks_size as usize,
+	)) {
+		Ok(blocks) => blocks,
+		Err(e) => return Error::Codec(e).into(),
+	};
+
+	let backup_path =
+		match String::decode(&mut slice::from_raw_parts(backup_path, backup_path_size as usize)) {
+			Ok(path) => PathBuf::from(path),
+			Err(e) => return Error::Codec(e).into(),
+		};
+
+	if let Err(e) = initialization::export_state_backup(shard, sidechain_blocks, backup_path) {
+		error!("Failed to export state backup of shard ({:?}): {:?}", shard, e);
+		return sgx_status_t::SGX_ERROR_UNEXPECTED
+	}
+
+	sgx_status_t::SGX_SUCCESS
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn import_state_backup(
+	backup_path: *const u8,
+	backup_path_size: u32,
+) -> sgx_status_t {
+	let backup_path =
+		match String::decode(&mut slice::from_raw_parts(backup_path, backup_path_size as usize)) {
+			Ok(path) => PathBuf::from(path),
+			Err(e) => return Error::Codec(e).into(),
+		};
+
+	if let Err(e) = initialization::import_state_backup(backup_path) {
+		error!("Failed to import state backup: {:?}", e);
+		return sgx_status_t::SGX_ERROR_UNEXPECTED
+	}
+
+	sgx_status_t::SGX_SUCCESS
+}
+
 #[no_mangle]
 pub unsafe extern "C" fn sync_parentchain(
 	blocks_to_sync: *const u8,
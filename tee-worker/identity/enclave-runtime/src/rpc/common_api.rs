@@ -3,6 +3,7 @@ use crate::{
 		generate_dcap_ra_extrinsic_from_quote_internal,
 		generate_ias_ra_extrinsic_from_der_cert_internal,
 	},
+	rpc::id_graph_subscription_api::add_id_graph_subscription_api,
 	std::borrow::ToOwned,
 	utils::get_validator_accessor_from_integritee_solo_or_parachain,
 };
@@ -11,24 +12,40 @@ use codec::{Decode, Encode};
 use core::result::Result;
 use ita_sgx_runtime::{Runtime, System, VERSION};
 use ita_stf::{aes_encrypt_default, AesOutput, Getter, TrustedCallSigned};
-use itc_parentchain::light_client::{concurrent_access::ValidatorAccess, ExtrinsicSender};
-use itp_ocall_api::EnclaveAttestationOCallApi;
+use itc_parentchain::light_client::{
+	concurrent_access::ValidatorAccess, ExtrinsicSender, LightClientState,
+};
+use itp_ocall_api::{EnclaveAttestationOCallApi, EnclaveOnChainOCallApi};
 use itp_primitives_cache::{GetPrimitives, GLOBAL_PRIMITIVES_CACHE};
 use itp_rpc::RpcReturnValue;
 use itp_sgx_crypto::{
 	ed25519_derivation::DeriveEd25519,
-	key_repository::{AccessKey, AccessPubkey},
+	key_repository::{AccessKey, AccessPubkey, RotatedShieldingKey},
 	ShieldingCryptoDecrypt, ShieldingCryptoEncrypt,
 };
-use itp_sgx_externalities::SgxExternalitiesTrait;
+use itp_sgx_externalities::{record_reads, SgxExternalities, SgxExternalitiesTrait};
 use itp_stf_executor::getter_executor::ExecuteGetter;
 use itp_stf_state_handler::handle_state::HandleState;
+use itp_storage::storage_value_key;
+use itp_time_utils::now_as_millis;
 use itp_top_pool_author::traits::AuthorApi;
-use itp_types::{DirectRequestStatus, Index, RsaRequest, ShardIdentifier, H256};
+use itp_types::{
+	parentchain::ParentchainId, AccountId, DirectRequestStatus, Index, RsaRequest, ShardIdentifier,
+	H256,
+};
 use itp_utils::{FromHexPrefixed, ToHexPrefixed};
+use its_primitives::types::{
+	header::SidechainHeader,
+	state_proof::{ProvenGetterValue, SidechainStateProof},
+};
 use its_rpc_handler::direct_top_pool_api::add_top_pool_direct_rpc_methods;
+use its_sidechain::state::with_confirmed_state;
 use jsonrpc_core::{serde_json::json, IoHandler, Params, Value};
 use lc_data_providers::DataProviderConfig;
+use lc_evm_dynamic_assertions::{
+	dry_run::{DryRunAssertionRequest, DryRunAssertionRequestSigned, DryRunReplayGuard},
+	dry_run_assertion,
+};
 use lc_identity_verification::web2::{email, twitter};
 use litentry_macros::{if_development, if_development_or};
 use litentry_primitives::{aes_decrypt, AesRequest, DecryptableRequest, Identity};
@@ -36,7 +53,13 @@ use log::debug;
 use sgx_crypto_helper::rsa3072::Rsa3072PubKey;
 use sp_core::Pair;
 use sp_runtime::OpaqueExtrinsic;
-use std::{boxed::Box, format, str, string::String, sync::Arc, vec::Vec};
+use std::{
+	boxed::Box,
+	format, str,
+	string::String,
+	sync::{Arc, SgxMutex as Mutex},
+	vec::Vec,
+};
 
 fn compute_hex_encoded_return_error(error_msg: &str) -> String {
 	RpcReturnValue::from_error_message(error_msg).to_hex()
@@ -52,15 +75,16 @@ pub fn add_common_api<Author, GetterExecutor, AccessShieldingKey, OcallApi, Stat
 	data_provider_config: Arc<DataProviderConfig>,
 ) where
 	Author: AuthorApi<H256, H256, TrustedCallSigned, Getter> + Send + Sync + 'static,
-	GetterExecutor: ExecuteGetter + Send + Sync + 'static,
+	GetterExecutor: ExecuteGetter<StateType = SgxExternalities> + Send + Sync + 'static,
 	AccessShieldingKey: AccessPubkey<KeyType = Rsa3072PubKey> + AccessKey + Send + Sync + 'static,
 	<AccessShieldingKey as AccessKey>::KeyType:
 		ShieldingCryptoDecrypt + ShieldingCryptoEncrypt + DeriveEd25519 + Send + Sync + 'static,
-	OcallApi: EnclaveAttestationOCallApi + Send + Sync + 'static,
+	OcallApi: EnclaveAttestationOCallApi + EnclaveOnChainOCallApi + Send + Sync + 'static,
 	State: HandleState + Send + Sync + 'static,
 	State::StateT: SgxExternalitiesTrait,
 {
 	add_top_pool_direct_rpc_methods(top_pool_author.clone(), io_handler);
+	add_id_graph_subscription_api(io_handler, shielding_key.clone(), state.clone());
 
 	let shielding_key_cloned = shielding_key.clone();
 	io_handler.add_sync_method("author_getShieldingKey", move |_: Params| {
@@ -241,6 +265,82 @@ pub fn add_common_api<Author, GetterExecutor, AccessShieldingKey, OcallApi, Stat
 		Ok(json!(json_value.to_hex()))
 	});
 
+	let shielding_key_cloned = shielding_key.clone();
+	let ocall_api_cloned = ocall_api.clone();
+	let dry_run_replay_guard = Mutex::new(DryRunReplayGuard::default());
+	io_handler.add_sync_method("identity_dryRunAssertion", move |params: Params| {
+		debug!("worker_api_direct rpc was called: identity_dryRunAssertion");
+
+		let shielding_key = match RotatedShieldingKey::retrieve(&*shielding_key_cloned) {
+			Ok(key) => key,
+			Err(e) => return Ok(json!(compute_hex_encoded_return_error(&format!("{:?}", e)))),
+		};
+
+		let return_value: Result<AesOutput, String> = (|| {
+			let hex_encoded_params =
+				params.parse::<Vec<String>>().map_err(|e| format!("{:?}", e))?;
+			let param = &hex_encoded_params.get(0).ok_or("Could not get first param")?;
+			let mut request = AesRequest::from_hex(param).map_err(|e| format!("{:?}", e))?;
+
+			let aes_key = request
+				.decrypt_aes_key(Box::new(shielding_key))
+				.map_err(|_err: ()| "Could not decrypt request AES key")?;
+
+			let encoded_request = aes_decrypt(&aes_key, &mut request.payload)
+				.ok_or(())
+				.map_err(|_err: ()| "Could not decrypt dry run request")?;
+			let signed_request =
+				DryRunAssertionRequestSigned::decode(&mut encoded_request.as_slice())
+					.map_err(|e| format!("Could not decode dry run request: {:?}", e))?;
+
+			if !signed_request.verify_signature() {
+				return Err("Invalid signature".into())
+			}
+
+			// outside of development mode only assertion developers are allowed to dry run
+			let membership_check_required = if_development_or!(false, true);
+			if membership_check_required {
+				ensure_developer_committee_member(
+					ocall_api_cloned.as_ref(),
+					&signed_request.request.who,
+				)?;
+			}
+			dry_run_replay_guard
+				.lock()
+				.map_err(|_| "Could not acquire lock on dry run replay guard")?
+				.check(&signed_request.request, now_as_millis())?;
+
+			let DryRunAssertionRequest {
+				byte_code,
+				secrets,
+				params: assertion_params,
+				identities,
+				policy,
+				..
+			} = signed_request.request;
+			let result = dry_run_assertion(
+				byte_code,
+				secrets,
+				assertion_params,
+				&identities,
+				policy.as_ref(),
+			)
+			.map_err(|e| format!("{:?}", e))?;
+
+			Ok(aes_encrypt_default(&aes_key, result.encode().as_slice()))
+		})();
+
+		match return_value {
+			Ok(aes_output) => Ok(json!(RpcReturnValue {
+				do_watch: false,
+				value: aes_output.encode(),
+				status: DirectRequestStatus::Ok,
+			}
+			.to_hex())),
+			Err(error) => Ok(json!(compute_hex_encoded_return_error(error.as_str()))),
+		}
+	});
+
 	// TODO: deprecate
 	let getter_executor_cloned = getter_executor.clone();
 	io_handler.add_sync_method("state_executeGetter", move |params: Params| {
@@ -261,10 +361,11 @@ pub fn add_common_api<Author, GetterExecutor, AccessShieldingKey, OcallApi, Stat
 	io_handler.add_sync_method("state_executeAesGetter", move |params: Params| {
 		debug!("worker_api_direct rpc was called: state_executeAesGetter");
 
-		let shielding_key = match shielding_key.retrieve_key().map_err(|e| format!("{:?}", e)) {
-			Ok(key) => key,
-			Err(e) => return Ok(json!(compute_hex_encoded_return_error(&e))),
-		};
+		let shielding_key =
+			match RotatedShieldingKey::retrieve(&*shielding_key).map_err(|e| format!("{:?}", e)) {
+				Ok(key) => key,
+				Err(e) => return Ok(json!(compute_hex_encoded_return_error(&e))),
+			};
 
 		let return_value: Result<AesOutput, String> = (|| {
 			let hex_encoded_params =
@@ -282,6 +383,16 @@ pub fn add_common_api<Author, GetterExecutor, AccessShieldingKey, OcallApi, Stat
 
 			let shard = request.shard();
 
+			// an optional second param requests a proof against the confirmed sidechain block
+			if hex_encoded_params.get(1).map_or(false, |p| p == "true") {
+				let proven = prove_confirmed_read(&shard, |state| {
+					getter_executor
+						.execute_getter_on_state(state, encoded_trusted_getter)
+						.map_err(|e| format!("{:?}", e))
+				})?;
+				return Ok(aes_encrypt_default(&aes_key, proven.encode().as_slice()))
+			}
+
 			let state_getter_value = getter_executor
 				.execute_getter(&shard, encoded_trusted_getter)
 				.map_err(|e| format!("{:?}", e))?;
@@ -357,8 +468,10 @@ pub fn add_common_api<Author, GetterExecutor, AccessShieldingKey, OcallApi, Stat
 						"state_getStorage is not avaiable"
 					))),
 			};
-			match params.parse::<(String, String)>() {
-				Ok((shard_str, key_hash)) => {
+			match params.parse::<Vec<String>>().as_deref() {
+				Ok([shard_str, key_hash, rest @ ..]) => {
+					// an optional third param requests a proof against the confirmed block
+					let with_proof = matches!(rest, [p] if p == "true");
 					let key_hash = if key_hash.starts_with("0x") {
 						#[allow(clippy::unwrap_used)]
 						key_hash.strip_prefix("0x").unwrap()
@@ -379,6 +492,17 @@ pub fn add_common_api<Author, GetterExecutor, AccessShieldingKey, OcallApi, Stat
 							return Ok(json!(compute_hex_encoded_return_error(error_msg.as_str())))
 						},
 					};
+					if with_proof {
+						let json_value = match prove_confirmed_read(&shard, |state| {
+							Ok(state.get(key_hash.as_slice()).cloned())
+						}) {
+							Ok(proven) =>
+								RpcReturnValue::new(proven.encode(), false, DirectRequestStatus::Ok)
+									.to_hex(),
+							Err(e) => compute_hex_encoded_return_error(e.as_str()),
+						};
+						return Ok(json!(json_value))
+					}
 					match local_state.load_cloned(&shard) {
 						Ok((state, _)) => {
 							// Get storage by key hash
@@ -394,7 +518,7 @@ pub fn add_common_api<Author, GetterExecutor, AccessShieldingKey, OcallApi, Stat
 						},
 					}
 				},
-				Err(_err) => Ok(json!(compute_hex_encoded_return_error("parse error"))),
+				_ => Ok(json!(compute_hex_encoded_return_error("parse error"))),
 			}
 		});
 	});
@@ -528,6 +652,31 @@ fn execute_rsa_getter_inner<GE: ExecuteGetter>(
 	Ok(getter_result)
 }
 
+/// Execute `read` on the state after the confirmed sidechain block of `shard` and prove the
+/// storage entries it read against the header of that block.
+fn prove_confirmed_read(
+	shard: &ShardIdentifier,
+	read: impl FnOnce(&mut SgxExternalities) -> Result<Option<Vec<u8>>, String>,
+) -> Result<ProvenGetterValue, String> {
+	with_confirmed_state(shard, |confirmed| {
+		let header = SidechainHeader::decode(&mut confirmed.header.as_slice())
+			.map_err(|e| format!("{:?}", e))?;
+		let (value, keys) = record_reads(|| read(&mut confirmed.state));
+		let keys: Vec<Vec<u8>> = keys.into_iter().collect();
+		let reads = keys
+			.iter()
+			.map(|k| (k.clone(), confirmed.state.state.get(k).cloned()))
+			.collect();
+
+		Ok(ProvenGetterValue {
+			value: value?,
+			reads,
+			proof: SidechainStateProof { header, proof: confirmed.tree.prove_reads(&keys) },
+		})
+	})
+	.ok_or("No confirmed sidechain block has been imported yet")?
+}
+
 fn forward_dcap_quote_inner(params: Params) -> Result<OpaqueExtrinsic, String> {
 	let hex_encoded_params = params.parse::<Vec<String>>().map_err(|e| format!("{:?}", e))?;
 
@@ -593,6 +742,36 @@ fn attesteer_forward_ias_attestation_report_inner(
 	Ok(ext)
 }
 
+fn ensure_developer_committee_member<OcallApi: EnclaveOnChainOCallApi>(
+	ocall_api: &OcallApi,
+	who: &Identity,
+) -> Result<(), String> {
+	let account_id = who.to_native_account().ok_or("Invalid identity")?;
+
+	let validator_access = get_validator_accessor_from_integritee_solo_or_parachain()
+		.map_err(|e| format!("{:?}", e))?;
+	let header = validator_access
+		.execute_on_validator(|v| v.latest_finalized_header())
+		.map_err(|e| format!("{:?}", e))?;
+
+	let members: Vec<AccountId> = ocall_api
+		.get_storage_verified(
+			storage_value_key("DeveloperCommittee", "Members"),
+			&header,
+			&ParentchainId::Litentry,
+		)
+		.map_err(|e| format!("Could not get developer committee members: {:?}", e))?
+		.value()
+		.to_owned()
+		.unwrap_or_default();
+
+	if members.contains(&account_id) {
+		Ok(())
+	} else {
+		Err("Requester is not a member of the developer committee".into())
+	}
+}
+
 // converts the rpc methods vector to a string and adds commas and brackets for readability
 pub fn decode_shard_from_base58(shard_base58: &str) -> Result<ShardIdentifier, String> {
 	let shard_vec = match shard_base58.from_base58() {
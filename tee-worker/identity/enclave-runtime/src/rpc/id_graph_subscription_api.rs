@@ -0,0 +1,219 @@
+// Copyright 2020-2024 Trust Computing GmbH.
+// This file is part of Litentry.
+//
+// Litentry is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// Litentry is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with Litentry.  If not, see <https://www.gnu.org/licenses/>.
+
+use codec::{Decode, Encode};
+use ita_sgx_runtime::IdentityManagement;
+use ita_stf::{Getter, TrustedCallSigned};
+use itp_rpc::RpcReturnValue;
+use itp_sgx_crypto::{
+	key_repository::{AccessKey, RotatedShieldingKey},
+	ShieldingCryptoDecrypt, ShieldingCryptoEncrypt,
+};
+use itp_sgx_externalities::SgxExternalitiesTrait;
+use itp_stf_state_handler::handle_state::HandleState;
+use itp_top_pool_author::traits::AuthorApi;
+use itp_types::{DirectRequestStatus, ShardIdentifier, TrustedOperationStatus, H256};
+use itp_utils::FromHexPrefixed;
+use jsonrpc_core::{serde_json::json, IoHandler, Params};
+use lc_id_graph_subscription::{id_graph_subscriptions, IdGraphSubscriptionRequest};
+use litentry_primitives::{aes_decrypt, AesRequest, DecryptableRequest, Identity};
+use log::*;
+use std::{boxed::Box, format, string::String, sync::Arc, vec::Vec};
+
+fn compute_hex_encoded_return_error(error_msg: &str) -> String {
+	RpcReturnValue::from_error_message(error_msg).to_hex()
+}
+
+pub fn add_id_graph_subscription_api<AccessShieldingKey, State>(
+	io_handler: &mut IoHandler,
+	shielding_key: Arc<AccessShieldingKey>,
+	state: Option<Arc<State>>,
+) where
+	AccessShieldingKey: AccessKey + Send + Sync + 'static,
+	<AccessShieldingKey as AccessKey>::KeyType:
+		ShieldingCryptoDecrypt + ShieldingCryptoEncrypt + Send + Sync + 'static,
+	State: HandleState + Send + Sync + 'static,
+	State::StateT: SgxExternalitiesTrait,
+{
+	io_handler.add_sync_method(
+		"identity_getIdGraphSubscriptionChallenge",
+		move |params: Params| {
+			debug!("worker_api_direct rpc was called: identity_getIdGraphSubscriptionChallenge");
+			let return_value: Result<H256, String> = (|| {
+				let (identity_hex, nonce_hex) =
+					params.parse::<(String, String)>().map_err(|e| format!("{:?}", e))?;
+				let who = Identity::from_hex(identity_hex.as_str())
+					.map_err(|e| format!("Could not decode identity: {:?}", e))?;
+				let nonce = H256::from_hex(nonce_hex.as_str())
+					.map_err(|e| format!("Could not decode connection nonce: {:?}", e))?;
+				id_graph_subscriptions()
+					.new_challenge(&who, nonce)
+					.map_err(|e| format!("{:?}", e))
+			})();
+
+			match return_value {
+				Ok(challenge) => Ok(json!(RpcReturnValue {
+					do_watch: false,
+					value: challenge.encode(),
+					status: DirectRequestStatus::Ok,
+				}
+				.to_hex())),
+				Err(error) => Ok(json!(compute_hex_encoded_return_error(error.as_str()))),
+			}
+		},
+	);
+
+	// the connection stays open, notifications are pushed to it under the subscription hash
+	let unsubscribe_shielding_key = shielding_key.clone();
+	io_handler.add_sync_method("identity_subscribeIdGraphChanges", move |params: Params| {
+		debug!("worker_api_direct rpc was called: identity_subscribeIdGraphChanges");
+		let local_state = match state.clone() {
+			Some(s) => s,
+			None =>
+				return Ok(json!(compute_hex_encoded_return_error(
+					"identity_subscribeIdGraphChanges is not available"
+				))),
+		};
+		let shielding_key = match RotatedShieldingKey::retrieve(&*shielding_key) {
+			Ok(key) => key,
+			Err(e) => return Ok(json!(compute_hex_encoded_return_error(&format!("{:?}", e)))),
+		};
+
+		let return_value: Result<H256, String> = (|| {
+			let hex_encoded_params =
+				params.parse::<Vec<String>>().map_err(|e| format!("{:?}", e))?;
+			let param = &hex_encoded_params.get(0).ok_or("Could not get first param")?;
+			let mut request = AesRequest::from_hex(param).map_err(|e| format!("{:?}", e))?;
+
+			let aes_key = request
+				.decrypt_aes_key(Box::new(shielding_key))
+				.map_err(|_err: ()| "Could not decrypt request AES key")?;
+
+			let encoded_request = aes_decrypt(&aes_key, &mut request.payload)
+				.ok_or(())
+				.map_err(|_err: ()| "Could not decrypt subscription request")?;
+			let subscription_request =
+				IdGraphSubscriptionRequest::decode(&mut encoded_request.as_slice())
+					.map_err(|e| format!("Could not decode subscription request: {:?}", e))?;
+
+			let shard = request.shard();
+			let id_graph_hash =
+				current_id_graph_hash(local_state.as_ref(), &shard, &subscription_request.who)?;
+
+			id_graph_subscriptions()
+				.subscribe(shard, &subscription_request, aes_key, id_graph_hash)
+				.map_err(|e| format!("Could not subscribe: {:?}", e))
+		})();
+
+		match return_value {
+			Ok(subscription_hash) => Ok(json!(RpcReturnValue {
+				do_watch: true,
+				value: subscription_hash.encode(),
+				status: DirectRequestStatus::TrustedOperationStatus(
+					TrustedOperationStatus::Submitted,
+					subscription_hash
+				),
+			}
+			.to_hex())),
+			Err(error) => Ok(json!(compute_hex_encoded_return_error(error.as_str()))),
+		}
+	});
+
+	// the subscription hash is sent with the AES key of the subscription, which authenticates it
+	io_handler.add_sync_method("identity_unsubscribeIdGraphChanges", move |params: Params| {
+		debug!("worker_api_direct rpc was called: identity_unsubscribeIdGraphChanges");
+		let shielding_key = match RotatedShieldingKey::retrieve(&*unsubscribe_shielding_key) {
+			Ok(key) => key,
+			Err(e) => return Ok(json!(compute_hex_encoded_return_error(&format!("{:?}", e)))),
+		};
+
+		let return_value: Result<bool, String> = (|| {
+			let hex_encoded_params =
+				params.parse::<Vec<String>>().map_err(|e| format!("{:?}", e))?;
+			let param = &hex_encoded_params.get(0).ok_or("Could not get first param")?;
+			let mut request = AesRequest::from_hex(param).map_err(|e| format!("{:?}", e))?;
+
+			let aes_key = request
+				.decrypt_aes_key(Box::new(shielding_key))
+				.map_err(|_err: ()| "Could not decrypt request AES key")?;
+
+			let encoded_hash = aes_decrypt(&aes_key, &mut request.payload)
+				.ok_or(())
+				.map_err(|_err: ()| "Could not decrypt unsubscription request")?;
+			let hash = H256::decode(&mut encoded_hash.as_slice())
+				.map_err(|e| format!("Could not decode subscription hash: {:?}", e))?;
+			Ok(id_graph_subscriptions().unsubscribe(&hash, &aes_key))
+		})();
+
+		match return_value {
+			Ok(removed) => Ok(json!(RpcReturnValue {
+				do_watch: false,
+				value: removed.encode(),
+				status: DirectRequestStatus::Ok,
+			}
+			.to_hex())),
+			Err(error) => Ok(json!(compute_hex_encoded_return_error(error.as_str()))),
+		}
+	});
+}
+
+fn current_id_graph_hash<State>(
+	state: &State,
+	shard: &ShardIdentifier,
+	who: &Identity,
+) -> Result<Option<H256>, String>
+where
+	State: HandleState,
+	State::StateT: SgxExternalitiesTrait,
+{
+	let (mut state, _) = state
+		.load_cloned(shard)
+		.map_err(|e| format!("load shard failure due to: {:?}", e))?;
+	Ok(state.execute_with(|| IdentityManagement::id_graph_hash(who)))
+}
+
+/// Push the changed ID graphs of all subscribers of the given shards, called after block production.
+pub(crate) fn notify_id_graph_subscribers<State, Author>(
+	state: &State,
+	shards: &[ShardIdentifier],
+	author: &Author,
+) where
+	State: HandleState,
+	State::StateT: SgxExternalitiesTrait,
+	Author: AuthorApi<H256, H256, TrustedCallSigned, Getter>,
+{
+	let subscriptions = id_graph_subscriptions();
+	let subscribers = subscriptions.subscribers();
+	if subscribers.is_empty() {
+		return
+	}
+
+	for shard in shards {
+		let (mut shard_state, _) = match state.load_cloned(shard) {
+			Ok(s) => s,
+			Err(e) => {
+				warn!("Could not load state of shard {:?} to notify subscribers: {:?}", shard, e);
+				continue
+			},
+		};
+		for (_, who) in subscribers.iter().filter(|(s, _)| s == shard) {
+			let id_graph_hash = shard_state.execute_with(|| IdentityManagement::id_graph_hash(who));
+			for (hash, value) in subscriptions.id_graph_updated(shard, who, id_graph_hash) {
+				author.send_rpc_response(hash, value, true);
+			}
+		}
+	}
+}
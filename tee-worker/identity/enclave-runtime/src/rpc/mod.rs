@@ -15,4 +15,5 @@
 
 */
 pub mod common_api;
+pub mod id_graph_subscription_api;
 pub mod rpc_response_channel;
@@ -0,0 +1,386 @@
+// Copyright 2020-2024 Trust Computing GmbH.
+// This file is part of Litentry.
+//
+// Litentry is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// Litentry is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with Litentry.  If not, see <https://www.gnu.org/licenses/>.
+
+//! Export and import of encrypted shard backups, see [`StateBackupBundle`].
+
+use crate::{
+	error::{Error, Result as EnclaveResult},
+	tls_ra::seal_handler::{SealStateAndKeys, UnsealStateAndKeys},
+};
+use codec::{Decode, DecodeAll, Encode};
+use itc_parentchain::light_client::LightClientSealing;
+use itp_sgx_crypto::aes256::{aes_decrypt, aes_encrypt_default, Aes256Key, AesOutput};
+use itp_types::{AccountId, ShardIdentifier};
+use its_primitives::{
+	traits::{Block, BlockData, Header, SignedBlock as SignedBlockT},
+	types::{
+		state_backup::{StateBackupBundle, StateBackupPayload, STATE_BACKUP_VERSION},
+		SignedBlock as SignedSidechainBlock,
+	},
+};
+use log::*;
+use sgx_trts::trts::rsgx_read_rand;
+use sgx_tseal::SgxSealedData;
+use sgx_types::{
+	sgx_attributes_t, sgx_sealed_data_t, sgx_status_t, SGX_KEYPOLICY_MRSIGNER,
+	TSEAL_DEFAULT_FLAGSMASK, TSEAL_DEFAULT_MISCMASK,
+};
+use std::{format, vec, vec::Vec};
+
+/// Create a backup of the given shard, including the given sidechain blocks.
+pub(crate) fn create_state_backup<Sealer, TargetLightClientSeal>(
+	sealer: &Sealer,
+	target_a_light_client_seal: &TargetLightClientSeal,
+	target_b_light_client_seal: &TargetLightClientSeal,
+	shard: ShardIdentifier,
+	sidechain_blocks: Vec<SignedSidechainBlock>,
+) -> EnclaveResult<StateBackupBundle>
+where
+	Sealer: UnsealStateAndKeys,
+	TargetLightClientSeal: LightClientSealing,
+	TargetLightClientSeal::LightClientState: Encode,
+{
+	verify_sidechain_blocks(&shard, &sidechain_blocks)?;
+
+	let payload = StateBackupPayload {
+		shard,
+		state: sealer.unseal_state(&shard)?,
+		light_client_state: sealer.unseal_light_client_state()?,
+		target_a_light_client_state: unseal_target_light_client_state(target_a_light_client_seal)?,
+		target_b_light_client_state: unseal_target_light_client_state(target_b_light_client_seal)?,
+		last_sidechain_block_hash: sidechain_blocks.last().map(|b| b.hash()),
+	};
+
+	let mut backup_key = Aes256Key::default();
+	rsgx_read_rand(&mut backup_key)?;
+	let encrypted_payload = aes_encrypt_default(&backup_key, &payload.encode());
+	if encrypted_payload.ciphertext.is_empty() {
+		return Err(Error::Other("Failed to encrypt state backup".into()))
+	}
+
+	Ok(StateBackupBundle {
+		version: STATE_BACKUP_VERSION,
+		shard,
+		sidechain_blocks,
+		sealed_backup_key: seal_backup_key(&backup_key)?,
+		encrypted_payload: encrypted_payload.encode(),
+	})
+}
+
+/// Restore the state and the light client states from a backup.
+///
+/// `fetch_registered_enclaves` returns the enclaves registered on the Litentry parentchain,
+/// given the encoded light client state of the backup. The sidechain blocks must be authored
+/// by one of them. They are only verified here, storing them is up to the untrusted worker.
+pub(crate) fn restore_state_backup<Sealer, TargetLightClientSeal, FetchRegisteredEnclaves>(
+	sealer: &Sealer,
+	target_a_light_client_seal: &TargetLightClientSeal,
+	target_b_light_client_seal: &TargetLightClientSeal,
+	bundle: StateBackupBundle,
+	fetch_registered_enclaves: FetchRegisteredEnclaves,
+) -> EnclaveResult<()>
+where
+	Sealer: SealStateAndKeys,
+	TargetLightClientSeal: LightClientSealing,
+	TargetLightClientSeal::LightClientState: Decode,
+	FetchRegisteredEnclaves: FnOnce(&[u8]) -> EnclaveResult<Vec<AccountId>>,
+{
+	if bundle.version != STATE_BACKUP_VERSION {
+		return Err(Error::Other(
+			format!("Unsupported state backup version: {}", bundle.version).into(),
+		))
+	}
+
+	let backup_key = unseal_backup_key(bundle.sealed_backup_key)?;
+	let mut encrypted_payload = AesOutput::decode_all(&mut bundle.encrypted_payload.as_slice())?;
+	let payload = aes_decrypt(&backup_key, &mut encrypted_payload)
+		.ok_or_else(|| Error::Other("State backup payload failed authentication".into()))?;
+	let payload = StateBackupPayload::decode_all(&mut payload.as_slice())?;
+
+	if payload.shard != bundle.shard {
+		return Err(Error::Other("State backup shard does not match".into()))
+	}
+	verify_sidechain_blocks(&bundle.shard, &bundle.sidechain_blocks)?;
+	if payload.last_sidechain_block_hash != bundle.sidechain_blocks.last().map(|b| b.hash()) {
+		return Err(Error::Other("State backup sidechain blocks do not match the state".into()))
+	}
+	let registered_enclaves = fetch_registered_enclaves(&payload.light_client_state)?;
+	verify_sidechain_block_authors(&bundle.sidechain_blocks, &registered_enclaves)?;
+
+	sealer.seal_state(&payload.state, &bundle.shard)?;
+	sealer.seal_light_client_state(&payload.light_client_state)?;
+	if let Some(state) = payload.target_a_light_client_state {
+		seal_target_light_client_state(target_a_light_client_seal, &state)?;
+	}
+	if let Some(state) = payload.target_b_light_client_state {
+		seal_target_light_client_state(target_b_light_client_seal, &state)?;
+	}
+	info!(
+		"Restored shard {:?} from state backup with {} sidechain blocks",
+		bundle.shard,
+		bundle.sidechain_blocks.len()
+	);
+	Ok(())
+}
+
+fn unseal_target_light_client_state<LightClientSeal>(
+	seal: &LightClientSeal,
+) -> EnclaveResult<Option<Vec<u8>>>
+where
+	LightClientSeal: LightClientSealing,
+	LightClientSeal::LightClientState: Encode,
+{
+	if !seal.exists() {
+		return Ok(None)
+	}
+	Ok(Some(seal.unseal()?.encode()))
+}
+
+fn seal_target_light_client_state<LightClientSeal>(
+	seal: &LightClientSeal,
+	mut bytes: &[u8],
+) -> EnclaveResult<()>
+where
+	LightClientSeal: LightClientSealing,
+	LightClientSeal::LightClientState: Decode,
+{
+	let state = LightClientSeal::LightClientState::decode(&mut bytes)?;
+	Ok(seal.seal(&state)?)
+}
+
+/// Seal the backup key with the MRSIGNER key policy, so that any enclave of the same
+/// signer can import the backup.
+fn seal_backup_key(backup_key: &Aes256Key) -> EnclaveResult<Vec<u8>> {
+	let attribute_mask = sgx_attributes_t { flags: TSEAL_DEFAULT_FLAGSMASK, xfrm: 0 };
+	let sealed_data = SgxSealedData::<[u8]>::seal_data_ex(
+		SGX_KEYPOLICY_MRSIGNER,
+		attribute_mask,
+		TSEAL_DEFAULT_MISCMASK,
+		&[],
+		backup_key,
+	)?;
+
+	let sealed_size = SgxSealedData::<[u8]>::calc_raw_sealed_data_size(0, backup_key.len() as u32);
+	let mut sealed_backup_key = vec![0u8; sealed_size as usize];
+	unsafe {
+		sealed_data.to_raw_sealed_data_t(
+			sealed_backup_key.as_mut_ptr() as *mut sgx_sealed_data_t,
+			sealed_size,
+		)
+	}
+	.ok_or(Error::Sgx(sgx_status_t::SGX_ERROR_UNEXPECTED))?;
+	Ok(sealed_backup_key)
+}
+
+fn unseal_backup_key(mut sealed_backup_key: Vec<u8>) -> EnclaveResult<Aes256Key> {
+	let sealed_data = unsafe {
+		SgxSealedData::<[u8]>::from_raw_sealed_data_t(
+			sealed_backup_key.as_mut_ptr() as *mut sgx_sealed_data_t,
+			sealed_backup_key.len() as u32,
+		)
+	}
+	.ok_or_else(|| Error::Other("Invalid sealed state backup key".into()))?;
+	let unsealed_data = sealed_data.unseal_data()?;
+	Aes256Key::try_from(unsealed_data.get_decrypt_txt())
+		.map_err(|_| Error::Other("Invalid state backup key length".into()))
+}
+
+/// The sidechain blocks must be a signed, consecutive chain of the given shard.
+fn verify_sidechain_blocks(
+	shard: &ShardIdentifier,
+	sidechain_blocks: &[SignedSidechainBlock],
+) -> EnclaveResult<()> {
+	for block in sidechain_blocks {
+		if block.block().header().shard_id() != *shard || !block.verify_signature() {
+			return Err(Error::Other(
+				format!("Invalid sidechain block in state backup: {:?}", block.hash()).into(),
+			))
+		}
+	}
+
+	for (parent, child) in sidechain_blocks.iter().zip(sidechain_blocks.iter().skip(1)) {
+		let child_header = child.block().header();
+		if child_header.parent_hash() != parent.hash()
+			|| child_header.block_number()
+				!= parent.block().header().block_number().saturating_add(1)
+		{
+			return Err(Error::Other("Sidechain blocks in state backup are not consecutive".into()))
+		}
+	}
+	Ok(())
+}
+
+fn verify_sidechain_block_authors(
+	sidechain_blocks: &[SignedSidechainBlock],
+	registered_enclaves: &[AccountId],
+) -> EnclaveResult<()> {
+	for block in sidechain_blocks {
+		let author = AccountId::from(block.block().block_data().block_author().0);
+		if !registered_enclaves.contains(&author) {
+			return Err(Error::Other(
+				format!(
+					"Sidechain block {:?} is not authored by a registered enclave",
+					block.hash()
+				)
+				.into(),
+			))
+		}
+	}
+	Ok(())
+}
+
+#[cfg(feature = "test")]
+pub mod tests {
+	use super::*;
+	use crate::tls_ra::mocks::SealHandlerMock;
+	use itc_parentchain::light_client::mocks::validator_mock_seal::LightValidationStateSealMock;
+	use its_primitives::{
+		traits::SignBlock,
+		types::{
+			block_data::BlockData as SidechainBlockData, header::SidechainHeader,
+			Block as SidechainBlock,
+		},
+	};
+	use sp_core::{ed25519, Pair, H256};
+	use std::sync::{Arc, SgxRwLock as RwLock};
+
+	fn seal_handler(state: Vec<u8>, light_client_state: Vec<u8>) -> SealHandlerMock {
+		let lock = |bytes: Vec<u8>| Arc::new(RwLock::new(bytes));
+		SealHandlerMock::new(
+			lock(Vec::new()),
+			lock(Vec::new()),
+			lock(state),
+			lock(light_client_state),
+			lock(Vec::new()),
+		)
+	}
+
+	fn create_backup(
+		shard: ShardIdentifier,
+		sidechain_blocks: Vec<SignedSidechainBlock>,
+	) -> StateBackupBundle {
+		let target_seal = LightValidationStateSealMock::new();
+		create_state_backup(
+			&seal_handler(vec![1, 2, 3], vec![4, 5]),
+			&target_seal,
+			&target_seal,
+			shard,
+			sidechain_blocks,
+		)
+		.unwrap()
+	}
+
+	fn restore_backup(
+		sealer: &SealHandlerMock,
+		bundle: StateBackupBundle,
+		registered_enclaves: Vec<AccountId>,
+	) -> EnclaveResult<()> {
+		let target_seal = LightValidationStateSealMock::new();
+		restore_state_backup(sealer, &target_seal, &target_seal, bundle, |_| {
+			Ok(registered_enclaves)
+		})
+	}
+
+	fn signed_sidechain_block(
+		shard: ShardIdentifier,
+		author: &ed25519::Pair,
+	) -> SignedSidechainBlock {
+		SidechainBlock {
+			header: SidechainHeader {
+				parent_hash: H256::default(),
+				block_number: 1,
+				shard_id: shard,
+				block_data_hash: H256::default(),
+				next_finalization_block_number: 1,
+				state_root: H256::default(),
+			},
+			block_data: SidechainBlockData {
+				timestamp: 0,
+				layer_one_head: H256::default(),
+				block_author: author.public(),
+				signed_top_hashes: Vec::new(),
+				encrypted_state_diff: Vec::new(),
+			},
+		}
+		.sign_block(author)
+	}
+
+	pub fn create_and_restore_state_backup_works() {
+		// given
+		let shard = ShardIdentifier::repeat_byte(1);
+		let author = ed25519::Pair::from_seed(&[1u8; 32]);
+		let bundle = create_backup(shard, vec![signed_sidechain_block(shard, &author)]);
+		let restored_seal_handler = seal_handler(Vec::new(), Vec::new());
+
+		// when
+		restore_backup(&restored_seal_handler, bundle, vec![AccountId::from(author.public().0)])
+			.unwrap();
+
+		// then
+		assert_eq!(restored_seal_handler.unseal_state(&shard).unwrap(), vec![1, 2, 3]);
+		assert_eq!(restored_seal_handler.unseal_light_client_state().unwrap(), vec![4, 5]);
+	}
+
+	pub fn restore_state_backup_fails_for_tampered_payload() {
+		// given
+		let shard = ShardIdentifier::repeat_byte(1);
+		let mut bundle = create_backup(shard, Vec::new());
+		let mut encrypted_payload =
+			AesOutput::decode_all(&mut bundle.encrypted_payload.as_slice()).unwrap();
+		encrypted_payload.ciphertext[0] ^= 1;
+		bundle.encrypted_payload = encrypted_payload.encode();
+		let restored_seal_handler = seal_handler(Vec::new(), Vec::new());
+
+		// when
+		let result = restore_backup(&restored_seal_handler, bundle, Vec::new());
+
+		// then
+		assert!(result.is_err());
+		assert!(restored_seal_handler.unseal_state(&shard).unwrap().is_empty());
+	}
+
+	pub fn restore_state_backup_fails_for_unregistered_block_author() {
+		// given
+		let shard = ShardIdentifier::repeat_byte(1);
+		let author = ed25519::Pair::from_seed(&[1u8; 32]);
+		let registered_enclave = ed25519::Pair::from_seed(&[2u8; 32]);
+		let bundle = create_backup(shard, vec![signed_sidechain_block(shard, &author)]);
+		let restored_seal_handler = seal_handler(Vec::new(), Vec::new());
+
+		// when
+		let result = restore_backup(
+			&restored_seal_handler,
+			bundle,
+			vec![AccountId::from(registered_enclave.public().0)],
+		);
+
+		// then
+		assert!(result.is_err());
+		assert!(restored_seal_handler.unseal_state(&shard).unwrap().is_empty());
+	}
+
+	pub fn restore_state_backup_fails_for_unsupported_version() {
+		// given
+		let mut bundle = create_backup(ShardIdentifier::repeat_byte(1), Vec::new());
+		bundle.version = STATE_BACKUP_VERSION.saturating_add(1);
+
+		// when
+		let result = restore_backup(&seal_handler(Vec::new(), Vec::new()), bundle, Vec::new());
+
+		// then
+		assert!(result.is_err());
+	}
+}
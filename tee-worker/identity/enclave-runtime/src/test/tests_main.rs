@@ -18,6 +18,7 @@
 use crate::test::evm_pallet_tests;
 
 use crate::{
+	state_backup,
 	sync::tests::{enclave_rw_lock_works, sidechain_rw_lock_works},
 	test::{
 		cert_tests::*,
@@ -64,7 +65,7 @@ use its_primitives::{
 };
 use its_sidechain::{
 	block_composer::{BlockComposer, ComposeBlock},
-	state::SidechainSystemExt,
+	state::{SidechainSystemExt, StateRootExt},
 };
 use litentry_primitives::Identity;
 use sgx_tunittest::*;
@@ -89,6 +90,8 @@ pub extern "C" fn test_main_entrance() -> size_t {
 		itp_stf_state_handler::test::sgx_tests::test_file_io_get_state_hash_works,
 		itp_stf_state_handler::test::sgx_tests::test_list_state_ids_ignores_files_not_matching_the_pattern,
 		itp_stf_state_handler::test::sgx_tests::test_in_memory_state_initializes_from_shard_directory,
+		itp_stf_state_handler::test::sgx_tests::test_rotate_state_key_re_encrypts_state_snapshots,
+		itp_stf_state_handler::test::sgx_tests::test_interrupted_state_key_rotation_is_reverted_if_new_key_was_not_sealed,
 		itp_sgx_crypto::tests::aes_sealing_works,
 		itp_sgx_crypto::tests::aes256_creating_repository_with_same_path_and_prefix_results_in_same_key,
 		itp_sgx_crypto::tests::aes256_creating_repository_with_same_path_and_prefix_but_new_key_results_in_new_key,
@@ -97,6 +100,7 @@ pub extern "C" fn test_main_entrance() -> size_t {
 		itp_sgx_crypto::tests::using_get_ed25519_repository_twice_initializes_key_only_once,
 		itp_sgx_crypto::tests::rsa3072_sealing_works,
 		itp_sgx_crypto::tests::using_get_rsa3072_repository_twice_initializes_key_only_once,
+		itp_sgx_crypto::tests::rotated_rsa3072_key_is_kept_as_previous_key_after_restart,
 		test_compose_block,
 		test_submit_trusted_call_to_top_pool,
 		test_submit_trusted_getter_to_top_pool,
@@ -144,6 +148,7 @@ pub extern "C" fn test_main_entrance() -> size_t {
 		tls_ra::seal_handler::test::seal_shielding_key_works,
 		tls_ra::seal_handler::test::seal_shielding_key_fails_for_invalid_key,
 		tls_ra::seal_handler::test::unseal_seal_shielding_key_works,
+		tls_ra::seal_handler::test::unseal_seal_rotated_shielding_keys_works,
 		tls_ra::seal_handler::test::seal_state_key_works,
 		tls_ra::seal_handler::test::seal_state_key_fails_for_invalid_key,
 		tls_ra::seal_handler::test::unseal_seal_state_key_works,
@@ -152,6 +157,16 @@ pub extern "C" fn test_main_entrance() -> size_t {
 		tls_ra::seal_handler::test::unseal_seal_state_works,
 		tls_ra::tests::test_state_and_key_provisioning,
 		tls_ra::tests::test_tls_ra_server_client_networking,
+		tls_ra::tests::test_tls_ra_provisioning_skips_provisioned_payloads,
+		tls_ra::tests::test_tls_ra_provisioning_of_rotated_shielding_key,
+		tls_ra::tests::test_state_provisioning_accepts_confirmed_state,
+		tls_ra::tests::test_tls_ra_provisioning_resumes_partial_state,
+		tls_ra::tests::test_state_provisioning_rejects_unverified_state,
+		// state backup unit tests
+		state_backup::tests::create_and_restore_state_backup_works,
+		state_backup::tests::restore_state_backup_fails_for_tampered_payload,
+		state_backup::tests::restore_state_backup_fails_for_unregistered_block_author,
+		state_backup::tests::restore_state_backup_fails_for_unsupported_version,
 		// RPC tests
 		direct_rpc_tests::get_state_request_works,
 
@@ -164,6 +179,10 @@ pub extern "C" fn test_main_entrance() -> size_t {
 
 		// evm assertions test
 		lc_evm_dynamic_assertions::repository::sgx_tests::restores_state_from_seal,
+		lc_evm_dynamic_assertions::repository::sgx_tests::seals_every_assertion_version,
+		lc_evm_dynamic_assertions::repository::sgx_tests::drops_oldest_assertion_versions,
+		lc_evm_dynamic_assertions::repository::sgx_tests::restores_state_from_legacy_seal,
+		lc_evm_dynamic_assertions::repository::sgx_tests::seals_assertion_policy,
 
 		// these unit test (?) need an ipfs node running..
 		// ipfs::test_creates_ipfs_content_struct_works,
@@ -211,6 +230,7 @@ fn test_compose_block() {
 	// then
 	assert!(signed_block.verify_signature());
 	assert_eq!(signed_block.block().header().block_number(), 1);
+	assert_eq!(signed_block.block().header().state_root(), state.state_root());
 }
 
 fn test_submit_trusted_call_to_top_pool() {
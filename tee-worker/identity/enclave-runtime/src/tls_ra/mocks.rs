@@ -26,6 +26,7 @@ use std::{
 #[derive(Clone)]
 pub struct SealHandlerMock {
 	pub shielding_key: Arc<RwLock<Vec<u8>>>,
+	pub previous_shielding_key: Arc<RwLock<Vec<u8>>>,
 	pub state_key: Arc<RwLock<Vec<u8>>>,
 	pub state: Arc<RwLock<Vec<u8>>>,
 	pub light_client_state: Arc<RwLock<Vec<u8>>>,
@@ -40,7 +41,14 @@ impl SealHandlerMock {
 		light_client_state: Arc<RwLock<Vec<u8>>>,
 		assertions_state: Arc<RwLock<Vec<u8>>>,
 	) -> Self {
-		Self { shielding_key, state_key, state, light_client_state, assertions_state }
+		Self {
+			shielding_key,
+			previous_shielding_key: Default::default(),
+			state_key,
+			state,
+			light_client_state,
+			assertions_state,
+		}
 	}
 }
 
@@ -50,6 +58,11 @@ impl SealStateAndKeys for SealHandlerMock {
 		Ok(())
 	}
 
+	fn seal_previous_shielding_key(&self, bytes: &[u8]) -> EnclaveResult<()> {
+		*self.previous_shielding_key.write().unwrap() = bytes.to_vec();
+		Ok(())
+	}
+
 	fn seal_state_key(&self, bytes: &[u8]) -> EnclaveResult<()> {
 		*self.state_key.write().unwrap() = bytes.to_vec();
 		Ok(())
@@ -80,6 +93,10 @@ impl UnsealStateAndKeys for SealHandlerMock {
 		Ok(self.shielding_key.read().unwrap().clone())
 	}
 
+	fn unseal_previous_shielding_key(&self) -> EnclaveResult<Vec<u8>> {
+		Ok(self.previous_shielding_key.read().unwrap().clone())
+	}
+
 	fn unseal_state_key(&self) -> EnclaveResult<Vec<u8>> {
 		Ok(self.state_key.read().unwrap().clone())
 	}
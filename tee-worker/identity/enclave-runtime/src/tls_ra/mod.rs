@@ -19,7 +19,8 @@
 //! including the remote attestation and tls / tcp connection part.
 
 use codec::{Decode, Encode, MaxEncodedLen};
-use itp_types::{AccountId, ShardIdentifier};
+use itp_types::{AccountId, ShardIdentifier, H256};
+use std::vec::Vec;
 
 mod authentication;
 pub mod seal_handler;
@@ -54,6 +55,7 @@ pub enum Opcode {
 	State,
 	LightClient,
 	Assertions,
+	PreviousShieldingKey,
 }
 
 impl From<u8> for Opcode {
@@ -64,6 +66,7 @@ impl From<u8> for Opcode {
 			2 => Opcode::State,
 			3 => Opcode::LightClient,
 			4 => Opcode::Assertions,
+			5 => Opcode::PreviousShieldingKey,
 			_ => unimplemented!("Unsupported/unknown Opcode for MU-RA exchange"),
 		}
 	}
@@ -75,9 +78,56 @@ impl Opcode {
 	}
 }
 
+/// Set of payloads, encoded as a bit mask indexed by their opcodes.
+///
+/// Used to resume an interrupted provisioning without transferring the
+/// already sealed payloads again.
+#[derive(Copy, Clone, Debug, Default, Eq, PartialEq, Decode, Encode, MaxEncodedLen)]
+#[repr(transparent)]
+pub struct ProvisionedPayloads(pub u8);
+
+impl ProvisionedPayloads {
+	pub fn contains(&self, opcode: Opcode) -> bool {
+		self.0 & Self::bit(opcode) != 0
+	}
+
+	pub fn insert(&mut self, opcode: Opcode) {
+		self.0 |= Self::bit(opcode);
+	}
+
+	pub fn remove(&mut self, opcode: Opcode) {
+		self.0 &= !Self::bit(opcode);
+	}
+
+	fn bit(opcode: Opcode) -> u8 {
+		1u8.checked_shl(opcode as u32).unwrap_or_default()
+	}
+}
+
+/// Point from which a payload received partially in an interrupted provisioning is resumed.
+///
+/// Sent by the client for the state it has partially received, and by the server ahead of
+/// the state payload, with the offset it actually resumes from (0 for a different state).
+#[derive(Copy, Clone, Debug, Default, Eq, PartialEq, Decode, Encode, MaxEncodedLen)]
+pub struct PayloadResumption {
+	pub payload_hash: H256,
+	pub offset: u64,
+}
+
+/// State received partially in an interrupted provisioning, kept to resume it.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct PartialPayload {
+	pub payload_hash: H256,
+	pub bytes: Vec<u8>,
+}
+
 /// The data structure to be sent by the client to request provisioning
 #[derive(Clone, Debug, Eq, PartialEq, Decode, Encode, MaxEncodedLen)]
 pub struct ClientProvisioningRequest {
 	pub shard: ShardIdentifier,
 	pub account: AccountId,
+	/// Payloads the client already has, the server doesn't send them again.
+	pub skip_payloads: ProvisionedPayloads,
+	/// Part of the state the client already has, the server only sends the rest of it.
+	pub resume_state: PayloadResumption,
 }
@@ -23,12 +23,14 @@ use codec::{Decode, Encode};
 use ita_stf::{State as StfState, StateType as StfStateType};
 use itc_parentchain::light_client::LightClientSealing;
 use itp_sgx_crypto::{
-	key_repository::{AccessKey, MutateKey},
+	key_repository::{AccessKey, MutateKey, RotateKey},
 	Aes,
 };
 use itp_sgx_externalities::SgxExternalitiesTrait;
 use itp_sgx_io::SealedIO;
-use itp_stf_state_handler::handle_state::HandleState;
+use itp_stf_state_handler::{
+	file_io::StateDir, handle_state::HandleState, state_key_rotation::rotate_state_key,
+};
 use itp_types::ShardIdentifier;
 use lc_evm_dynamic_assertions::sealing::UnsealedAssertions;
 use log::*;
@@ -49,6 +51,7 @@ pub struct SealHandler<
 	shielding_key_repository: Arc<ShieldingKeyRepository>,
 	light_client_seal: Arc<LightClientSeal>,
 	assertions_seal: Arc<AssertionsSeal>,
+	state_dir: Option<StateDir>,
 }
 
 impl<ShieldingKeyRepository, StateKeyRepository, StateHandler, LightClientSeal, AssertionsSeal>
@@ -73,12 +76,20 @@ impl<ShieldingKeyRepository, StateKeyRepository, StateHandler, LightClientSeal,
 			shielding_key_repository,
 			light_client_seal,
 			assertions_seal,
+			state_dir: None,
 		}
 	}
+
+	/// Re-encrypt the state snapshots in `state_dir` when a different state key is sealed.
+	pub fn with_state_dir(mut self, state_dir: StateDir) -> Self {
+		self.state_dir = Some(state_dir);
+		self
+	}
 }
 
 pub trait SealStateAndKeys {
 	fn seal_shielding_key(&self, bytes: &[u8]) -> EnclaveResult<()>;
+	fn seal_previous_shielding_key(&self, bytes: &[u8]) -> EnclaveResult<()>;
 	fn seal_state_key(&self, bytes: &[u8]) -> EnclaveResult<()>;
 	fn seal_state(&self, bytes: &[u8], shard: &ShardIdentifier) -> EnclaveResult<()>;
 	fn seal_new_empty_state(&self, shard: &ShardIdentifier) -> EnclaveResult<()>;
@@ -88,6 +99,7 @@ pub trait SealStateAndKeys {
 
 pub trait UnsealStateAndKeys {
 	fn unseal_shielding_key(&self) -> EnclaveResult<Vec<u8>>;
+	fn unseal_previous_shielding_key(&self) -> EnclaveResult<Vec<u8>>;
 	fn unseal_state_key(&self) -> EnclaveResult<Vec<u8>>;
 	fn unseal_state(&self, shard: &ShardIdentifier) -> EnclaveResult<Vec<u8>>;
 	fn unseal_light_client_state(&self) -> EnclaveResult<Vec<u8>>;
@@ -103,7 +115,8 @@ impl<ShieldingKeyRepository, StateKeyRepository, StateHandler, LightClientSeal,
 		LightClientSeal,
 		AssertionsSeal,
 	> where
-	ShieldingKeyRepository: AccessKey<KeyType = Rsa3072KeyPair> + MutateKey<Rsa3072KeyPair>,
+	ShieldingKeyRepository:
+		AccessKey<KeyType = Rsa3072KeyPair> + MutateKey<Rsa3072KeyPair> + RotateKey<Rsa3072KeyPair>,
 	StateKeyRepository: AccessKey<KeyType = Aes> + MutateKey<Aes>,
 	StateHandler: HandleState<StateT = StfState>,
 	LightClientSeal: LightClientSealing,
@@ -120,10 +133,38 @@ impl<ShieldingKeyRepository, StateKeyRepository, StateHandler, LightClientSeal,
 		Ok(())
 	}
 
+	/// Seal the key replaced by the latest rotation of the provisioning peer, if it's
+	/// still within its grace period.
+	fn seal_previous_shielding_key(&self, mut bytes: &[u8]) -> EnclaveResult<()> {
+		let (key_bytes, expires_at) = match Option::<(Vec<u8>, u64)>::decode(&mut bytes)? {
+			Some(previous_key) => previous_key,
+			None => return Ok(()),
+		};
+		let key: Rsa3072KeyPair = serde_json::from_slice(&key_bytes).map_err(|e| {
+			error!("    [Enclave] Received Invalid previous RSA key");
+			EnclaveError::Other(e.into())
+		})?;
+		self.shielding_key_repository.update_previous_key(key, expires_at)?;
+		info!("Successfully stored the previous shielding key, expires at {}", expires_at);
+		Ok(())
+	}
+
 	fn seal_state_key(&self, mut bytes: &[u8]) -> EnclaveResult<()> {
 		let aes = Aes::decode(&mut bytes)?;
-		self.state_key_repository.update_key(aes)?;
-		info!("Successfully stored a new state key");
+		match self.state_dir.as_ref() {
+			// the state snapshots can't be read anymore with the replaced key
+			Some(state_dir) if aes != self.state_key_repository.retrieve_key()? => {
+				let state_key_repository = self.state_key_repository.as_ref();
+				self.state_handler.execute_without_snapshot_writes(|| {
+					rotate_state_key(state_dir, state_key_repository, aes)
+				})??;
+				info!("Successfully stored a new state key and re-encrypted the state snapshots");
+			},
+			_ => {
+				self.state_key_repository.update_key(aes)?;
+				info!("Successfully stored a new state key");
+			},
+		}
 		Ok(())
 	}
 
@@ -176,7 +217,8 @@ impl<ShieldingKeyRepository, StateKeyRepository, StateHandler, LightClientSeal,
 		LightClientSeal,
 		AssertionsSeal,
 	> where
-	ShieldingKeyRepository: AccessKey<KeyType = Rsa3072KeyPair> + MutateKey<Rsa3072KeyPair>,
+	ShieldingKeyRepository:
+		AccessKey<KeyType = Rsa3072KeyPair> + MutateKey<Rsa3072KeyPair> + RotateKey<Rsa3072KeyPair>,
 	StateKeyRepository: AccessKey<KeyType = Aes> + MutateKey<Aes>,
 	StateHandler: HandleState<StateT = StfState>,
 	LightClientSeal: LightClientSealing,
@@ -191,6 +233,21 @@ impl<ShieldingKeyRepository, StateKeyRepository, StateHandler, LightClientSeal,
 		serde_json::to_vec(&shielding_key).map_err(|e| EnclaveError::Other(e.into()))
 	}
 
+	fn unseal_previous_shielding_key(&self) -> EnclaveResult<Vec<u8>> {
+		let previous_key = self
+			.shielding_key_repository
+			.retrieve_previous_key_with_expiry()
+			.map_err(|e| EnclaveError::Other(format!("{:?}", e).into()))?;
+		let previous_key = match previous_key {
+			Some((key, expires_at)) => Some((
+				serde_json::to_vec(&key).map_err(|e| EnclaveError::Other(e.into()))?,
+				expires_at,
+			)),
+			None => None,
+		};
+		Ok(previous_key.encode())
+	}
+
 	fn unseal_state_key(&self) -> EnclaveResult<Vec<u8>> {
 		self.state_key_repository
 			.retrieve_key()
@@ -262,6 +319,32 @@ pub mod test {
 		assert!(result.is_ok());
 	}
 
+	pub fn unseal_seal_rotated_shielding_keys_works() {
+		// given
+		let server_seal_handler = SealHandlerMock::default();
+		let initial_key = Rsa3072KeyPair::new().unwrap();
+		server_seal_handler
+			.shielding_key_repository
+			.update_key(initial_key.clone())
+			.unwrap();
+		server_seal_handler
+			.shielding_key_repository
+			.rotate_key(Rsa3072KeyPair::new().unwrap(), u64::MAX)
+			.unwrap();
+		let client_seal_handler = SealHandlerMock::default();
+
+		// when
+		let previous_key = server_seal_handler.unseal_previous_shielding_key().unwrap();
+		client_seal_handler.seal_previous_shielding_key(&previous_key).unwrap();
+
+		// then
+		let client_repository = &client_seal_handler.shielding_key_repository;
+		assert_eq!(
+			serde_json::to_vec(&client_repository.retrieve_previous_key().unwrap()).unwrap(),
+			serde_json::to_vec(&Some(initial_key)).unwrap()
+		);
+	}
+
 	pub fn seal_state_key_works() {
 		let seal_handler = SealHandlerMock::default();
 		let key_pair_in_bytes = Aes::default().encode();
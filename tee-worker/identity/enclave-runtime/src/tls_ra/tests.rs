@@ -19,9 +19,11 @@
 
 use super::{
 	mocks::SealHandlerMock, tls_ra_client::request_state_provisioning_internal,
-	tls_ra_server::run_state_provisioning_server_internal,
+	tls_ra_server::run_state_provisioning_server_internal, Opcode, PartialPayload,
+	ProvisionedPayloads,
 };
 use crate::{
+	error::Result as EnclaveResult,
 	initialization::global_components::EnclaveStf,
 	tls_ra::seal_handler::{SealHandler, SealStateAndKeys, UnsealStateAndKeys},
 };
@@ -33,10 +35,19 @@ use itp_stf_interface::InitState;
 use itp_stf_primitives::types::AccountId;
 use itp_stf_state_handler::handle_state::HandleState;
 use itp_test::mock::handle_state_mock::HandleStateMock;
-use itp_types::ShardIdentifier;
+use itp_types::{ShardIdentifier, SidechainBlockConfirmation, H256};
+use its_primitives::{
+	traits::Block as BlockT,
+	types::{
+		block_data::BlockData as SidechainBlockData, header::SidechainHeader,
+		Block as SidechainBlock,
+	},
+};
+use its_sidechain::state::{LastBlockExt, SidechainSystemExt, StateRootExt};
 use lc_evm_dynamic_assertions::mock::AssertionsSealMock;
 use sgx_crypto_helper::{rsa3072::Rsa3072KeyPair, RsaKeyPair};
 use sgx_types::{sgx_quote_sign_type_t, sgx_target_info_t};
+use sp_core::hashing::blake2_256;
 use std::{
 	net::{TcpListener, TcpStream},
 	os::unix::io::AsRawFd,
@@ -51,7 +62,10 @@ static SIGN_TYPE: sgx_quote_sign_type_t = sgx_quote_sign_type_t::SGX_UNLINKABLE_
 static SKIP_RA: i32 = 1;
 static QUOTE_SIZE: u32 = 0;
 
-fn run_state_provisioning_server(seal_handler: impl UnsealStateAndKeys, port: u16) {
+fn run_state_provisioning_server(
+	seal_handler: impl UnsealStateAndKeys,
+	port: u16,
+) -> EnclaveResult<()> {
 	let listener = TcpListener::bind(server_addr(port)).unwrap();
 
 	let (socket, _addr) = listener.accept().unwrap();
@@ -64,7 +78,6 @@ fn run_state_provisioning_server(seal_handler: impl UnsealStateAndKeys, port: u1
 		SKIP_RA,
 		seal_handler,
 	)
-	.unwrap();
 }
 
 fn server_addr(port: u16) -> String {
@@ -108,11 +121,12 @@ pub fn test_tls_ra_server_client_networking() {
 
 	// Start server.
 	let server_thread_handle = thread::spawn(move || {
-		run_state_provisioning_server(server_seal_handler, port);
+		run_state_provisioning_server(server_seal_handler, port).unwrap();
 	});
 	thread::sleep(Duration::from_secs(1));
 
 	// Start client.
+	let mut provisioned_payloads = ProvisionedPayloads::default();
 	let socket = TcpStream::connect(server_addr(port)).unwrap();
 	let sgx_target_info: sgx_target_info_t = sgx_target_info_t::default();
 	let result = request_state_provisioning_internal(
@@ -121,6 +135,9 @@ pub fn test_tls_ra_server_client_networking() {
 		Some(&sgx_target_info),
 		Some(&QUOTE_SIZE),
 		shard,
+		None,
+		&mut provisioned_payloads,
+		&mut PartialPayload::default(),
 		SKIP_RA,
 		client_seal_handler,
 		client_account,
@@ -137,6 +154,133 @@ pub fn test_tls_ra_server_client_networking() {
 	assert_eq!(*client_state.read().unwrap(), state_encoded);
 	assert_eq!(*client_state_key.read().unwrap(), state_key_encoded);
 	assert_eq!(*assertions_state.read().unwrap(), assertions_state_encoded);
+	assert!(provisioned_payloads.contains(Opcode::ShieldingKey));
+	assert!(provisioned_payloads.contains(Opcode::State));
+	assert!(provisioned_payloads.contains(Opcode::LightClient));
+}
+
+pub fn test_tls_ra_provisioning_skips_provisioned_payloads() {
+	// given
+	let shard = ShardIdentifier::default();
+	let client_account = AccountId::from([42; 32]);
+	let server_seal_handler = SealHandlerMock::new(
+		Arc::new(RwLock::new(vec![1, 2, 3])),
+		Arc::new(RwLock::new(vec![5, 2, 3, 7])),
+		Arc::new(RwLock::new(vec![1u8; 100])),
+		Arc::new(RwLock::new(vec![2u8; 100])),
+		Arc::new(RwLock::new(vec![])),
+	);
+	let client_shielding_key = Arc::new(RwLock::new(vec![0, 0, 1]));
+	let client_state_key = Arc::new(RwLock::new(vec![0, 0, 2]));
+	let client_state = Arc::new(RwLock::new(vec![0, 0, 3]));
+	let client_light_client_state = Arc::new(RwLock::new(vec![0, 0, 4]));
+	let client_seal_handler = SealHandlerMock::new(
+		client_shielding_key.clone(),
+		client_state_key.clone(),
+		client_state.clone(),
+		client_light_client_state.clone(),
+		Arc::new(RwLock::new(Vec::new())),
+	);
+
+	// the state was sealed in an interrupted provisioning, but not the light client state
+	let mut provisioned_payloads = ProvisionedPayloads::default();
+	provisioned_payloads.insert(Opcode::ShieldingKey);
+	provisioned_payloads.insert(Opcode::StateKey);
+	provisioned_payloads.insert(Opcode::State);
+
+	let port: u16 = 3151;
+	let server_thread_handle = thread::spawn(move || {
+		run_state_provisioning_server(server_seal_handler, port).unwrap();
+	});
+	thread::sleep(Duration::from_secs(1));
+
+	// when
+	let socket = TcpStream::connect(server_addr(port)).unwrap();
+	let sgx_target_info: sgx_target_info_t = sgx_target_info_t::default();
+	let result = request_state_provisioning_internal(
+		socket.as_raw_fd(),
+		SIGN_TYPE,
+		Some(&sgx_target_info),
+		Some(&QUOTE_SIZE),
+		shard,
+		None,
+		&mut provisioned_payloads,
+		&mut PartialPayload::default(),
+		SKIP_RA,
+		client_seal_handler,
+		client_account,
+	);
+	server_thread_handle.join().unwrap();
+
+	// then
+	assert!(result.is_ok());
+	assert_eq!(*client_shielding_key.read().unwrap(), vec![0, 0, 1]);
+	assert_eq!(*client_state_key.read().unwrap(), vec![0, 0, 2]);
+	// state and light client state are provisioned together
+	assert_eq!(*client_state.read().unwrap(), vec![1u8; 100]);
+	assert_eq!(*client_light_client_state.read().unwrap(), vec![2u8; 100]);
+	assert!(provisioned_payloads.contains(Opcode::LightClient));
+	assert!(provisioned_payloads.contains(Opcode::Assertions));
+}
+
+pub fn test_tls_ra_provisioning_of_rotated_shielding_key() {
+	// given
+	let shard = ShardIdentifier::default();
+	let client_account = AccountId::from([42; 32]);
+	let server_seal_handler = SealHandlerMock::new(
+		Arc::new(RwLock::new(vec![1, 2, 3])),
+		Arc::new(RwLock::new(vec![5, 2, 3, 7])),
+		Arc::new(RwLock::new(vec![1u8; 100])),
+		Arc::new(RwLock::new(vec![2u8; 100])),
+		Arc::new(RwLock::new(vec![])),
+	);
+	*server_seal_handler.previous_shielding_key.write().unwrap() = vec![4, 5, 6];
+	let client_state = Arc::new(RwLock::new(vec![0, 0, 3]));
+	let client_seal_handler = SealHandlerMock::new(
+		Arc::new(RwLock::new(vec![0, 0, 1])),
+		Arc::new(RwLock::new(vec![0, 0, 2])),
+		client_state.clone(),
+		Arc::new(RwLock::new(vec![0, 0, 4])),
+		Arc::new(RwLock::new(Vec::new())),
+	);
+
+	// a running worker only syncs the keys of a shielding key rotation
+	let mut provisioned_payloads = ProvisionedPayloads(u8::MAX);
+	provisioned_payloads.remove(Opcode::ShieldingKey);
+	provisioned_payloads.remove(Opcode::PreviousShieldingKey);
+	provisioned_payloads.remove(Opcode::StateKey);
+
+	let port: u16 = 3155;
+	let server_thread_handle = thread::spawn(move || {
+		run_state_provisioning_server(server_seal_handler, port).unwrap();
+	});
+	thread::sleep(Duration::from_secs(1));
+
+	// when
+	let socket = TcpStream::connect(server_addr(port)).unwrap();
+	let sgx_target_info: sgx_target_info_t = sgx_target_info_t::default();
+	let result = request_state_provisioning_internal(
+		socket.as_raw_fd(),
+		SIGN_TYPE,
+		Some(&sgx_target_info),
+		Some(&QUOTE_SIZE),
+		shard,
+		None,
+		&mut provisioned_payloads,
+		&mut PartialPayload::default(),
+		SKIP_RA,
+		client_seal_handler.clone(),
+		client_account,
+	);
+	server_thread_handle.join().unwrap();
+
+	// then
+	assert!(result.is_ok());
+	assert_eq!(*client_seal_handler.shielding_key.read().unwrap(), vec![1, 2, 3]);
+	assert_eq!(*client_seal_handler.previous_shielding_key.read().unwrap(), vec![4, 5, 6]);
+	assert_eq!(*client_seal_handler.state_key.read().unwrap(), vec![5, 2, 3, 7]);
+	assert_eq!(*client_state.read().unwrap(), vec![0, 0, 3]);
+	assert_eq!(provisioned_payloads, ProvisionedPayloads(u8::MAX));
 }
 
 // Test state and key provisioning with 'real' data structures.
@@ -156,11 +300,12 @@ pub fn test_state_and_key_provisioning() {
 
 	// Start server.
 	let server_thread_handle = thread::spawn(move || {
-		run_state_provisioning_server(server_seal_handler, port);
+		run_state_provisioning_server(server_seal_handler, port).unwrap();
 	});
 	thread::sleep(Duration::from_secs(1));
 
 	// Start client.
+	let mut provisioned_payloads = ProvisionedPayloads::default();
 	let socket = TcpStream::connect(server_addr(port)).unwrap();
 	let sgx_target_info: sgx_target_info_t = sgx_target_info_t::default();
 	let result = request_state_provisioning_internal(
@@ -169,6 +314,9 @@ pub fn test_state_and_key_provisioning() {
 		Some(&sgx_target_info),
 		Some(&QUOTE_SIZE),
 		shard,
+		None,
+		&mut provisioned_payloads,
+		&mut PartialPayload::default(),
 		SKIP_RA,
 		client_seal_handler,
 		client_account,
@@ -180,6 +328,171 @@ pub fn test_state_and_key_provisioning() {
 	assert!(result.is_ok());
 }
 
+pub fn test_tls_ra_provisioning_resumes_partial_state() {
+	// given
+	let shard = ShardIdentifier::default();
+	let client_account = AccountId::from([42; 32]);
+	let server_state = vec![1u8; 100];
+	let server_seal_handler = SealHandlerMock::new(
+		Arc::new(RwLock::new(vec![1, 2, 3])),
+		Arc::new(RwLock::new(vec![5, 2, 3, 7])),
+		Arc::new(RwLock::new(server_state.clone())),
+		Arc::new(RwLock::new(vec![2u8; 100])),
+		Arc::new(RwLock::new(vec![])),
+	);
+	let client_state = Arc::new(RwLock::new(Vec::new()));
+	let client_seal_handler = SealHandlerMock::new(
+		Arc::new(RwLock::new(Vec::new())),
+		Arc::new(RwLock::new(Vec::new())),
+		client_state.clone(),
+		Arc::new(RwLock::new(Vec::new())),
+		Arc::new(RwLock::new(Vec::new())),
+	);
+
+	// the first 40 bytes of the state were received in an interrupted provisioning
+	let mut partial_state = PartialPayload {
+		payload_hash: blake2_256(&server_state).into(),
+		bytes: server_state[..40].to_vec(),
+	};
+	let mut provisioned_payloads = ProvisionedPayloads::default();
+
+	let port: u16 = 3156;
+	let server_thread_handle = thread::spawn(move || {
+		run_state_provisioning_server(server_seal_handler, port).unwrap();
+	});
+	thread::sleep(Duration::from_secs(1));
+
+	// when
+	let socket = TcpStream::connect(server_addr(port)).unwrap();
+	let sgx_target_info: sgx_target_info_t = sgx_target_info_t::default();
+	let result = request_state_provisioning_internal(
+		socket.as_raw_fd(),
+		SIGN_TYPE,
+		Some(&sgx_target_info),
+		Some(&QUOTE_SIZE),
+		shard,
+		None,
+		&mut provisioned_payloads,
+		&mut partial_state,
+		SKIP_RA,
+		client_seal_handler,
+		client_account,
+	);
+	server_thread_handle.join().unwrap();
+
+	// then
+	assert!(result.is_ok());
+	assert_eq!(*client_state.read().unwrap(), server_state);
+	assert_eq!(partial_state, PartialPayload::default());
+}
+
+pub fn test_state_provisioning_accepts_confirmed_state() {
+	// given
+	let (confirmed_state, confirmed_block) = state_at_sidechain_block(5);
+	let (later_state, _) = state_at_sidechain_block(6);
+	let confirmed_block =
+		SidechainBlockConfirmation { block_number: 5, block_header_hash: confirmed_block.hash() };
+
+	// when
+	let confirmed = provision_state(confirmed_state, confirmed_block, 3152);
+	let later = provision_state(later_state, confirmed_block, 3157);
+
+	// then
+	assert!(confirmed.is_ok());
+	assert!(later.is_ok());
+}
+
+pub fn test_state_provisioning_rejects_unverified_state() {
+	// given
+	let confirmed_block =
+		SidechainBlockConfirmation { block_number: 5, block_header_hash: H256::repeat_byte(5) };
+	let (outdated_state, _) = state_at_sidechain_block(4);
+	let (forked_state, _) = state_at_sidechain_block(5);
+	let (mut tampered_state, _) = state_at_sidechain_block(6);
+	tampered_state.set_timestamp(&42);
+
+	// when
+	let outdated = provision_state(outdated_state, confirmed_block, 3153);
+	let forked = provision_state(forked_state, confirmed_block, 3154);
+	let tampered = provision_state(tampered_state, confirmed_block, 3158);
+
+	// then
+	assert!(outdated.is_err());
+	assert!(forked.is_err());
+	assert!(tampered.is_err());
+}
+
+/// State after importing a sidechain block with the given number.
+fn state_at_sidechain_block(block_number: u64) -> (State, SidechainBlock) {
+	let mut state = EnclaveStf::init_state(AccountId::new([1u8; 32]));
+	state.set_block_number(&block_number);
+	let block = SidechainBlock {
+		header: SidechainHeader {
+			parent_hash: H256::repeat_byte(1),
+			block_number,
+			shard_id: ShardIdentifier::from([1u8; 32]),
+			block_data_hash: H256::repeat_byte(2),
+			next_finalization_block_number: block_number,
+			state_root: state.state_root(),
+		},
+		block_data: SidechainBlockData {
+			timestamp: 0,
+			layer_one_head: H256::repeat_byte(3),
+			block_author: Default::default(),
+			signed_top_hashes: Vec::new(),
+			encrypted_state_diff: Vec::new(),
+		},
+	};
+	state.set_last_block(&block);
+	(state, block)
+}
+
+/// Provision the given state, with `confirmed_block` being the latest sidechain block
+/// confirmed on the parentchain.
+fn provision_state(
+	state: State,
+	confirmed_block: SidechainBlockConfirmation,
+	port: u16,
+) -> EnclaveResult<()> {
+	let client_account = AccountId::from([42; 32]);
+	let shard = ShardIdentifier::from([1u8; 32]);
+
+	let server_seal_handler = create_seal_handler(
+		Aes::new([3u8; 16], [0u8; 16]),
+		Rsa3072KeyPair::new().unwrap(),
+		state,
+		&shard,
+	);
+	let client_seal_handler =
+		create_seal_handler(Aes::default(), Rsa3072KeyPair::default(), State::default(), &shard);
+
+	// the server fails if the client disconnects early, which is not relevant here
+	let server_thread_handle = thread::spawn(move || {
+		let _ = run_state_provisioning_server(server_seal_handler, port);
+	});
+	thread::sleep(Duration::from_secs(1));
+
+	let mut provisioned_payloads = ProvisionedPayloads::default();
+	let socket = TcpStream::connect(server_addr(port)).unwrap();
+	let sgx_target_info: sgx_target_info_t = sgx_target_info_t::default();
+	let result = request_state_provisioning_internal(
+		socket.as_raw_fd(),
+		SIGN_TYPE,
+		Some(&sgx_target_info),
+		Some(&QUOTE_SIZE),
+		shard,
+		Some(confirmed_block),
+		&mut provisioned_payloads,
+		&mut PartialPayload::default(),
+		SKIP_RA,
+		client_seal_handler,
+		client_account,
+	);
+	server_thread_handle.join().unwrap();
+
+	result
+}
+
 fn create_seal_handler(
 	state_key: Aes,
 	shielding_key: Rsa3072KeyPair,
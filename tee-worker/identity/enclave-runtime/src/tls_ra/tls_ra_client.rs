@@ -17,40 +17,70 @@
 
 //! Implementation of the client part of the state provisioning.
 
-use super::{authentication::ServerAuth, Opcode, TcpHeader};
+use super::{
+	authentication::ServerAuth, Opcode, PartialPayload, PayloadResumption, ProvisionedPayloads,
+	TcpHeader,
+};
 use crate::{
 	attestation::create_ra_report_and_signature,
 	error::{Error as EnclaveError, Result as EnclaveResult},
-	initialization::global_components::{
-		EnclaveSealHandler, GLOBAL_INTEGRITEE_PARENTCHAIN_LIGHT_CLIENT_SEAL,
-		GLOBAL_SHIELDING_KEY_REPOSITORY_COMPONENT, GLOBAL_STATE_KEY_REPOSITORY_COMPONENT,
+	get_base_path,
+	initialization::{
+		global_components::{
+			EnclaveSealHandler, GLOBAL_INTEGRITEE_PARENTCHAIN_LIGHT_CLIENT_SEAL,
+			GLOBAL_SHIELDING_KEY_REPOSITORY_COMPONENT, GLOBAL_STATE_KEY_REPOSITORY_COMPONENT,
+		},
+		refresh_enclave_signer,
 	},
 	ocall::OcallApi,
 	tls_ra::{seal_handler::SealStateAndKeys, ClientProvisioningRequest},
 	GLOBAL_SIGNING_KEY_REPOSITORY_COMPONENT, GLOBAL_STATE_HANDLER_COMPONENT,
 };
-use codec::Encode;
+use codec::{Decode, Encode, MaxEncodedLen};
 
+use ita_stf::{State as StfState, StateType as StfStateType};
 use itp_attestation_handler::{RemoteAttestationType, DEV_HOSTNAME};
 use itp_component_container::ComponentGetter;
 
 use itp_ocall_api::EnclaveAttestationOCallApi;
 use itp_sgx_crypto::key_repository::AccessPubkey;
-use itp_types::{AccountId, ShardIdentifier};
+use itp_sgx_externalities::SgxExternalitiesTrait;
+use itp_stf_state_handler::file_io::StateDir;
+use itp_types::{AccountId, ShardIdentifier, SidechainBlockConfirmation};
+use its_primitives::{
+	traits::{Block as BlockT, Header as HeaderT},
+	types::Block as SidechainBlock,
+};
+use its_sidechain::state::{legacy_header_hash, LastBlockExt, SidechainSystemExt, StateRootExt};
+use lazy_static::lazy_static;
 use lc_evm_dynamic_assertions::{sealing::io::AssertionsSeal, ASSERTIONS_FILE};
 
 use log::*;
 use rustls::{ClientConfig, ClientSession, Stream};
 use sgx_types::*;
+use sp_core::hashing::blake2_256;
 use std::{
 	backtrace::{self, PrintFormat},
+	cmp::min,
 	convert::TryInto,
+	format,
 	io::{Read, Write},
+	mem,
 	net::TcpStream,
 	slice,
-	sync::Arc,
+	sync::{Arc, SgxRwLock as RwLock},
 	vec::Vec,
 };
+
+/// Size of the chunks the state is read in, each chunk is kept if the transfer is interrupted.
+const STATE_CHUNK_SIZE: usize = 64 * 1024;
+
+lazy_static! {
+	/// State received partially in an interrupted provisioning, kept across provisioning
+	/// requests to resume it.
+	static ref PARTIAL_STATE: RwLock<PartialPayload> = Default::default();
+}
+
 /// Client part of the TCP-level connection and the underlying TLS-level session.
 ///
 /// Includes a seal handler, which handles the storage part of the received data.
@@ -61,6 +91,9 @@ where
 	tls_stream: Stream<'a, ClientSession, TcpStream>,
 	seal_handler: StateAndKeySealer,
 	shard: ShardIdentifier,
+	confirmed_block: Option<SidechainBlockConfirmation>,
+	provisioned_payloads: &'a mut ProvisionedPayloads,
+	partial_state: &'a mut PartialPayload,
 }
 
 impl<'a, StateAndKeySealer> TlsClient<'a, StateAndKeySealer>
@@ -71,14 +104,25 @@ where
 		tls_stream: Stream<'a, ClientSession, TcpStream>,
 		seal_handler: StateAndKeySealer,
 		shard: ShardIdentifier,
+		confirmed_block: Option<SidechainBlockConfirmation>,
+		provisioned_payloads: &'a mut ProvisionedPayloads,
+		partial_state: &'a mut PartialPayload,
 	) -> TlsClient<StateAndKeySealer> {
-		TlsClient { tls_stream, seal_handler, shard }
+		TlsClient {
+			tls_stream,
+			seal_handler,
+			shard,
+			confirmed_block,
+			provisioned_payloads,
+			partial_state,
+		}
 	}
 
 	/// Read all data sent by the server of the specific shard.
 	///
-	/// We trust here that the server sends us the correct data, as
-	/// we do not have any way to test it.
+	/// Apart from the state, which must match the header of its last sidechain block and not
+	/// be older than the latest sidechain block confirmed on the parentchain, we trust here
+	/// that the server sends us the correct data, as we do not have any way to test it.
 	fn obtain_provisioning_for_shard(&mut self, account: AccountId) -> EnclaveResult<()> {
 		debug!(
 			"obtain_provisioning_for_shard called, about to call self.send_provisioning_request()."
@@ -91,31 +135,43 @@ where
 	/// Send the shard of the state we want to receive to the provisioning server.
 	fn send_provisioning_request(&mut self, account: AccountId) -> EnclaveResult<()> {
 		debug!("self.send_provisioning_request() called.");
-		self.tls_stream
-			.write_all(&ClientProvisioningRequest { shard: self.shard, account }.encode())?;
+		let request = ClientProvisioningRequest {
+			shard: self.shard,
+			account,
+			skip_payloads: self.skip_payloads(),
+			resume_state: PayloadResumption {
+				payload_hash: self.partial_state.payload_hash,
+				offset: self.partial_state.bytes.len() as u64,
+			},
+		};
+		self.tls_stream.write_all(&request.encode())?;
 		debug!("write_all succeeded.");
 		Ok(())
 	}
 
+	/// Payloads which don't need to be provisioned again.
+	///
+	/// The state and the light client state must come from the same peer, otherwise
+	/// parentchain blocks could be skipped or imported twice.
+	fn skip_payloads(&self) -> ProvisionedPayloads {
+		let mut skip_payloads = *self.provisioned_payloads;
+		if !skip_payloads.contains(Opcode::LightClient) {
+			skip_payloads.remove(Opcode::State);
+		}
+		skip_payloads
+	}
+
 	/// Read and seal all relevant data sent by the server.
 	fn read_and_seal_all(&mut self) -> EnclaveResult<()> {
-		let mut received_payloads: Vec<Opcode> = Vec::new();
-
-		loop {
-			let maybe_opcode = self.read_and_seal()?;
-			match maybe_opcode {
-				None => break,
-				Some(o) => {
-					received_payloads.push(o);
-				},
-			}
+		while let Some(opcode) = self.read_and_seal()? {
+			self.provisioned_payloads.insert(opcode);
 		}
 		info!("Successfully read and sealed all data sent by the state provisioning server.");
 
 		// In case we receive a shielding key, but no state, we need to reset our state
 		// to update the enclave account.
-		if received_payloads.contains(&Opcode::ShieldingKey)
-			&& !received_payloads.contains(&Opcode::State)
+		if self.provisioned_payloads.contains(Opcode::ShieldingKey)
+			&& !self.provisioned_payloads.contains(Opcode::State)
 		{
 			self.seal_handler.seal_new_empty_state(&self.shard)?;
 		}
@@ -132,11 +188,19 @@ where
 			return Ok(None)
 		}
 		let header = self.read_header(start_byte[0])?;
-		let bytes = self.read_until(header.payload_length as usize)?;
+		let bytes = match header.opcode {
+			Opcode::State => self.read_state(header.payload_length as usize)?,
+			_ => self.read_until(header.payload_length as usize)?,
+		};
 		match header.opcode {
 			Opcode::ShieldingKey => self.seal_handler.seal_shielding_key(&bytes)?,
+			Opcode::PreviousShieldingKey =>
+				self.seal_handler.seal_previous_shielding_key(&bytes)?,
 			Opcode::StateKey => self.seal_handler.seal_state_key(&bytes)?,
-			Opcode::State => self.seal_handler.seal_state(&bytes, &self.shard)?,
+			Opcode::State => {
+				verify_provisioned_state(&bytes, &self.confirmed_block)?;
+				self.seal_handler.seal_state(&bytes, &self.shard)?
+			},
 			Opcode::LightClient => self.seal_handler.seal_light_client_state(&bytes)?,
 			Opcode::Assertions => self.seal_handler.seal_assertions_state(&bytes)?,
 		};
@@ -160,6 +224,42 @@ where
 		Ok(TcpHeader::new(opcode, payload_length))
 	}
 
+	/// Read the state, continuing the partially received state if the server resumes it.
+	///
+	/// The state is read in chunks, which are kept if the transfer is interrupted.
+	fn read_state(&mut self, length: usize) -> EnclaveResult<Vec<u8>> {
+		let mut resumption = vec![0u8; PayloadResumption::max_encoded_len()];
+		self.tls_stream.read_exact(&mut resumption)?;
+		let resumption = PayloadResumption::decode(&mut resumption.as_slice())?;
+		let offset = resumption.offset as usize;
+		if offset > length
+			|| (offset > 0
+				&& (self.partial_state.payload_hash != resumption.payload_hash
+					|| self.partial_state.bytes.len() < offset))
+		{
+			return Err(EnclaveError::Other("Server resumes a state we haven't received".into()))
+		}
+		debug!("Read state payload - length: {}, resumed from: {}", length, offset);
+
+		self.partial_state.payload_hash = resumption.payload_hash;
+		self.partial_state.bytes.truncate(offset);
+		let mut chunk = vec![0u8; STATE_CHUNK_SIZE];
+		while self.partial_state.bytes.len() < length {
+			let chunk_length = min(chunk.len(), length - self.partial_state.bytes.len());
+			let read_size = self.tls_stream.read(&mut chunk[..chunk_length])?;
+			if read_size == 0 {
+				return Err(EnclaveError::Other("Connection closed while reading the state".into()))
+			}
+			self.partial_state.bytes.extend_from_slice(&chunk[..read_size]);
+		}
+
+		let state = mem::take(&mut *self.partial_state).bytes;
+		if blake2_256(&state) != resumption.payload_hash.0 {
+			return Err(EnclaveError::Other("Provisioned state doesn't match its hash".into()))
+		}
+		Ok(state)
+	}
+
 	/// Read all bytes into a buffer of given length.
 	fn read_until(&mut self, length: usize) -> EnclaveResult<Vec<u8>> {
 		let mut bytes = vec![0u8; length];
@@ -168,7 +268,63 @@ where
 	}
 }
 
+/// Ensure the provisioned state is the state after its last sidechain block, and that block
+/// is the latest sidechain block confirmed on the parentchain or a later one.
+///
+/// Blocks after the confirmed one can't be verified against the parentchain, but their
+/// header still has to match the state. A migrated last block keeps the hash of its legacy
+/// header, see `its_state::migrate_last_block`.
+fn verify_provisioned_state(
+	mut bytes: &[u8],
+	confirmed_block: &Option<SidechainBlockConfirmation>,
+) -> EnclaveResult<()> {
+	let confirmed_block = match confirmed_block {
+		Some(b) => b,
+		None => return Ok(()),
+	};
+	let state = StfState::new(StfStateType::decode(&mut bytes)?);
+	let last_block: SidechainBlock = state.get_last_block().ok_or_else(|| {
+		EnclaveError::Other("Provisioned state has no sidechain block, but one is confirmed".into())
+	})?;
+	let block_number = last_block.header().block_number();
+	let last_block_hash = state.get_last_block_hash();
+	let is_last_block_hash_valid = last_block_hash == Some(last_block.hash())
+		|| last_block_hash == Some(legacy_header_hash(last_block.header()));
+
+	if !is_last_block_hash_valid || state.state_root() != last_block.header().state_root() {
+		return Err(EnclaveError::Other(
+			format!(
+				"Provisioned state doesn't match the header of its sidechain block {}",
+				block_number
+			)
+			.into(),
+		))
+	}
+	if block_number < confirmed_block.block_number {
+		return Err(EnclaveError::Other(
+			format!(
+				"Provisioned state is at sidechain block {}, but block {} is confirmed already",
+				block_number, confirmed_block.block_number
+			)
+			.into(),
+		))
+	}
+	if block_number == confirmed_block.block_number
+		&& last_block_hash != Some(confirmed_block.block_header_hash)
+	{
+		return Err(EnclaveError::Other(
+			format!(
+				"Provisioned state doesn't match the confirmed sidechain block {}",
+				block_number
+			)
+			.into(),
+		))
+	}
+	Ok(())
+}
+
 #[no_mangle]
+#[allow(clippy::too_many_arguments)]
 pub unsafe extern "C" fn request_state_provisioning(
 	socket_fd: c_int,
 	sign_type: sgx_quote_sign_type_t,
@@ -176,11 +332,38 @@ pub unsafe extern "C" fn request_state_provisioning(
 	quote_size: Option<&u32>,
 	shard: *const u8,
 	shard_size: u32,
+	confirmed_block: *const u8,
+	confirmed_block_size: u32,
+	provisioned_payloads: *mut u8,
 	skip_ra: c_int,
 ) -> sgx_status_t {
 	let _ = backtrace::enable_backtrace("enclave.signed.so", PrintFormat::Short);
 	let shard = ShardIdentifier::from_slice(slice::from_raw_parts(shard, shard_size as usize));
 
+	let mut confirmed_block_slice =
+		slice::from_raw_parts(confirmed_block, confirmed_block_size as usize);
+	let confirmed_block: Option<SidechainBlockConfirmation> =
+		match Decode::decode(&mut confirmed_block_slice) {
+			Ok(b) => b,
+			Err(e) => {
+				error!("Failed to decode confirmed sidechain block: {:?}", e);
+				return sgx_status_t::SGX_ERROR_UNEXPECTED
+			},
+		};
+
+	let provisioned_payloads = match (provisioned_payloads as *mut ProvisionedPayloads).as_mut() {
+		Some(p) => p,
+		None => return sgx_status_t::SGX_ERROR_INVALID_PARAMETER,
+	};
+
+	let mut partial_state = match PARTIAL_STATE.write() {
+		Ok(p) => p,
+		Err(e) => {
+			error!("{:?}", e);
+			return sgx_status_t::SGX_ERROR_UNEXPECTED
+		},
+	};
+
 	let state_handler = match GLOBAL_STATE_HANDLER_COMPONENT.get() {
 		Ok(s) => s,
 		Err(e) => {
@@ -215,13 +398,19 @@ pub unsafe extern "C" fn request_state_provisioning(
 
 	let assertions_seal = Arc::new(AssertionsSeal::new(ASSERTIONS_FILE.into()));
 
+	let state_dir = match get_base_path() {
+		Ok(p) => StateDir::new(p),
+		Err(e) => return e.into(),
+	};
+
 	let seal_handler = EnclaveSealHandler::new(
 		state_handler,
 		state_key_repository,
 		shielding_key_repository,
 		light_client_seal,
 		assertions_seal,
-	);
+	)
+	.with_state_dir(state_dir);
 
 	let signing_key_repository = match GLOBAL_SIGNING_KEY_REPOSITORY_COMPONENT.get() {
 		Ok(s) => s,
@@ -242,6 +431,9 @@ pub unsafe extern "C" fn request_state_provisioning(
 		quoting_enclave_target_info,
 		quote_size,
 		shard,
+		confirmed_block,
+		provisioned_payloads,
+		&mut partial_state,
 		skip_ra,
 		seal_handler,
 		client_account,
@@ -250,10 +442,21 @@ pub unsafe extern "C" fn request_state_provisioning(
 		return e.into()
 	};
 
+	// the enclave signer is derived from the provisioned shielding key
+	if provisioned_payloads.contains(Opcode::ShieldingKey) {
+		if let Err(e) = refresh_enclave_signer() {
+			error!("Failed to update the enclave signer: {:?}", e);
+			return e.into()
+		}
+	}
+
 	sgx_status_t::SGX_SUCCESS
 }
 
 /// Internal [`request_state_provisioning`] function to be able to use the handy `?` operator.
+///
+/// `provisioned_payloads` is updated with every sealed payload and `partial_state` with every
+/// chunk of the state read, so that an interrupted provisioning can be resumed.
 // allowing clippy rant because this fn will be refactored with MU RA deprecation
 #[allow(clippy::too_many_arguments)]
 pub(crate) fn request_state_provisioning_internal<StateAndKeySealer: SealStateAndKeys>(
@@ -262,6 +465,9 @@ pub(crate) fn request_state_provisioning_internal<StateAndKeySealer: SealStateAn
 	quoting_enclave_target_info: Option<&sgx_target_info_t>,
 	quote_size: Option<&u32>,
 	shard: ShardIdentifier,
+	confirmed_block: Option<SidechainBlockConfirmation>,
+	provisioned_payloads: &mut ProvisionedPayloads,
+	partial_state: &mut PartialPayload,
 	skip_ra: c_int,
 	seal_handler: StateAndKeySealer,
 	client_account: AccountId,
@@ -282,6 +488,9 @@ pub(crate) fn request_state_provisioning_internal<StateAndKeySealer: SealStateAn
 		rustls::Stream::new(&mut client_session, &mut tcp_stream),
 		seal_handler,
 		shard,
+		confirmed_block,
+		provisioned_payloads,
+		partial_state,
 	);
 
 	info!("Requesting keys and state from mu-ra server of fellow validateer");
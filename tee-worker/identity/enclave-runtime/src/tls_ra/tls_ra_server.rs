@@ -17,7 +17,10 @@
 
 //! Implementation of the server part of the state provisioning.
 
-use super::{authentication::ClientAuth, ClientProvisioningRequest, Opcode, TcpHeader};
+use super::{
+	authentication::ClientAuth, ClientProvisioningRequest, Opcode, PayloadResumption,
+	ProvisionedPayloads, TcpHeader,
+};
 use crate::{
 	attestation::create_ra_report_and_signature,
 	error::{Error as EnclaveError, Result as EnclaveResult},
@@ -29,7 +32,7 @@ use crate::{
 	tls_ra::seal_handler::UnsealStateAndKeys,
 	GLOBAL_STATE_HANDLER_COMPONENT,
 };
-use codec::Decode;
+use codec::{Decode, Encode, MaxEncodedLen};
 use itp_attestation_handler::RemoteAttestationType;
 use itp_component_container::ComponentGetter;
 use itp_ocall_api::EnclaveAttestationOCallApi;
@@ -39,8 +42,10 @@ use lc_evm_dynamic_assertions::{sealing::io::AssertionsSeal, ASSERTIONS_FILE};
 use log::*;
 use rustls::{ServerConfig, ServerSession, StreamOwned};
 use sgx_types::*;
+use sp_core::hashing::blake2_256;
 use std::{
 	backtrace::{self, PrintFormat},
+	cmp::min,
 	io::{Read, Write},
 	net::TcpStream,
 	sync::Arc,
@@ -91,12 +96,16 @@ where
 		let request = self.await_shard_request_from_client()?;
 		println!("    [Enclave] (MU-RA-Server) handle_shard_request_from_client, await_shard_request_from_client() OK");
 		println!("    [Enclave] (MU-RA-Server) handle_shard_request_from_client, write_all()");
-		self.write_provisioning_payloads(&request.shard)
+		self.write_provisioning_payloads(
+			&request.shard,
+			request.skip_payloads,
+			request.resume_state,
+		)
 	}
 
 	/// Read the shard of the state the client wants to receive.
 	fn await_shard_request_from_client(&mut self) -> EnclaveResult<ClientProvisioningRequest> {
-		let mut request = [0u8; std::mem::size_of::<ClientProvisioningRequest>()];
+		let mut request = vec![0u8; ClientProvisioningRequest::max_encoded_len()];
 		println!(
 			"    [Enclave] (MU-RA-Server) await_shard_request_from_client, calling read_exact()"
 		);
@@ -105,21 +114,39 @@ where
 			.map_err(|_| EnclaveError::Other("matching byte size can't fail to decode".into()))
 	}
 
-	/// Sends all relevant data to the client.
-	fn write_provisioning_payloads(&mut self, shard: &ShardIdentifier) -> EnclaveResult<()> {
-		debug!("Provisioning is set to: {:?}", self.provisioning_payload);
-		match self.provisioning_payload {
-			ProvisioningPayload::Everything => {
-				self.write_shielding_key()?;
-				self.write_state_key()?;
-				self.write_state(shard)?;
-				self.write_light_client_state()?;
-				self.write_assertions_state()?;
-			},
-			ProvisioningPayload::ShieldingKeyAndLightClient => {
-				self.write_shielding_key()?;
-				self.write_light_client_state()?;
-			},
+	/// Sends all relevant data to the client, except for the payloads it already has.
+	fn write_provisioning_payloads(
+		&mut self,
+		shard: &ShardIdentifier,
+		skip_payloads: ProvisionedPayloads,
+		resume_state: PayloadResumption,
+	) -> EnclaveResult<()> {
+		debug!(
+			"Provisioning is set to: {:?}, skipping: {:?}",
+			self.provisioning_payload, skip_payloads
+		);
+		let opcodes: &[Opcode] = match self.provisioning_payload {
+			ProvisioningPayload::Everything => &[
+				Opcode::ShieldingKey,
+				Opcode::PreviousShieldingKey,
+				Opcode::StateKey,
+				Opcode::State,
+				Opcode::LightClient,
+				Opcode::Assertions,
+			],
+			ProvisioningPayload::ShieldingKeyAndLightClient =>
+				&[Opcode::ShieldingKey, Opcode::PreviousShieldingKey, Opcode::LightClient],
+		};
+
+		for opcode in opcodes.iter().filter(|o| !skip_payloads.contains(**o)) {
+			match opcode {
+				Opcode::ShieldingKey => self.write_shielding_key()?,
+				Opcode::PreviousShieldingKey => self.write_previous_shielding_key()?,
+				Opcode::StateKey => self.write_state_key()?,
+				Opcode::State => self.write_state(shard, resume_state)?,
+				Opcode::LightClient => self.write_light_client_state()?,
+				Opcode::Assertions => self.write_assertions_state()?,
+			}
 		}
 
 		debug!("Successfully provisioned all payloads to peer");
@@ -132,15 +159,38 @@ where
 		Ok(())
 	}
 
+	fn write_previous_shielding_key(&mut self) -> EnclaveResult<()> {
+		let previous_shielding_key = self.seal_handler.unseal_previous_shielding_key()?;
+		self.write(Opcode::PreviousShieldingKey, &previous_shielding_key)?;
+		Ok(())
+	}
+
 	fn write_state_key(&mut self) -> EnclaveResult<()> {
 		let state_key = self.seal_handler.unseal_state_key()?;
 		self.write(Opcode::StateKey, &state_key)?;
 		Ok(())
 	}
 
-	fn write_state(&mut self, shard: &ShardIdentifier) -> EnclaveResult<()> {
+	/// Sends the state, preceded by the point it is resumed from. Only the rest of the state
+	/// is sent if the client has already received a part of this very state.
+	fn write_state(
+		&mut self,
+		shard: &ShardIdentifier,
+		resume_state: PayloadResumption,
+	) -> EnclaveResult<()> {
 		let state = self.seal_handler.unseal_state(shard)?;
-		self.write(Opcode::State, &state)?;
+		let payload_hash = blake2_256(&state).into();
+		let offset = if resume_state.payload_hash == payload_hash {
+			min(resume_state.offset, state.len() as u64)
+		} else {
+			0
+		};
+
+		self.write_header(TcpHeader::new(Opcode::State, state.len() as u64))?;
+		self.tls_stream
+			.write_all(&PayloadResumption { payload_hash, offset }.encode())?;
+		debug!("Write state payload - length: {}, resumed from: {}", state.len(), offset);
+		self.tls_stream.write_all(&state[offset as usize..])?;
 		Ok(())
 	}
 
@@ -24,6 +24,7 @@ use crate::{
 		GLOBAL_SIDECHAIN_IMPORT_QUEUE_WORKER_COMPONENT, GLOBAL_SIGNING_KEY_REPOSITORY_COMPONENT,
 		GLOBAL_STATE_HANDLER_COMPONENT, GLOBAL_TOP_POOL_AUTHOR_COMPONENT,
 	},
+	rpc::id_graph_subscription_api::notify_id_graph_subscribers,
 	sync::{EnclaveLock, EnclaveStateRWLock},
 	utils::{
 		get_extrinsic_factory_from_integritee_solo_or_parachain,
@@ -167,6 +168,15 @@ fn execute_top_pool_trusted_calls_internal() -> Result<()> {
 
 	let top_pool_author = GLOBAL_TOP_POOL_AUTHOR_COMPONENT.get()?;
 
+	// The state is synced now, re-insert the trusted calls that were pending when the worker was
+	// stopped (once per shard) and seal the calls submitted since the last slot.
+	for shard in shards.iter() {
+		if let Err(e) = top_pool_author.restore_pending_tops(*shard) {
+			error!("Failed to restore pending trusted calls of shard {:?}: {:?}", shard, e);
+		}
+		top_pool_author.seal_pending_tops(*shard);
+	}
+
 	let block_composer = GLOBAL_SIDECHAIN_BLOCK_COMPOSER_COMPONENT.get()?;
 
 	let ocall_api = GLOBAL_OCALL_API_COMPONENT.get()?;
@@ -204,7 +214,7 @@ fn execute_top_pool_trusted_calls_internal() -> Result<()> {
 			log_remaining_slot_duration(&slot, "Before AURA");
 
 			let env = ProposerFactory::<Block, _, _, _, _>::new(
-				top_pool_author,
+				top_pool_author.clone(),
 				stf_executor,
 				block_composer,
 				ocall_api.clone(),
@@ -228,7 +238,7 @@ fn execute_top_pool_trusted_calls_internal() -> Result<()> {
 					maybe_target_a_parentchain_import_dispatcher,
 					maybe_target_b_parentchain_import_dispatcher,
 					env,
-					shards,
+					shards.clone(),
 				)?;
 
 			if_development!({
@@ -249,6 +259,8 @@ fn execute_top_pool_trusted_calls_internal() -> Result<()> {
 			send_blocks_and_extrinsics::<Block, _, _>(blocks, parentchain_calls, ocall_api)?;
 
 			log_remaining_slot_duration(&slot, "After broadcasting and sending extrinsic");
+
+			notify_id_graph_subscribers(state_handler.as_ref(), &shards, top_pool_author.as_ref());
 		},
 		None => {
 			debug!("No slot yielded. Skipping block production.");
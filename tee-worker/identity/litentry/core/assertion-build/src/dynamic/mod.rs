@@ -18,7 +18,7 @@ use crate::{dynamic::repository::SmartContractByteCode, *};
 use itp_ocall_api::EnclaveMetricsOCallApi;
 use itp_types::Assertion;
 use lc_credentials::{assertion_logic::AssertionLogic, Credential, IssuerRuntimeVersion};
-use lc_dynamic_assertion::{AssertionExecutor, AssertionLogicRepository};
+use lc_dynamic_assertion::{AssertionExecutionError, AssertionExecutor, AssertionLogicRepository};
 use lc_evm_dynamic_assertions::EvmAssertionExecutor;
 use litentry_primitives::AssertionBuildRequest;
 use log::error;
@@ -44,10 +44,17 @@ pub fn build<
 			&req.identities,
 		)
 		.map_err(|e| {
-			Error::RequestVCFailed(
-				Assertion::Dynamic(params.clone()),
-				ErrorDetail::StfError(ErrorString::truncate_from(e.into())),
-			)
+			let detail = match e {
+				AssertionExecutionError::LimitExceeded(reason) =>
+					ErrorDetail::AssertionLimitExceeded(ErrorString::truncate_from(reason.into())),
+				AssertionExecutionError::DomainNotAllowed(reason) =>
+					ErrorDetail::AssertionDomainNotAllowed(ErrorString::truncate_from(
+						reason.into(),
+					)),
+				AssertionExecutionError::Other(reason) =>
+					ErrorDetail::StfError(ErrorString::truncate_from(reason.into())),
+			};
+			Error::RequestVCFailed(Assertion::Dynamic(params.clone()), detail)
 		})?;
 
 	let runtime_version = IssuerRuntimeVersion {
@@ -75,6 +82,10 @@ pub fn build<
 				result.schema_url,
 				result.meet,
 			);
+			credential_unsigned.update_dynamic_assertion_info(
+				format!("{:?}", params.smart_contract_id),
+				result.version,
+			);
 
 			Ok((credential_unsigned, if params.return_log { result.contract_logs } else { vec![] }))
 		},
@@ -149,6 +160,13 @@ pub mod assertion_test {
 
 		// then
 		assert!(credential.credential_subject.values[0]);
+		assert_eq!(
+			credential
+				.credential_subject
+				.dynamic_assertion
+				.map(|info| (info.id, info.version)),
+			Some((format!("{:?}", hash(1)), 0))
+		);
 		// assert!(vc_logs.len() == 0);
 	}
 
@@ -20,7 +20,7 @@ compile_error!("feature \"std\" and feature \"sgx\" cannot be enabled at the sam
 #[cfg(all(not(feature = "std"), feature = "sgx"))]
 extern crate sgx_tstd as std;
 
-use lc_dynamic_assertion::AssertionLogicRepository;
+use lc_dynamic_assertion::{AssertionLogicRepository, AssertionPolicy, AssertionVersion};
 use primitive_types::H160;
 use std::{
 	collections::HashMap,
@@ -40,6 +40,7 @@ use std::sync::Mutex;
 #[allow(clippy::type_complexity)]
 pub struct InMemorySmartContractRepo {
 	map: Mutex<HashMap<H160, (Vec<u8>, Vec<String>)>>,
+	policies: Mutex<HashMap<H160, AssertionPolicy>>,
 }
 
 impl InMemorySmartContractRepo {
@@ -78,7 +79,7 @@ impl InMemorySmartContractRepo {
 				)
 		);
 
-		InMemorySmartContractRepo { map: map.into() }
+		InMemorySmartContractRepo { map: map.into(), policies: HashMap::new().into() }
 	}
 }
 
@@ -88,18 +89,45 @@ impl Default for InMemorySmartContractRepo {
 	}
 }
 
+// keeps a single version of each assertion, always reported as version 0
 impl AssertionLogicRepository for InMemorySmartContractRepo {
 	type Id = H160;
 	type Item = (Vec<u8>, Vec<String>);
 
-	fn get(&self, id: &H160) -> Result<Option<Self::Item>, String> {
-		Ok(self.map.lock().unwrap().get(id).cloned())
+	fn get(&self, id: &H160) -> Result<Option<(AssertionVersion, Self::Item)>, String> {
+		Ok(self.map.lock().unwrap().get(id).cloned().map(|item| (0, item)))
 	}
 
-	fn save(&self, id: Self::Id, item: Self::Item) -> Result<(), String> {
+	fn get_version(
+		&self,
+		id: &Self::Id,
+		version: AssertionVersion,
+	) -> Result<Option<Self::Item>, String> {
+		Ok(self.map.lock().unwrap().get(id).filter(|_| version == 0).cloned())
+	}
+
+	fn save(
+		&self,
+		id: Self::Id,
+		_version: AssertionVersion,
+		item: Self::Item,
+	) -> Result<(), String> {
 		self.map.lock().unwrap().insert(id, item);
 		Ok(())
 	}
+
+	fn activate(&self, _id: &Self::Id, _version: AssertionVersion) -> Result<(), String> {
+		Ok(())
+	}
+
+	fn get_policy(&self, id: &Self::Id) -> Result<Option<AssertionPolicy>, String> {
+		Ok(self.policies.lock().unwrap().get(id).cloned())
+	}
+
+	fn save_policy(&self, id: Self::Id, policy: AssertionPolicy) -> Result<(), String> {
+		self.policies.lock().unwrap().insert(id, policy);
+		Ok(())
+	}
 }
 
 fn hash(a: u64) -> H160 {
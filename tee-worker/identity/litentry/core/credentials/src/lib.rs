@@ -134,6 +134,15 @@ impl Issuer {
 	}
 }
 
+#[derive(Serialize, Deserialize, Encode, Decode, Clone, Debug, PartialEq, Eq, TypeInfo)]
+#[serde(rename_all = "camelCase")]
+pub struct DynamicAssertionInfo {
+	/// ID of the assertion smart contract
+	pub id: String,
+	/// Version of the assertion smart contract
+	pub version: u32,
+}
+
 #[derive(Serialize, Deserialize, Encode, Decode, Clone, Debug, PartialEq, Eq, TypeInfo)]
 #[serde(rename_all = "camelCase")]
 pub struct CredentialSubject {
@@ -146,6 +155,9 @@ pub struct CredentialSubject {
 	/// (Optional) Data source definitions for trusted data providers
 	#[serde(skip_serializing_if = "Option::is_none")]
 	pub data_source: Option<Vec<DataSource>>,
+	/// (Optional) The dynamic assertion the credential was computed with
+	#[serde(skip_serializing_if = "Option::is_none")]
+	pub dynamic_assertion: Option<DynamicAssertionInfo>,
 	/// Several sets of assertions.
 	/// Each assertion contains multiple steps to describe how to fetch data and calculate the value
 	#[serde(skip_deserializing)]
@@ -269,6 +281,14 @@ impl Credential {
 		Ok(vc)
 	}
 
+	/// Turns the credential into a new, unsigned one with the same claims, it gets a fresh
+	/// id and issuance date and has to be signed again.
+	pub fn reissue(&mut self) {
+		self.issuance_date = now_as_iso8601();
+		self.proof = None;
+		self.generate_id();
+	}
+
 	pub fn add_proof(&mut self, sig: &Vec<u8>, verification_method: String) {
 		self.proof = Some(Proof::new(sig, verification_method));
 	}
@@ -539,6 +559,10 @@ impl Credential {
 		self.credential_subject.values = vec![result];
 		self.credential_schema.id = schema_url;
 	}
+
+	pub fn update_dynamic_assertion_info(&mut self, id: String, version: u32) {
+		self.credential_subject.dynamic_assertion = Some(DynamicAssertionInfo { id, version });
+	}
 }
 
 /// Assertion To-Date
@@ -584,6 +608,22 @@ mod tests {
 		assert_eq!(id, "did:litentry:substrate:0x0000000000000000000000000000000000000000000000000000000000000000");
 	}
 
+	#[test]
+	fn reissue_works() {
+		let identity = AccountId::from([0; 32]).into();
+		let shard = ShardIdentifier::default();
+		let runtime_version = IssuerRuntimeVersion { parachain: 0u32, sidechain: 0u32 };
+		let mut vc = Credential::new(&identity, &shard, &runtime_version).unwrap();
+		vc.add_proof(&vec![1u8; 64], "verification_method".to_string());
+		let id = vc.id.clone();
+
+		vc.reissue();
+
+		assert_ne!(vc.id, id);
+		assert!(vc.proof.is_none());
+		assert!(vc.validate_unsigned().is_ok());
+	}
+
 	#[test]
 	fn update_holder_works() {
 		let who = AccountId::from([0; 32]);
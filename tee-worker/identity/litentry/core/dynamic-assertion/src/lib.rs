@@ -22,19 +22,47 @@ compile_error!("feature \"std\" and feature \"sgx\" cannot be enabled at the sam
 #[cfg(all(not(feature = "std"), feature = "sgx"))]
 extern crate sgx_tstd as std;
 
-pub use litentry_primitives::{Identity, IdentityNetworkTuple, Web3Network};
-use std::{string::String, vec::Vec};
+use codec::{Decode, Encode};
+pub use litentry_primitives::{AssertionPolicy, Identity, IdentityNetworkTuple, Web3Network};
+use std::{
+	string::{String, ToString},
+	vec::Vec,
+};
+
+pub type AssertionVersion = u32;
 
 // Used to retrieve assertion logic and secrets
 pub trait AssertionLogicRepository {
 	type Id;
 	type Item;
 
+	// returns the active version of the assertion together with its logic
 	#[allow(clippy::type_complexity)]
-	fn get(&self, id: &Self::Id) -> Result<Option<Self::Item>, String>;
-	fn save(&self, id: Self::Id, item: Self::Item) -> Result<(), String>;
+	fn get(&self, id: &Self::Id) -> Result<Option<(AssertionVersion, Self::Item)>, String>;
+	fn get_version(
+		&self,
+		id: &Self::Id,
+		version: AssertionVersion,
+	) -> Result<Option<Self::Item>, String>;
+	// stores the given version of the assertion and makes it the active one
+	fn save(&self, id: Self::Id, version: AssertionVersion, item: Self::Item)
+		-> Result<(), String>;
+	fn activate(&self, id: &Self::Id, version: AssertionVersion) -> Result<(), String>;
+	// returns the policy the assertion was created with, `None` for assertions created
+	// before policies were introduced
+	fn get_policy(&self, id: &Self::Id) -> Result<Option<AssertionPolicy>, String>;
+	fn save_policy(&self, id: Self::Id, policy: AssertionPolicy) -> Result<(), String>;
 }
 
+// an outgoing http request made by the assertion logic during its execution
+#[derive(Encode, Decode, Clone, Debug, PartialEq, Eq)]
+pub struct HttpRequestLog {
+	pub method: String,
+	pub url: String,
+	pub body: Option<String>,
+}
+
+#[derive(Encode, Decode, Clone, Debug, PartialEq, Eq)]
 pub struct AssertionResult {
 	pub description: String,
 	pub assertion_type: String,
@@ -42,6 +70,30 @@ pub struct AssertionResult {
 	pub schema_url: String,
 	pub meet: bool,
 	pub contract_logs: Vec<String>,
+	pub version: AssertionVersion,
+	pub gas_used: u64,
+	pub http_requests: Vec<HttpRequestLog>,
+}
+
+#[derive(Encode, Decode, Clone, Debug, PartialEq, Eq)]
+pub enum AssertionExecutionError {
+	// the assertion exceeded one of the resource limits of its policy
+	LimitExceeded(String),
+	// the assertion called a domain outside of the allowlist of its policy
+	DomainNotAllowed(String),
+	Other(String),
+}
+
+impl From<String> for AssertionExecutionError {
+	fn from(e: String) -> Self {
+		AssertionExecutionError::Other(e)
+	}
+}
+
+impl From<&str> for AssertionExecutionError {
+	fn from(e: &str) -> Self {
+		AssertionExecutionError::Other(e.to_string())
+	}
 }
 
 pub trait AssertionExecutor<I, P> {
@@ -50,5 +102,5 @@ pub trait AssertionExecutor<I, P> {
 		assertion_id: I,
 		assertion_params: P,
 		identities: &[IdentityNetworkTuple],
-	) -> Result<AssertionResult, String>;
+	) -> Result<AssertionResult, AssertionExecutionError>;
 }
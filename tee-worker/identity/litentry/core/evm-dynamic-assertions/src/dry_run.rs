@@ -0,0 +1,180 @@
+// Copyright 2020-2024 Trust Computing GmbH.
+// This file is part of Litentry.
+//
+// Litentry is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// Litentry is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with Litentry.  If not, see <https://www.gnu.org/licenses/>.
+
+#[cfg(all(not(feature = "std"), feature = "sgx"))]
+extern crate sgx_tstd as std;
+
+use crate::{AssertionParams, SmartContractByteCode};
+use codec::{Decode, Encode};
+use lc_dynamic_assertion::{AssertionPolicy, Identity, IdentityNetworkTuple};
+use litentry_primitives::LitentryMultiSignature;
+use std::{
+	collections::BTreeMap,
+	format,
+	string::{String, ToString},
+	vec::Vec,
+};
+
+/// Latest expiry a dry run request may have, in milliseconds from now. It bounds for how long
+/// the nonces of accepted requests need to be remembered.
+pub const MAX_DRY_RUN_REQUEST_LIFETIME: u64 = 5 * 60 * 1000;
+
+/// Assertion logic to be executed without issuing a VC or touching any state.
+#[derive(Encode, Decode, Clone, Debug, PartialEq, Eq)]
+pub struct DryRunAssertionRequest {
+	pub who: Identity,
+	pub byte_code: SmartContractByteCode,
+	pub secrets: Vec<String>,
+	pub params: AssertionParams,
+	pub identities: Vec<IdentityNetworkTuple>,
+	// policy to run the assertion with, `None` applies the default limits only
+	pub policy: Option<AssertionPolicy>,
+	// random value picked by the requester, a signed request is only run once
+	pub nonce: [u8; 32],
+	// unix timestamp in milliseconds after which the request is refused
+	pub expires_at: u64,
+}
+
+#[derive(Encode, Decode, Clone, Debug, PartialEq, Eq)]
+pub struct DryRunAssertionRequestSigned {
+	pub request: DryRunAssertionRequest,
+	pub signature: LitentryMultiSignature,
+}
+
+impl DryRunAssertionRequestSigned {
+	// the signature is expected to be made by `who` over the encoded request
+	pub fn verify_signature(&self) -> bool {
+		self.signature.verify(self.request.encode().as_slice(), &self.request.who)
+	}
+}
+
+/// Nonces of the dry run requests accepted so far, kept until the requests expire.
+#[derive(Default)]
+pub struct DryRunReplayGuard {
+	nonces: BTreeMap<[u8; 32], u64>,
+}
+
+impl DryRunReplayGuard {
+	// to be called once the signature of the request has been verified
+	pub fn check(&mut self, request: &DryRunAssertionRequest, now: u64) -> Result<(), String> {
+		if request.expires_at < now {
+			return Err("Dry run request expired".to_string())
+		}
+		if request.expires_at > now.saturating_add(MAX_DRY_RUN_REQUEST_LIFETIME) {
+			return Err(format!(
+				"Dry run request must expire within {} ms",
+				MAX_DRY_RUN_REQUEST_LIFETIME
+			))
+		}
+		self.nonces.retain(|_, expires_at| *expires_at >= now);
+		if self.nonces.contains_key(&request.nonce) {
+			return Err("Dry run request was already submitted".to_string())
+		}
+		self.nonces.insert(request.nonce, request.expires_at);
+		Ok(())
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use litentry_primitives::Web3Network;
+	use sp_core::{ed25519, Pair};
+
+	fn request(who: Identity) -> DryRunAssertionRequest {
+		DryRunAssertionRequest {
+			who: who.clone(),
+			byte_code: vec![0u8; 4],
+			secrets: vec!["secret".into()],
+			params: vec![],
+			identities: vec![(who, vec![Web3Network::Litentry])],
+			policy: None,
+			nonce: [1u8; 32],
+			expires_at: 1_000,
+		}
+	}
+
+	#[test]
+	pub fn verifies_signature_of_requester() {
+		// given
+		let pair = ed25519::Pair::from_seed(&[1u8; 32]);
+		let request = request(Identity::Substrate(pair.public().into()));
+		let signature = pair.sign(request.encode().as_slice()).into();
+
+		// when
+		let signed = DryRunAssertionRequestSigned { request, signature };
+
+		// then
+		assert!(signed.verify_signature());
+	}
+
+	#[test]
+	pub fn rejects_signature_of_other_account() {
+		// given
+		let pair = ed25519::Pair::from_seed(&[1u8; 32]);
+		let other = ed25519::Pair::from_seed(&[2u8; 32]);
+		let request = request(Identity::Substrate(pair.public().into()));
+		let signature = other.sign(request.encode().as_slice()).into();
+
+		// when
+		let signed = DryRunAssertionRequestSigned { request, signature };
+
+		// then
+		assert!(!signed.verify_signature());
+	}
+
+	#[test]
+	pub fn rejects_replayed_request() {
+		// given
+		let mut guard = DryRunReplayGuard::default();
+		let request = request(Identity::Substrate([1u8; 32].into()));
+
+		// when
+		let first = guard.check(&request, 500);
+		let replayed = guard.check(&request, 600);
+
+		// then
+		assert!(first.is_ok());
+		assert!(replayed.is_err());
+	}
+
+	#[test]
+	pub fn rejects_expired_request() {
+		// given
+		let mut guard = DryRunReplayGuard::default();
+		let request = request(Identity::Substrate([1u8; 32].into()));
+
+		// when
+		let result = guard.check(&request, 1_001);
+
+		// then
+		assert_eq!(result, Err("Dry run request expired".to_string()));
+	}
+
+	#[test]
+	pub fn rejects_request_expiring_too_late() {
+		// given
+		let mut guard = DryRunReplayGuard::default();
+		let mut request = request(Identity::Substrate([1u8; 32].into()));
+		request.expires_at = MAX_DRY_RUN_REQUEST_LIFETIME + 1;
+
+		// when
+		let result = guard.check(&request, 0);
+
+		// then
+		assert!(result.is_err());
+	}
+}
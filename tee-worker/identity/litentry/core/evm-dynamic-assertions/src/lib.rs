@@ -42,13 +42,13 @@ use ethabi::{
 use evm::{
 	backend::{MemoryBackend, MemoryVicinity},
 	executor::stack::{MemoryStackState, StackExecutor, StackSubstateMetadata},
-	Config, ExitReason,
+	Config, ExitError, ExitReason,
 };
 use itp_enclave_metrics::EnclaveMetric;
 use itp_ocall_api::EnclaveMetricsOCallApi;
 use lc_dynamic_assertion::{
-	AssertionExecutor, AssertionLogicRepository, AssertionResult, Identity, IdentityNetworkTuple,
-	Web3Network,
+	AssertionExecutionError, AssertionExecutor, AssertionLogicRepository, AssertionPolicy,
+	AssertionResult, AssertionVersion, HttpRequestLog, Identity, IdentityNetworkTuple, Web3Network,
 };
 use std::{
 	collections::BTreeMap,
@@ -59,6 +59,7 @@ use std::{
 	vec::Vec,
 };
 
+pub mod dry_run;
 mod precompiles;
 pub mod repository;
 pub mod sealing;
@@ -78,33 +79,94 @@ pub struct EvmAssertionExecutor<A: AssertionLogicRepository, MetricsApi: Enclave
 	pub metrics_api: Arc<MetricsApi>,
 }
 
+pub struct SmartContractExecution {
+	pub exit_reason: ExitReason,
+	pub return_data: Vec<u8>,
+	pub contract_logs: Vec<String>,
+	pub gas_used: u64,
+	pub http_requests: Vec<HttpRequestLog>,
+	// set when the execution was aborted because of the assertion policy
+	pub violation: Option<AssertionExecutionError>,
+}
+
 pub fn execute_smart_contract(
 	byte_code: Vec<u8>,
 	input_data: Vec<u8>,
 ) -> (ExitReason, Vec<u8>, Vec<String>) {
+	let execution = execute_smart_contract_traced(byte_code, input_data, None);
+	(execution.exit_reason, execution.return_data, execution.contract_logs)
+}
+
+// same as `execute_smart_contract`, but also reports the gas used by the deployment and the call
+// and every http request issued by the precompiles, both are bounded by the given policy
+pub fn execute_smart_contract_traced(
+	byte_code: Vec<u8>,
+	input_data: Vec<u8>,
+	policy: Option<&AssertionPolicy>,
+) -> SmartContractExecution {
 	// prepare EVM runtime
 	let config = prepare_config();
 	let vicinity = prepare_memory();
 	let state = BTreeMap::new();
 	let mut backend = MemoryBackend::new(&vicinity, state);
-	let metadata = StackSubstateMetadata::new(u64::MAX, &config);
+	let precompiles = Precompiles::new(policy);
+	let max_gas = precompiles.limits.max_gas;
+	let metadata = StackSubstateMetadata::new(max_gas, &config);
 	let state = MemoryStackState::new(metadata, &mut backend);
-	let precompiles = Precompiles { contract_logs: Vec::new().into() };
 	let mut executor = StackExecutor::new_with_precompiles(state, &config, &precompiles);
 
 	// caller, just an unused account
 	let caller = hash(5); //0x05
 
-	// deploy assertion smart contract
+	// deploy assertion smart contract, the constructor runs on the same gas budget as the call
 	let address = executor.create_address(evm::CreateScheme::Legacy { caller });
-	let _create_result =
-		executor.transact_create(caller, U256::zero(), byte_code, u64::MAX, Vec::new());
-
-	// call assertion smart contract
-	let (reason, data) =
-		executor.transact_call(caller, address, U256::zero(), input_data, u64::MAX, Vec::new());
+	let (create_reason, _) =
+		executor.transact_create(caller, U256::zero(), byte_code, max_gas, Vec::new());
+	let deployment_gas = executor.used_gas();
+
+	// call assertion smart contract with what is left of the budget
+	let (reason, data) = if create_reason.is_succeed() {
+		executor.transact_call(
+			caller,
+			address,
+			U256::zero(),
+			input_data,
+			max_gas.saturating_sub(deployment_gas),
+			Vec::new(),
+		)
+	} else {
+		(create_reason, Vec::new())
+	};
+	let gas_used = executor.used_gas();
+
+	let violation = precompiles.violation.take().or_else(|| match reason {
+		ExitReason::Error(ExitError::OutOfGas) => Some(AssertionExecutionError::LimitExceeded(
+			std::format!("gas limit of {} exceeded", max_gas),
+		)),
+		_ if precompiles.remaining_time().is_none() => Some(precompiles.execution_time_exceeded()),
+		_ => None,
+	});
+
+	SmartContractExecution {
+		exit_reason: reason,
+		return_data: data,
+		contract_logs: precompiles.contract_logs.take(),
+		gas_used,
+		http_requests: precompiles.http_requests.take(),
+		violation,
+	}
+}
 
-	(reason, data, precompiles.contract_logs.take())
+// executes the given assertion logic without looking it up in the repository,
+// used to let assertion developers try out their contracts before deploying them
+pub fn dry_run_assertion(
+	smart_contract_byte_code: SmartContractByteCode,
+	secrets: Vec<String>,
+	assertion_params: AssertionParams,
+	identities: &[IdentityNetworkTuple],
+	policy: Option<&AssertionPolicy>,
+) -> Result<AssertionResult, AssertionExecutionError> {
+	execute_assertion(smart_contract_byte_code, secrets, assertion_params, identities, 0, policy)
 }
 
 impl<A, MetricsApi> AssertionExecutor<AssertionId, AssertionParams>
@@ -118,9 +180,9 @@ where
 		assertion_id: A::Id,
 		assertion_params: AssertionParams,
 		identities: &[IdentityNetworkTuple],
-	) -> Result<AssertionResult, String> {
+	) -> Result<AssertionResult, AssertionExecutionError> {
 		let start_time = Instant::now();
-		let (smart_contract_byte_code, secrets) = self
+		let (version, (smart_contract_byte_code, secrets)) = self
 			.assertion_repository
 			.get(&assertion_id)
 			.map_err(|_| "Could not access assertion repository")?
@@ -132,27 +194,57 @@ where
 			log::warn!("Failed to update DynamicAssertionGetTime metric with error: {:?}", e);
 		}
 
-		let input = prepare_execute_call_input(identities, secrets, assertion_params)
-			.map_err(|_| "Could not prepare evm execution input")?;
-
-		let call_result = execute_smart_contract(smart_contract_byte_code, input);
-
-		if call_result.0.is_succeed() {
-			let (description, assertion_type, assertions, schema_url, meet) =
-				decode_result(&call_result.1)
-					.map_err(|_| "Could not decode evm assertion execution result")?;
-
-			Ok(AssertionResult {
-				description,
-				assertion_type,
-				assertions,
-				schema_url,
-				meet,
-				contract_logs: call_result.2,
-			})
-		} else {
-			Err(std::format!("Fail to execution evm dynamic assertion: {:?}", call_result.0))
-		}
+		let policy = self
+			.assertion_repository
+			.get_policy(&assertion_id)
+			.map_err(|_| "Could not access assertion repository")?;
+
+		execute_assertion(
+			smart_contract_byte_code,
+			secrets,
+			assertion_params,
+			identities,
+			version,
+			policy.as_ref(),
+		)
+	}
+}
+
+fn execute_assertion(
+	smart_contract_byte_code: SmartContractByteCode,
+	secrets: Vec<String>,
+	assertion_params: AssertionParams,
+	identities: &[IdentityNetworkTuple],
+	version: AssertionVersion,
+	policy: Option<&AssertionPolicy>,
+) -> Result<AssertionResult, AssertionExecutionError> {
+	let input = prepare_execute_call_input(identities, secrets, assertion_params)
+		.map_err(|_| "Could not prepare evm execution input")?;
+
+	let execution = execute_smart_contract_traced(smart_contract_byte_code, input, policy);
+	if let Some(violation) = execution.violation {
+		return Err(violation)
+	}
+
+	if execution.exit_reason.is_succeed() {
+		let (description, assertion_type, assertions, schema_url, meet) =
+			decode_result(&execution.return_data)
+				.map_err(|_| "Could not decode evm assertion execution result")?;
+
+		Ok(AssertionResult {
+			description,
+			assertion_type,
+			assertions,
+			schema_url,
+			meet,
+			contract_logs: execution.contract_logs,
+			version,
+			gas_used: execution.gas_used,
+			http_requests: execution.http_requests,
+		})
+	} else {
+		Err(std::format!("Fail to execution evm dynamic assertion: {:?}", execution.exit_reason)
+			.into())
 	}
 }
 
@@ -273,7 +365,29 @@ pub fn failure_precompile_output(token: ethabi::Token) -> evm::executor::stack::
 #[cfg(test)]
 mod tests {
 	use super::*;
-	use litentry_primitives::{Address32, Identity};
+	use litentry_primitives::{Address32, AssertionLimits, Identity};
+
+	#[test]
+	pub fn should_bound_constructor_by_gas_limit() {
+		// given
+		// constructor looping forever without calling any precompile: JUMPDEST PUSH1 0 JUMP
+		let byte_code = vec![0x5b, 0x60, 0x00, 0x56];
+		let policy = AssertionPolicy {
+			limits: AssertionLimits { max_gas: 1_000_000, ..Default::default() },
+			..Default::default()
+		};
+
+		// when
+		let execution = execute_smart_contract_traced(byte_code, Vec::new(), Some(&policy));
+
+		// then
+		assert_eq!(execution.exit_reason, ExitReason::Error(ExitError::OutOfGas));
+		assert_eq!(execution.gas_used, 1_000_000);
+		assert_eq!(
+			execution.violation,
+			Some(AssertionExecutionError::LimitExceeded("gas limit of 1000000 exceeded".into()))
+		);
+	}
 
 	#[test]
 	pub fn should_tokenize_identity_with_networks() {
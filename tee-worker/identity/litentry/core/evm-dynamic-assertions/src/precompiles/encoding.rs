@@ -0,0 +1,430 @@
+// Copyright 2020-2024 Trust Computing GmbH.
+// This file is part of Litentry.
+//
+// Litentry is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// Litentry is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with Litentry.  If not, see <https://www.gnu.org/licenses/>.
+
+use crate::{failure_precompile_output, precompiles::PrecompileResult, success_precompile_output};
+use base58::{FromBase58, ToBase58};
+use bech32::{Bech32, Hrp};
+use blake2_rfc::blake2b::Blake2b;
+use ethabi::ethereum_types::U256;
+use std::{format, string::String, vec, vec::Vec};
+
+const SS58_PREFIX: &[u8] = b"SS58PRE";
+const SS58_CHECKSUM_LEN: usize = 2;
+
+pub fn base58_encode(input: Vec<u8>) -> PrecompileResult {
+	let bytes = match decode_bytes(&input) {
+		Some(v) => v,
+		None => return Ok(failure_precompile_output(ethabi::Token::String(Default::default()))),
+	};
+	Ok(success_precompile_output(ethabi::Token::String(bytes.to_base58())))
+}
+
+pub fn base58_decode(input: Vec<u8>) -> PrecompileResult {
+	let value = match decode_string(&input) {
+		Some(v) => v,
+		None => return Ok(failure_precompile_output(ethabi::Token::Bytes(Default::default()))),
+	};
+	match value.from_base58() {
+		Ok(bytes) => Ok(success_precompile_output(ethabi::Token::Bytes(bytes))),
+		Err(e) => {
+			log::debug!("Cannot decode base58 string {:?}, reason: {:?}", value, e);
+			Ok(failure_precompile_output(ethabi::Token::Bytes(Default::default())))
+		},
+	}
+}
+
+pub fn bech32_encode(input: Vec<u8>) -> PrecompileResult {
+	let decoded =
+		match ethabi::decode(&[ethabi::ParamType::String, ethabi::ParamType::Bytes], &input) {
+			Ok(d) => d,
+			Err(e) => {
+				log::debug!("Could not decode input {:?}, reason: {:?}", input, e);
+				return Ok(failure_precompile_output(ethabi::Token::String(Default::default())))
+			},
+		};
+	let hrp = decoded.get(0).and_then(|v| v.clone().into_string());
+	let data = decoded.get(1).and_then(|v| v.clone().into_bytes());
+
+	let value = match (hrp, data) {
+		(Some(hrp), Some(data)) => match Hrp::parse(&hrp)
+			.map_err(|e| format!("{:?}", e))
+			.and_then(|hrp| bech32::encode::<Bech32>(hrp, &data).map_err(|e| format!("{:?}", e)))
+		{
+			Ok(v) => v,
+			Err(e) => {
+				log::debug!("Cannot encode bech32 string with hrp {:?}, reason: {:?}", hrp, e);
+				return Ok(failure_precompile_output(ethabi::Token::String(Default::default())))
+			},
+		},
+		_ => {
+			log::debug!("Could not decode input {:?}, reason: hrp or data is invalid", input);
+			return Ok(failure_precompile_output(ethabi::Token::String(Default::default())))
+		},
+	};
+
+	Ok(success_precompile_output(ethabi::Token::String(value)))
+}
+
+// bech32Decode(string hrp, string value), accepts both bech32 and bech32m checksums and
+// fails unless the human readable part matches `hrp`, returns the data part only
+pub fn bech32_decode(input: Vec<u8>) -> PrecompileResult {
+	let decoded =
+		match ethabi::decode(&[ethabi::ParamType::String, ethabi::ParamType::String], &input) {
+			Ok(d) => d,
+			Err(e) => {
+				log::debug!("Could not decode input {:?}, reason: {:?}", input, e);
+				return Ok(failure_precompile_output(ethabi::Token::Bytes(Default::default())))
+			},
+		};
+	let expected_hrp = decoded.get(0).and_then(|v| v.clone().into_string());
+	let value = decoded.get(1).and_then(|v| v.clone().into_string());
+
+	let (expected_hrp, value) = match (expected_hrp, value) {
+		(Some(expected_hrp), Some(value)) => (expected_hrp, value),
+		_ => {
+			log::debug!("Could not decode input {:?}, reason: hrp or value is invalid", input);
+			return Ok(failure_precompile_output(ethabi::Token::Bytes(Default::default())))
+		},
+	};
+	match bech32::decode(&value) {
+		Ok((hrp, data)) if hrp.to_lowercase() == expected_hrp.to_lowercase() =>
+			Ok(success_precompile_output(ethabi::Token::Bytes(data))),
+		Ok((hrp, _)) => {
+			log::debug!("Unexpected bech32 hrp {:?}, expected {:?}", hrp, expected_hrp);
+			Ok(failure_precompile_output(ethabi::Token::Bytes(Default::default())))
+		},
+		Err(e) => {
+			log::debug!("Cannot decode bech32 string {:?}, reason: {:?}", value, e);
+			Ok(failure_precompile_output(ethabi::Token::Bytes(Default::default())))
+		},
+	}
+}
+
+pub fn ss58_encode(input: Vec<u8>) -> PrecompileResult {
+	let decoded =
+		match ethabi::decode(&[ethabi::ParamType::Bytes, ethabi::ParamType::Uint(16)], &input) {
+			Ok(d) => d,
+			Err(e) => {
+				log::debug!("Could not decode input {:?}, reason: {:?}", input, e);
+				return Ok(failure_precompile_output(ethabi::Token::String(Default::default())))
+			},
+		};
+	let public_key = decoded.get(0).and_then(|v| v.clone().into_bytes());
+	let prefix = decoded.get(1).and_then(|v| v.clone().into_uint()).and_then(to_u16);
+
+	let value = match (public_key, prefix) {
+		(Some(public_key), Some(prefix)) => match ss58_encode_with_prefix(&public_key, prefix) {
+			Ok(v) => v,
+			Err(e) => {
+				log::debug!("{:?}", e);
+				return Ok(failure_precompile_output(ethabi::Token::String(Default::default())))
+			},
+		},
+		_ => {
+			log::debug!(
+				"Could not decode input {:?}, reason: public key or prefix is invalid",
+				input
+			);
+			return Ok(failure_precompile_output(ethabi::Token::String(Default::default())))
+		},
+	};
+
+	Ok(success_precompile_output(ethabi::Token::String(value)))
+}
+
+// ethabi doesn't range check `uint16`, truncating would accept e.g. `2^32 + 42` as 42
+fn to_u16(value: U256) -> Option<u16> {
+	if value > U256::from(u16::MAX) {
+		return None
+	}
+	Some(value.low_u32() as u16)
+}
+
+// returns the public key, the network prefix is checked but dropped
+pub fn ss58_decode(input: Vec<u8>) -> PrecompileResult {
+	let value = match decode_string(&input) {
+		Some(v) => v,
+		None => return Ok(failure_precompile_output(ethabi::Token::Bytes(Default::default()))),
+	};
+	match ss58_decode_public_key(&value) {
+		Ok(public_key) => Ok(success_precompile_output(ethabi::Token::Bytes(public_key))),
+		Err(e) => {
+			log::debug!("{:?}", e);
+			Ok(failure_precompile_output(ethabi::Token::Bytes(Default::default())))
+		},
+	}
+}
+
+// mostly copied from https://github.com/hack-ink/substrate-minimal/blob/main/subcryptor/src/lib.rs
+// no_std version is used here
+pub fn ss58_encode_with_prefix(
+	public_key: &[u8],
+	prefix: u16,
+) -> core::result::Result<String, String> {
+	let mut bytes = match prefix {
+		0..=63 => vec![prefix as u8],
+		64..=16_383 => {
+			let first = ((prefix & 0b0000_0000_1111_1100) as u8) >> 2;
+			let second = ((prefix >> 8) as u8) | ((prefix & 0b0000_0000_0000_0011) as u8) << 6;
+
+			vec![first | 0b01000000, second]
+		},
+		_ => Err(format!("Fail to encode ss58 address, prefix: {:?}", prefix))?,
+	};
+
+	bytes.extend(public_key);
+
+	let checksum = ss58_checksum(&bytes);
+	bytes.extend(&checksum[0..SS58_CHECKSUM_LEN]);
+
+	Ok(bytes.to_base58())
+}
+
+fn ss58_decode_public_key(address: &str) -> core::result::Result<Vec<u8>, String> {
+	let data = address
+		.from_base58()
+		.map_err(|e| format!("Fail to decode ss58 address {:?}, reason: {:?}", address, e))?;
+	let prefix_len = match data.first() {
+		Some(0..=63) => 1,
+		Some(64..=127) => 2,
+		_ => Err(format!("Fail to decode ss58 address {:?}, reason: invalid prefix", address))?,
+	};
+	if data.len() < prefix_len + SS58_CHECKSUM_LEN {
+		Err(format!("Fail to decode ss58 address {:?}, reason: invalid length", address))?
+	}
+
+	let body_len = data.len() - SS58_CHECKSUM_LEN;
+	let checksum = ss58_checksum(&data[0..body_len]);
+	if data[body_len..] != checksum[0..SS58_CHECKSUM_LEN] {
+		Err(format!("Fail to decode ss58 address {:?}, reason: invalid checksum", address))?
+	}
+
+	Ok(data[prefix_len..body_len].to_vec())
+}
+
+fn ss58_checksum(data: &[u8]) -> Vec<u8> {
+	let mut context = Blake2b::new(64);
+	context.update(SS58_PREFIX);
+	context.update(data);
+	context.finalize().as_bytes().to_vec()
+}
+
+fn decode_bytes(input: &[u8]) -> Option<Vec<u8>> {
+	match ethabi::decode(&[ethabi::ParamType::Bytes], input) {
+		Ok(d) => d.get(0).and_then(|v| v.clone().into_bytes()),
+		Err(e) => {
+			log::debug!("Could not decode bytes {:?}, reason: {:?}", input, e);
+			None
+		},
+	}
+}
+
+fn decode_string(input: &[u8]) -> Option<String> {
+	match ethabi::decode(&[ethabi::ParamType::String], input) {
+		Ok(d) => d.get(0).and_then(|v| v.clone().into_string()),
+		Err(e) => {
+			log::debug!("Could not decode string {:?}, reason: {:?}", input, e);
+			None
+		},
+	}
+}
+
+#[cfg(test)]
+pub mod test {
+	use crate::{
+		failure_precompile_output,
+		precompiles::encoding::{
+			base58_decode, base58_encode, bech32_decode, bech32_encode, ss58_decode, ss58_encode,
+		},
+		success_precompile_output,
+	};
+	use ethabi::{encode, ethereum_types::U256, Token};
+	use litentry_hex_utils::decode_hex;
+
+	const SUBSTRATE_PUBKEY: &str =
+		"0xd4e35b16ec6b417386b948e7eaf5cc642a243096cecf366e6313689b90969f42";
+
+	#[test]
+	fn test_base58_encode() {
+		// given
+		let encoded = encode(&[Token::Bytes("hello world".as_bytes().to_vec())]);
+
+		// when
+		let result = base58_encode(encoded).unwrap();
+
+		// then
+		assert_eq!(success_precompile_output(Token::String("StV1DL6CwTryKyV".into())), result)
+	}
+
+	#[test]
+	fn test_base58_decode() {
+		// given
+		let encoded = encode(&[Token::String("StV1DL6CwTryKyV".into())]);
+
+		// when
+		let result = base58_decode(encoded).unwrap();
+
+		// then
+		assert_eq!(
+			success_precompile_output(Token::Bytes("hello world".as_bytes().to_vec())),
+			result
+		)
+	}
+
+	#[test]
+	fn test_base58_decode_fail() {
+		// given
+		let encoded = encode(&[Token::String("0OIl".into())]);
+
+		// when
+		let result = base58_decode(encoded).unwrap();
+
+		// then
+		assert_eq!(failure_precompile_output(Token::Bytes(Default::default())), result)
+	}
+
+	#[test]
+	fn test_bech32_encode() {
+		// given
+		let encoded = encode(&[Token::String("abc".into()), Token::Bytes(vec![1, 2, 3, 4])]);
+
+		// when
+		let result = bech32_encode(encoded).unwrap();
+
+		// then
+		assert_eq!(success_precompile_output(Token::String("abc1qypqxpqec3ejy".into())), result)
+	}
+
+	#[test]
+	fn test_bech32_decode() {
+		// given
+		let encoded =
+			encode(&[Token::String("abc".into()), Token::String("abc1qypqxpqec3ejy".into())]);
+
+		// when
+		let result = bech32_decode(encoded).unwrap();
+
+		// then
+		assert_eq!(success_precompile_output(Token::Bytes(vec![1, 2, 3, 4])), result)
+	}
+
+	#[test]
+	fn test_bech32_decode_fail_with_unexpected_hrp() {
+		// given
+		let encoded =
+			encode(&[Token::String("bc".into()), Token::String("abc1qypqxpqec3ejy".into())]);
+
+		// when
+		let result = bech32_decode(encoded).unwrap();
+
+		// then
+		assert_eq!(failure_precompile_output(Token::Bytes(Default::default())), result)
+	}
+
+	#[test]
+	fn test_bech32_decode_fail() {
+		// given
+		let encoded =
+			encode(&[Token::String("abc".into()), Token::String("abc1qypqxpqqqqqq".into())]);
+
+		// when
+		let result = bech32_decode(encoded).unwrap();
+
+		// then
+		assert_eq!(failure_precompile_output(Token::Bytes(Default::default())), result)
+	}
+
+	#[test]
+	fn test_ss58_encode() {
+		vec![
+			(0_u16, "15p8h3KAmkREatSn2e9TkD7ALJDo5UXZC56q7Bat2QQdxRgn"),
+			(2_u16, "HPTD2PyYLAgu1FhqhuWW1e1dGWPBqnbZxD6LYsUx7bcXD1j"),
+			(31_u16, "4BDaho6fgmXmRhzA79RCT9MLNLeunA3h3EGuBNXDNuqkgerR"),
+			(131_u16, "jcS3pqDZ5mnXNxkLTBM3kHa5ypL4pSi39CWGoCASC51onUzHW"),
+		]
+		.into_iter()
+		.for_each(|(prefix, expected_address)| {
+			// given
+			let encoded = encode(&[
+				Token::Bytes(decode_hex(SUBSTRATE_PUBKEY.as_bytes()).unwrap()),
+				Token::Uint(prefix.into()),
+			]);
+
+			// when
+			let result = ss58_encode(encoded).unwrap();
+
+			// then
+			assert_eq!(success_precompile_output(Token::String(expected_address.into())), result);
+		});
+	}
+
+	#[test]
+	fn test_ss58_encode_fail_with_out_of_range_prefix() {
+		vec![U256::from(u16::MAX) + 1, (U256::one() << 32) + 42]
+			.into_iter()
+			.for_each(|prefix| {
+				// given
+				let encoded = encode(&[
+					Token::Bytes(decode_hex(SUBSTRATE_PUBKEY.as_bytes()).unwrap()),
+					Token::Uint(prefix),
+				]);
+
+				// when
+				let result = ss58_encode(encoded).unwrap();
+
+				// then
+				assert_eq!(failure_precompile_output(Token::String(Default::default())), result);
+			});
+	}
+
+	#[test]
+	fn test_ss58_decode() {
+		vec![
+			"15p8h3KAmkREatSn2e9TkD7ALJDo5UXZC56q7Bat2QQdxRgn",
+			"jcS3pqDZ5mnXNxkLTBM3kHa5ypL4pSi39CWGoCASC51onUzHW",
+		]
+		.into_iter()
+		.for_each(|address| {
+			// given
+			let encoded = encode(&[Token::String(address.into())]);
+
+			// when
+			let result = ss58_decode(encoded).unwrap();
+
+			// then
+			assert_eq!(
+				success_precompile_output(Token::Bytes(
+					decode_hex(SUBSTRATE_PUBKEY.as_bytes()).unwrap()
+				)),
+				result
+			);
+		});
+	}
+
+	#[test]
+	fn test_ss58_decode_fail() {
+		// given
+		// last character altered, checksum doesn't match
+		let encoded =
+			encode(&[Token::String("15p8h3KAmkREatSn2e9TkD7ALJDo5UXZC56q7Bat2QQdxRgm".into())]);
+
+		// when
+		let result = ss58_decode(encoded).unwrap();
+
+		// then
+		assert_eq!(failure_precompile_output(Token::Bytes(Default::default())), result)
+	}
+}
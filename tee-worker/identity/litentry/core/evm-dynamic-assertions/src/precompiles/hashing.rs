@@ -0,0 +1,124 @@
+// Copyright 2020-2024 Trust Computing GmbH.
+// This file is part of Litentry.
+//
+// Litentry is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// Litentry is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with Litentry.  If not, see <https://www.gnu.org/licenses/>.
+
+use crate::{failure_precompile_output, precompiles::PrecompileResult, success_precompile_output};
+use sp_core::hashing::{blake2_128, blake2_256, sha2_256};
+use std::vec::Vec;
+
+pub fn sha256(input: Vec<u8>) -> PrecompileResult {
+	hash_bytes(input, |bytes| sha2_256(bytes).to_vec())
+}
+
+pub fn blake2b_256(input: Vec<u8>) -> PrecompileResult {
+	hash_bytes(input, |bytes| blake2_256(bytes).to_vec())
+}
+
+pub fn blake2b_128(input: Vec<u8>) -> PrecompileResult {
+	hash_bytes(input, |bytes| blake2_128(bytes).to_vec())
+}
+
+fn hash_bytes(input: Vec<u8>, hasher: impl Fn(&[u8]) -> Vec<u8>) -> PrecompileResult {
+	let decoded = match ethabi::decode(&[ethabi::ParamType::Bytes], &input) {
+		Ok(d) => d,
+		Err(e) => {
+			log::debug!("Could not decode bytes {:?}, reason: {:?}", input, e);
+			return Ok(failure_precompile_output(ethabi::Token::Bytes(Default::default())))
+		},
+	};
+	let bytes = match decoded.get(0).and_then(|v| v.clone().into_bytes()) {
+		Some(v) => v,
+		None => {
+			log::debug!("Could not convert decoded[0] to bytes");
+			return Ok(failure_precompile_output(ethabi::Token::Bytes(Default::default())))
+		},
+	};
+	Ok(success_precompile_output(ethabi::Token::Bytes(hasher(&bytes))))
+}
+
+#[cfg(test)]
+pub mod test {
+	use crate::{
+		failure_precompile_output,
+		precompiles::hashing::{blake2b_128, blake2b_256, sha256},
+		success_precompile_output,
+	};
+	use ethabi::{encode, Token};
+
+	#[test]
+	pub fn test_sha256() {
+		// given
+		let encoded = encode(&[Token::Bytes("abc".as_bytes().to_vec())]);
+
+		// when
+		let result = sha256(encoded).unwrap();
+
+		// then
+		assert_eq!(
+			success_precompile_output(Token::Bytes(
+				hex::decode("ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad")
+					.unwrap()
+			)),
+			result
+		)
+	}
+
+	#[test]
+	pub fn test_blake2b_256() {
+		// given
+		let encoded = encode(&[Token::Bytes("abc".as_bytes().to_vec())]);
+
+		// when
+		let result = blake2b_256(encoded).unwrap();
+
+		// then
+		assert_eq!(
+			success_precompile_output(Token::Bytes(
+				hex::decode("bddd813c634239723171ef3fee98579b94964e3bb1cb3e427262c8c068d52319")
+					.unwrap()
+			)),
+			result
+		)
+	}
+
+	#[test]
+	pub fn test_blake2b_128() {
+		// given
+		let encoded = encode(&[Token::Bytes("abc".as_bytes().to_vec())]);
+
+		// when
+		let result = blake2b_128(encoded).unwrap();
+
+		// then
+		assert_eq!(
+			success_precompile_output(Token::Bytes(
+				hex::decode("cf4ab791c62b8d2b2109c90275287816").unwrap()
+			)),
+			result
+		)
+	}
+
+	#[test]
+	pub fn test_sha256_fail() {
+		// given
+		let encoded = encode(&[]);
+
+		// when
+		let result = sha256(encoded).unwrap();
+
+		// then
+		assert_eq!(failure_precompile_output(Token::Bytes(Default::default())), result)
+	}
+}
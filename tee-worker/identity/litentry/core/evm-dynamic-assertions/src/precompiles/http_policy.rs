@@ -0,0 +1,283 @@
+// Copyright 2020-2024 Trust Computing GmbH.
+// This file is part of Litentry.
+//
+// Litentry is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// Litentry is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with Litentry.  If not, see <https://www.gnu.org/licenses/>.
+
+#[cfg(all(not(feature = "std"), feature = "sgx"))]
+extern crate sgx_tstd as std;
+
+use crate::precompiles::Precompiles;
+use itc_rest_client::{
+	error::Error,
+	http_client::{EncodedBody, Send, SendHttpRequest},
+	rest_client::{Method, Request, Response, Url},
+	Query, RestPath,
+};
+use lc_dynamic_assertion::{AssertionExecutionError, HttpRequestLog};
+use std::{format, io, string::String, vec::Vec};
+
+/// Sends the requests of the executed assertion and stops reading a response as soon as it
+/// exceeds the response size limit or the execution time limit is exhausted, so that neither
+/// is only noticed once the whole response has been buffered.
+pub struct PolicySend<'a> {
+	precompiles: &'a Precompiles,
+}
+
+impl<'a> PolicySend<'a> {
+	pub fn new(precompiles: &'a Precompiles) -> Self {
+		Self { precompiles }
+	}
+}
+
+impl<'a> Send for PolicySend<'a> {
+	fn execute_send_request(
+		&self,
+		request: &mut Request,
+		writer: &mut Vec<u8>,
+	) -> Result<Response, Error> {
+		let mut writer = PolicyWriter { precompiles: self.precompiles, body: writer };
+		request.send(&mut writer).map_err(Error::HttpReqError)
+	}
+}
+
+struct PolicyWriter<'a, 'b> {
+	precompiles: &'a Precompiles,
+	body: &'b mut Vec<u8>,
+}
+
+impl<'a, 'b> io::Write for PolicyWriter<'a, 'b> {
+	fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+		let max_response_size = self.precompiles.limits.max_response_size;
+		if self.body.len().saturating_add(buf.len()) > max_response_size as usize {
+			self.precompiles.violate(AssertionExecutionError::LimitExceeded(format!(
+				"http response size limit of {} bytes exceeded",
+				max_response_size
+			)));
+			return Err(io::Error::new(io::ErrorKind::Other, "response size limit exceeded"))
+		}
+		if self.precompiles.remaining_time().is_none() {
+			self.precompiles.violate(self.precompiles.execution_time_exceeded());
+			return Err(io::Error::new(io::ErrorKind::TimedOut, "execution time limit exceeded"))
+		}
+		self.body.extend_from_slice(buf);
+		Ok(buf.len())
+	}
+
+	fn flush(&mut self) -> io::Result<()> {
+		Ok(())
+	}
+}
+
+/// Http client wrapper which enforces the call limit and domain allowlist of the executed
+/// assertion and keeps track of every request sent through it, so that the requests can be
+/// reported back to the developer. Responses are bounded by `PolicySend`.
+pub struct PolicyHttpClient<'a, T: SendHttpRequest> {
+	client: T,
+	precompiles: &'a Precompiles,
+}
+
+impl<'a, T: SendHttpRequest> PolicyHttpClient<'a, T> {
+	pub fn new(client: T, precompiles: &'a Precompiles) -> Self {
+		Self { client, precompiles }
+	}
+
+	fn check_policy(&self, url: &Url) -> Result<(), Error> {
+		let limits = &self.precompiles.limits;
+		if self.precompiles.remaining_time().is_none() {
+			return Err(self.violate(self.precompiles.execution_time_exceeded()))
+		}
+		if self.precompiles.http_requests.borrow().len() >= limits.max_http_calls as usize {
+			return Err(self.violate(AssertionExecutionError::LimitExceeded(format!(
+				"http call limit of {} exceeded",
+				limits.max_http_calls
+			))))
+		}
+		if !self.precompiles.is_domain_allowed(url) {
+			return Err(self.violate(AssertionExecutionError::DomainNotAllowed(format!(
+				"{} is not in the domain allowlist",
+				url.host_str().unwrap_or_default()
+			))))
+		}
+		Ok(())
+	}
+
+	fn violate(&self, violation: AssertionExecutionError) -> Error {
+		self.precompiles.violate(violation);
+		Error::RequestError
+	}
+
+	fn record(&self, method: &Method, url: &Url, body: &Option<String>) {
+		self.precompiles.http_requests.borrow_mut().push(HttpRequestLog {
+			method: format!("{:?}", method),
+			url: url.as_str().into(),
+			body: body.clone(),
+		});
+	}
+}
+
+impl<'a, T: SendHttpRequest> SendHttpRequest for PolicyHttpClient<'a, T> {
+	fn send_request<U, R>(
+		&self,
+		base_url: Url,
+		method: Method,
+		params: U,
+		query: Option<&Query<'_>>,
+		maybe_body: Option<String>,
+	) -> Result<(Response, EncodedBody), Error>
+	where
+		R: RestPath<U>,
+	{
+		self.check_policy(&base_url)?;
+		self.record(&method, &base_url, &maybe_body);
+		self.client.send_request::<U, R>(base_url, method, params, query, maybe_body)
+	}
+
+	fn send_request_raw(
+		&self,
+		url: Url,
+		method: Method,
+		maybe_body: Option<String>,
+		headers: Vec<(String, String)>,
+	) -> Result<(Response, EncodedBody), Error> {
+		self.check_policy(&url)?;
+		self.record(&method, &url, &maybe_body);
+		self.client.send_request_raw(url, method, maybe_body, headers)
+	}
+}
+
+#[cfg(test)]
+pub mod test {
+	use crate::precompiles::{
+		http_get::http_get_bool,
+		http_policy::{PolicyHttpClient, PolicyWriter},
+		mocks::MockedHttpClient,
+		Precompiles,
+	};
+	use ethabi::{encode, Token};
+	use lc_dynamic_assertion::AssertionExecutionError;
+	use litentry_primitives::{AssertionLimits, AssertionPolicy};
+	use std::io::Write;
+
+	fn prepare_input_data(url: &str) -> Vec<u8> {
+		encode(&[Token::String(url.into()), Token::String("/bool".into()), Token::Array(vec![])])
+	}
+
+	fn policy(max_http_calls: u32, allowed_domains: Vec<&str>) -> AssertionPolicy {
+		AssertionPolicy {
+			limits: AssertionLimits { max_http_calls, ..Default::default() },
+			allowed_domains: allowed_domains
+				.into_iter()
+				.map(|d| d.as_bytes().to_vec().try_into().unwrap())
+				.collect::<Vec<_>>()
+				.try_into()
+				.unwrap(),
+		}
+	}
+
+	#[test]
+	pub fn test_records_http_requests() {
+		// given
+		let precompiles = Precompiles::default();
+		let client = PolicyHttpClient::new(MockedHttpClient::default(), &precompiles);
+
+		// when
+		http_get_bool(prepare_input_data("https://www.litentry.com/"), client).unwrap();
+
+		// then
+		let requests = precompiles.http_requests.take();
+		assert_eq!(requests.len(), 1);
+		assert_eq!(requests[0].method, "GET");
+		assert_eq!(requests[0].url, "https://www.litentry.com/");
+		assert_eq!(requests[0].body, None);
+		assert_eq!(precompiles.violation.take(), None);
+	}
+
+	#[test]
+	pub fn test_allows_subdomain_of_allowed_domain() {
+		// given
+		let precompiles = Precompiles::new(Some(&policy(1, vec!["litentry.com"])));
+		let client = PolicyHttpClient::new(MockedHttpClient::default(), &precompiles);
+
+		// when
+		http_get_bool(prepare_input_data("https://www.litentry.com/"), client).unwrap();
+
+		// then
+		assert_eq!(precompiles.http_requests.take().len(), 1);
+		assert_eq!(precompiles.violation.take(), None);
+	}
+
+	#[test]
+	pub fn test_blocks_domain_outside_of_allowlist() {
+		// given
+		let precompiles = Precompiles::new(Some(&policy(1, vec!["litentry.com"])));
+		let client = PolicyHttpClient::new(MockedHttpClient::default(), &precompiles);
+
+		// when
+		http_get_bool(prepare_input_data("https://notlitentry.com/"), client).unwrap();
+
+		// then
+		assert!(precompiles.http_requests.take().is_empty());
+		assert_eq!(
+			precompiles.violation.take(),
+			Some(AssertionExecutionError::DomainNotAllowed(
+				"notlitentry.com is not in the domain allowlist".into()
+			))
+		);
+	}
+
+	#[test]
+	pub fn test_blocks_http_calls_over_limit() {
+		// given
+		let precompiles = Precompiles::new(Some(&policy(1, vec!["litentry.com"])));
+
+		// when
+		let client = PolicyHttpClient::new(MockedHttpClient::default(), &precompiles);
+		http_get_bool(prepare_input_data("https://www.litentry.com/"), client).unwrap();
+		let client = PolicyHttpClient::new(MockedHttpClient::default(), &precompiles);
+		http_get_bool(prepare_input_data("https://www.litentry.com/"), client).unwrap();
+
+		// then
+		assert_eq!(precompiles.http_requests.take().len(), 1);
+		assert_eq!(
+			precompiles.violation.take(),
+			Some(AssertionExecutionError::LimitExceeded("http call limit of 1 exceeded".into()))
+		);
+	}
+
+	#[test]
+	pub fn test_stops_reading_response_over_size_limit() {
+		// given
+		let precompiles = Precompiles::new(Some(&AssertionPolicy {
+			limits: AssertionLimits { max_response_size: 4, ..Default::default() },
+			allowed_domains: Default::default(),
+		}));
+		let mut body = Vec::new();
+		let mut writer = PolicyWriter { precompiles: &precompiles, body: &mut body };
+
+		// when
+		let first = writer.write(&[1, 2, 3]);
+		let second = writer.write(&[4, 5]);
+
+		// then
+		assert!(first.is_ok());
+		assert!(second.is_err());
+		assert_eq!(body, vec![1, 2, 3]);
+		assert_eq!(
+			precompiles.violation.take(),
+			Some(AssertionExecutionError::LimitExceeded(
+				"http response size limit of 4 bytes exceeded".into()
+			))
+		);
+	}
+}
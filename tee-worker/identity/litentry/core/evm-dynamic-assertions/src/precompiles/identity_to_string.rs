@@ -14,14 +14,17 @@
 // You should have received a copy of the GNU General Public License
 // along with Litentry.  If not, see <https://www.gnu.org/licenses/>.
 
-use crate::{failure_precompile_output, precompiles::PrecompileResult, success_precompile_output};
+use crate::{
+	failure_precompile_output,
+	precompiles::{encoding::ss58_encode_with_prefix, PrecompileResult},
+	success_precompile_output,
+};
 use base58::ToBase58;
-use blake2_rfc::blake2b::Blake2b;
 use litentry_primitives::{
 	p2pkh_address, p2sh_address, p2tr_address, p2wpkh_address, p2wsh_address, Web3Network,
 };
 use ss58_registry::Ss58AddressFormat;
-use std::{format, string::String, vec, vec::Vec};
+use std::{format, string::String, vec::Vec};
 
 pub fn identity_to_string(input: Vec<u8>) -> PrecompileResult {
 	let decoded =
@@ -87,36 +90,11 @@ pub fn identity_to_string(input: Vec<u8>) -> PrecompileResult {
 	Ok(success_precompile_output(ethabi::Token::String(value)))
 }
 
-// mostly copied from https://github.com/hack-ink/substrate-minimal/blob/main/subcryptor/src/lib.rs
-// no_std version is used here
 fn ss58_address_of(public_key: &[u8], network: &str) -> core::result::Result<String, String> {
 	let network = Ss58AddressFormat::try_from(network).map_err(|e| {
 		format!("Fail to parse ss58 address, network: {:?}, reason: {:?}", network, e)
 	})?;
-	let prefix = u16::from(network);
-	let mut bytes = match prefix {
-		0..=63 => vec![prefix as u8],
-		64..=16_383 => {
-			let first = ((prefix & 0b0000_0000_1111_1100) as u8) >> 2;
-			let second = ((prefix >> 8) as u8) | ((prefix & 0b0000_0000_0000_0011) as u8) << 6;
-
-			vec![first | 0b01000000, second]
-		},
-		_ => Err(format!("Fail to parse ss58 address, network: {:?}", network))?,
-	};
-
-	bytes.extend(public_key);
-
-	let blake2b = {
-		let mut context = Blake2b::new(64);
-		context.update(b"SS58PRE");
-		context.update(&bytes);
-		context.finalize()
-	};
-
-	bytes.extend(&blake2b.as_bytes()[0..2]);
-
-	Ok(bytes.to_base58())
+	ss58_encode_with_prefix(public_key, u16::from(network))
 }
 
 fn web3_network_to_chain(network: u8) -> Result<String, String> {
@@ -83,7 +83,7 @@ pub mod test {
 		let data = prepare_input_data(&json.to_string(), "/key");
 
 		// when:
-		let precompiles = Precompiles { contract_logs: Vec::new().into() };
+		let precompiles = Precompiles::default();
 		let result = json_get_string(data, &precompiles).unwrap();
 
 		// then
@@ -101,7 +101,7 @@ pub mod test {
 		let data = prepare_input_data(&json.to_string(), "/key");
 
 		// when:
-		let precompiles = Precompiles { contract_logs: Vec::new().into() };
+		let precompiles = Precompiles::default();
 		let result = json_get_i64(data, &precompiles).unwrap();
 
 		// then
@@ -119,7 +119,7 @@ pub mod test {
 		let data = prepare_input_data(&json.to_string(), "/key");
 
 		// when:
-		let precompiles = Precompiles { contract_logs: Vec::new().into() };
+		let precompiles = Precompiles::default();
 		let result = json_get_bool(data, &precompiles).unwrap();
 
 		// then
@@ -133,7 +133,7 @@ pub mod test {
 		let data = prepare_input_data(&json.to_string(), "");
 
 		// when:
-		let precompiles = Precompiles { contract_logs: Vec::new().into() };
+		let precompiles = Precompiles::default();
 		let result = get_array_len(data, &precompiles).unwrap();
 
 		// then
@@ -153,7 +153,7 @@ pub mod test {
 		let data = prepare_input_data(&json.to_string(), "/nested");
 
 		// when:
-		let precompiles = Precompiles { contract_logs: Vec::new().into() };
+		let precompiles = Precompiles::default();
 		let result = get_array_len(data, &precompiles).unwrap();
 
 		// then
@@ -126,7 +126,7 @@ pub mod test {
 		]);
 
 		// when
-		let precompiles = Precompiles { contract_logs: Vec::new().into() };
+		let precompiles = Precompiles::default();
 		let result = logging(encoded, &precompiles).unwrap();
 
 		// then
@@ -140,7 +140,7 @@ pub mod test {
 		let encoded = encode(&[Token::String("This is an info message".into())]);
 
 		// when
-		let precompiles = Precompiles { contract_logs: Vec::new().into() };
+		let precompiles = Precompiles::default();
 		let result = logging(encoded, &precompiles).unwrap();
 
 		// then
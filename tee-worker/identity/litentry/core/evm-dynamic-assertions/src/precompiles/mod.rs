@@ -21,24 +21,46 @@ extern crate sgx_tstd as std;
 use crate::sgx_reexport_prelude::*;
 
 use crate::precompiles::{
+	encoding::{
+		base58_decode, base58_encode, bech32_decode, bech32_encode, ss58_decode, ss58_encode,
+	},
+	hashing::{blake2b_128, blake2b_256, sha256},
 	hex_to_number::hex_to_number,
 	http_get::{http_get, http_get_bool, http_get_i64, http_get_string},
+	http_policy::{PolicyHttpClient, PolicySend},
 	http_post::{http_post, http_post_bool, http_post_i64, http_post_string},
 	identity_to_string::identity_to_string,
 	logging::logging,
 	parse_decimal::parse_decimal,
 	parse_int::parse_int,
+	string_utils::{string_concat, string_split, string_to_lowercase, string_to_uppercase},
 	to_hex::to_hex,
+	verify_signature::verify_signature,
 };
 use ethabi::ethereum_types::H160;
-use evm::executor::stack::{
-	IsPrecompileResult, PrecompileFailure, PrecompileHandle, PrecompileOutput, PrecompileSet,
+use evm::{
+	executor::stack::{
+		IsPrecompileResult, PrecompileFailure, PrecompileHandle, PrecompileOutput, PrecompileSet,
+	},
+	ExitError,
+};
+use itc_rest_client::{http_client::HttpClient, rest_client::Url};
+use lc_dynamic_assertion::{AssertionExecutionError, HttpRequestLog};
+use litentry_primitives::{AssertionLimits, AssertionPolicy};
+use std::{
+	cell::RefCell,
+	format,
+	result::Result as StdResult,
+	string::String,
+	time::{Duration, Instant},
+	vec::Vec,
 };
-use itc_rest_client::http_client::HttpClient;
-use std::{cell::RefCell, result::Result as StdResult, string::String, vec::Vec};
 
+mod encoding;
+mod hashing;
 mod hex_to_number;
 mod http_get;
+mod http_policy;
 mod http_post;
 mod identity_to_string;
 mod json_utils;
@@ -46,7 +68,9 @@ mod logging;
 mod macros;
 mod parse_decimal;
 mod parse_int;
+mod string_utils;
 mod to_hex;
+mod verify_signature;
 
 #[cfg(test)]
 mod mocks;
@@ -55,21 +79,82 @@ pub type PrecompileResult = StdResult<PrecompileOutput, PrecompileFailure>;
 
 pub struct Precompiles {
 	pub contract_logs: RefCell<Vec<String>>,
+	pub http_requests: RefCell<Vec<HttpRequestLog>>,
+	pub limits: AssertionLimits,
+	// `None` lets the assertion call any domain
+	pub allowed_domains: Option<Vec<String>>,
+	pub started_at: Instant,
+	// first policy violation, it aborts the execution
+	pub violation: RefCell<Option<AssertionExecutionError>>,
+}
+
+impl Precompiles {
+	// assertions created before policies were introduced are only subject to the default limits
+	pub fn new(policy: Option<&AssertionPolicy>) -> Self {
+		Self {
+			contract_logs: Vec::new().into(),
+			http_requests: Vec::new().into(),
+			limits: policy.map(|p| p.limits).unwrap_or_default(),
+			allowed_domains: policy.map(|p| {
+				p.allowed_domains
+					.iter()
+					.map(|domain| String::from_utf8_lossy(domain).to_lowercase())
+					.collect()
+			}),
+			started_at: Instant::now(),
+			violation: None.into(),
+		}
+	}
+
+	// `None` once the execution time limit is exhausted
+	pub fn remaining_time(&self) -> Option<Duration> {
+		Duration::from_millis(self.limits.max_execution_time)
+			.checked_sub(self.started_at.elapsed())
+			.filter(|remaining| !remaining.is_zero())
+	}
+
+	// the first violation is kept, it aborts the execution of the assertion
+	pub fn violate(&self, violation: AssertionExecutionError) {
+		log::debug!("Assertion policy violated: {:?}", violation);
+		self.violation.borrow_mut().get_or_insert(violation);
+	}
+
+	pub fn execution_time_exceeded(&self) -> AssertionExecutionError {
+		AssertionExecutionError::LimitExceeded(format!(
+			"execution time limit of {} ms exceeded",
+			self.limits.max_execution_time
+		))
+	}
+
+	pub fn is_domain_allowed(&self, url: &Url) -> bool {
+		match (&self.allowed_domains, url.host_str()) {
+			(None, _) => true,
+			(Some(allowed_domains), Some(host)) => allowed_domains.iter().any(|domain| {
+				host == domain
+					|| (host.ends_with(domain.as_str())
+						&& host[..host.len() - domain.len()].ends_with('.'))
+			}),
+			(Some(_), None) => false,
+		}
+	}
+}
+
+impl Default for Precompiles {
+	fn default() -> Self {
+		Self::new(None)
+	}
 }
 
 impl PrecompileSet for Precompiles {
 	fn execute(&self, handle: &mut impl PrecompileHandle) -> Option<PrecompileResult> {
 		let mut headers = itc_rest_client::rest_client::Headers::new();
 		headers.insert(http::header::CONNECTION.as_str(), "close");
-		let client = HttpClient::new(
-			itc_rest_client::http_client::DefaultSend {},
-			true,
-			Some(core::time::Duration::from_secs(5)),
-			Some(headers),
-			None,
-		);
-
-		match handle.code_address() {
+		let timeout = self.remaining_time().unwrap_or_default().min(Duration::from_secs(5));
+		let client =
+			HttpClient::new(PolicySend::new(self), true, Some(timeout), Some(headers), None);
+		let client = PolicyHttpClient::new(client, self);
+
+		let result = match handle.code_address() {
 			a if a == hash(1000) => Some(http_get_i64(handle.input().to_vec(), client)),
 			a if a == hash(1001) => Some(http_get_bool(handle.input().to_vec(), client)),
 			a if a == hash(1002) => Some(http_get_string(handle.input().to_vec(), client)),
@@ -89,8 +174,38 @@ impl PrecompileSet for Precompiles {
 			a if a == hash(1101) => Some(json_utils::json_get_i64(handle.input().to_vec(), self)),
 			a if a == hash(1102) => Some(json_utils::json_get_bool(handle.input().to_vec(), self)),
 			a if a == hash(1103) => Some(json_utils::get_array_len(handle.input().to_vec(), self)),
+			a if a == hash(1150) => Some(sha256(handle.input().to_vec())),
+			a if a == hash(1151) => Some(blake2b_256(handle.input().to_vec())),
+			a if a == hash(1152) => Some(blake2b_128(handle.input().to_vec())),
+			a if a == hash(1153) => Some(verify_signature(handle.input().to_vec())),
+			a if a == hash(1200) => Some(base58_encode(handle.input().to_vec())),
+			a if a == hash(1201) => Some(base58_decode(handle.input().to_vec())),
+			a if a == hash(1202) => Some(bech32_encode(handle.input().to_vec())),
+			a if a == hash(1203) => Some(bech32_decode(handle.input().to_vec())),
+			a if a == hash(1204) => Some(ss58_encode(handle.input().to_vec())),
+			a if a == hash(1205) => Some(ss58_decode(handle.input().to_vec())),
+			a if a == hash(1250) => Some(string_concat(handle.input().to_vec())),
+			a if a == hash(1251) => Some(string_split(handle.input().to_vec())),
+			a if a == hash(1252) => Some(string_to_lowercase(handle.input().to_vec())),
+			a if a == hash(1253) => Some(string_to_uppercase(handle.input().to_vec())),
 			_ => None,
+		};
+
+		// the execution time limit is only checked on precompile calls, the time spent in the EVM
+		// itself is not measured, it's only capped indirectly by the gas limit which covers both
+		// the deployment and the call
+		if result.is_some() && self.remaining_time().is_none() {
+			self.violate(self.execution_time_exceeded());
+		}
+
+		// revert the whole call stack once the policy is violated, the contract must not be able
+		// to carry on with a partial result
+		if result.is_some() && self.violation.borrow().is_some() {
+			return Some(Err(PrecompileFailure::Error {
+				exit_status: ExitError::Other("assertion policy violated".into()),
+			}))
 		}
+		result
 	}
 
 	fn is_precompile(&self, address: H160, _remaining_gas: u64) -> IsPrecompileResult {
@@ -131,6 +246,34 @@ impl PrecompileSet for Precompiles {
 				IsPrecompileResult::Answer { is_precompile: true, extra_cost: 0 },
 			a if a == hash(1103) =>
 				IsPrecompileResult::Answer { is_precompile: true, extra_cost: 0 },
+			a if a == hash(1150) =>
+				IsPrecompileResult::Answer { is_precompile: true, extra_cost: 0 },
+			a if a == hash(1151) =>
+				IsPrecompileResult::Answer { is_precompile: true, extra_cost: 0 },
+			a if a == hash(1152) =>
+				IsPrecompileResult::Answer { is_precompile: true, extra_cost: 0 },
+			a if a == hash(1153) =>
+				IsPrecompileResult::Answer { is_precompile: true, extra_cost: 0 },
+			a if a == hash(1200) =>
+				IsPrecompileResult::Answer { is_precompile: true, extra_cost: 0 },
+			a if a == hash(1201) =>
+				IsPrecompileResult::Answer { is_precompile: true, extra_cost: 0 },
+			a if a == hash(1202) =>
+				IsPrecompileResult::Answer { is_precompile: true, extra_cost: 0 },
+			a if a == hash(1203) =>
+				IsPrecompileResult::Answer { is_precompile: true, extra_cost: 0 },
+			a if a == hash(1204) =>
+				IsPrecompileResult::Answer { is_precompile: true, extra_cost: 0 },
+			a if a == hash(1205) =>
+				IsPrecompileResult::Answer { is_precompile: true, extra_cost: 0 },
+			a if a == hash(1250) =>
+				IsPrecompileResult::Answer { is_precompile: true, extra_cost: 0 },
+			a if a == hash(1251) =>
+				IsPrecompileResult::Answer { is_precompile: true, extra_cost: 0 },
+			a if a == hash(1252) =>
+				IsPrecompileResult::Answer { is_precompile: true, extra_cost: 0 },
+			a if a == hash(1253) =>
+				IsPrecompileResult::Answer { is_precompile: true, extra_cost: 0 },
 			_ => IsPrecompileResult::Answer { is_precompile: false, extra_cost: 0 },
 		}
 	}
@@ -0,0 +1,176 @@
+// Copyright 2020-2024 Trust Computing GmbH.
+// This file is part of Litentry.
+//
+// Litentry is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// Litentry is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with Litentry.  If not, see <https://www.gnu.org/licenses/>.
+
+use crate::{failure_precompile_output, precompiles::PrecompileResult, success_precompile_output};
+use std::{string::String, vec::Vec};
+
+pub fn string_concat(input: Vec<u8>) -> PrecompileResult {
+	let (first, second) = match decode_two_strings(&input) {
+		Some(v) => v,
+		None => return Ok(failure_precompile_output(ethabi::Token::String(Default::default()))),
+	};
+	Ok(success_precompile_output(ethabi::Token::String(first + &second)))
+}
+
+pub fn string_split(input: Vec<u8>) -> PrecompileResult {
+	let (value, separator) = match decode_two_strings(&input) {
+		Some(v) => v,
+		None => return Ok(failure_precompile_output(ethabi::Token::Array(Default::default()))),
+	};
+	if separator.is_empty() {
+		log::debug!("Cannot split string {:?} by an empty separator", value);
+		return Ok(failure_precompile_output(ethabi::Token::Array(Default::default())))
+	}
+	let parts = value
+		.split(separator.as_str())
+		.map(|v| ethabi::Token::String(v.into()))
+		.collect();
+	Ok(success_precompile_output(ethabi::Token::Array(parts)))
+}
+
+pub fn string_to_lowercase(input: Vec<u8>) -> PrecompileResult {
+	let value = match decode_string(&input) {
+		Some(v) => v,
+		None => return Ok(failure_precompile_output(ethabi::Token::String(Default::default()))),
+	};
+	Ok(success_precompile_output(ethabi::Token::String(value.to_lowercase())))
+}
+
+pub fn string_to_uppercase(input: Vec<u8>) -> PrecompileResult {
+	let value = match decode_string(&input) {
+		Some(v) => v,
+		None => return Ok(failure_precompile_output(ethabi::Token::String(Default::default()))),
+	};
+	Ok(success_precompile_output(ethabi::Token::String(value.to_uppercase())))
+}
+
+fn decode_string(input: &[u8]) -> Option<String> {
+	match ethabi::decode(&[ethabi::ParamType::String], input) {
+		Ok(d) => d.get(0).and_then(|v| v.clone().into_string()),
+		Err(e) => {
+			log::debug!("Could not decode string {:?}, reason: {:?}", input, e);
+			None
+		},
+	}
+}
+
+fn decode_two_strings(input: &[u8]) -> Option<(String, String)> {
+	match ethabi::decode(&[ethabi::ParamType::String, ethabi::ParamType::String], input) {
+		Ok(d) => match (
+			d.get(0).and_then(|v| v.clone().into_string()),
+			d.get(1).and_then(|v| v.clone().into_string()),
+		) {
+			(Some(first), Some(second)) => Some((first, second)),
+			_ => None,
+		},
+		Err(e) => {
+			log::debug!("Could not decode strings {:?}, reason: {:?}", input, e);
+			None
+		},
+	}
+}
+
+#[cfg(test)]
+pub mod test {
+	use crate::{
+		failure_precompile_output,
+		precompiles::string_utils::{
+			string_concat, string_split, string_to_lowercase, string_to_uppercase,
+		},
+		success_precompile_output,
+	};
+	use ethabi::{encode, Token};
+
+	#[test]
+	fn test_string_concat() {
+		// given
+		let encoded = encode(&[Token::String("lit".into()), Token::String("entry".into())]);
+
+		// when
+		let result = string_concat(encoded).unwrap();
+
+		// then
+		assert_eq!(success_precompile_output(Token::String("litentry".into())), result)
+	}
+
+	#[test]
+	fn test_string_split() {
+		// given
+		let encoded = encode(&[Token::String("a,b,,c".into()), Token::String(",".into())]);
+
+		// when
+		let result = string_split(encoded).unwrap();
+
+		// then
+		assert_eq!(
+			success_precompile_output(Token::Array(vec![
+				Token::String("a".into()),
+				Token::String("b".into()),
+				Token::String("".into()),
+				Token::String("c".into()),
+			])),
+			result
+		)
+	}
+
+	#[test]
+	fn test_string_split_fail() {
+		// given
+		let encoded = encode(&[Token::String("abc".into()), Token::String("".into())]);
+
+		// when
+		let result = string_split(encoded).unwrap();
+
+		// then
+		assert_eq!(failure_precompile_output(Token::Array(Default::default())), result)
+	}
+
+	#[test]
+	fn test_string_to_lowercase() {
+		// given
+		let encoded = encode(&[Token::String("0xAbCdEf".into())]);
+
+		// when
+		let result = string_to_lowercase(encoded).unwrap();
+
+		// then
+		assert_eq!(success_precompile_output(Token::String("0xabcdef".into())), result)
+	}
+
+	#[test]
+	fn test_string_to_uppercase() {
+		// given
+		let encoded = encode(&[Token::String("0xAbCdEf".into())]);
+
+		// when
+		let result = string_to_uppercase(encoded).unwrap();
+
+		// then
+		assert_eq!(success_precompile_output(Token::String("0XABCDEF".into())), result)
+	}
+
+	#[test]
+	fn test_string_to_lowercase_fail() {
+		// given
+		let encoded = encode(&[]);
+
+		// when
+		let result = string_to_lowercase(encoded).unwrap();
+
+		// then
+		assert_eq!(failure_precompile_output(Token::String(Default::default())), result)
+	}
+}
@@ -0,0 +1,191 @@
+// Copyright 2020-2024 Trust Computing GmbH.
+// This file is part of Litentry.
+//
+// Litentry is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// Litentry is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with Litentry.  If not, see <https://www.gnu.org/licenses/>.
+
+use crate::{failure_precompile_output, precompiles::PrecompileResult, success_precompile_output};
+use ethabi::ethereum_types::U256;
+use litentry_primitives::{
+	Address20, Address32, Address33, BitcoinSignature, EthereumSignature, Identity,
+	LitentryMultiSignature,
+};
+use sp_core::{ecdsa, ed25519, sr25519};
+use std::vec::Vec;
+
+// verifySignature(uint32 identityType, bytes identityValue, uint32 signatureType, bytes signature, bytes message)
+// identity types follow `identity_with_networks_to_token`, signature types follow the codec index of `LitentryMultiSignature`
+pub fn verify_signature(input: Vec<u8>) -> PrecompileResult {
+	let decoded = match ethabi::decode(
+		&[
+			ethabi::ParamType::Uint(32),
+			ethabi::ParamType::Bytes,
+			ethabi::ParamType::Uint(32),
+			ethabi::ParamType::Bytes,
+			ethabi::ParamType::Bytes,
+		],
+		&input,
+	) {
+		Ok(d) => d,
+		Err(e) => {
+			log::debug!("Could not decode input {:?}, reason: {:?}", input, e);
+			return Ok(failure_precompile_output(ethabi::Token::Bool(Default::default())))
+		},
+	};
+
+	let identity_type = decoded.get(0).and_then(|v| v.clone().into_uint()).and_then(to_u32);
+	let identity_value = decoded.get(1).and_then(|v| v.clone().into_bytes());
+	let signature_type = decoded.get(2).and_then(|v| v.clone().into_uint()).and_then(to_u32);
+	let signature_value = decoded.get(3).and_then(|v| v.clone().into_bytes());
+	let message = decoded.get(4).and_then(|v| v.clone().into_bytes());
+
+	let (identity, signature, message) =
+		match (identity_type, identity_value, signature_type, signature_value, message) {
+			(Some(it), Some(iv), Some(st), Some(sv), Some(m)) =>
+				match (to_identity(it, &iv), to_signature(st, &sv)) {
+					(Some(identity), Some(signature)) => (identity, signature, m),
+					_ => {
+						log::debug!(
+							"Could not decode input {:?}, reason: identity or signature is invalid",
+							input
+						);
+						return Ok(failure_precompile_output(
+							ethabi::Token::Bool(Default::default()),
+						))
+					},
+				},
+			_ => {
+				log::debug!("Could not decode input {:?}, reason: missing parameters", input);
+				return Ok(failure_precompile_output(ethabi::Token::Bool(Default::default())))
+			},
+		};
+
+	Ok(success_precompile_output(ethabi::Token::Bool(signature.verify(&message, &identity))))
+}
+
+// ethabi doesn't range check `uint32`, `as_u32` would panic on anything wider
+fn to_u32(value: U256) -> Option<u32> {
+	if value > U256::from(u32::MAX) {
+		return None
+	}
+	Some(value.low_u32())
+}
+
+fn to_identity(identity_type: u32, value: &[u8]) -> Option<Identity> {
+	match identity_type {
+		3 => Address32::try_from(value).ok().map(Identity::Substrate),
+		4 => Address20::try_from(value).ok().map(Identity::Evm),
+		5 => Address33::try_from(value).ok().map(Identity::Bitcoin),
+		6 => Address32::try_from(value).ok().map(Identity::Solana),
+		// web2 identities cannot sign
+		_ => None,
+	}
+}
+
+fn to_signature(signature_type: u32, value: &[u8]) -> Option<LitentryMultiSignature> {
+	match signature_type {
+		0 => ed25519::Signature::try_from(value).ok().map(LitentryMultiSignature::Ed25519),
+		1 => sr25519::Signature::try_from(value).ok().map(LitentryMultiSignature::Sr25519),
+		2 => ecdsa::Signature::try_from(value).ok().map(LitentryMultiSignature::Ecdsa),
+		3 => EthereumSignature::try_from(value).ok().map(LitentryMultiSignature::Ethereum),
+		4 => BitcoinSignature::try_from(value).ok().map(LitentryMultiSignature::Bitcoin),
+		_ => None,
+	}
+}
+
+#[cfg(test)]
+pub mod test {
+	use crate::{
+		failure_precompile_output, precompiles::verify_signature::verify_signature,
+		success_precompile_output,
+	};
+	use ethabi::{encode, ethereum_types::U256, Token};
+	use sp_core::{ed25519, Pair};
+
+	#[test]
+	pub fn test_verify_ed25519_signature() {
+		// given
+		let pair = ed25519::Pair::from_seed(&[1u8; 32]);
+		let message = "litentry".as_bytes().to_vec();
+		let signature = pair.sign(&message);
+		let encoded = encode(&[
+			Token::Uint(3.into()),
+			Token::Bytes(pair.public().0.to_vec()),
+			Token::Uint(0.into()),
+			Token::Bytes(signature.0.to_vec()),
+			Token::Bytes(message),
+		]);
+
+		// when
+		let result = verify_signature(encoded).unwrap();
+
+		// then
+		assert_eq!(success_precompile_output(Token::Bool(true)), result)
+	}
+
+	#[test]
+	pub fn test_verify_ed25519_signature_wrong_message() {
+		// given
+		let pair = ed25519::Pair::from_seed(&[1u8; 32]);
+		let signature = pair.sign("litentry".as_bytes());
+		let encoded = encode(&[
+			Token::Uint(3.into()),
+			Token::Bytes(pair.public().0.to_vec()),
+			Token::Uint(0.into()),
+			Token::Bytes(signature.0.to_vec()),
+			Token::Bytes("other".as_bytes().to_vec()),
+		]);
+
+		// when
+		let result = verify_signature(encoded).unwrap();
+
+		// then
+		assert_eq!(success_precompile_output(Token::Bool(false)), result)
+	}
+
+	#[test]
+	pub fn test_verify_signature_fail_with_out_of_range_type() {
+		// given
+		let encoded = encode(&[
+			Token::Uint(U256::from(u32::MAX) + 3),
+			Token::Bytes([0u8; 32].to_vec()),
+			Token::Uint(0.into()),
+			Token::Bytes([0u8; 64].to_vec()),
+			Token::Bytes("litentry".as_bytes().to_vec()),
+		]);
+
+		// when
+		let result = verify_signature(encoded).unwrap();
+
+		// then
+		assert_eq!(failure_precompile_output(Token::Bool(Default::default())), result)
+	}
+
+	#[test]
+	pub fn test_verify_signature_fail() {
+		// given
+		let encoded = encode(&[
+			Token::Uint(0.into()),
+			Token::Bytes("alice".as_bytes().to_vec()),
+			Token::Uint(0.into()),
+			Token::Bytes([0u8; 64].to_vec()),
+			Token::Bytes("litentry".as_bytes().to_vec()),
+		]);
+
+		// when
+		let result = verify_signature(encoded).unwrap();
+
+		// then
+		assert_eq!(failure_precompile_output(Token::Bool(Default::default())), result)
+	}
+}
@@ -22,11 +22,12 @@ extern crate sgx_tstd as std;
 
 use crate::{
 	sealing::io::{seal_state, unseal_state},
-	AssertionId, SmartContractByteCode,
+	AssertionId, AssertionRepositoryItem,
 };
-use lc_dynamic_assertion::AssertionLogicRepository;
+use codec::{Decode, Encode};
+use lc_dynamic_assertion::{AssertionLogicRepository, AssertionPolicy, AssertionVersion};
 use std::{
-	collections::HashMap,
+	collections::{BTreeMap, HashMap},
 	format,
 	string::{String, ToString},
 	vec::Vec,
@@ -38,9 +39,22 @@ use std::sync::SgxMutex as Mutex;
 #[cfg(feature = "std")]
 use std::sync::Mutex;
 
-pub type AssertionsMap = HashMap<AssertionId, (SmartContractByteCode, Vec<String>)>;
+pub type AssertionsMap = HashMap<AssertionId, VersionedAssertion>;
 
-// Assertion repository backed by sealed file. Contains only latest state from parachain storage.
+// Versions kept per assertion, mirrors `MaxAssertionVersions` of the parachain runtimes which
+// only allow rolling back to one of the retained versions anyway
+pub const MAX_ASSERTION_VERSIONS: usize = 10;
+
+// Every version of an assertion seen on the parachain, together with the active one and the
+// policy the assertion was created with
+#[derive(Encode, Decode, Clone, Debug, Default, PartialEq, Eq)]
+pub struct VersionedAssertion {
+	pub active_version: AssertionVersion,
+	pub versions: BTreeMap<AssertionVersion, AssertionRepositoryItem>,
+	pub policy: Option<AssertionPolicy>,
+}
+
+// Assertion repository backed by sealed file. Contains every version from parachain storage.
 pub struct EvmAssertionRepository {
 	path: String,
 	state: Mutex<AssertionsMap>,
@@ -51,48 +65,129 @@ impl EvmAssertionRepository {
 		let mut state = HashMap::new();
 		let unsealed = unseal_state(path)
 			.map_err(|e| format!("Could not unseal assertions state: {:?}", e))?;
-		unsealed.into_iter().for_each(|(id, byte_code)| {
-			state.insert(id, byte_code);
+		unsealed.into_iter().for_each(|(id, assertion)| {
+			state.insert(id, assertion);
 		});
 
 		Ok(EvmAssertionRepository { state: state.into(), path: path.to_string() })
 	}
+
+	fn seal(&self, state: &AssertionsMap) -> Result<(), String> {
+		// prepare data for encoding
+		let unsealed_state: Vec<(AssertionId, VersionedAssertion)> =
+			state.iter().map(|(key, val)| (*key, val.clone())).collect();
+		seal_state(&self.path, unsealed_state)
+			.map_err(|e| format!("Could not seal assertions state: {:?}", e))
+	}
 }
 
 impl AssertionLogicRepository for EvmAssertionRepository {
 	type Id = AssertionId;
-	type Item = (SmartContractByteCode, Vec<String>);
+	type Item = AssertionRepositoryItem;
 
-	fn get(&self, id: &Self::Id) -> Result<Option<Self::Item>, String> {
+	fn get(&self, id: &Self::Id) -> Result<Option<(AssertionVersion, Self::Item)>, String> {
 		Ok(self
 			.state
 			.lock()
 			.map_err(|e| format!("Could not acquire lock on inner state: {:?}", e))?
 			.get(id)
-			.cloned())
+			.and_then(|assertion| {
+				assertion
+					.versions
+					.get(&assertion.active_version)
+					.map(|item| (assertion.active_version, item.clone()))
+			}))
 	}
 
-	fn save(&self, id: Self::Id, item: Self::Item) -> Result<(), String> {
-		self.state
+	fn get_version(
+		&self,
+		id: &Self::Id,
+		version: AssertionVersion,
+	) -> Result<Option<Self::Item>, String> {
+		Ok(self
+			.state
 			.lock()
 			.map_err(|e| format!("Could not acquire lock on inner state: {:?}", e))?
-			.insert(id, item);
-		// prepare data for encoding
-		let unsealed_state: Vec<(AssertionId, Self::Item)> = self
+			.get(id)
+			.and_then(|assertion| assertion.versions.get(&version).cloned()))
+	}
+
+	fn save(
+		&self,
+		id: Self::Id,
+		version: AssertionVersion,
+		item: Self::Item,
+	) -> Result<(), String> {
+		let mut state = self
+			.state
+			.lock()
+			.map_err(|e| format!("Could not acquire lock on inner state: {:?}", e))?;
+		let previous = state.get(&id).cloned();
+		let assertion = state.entry(id).or_default();
+		assertion.versions.insert(version, item);
+		assertion.active_version = version;
+		while assertion.versions.len() > MAX_ASSERTION_VERSIONS {
+			if let Some(oldest) = assertion.versions.keys().next().copied() {
+				assertion.versions.remove(&oldest);
+			}
+		}
+
+		if let Err(e) = self.seal(&state) {
+			//clean up memory state
+			match previous {
+				Some(previous) => state.insert(id, previous),
+				None => state.remove(&id),
+			};
+			return Err(e)
+		}
+		Ok(())
+	}
+
+	fn activate(&self, id: &Self::Id, version: AssertionVersion) -> Result<(), String> {
+		let mut state = self
+			.state
+			.lock()
+			.map_err(|e| format!("Could not acquire lock on inner state: {:?}", e))?;
+		let assertion = state.get_mut(id).ok_or_else(|| format!("Assertion {:?} not found", id))?;
+		if !assertion.versions.contains_key(&version) {
+			return Err(format!("Version {} of assertion {:?} not found", version, id))
+		}
+		let previous_version = core::mem::replace(&mut assertion.active_version, version);
+
+		if let Err(e) = self.seal(&state) {
+			//clean up memory state
+			if let Some(assertion) = state.get_mut(id) {
+				assertion.active_version = previous_version;
+			}
+			return Err(e)
+		}
+		Ok(())
+	}
+
+	fn get_policy(&self, id: &Self::Id) -> Result<Option<AssertionPolicy>, String> {
+		Ok(self
 			.state
 			.lock()
 			.map_err(|e| format!("Could not acquire lock on inner state: {:?}", e))?
-			.iter()
-			.map(|(key, val)| (*key, val.clone()))
-			.collect();
+			.get(id)
+			.and_then(|assertion| assertion.policy.clone()))
+	}
 
-		if let Err(e) = seal_state(&self.path, unsealed_state) {
+	fn save_policy(&self, id: Self::Id, policy: AssertionPolicy) -> Result<(), String> {
+		let mut state = self
+			.state
+			.lock()
+			.map_err(|e| format!("Could not acquire lock on inner state: {:?}", e))?;
+		let assertion =
+			state.get_mut(&id).ok_or_else(|| format!("Assertion {:?} not found", id))?;
+		let previous_policy = assertion.policy.replace(policy);
+
+		if let Err(e) = self.seal(&state) {
 			//clean up memory state
-			self.state
-				.lock()
-				.map_err(|e| format!("Could not acquire lock on inner state: {:?}", e))?
-				.remove(&id);
-			return Err(format!("Could not seal assertions state: {:?}", e))
+			if let Some(assertion) = state.get_mut(&id) {
+				assertion.policy = previous_policy;
+			}
+			return Err(e)
 		}
 		Ok(())
 	}
@@ -100,11 +195,15 @@ impl AssertionLogicRepository for EvmAssertionRepository {
 
 #[cfg(feature = "sgx-test")]
 pub mod sgx_tests {
-	use crate::{repository::EvmAssertionRepository, sealing::io::seal_state};
+	use crate::{
+		repository::{EvmAssertionRepository, VersionedAssertion, MAX_ASSERTION_VERSIONS},
+		sealing::io::seal_state,
+	};
 	use ethabi::ethereum_types::H160;
 	use itp_sgx_temp_dir::TempDir;
-	use lc_dynamic_assertion::AssertionLogicRepository;
-	use sgx_tstd::{string::ToString, vec, vec::Vec};
+	use lc_dynamic_assertion::{AssertionLogicRepository, AssertionPolicy};
+	use litentry_primitives::AssertionLimits;
+	use sgx_tstd::{collections::BTreeMap, string::ToString, vec, vec::Vec};
 
 	pub fn restores_state_from_seal() {
 		let seal_file_name = "test_sealed_assertion.bin";
@@ -115,14 +214,99 @@ pub mod sgx_tests {
 		let byte_code: Vec<u8> = [1; 67].to_vec();
 		let secrets = vec!["secret_1".to_string()];
 
+		let mut versions = BTreeMap::new();
+		versions.insert(0, ([0; 67].to_vec(), vec![]));
+		versions.insert(1, (byte_code.clone(), secrets.clone()));
+
 		seal_state(
 			seal_path.to_str().unwrap(),
-			vec![(assertion_id.clone(), (byte_code.clone(), secrets.clone()))],
+			vec![(
+				assertion_id.clone(),
+				VersionedAssertion { active_version: 1, versions, policy: None },
+			)],
 		)
 		.unwrap();
 
 		let repository = EvmAssertionRepository::new(seal_path.to_str().unwrap()).unwrap();
-		let assertion = repository.get(&assertion_id).unwrap().unwrap();
+		let (version, assertion) = repository.get(&assertion_id).unwrap().unwrap();
+		assert_eq!(version, 1);
+		assert_eq!(assertion.0, byte_code);
+		assert_eq!(assertion.1, secrets);
+	}
+
+	pub fn seals_every_assertion_version() {
+		let seal_file_name = "test_sealed_assertion_versions.bin";
+		let temp_dir = TempDir::with_prefix("evm_assertion_seal_tests").unwrap();
+		let seal_path = temp_dir.path().join(seal_file_name);
+
+		let assertion_id = H160::default();
+		let repository = EvmAssertionRepository::new(seal_path.to_str().unwrap()).unwrap();
+		repository.save(assertion_id, 0, ([0; 67].to_vec(), vec![])).unwrap();
+		repository
+			.save(assertion_id, 1, ([1; 67].to_vec(), vec!["secret_1".to_string()]))
+			.unwrap();
+		repository.activate(&assertion_id, 0).unwrap();
+
+		let repository = EvmAssertionRepository::new(seal_path.to_str().unwrap()).unwrap();
+		let (version, assertion) = repository.get(&assertion_id).unwrap().unwrap();
+		assert_eq!(version, 0);
+		assert_eq!(assertion.0, [0; 67].to_vec());
+		let assertion = repository.get_version(&assertion_id, 1).unwrap().unwrap();
+		assert_eq!(assertion.0, [1; 67].to_vec());
+		assert!(repository.activate(&assertion_id, 2).is_err());
+	}
+
+	pub fn drops_oldest_assertion_versions() {
+		let seal_file_name = "test_sealed_assertion_version_limit.bin";
+		let temp_dir = TempDir::with_prefix("evm_assertion_seal_tests").unwrap();
+		let seal_path = temp_dir.path().join(seal_file_name);
+
+		let assertion_id = H160::default();
+		let repository = EvmAssertionRepository::new(seal_path.to_str().unwrap()).unwrap();
+		(0..=MAX_ASSERTION_VERSIONS as u32).for_each(|version| {
+			repository.save(assertion_id, version, ([0; 67].to_vec(), vec![])).unwrap();
+		});
+
+		assert!(repository.get_version(&assertion_id, 0).unwrap().is_none());
+		assert!(repository.get_version(&assertion_id, 1).unwrap().is_some());
+		let (version, _) = repository.get(&assertion_id).unwrap().unwrap();
+		assert_eq!(version, MAX_ASSERTION_VERSIONS as u32);
+	}
+
+	pub fn seals_assertion_policy() {
+		let seal_file_name = "test_sealed_assertion_policy.bin";
+		let temp_dir = TempDir::with_prefix("evm_assertion_seal_tests").unwrap();
+		let seal_path = temp_dir.path().join(seal_file_name);
+
+		let assertion_id = H160::default();
+		let policy = AssertionPolicy {
+			limits: AssertionLimits { max_http_calls: 1, ..Default::default() },
+			allowed_domains: Default::default(),
+		};
+		let repository = EvmAssertionRepository::new(seal_path.to_str().unwrap()).unwrap();
+		assert!(repository.save_policy(assertion_id, policy.clone()).is_err());
+		repository.save(assertion_id, 0, ([0; 67].to_vec(), vec![])).unwrap();
+		repository.save_policy(assertion_id, policy.clone()).unwrap();
+
+		let repository = EvmAssertionRepository::new(seal_path.to_str().unwrap()).unwrap();
+		assert_eq!(repository.get_policy(&assertion_id).unwrap(), Some(policy));
+	}
+
+	pub fn restores_state_from_legacy_seal() {
+		let seal_file_name = "test_sealed_legacy_assertion.bin";
+		let temp_dir = TempDir::with_prefix("evm_assertion_seal_tests").unwrap();
+		let seal_path = temp_dir.path().join(seal_file_name);
+
+		let assertion_id = H160::default();
+		let byte_code: Vec<u8> = [1; 67].to_vec();
+		let secrets = vec!["secret_1".to_string()];
+		let legacy_state: Vec<(H160, (Vec<u8>, Vec<sgx_tstd::string::String>))> =
+			vec![(assertion_id, (byte_code.clone(), secrets.clone()))];
+		itp_sgx_io::seal(&codec::Encode::encode(&legacy_state), seal_path.clone()).unwrap();
+
+		let repository = EvmAssertionRepository::new(seal_path.to_str().unwrap()).unwrap();
+		let (version, assertion) = repository.get(&assertion_id).unwrap().unwrap();
+		assert_eq!(version, 0);
 		assert_eq!(assertion.0, byte_code);
 		assert_eq!(assertion.1, secrets);
 	}
@@ -1,10 +1,14 @@
 #[cfg(feature = "sgx")]
 use thiserror_sgx as thiserror;
 
-use crate::{AssertionId, SmartContractByteCode};
-use std::{boxed::Box, string::String, vec::Vec};
+use crate::{repository::VersionedAssertion, AssertionId, AssertionRepositoryItem};
+use codec::{Decode, DecodeAll};
+use std::{boxed::Box, collections::BTreeMap, vec::Vec};
 
-pub type UnsealedAssertions = Vec<(AssertionId, (SmartContractByteCode, Vec<String>))>;
+pub type UnsealedAssertions = Vec<(AssertionId, VersionedAssertion)>;
+
+// Format used before assertions were versioned, kept to restore state sealed by older enclaves
+pub type LegacyUnsealedAssertions = Vec<(AssertionId, AssertionRepositoryItem)>;
 
 #[derive(Debug, thiserror::Error)]
 pub enum SealingError {
@@ -30,6 +34,22 @@ impl From<codec::Error> for SealingError {
 	}
 }
 
+pub fn decode_unsealed_assertions(bytes: &[u8]) -> Result<UnsealedAssertions, SealingError> {
+	if let Ok(unsealed) = UnsealedAssertions::decode_all(&mut &bytes[..]) {
+		return Ok(unsealed)
+	}
+	// every legacy assertion becomes version 0, the version `create_assertion` stores
+	let legacy = LegacyUnsealedAssertions::decode(&mut &bytes[..])?;
+	Ok(legacy
+		.into_iter()
+		.map(|(id, item)| {
+			let mut versions = BTreeMap::new();
+			versions.insert(0, item);
+			(id, VersionedAssertion { active_version: 0, versions, policy: None })
+		})
+		.collect())
+}
+
 #[cfg(feature = "std")]
 pub mod io {
 	use crate::sealing::{SealingError, UnsealedAssertions};
@@ -46,8 +66,8 @@ pub mod io {
 
 #[cfg(feature = "sgx")]
 pub mod io {
-	use crate::sealing::{SealingError, UnsealedAssertions};
-	pub use codec::{Decode, Encode};
+	use crate::sealing::{decode_unsealed_assertions, SealingError, UnsealedAssertions};
+	pub use codec::Encode;
 	use itp_sgx_io::{seal as io_seal, unseal as io_unseal, SealedIO};
 	use log::{debug, info};
 	use std::{path::PathBuf, sgxfs::SgxFile, vec};
@@ -81,7 +101,7 @@ pub mod io {
 				self.seal(&vec![])?;
 			}
 
-			Ok(io_unseal(self.base_path.clone()).map(|b| Decode::decode(&mut b.as_slice()))??)
+			decode_unsealed_assertions(&io_unseal(self.base_path.clone())?)
 		}
 
 		fn seal(&self, unsealed: &Self::Unsealed) -> Result<(), Self::Error> {
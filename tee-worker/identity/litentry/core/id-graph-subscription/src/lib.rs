@@ -0,0 +1,550 @@
+// Copyright 2020-2024 Trust Computing GmbH.
+// This file is part of Litentry.
+//
+// Litentry is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// Litentry is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with Litentry.  If not, see <https://www.gnu.org/licenses/>.
+
+//! Subscriptions of ID graph owners to changes of their ID graph and to VC issuances.
+//!
+//! A client picks a random nonce for its connection, fetches a challenge for its identity and
+//! that nonce, signs both and subscribes with an AES request. The direct RPC connection is then
+//! kept open and every change is pushed to it, encrypted with the AES key of the subscription
+//! request. Unsubscribing requires the same AES key, subscriptions end with their connection
+//! or after `SUBSCRIPTION_LIFETIME`.
+
+#![cfg_attr(not(feature = "std"), no_std)]
+
+#[cfg(all(feature = "std", feature = "sgx"))]
+compile_error!("feature \"std\" and feature \"sgx\" cannot be enabled at the same time");
+
+#[cfg(all(not(feature = "std"), feature = "sgx"))]
+extern crate sgx_tstd as std;
+
+// re-export module to properly feature gate sgx and regular std environment
+#[cfg(all(not(feature = "std"), feature = "sgx"))]
+pub mod sgx_reexport_prelude {
+	pub use rand_sgx as rand;
+}
+
+#[cfg(all(not(feature = "std"), feature = "sgx"))]
+pub use crate::sgx_reexport_prelude::*;
+
+#[cfg(feature = "std")]
+use std::sync::Mutex;
+#[cfg(feature = "sgx")]
+use std::sync::SgxMutex as Mutex;
+
+use codec::{Decode, Encode};
+use itp_types::{ShardIdentifier, H256};
+use lazy_static::lazy_static;
+use litentry_primitives::{
+	aes_encrypt_default, Assertion, Identity, LitentryMultiSignature, RequestAesKey,
+};
+use log::*;
+use rand::Rng;
+use sp_core::blake2_256;
+use std::{
+	collections::BTreeMap,
+	sync::Arc,
+	time::{Duration, Instant},
+	vec::Vec,
+};
+
+/// Prefix of the message a subscriber signs, it keeps the signature from being valid for anything else.
+pub const CHALLENGE_MESSAGE_PREFIX: &[u8] = b"Litentry ID graph subscription:";
+
+/// How long a challenge can be used to subscribe.
+pub const CHALLENGE_LIFETIME: Duration = Duration::from_secs(300);
+
+/// Challenges that may be pending at the same time, over all identities.
+pub const MAX_PENDING_CHALLENGES: usize = 10_000;
+
+/// Challenges (one per connection nonce) that may be pending for an identity at the same time.
+pub const MAX_PENDING_CHALLENGES_PER_IDENTITY: usize = 4;
+
+/// How long a subscription lasts before the client has to subscribe again.
+pub const SUBSCRIPTION_LIFETIME: Duration = Duration::from_secs(24 * 60 * 60);
+
+/// Subscriptions that may exist at the same time, over all identities.
+pub const MAX_SUBSCRIPTIONS: usize = 10_000;
+
+/// Subscriptions an identity may hold at the same time, the oldest one is dropped beyond that.
+pub const MAX_SUBSCRIPTIONS_PER_IDENTITY: usize = 4;
+
+lazy_static! {
+	static ref GLOBAL_ID_GRAPH_SUBSCRIPTIONS: Arc<IdGraphSubscriptions> = Default::default();
+}
+
+/// The subscriptions shared by the direct RPC server, the block production and the VC issuance.
+pub fn id_graph_subscriptions() -> Arc<IdGraphSubscriptions> {
+	GLOBAL_ID_GRAPH_SUBSCRIPTIONS.clone()
+}
+
+/// Payload of the AES request to subscribe, the signature is over `message(nonce, challenge)`.
+#[derive(Encode, Decode, Clone, Debug, PartialEq, Eq)]
+pub struct IdGraphSubscriptionRequest {
+	pub who: Identity,
+	/// Chosen by the client for its connection when fetching the challenge.
+	pub nonce: H256,
+	pub challenge: H256,
+	pub signature: LitentryMultiSignature,
+}
+
+impl IdGraphSubscriptionRequest {
+	pub fn message(nonce: &H256, challenge: &H256) -> Vec<u8> {
+		[CHALLENGE_MESSAGE_PREFIX, nonce.as_bytes(), challenge.as_bytes()].concat()
+	}
+
+	pub fn verify_signature(&self) -> bool {
+		self.signature.verify(&Self::message(&self.nonce, &self.challenge), &self.who)
+	}
+}
+
+/// Pushed to the subscriber, AES encrypted.
+#[derive(Encode, Decode, Clone, Debug, PartialEq, Eq)]
+pub enum IdGraphNotification {
+	/// The ID graph of the subscriber changed, `None` if it is empty.
+	IdGraphChanged { id_graph_hash: Option<H256> },
+	/// A VC was issued for the subscriber, based on the ID graph with the given hash.
+	VcIssued { assertion: Assertion, id_graph_hash: H256 },
+}
+
+/// Subscription hash (the watched connection) and the encoded, encrypted notification.
+pub type Notification = (H256, Vec<u8>);
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Error {
+	TooManyPendingChallenges,
+	TooManySubscriptions,
+	ChallengeNotFound,
+	ChallengeExpired,
+	InvalidSignature,
+}
+
+struct Subscription {
+	hash: H256,
+	shard: ShardIdentifier,
+	key: RequestAesKey,
+	id_graph_hash: Option<H256>,
+	subscribed_at: Instant,
+}
+
+#[derive(Default)]
+struct SubscriptionState {
+	/// Pending challenges by identity and connection nonce.
+	challenges: BTreeMap<(Identity, H256), (H256, Instant)>,
+	subscriptions: BTreeMap<Identity, Vec<Subscription>>,
+}
+
+impl SubscriptionState {
+	fn prune_expired_challenges(&mut self, now: Instant) {
+		self.challenges
+			.retain(|_, (_, issued)| now.saturating_duration_since(*issued) < CHALLENGE_LIFETIME);
+	}
+
+	fn prune_expired_subscriptions(&mut self, now: Instant) {
+		self.subscriptions.retain(|_, subscriptions| {
+			subscriptions
+				.retain(|s| now.saturating_duration_since(s.subscribed_at) < SUBSCRIPTION_LIFETIME);
+			!subscriptions.is_empty()
+		});
+	}
+
+	fn subscription_count(&self) -> usize {
+		self.subscriptions.values().map(Vec::len).sum()
+	}
+
+	fn remove_subscriptions(&mut self, mut matches: impl FnMut(&Subscription) -> bool) -> bool {
+		let mut found = false;
+		self.subscriptions.retain(|_, subscriptions| {
+			let len = subscriptions.len();
+			subscriptions.retain(|s| !matches(s));
+			found |= subscriptions.len() != len;
+			!subscriptions.is_empty()
+		});
+		found
+	}
+}
+
+#[derive(Default)]
+pub struct IdGraphSubscriptions {
+	state: Mutex<SubscriptionState>,
+}
+
+impl IdGraphSubscriptions {
+	/// Issue a new challenge for `who` and the connection `nonce`, replacing a pending one
+	/// of the same nonce.
+	pub fn new_challenge(&self, who: &Identity, nonce: H256) -> Result<H256, Error> {
+		let challenge = H256::from(rand::thread_rng().gen::<[u8; 32]>());
+		self.insert_challenge(who, nonce, challenge, Instant::now())?;
+		Ok(challenge)
+	}
+
+	fn insert_challenge(
+		&self,
+		who: &Identity,
+		nonce: H256,
+		challenge: H256,
+		now: Instant,
+	) -> Result<(), Error> {
+		let mut state = self.state.lock().unwrap();
+		let key = (who.clone(), nonce);
+		if !state.challenges.contains_key(&key) {
+			if state.challenges.len() >= MAX_PENDING_CHALLENGES {
+				state.prune_expired_challenges(now);
+			}
+			if state.challenges.len() >= MAX_PENDING_CHALLENGES {
+				return Err(Error::TooManyPendingChallenges)
+			}
+			let pending_of_who = state.challenges.keys().filter(|(w, _)| w == who).count();
+			if pending_of_who >= MAX_PENDING_CHALLENGES_PER_IDENTITY {
+				state.prune_expired_challenges(now);
+				let pending_of_who = state.challenges.keys().filter(|(w, _)| w == who).count();
+				if pending_of_who >= MAX_PENDING_CHALLENGES_PER_IDENTITY {
+					return Err(Error::TooManyPendingChallenges)
+				}
+			}
+		}
+		state.challenges.insert(key, (challenge, now));
+		Ok(())
+	}
+
+	/// Consume the challenge of `request.who` and `request.nonce` and subscribe if the signature
+	/// over it is valid.
+	///
+	/// Returns the subscription hash, under which the connection must be watched.
+	pub fn subscribe(
+		&self,
+		shard: ShardIdentifier,
+		request: &IdGraphSubscriptionRequest,
+		key: RequestAesKey,
+		id_graph_hash: Option<H256>,
+	) -> Result<H256, Error> {
+		let now = Instant::now();
+		let mut state = self.state.lock().unwrap();
+		// a challenge can only be used once, whatever the outcome
+		let (challenge, issued) = state
+			.challenges
+			.remove(&(request.who.clone(), request.nonce))
+			.ok_or(Error::ChallengeNotFound)?;
+		if challenge != request.challenge {
+			return Err(Error::ChallengeNotFound)
+		}
+		if now.saturating_duration_since(issued) >= CHALLENGE_LIFETIME {
+			return Err(Error::ChallengeExpired)
+		}
+		if !request.verify_signature() {
+			return Err(Error::InvalidSignature)
+		}
+
+		let replaces_own = state
+			.subscriptions
+			.get(&request.who)
+			.map_or(false, |s| s.len() >= MAX_SUBSCRIPTIONS_PER_IDENTITY);
+		if !replaces_own && state.subscription_count() >= MAX_SUBSCRIPTIONS {
+			state.prune_expired_subscriptions(now);
+			if state.subscription_count() >= MAX_SUBSCRIPTIONS {
+				return Err(Error::TooManySubscriptions)
+			}
+		}
+
+		let hash = H256::from(blake2_256(&request.encode()));
+		let subscriptions = state.subscriptions.entry(request.who.clone()).or_default();
+		if subscriptions.len() >= MAX_SUBSCRIPTIONS_PER_IDENTITY {
+			let dropped = subscriptions.remove(0);
+			debug!("Dropping oldest ID graph subscription {:?}", dropped.hash);
+		}
+		subscriptions.push(Subscription { hash, shard, key, id_graph_hash, subscribed_at: now });
+		Ok(hash)
+	}
+
+	/// Remove the subscription with the given hash if it was made with `key`, returns whether
+	/// it existed.
+	pub fn unsubscribe(&self, hash: &H256, key: &RequestAesKey) -> bool {
+		let mut state = self.state.lock().unwrap();
+		state.remove_subscriptions(|s| &s.hash == hash && &s.key == key)
+	}
+
+	/// Remove the subscriptions watched by a closed connection.
+	pub fn connection_closed(&self, hashes: &[H256]) {
+		let mut state = self.state.lock().unwrap();
+		if state.remove_subscriptions(|s| hashes.contains(&s.hash)) {
+			debug!("Removed ID graph subscriptions of a closed connection");
+		}
+	}
+
+	/// All subscribed identities, together with the shard of their subscription.
+	///
+	/// Expired subscriptions are removed on the way.
+	pub fn subscribers(&self) -> Vec<(ShardIdentifier, Identity)> {
+		self.subscribers_at(Instant::now())
+	}
+
+	fn subscribers_at(&self, now: Instant) -> Vec<(ShardIdentifier, Identity)> {
+		let mut state = self.state.lock().unwrap();
+		state.prune_expired_subscriptions(now);
+		let mut subscribers: Vec<(ShardIdentifier, Identity)> = state
+			.subscriptions
+			.iter()
+			.flat_map(|(who, subscriptions)| subscriptions.iter().map(|s| (s.shard, who.clone())))
+			.collect();
+		subscribers.sort();
+		subscribers.dedup();
+		subscribers
+	}
+
+	/// Record the current ID graph hash of `who`, notifying the subscriptions that saw a different one.
+	pub fn id_graph_updated(
+		&self,
+		shard: &ShardIdentifier,
+		who: &Identity,
+		id_graph_hash: Option<H256>,
+	) -> Vec<Notification> {
+		let notification = IdGraphNotification::IdGraphChanged { id_graph_hash }.encode();
+		let mut state = self.state.lock().unwrap();
+		state
+			.subscriptions
+			.get_mut(who)
+			.map(|subscriptions| {
+				subscriptions
+					.iter_mut()
+					.filter(|s| &s.shard == shard && s.id_graph_hash != id_graph_hash)
+					.map(|s| {
+						s.id_graph_hash = id_graph_hash;
+						(s.hash, aes_encrypt_default(&s.key, &notification).encode())
+					})
+					.collect()
+			})
+			.unwrap_or_default()
+	}
+
+	/// Notify the subscriptions of `who` about an issued VC.
+	pub fn vc_issued(
+		&self,
+		shard: &ShardIdentifier,
+		who: &Identity,
+		assertion: &Assertion,
+		id_graph_hash: H256,
+	) -> Vec<Notification> {
+		let notification =
+			IdGraphNotification::VcIssued { assertion: assertion.clone(), id_graph_hash }.encode();
+		let state = self.state.lock().unwrap();
+		state
+			.subscriptions
+			.get(who)
+			.map(|subscriptions| {
+				subscriptions
+					.iter()
+					.filter(|s| &s.shard == shard)
+					.map(|s| (s.hash, aes_encrypt_default(&s.key, &notification).encode()))
+					.collect()
+			})
+			.unwrap_or_default()
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use litentry_primitives::{aes_decrypt, AesOutput};
+	use sp_core::{ed25519, Pair};
+
+	const NONCE: H256 = H256::repeat_byte(9);
+
+	fn signed_request(pair: &ed25519::Pair, challenge: H256) -> IdGraphSubscriptionRequest {
+		IdGraphSubscriptionRequest {
+			who: Identity::Substrate(pair.public().into()),
+			nonce: NONCE,
+			challenge,
+			signature: LitentryMultiSignature::Ed25519(
+				pair.sign(&IdGraphSubscriptionRequest::message(&NONCE, &challenge)),
+			),
+		}
+	}
+
+	fn subscribe(
+		subscriptions: &IdGraphSubscriptions,
+		pair: &ed25519::Pair,
+		key: RequestAesKey,
+	) -> H256 {
+		let who = Identity::Substrate(pair.public().into());
+		let challenge = subscriptions.new_challenge(&who, NONCE).unwrap();
+		subscriptions
+			.subscribe(ShardIdentifier::default(), &signed_request(pair, challenge), key, None)
+			.unwrap()
+	}
+
+	fn decrypt(key: &RequestAesKey, notification: &Notification) -> IdGraphNotification {
+		let mut output = AesOutput::decode(&mut notification.1.as_slice()).unwrap();
+		let decrypted = aes_decrypt(key, &mut output).unwrap();
+		IdGraphNotification::decode(&mut decrypted.as_slice()).unwrap()
+	}
+
+	#[test]
+	fn subscribe_requires_signed_challenge_once() {
+		let subscriptions = IdGraphSubscriptions::default();
+		let pair = ed25519::Pair::from_seed(&[1u8; 32]);
+		let who = Identity::Substrate(pair.public().into());
+		let shard = ShardIdentifier::default();
+
+		// given
+		let challenge = subscriptions.new_challenge(&who, NONCE).unwrap();
+		let mut forged = signed_request(&ed25519::Pair::from_seed(&[2u8; 32]), challenge);
+		forged.who = who.clone();
+
+		// when / then
+		assert_eq!(
+			subscriptions.subscribe(shard, &forged, [0u8; 32], None),
+			Err(Error::InvalidSignature)
+		);
+		// the challenge is consumed by the failed attempt
+		assert_eq!(
+			subscriptions.subscribe(shard, &signed_request(&pair, challenge), [0u8; 32], None),
+			Err(Error::ChallengeNotFound)
+		);
+
+		let challenge = subscriptions.new_challenge(&who, NONCE).unwrap();
+		let request = signed_request(&pair, challenge);
+		assert!(subscriptions.subscribe(shard, &request, [0u8; 32], None).is_ok());
+		// replaying the same request is rejected
+		assert_eq!(
+			subscriptions.subscribe(shard, &request, [0u8; 32], None),
+			Err(Error::ChallengeNotFound)
+		);
+		assert_eq!(subscriptions.subscribers(), vec![(shard, who)]);
+	}
+
+	#[test]
+	fn expired_challenge_is_rejected() {
+		let subscriptions = IdGraphSubscriptions::default();
+		let pair = ed25519::Pair::from_seed(&[1u8; 32]);
+		let who = Identity::Substrate(pair.public().into());
+		let challenge = H256::repeat_byte(7);
+
+		// given
+		let issued = Instant::now() - CHALLENGE_LIFETIME;
+		subscriptions.insert_challenge(&who, NONCE, challenge, issued).unwrap();
+
+		// when
+		let result = subscriptions.subscribe(
+			ShardIdentifier::default(),
+			&signed_request(&pair, challenge),
+			[0u8; 32],
+			None,
+		);
+
+		// then
+		assert_eq!(result, Err(Error::ChallengeExpired));
+	}
+
+	#[test]
+	fn notifications_are_encrypted_and_only_sent_on_change() {
+		let subscriptions = IdGraphSubscriptions::default();
+		let pair = ed25519::Pair::from_seed(&[1u8; 32]);
+		let who = Identity::Substrate(pair.public().into());
+		let shard = ShardIdentifier::default();
+		let key = [3u8; 32];
+		let current = Some(H256::repeat_byte(1));
+
+		// given
+		let challenge = subscriptions.new_challenge(&who, NONCE).unwrap();
+		let hash = subscriptions
+			.subscribe(shard, &signed_request(&pair, challenge), key, current)
+			.unwrap();
+
+		// when / then
+		assert!(subscriptions.id_graph_updated(&shard, &who, current).is_empty());
+
+		let changed = Some(H256::repeat_byte(2));
+		let notifications = subscriptions.id_graph_updated(&shard, &who, changed);
+		assert_eq!(notifications.len(), 1);
+		assert_eq!(notifications[0].0, hash);
+		assert_eq!(
+			decrypt(&key, &notifications[0]),
+			IdGraphNotification::IdGraphChanged { id_graph_hash: changed }
+		);
+		assert!(subscriptions.id_graph_updated(&shard, &who, changed).is_empty());
+
+		let notifications =
+			subscriptions.vc_issued(&shard, &who, &Assertion::A1, H256::repeat_byte(2));
+		assert_eq!(
+			decrypt(&key, &notifications[0]),
+			IdGraphNotification::VcIssued {
+				assertion: Assertion::A1,
+				id_graph_hash: H256::repeat_byte(2)
+			}
+		);
+
+		assert!(!subscriptions.unsubscribe(&hash, &[4u8; 32]));
+		assert!(subscriptions.unsubscribe(&hash, &key));
+		assert!(subscriptions.subscribers().is_empty());
+		assert!(subscriptions.vc_issued(&shard, &who, &Assertion::A1, H256::zero()).is_empty());
+	}
+
+	#[test]
+	fn challenges_of_different_nonces_are_kept_apart_and_limited() {
+		let subscriptions = IdGraphSubscriptions::default();
+		let pair = ed25519::Pair::from_seed(&[1u8; 32]);
+		let who = Identity::Substrate(pair.public().into());
+
+		// given
+		let challenge = subscriptions.new_challenge(&who, NONCE).unwrap();
+		for i in 1..MAX_PENDING_CHALLENGES_PER_IDENTITY {
+			subscriptions.new_challenge(&who, H256::repeat_byte(i as u8)).unwrap();
+		}
+
+		// when / then
+		assert_eq!(
+			subscriptions.new_challenge(&who, H256::repeat_byte(0xff)),
+			Err(Error::TooManyPendingChallenges)
+		);
+		// the challenges of other nonces did not replace the one of `NONCE`
+		assert!(subscriptions
+			.subscribe(
+				ShardIdentifier::default(),
+				&signed_request(&pair, challenge),
+				[0u8; 32],
+				None
+			)
+			.is_ok());
+		assert!(subscriptions.new_challenge(&who, H256::repeat_byte(0xff)).is_ok());
+	}
+
+	#[test]
+	fn subscriptions_of_closed_connection_are_removed() {
+		let subscriptions = IdGraphSubscriptions::default();
+		let first = subscribe(&subscriptions, &ed25519::Pair::from_seed(&[1u8; 32]), [0u8; 32]);
+		let second = subscribe(&subscriptions, &ed25519::Pair::from_seed(&[2u8; 32]), [0u8; 32]);
+
+		// when
+		subscriptions.connection_closed(&[first]);
+
+		// then
+		assert_eq!(subscriptions.subscribers().len(), 1);
+		assert!(!subscriptions.unsubscribe(&first, &[0u8; 32]));
+		assert!(subscriptions.unsubscribe(&second, &[0u8; 32]));
+	}
+
+	#[test]
+	fn expired_subscriptions_are_pruned() {
+		let subscriptions = IdGraphSubscriptions::default();
+
+		// given
+		subscribe(&subscriptions, &ed25519::Pair::from_seed(&[1u8; 32]), [0u8; 32]);
+
+		// when / then
+		assert_eq!(subscriptions.subscribers().len(), 1);
+		assert!(subscriptions.subscribers_at(Instant::now() + SUBSCRIPTION_LIFETIME).is_empty());
+		assert!(subscriptions.subscribers().is_empty());
+	}
+}
@@ -42,7 +42,10 @@ use ita_stf::{Getter, TrustedCall, TrustedCallSigned};
 use itp_extrinsics_factory::CreateExtrinsics;
 use itp_node_api::metadata::{provider::AccessNodeMetadata, NodeMetadataTrait};
 use itp_ocall_api::{EnclaveAttestationOCallApi, EnclaveMetricsOCallApi, EnclaveOnChainOCallApi};
-use itp_sgx_crypto::{key_repository::AccessKey, ShieldingCryptoDecrypt, ShieldingCryptoEncrypt};
+use itp_sgx_crypto::{
+	key_repository::{AccessKey, RotatedShieldingKey},
+	ShieldingCryptoDecrypt, ShieldingCryptoEncrypt,
+};
 use itp_stf_executor::traits::StfEnclaveSigning as StfEnclaveSigningTrait;
 use itp_stf_primitives::{traits::TrustedCallVerification, types::TrustedOperation};
 use itp_top_pool_author::traits::AuthorApi as AuthorApiTrait;
@@ -209,7 +212,7 @@ where
 	NodeMetadataRepo::MetadataType: NodeMetadataTrait,
 {
 	let connection_hash = request.using_encoded(|x| H256::from(blake2_256(x)));
-	let enclave_shielding_key = match context.shielding_key.retrieve_key() {
+	let enclave_shielding_key = match RotatedShieldingKey::retrieve(&*context.shielding_key) {
 		Ok(value) => value,
 		Err(e) => {
 			let res: Result<(), NativeTaskError> =
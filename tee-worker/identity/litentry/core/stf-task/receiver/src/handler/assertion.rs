@@ -24,7 +24,7 @@ use itp_stf_executor::traits::StfEnclaveSigning;
 use itp_stf_state_handler::handle_state::HandleState;
 use itp_top_pool_author::traits::AuthorApi;
 use itp_utils::stringify::account_id_to_string;
-use lc_credentials::credential_schema;
+use lc_credentials::{credential_schema, Credential};
 use lc_data_providers::DataProviderConfig;
 use lc_dynamic_assertion::AssertionLogicRepository;
 use lc_evm_dynamic_assertions::AssertionRepositoryItem;
@@ -47,7 +47,7 @@ fn build_holding_time(
 	htype: AmountHoldingTimeType,
 	min_balance: ParameterString,
 	data_provider_config: &DataProviderConfig,
-) -> Result<lc_credentials::Credential, VCMPError> {
+) -> Result<Credential, VCMPError> {
 	lc_assertion_build::holding_time::build(req, htype, min_balance, data_provider_config)
 }
 
@@ -62,6 +62,28 @@ pub fn create_credential_str<
 	req: &AssertionBuildRequest,
 	context: &Arc<StfTaskContext<ShieldingKeyRepository, A, S, H, O, AR>>,
 ) -> Result<(Vec<u8>, Option<Vec<u8>>), VCMPError>
+where
+	ShieldingKeyRepository: AccessKey,
+	<ShieldingKeyRepository as AccessKey>::KeyType: ShieldingCryptoEncrypt + 'static,
+{
+	let (mut credential, vc_logs) = build_credential(req, context)?;
+	let credential_str = sign_credential(&mut credential, req, context)?;
+	Ok((credential_str, vc_logs))
+}
+
+/// Builds the unsigned credential of the requested assertion, together with the
+/// logs of a dynamic assertion.
+pub fn build_credential<
+	ShieldingKeyRepository,
+	A: AuthorApi<Hash, Hash, TrustedCallSigned, Getter>,
+	S: StfEnclaveSigning<TrustedCallSigned>,
+	H: HandleState,
+	O: EnclaveOnChainOCallApi + EnclaveMetricsOCallApi,
+	AR: AssertionLogicRepository<Id = H160, Item = AssertionRepositoryItem>,
+>(
+	req: &AssertionBuildRequest,
+	context: &Arc<StfTaskContext<ShieldingKeyRepository, A, S, H, O, AR>>,
+) -> Result<(Credential, Option<Vec<u8>>), VCMPError>
 where
 	ShieldingKeyRepository: AccessKey,
 	<ShieldingKeyRepository as AccessKey>::KeyType: ShieldingCryptoEncrypt + 'static,
@@ -210,6 +232,36 @@ where
 		Assertion::LinkedIdentities => lc_assertion_build_v2::linked_identities::build(req),
 	}?;
 
+	// the block numbers the claims were read at, they are kept when the credential is re-issued
+	credential.parachain_block_number = req.parachain_block_number;
+	credential.sidechain_block_number = req.sidechain_block_number;
+
+	Ok((
+		credential,
+		vc_logs.map(|v| {
+			v.iter().flat_map(|s| s.as_bytes().iter().cloned().chain(once(b'\n'))).collect()
+		}),
+	))
+}
+
+/// Post-processes and signs a credential built by `build_credential`, returns the signed
+/// credential as json.
+pub fn sign_credential<
+	ShieldingKeyRepository,
+	A: AuthorApi<Hash, Hash, TrustedCallSigned, Getter>,
+	S: StfEnclaveSigning<TrustedCallSigned>,
+	H: HandleState,
+	O: EnclaveOnChainOCallApi + EnclaveMetricsOCallApi,
+	AR: AssertionLogicRepository<Id = H160, Item = AssertionRepositoryItem>,
+>(
+	credential: &mut Credential,
+	req: &AssertionBuildRequest,
+	context: &Arc<StfTaskContext<ShieldingKeyRepository, A, S, H, O, AR>>,
+) -> Result<Vec<u8>, VCMPError>
+where
+	ShieldingKeyRepository: AccessKey,
+	<ShieldingKeyRepository as AccessKey>::KeyType: ShieldingCryptoEncrypt + 'static,
+{
 	// post-process the credential
 	let enclave_signer_account = context.enclave_signer.get_enclave_account().map_err(|e| {
 		VCMPError::RequestVCFailed(
@@ -218,9 +270,6 @@ where
 		)
 	})?;
 
-	credential.parachain_block_number = req.parachain_block_number;
-	credential.sidechain_block_number = req.sidechain_block_number;
-
 	credential.credential_subject.endpoint =
 		context.data_provider_config.credential_endpoint.to_string();
 
@@ -260,10 +309,5 @@ where
 		.map_err(|_| VCMPError::RequestVCFailed(req.assertion.clone(), ErrorDetail::ParseError))?;
 	debug!("Credential: {}, length: {}", credential_str, credential_str.len());
 
-	Ok((
-		credential_str.as_bytes().to_vec(),
-		vc_logs.map(|v| {
-			v.iter().flat_map(|s| s.as_bytes().iter().cloned().chain(once(b'\n'))).collect()
-		}),
-	))
+	Ok(credential_str.as_bytes().to_vec())
 }
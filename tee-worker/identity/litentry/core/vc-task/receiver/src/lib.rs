@@ -25,7 +25,7 @@ use frame_support::{ensure, sp_runtime::traits::One};
 use futures::executor::ThreadPoolBuilder;
 use ita_sgx_runtime::{
 	pallet_identity_management_tee::get_eligible_identities, BlockNumber, Hash, Runtime,
-	VERSION as SIDECHAIN_VERSION,
+	VcIssuanceRecord, VERSION as SIDECHAIN_VERSION,
 };
 
 #[cfg(feature = "development")]
@@ -43,24 +43,35 @@ use itp_node_api::metadata::{
 	NodeMetadataTrait,
 };
 use itp_ocall_api::{EnclaveAttestationOCallApi, EnclaveMetricsOCallApi, EnclaveOnChainOCallApi};
-use itp_sgx_crypto::{key_repository::AccessKey, ShieldingCryptoDecrypt, ShieldingCryptoEncrypt};
+use itp_sgx_crypto::{
+	key_repository::{AccessKey, RotatedShieldingKey},
+	ShieldingCryptoDecrypt, ShieldingCryptoEncrypt,
+};
 use itp_sgx_externalities::SgxExternalitiesTrait;
 use itp_stf_executor::traits::StfEnclaveSigning;
 use itp_stf_primitives::{traits::TrustedCallVerification, types::TrustedOperation};
 use itp_stf_state_handler::handle_state::HandleState;
 use itp_storage::{storage_map_key, storage_value_key, StorageHasher};
+use itp_time_utils::now_as_millis;
 use itp_top_pool_author::traits::AuthorApi;
 use itp_types::{
 	parentchain::ParentchainId, AccountId, BlockNumber as SidechainBlockNumber, OpaqueCall,
 	ShardIdentifier, H256,
 };
+use itp_utils::FromHexPrefixed;
+use lc_credentials::Credential;
 use lc_dynamic_assertion::AssertionLogicRepository;
 use lc_evm_dynamic_assertions::AssertionRepositoryItem;
-use lc_stf_task_receiver::{handler::assertion::create_credential_str, StfTaskContext};
+use lc_id_graph_subscription::id_graph_subscriptions;
+use lc_stf_task_receiver::{
+	handler::assertion::{build_credential, sign_credential},
+	StfTaskContext,
+};
 use lc_vc_task_sender::init_vc_task_sender;
 use litentry_macros::if_development_or;
 use litentry_primitives::{
-	Assertion, AssertionBuildRequest, DecryptableRequest, Identity, ParentchainBlockNumber,
+	Assertion, AssertionBuildRequest, DecryptableRequest, ErrorDetail, Identity,
+	ParentchainBlockNumber, VCMPError,
 };
 use log::*;
 use pallet_identity_management_tee::{identity_context::sort_id_graph, IdentityContext};
@@ -69,16 +80,19 @@ use std::{
 	boxed::Box,
 	collections::{HashMap, HashSet},
 	format,
-	string::ToString,
+	string::{String, ToString},
 	sync::{
 		mpsc::{channel, Sender},
 		Arc,
 	},
 	thread,
-	time::Instant,
+	time::{Duration, Instant},
 	vec::Vec,
 };
 
+mod vc_cache;
+use vc_cache::{CachedVc, VcCache};
+
 pub fn run_vc_handler_runner<ShieldingKeyRepository, A, S, H, O, Z, N, AR>(
 	context: Arc<StfTaskContext<ShieldingKeyRepository, A, S, H, O, AR>>,
 	extrinsic_factory: Arc<Z>,
@@ -116,11 +130,13 @@ pub fn run_vc_handler_runner<ShieldingKeyRepository, A, S, H, O, Z, N, AR>(
 
 	// use local registry to manage request reponse status
 	let req_registry = RequestRegistry::new();
+	let vc_cache = VcCache::default();
 
 	while let Ok(mut req) = vc_task_receiver.recv() {
+		let skip_cache = req.options.skip_cache;
 		let request = &mut req.request;
 		let connection_hash = request.using_encoded(|x| H256::from(blake2_256(x)));
-		let enclave_shielding_key = match context.shielding_key.retrieve_key() {
+		let enclave_shielding_key = match RotatedShieldingKey::retrieve(&*context.shielding_key) {
 			Ok(value) => value,
 			Err(e) => {
 				send_vc_response(
@@ -181,6 +197,28 @@ pub fn run_vc_handler_runner<ShieldingKeyRepository, A, S, H, O, Z, N, AR>(
 			continue
 		}
 
+		// reserve issuance slots, so that a single identity cannot flood the VC issuance
+		let assertions = match &tcs.call {
+			TrustedCall::request_batch_vc(_, _, assertions, ..) => assertions.len() as u32,
+			_ => 1,
+		};
+		// one permit per assertion, each is released as soon as its assertion is processed
+		let mut permits =
+			match context.author_api.admit_vc_request(tcs.sender_identity(), assertions) {
+				Ok(permit) => permit.split().into_iter(),
+				Err(e) => {
+					send_vc_response(
+						connection_hash,
+						context.clone(),
+						Err(RequestVcErrorDetail::RateLimited(e.to_string())),
+						0u8,
+						0u8,
+						false,
+					);
+					continue
+				},
+			};
+
 		// Until now, preparation work is done. If any error happens, error message would have been returned already.
 
 		if let TrustedCall::request_vc(..) = tcs.call {
@@ -192,6 +230,8 @@ pub fn run_vc_handler_runner<ShieldingKeyRepository, A, S, H, O, Z, N, AR>(
 			let node_metadata_repo_pool = node_metadata_repo.clone();
 			let tc_sender_pool = tc_sender.clone();
 			let req_registry_pool = req_registry.clone();
+			let vc_cache_pool = vc_cache.clone();
+			let permit_pool = permits.next();
 			pool.spawn_ok(async move {
 				let response = process_single_request(
 					shard_pool,
@@ -200,6 +240,7 @@ pub fn run_vc_handler_runner<ShieldingKeyRepository, A, S, H, O, Z, N, AR>(
 					node_metadata_repo_pool,
 					tc_sender_pool,
 					tcs.call.clone(),
+					(!skip_cache).then_some(&vc_cache_pool),
 				);
 
 				// Totally fine to `unwrap` here. Because new item was just added above.
@@ -219,6 +260,8 @@ pub fn run_vc_handler_runner<ShieldingKeyRepository, A, S, H, O, Z, N, AR>(
 						);
 					},
 				}
+				// release the issuance slot
+				drop(permit_pool);
 			});
 		} else if let TrustedCall::request_batch_vc(
 			signer,
@@ -243,6 +286,8 @@ pub fn run_vc_handler_runner<ShieldingKeyRepository, A, S, H, O, Z, N, AR>(
 			let assertion_len = unique_assertions.len() as u8;
 			req_registry.add_new_item(connection_hash, assertion_len);
 			for (idx, assertion) in unique_assertions.iter().enumerate() {
+				// the slot of a duplicate assertion is released at the end of the iteration
+				let permit_pool = permits.next();
 				if let Some(assertion) = assertion {
 					let new_call = TrustedCall::request_vc(
 						signer.clone(),
@@ -258,6 +303,7 @@ pub fn run_vc_handler_runner<ShieldingKeyRepository, A, S, H, O, Z, N, AR>(
 					let node_metadata_repo_pool = node_metadata_repo.clone();
 					let tc_sender_pool = tc_sender.clone();
 					let req_registry_pool = req_registry.clone();
+					let vc_cache_pool = vc_cache.clone();
 
 					pool.spawn_ok(async move {
 						let response = process_single_request(
@@ -267,6 +313,7 @@ pub fn run_vc_handler_runner<ShieldingKeyRepository, A, S, H, O, Z, N, AR>(
 							node_metadata_repo_pool,
 							tc_sender_pool,
 							new_call,
+							(!skip_cache).then_some(&vc_cache_pool),
 						);
 
 						// Totally fine to `unwrap` here. Because new item was just added above.
@@ -295,6 +342,8 @@ pub fn run_vc_handler_runner<ShieldingKeyRepository, A, S, H, O, Z, N, AR>(
 								);
 							},
 						}
+						// release the issuance slot of this assertion
+						drop(permit_pool);
 					});
 				} else {
 					// Totally fine to `unwrap` here. Because new item was just added above.
@@ -381,6 +430,22 @@ impl RequestRegistry {
 	}
 }
 
+// surface dynamic assertion policy violations as dedicated errors, so that assertion
+// developers can tell them apart from generic build failures
+fn into_request_vc_error_detail(e: VCMPError) -> RequestVcErrorDetail {
+	match e {
+		VCMPError::RequestVCFailed(_, ErrorDetail::AssertionLimitExceeded(reason)) =>
+			RequestVcErrorDetail::AssertionLimitExceeded(
+				String::from_utf8_lossy(&reason).into_owned(),
+			),
+		VCMPError::RequestVCFailed(_, ErrorDetail::AssertionDomainNotAllowed(reason)) =>
+			RequestVcErrorDetail::AssertionDomainNotAllowed(
+				String::from_utf8_lossy(&reason).into_owned(),
+			),
+		e => RequestVcErrorDetail::AssertionBuildFailed(Box::new(e)),
+	}
+}
+
 fn send_vc_response<ShieldingKeyRepository, A, S, H, O, AR>(
 	hash: H256,
 	context: Arc<StfTaskContext<ShieldingKeyRepository, A, S, H, O, AR>>,
@@ -412,6 +477,23 @@ fn send_vc_response<ShieldingKeyRepository, A, S, H, O, AR>(
 	}
 }
 
+fn vc_issuance_record(
+	credential: &Credential,
+	assertion: &Assertion,
+	id_graph_hash: H256,
+) -> Result<VcIssuanceRecord, String> {
+	let vc_id = H256::from_hex(&credential.id).map_err(|e| format!("{:?}", e))?;
+
+	Ok(VcIssuanceRecord {
+		vc_id,
+		assertion: assertion.clone(),
+		issued_at: now_as_millis(),
+		id_graph_hash,
+		// VCs issued by the worker don't carry an expiration date
+		expires_at: None,
+	})
+}
+
 fn process_single_request<ShieldingKeyRepository, A, S, H, O, Z, N, AR>(
 	shard: H256,
 	context: Arc<StfTaskContext<ShieldingKeyRepository, A, S, H, O, AR>>,
@@ -419,6 +501,7 @@ fn process_single_request<ShieldingKeyRepository, A, S, H, O, Z, N, AR>(
 	node_metadata_repo: Arc<N>,
 	tc_sender: Sender<(ShardIdentifier, TrustedCall)>,
 	call: TrustedCall,
+	vc_cache: Option<&VcCache>,
 ) -> Result<Vec<u8>, RequestVcErrorDetail>
 where
 	ShieldingKeyRepository: AccessKey + core::marker::Send + core::marker::Sync,
@@ -556,6 +639,12 @@ where
 			),
 		}
 
+		// a cached VC is only served for the very same IDGraph, any IDGraph mutation
+		// changes `id_graph_hash` and thus invalidates the cached VCs
+		let vc_cache = vc_cache.and_then(|c| assertion.vc_cache_ttl().map(|ttl| (c, ttl)));
+		let vc_cache_key = VcCache::key(&assertion, &identities);
+		let cached_vc = vc_cache.and_then(|(c, _)| c.get(&who, id_graph_hash, &vc_cache_key));
+
 		let req = AssertionBuildRequest {
 			shard,
 			signer: signer_account,
@@ -572,22 +661,32 @@ where
 			req_ext_hash,
 		};
 
-		let (vc_payload, vc_logs) = create_credential_str(&req, &context)
-			.map_err(|e| RequestVcErrorDetail::AssertionBuildFailed(Box::new(e)))?;
-
-		let call_index = node_metadata_repo
-			.get_from_metadata(|m| m.vc_issued_call_indexes())
-			.map_err(|e| RequestVcErrorDetail::MetadataRetrievalFailed(e.to_string()))?
-			.map_err(|e| RequestVcErrorDetail::InvalidMetadata(format!("{:?}", e)))?;
+		let (mut credential, vc_logs) = match cached_vc {
+			Some(CachedVc { mut credential, vc_logs }) => {
+				debug!("Serving cached vc for {}", who.to_did().unwrap_or_default());
+				// a cached VC is never handed out twice, it's re-issued as a new VC
+				credential.reissue();
+				(credential, vc_logs)
+			},
+			None => {
+				let (credential, vc_logs) =
+					build_credential(&req, &context).map_err(into_request_vc_error_detail)?;
+				if let Some((c, ttl)) = vc_cache {
+					c.insert(
+						&who,
+						id_graph_hash,
+						vc_cache_key,
+						CachedVc { credential: credential.clone(), vc_logs: vc_logs.clone() },
+						Duration::from_secs(ttl),
+					);
+				}
+				(credential, vc_logs)
+			},
+		};
+		let vc_payload = sign_credential(&mut credential, &req, &context)
+			.map_err(into_request_vc_error_detail)?;
 
 		let key = maybe_key.ok_or(RequestVcErrorDetail::MissingAesKey)?;
-		let call = OpaqueCall::from_tuple(&(
-			call_index,
-			who.clone(),
-			assertion.clone(),
-			id_graph_hash,
-			req_ext_hash,
-		));
 
 		let mutated_id_graph = if should_create_id_graph { id_graph } else { Default::default() };
 
@@ -603,11 +702,35 @@ where
 			.enclave_signer
 			.get_enclave_account()
 			.map_err(|_| RequestVcErrorDetail::EnclaveSignerRetrievalFailed)?;
-		let c = TrustedCall::maybe_create_id_graph(enclave_signer.into(), who.clone());
+		let c = TrustedCall::maybe_create_id_graph(enclave_signer.clone().into(), who.clone());
 		tc_sender
 			.send((shard, c))
 			.map_err(|e| RequestVcErrorDetail::TrustedCallSendingFailed(e.to_string()))?;
 
+		// keep a record of the VC in the issuance history of `who`
+		match vc_issuance_record(&credential, &assertion, id_graph_hash) {
+			Ok(record) => {
+				let c = TrustedCall::record_vc_issuance(enclave_signer.into(), who.clone(), record);
+				tc_sender
+					.send((shard, c))
+					.map_err(|e| RequestVcErrorDetail::TrustedCallSendingFailed(e.to_string()))?;
+			},
+			Err(e) => warn!("Could not create vc issuance record: {}", e),
+		}
+
+		// a re-issued cached VC is a new VC as well, the parachain records every issued VC
+		let call_index = node_metadata_repo
+			.get_from_metadata(|m| m.vc_issued_call_indexes())
+			.map_err(|e| RequestVcErrorDetail::MetadataRetrievalFailed(e.to_string()))?
+			.map_err(|e| RequestVcErrorDetail::InvalidMetadata(format!("{:?}", e)))?;
+		let call = OpaqueCall::from_tuple(&(
+			call_index,
+			who.clone(),
+			assertion.clone(),
+			id_graph_hash,
+			req_ext_hash,
+		));
+
 		// this internally fetches nonce from a mutex and then updates it thereby ensuring ordering
 		let xt = extrinsic_factory
 			.create_extrinsics(&[call], None)
@@ -626,6 +749,13 @@ where
 		}
 
 		info!("Vc issued for {}, assertion: {:?}", who.to_did().unwrap_or_default(), assertion);
+
+		// push the issuance to the subscriptions of the ID graph owner
+		for (hash, value) in
+			id_graph_subscriptions().vc_issued(&shard, &who, &assertion, id_graph_hash)
+		{
+			context.author_api.send_rpc_response(hash, value, true);
+		}
 		Ok(res.encode())
 	} else {
 		// Would never come here.
@@ -0,0 +1,218 @@
+// Copyright 2020-2024 Trust Computing GmbH.
+// This file is part of Litentry.
+//
+// Litentry is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// Litentry is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with Litentry.  If not, see <https://www.gnu.org/licenses/>.
+
+#[cfg(feature = "std")]
+use std::sync::Mutex;
+#[cfg(feature = "sgx")]
+use std::sync::SgxMutex as Mutex;
+
+use codec::Encode;
+use itp_types::H256;
+use lc_credentials::Credential;
+use litentry_primitives::{Assertion, Identity, IdentityNetworkTuple};
+use sp_core::blake2_256;
+use std::{
+	collections::{BTreeMap, HashMap},
+	sync::Arc,
+	time::{Duration, Instant},
+	vec::Vec,
+};
+
+// upper bound of identities with cached VCs, so that the enclave memory stays bounded
+pub const VC_CACHE_MAX_IDENTITIES: usize = 10_000;
+
+/// Unsigned VC as returned by `build_credential`, a cache hit is re-issued with a fresh
+/// id and issuance date and signed per request.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct CachedVc {
+	pub credential: Credential,
+	pub vc_logs: Option<Vec<u8>>,
+}
+
+struct CacheEntry {
+	vc: CachedVc,
+	expires_at: Instant,
+}
+
+// all VCs cached for one identity were built against the same IDGraph
+struct IdentityEntries {
+	id_graph_hash: H256,
+	vcs: HashMap<H256, CacheEntry>,
+}
+
+/// In-memory cache of issued VCs, keyed by (IDGraph hash, assertion, networks).
+///
+/// Any mutation of the IDGraph changes its hash, in which case all VCs cached for
+/// the identity are dropped on the next access.
+#[derive(Clone)]
+pub struct VcCache {
+	entries: Arc<Mutex<BTreeMap<Identity, IdentityEntries>>>,
+	max_identities: usize,
+}
+
+impl Default for VcCache {
+	fn default() -> Self {
+		Self::new(VC_CACHE_MAX_IDENTITIES)
+	}
+}
+
+impl VcCache {
+	pub fn new(max_identities: usize) -> Self {
+		Self { entries: Arc::new(Mutex::new(BTreeMap::new())), max_identities }
+	}
+
+	// the networks are part of `identities`, they change with the networks of the assertion
+	// and the networks the user configured for each identity
+	pub fn key(assertion: &Assertion, identities: &[IdentityNetworkTuple]) -> H256 {
+		H256::from(blake2_256(&(assertion, identities).encode()))
+	}
+
+	pub fn get(&self, who: &Identity, id_graph_hash: H256, key: &H256) -> Option<CachedVc> {
+		let mut entries = self.entries.lock().unwrap();
+		let identity_entries = entries.get_mut(who)?;
+		if identity_entries.id_graph_hash != id_graph_hash {
+			entries.remove(who);
+			return None
+		}
+		match identity_entries.vcs.get(key) {
+			Some(entry) if entry.expires_at > Instant::now() => Some(entry.vc.clone()),
+			Some(_) => {
+				identity_entries.vcs.remove(key);
+				None
+			},
+			None => None,
+		}
+	}
+
+	pub fn insert(
+		&self,
+		who: &Identity,
+		id_graph_hash: H256,
+		key: H256,
+		vc: CachedVc,
+		ttl: Duration,
+	) {
+		let now = Instant::now();
+		let mut entries = self.entries.lock().unwrap();
+		if !entries.contains_key(who) && entries.len() >= self.max_identities {
+			entries.retain(|_, identity_entries| {
+				identity_entries.vcs.retain(|_, entry| entry.expires_at > now);
+				!identity_entries.vcs.is_empty()
+			});
+			if entries.len() >= self.max_identities {
+				log::debug!("VC cache is full, skip caching");
+				return
+			}
+		}
+
+		let identity_entries = entries
+			.entry(who.clone())
+			.or_insert_with(|| IdentityEntries { id_graph_hash, vcs: HashMap::new() });
+		if identity_entries.id_graph_hash != id_graph_hash {
+			identity_entries.id_graph_hash = id_graph_hash;
+			identity_entries.vcs.clear();
+		}
+		identity_entries.vcs.insert(key, CacheEntry { vc, expires_at: now + ttl });
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use itp_types::ShardIdentifier;
+	use lc_credentials::IssuerRuntimeVersion;
+	use litentry_primitives::Web3Network;
+	use sp_core::{ed25519, Pair};
+
+	fn who() -> Identity {
+		Identity::Substrate(ed25519::Pair::from_seed(&[1u8; 32]).public().into())
+	}
+
+	fn vc(payload: &[u8]) -> CachedVc {
+		let runtime_version = IssuerRuntimeVersion { parachain: 0u32, sidechain: 0u32 };
+		let credential =
+			Credential::new(&who(), &ShardIdentifier::default(), &runtime_version).unwrap();
+		CachedVc { credential, vc_logs: Some(payload.to_vec()) }
+	}
+
+	#[test]
+	fn returns_cached_vc_for_same_id_graph() {
+		// given
+		let cache = VcCache::default();
+		let key = VcCache::key(&Assertion::A1, &[(who(), vec![Web3Network::Litentry])]);
+		let vc = vc(b"vc");
+		cache.insert(&who(), H256::repeat_byte(1), key, vc.clone(), Duration::from_secs(60));
+
+		// when
+		let cached = cache.get(&who(), H256::repeat_byte(1), &key);
+
+		// then
+		assert_eq!(cached, Some(vc));
+	}
+
+	#[test]
+	fn drops_cached_vcs_when_id_graph_changes() {
+		// given
+		let cache = VcCache::default();
+		let key = VcCache::key(&Assertion::A1, &[]);
+		cache.insert(&who(), H256::repeat_byte(1), key, vc(b"vc"), Duration::from_secs(60));
+
+		// when
+		let cached = cache.get(&who(), H256::repeat_byte(2), &key);
+
+		// then
+		assert_eq!(cached, None);
+		assert_eq!(cache.get(&who(), H256::repeat_byte(1), &key), None);
+	}
+
+	#[test]
+	fn does_not_return_expired_vc() {
+		// given
+		let cache = VcCache::default();
+		let key = VcCache::key(&Assertion::A1, &[]);
+		cache.insert(&who(), H256::repeat_byte(1), key, vc(b"vc"), Duration::ZERO);
+
+		// when
+		let cached = cache.get(&who(), H256::repeat_byte(1), &key);
+
+		// then
+		assert_eq!(cached, None);
+	}
+
+	#[test]
+	fn networks_are_part_of_the_key() {
+		let litentry = VcCache::key(&Assertion::A1, &[(who(), vec![Web3Network::Litentry])]);
+		let polkadot = VcCache::key(&Assertion::A1, &[(who(), vec![Web3Network::Polkadot])]);
+		assert_ne!(litentry, polkadot);
+	}
+
+	#[test]
+	fn skips_caching_when_full() {
+		// given
+		let cache = VcCache::new(1);
+		let other = Identity::Substrate(ed25519::Pair::from_seed(&[2u8; 32]).public().into());
+		let key = VcCache::key(&Assertion::A1, &[]);
+		let vc_of_who = vc(b"vc");
+		cache.insert(&who(), H256::repeat_byte(1), key, vc_of_who.clone(), Duration::from_secs(60));
+
+		// when
+		cache.insert(&other, H256::repeat_byte(1), key, vc(b"other"), Duration::from_secs(60));
+
+		// then
+		assert_eq!(cache.get(&other, H256::repeat_byte(1), &key), None);
+		assert_eq!(cache.get(&who(), H256::repeat_byte(1), &key), Some(vc_of_who));
+	}
+}
@@ -7,6 +7,7 @@ compile_error!("feature \"std\" and feature \"sgx\" cannot be enabled at the sam
 #[cfg(all(not(feature = "std"), feature = "sgx"))]
 extern crate sgx_tstd as std;
 
+use codec::{Decode, Encode};
 use lazy_static::lazy_static;
 use litentry_primitives::AesRequest;
 use log::*;
@@ -26,6 +27,14 @@ use std::{
 #[derive(Debug)]
 pub struct VCRequest {
 	pub request: AesRequest,
+	pub options: RequestVcOptions,
+}
+
+/// Optional second param of `author_requestVc`, hex-encoded
+#[derive(Encode, Decode, Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct RequestVcOptions {
+	// bypass the enclave-side VC cache and always build the VC from scratch
+	pub skip_cache: bool,
 }
 
 pub type VcSender = MpscSender<VCRequest>;
@@ -17,7 +17,8 @@
 
 use clap::ArgMatches;
 use itc_rest_client::rest_client::Url;
-use itp_types::{parentchain::ParentchainId, ShardIdentifier};
+use itp_types::{parentchain::ParentchainId, AdmissionConfig, ShardIdentifier};
+use its_storage::{PruningLimits, RetentionPolicy};
 use parse_duration::parse;
 use serde::{Deserialize, Serialize};
 use std::{
@@ -79,6 +80,10 @@ pub struct Config {
 	pub fail_slot_mode: Option<String>,
 	/// slot number to fail at
 	pub fail_at: u64,
+	/// retention policy of the sidechain block storage
+	pub sidechain_retention_policy: RetentionPolicy,
+	/// rate limits and concurrency caps for trusted calls and VC requests
+	pub admission_config: AdmissionConfig,
 }
 
 #[allow(clippy::too_many_arguments)]
@@ -107,6 +112,8 @@ impl Config {
 		parentchain_start_block: String,
 		fail_slot_mode: Option<String>,
 		fail_at: u64,
+		sidechain_retention_policy: RetentionPolicy,
+		admission_config: AdmissionConfig,
 	) -> Self {
 		Self {
 			litentry_rpc_url,
@@ -132,6 +139,8 @@ impl Config {
 			parentchain_start_block,
 			fail_slot_mode,
 			fail_at,
+			sidechain_retention_policy,
+			admission_config,
 		}
 	}
 
@@ -275,6 +284,8 @@ impl From<&ArgMatches<'_>> for Config {
 			m.value_of("parentchain-start-block").unwrap_or(DEFAULT_PARENTCHAIN_START_BLOCK);
 		let fail_slot_mode = m.value_of("fail-slot-mode").map(|v| v.to_string());
 		let fail_at = m.value_of("fail-at").unwrap_or(DEFAULT_FAIL_AT).parse().unwrap();
+		let sidechain_retention_policy = sidechain_retention_policy(m);
+		let admission_config = admission_config(m);
 		Self::new(
 			m.value_of("node-url").unwrap_or(DEFAULT_NODE_URL).into(),
 			m.value_of("node-port").unwrap_or(DEFAULT_NODE_PORT).into(),
@@ -302,10 +313,68 @@ impl From<&ArgMatches<'_>> for Config {
 			parentchain_start_block.to_string(),
 			fail_slot_mode,
 			fail_at,
+			sidechain_retention_policy,
+			admission_config,
 		)
 	}
 }
 
+fn sidechain_retention_policy(m: &ArgMatches<'_>) -> RetentionPolicy {
+	if m.is_present("sidechain-archive") {
+		return RetentionPolicy::Archive
+	}
+	let limits = PruningLimits {
+		max_blocks: m.value_of("sidechain-keep-blocks").map(|v| {
+			v.parse()
+				.unwrap_or_else(|e| panic!("sidechain-keep-blocks parsing error: {:?}", e))
+		}),
+		max_age: m.value_of("sidechain-max-block-age").map(|v| {
+			parse(v).unwrap_or_else(|e| panic!("sidechain-max-block-age parsing error: {:?}", e))
+		}),
+		max_disk_bytes: m.value_of("sidechain-max-disk-mb").map(|v| {
+			v.parse::<u64>()
+				.unwrap_or_else(|e| panic!("sidechain-max-disk-mb parsing error: {:?}", e))
+				.saturating_mul(1024 * 1024)
+		}),
+	};
+	// keep the default block count limit, unless any limit is given explicitly
+	if limits == PruningLimits::default() {
+		RetentionPolicy::default()
+	} else {
+		RetentionPolicy::Prune(limits)
+	}
+}
+
+fn admission_config(m: &ArgMatches<'_>) -> AdmissionConfig {
+	let default = AdmissionConfig::default();
+	AdmissionConfig {
+		window: m.value_of("call-quota-window").map_or(default.window, |v| {
+			parse(v).unwrap_or_else(|e| panic!("call-quota-window parsing error: {:?}", e))
+		}),
+		max_calls_per_window: m.value_of("max-calls-per-window").map_or(
+			default.max_calls_per_window,
+			|v| {
+				v.parse()
+					.unwrap_or_else(|e| panic!("max-calls-per-window parsing error: {:?}", e))
+			},
+		),
+		max_concurrent_vc_requests_per_identity: m
+			.value_of("max-concurrent-vc-requests-per-identity")
+			.map_or(default.max_concurrent_vc_requests_per_identity, |v| {
+				v.parse().unwrap_or_else(|e| {
+					panic!("max-concurrent-vc-requests-per-identity parsing error: {:?}", e)
+				})
+			}),
+		max_concurrent_vc_requests: m.value_of("max-concurrent-vc-requests").map_or(
+			default.max_concurrent_vc_requests,
+			|v| {
+				v.parse()
+					.unwrap_or_else(|e| panic!("max-concurrent-vc-requests parsing error: {:?}", e))
+			},
+		),
+	}
+}
+
 #[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
 pub struct RunConfig {
 	/// Skip remote attestation. Set this flag if running enclave in SW mode
@@ -433,7 +502,65 @@ mod test {
 		assert_eq!(config.mock_server_port, DEFAULT_MOCK_SERVER_PORT);
 		assert_eq!(config.parentchain_start_block, DEFAULT_PARENTCHAIN_START_BLOCK);
 		assert_matches!(config.fail_slot_mode, Option::None);
-		assert_eq!(config.fail_at, DEFAULT_FAIL_AT.parse::<u64>().unwrap())
+		assert_eq!(config.fail_at, DEFAULT_FAIL_AT.parse::<u64>().unwrap());
+		assert_eq!(config.sidechain_retention_policy, RetentionPolicy::default());
+		assert_eq!(config.admission_config, AdmissionConfig::default());
+	}
+
+	#[test]
+	fn sidechain_archive_mode_is_set_correctly() {
+		let mut args = ArgMatches::default();
+		args.args = HashMap::from([("sidechain-archive", Default::default())]);
+
+		let config = Config::from(&args);
+
+		assert_eq!(config.sidechain_retention_policy, RetentionPolicy::Archive);
+	}
+
+	#[test]
+	fn sidechain_pruning_limits_are_parsed_correctly() {
+		let mut args = ArgMatches::default();
+		args.args = HashMap::from([
+			("sidechain-max-block-age", Default::default()),
+			("sidechain-max-disk-mb", Default::default()),
+		]);
+		// Workaround because MatchedArg is private.
+		args.args.get_mut("sidechain-max-block-age").unwrap().vals = vec!["2h".into()];
+		args.args.get_mut("sidechain-max-disk-mb").unwrap().vals = vec!["512".into()];
+
+		let config = Config::from(&args);
+
+		assert_eq!(
+			config.sidechain_retention_policy,
+			RetentionPolicy::Prune(PruningLimits {
+				max_blocks: None,
+				max_age: Some(Duration::from_secs(2 * 60 * 60)),
+				max_disk_bytes: Some(512 * 1024 * 1024),
+			})
+		);
+	}
+
+	#[test]
+	fn admission_config_is_parsed_correctly() {
+		let mut args = ArgMatches::default();
+		args.args = HashMap::from([
+			("call-quota-window", Default::default()),
+			("max-concurrent-vc-requests", Default::default()),
+		]);
+		// Workaround because MatchedArg is private.
+		args.args.get_mut("call-quota-window").unwrap().vals = vec!["5m".into()];
+		args.args.get_mut("max-concurrent-vc-requests").unwrap().vals = vec!["42".into()];
+
+		let config = Config::from(&args);
+
+		assert_eq!(
+			config.admission_config,
+			AdmissionConfig {
+				window: Duration::from_secs(5 * 60),
+				max_concurrent_vc_requests: 42,
+				..Default::default()
+			}
+		);
 	}
 
 	#[test]
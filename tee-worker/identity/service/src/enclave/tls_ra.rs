@@ -19,7 +19,7 @@ use itp_enclave_api::{
 	remote_attestation::{RemoteAttestation, TlsRemoteAttestation},
 	EnclaveResult,
 };
-use itp_types::ShardIdentifier;
+use itp_types::{ShardIdentifier, SidechainBlockConfirmation};
 use log::*;
 use sgx_types::*;
 use std::{
@@ -75,6 +75,8 @@ pub fn enclave_request_state_provisioning<E: TlsRemoteAttestation + RemoteAttest
 	sign_type: sgx_quote_sign_type_t,
 	addr: &str,
 	shard: &ShardIdentifier,
+	confirmed_block: Option<SidechainBlockConfirmation>,
+	provisioned_payloads: &mut u8,
 	skip_ra: bool,
 ) -> EnclaveResult<()> {
 	info!("[MU-RA-Client] Requesting key provisioning from {}", addr);
@@ -110,6 +112,8 @@ pub fn enclave_request_state_provisioning<E: TlsRemoteAttestation + RemoteAttest
 		quoting_enclave_target_info.as_ref(),
 		quote_size.as_ref(),
 		shard,
+		confirmed_block,
+		provisioned_payloads,
 		skip_ra,
 	)
 }
@@ -63,7 +63,10 @@ use substrate_api_client::{
 use litentry_primitives::extract_tcb_info_from_raw_dcap_quote;
 
 use crate::error::ServiceResult;
-use itp_types::parentchain::{AccountId, Balance, ParentchainId};
+use itp_types::parentchain::{
+	events::{ShieldingKeyRotated, ShieldingKeyRotationRequested},
+	AccountId, Balance, ParentchainId,
+};
 use sp_core::crypto::{AccountId32, Ss58Codec};
 use sp_keyring::AccountKeyring;
 use sp_runtime::MultiSigner;
@@ -225,7 +228,7 @@ pub(crate) fn main() {
 		info!("*** Requesting state from a registered worker \n");
 		let node_api =
 			node_api_factory.create_api().expect("Failed to create parentchain node API");
-		sync_state::sync_state::<_, _, WorkerModeProvider>(
+		sync_state::sync_state(
 			&node_api,
 			&extract_shard(smatches.value_of("shard"), enclave.as_ref()),
 			enclave.as_ref(),
@@ -279,6 +282,8 @@ pub(crate) fn main() {
 				sgx_quote_sign_type_t::SGX_UNLINKABLE_SIGNATURE,
 				&config.mu_ra_url_external(),
 				&shard,
+				None,
+				&mut 0,
 				sub_matches.is_present("skip-ra"),
 			)
 			.unwrap();
@@ -299,6 +304,25 @@ pub(crate) fn main() {
 			node_api_factory.create_api().expect("Failed to create parentchain node API");
 		init_parentchain(&enclave, &node_api, &tee_accountid, ParentchainId::Litentry, &shard);
 		enclave.upload_id_graph();
+	} else if let Some(sub_matches) = matches.subcommand_matches("export-state-backup") {
+		let shard = extract_shard(sub_matches.value_of("shard"), enclave.as_ref());
+		let from_block = sub_matches
+			.value_of("from-block")
+			.map(|b| b.parse().expect("from-block must be a sidechain block number"))
+			.unwrap_or_default();
+		let backup_path = sub_matches.value_of("output").expect("output is required");
+		setup::export_state_backup(
+			enclave.as_ref(),
+			sidechain_blockstorage.as_ref(),
+			&shard,
+			from_block,
+			backup_path,
+		)
+		.expect("Failed to export state backup");
+	} else if let Some(sub_matches) = matches.subcommand_matches("import-state-backup") {
+		let backup_path = sub_matches.value_of("input").expect("input is required");
+		setup::import_state_backup(enclave.as_ref(), sidechain_blockstorage.as_ref(), backup_path)
+			.expect("Failed to import state backup");
 	} else {
 		info!("For options: use --help");
 	}
@@ -318,7 +342,14 @@ fn start_worker<E, T, D, InitializationHandler, WorkerModeProvider>(
 	quote_size: Option<u32>,
 ) where
 	T: GetTokioHandle,
-	E: EnclaveBase + Sidechain + RemoteAttestation + TlsRemoteAttestation + Clone,
+	E: EnclaveBase
+		+ Sidechain
+		+ RemoteAttestation
+		+ TlsRemoteAttestation
+		+ Clone
+		+ Sync
+		+ Send
+		+ 'static,
 	D: BlockPruner + FetchBlocks<SignedSidechainBlock> + Sync + Send + 'static,
 	InitializationHandler: TrackInitialization + IsInitialized + Sync + Send + 'static,
 	WorkerModeProvider: ProvideWorkerMode,
@@ -561,12 +592,7 @@ fn start_worker<E, T, D, InitializationHandler, WorkerModeProvider>(
 					if first_run {
 						// obtain provisioning from last active worker as this hasn't been done before
 						info!("my state doesn't know the creation header of the shard. will request provisioning");
-						sync_state::sync_state::<_, _, WorkerModeProvider>(
-							&litentry_rpc_api,
-							&shard,
-							enclave.as_ref(),
-							skip_ra,
-						);
+						sync_state::sync_state(&litentry_rpc_api, shard, enclave.as_ref(), skip_ra);
 
 						info!("re-register the enclave to update the keys after provisioning");
 						register_enclave(
@@ -662,6 +688,16 @@ fn start_worker<E, T, D, InitializationHandler, WorkerModeProvider>(
 		},
 	}
 
+	spawn_shielding_key_rotation_listener(
+		shard,
+		litentry_rpc_api.clone(),
+		enclave.clone(),
+		tee_accountid.clone(),
+		trusted_url.clone(),
+		skip_ra,
+		is_development_mode,
+	);
+
 	let maybe_target_a_rpc_api = if let Some(url) = config.target_a_parentchain_rpc_endpoint() {
 		Some(init_target_parentchain(
 			&enclave,
@@ -695,6 +731,7 @@ fn start_worker<E, T, D, InitializationHandler, WorkerModeProvider>(
 			sidechain_storage,
 			config.clone().fail_slot_mode,
 			config.fail_at,
+			config.sidechain_retention_policy.clone(),
 		)
 		.unwrap();
 	}
@@ -844,6 +881,91 @@ fn spawn_worker_for_shard_polling<InitializationHandler>(
 	});
 }
 
+/// Rotate the shielding key when requested on the parentchain.
+///
+/// Only the primary worker generates a new key, the other workers obtain it from the
+/// primary once it's published and re-register to publish it for themselves.
+fn spawn_shielding_key_rotation_listener<E>(
+	shard: &ShardIdentifier,
+	node_api: ParentchainApi,
+	enclave: Arc<E>,
+	tee_account: AccountId32,
+	trusted_url: String,
+	skip_ra: bool,
+	is_development_mode: bool,
+) where
+	E: EnclaveBase
+		+ Sidechain
+		+ RemoteAttestation
+		+ TlsRemoteAttestation
+		+ Clone
+		+ Sync
+		+ Send
+		+ 'static,
+{
+	let shard = *shard;
+	thread::Builder::new()
+		.name("shielding_key_rotation_listener".to_owned())
+		.spawn(move || {
+			let mut subscription = match node_api.subscribe_events() {
+				Ok(subscription) => subscription,
+				Err(e) => {
+					error!("Failed to subscribe to events for shielding key rotations: {:?}", e);
+					return
+				},
+			};
+			let primary_enclave = || {
+				node_api
+					.primary_enclave_identifier_for_shard(WorkerType::Identity, &shard, None)
+					.ok()
+					.flatten()
+			};
+			while let Some(Ok(events)) = subscription.next_events_from_metadata() {
+				for event in events.iter().flatten() {
+					if let Ok(Some(ev)) = event.as_event::<ShieldingKeyRotationRequested>() {
+						if ev.worker_type != WorkerType::Identity
+							|| primary_enclave().as_ref() != Some(&tee_account)
+						{
+							continue
+						}
+						info!("{}", ev);
+						match enclave.rotate_shielding_key(ev.grace_period_secs) {
+							Ok(()) => info!("Rotated shielding key"),
+							Err(e) => error!("Failed to rotate shielding key: {:?}", e),
+						}
+					} else if let Ok(Some(ev)) = event.as_event::<ShieldingKeyRotated>() {
+						if ev.who == tee_account || primary_enclave().as_ref() != Some(&ev.who) {
+							continue
+						}
+						info!("{}", ev);
+						if let Err(e) = sync_state::sync_rotated_shielding_key(
+							&node_api,
+							&ev.who,
+							&shard,
+							enclave.as_ref(),
+							skip_ra,
+						) {
+							error!("Failed to obtain rotated shielding key: {:?}", e);
+							continue
+						}
+						if let Err(e) = register_enclave(
+							enclave.clone(),
+							&node_api,
+							&tee_account,
+							&trusted_url,
+							skip_ra,
+							is_development_mode,
+						) {
+							error!("Failed to register rotated shielding key: {:?}", e);
+						}
+					}
+				}
+			}
+			error!("Parentchain event subscription for shielding key rotations terminated");
+		})
+		.unwrap();
+}
+
 #[cfg(feature = "attesteer")]
 fn fetch_marblerun_events_every_hour<E>(
 	api: ParentchainApi,
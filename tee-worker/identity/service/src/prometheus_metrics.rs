@@ -33,6 +33,7 @@ use itc_rest_client::{
 	RestGet, RestPath,
 };
 use itp_enclave_metrics::EnclaveMetric;
+use its_storage::RetentionStatus;
 use lazy_static::lazy_static;
 use litentry_primitives::{Assertion, Identity, RequestType};
 use log::*;
@@ -105,6 +106,21 @@ lazy_static! {
 	static ref DYNAMIC_ASSERTION_GET_TIME: Histogram =
 		register_histogram!("litentry_worker_dynamic_assertion_get_time", "Time taken to get a dynamic assertion")
 			.unwrap();
+	static ref SIDECHAIN_STORAGE_ARCHIVE_MODE: IntGauge =
+		register_int_gauge!("litentry_worker_sidechain_storage_archive_mode", "Whether the sidechain storage is never pruned (1) or not (0)")
+			.unwrap();
+	static ref SIDECHAIN_STORAGE_STORED_BLOCKS: IntGauge =
+		register_int_gauge!("litentry_worker_sidechain_storage_stored_blocks", "Number of sidechain blocks in storage")
+			.unwrap();
+	static ref SIDECHAIN_STORAGE_PRUNED_BLOCKS: IntGauge =
+		register_int_gauge!("litentry_worker_sidechain_storage_pruned_blocks", "Number of sidechain blocks pruned in the last pruning run")
+			.unwrap();
+	static ref SIDECHAIN_STORAGE_OLDEST_BLOCK_TIMESTAMP: IntGauge =
+		register_int_gauge!("litentry_worker_sidechain_storage_oldest_block_timestamp", "Timestamp (ms) of the oldest sidechain block in storage")
+			.unwrap();
+	static ref SIDECHAIN_STORAGE_DISK_USAGE: IntGauge =
+		register_int_gauge!("litentry_worker_sidechain_storage_disk_usage_bytes", "Estimated disk usage of the sidechain storage")
+			.unwrap();
 }
 
 pub async fn start_metrics_server<MetricsHandler>(
@@ -266,6 +282,16 @@ impl ReceiveEnclaveMetrics for EnclaveMetricsReceiver {
 	}
 }
 
+/// Update the sidechain storage retention metrics, reported by the pruning loop.
+pub fn set_sidechain_retention_status(status: &RetentionStatus) {
+	SIDECHAIN_STORAGE_ARCHIVE_MODE.set(status.archive as i64);
+	SIDECHAIN_STORAGE_STORED_BLOCKS.set(status.stored_blocks as i64);
+	SIDECHAIN_STORAGE_PRUNED_BLOCKS.set(status.pruned_blocks as i64);
+	SIDECHAIN_STORAGE_OLDEST_BLOCK_TIMESTAMP
+		.set(status.oldest_block_timestamp.unwrap_or_default() as i64);
+	SIDECHAIN_STORAGE_DISK_USAGE.set(status.disk_usage_bytes as i64);
+}
+
 // Function to increment STF calls with labels
 fn inc_stf_calls(category: &str, label: &str) {
 	ENCLAVE_STF_TASKS.with_label_values(&[category, label]).inc();
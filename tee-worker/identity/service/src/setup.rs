@@ -26,14 +26,15 @@ use std::{fs, path::Path};
 
 #[cfg(feature = "link-binary")]
 pub(crate) use needs_enclave::{
-	generate_shielding_key_file, generate_signing_key_file, init_shard, initialize_shard_and_keys,
-	migrate_shard,
+	export_state_backup, generate_shielding_key_file, generate_signing_key_file,
+	import_state_backup, init_shard, initialize_shard_and_keys, migrate_shard,
 };
 
 #[cfg(feature = "link-binary")]
 mod needs_enclave {
 	use crate::error::{Error, ServiceResult};
-	use codec::Encode;
+	use base58::ToBase58;
+	use codec::{Decode, Encode};
 	use itp_enclave_api::{enclave_base::EnclaveBase, Enclave};
 	use itp_settings::files::{
 		LITENTRY_PARENTCHAIN_LIGHT_CLIENT_DB_PATH, SHARDS_PATH, SHIELDING_KEY_FILE,
@@ -41,6 +42,10 @@ mod needs_enclave {
 		TARGET_B_PARENTCHAIN_LIGHT_CLIENT_DB_PATH,
 	};
 	use itp_types::ShardIdentifier;
+	use its_primitives::types::{
+		state_backup::StateBackupBundle, BlockNumber, SignedBlock as SignedSidechainBlock,
+	};
+	use its_storage::{interface::FetchBlocks, BlockStorage};
 	use log::*;
 	use std::{fs, fs::File, path::Path};
 
@@ -60,8 +65,6 @@ mod needs_enclave {
 	}
 
 	pub(crate) fn init_shard(enclave: &Enclave, shard_identifier: &ShardIdentifier) {
-		use base58::ToBase58;
-
 		match enclave.init_shard(shard_identifier.encode()) {
 			Err(e) => {
 				println!(
@@ -87,6 +90,65 @@ mod needs_enclave {
 		}
 	}
 
+	/// Exports the state of the shard, its sidechain blocks from `from_block` on and
+	/// the parentchain light clients into an encrypted backup.
+	pub(crate) fn export_state_backup<SidechainStorage>(
+		enclave: &Enclave,
+		sidechain_storage: &SidechainStorage,
+		shard: &ShardIdentifier,
+		from_block: BlockNumber,
+		backup_path: &str,
+	) -> ServiceResult<()>
+	where
+		SidechainStorage: FetchBlocks<SignedSidechainBlock>,
+	{
+		let sidechain_blocks = sidechain_storage
+			.fetch_blocks_from_number(from_block, shard)
+			.map_err(|e| Error::Custom(e.into()))?;
+		enclave.export_state_backup(shard, &sidechain_blocks, backup_path)?;
+		println!(
+			"[+] State backup of shard {:?} with {} sidechain blocks written to '{}'",
+			shard.0.to_base58(),
+			sidechain_blocks.len(),
+			backup_path
+		);
+		Ok(())
+	}
+
+	/// Restores a shard from a backup created by [`export_state_backup`].
+	///
+	/// The enclave must have the same MRSIGNER as the exporting enclave, the sidechain blocks
+	/// must be authored by enclaves registered on the parentchain, and the sidechain storage
+	/// must not contain blocks of the shard yet.
+	pub(crate) fn import_state_backup<SidechainStorage>(
+		enclave: &Enclave,
+		sidechain_storage: &SidechainStorage,
+		backup_path: &str,
+	) -> ServiceResult<()>
+	where
+		SidechainStorage: FetchBlocks<SignedSidechainBlock> + BlockStorage<SignedSidechainBlock>,
+	{
+		let bundle = StateBackupBundle::decode(
+			&mut fs::read(backup_path).map_err(|e| Error::Custom(e.into()))?.as_slice(),
+		)?;
+		if sidechain_storage.latest_block(&bundle.shard).is_some() {
+			return Err(Error::Custom(
+				format!(
+					"Sidechain storage already contains blocks of shard {:?}",
+					bundle.shard.0.to_base58()
+				)
+				.into(),
+			))
+		}
+
+		enclave.import_state_backup(backup_path)?;
+		sidechain_storage
+			.store_blocks(bundle.sidechain_blocks)
+			.map_err(|e| Error::Custom(e.into()))?;
+		println!("[+] Restored shard {:?} from '{}'", bundle.shard.0.to_base58(), backup_path);
+		Ok(())
+	}
+
 	pub(crate) fn generate_signing_key_file(enclave: &Enclave) {
 		info!("*** Get the signing key from the TEE\n");
 		let pubkey = enclave.get_ecc_signing_pubkey().unwrap();
@@ -19,17 +19,17 @@ use crate::{
 	config::Config,
 	error::{Error, ServiceResult},
 	parentchain_handler::HandleParentchain,
+	prometheus_metrics::set_sidechain_retention_status,
 };
 use futures::executor::block_on;
 use itp_enclave_api::{enclave_base::EnclaveBase, sidechain::Sidechain};
-use itp_settings::{
-	files::{SIDECHAIN_PURGE_INTERVAL, SIDECHAIN_PURGE_LIMIT},
-	sidechain::SLOT_DURATION,
-};
+use itp_settings::{files::SIDECHAIN_PURGE_INTERVAL, sidechain::SLOT_DURATION};
 use itp_types::{Header, ShardIdentifier};
 use its_consensus_slots::start_slot_worker;
 use its_primitives::types::block::SignedBlock as SignedSidechainBlock;
-use its_storage::{interface::FetchBlocks, start_sidechain_pruning_loop, BlockPruner};
+use its_storage::{
+	interface::FetchBlocks, start_sidechain_pruning_loop, BlockPruner, RetentionPolicy,
+};
 use log::*;
 use std::{sync::Arc, thread};
 use tokio::runtime::Handle;
@@ -59,6 +59,7 @@ pub(crate) fn sidechain_init_block_production<Enclave, SidechainStorage>(
 	sidechain_storage: Arc<SidechainStorage>,
 	fail_mode: Option<String>,
 	fail_at: u64,
+	retention_policy: RetentionPolicy,
 ) -> ServiceResult<()>
 where
 	Enclave: EnclaveBase + Sidechain,
@@ -86,13 +87,15 @@ where
 
 	// ------------------------------------------------------------------------
 	// start sidechain pruning loop
+	info!("sidechain storage retention policy: {:?}", retention_policy);
 	thread::Builder::new()
 		.name("sidechain_pruning_loop".to_owned())
 		.spawn(move || {
 			start_sidechain_pruning_loop(
 				&sidechain_storage,
 				SIDECHAIN_PURGE_INTERVAL,
-				SIDECHAIN_PURGE_LIMIT,
+				retention_policy,
+				set_sidechain_retention_status,
 			);
 		})
 		.map_err(|e| Error::Custom(Box::new(e)))?;
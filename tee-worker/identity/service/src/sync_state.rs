@@ -15,82 +15,165 @@
 */
 
 //! Request state keys from a fellow validateer.
+//!
+//! All registered and authorized enclaves of the shard are tried, the most recently
+//! seen first. Failed rounds are retried with an exponential backoff until a peer provides
+//! the state. Payloads sealed in an interrupted attempt are not requested again, and a
+//! partially received state is resumed if the next peer has the same state.
 
 use crate::{
 	enclave::tls_ra::enclave_request_state_provisioning,
 	error::{Error, ServiceResult as Result},
 };
-use futures::executor;
 use itc_rpc_client::direct_client::{DirectApi, DirectClient as DirectWorkerApi};
 use itp_enclave_api::{
 	enclave_base::EnclaveBase,
 	remote_attestation::{RemoteAttestation, TlsRemoteAttestation},
 };
 use itp_node_api::api_client::PalletTeebagApi;
-use itp_settings::worker_mode::{ProvideWorkerMode, WorkerMode};
-use itp_types::{ShardIdentifier, WorkerType};
-use log::info;
+use itp_types::{AccountId, Enclave, ShardIdentifier, WorkerType};
+use log::{info, warn};
 use sgx_types::sgx_quote_sign_type_t;
-use sp_runtime::MultiSigner;
-use std::string::String;
+use std::{cmp::min, string::String, thread, time::Duration};
 
+/// Delay before the second round over all peers, doubled for each further round.
+const INITIAL_RETRY_DELAY: Duration = Duration::from_secs(2);
+const MAX_RETRY_DELAY: Duration = Duration::from_secs(60);
+/// Provisioning payloads of a shielding key rotation, indexed by their opcodes: the shielding
+/// key (0), the state key (1) and the previous shielding key (5).
+const ROTATED_SHIELDING_KEY_PAYLOADS: u8 = 0b0010_0011;
+
+/// Request the state of the shard from its peers, blocks until one of them provided it.
 pub(crate) fn sync_state<
 	E: TlsRemoteAttestation + EnclaveBase + RemoteAttestation,
 	NodeApi: PalletTeebagApi,
-	WorkerModeProvider: ProvideWorkerMode,
 >(
 	node_api: &NodeApi,
 	shard: &ShardIdentifier,
 	enclave_api: &E,
 	skip_ra: bool,
 ) {
-	let provider_url = match WorkerModeProvider::worker_mode() {
-		WorkerMode::Sidechain | WorkerMode::OffChainWorker =>
-		// TODO(Litentry P-629): maybe implement `get_enclave_url_of_last_active`
-			executor::block_on(get_enclave_url_of_primary_worker_for_shard(node_api, shard))
-				.expect("Author of primary worker for shard could not be found"),
-	};
+	// kept across attempts to resume a partial transfer
+	let mut provisioned_payloads = 0u8;
+	let mut retry_delay = INITIAL_RETRY_DELAY;
+
+	for round in 1u64.. {
+		match request_state_provisioning_from_peers(
+			node_api,
+			shard,
+			enclave_api,
+			&mut provisioned_payloads,
+			skip_ra,
+		) {
+			Ok(provider_url) => {
+				println!(
+					"[+] State provisioning successfully performed by worker at {}.",
+					provider_url
+				);
+				return
+			},
+			Err(e) => warn!("State provisioning round {} failed: {:?}", round, e),
+		}
+
+		info!("Retrying state provisioning in {:?}", retry_delay);
+		thread::sleep(retry_delay);
+		retry_delay = min(retry_delay.saturating_mul(2), MAX_RETRY_DELAY);
+	}
+}
+
+/// Request the keys of a shielding key rotation from the enclave which performed it.
+///
+/// Everything else is marked as provisioned, so the running worker keeps its own state,
+/// re-encrypted with the rotated state key.
+pub(crate) fn sync_rotated_shielding_key<
+	E: TlsRemoteAttestation + EnclaveBase + RemoteAttestation,
+	NodeApi: PalletTeebagApi,
+>(
+	node_api: &NodeApi,
+	rotating_enclave: &AccountId,
+	shard: &ShardIdentifier,
+	enclave_api: &E,
+	skip_ra: bool,
+) -> Result<()> {
+	let peer = node_api.enclave(rotating_enclave, None)?.ok_or(Error::NoPeerWorkerFound)?;
+	let url = String::from_utf8_lossy(peer.url.as_slice()).to_string();
+	let provider_url = DirectWorkerApi::new(url).get_mu_ra_url()?;
 
-	println!("Requesting state provisioning from worker at {}", &provider_url);
+	println!("Requesting rotated shielding key from worker at {}", &provider_url);
 
+	let mut provisioned_payloads = !ROTATED_SHIELDING_KEY_PAYLOADS;
 	enclave_request_state_provisioning(
 		enclave_api,
 		sgx_quote_sign_type_t::SGX_UNLINKABLE_SIGNATURE,
 		&provider_url,
 		shard,
+		None,
+		&mut provisioned_payloads,
 		skip_ra,
-	)
-	.unwrap();
-	println!("[+] State provisioning successfully performed.");
+	)?;
+	Ok(())
 }
 
-/// Returns the url of the primary worker for the given shard
-async fn get_enclave_url_of_primary_worker_for_shard<NodeApi: PalletTeebagApi>(
+/// Requests the state from the peers of the shard until one succeeds, returns its mu-ra url.
+fn request_state_provisioning_from_peers<
+	E: TlsRemoteAttestation + EnclaveBase + RemoteAttestation,
+	NodeApi: PalletTeebagApi,
+>(
 	node_api: &NodeApi,
 	shard: &ShardIdentifier,
+	enclave_api: &E,
+	provisioned_payloads: &mut u8,
+	skip_ra: bool,
 ) -> Result<String> {
-	let enclave = node_api
-		.primary_enclave_for_shard(WorkerType::Identity, shard, None)?
-		.ok_or_else(|| Error::NoWorkerForShardFound(*shard))?;
-	let worker_api_direct =
-		DirectWorkerApi::new(String::from_utf8_lossy(enclave.url.as_slice()).to_string());
-	Ok(worker_api_direct.get_mu_ra_url()?)
+	let confirmed_block = node_api.latest_sidechain_block_confirmation(shard, None)?;
+	let peers = get_peer_enclaves_for_shard(node_api, shard, enclave_api)?;
+	if peers.is_empty() {
+		return Err(Error::NoWorkerForShardFound(*shard))
+	}
+
+	for peer in peers {
+		let url = String::from_utf8_lossy(peer.url.as_slice()).to_string();
+		let provider_url = match DirectWorkerApi::new(url.clone()).get_mu_ra_url() {
+			Ok(u) => u,
+			Err(e) => {
+				warn!("Failed to get mu-ra url of worker at {}: {:?}", url, e);
+				continue
+			},
+		};
+
+		println!("Requesting state provisioning from worker at {}", &provider_url);
+
+		match enclave_request_state_provisioning(
+			enclave_api,
+			sgx_quote_sign_type_t::SGX_UNLINKABLE_SIGNATURE,
+			&provider_url,
+			shard,
+			confirmed_block,
+			provisioned_payloads,
+			skip_ra,
+		) {
+			Ok(()) => return Ok(provider_url),
+			Err(e) => warn!("State provisioning from worker at {} failed: {:?}", provider_url, e),
+		}
+	}
+
+	Err(Error::NoPeerWorkerFound)
 }
 
-/// Returns the url of the first Enclave that matches our own MRENCLAVE.
-///
-/// This should be run before we register ourselves as enclave, to ensure we don't get our own url.
-async fn get_enclave_url_of_first_registered<NodeApi: PalletTeebagApi, EnclaveApi: EnclaveBase>(
+/// Returns the registered and authorized enclaves of the shard except for ourselves,
+/// the most recently seen first.
+fn get_peer_enclaves_for_shard<NodeApi: PalletTeebagApi, EnclaveApi: EnclaveBase>(
 	node_api: &NodeApi,
+	shard: &ShardIdentifier,
 	enclave_api: &EnclaveApi,
-) -> Result<String> {
-	let self_mrenclave = enclave_api.get_fingerprint()?;
-	let first_enclave = node_api
-		.all_enclaves(WorkerType::Identity, None)?
+) -> Result<Vec<Enclave>> {
+	let self_account = AccountId::from(*enclave_api.get_ecc_signing_pubkey()?.as_array_ref());
+	let mut peers: Vec<Enclave> = node_api
+		.authorized_enclaves_for_shard(WorkerType::Identity, shard, None)?
 		.into_iter()
-		.find(|e| e.mrenclave == self_mrenclave.to_fixed_bytes())
-		.ok_or(Error::NoPeerWorkerFound)?;
-	let worker_api_direct =
-		DirectWorkerApi::new(String::from_utf8_lossy(first_enclave.url.as_slice()).to_string());
-	Ok(worker_api_direct.get_mu_ra_url()?)
+		.filter(|(account, _)| account != &self_account)
+		.map(|(_, enclave)| enclave)
+		.collect();
+	peers.sort_by(|a, b| b.last_seen_timestamp.cmp(&a.last_seen_timestamp));
+	Ok(peers)
 }
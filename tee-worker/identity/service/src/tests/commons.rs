@@ -61,5 +61,7 @@ pub fn local_worker_config(
 		"0".to_string(),
 		None,
 		0,
+		Default::default(),
+		Default::default(),
 	)
 }
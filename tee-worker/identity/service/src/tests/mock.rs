@@ -16,7 +16,10 @@
 */
 
 use itp_node_api::api_client::{ApiResult, PalletTeebagApi};
-use itp_types::{AccountId, Enclave, MrEnclave, ShardIdentifier, WorkerType, H256 as Hash};
+use itp_types::{
+	AccountId, Enclave, MrEnclave, ShardIdentifier, SidechainBlockConfirmation, WorkerType,
+	H256 as Hash,
+};
 use std::collections::HashSet;
 
 pub struct TestNodeApi;
@@ -66,4 +69,28 @@ impl PalletTeebagApi for TestNodeApi {
 	) -> ApiResult<Option<Enclave>> {
 		unreachable!()
 	}
+	fn authorized_enclaves(
+		&self,
+		_worker_type: WorkerType,
+		_at_block: Option<Self::Hash>,
+	) -> ApiResult<Vec<MrEnclave>> {
+		unreachable!()
+	}
+
+	fn authorized_enclaves_for_shard(
+		&self,
+		_worker_type: WorkerType,
+		_shard: &ShardIdentifier,
+		_at_block: Option<Self::Hash>,
+	) -> ApiResult<Vec<(AccountId, Enclave)>> {
+		unreachable!()
+	}
+
+	fn latest_sidechain_block_confirmation(
+		&self,
+		_shard: &ShardIdentifier,
+		_at_block: Option<Self::Hash>,
+	) -> ApiResult<Option<SidechainBlockConfirmation>> {
+		unreachable!()
+	}
 }
@@ -26,7 +26,7 @@ use itp_types::{
 		Balance, Header, ParentchainId, ParentchainInitParams,
 		ParentchainInitParams::{Parachain, Solochain},
 	},
-	EnclaveFingerprint, ShardIdentifier,
+	AdmissionConfig, EnclaveFingerprint, ShardIdentifier,
 };
 use sgx_crypto_helper::rsa3072::Rsa3072PubKey;
 use sp_core::ed25519;
@@ -35,7 +35,13 @@ use sp_core::ed25519;
 pub struct EnclaveMock;
 
 impl EnclaveBase for EnclaveMock {
-	fn init(&self, _mu_ra_url: &str, _untrusted_url: &str, _base_dir: &str) -> EnclaveResult<()> {
+	fn init(
+		&self,
+		_mu_ra_url: &str,
+		_untrusted_url: &str,
+		_base_dir: &str,
+		_admission_config: &AdmissionConfig,
+	) -> EnclaveResult<()> {
 		Ok(())
 	}
 
@@ -107,6 +113,23 @@ impl EnclaveBase for EnclaveMock {
 	fn upload_id_graph(&self) -> EnclaveResult<()> {
 		unimplemented!()
 	}
+
+	fn rotate_shielding_key(&self, _grace_period_secs: u64) -> EnclaveResult<()> {
+		unimplemented!()
+	}
+
+	fn export_state_backup<SidechainBlock: Encode>(
+		&self,
+		_shard: &ShardIdentifier,
+		_sidechain_blocks: &[SidechainBlock],
+		_backup_path: &str,
+	) -> EnclaveResult<()> {
+		unimplemented!()
+	}
+
+	fn import_state_backup(&self, _backup_path: &str) -> EnclaveResult<()> {
+		unimplemented!()
+	}
 }
 
 impl Sidechain for EnclaveMock {
@@ -27,7 +27,7 @@ use its_primitives::traits::{
 	Block as SidechainBlockTrait, BlockData, Header as HeaderTrait, SignBlock,
 	SignedBlock as SignedSidechainBlockTrait,
 };
-use its_state::{LastBlockExt, SidechainState, SidechainSystemExt};
+use its_state::{LastBlockExt, SidechainState, SidechainSystemExt, StateRootExt};
 use log::*;
 use sp_core::Pair;
 use sp_runtime::{
@@ -97,6 +97,7 @@ where
 		+ SidechainState
 		+ SidechainSystemExt
 		+ StateHash
+		+ StateRootExt
 		+ LastBlockExt<SignedSidechainBlock::Block>
 		+ Encode,
 	<Externalities as SgxExternalitiesTrait>::SgxExternalitiesType: Encode,
@@ -119,19 +120,22 @@ where
 		let author_public = self.signer.public();
 
 		let state_hash_new = aposteriori_state.hash();
-
-		let (block_number, parent_hash, next_finalization_block_number) =
-			match aposteriori_state.get_last_block() {
-				Some(block) => (
-					block.header().block_number() + 1,
-					block.hash(),
-					block.header().next_finalization_block_number(),
-				),
-				None => {
-					info!("Seems to be first sidechain block.");
-					(1, Default::default(), 1)
-				},
-			};
+		let last_block = aposteriori_state.get_last_block();
+		let state_root =
+			aposteriori_state.state_root_from(last_block.as_ref().map(|b| b.header().state_root()));
+
+		// a migrated last block keeps the hash of its legacy header, see `migrate_last_block`
+		let (block_number, parent_hash, next_finalization_block_number) = match last_block {
+			Some(block) => (
+				block.header().block_number() + 1,
+				aposteriori_state.get_last_block_hash().unwrap_or_else(|| block.hash()),
+				block.header().next_finalization_block_number(),
+			),
+			None => {
+				info!("Seems to be first sidechain block.");
+				(1, Default::default(), 1)
+			},
+		};
 
 		if block_number != aposteriori_state.get_block_number().unwrap_or(0) {
 			return Err(Error::Other("[Sidechain] BlockNumber is not LastBlock's Number + 1".into()))
@@ -172,6 +176,7 @@ where
 			shard,
 			block_data.hash(),
 			finalization_candidate,
+			state_root,
 		);
 
 		let block = SignedSidechainBlock::Block::new(header.clone(), block_data);
@@ -52,10 +52,12 @@ pub mod slot;
 
 type AuthorityId<P> = <P as Pair>::Public;
 
+/// Verify a sidechain block against the last imported block and the hash it's linked to by its
+/// successor. The hash is the one of the last block, unless the last block was migrated.
 pub fn verify_sidechain_block<AuthorityPair, ParentchainBlock, SignedSidechainBlock>(
 	signed_block: SignedSidechainBlock,
 	slot_duration: Duration,
-	last_block: &Option<<SignedSidechainBlock as SignedBlock>::Block>,
+	last_block: &Option<(<SignedSidechainBlock as SignedBlock>::Block, BlockHash)>,
 	parentchain_header: &ParentchainBlock::Header,
 	authorities: &[AuthorityId<AuthorityPair>],
 ) -> Result<SignedSidechainBlock, ConsensusError>
@@ -79,8 +81,12 @@ where
 	// We need to check the ancestry first to ensure that an already imported block does not result
 	// in an author verification error, but rather a `BlockAlreadyImported` error.
 	match last_block {
-		Some(last_block) =>
-			verify_block_ancestry::<SignedSidechainBlock::Block>(signed_block.block(), last_block)?,
+		Some((last_block, last_block_hash)) =>
+			verify_block_ancestry::<SignedSidechainBlock::Block>(
+				signed_block.block(),
+				last_block,
+				last_block_hash,
+			)?,
 		None => ensure_first_block(signed_block.block())?,
 	}
 
@@ -139,6 +145,7 @@ where
 fn verify_block_ancestry<SidechainBlock: SidechainBlockTrait>(
 	block: &SidechainBlock,
 	last_block: &SidechainBlock,
+	last_block_hash: &BlockHash,
 ) -> Result<(), ConsensusError> {
 	// These next two checks might seem redundant at first glance. However, they are distinct (see comments).
 
@@ -156,7 +163,7 @@ fn verify_block_ancestry<SidechainBlock: SidechainBlockTrait>(
 		last_block.header().block_number() + 1 == block.header().block_number(),
 		ConsensusError::BlockAncestryMismatch(
 			last_block.header().block_number(),
-			last_block.hash(),
+			*last_block_hash,
 			format!(
 				"Invalid block number, {} does not succeed {}",
 				block.header().block_number(),
@@ -166,10 +173,10 @@ fn verify_block_ancestry<SidechainBlock: SidechainBlockTrait>(
 	);
 
 	ensure!(
-		last_block.hash() == block.header().parent_hash(),
+		*last_block_hash == block.header().parent_hash(),
 		ConsensusError::BlockAncestryMismatch(
 			last_block.header().block_number(),
-			last_block.hash(),
+			*last_block_hash,
 			"Parent hash does not match".into(),
 		)
 	);
@@ -292,7 +299,7 @@ mod tests {
 			.build();
 		let curr_block = SidechainBlockBuilder::default().with_header(header).build();
 
-		assert_ok!(verify_block_ancestry(&curr_block, &last_block));
+		assert_ok!(verify_block_ancestry(&curr_block, &last_block, &last_block.hash()));
 	}
 
 	#[test]
@@ -304,7 +311,11 @@ mod tests {
 			.build();
 		let curr_block = SidechainBlockBuilder::default().with_header(header).build();
 
-		assert_ancestry_mismatch_err(verify_block_ancestry(&curr_block, &last_block));
+		assert_ancestry_mismatch_err(verify_block_ancestry(
+			&curr_block,
+			&last_block,
+			&last_block.hash(),
+		));
 	}
 
 	#[test]
@@ -313,7 +324,24 @@ mod tests {
 		let header = SidechainHeaderBuilder::default().with_block_number(2).build();
 		let curr_block = SidechainBlockBuilder::default().with_header(header).build();
 
-		assert_ancestry_mismatch_err(verify_block_ancestry(&curr_block, &last_block));
+		assert_ancestry_mismatch_err(verify_block_ancestry(
+			&curr_block,
+			&last_block,
+			&last_block.hash(),
+		));
+	}
+
+	#[test]
+	fn verify_block_ancestry_works_with_hash_of_migrated_last_block() {
+		let last_block = SidechainBlockBuilder::default().build();
+		let last_block_hash = H256::random();
+		let header = SidechainHeaderBuilder::default()
+			.with_parent_hash(last_block_hash)
+			.with_block_number(2)
+			.build();
+		let curr_block = SidechainBlockBuilder::default().with_header(header).build();
+
+		assert_ok!(verify_block_ancestry(&curr_block, &last_block, &last_block_hash));
 	}
 
 	#[test]
@@ -329,7 +357,7 @@ mod tests {
 		assert_ok!(verify_sidechain_block::<Pair, ParentchainBlock, _>(
 			curr_block,
 			SLOT_DURATION,
-			&Some(last_block),
+			&Some((last_block.clone(), last_block.hash())),
 			&parentchain_header,
 			&authorities,
 		));
@@ -371,7 +399,7 @@ mod tests {
 			verify_sidechain_block::<Pair, ParentchainBlock, _>(
 				curr_block,
 				SLOT_DURATION,
-				&Some(last_block),
+				&Some((last_block.clone(), last_block.hash())),
 				&parentchain_header,
 				&authorities,
 			)
@@ -393,7 +421,7 @@ mod tests {
 		assert_ancestry_mismatch_err(verify_sidechain_block::<Pair, ParentchainBlock, _>(
 			curr_block,
 			SLOT_DURATION,
-			&Some(last_block),
+			&Some((last_block.clone(), last_block.hash())),
 			&parentchain_header,
 			&authorities,
 		));
@@ -437,7 +465,7 @@ mod tests {
 			verify_sidechain_block::<Pair, ParentchainBlock, _>(
 				curr_block,
 				SLOT_DURATION,
-				&Some(last_block),
+				&Some((last_block.clone(), last_block.hash())),
 				&parentchain_header,
 				&authorities,
 			)
@@ -481,7 +509,7 @@ mod tests {
 			verify_sidechain_block::<Pair, ParentchainBlock, _>(
 				signed_block_to_verify,
 				SLOT_DURATION,
-				&Some(last_block),
+				&Some((last_block.clone(), last_block.hash())),
 				&parentchain_header_2,
 				&authorities,
 			)
@@ -222,7 +222,7 @@ impl<
 
 	fn verifier(
 		&self,
-		maybe_last_sidechain_block: Option<SignedSidechainBlock::Block>,
+		maybe_last_sidechain_block: Option<(SignedSidechainBlock::Block, H256)>,
 	) -> Self::Verifier {
 		AuraVerifier::<Authority, ParentchainBlock, _, _>::new(
 			SLOT_DURATION,
@@ -39,7 +39,7 @@ use its_primitives::{
 	traits::{SignBlock, SignedBlock},
 	types::SignedBlock as SignedSidechainBlock,
 };
-use its_state::StateUpdate;
+use its_state::{StateRootExt, StateUpdate};
 use its_test::{
 	sidechain_block_builder::{SidechainBlockBuilder, SidechainBlockBuilderTrait},
 	sidechain_block_data_builder::SidechainBlockDataBuilder,
@@ -127,10 +127,12 @@ fn signed_block(
 	signer: Pair,
 ) -> SignedSidechainBlock {
 	let state_update = empty_encrypted_state_update(state_handler);
+	let (state, _) = state_handler.load_cloned(&shard()).unwrap();
 
 	let header = SidechainHeaderBuilder::default()
 		.with_parent_hash(H256::default())
 		.with_shard(shard())
+		.with_state_root(state.state_root())
 		.build();
 
 	let block_data = SidechainBlockDataBuilder::default()
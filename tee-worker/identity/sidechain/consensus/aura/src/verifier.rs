@@ -35,7 +35,7 @@ where
 	SignedSidechainBlock::Block: SidechainBlockTrait,
 {
 	slot_duration: Duration,
-	last_sidechain_block: Option<SignedSidechainBlock::Block>,
+	last_sidechain_block: Option<(SignedSidechainBlock::Block, BlockHash)>,
 	_phantom: PhantomData<(AuthorityPair, ParentchainBlock, Context)>,
 }
 
@@ -47,7 +47,7 @@ where
 {
 	pub fn new(
 		slot_duration: Duration,
-		last_sidechain_block: Option<SignedSidechainBlock::Block>,
+		last_sidechain_block: Option<(SignedSidechainBlock::Block, BlockHash)>,
 	) -> Self {
 		Self { slot_duration, last_sidechain_block, _phantom: Default::default() }
 	}
@@ -22,11 +22,16 @@ use codec::Decode;
 use itp_enclave_metrics::EnclaveMetric;
 use itp_ocall_api::{EnclaveMetricsOCallApi, EnclaveSidechainOCallApi};
 use itp_sgx_crypto::StateCrypto;
-use its_primitives::traits::{
-	Block as SidechainBlockTrait, BlockData, Header as HeaderTrait, ShardIdentifierFor,
-	SignedBlock as SignedSidechainBlockTrait,
+use its_primitives::{
+	traits::{
+		Block as SidechainBlockTrait, BlockData, Header as HeaderTrait, ShardIdentifierFor,
+		SignedBlock as SignedSidechainBlockTrait,
+	},
+	types::block::BlockHash,
+};
+use its_state::{
+	ConfirmedStateExt, LastBlockExt, SidechainState, SidechainSystemExt, StateRootExt,
 };
-use its_state::{LastBlockExt, SidechainState};
 use log::*;
 use sp_runtime::traits::Block as ParentchainBlockTrait;
 use std::{time::Instant, vec::Vec};
@@ -45,7 +50,10 @@ where
 	>;
 
 	/// Context needed to derive verifier relevant data.
-	type SidechainState: SidechainState + LastBlockExt<SignedSidechainBlock::Block>;
+	type SidechainState: SidechainState
+		+ StateRootExt
+		+ ConfirmedStateExt
+		+ LastBlockExt<SignedSidechainBlock::Block>;
 
 	/// Provides the cryptographic functions for our the state encryption.
 	type StateCrypto: StateCrypto;
@@ -53,10 +61,10 @@ where
 	/// Context needed to derive verifier relevant data.
 	type Context: EnclaveSidechainOCallApi + EnclaveMetricsOCallApi;
 
-	/// Get a verifier instance.
+	/// Get a verifier instance for the last imported block and the hash its successor links to.
 	fn verifier(
 		&self,
-		maybe_last_sidechain_block: Option<SignedSidechainBlock::Block>,
+		maybe_last_sidechain_block: Option<(SignedSidechainBlock::Block, BlockHash)>,
 	) -> Self::Verifier;
 
 	/// Apply a state update by providing a mutating function.
@@ -133,7 +141,8 @@ where
 				});
 
 		let block_import_params = self.verify_import(&shard, |state| {
-			let verifier = self.verifier(state.get_last_block());
+			// a migrated last block keeps the hash of its legacy header, see `migrate_last_block`
+			let verifier = self.verifier(state.get_last_block().zip(state.get_last_block_hash()));
 			verifier.verify(
 				signed_sidechain_block.clone(),
 				&peeked_parentchain_header,
@@ -160,10 +169,24 @@ where
 
 			let update = state_update_from_encrypted(encrypted_state_diff, state_key)?;
 
+			let parent_root = state.get_last_block().map(|b| b.header().state_root());
 			state.apply_state_update(&update).map_err(|e| Error::Other(e.into()))?;
 
+			let header = block_import_params.block().header();
+			if state.update_state_tree(parent_root) != header.state_root() {
+				return Err(Error::BadSidechainBlock(
+					block_import_params.hash(),
+					"State root does not match the state after applying the update".into(),
+				))
+			}
+
 			state.set_last_block(block_import_params.block());
 
+			// this is the block whose import gets confirmed on the parentchain
+			if header.block_number() == header.next_finalization_block_number() {
+				state.retain_as_confirmed(&shard, header, header.state_root());
+			}
+
 			Ok(state)
 		})?;
 		debug!(
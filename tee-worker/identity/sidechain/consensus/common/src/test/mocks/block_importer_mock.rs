@@ -21,7 +21,10 @@ use itp_sgx_crypto::aes::Aes;
 use itp_sgx_externalities::SgxExternalities;
 use itp_test::mock::onchain_mock::OnchainMock;
 use itp_types::H256;
-use its_primitives::traits::{ShardIdentifierFor, SignedBlock as SignedSidechainBlockTrait};
+use its_primitives::{
+	traits::{ShardIdentifierFor, SignedBlock as SignedSidechainBlockTrait},
+	types::block::BlockHash,
+};
 use sp_core::Pair;
 use sp_runtime::traits::Block as ParentchainBlockTrait;
 use std::{collections::VecDeque, sync::RwLock};
@@ -98,7 +101,7 @@ where
 
 	fn verifier(
 		&self,
-		_maybe_last_sidechain_block: Option<SignedSidechainBlock::Block>,
+		_maybe_last_sidechain_block: Option<(SignedSidechainBlock::Block, BlockHash)>,
 	) -> Self::Verifier {
 		todo!()
 	}
@@ -16,10 +16,16 @@
 */
 
 use crate::error::Result;
-use its_primitives::types::{BlockHash, BlockNumber, ShardIdentifier, SignedBlock};
+use its_primitives::types::{
+	block_explorer::{
+		SidechainBlockHeaderInfo, SidechainBlockInfo, SidechainBlockPage, MAX_BLOCKS_PER_PAGE,
+	},
+	BlockHash, BlockNumber, ShardIdentifier, SignedBlock,
+};
 use its_rpc_handler::constants::{
-	RPC_METHOD_NAME_BLOCK_HASH, RPC_METHOD_NAME_FETCH_BLOCKS_FROM_PEER,
-	RPC_METHOD_NAME_LATEST_BLOCK,
+	RPC_METHOD_NAME_BLOCK_HASH, RPC_METHOD_NAME_FETCH_BLOCKS_FROM_PEER, RPC_METHOD_NAME_GET_BLOCKS,
+	RPC_METHOD_NAME_GET_BLOCK_BY_HASH, RPC_METHOD_NAME_GET_BLOCK_BY_NUMBER,
+	RPC_METHOD_NAME_GET_BLOCK_HEADER, RPC_METHOD_NAME_LATEST_BLOCK,
 };
 use its_storage::interface::FetchBlocks;
 use jsonrpsee::{types::error::CallError, RpcModule};
@@ -98,6 +104,161 @@ where
 				}
 			},
 		)?;
+
+		// block explorer
+		fetch_sidechain_blocks_module.register_method(
+			RPC_METHOD_NAME_GET_BLOCK_BY_NUMBER,
+			|params, sidechain_block_fetcher| {
+				debug!("{}: {:?}", RPC_METHOD_NAME_GET_BLOCK_BY_NUMBER, params);
+				let (block_number, shard) = params.parse::<(BlockNumber, ShardIdentifier)>()?;
+				let blocks = sidechain_block_fetcher
+					.fetch_blocks_by_number(block_number, 1, &shard)
+					.map_err(|e| {
+						error!("Failed to fetch sidechain block from storage: {:?}", e);
+						CallError::Failed(e.into())
+					})?;
+				Ok(blocks.first().map(SidechainBlockInfo::from))
+			},
+		)?;
+
+		fetch_sidechain_blocks_module.register_method(
+			RPC_METHOD_NAME_GET_BLOCK_BY_HASH,
+			|params, sidechain_block_fetcher| {
+				debug!("{}: {:?}", RPC_METHOD_NAME_GET_BLOCK_BY_HASH, params);
+				let block_hash = params.one::<BlockHash>()?;
+				let block = fetch_block(sidechain_block_fetcher.as_ref(), &block_hash)?;
+				Ok(block.as_ref().map(SidechainBlockInfo::from))
+			},
+		)?;
+
+		fetch_sidechain_blocks_module.register_method(
+			RPC_METHOD_NAME_GET_BLOCK_HEADER,
+			|params, sidechain_block_fetcher| {
+				debug!("{}: {:?}", RPC_METHOD_NAME_GET_BLOCK_HEADER, params);
+				let block_hash = params.one::<BlockHash>()?;
+				let block = fetch_block(sidechain_block_fetcher.as_ref(), &block_hash)?;
+				Ok(block.as_ref().map(SidechainBlockHeaderInfo::from))
+			},
+		)?;
+
+		fetch_sidechain_blocks_module.register_method(
+			RPC_METHOD_NAME_GET_BLOCKS,
+			|params, sidechain_block_fetcher| {
+				debug!("{}: {:?}", RPC_METHOD_NAME_GET_BLOCKS, params);
+				let (from_block_number, limit, shard) =
+					params.parse::<(BlockNumber, u64, ShardIdentifier)>()?;
+				let limit = limit.min(MAX_BLOCKS_PER_PAGE);
+
+				let latest_block_number = match sidechain_block_fetcher.latest_block(&shard) {
+					Some(latest_block) => latest_block.number,
+					None => return Ok(SidechainBlockPage::default()),
+				};
+				let blocks = sidechain_block_fetcher
+					.fetch_blocks_by_number(from_block_number, limit, &shard)
+					.map_err(|e| {
+						error!("Failed to fetch sidechain blocks from storage: {:?}", e);
+						CallError::Failed(e.into())
+					})?;
+
+				let next_block_number = from_block_number.saturating_add(limit);
+				Ok(SidechainBlockPage {
+					blocks: blocks.iter().map(SidechainBlockInfo::from).collect(),
+					next_block_number: (limit > 0 && next_block_number <= latest_block_number)
+						.then_some(next_block_number),
+				})
+			},
+		)?;
 		Ok(fetch_sidechain_blocks_module)
 	}
 }
+
+fn fetch_block<FetchBlocksFromStorage: FetchBlocks<SignedBlock>>(
+	sidechain_block_fetcher: &FetchBlocksFromStorage,
+	block_hash: &BlockHash,
+) -> std::result::Result<Option<SignedBlock>, CallError> {
+	sidechain_block_fetcher.fetch_block(block_hash).map_err(|e| {
+		error!("Failed to fetch sidechain block from storage: {:?}", e);
+		CallError::Failed(e.into())
+	})
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use its_primitives::traits::Block as BlockTrait;
+	use its_storage::fetch_blocks_mock::FetchBlocksMock;
+	use its_test::{
+		sidechain_block_builder::{SidechainBlockBuilder, SidechainBlockBuilderTrait},
+		sidechain_header_builder::SidechainHeaderBuilder,
+	};
+	use jsonrpsee::{
+		types::to_json_value,
+		ws_client::{traits::Client, WsClientBuilder},
+		ws_server::WsServerBuilder,
+	};
+
+	async fn run_server(blocks: Vec<SignedBlock>, web_socket_url: &str) {
+		let mut server = WsServerBuilder::default().build(web_socket_url).await.unwrap();
+
+		let storage_block_fetcher = Arc::new(FetchBlocksMock::default().with_blocks(blocks));
+		let module = BlockFetchServerModuleBuilder::new(storage_block_fetcher).build().unwrap();
+		server.register_module(module).unwrap();
+
+		tokio::spawn(async move { server.start().await });
+	}
+
+	fn block_with_number(block_number: u64) -> SignedBlock {
+		SidechainBlockBuilder::random()
+			.with_header(SidechainHeaderBuilder::random().with_block_number(block_number).build())
+			.build_signed()
+	}
+
+	#[tokio::test]
+	async fn get_blocks_returns_page_with_top_hashes() {
+		const W1_URL: &str = "127.0.0.1:2234";
+
+		let blocks = vec![block_with_number(3), block_with_number(4), block_with_number(5)];
+		run_server(blocks.clone(), W1_URL).await;
+		let client = WsClientBuilder::default().build(&format!("ws://{}", W1_URL)).await.unwrap();
+
+		let page = client
+			.request::<SidechainBlockPage>(
+				RPC_METHOD_NAME_GET_BLOCKS,
+				vec![
+					to_json_value(4u64).unwrap(),
+					to_json_value(10u64).unwrap(),
+					to_json_value(ShardIdentifier::default()).unwrap(),
+				]
+				.into(),
+			)
+			.await
+			.unwrap();
+
+		let expected: Vec<SidechainBlockInfo> =
+			blocks[1..].iter().map(SidechainBlockInfo::from).collect();
+		assert_eq!(page.blocks, expected);
+		assert_eq!(page.blocks[0].signed_top_hashes, blocks[1].block.block_data.signed_top_hashes);
+		assert_eq!(page.next_block_number, None);
+	}
+
+	#[tokio::test]
+	async fn get_block_header_returns_block_author() {
+		const W1_URL: &str = "127.0.0.1:2235";
+
+		let block = block_with_number(3);
+		run_server(vec![block.clone()], W1_URL).await;
+		let client = WsClientBuilder::default().build(&format!("ws://{}", W1_URL)).await.unwrap();
+
+		let header = client
+			.request::<Option<SidechainBlockHeaderInfo>>(
+				RPC_METHOD_NAME_GET_BLOCK_HEADER,
+				vec![to_json_value(block.block.hash()).unwrap()].into(),
+			)
+			.await
+			.unwrap()
+			.unwrap();
+
+		assert_eq!(header.header, block.block.header);
+		assert_eq!(header.block_author, block.block.block_data.block_author.into());
+	}
+}
@@ -45,12 +45,16 @@ pub trait Header: Encode + Decode + Clone {
 
 	fn next_finalization_block_number(&self) -> u64;
 
+	/// get the merkle root of the state after this block
+	fn state_root(&self) -> H256;
+
 	fn new(
 		block_number: u64,
 		parent_hash: H256,
 		shard: Self::ShardIdentifier,
 		block_data_hash: H256,
 		next_finalization_block_number: u64,
+		state_root: H256,
 	) -> Self;
 }
 
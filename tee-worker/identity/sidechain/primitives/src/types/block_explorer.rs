@@ -0,0 +1,90 @@
+// Copyright 2020-2024 Trust Computing GmbH.
+// This file is part of Litentry.
+//
+// Litentry is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// Litentry is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with Litentry.  If not, see <https://www.gnu.org/licenses/>.
+
+//! Types of the sidechain block explorer RPC.
+
+use crate::{
+	traits::Block as BlockTrait,
+	types::{
+		block::{BlockHash, BlockNumber, Signature, SignedBlock, Timestamp},
+		header::SidechainHeader,
+	},
+};
+use codec::{Decode, Encode};
+use itp_types::AccountId;
+use sp_core::H256;
+use sp_std::vec::Vec;
+
+#[cfg(feature = "std")]
+use serde::{Deserialize, Serialize};
+
+/// Upper bound of blocks returned in a single [`SidechainBlockPage`].
+pub const MAX_BLOCKS_PER_PAGE: u64 = 100;
+
+/// Header of a sidechain block together with the enclave account which authored and signed it.
+#[derive(PartialEq, Eq, Clone, Encode, Decode, Debug)]
+#[cfg_attr(feature = "std", derive(Serialize, Deserialize))]
+pub struct SidechainBlockHeaderInfo {
+	pub hash: BlockHash,
+	pub header: SidechainHeader,
+	pub timestamp: Timestamp,
+	/// Parentchain header the block is based on.
+	pub layer_one_head: H256,
+	/// Registered enclave account of the block author.
+	pub block_author: AccountId,
+	pub signature: Signature,
+}
+
+/// Sidechain block without its encrypted state diff.
+#[derive(PartialEq, Eq, Clone, Encode, Decode, Debug)]
+#[cfg_attr(feature = "std", derive(Serialize, Deserialize))]
+pub struct SidechainBlockInfo {
+	pub header: SidechainBlockHeaderInfo,
+	/// Hashes of the trusted operations included in the block.
+	pub signed_top_hashes: Vec<H256>,
+}
+
+/// Page of sidechain blocks, oldest first.
+#[derive(PartialEq, Eq, Clone, Encode, Decode, Debug, Default)]
+#[cfg_attr(feature = "std", derive(Serialize, Deserialize))]
+pub struct SidechainBlockPage {
+	pub blocks: Vec<SidechainBlockInfo>,
+	/// Block number to request the next page from, `None` if there are no newer blocks.
+	pub next_block_number: Option<BlockNumber>,
+}
+
+impl From<&SignedBlock> for SidechainBlockHeaderInfo {
+	fn from(signed_block: &SignedBlock) -> Self {
+		let block = &signed_block.block;
+		SidechainBlockHeaderInfo {
+			hash: block.hash(),
+			header: block.header,
+			timestamp: block.block_data.timestamp,
+			layer_one_head: block.block_data.layer_one_head,
+			block_author: block.block_data.block_author.into(),
+			signature: signed_block.signature.clone(),
+		}
+	}
+}
+
+impl From<&SignedBlock> for SidechainBlockInfo {
+	fn from(signed_block: &SignedBlock) -> Self {
+		SidechainBlockInfo {
+			header: signed_block.into(),
+			signed_top_hashes: signed_block.block.block_data.signed_top_hashes.clone(),
+		}
+	}
+}
@@ -45,6 +45,9 @@ pub struct SidechainHeader {
 
 	/// The latest finalized block number
 	pub next_finalization_block_number: u64,
+
+	/// Merkle root of the state after this block, see `itp_storage::StateMerkleTree`.
+	pub state_root: H256,
 }
 
 impl SidechainHeader {
@@ -72,6 +75,9 @@ impl HeaderTrait for SidechainHeader {
 	fn next_finalization_block_number(&self) -> u64 {
 		self.next_finalization_block_number
 	}
+	fn state_root(&self) -> H256 {
+		self.state_root
+	}
 
 	fn new(
 		block_number: u64,
@@ -79,6 +85,7 @@ impl HeaderTrait for SidechainHeader {
 		shard: Self::ShardIdentifier,
 		block_data_hash: H256,
 		next_finalization_block_number: u64,
+		state_root: H256,
 	) -> SidechainHeader {
 		SidechainHeader {
 			block_number,
@@ -86,6 +93,7 @@ impl HeaderTrait for SidechainHeader {
 			shard_id: shard,
 			block_data_hash,
 			next_finalization_block_number,
+			state_root,
 		}
 	}
 }
@@ -17,6 +17,9 @@
 
 pub mod block;
 pub mod block_data;
+pub mod block_explorer;
 pub mod header;
+pub mod state_backup;
+pub mod state_proof;
 
 pub use block::*;
@@ -0,0 +1,53 @@
+// Copyright 2020-2024 Trust Computing GmbH.
+// This file is part of Litentry.
+//
+// Litentry is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// Litentry is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with Litentry.  If not, see <https://www.gnu.org/licenses/>.
+
+use crate::types::block::{BlockHash, ShardIdentifier, SignedBlock};
+use codec::{Decode, Encode};
+use sp_std::vec::Vec;
+
+/// Version of the [`StateBackupBundle`] format, bump it on any change of the format.
+pub const STATE_BACKUP_VERSION: u32 = 2;
+
+/// Backup of a shard, to recover it after losing all workers of a cluster.
+///
+/// Only an enclave of the same MRSIGNER can unseal the backup key and import the bundle.
+#[derive(PartialEq, Eq, Clone, Encode, Decode, Debug)]
+pub struct StateBackupBundle {
+	pub version: u32,
+	pub shard: ShardIdentifier,
+	/// Sidechain blocks of the shard, oldest first.
+	pub sidechain_blocks: Vec<SignedBlock>,
+	/// Random key of this backup, sealed with the MRSIGNER key policy.
+	pub sealed_backup_key: Vec<u8>,
+	/// Encoded `AesOutput` of the encoded [`StateBackupPayload`], encrypted and
+	/// authenticated (AES-GCM) with the backup key.
+	pub encrypted_payload: Vec<u8>,
+}
+
+/// The confidential part of a [`StateBackupBundle`].
+#[derive(PartialEq, Eq, Clone, Encode, Decode, Debug)]
+pub struct StateBackupPayload {
+	pub shard: ShardIdentifier,
+	/// Encoded state of the shard.
+	pub state: Vec<u8>,
+	/// Encoded light client state of the Litentry parentchain, i.e. the parentchain sync checkpoint.
+	pub light_client_state: Vec<u8>,
+	/// Encoded light client states of the target parentchains, if they were synced.
+	pub target_a_light_client_state: Option<Vec<u8>>,
+	pub target_b_light_client_state: Option<Vec<u8>>,
+	/// Hash of the last sidechain block in the bundle, binds the sidechain blocks to the state.
+	pub last_sidechain_block_hash: Option<BlockHash>,
+}
@@ -0,0 +1,220 @@
+// Copyright 2020-2024 Trust Computing GmbH.
+// This file is part of Litentry.
+//
+// Litentry is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// Litentry is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with Litentry.  If not, see <https://www.gnu.org/licenses/>.
+
+//! Proofs of sidechain state entries against the parentchain.
+//!
+//! Every sidechain header commits to the Merkle root of the state after its block. Proofs are
+//! served against the state after the block confirmed in `pallet-teebag`, i.e. the latest
+//! `SidechainBlockConfirmation` of the shard, so a client can check them with the header alone.
+
+use crate::types::header::SidechainHeader;
+use codec::{Decode, Encode};
+use itp_storage::{verify_state_reads, verify_state_values, Error, StateProof};
+use itp_types::SidechainBlockConfirmation;
+use sp_core::H256;
+use sp_std::vec::Vec;
+
+/// Proof of sidechain state entries against the header of the confirmed block.
+#[derive(PartialEq, Eq, Clone, Encode, Decode, Debug)]
+pub struct SidechainStateProof {
+	/// Header of the confirmed block, its state root is the root of the proof.
+	pub header: SidechainHeader,
+	pub proof: StateProof,
+}
+
+impl SidechainStateProof {
+	/// The state root of `self.header`, once it is linked to the confirmed block.
+	///
+	/// `ancestors` are the headers from the confirmed block up to the parent of `self.header`,
+	/// oldest first. They are empty if `self.header` is the confirmed block itself.
+	pub fn confirmed_state_root(
+		&self,
+		confirmation: &SidechainBlockConfirmation,
+		ancestors: &[SidechainHeader],
+	) -> Result<H256, Error> {
+		verify_header_chain(confirmation, ancestors, &self.header)?;
+		Ok(self.header.state_root)
+	}
+
+	/// Verify that `entries` are part of the state committed in the confirmed chain.
+	pub fn verify_values(
+		&self,
+		confirmation: &SidechainBlockConfirmation,
+		ancestors: &[SidechainHeader],
+		entries: &[(Vec<u8>, Vec<u8>)],
+	) -> Result<(), Error> {
+		let state_root = self.confirmed_state_root(confirmation, ancestors)?;
+		verify_state_values(state_root, entries, &self.proof)
+	}
+}
+
+/// Value of a getter executed on the state after the confirmed block, together with the proof
+/// of every storage entry the getter read.
+#[derive(PartialEq, Eq, Clone, Encode, Decode, Debug)]
+pub struct ProvenGetterValue {
+	pub value: Option<Vec<u8>>,
+	/// Storage entries read by the getter, `None` for keys that are absent.
+	pub reads: Vec<(Vec<u8>, Option<Vec<u8>>)>,
+	pub proof: SidechainStateProof,
+}
+
+impl ProvenGetterValue {
+	/// Verify that the storage entries read by the getter are those of the state after the
+	/// confirmed block.
+	///
+	/// The client still has to check that `self.value` follows from `self.reads`, e.g. by
+	/// decoding the value of the storage entry it queried.
+	pub fn verify(&self, confirmation: &SidechainBlockConfirmation) -> Result<(), Error> {
+		let state_root = self.proof.confirmed_state_root(confirmation, &[])?;
+		verify_state_reads(state_root, &self.reads, &self.proof.proof)
+	}
+}
+
+/// Verify that `ancestors` followed by `header` form a chain starting at the confirmed block.
+pub fn verify_header_chain(
+	confirmation: &SidechainBlockConfirmation,
+	ancestors: &[SidechainHeader],
+	header: &SidechainHeader,
+) -> Result<(), Error> {
+	let chain: Vec<&SidechainHeader> = ancestors.iter().chain(Some(header)).collect();
+
+	match chain.first() {
+		Some(first)
+			if first.block_number == confirmation.block_number
+				&& first.hash() == confirmation.block_header_hash => {},
+		_ => return Err(Error::UnconfirmedStateRoot),
+	}
+
+	for (parent, child) in chain.iter().zip(chain.iter().skip(1)) {
+		if child.parent_hash != parent.hash()
+			|| child.block_number != parent.block_number.saturating_add(1)
+		{
+			return Err(Error::UnconfirmedStateRoot)
+		}
+	}
+	Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::traits::Header as HeaderTrait;
+
+	fn child_of(parent: &SidechainHeader) -> SidechainHeader {
+		SidechainHeader::new(
+			parent.block_number + 1,
+			parent.hash(),
+			parent.shard_id,
+			H256::random(),
+			parent.next_finalization_block_number,
+			H256::random(),
+		)
+	}
+
+	fn confirmation_of(header: &SidechainHeader) -> SidechainBlockConfirmation {
+		SidechainBlockConfirmation {
+			block_number: header.block_number,
+			block_header_hash: header.hash(),
+		}
+	}
+
+	#[test]
+	fn header_chain_from_confirmed_block_verifies() {
+		// given
+		let confirmed = SidechainHeader::new(
+			10,
+			H256::random(),
+			H256::random(),
+			H256::random(),
+			10,
+			H256::random(),
+		);
+		let ancestors = vec![confirmed, child_of(&confirmed)];
+		let header = child_of(&ancestors[1]);
+
+		// then
+		assert_eq!(verify_header_chain(&confirmation_of(&confirmed), &ancestors, &header), Ok(()));
+		assert_eq!(verify_header_chain(&confirmation_of(&confirmed), &[], &confirmed), Ok(()));
+	}
+
+	#[test]
+	fn proven_getter_value_verifies_against_confirmed_header() {
+		// given
+		let entries = vec![(vec![1u8], vec![10u8]), (vec![3u8], vec![30u8])];
+		let tree = itp_storage::StateMerkleTree::new(entries.clone());
+		let header = SidechainHeader::new(
+			20,
+			H256::random(),
+			H256::random(),
+			H256::random(),
+			20,
+			tree.root(),
+		);
+		let reads = vec![(vec![1u8], Some(vec![10u8])), (vec![2u8], None)];
+		let proven = ProvenGetterValue {
+			value: Some(vec![10u8]),
+			reads,
+			proof: SidechainStateProof { header, proof: tree.prove_reads(&[[1u8], [2u8]]) },
+		};
+
+		// then
+		assert_eq!(proven.verify(&confirmation_of(&header)), Ok(()));
+		assert_eq!(
+			proven.verify(&confirmation_of(&child_of(&header))),
+			Err(Error::UnconfirmedStateRoot)
+		);
+	}
+
+	#[test]
+	fn header_chain_with_gap_is_rejected() {
+		// given
+		let confirmed = SidechainHeader::new(
+			10,
+			H256::random(),
+			H256::random(),
+			H256::random(),
+			10,
+			H256::random(),
+		);
+		let header = child_of(&child_of(&confirmed));
+
+		// then
+		assert_eq!(
+			verify_header_chain(&confirmation_of(&confirmed), &[confirmed], &header),
+			Err(Error::UnconfirmedStateRoot)
+		);
+	}
+
+	#[test]
+	fn header_chain_from_unconfirmed_block_is_rejected() {
+		// given
+		let confirmed = SidechainHeader::new(
+			10,
+			H256::random(),
+			H256::random(),
+			H256::random(),
+			10,
+			H256::random(),
+		);
+		let header = child_of(&confirmed);
+
+		// then
+		assert_eq!(
+			verify_header_chain(&confirmation_of(&confirmed), &[], &header),
+			Err(Error::UnconfirmedStateRoot)
+		);
+	}
+}
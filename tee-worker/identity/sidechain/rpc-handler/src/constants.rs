@@ -22,3 +22,7 @@ pub const RPC_METHOD_NAME_IMPORT_BLOCKS: &str = "sidechain_importBlock";
 pub const RPC_METHOD_NAME_FETCH_BLOCKS_FROM_PEER: &str = "sidechain_fetchBlocksFromPeer";
 pub const RPC_METHOD_NAME_LATEST_BLOCK: &str = "sidechain_latestBlock";
 pub const RPC_METHOD_NAME_BLOCK_HASH: &str = "sidechain_blockHash";
+pub const RPC_METHOD_NAME_GET_BLOCK_BY_NUMBER: &str = "sidechain_getBlockByNumber";
+pub const RPC_METHOD_NAME_GET_BLOCK_BY_HASH: &str = "sidechain_getBlockByHash";
+pub const RPC_METHOD_NAME_GET_BLOCKS: &str = "sidechain_getBlocks";
+pub const RPC_METHOD_NAME_GET_BLOCK_HEADER: &str = "sidechain_getBlockHeader";
@@ -15,7 +15,7 @@ use itp_types::{DirectRequestStatus, RsaRequest, ShardIdentifier, TrustedOperati
 use itp_utils::{FromHexPrefixed, ToHexPrefixed};
 use jsonrpc_core::{futures::executor, serde_json::json, Error as RpcError, IoHandler, Params};
 use lc_native_task_sender::{NativeTask, NativeTaskSender};
-use lc_vc_task_sender::{VCRequest, VcRequestSender};
+use lc_vc_task_sender::{RequestVcOptions, VCRequest, VcRequestSender};
 use litentry_primitives::AesRequest;
 use log::{debug, error, warn};
 use sp_core::{blake2_256, H256};
@@ -218,6 +218,7 @@ pub fn add_top_pool_direct_rpc_methods<R, TCS, G>(
 		}
 	});
 
+	let pending_calls_author = top_pool_author.clone();
 	io_handler.add_sync_method("author_pendingTrustedCallsFor", move |params: Params| {
 		debug!("worker_api_direct rpc was called: author_pendingTrustedCallsFor");
 		match params.parse::<(String, String)>() {
@@ -238,7 +239,8 @@ pub fn add_top_pool_direct_rpc_methods<R, TCS, G>(
 						return Ok(json!(compute_hex_encoded_return_error(error_msg.as_str())))
 					},
 				};
-				let trusted_calls = top_pool_author.get_pending_trusted_calls_for(shard, &account);
+				let trusted_calls =
+					pending_calls_author.get_pending_trusted_calls_for(shard, &account);
 				let json_value = RpcReturnValue {
 					do_watch: false,
 					value: trusted_calls.encode(),
@@ -253,6 +255,51 @@ pub fn add_top_pool_direct_rpc_methods<R, TCS, G>(
 			},
 		}
 	});
+
+	// author_getTrustedOperationStatus, also answers for operations that could not be
+	// restored after a restart of the worker
+	io_handler.add_sync_method("author_getTrustedOperationStatus", move |params: Params| {
+		debug!("worker_api_direct rpc was called: author_getTrustedOperationStatus");
+		match params.parse::<(String, String)>() {
+			Ok((shard_base58, hash_hex)) => {
+				let shard = match decode_shard_from_base58(shard_base58.as_str()) {
+					Ok(id) => id,
+					Err(msg) => {
+						let error_msg: String =
+							format!("Could not retrieve trusted operation status due to: {}", msg);
+						return Ok(json!(compute_hex_encoded_return_error(error_msg.as_str())))
+					},
+				};
+				let hash = match H256::from_hex(hash_hex.as_str()) {
+					Ok(hash) => hash,
+					Err(msg) => {
+						let error_msg: String = format!(
+							"Could not retrieve trusted operation status due to: {:?}",
+							msg
+						);
+						return Ok(json!(compute_hex_encoded_return_error(error_msg.as_str())))
+					},
+				};
+				match top_pool_author.get_trusted_operation_status(shard, &hash) {
+					Some(status) => {
+						let json_value = RpcReturnValue {
+							do_watch: false,
+							value: status.encode(),
+							status: DirectRequestStatus::Ok,
+						};
+						Ok(json!(json_value.to_hex()))
+					},
+					None =>
+						Ok(json!(compute_hex_encoded_return_error("Unknown trusted operation"))),
+				}
+			},
+			Err(e) => {
+				let error_msg: String =
+					format!("Could not retrieve trusted operation status due to: {}", e);
+				Ok(json!(compute_hex_encoded_return_error(error_msg.as_str())))
+			},
+		}
+	});
 }
 
 // converts the rpc methods vector to a string and adds commas and brackets for readability
@@ -336,12 +383,18 @@ where
 	response.map_err(|e| format!("{:?}", e))
 }
 
+// the optional second param is a hex-encoded `RequestVcOptions`
 fn author_submit_request_vc_inner(params: Params) -> Result<H256, String> {
-	let payload = get_request_payload(params)?;
-	let request = AesRequest::from_hex(&payload).map_err(|e| format!("{:?}", e))?;
+	let s_vec = params.parse::<Vec<String>>().map_err(|e| format!("{}", e))?;
+	let payload = s_vec.get(0).ok_or_else(|| "Empty params".to_string())?;
+	let options = match s_vec.get(1) {
+		Some(options) => RequestVcOptions::from_hex(options).map_err(|e| format!("{:?}", e))?,
+		None => RequestVcOptions::default(),
+	};
+	let request = AesRequest::from_hex(payload).map_err(|e| format!("{:?}", e))?;
 
 	let vc_request_sender = VcRequestSender::new();
-	if let Err(err) = vc_request_sender.send(VCRequest { request: request.clone() }) {
+	if let Err(err) = vc_request_sender.send(VCRequest { request: request.clone(), options }) {
 		let error_msg = format!("failed to send AesRequest within request_vc: {:?}", err);
 		error!("{}", error_msg);
 		Err(error_msg)
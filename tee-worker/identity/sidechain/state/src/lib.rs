@@ -25,9 +25,13 @@ extern crate sgx_tstd as std;
 
 mod error;
 mod impls;
+mod migration;
+mod state_tree_cache;
 
 pub use error::*;
 pub use impls::*;
+pub use migration::*;
+pub use state_tree_cache::{with_confirmed_state, ConfirmedState, STATE_TREE_CACHE_SIZE};
 
 #[cfg(all(not(feature = "std"), feature = "sgx"))]
 mod sgx_reexports {
@@ -35,7 +39,11 @@ mod sgx_reexports {
 }
 
 use codec::{Decode, Encode};
-use itp_sgx_externalities::{SgxExternalitiesDiffType, SgxExternalitiesTrait, StateHash};
+use itp_sgx_externalities::{
+	SgxExternalities, SgxExternalitiesDiffType, SgxExternalitiesTrait, SgxExternalitiesType,
+	StateHash,
+};
+use itp_storage::{storage_value_key, StateMerkleTree};
 use its_primitives::{
 	traits::Block as SidechainBlockTrait,
 	types::{BlockHash, BlockNumber, Timestamp},
@@ -43,6 +51,7 @@ use its_primitives::{
 use sp_core::H256;
 use sp_io::KillStorageResult;
 use sp_runtime::traits::Header as ParentchainHeaderTrait;
+use std::vec::Vec;
 
 /// Contains the necessary data to update the `SidechainDB` when importing a `SidechainBlock`.
 #[derive(PartialEq, Eq, Clone, Debug, Encode, Decode)]
@@ -139,6 +148,106 @@ impl<SidechainBlock: SidechainBlockTrait, E: SidechainState + SidechainSystemExt
 	}
 }
 
+/// Merkle commitment to the sidechain state, included in the sidechain block header.
+pub trait StateRootExt {
+	/// Build the merkle tree over the state.
+	///
+	/// The last block entries are excluded, as they are only written when the block carrying
+	/// the root is imported.
+	fn state_merkle_tree(&self) -> StateMerkleTree;
+
+	/// Get the merkle root of the state.
+	fn state_root(&self) -> H256 {
+		self.state_merkle_tree().root()
+	}
+
+	/// Get the merkle root of the state, starting from the tree of the last imported block.
+	///
+	/// Only the entries changed since that import are hashed. The whole tree is built if the
+	/// tree of `parent_root` is not cached or the changed entries are not known.
+	fn state_root_from(&self, parent_root: Option<H256>) -> H256;
+
+	/// Like [`Self::state_root_from`], but also caches the resulting tree and restarts tracking
+	/// the changed entries, so it becomes the base of the next block.
+	fn update_state_tree(&mut self, parent_root: Option<H256>) -> H256;
+}
+
+impl<T: SgxExternalitiesTrait<SgxExternalitiesType = SgxExternalitiesType>> StateRootExt for T {
+	fn state_merkle_tree(&self) -> StateMerkleTree {
+		let excluded = excluded_from_state_root();
+		StateMerkleTree::new(self.state().iter().filter(|(k, _)| !excluded.contains(*k)))
+	}
+
+	fn state_root_from(&self, parent_root: Option<H256>) -> H256 {
+		state_tree_from(self, parent_root).root()
+	}
+
+	fn update_state_tree(&mut self, parent_root: Option<H256>) -> H256 {
+		let tree = state_tree_from(self, parent_root);
+		let root = tree.root();
+		state_tree_cache::cache_tree(tree);
+		self.take_changed_keys();
+		root
+	}
+}
+
+fn excluded_from_state_root() -> [Vec<u8>; 2] {
+	[storage_value_key("System", "LastHash"), storage_value_key("System", "LastBlock")]
+}
+
+fn state_tree_from<T: SgxExternalitiesTrait<SgxExternalitiesType = SgxExternalitiesType>>(
+	state: &T,
+	parent_root: Option<H256>,
+) -> StateMerkleTree {
+	let parent_tree = parent_root.and_then(|root| state_tree_cache::cached_tree(&root));
+	match (parent_tree, state.changed_keys()) {
+		(Some(mut tree), Some(changed_keys)) => {
+			let excluded = excluded_from_state_root();
+			tree.apply_diff(
+				changed_keys
+					.iter()
+					.filter(|k| !excluded.contains(*k))
+					.map(|k| (k, state.state().get(k))),
+			);
+			tree
+		},
+		_ => state.state_merkle_tree(),
+	}
+}
+
+/// Keeps the state after the sidechain blocks whose import is confirmed on the parentchain.
+pub trait ConfirmedStateExt {
+	/// Keep a copy of the state as the state after the confirmed block with `header`.
+	fn retain_as_confirmed<Shard: Encode, Header: Encode>(
+		&self,
+		shard: &Shard,
+		header: &Header,
+		state_root: H256,
+	);
+}
+
+impl<T: SgxExternalitiesTrait<SgxExternalitiesType = SgxExternalitiesType>> ConfirmedStateExt
+	for T
+{
+	fn retain_as_confirmed<Shard: Encode, Header: Encode>(
+		&self,
+		shard: &Shard,
+		header: &Header,
+		state_root: H256,
+	) {
+		let tree =
+			state_tree_cache::cached_tree(&state_root).unwrap_or_else(|| self.state_merkle_tree());
+		state_tree_cache::set_confirmed_state(
+			shard,
+			ConfirmedState {
+				header: header.encode(),
+				state: SgxExternalities::new(self.state().clone()),
+				tree,
+			},
+		);
+	}
+}
+
 /// System extension for the `SidechainDB`.
 pub trait SidechainSystemExt {
 	/// Get the last block number.
@@ -0,0 +1,171 @@
+// Copyright 2020-2024 Trust Computing GmbH.
+// This file is part of Litentry.
+//
+// Litentry is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// Litentry is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with Litentry.  If not, see <https://www.gnu.org/licenses/>.
+
+//! Migration of the last block kept in the sidechain state.
+
+use crate::{SidechainState, SidechainSystemExt, StateRootExt};
+use codec::{Decode, DecodeAll, Encode};
+use itp_sgx_externalities::SgxExternalitiesTrait;
+use itp_storage::storage_value_key;
+use its_primitives::{
+	traits::{Block as BlockTrait, Header as HeaderTrait},
+	types::{block_data::BlockData, header::SidechainHeader, Block, ShardIdentifier},
+};
+use sp_core::H256;
+use sp_runtime::traits::{BlakeTwo256, Hash};
+
+/// Sidechain header before it committed to the state root.
+#[derive(Encode, Decode)]
+struct LegacySidechainHeader {
+	parent_hash: H256,
+	block_number: u64,
+	shard_id: ShardIdentifier,
+	block_data_hash: H256,
+	next_finalization_block_number: u64,
+}
+
+#[derive(Encode, Decode)]
+struct LegacySidechainBlock {
+	header: LegacySidechainHeader,
+	block_data: BlockData,
+}
+
+impl From<&SidechainHeader> for LegacySidechainHeader {
+	fn from(header: &SidechainHeader) -> Self {
+		LegacySidechainHeader {
+			parent_hash: header.parent_hash,
+			block_number: header.block_number,
+			shard_id: header.shard_id,
+			block_data_hash: header.block_data_hash,
+			next_finalization_block_number: header.next_finalization_block_number,
+		}
+	}
+}
+
+/// Hash of the header in the format before it committed to the state root.
+///
+/// It's the last block hash kept in the state of a migrated last block.
+pub fn legacy_header_hash(header: &SidechainHeader) -> H256 {
+	LegacySidechainHeader::from(header).using_encoded(BlakeTwo256::hash)
+}
+
+/// Migrate the last block kept in the state to the current header format.
+///
+/// The last block of a state written before sidechain headers committed to the state root can't
+/// be decoded anymore. Its header is re-created with the root of the current state, which the
+/// next block then builds on. The re-created header has a different hash, so the last block
+/// hash is left at the hash of the confirmed block, the next block links to it as its parent.
+/// Returns whether the last block was migrated.
+pub fn migrate_last_block<T: SidechainState + SgxExternalitiesTrait + StateRootExt>(
+	state: &mut T,
+) -> bool {
+	let legacy = match state
+		.get(&storage_value_key("System", "LastBlock"))
+		.and_then(|encoded| LegacySidechainBlock::decode_all(&mut encoded.as_slice()).ok())
+	{
+		Some(legacy) => legacy,
+		None => return false,
+	};
+
+	// a current block may happen to decode as a legacy one, but not with the same hash
+	if state.get_last_block_hash() != Some(legacy.header.using_encoded(BlakeTwo256::hash)) {
+		return false
+	}
+
+	let header = SidechainHeader::new(
+		legacy.header.block_number,
+		legacy.header.parent_hash,
+		legacy.header.shard_id,
+		legacy.header.block_data_hash,
+		legacy.header.next_finalization_block_number,
+		state.state_root(),
+	);
+	state.set_with_name("System", "LastBlock", &Block::new(header, legacy.block_data));
+	true
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::LastBlockExt;
+	use itp_sgx_externalities::SgxExternalities;
+	use sp_core::ed25519;
+
+	fn legacy_block() -> LegacySidechainBlock {
+		LegacySidechainBlock {
+			header: LegacySidechainHeader {
+				parent_hash: H256::random(),
+				block_number: 42,
+				shard_id: H256::random(),
+				block_data_hash: H256::random(),
+				next_finalization_block_number: 60,
+			},
+			block_data: BlockData {
+				timestamp: 1,
+				layer_one_head: H256::random(),
+				block_author: ed25519::Public::from_raw([1; 32]),
+				signed_top_hashes: vec![H256::random()],
+				encrypted_state_diff: vec![1, 2, 3],
+			},
+		}
+	}
+
+	#[test]
+	fn legacy_last_block_is_migrated() {
+		// given
+		let mut state = SgxExternalities::default();
+		let legacy = legacy_block();
+		let legacy_hash = legacy.header.using_encoded(BlakeTwo256::hash);
+		state.set_with_name("System", "LastBlock", &legacy);
+		state.set_last_block_hash(&legacy_hash);
+
+		// when
+		let migrated = migrate_last_block(&mut state);
+
+		// then
+		assert!(migrated);
+		let block: Block = state.get_last_block().unwrap();
+		assert_eq!(block.header().block_number(), 42);
+		assert_eq!(block.header().state_root(), state.state_root());
+		assert_eq!(block.block_data(), &legacy.block_data);
+		assert_eq!(state.get_last_block_hash(), Some(legacy_hash));
+		assert_eq!(legacy_header_hash(block.header()), legacy_hash);
+	}
+
+	#[test]
+	fn current_last_block_is_not_migrated() {
+		// given
+		let mut state = SgxExternalities::default();
+		let legacy = legacy_block();
+		let header = SidechainHeader::new(
+			1,
+			H256::random(),
+			H256::random(),
+			H256::random(),
+			1,
+			H256::random(),
+		);
+		let block = Block::new(header, legacy.block_data);
+		state.set_last_block(&block);
+
+		// when
+		let migrated = migrate_last_block(&mut state);
+
+		// then
+		assert!(!migrated);
+		assert_eq!(state.get_last_block(), Some(block));
+	}
+}
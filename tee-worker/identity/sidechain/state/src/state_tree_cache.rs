@@ -0,0 +1,84 @@
+// Copyright 2020-2024 Trust Computing GmbH.
+// This file is part of Litentry.
+//
+// Litentry is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// Litentry is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with Litentry.  If not, see <https://www.gnu.org/licenses/>.
+
+//! In-memory caches of sidechain state trees.
+//!
+//! The trees of the last imported blocks are kept, so the state root of the next block only
+//! hashes the entries that changed. The state of the latest confirmed block is kept as well,
+//! to serve storage proofs against a header the parentchain knows.
+
+#[cfg(feature = "std")]
+use std::sync::RwLock;
+
+#[cfg(all(not(feature = "std"), feature = "sgx"))]
+use std::sync::SgxRwLock as RwLock;
+
+use codec::Encode;
+use itp_sgx_externalities::SgxExternalities;
+use itp_storage::StateMerkleTree;
+use lazy_static::lazy_static;
+use sp_core::H256;
+use std::{
+	collections::{BTreeMap, VecDeque},
+	vec::Vec,
+};
+
+/// Number of state trees that are kept, newest first.
+pub const STATE_TREE_CACHE_SIZE: usize = 4;
+
+lazy_static! {
+	static ref STATE_TREES: RwLock<VecDeque<(H256, StateMerkleTree)>> = Default::default();
+	static ref CONFIRMED_STATES: RwLock<BTreeMap<Vec<u8>, ConfirmedState>> = Default::default();
+}
+
+/// State after the latest sidechain block whose import is confirmed on the parentchain.
+pub struct ConfirmedState {
+	/// Encoded header of the confirmed block.
+	pub header: Vec<u8>,
+	pub state: SgxExternalities,
+	pub tree: StateMerkleTree,
+}
+
+pub(crate) fn cached_tree(root: &H256) -> Option<StateMerkleTree> {
+	let trees = STATE_TREES.read().ok()?;
+	trees.iter().find(|(r, _)| r == root).map(|(_, tree)| tree.clone())
+}
+
+pub(crate) fn cache_tree(tree: StateMerkleTree) {
+	if let Ok(mut trees) = STATE_TREES.write() {
+		let root = tree.root();
+		trees.retain(|(r, _)| *r != root);
+		trees.push_front((root, tree));
+		trees.truncate(STATE_TREE_CACHE_SIZE);
+	}
+}
+
+pub(crate) fn set_confirmed_state<Shard: Encode>(shard: &Shard, confirmed: ConfirmedState) {
+	if let Ok(mut states) = CONFIRMED_STATES.write() {
+		states.insert(shard.encode(), confirmed);
+	}
+}
+
+/// Execute `f` on the state of the latest confirmed block of `shard`.
+///
+/// Returns `None` if no confirmed block was imported since the enclave started.
+pub fn with_confirmed_state<Shard: Encode, R>(
+	shard: &Shard,
+	f: impl FnOnce(&mut ConfirmedState) -> R,
+) -> Option<R> {
+	let mut states = CONFIRMED_STATES.write().ok()?;
+	states.get_mut(&shard.encode()).map(f)
+}
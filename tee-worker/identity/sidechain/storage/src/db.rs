@@ -18,7 +18,7 @@
 use super::{Error, Result};
 use codec::{Decode, Encode};
 use rocksdb::{WriteBatch, DB};
-use std::path::PathBuf;
+use std::{path::PathBuf, sync::Arc};
 
 /// Sidechain DB Storage structure:
 /// STORED_SHARDS_KEY -> Vec<(Shard)>
@@ -28,12 +28,25 @@ use std::path::PathBuf;
 
 /// Interface struct to rocks DB
 pub struct SidechainDB {
-	db: DB,
+	db: Arc<DB>,
+}
+
+/// Handle to compact the DB without access to the sidechain storage, e.g. outside of its lock
+#[derive(Clone)]
+pub struct DbCompaction {
+	db: Arc<DB>,
+}
+
+impl DbCompaction {
+	/// compacts the whole key range, so the disk space of deleted entries is reclaimed
+	pub fn compact(&self) {
+		self.db.compact_range(None::<&[u8]>, None::<&[u8]>)
+	}
 }
 
 impl SidechainDB {
 	pub fn open_default(path: PathBuf) -> Result<SidechainDB> {
-		Ok(SidechainDB { db: DB::open_default(path)? })
+		Ok(SidechainDB { db: Arc::new(DB::open_default(path)?) })
 	}
 
 	/// returns the decoded value of the DB entry, if there is one
@@ -59,6 +72,18 @@ impl SidechainDB {
 		batch.delete(key.encode())
 	}
 
+	/// returns a handle to compact the DB
+	pub fn compaction(&self) -> DbCompaction {
+		DbCompaction { db: self.db.clone() }
+	}
+
+	/// returns the estimated size of the DB in bytes (sst files and memtables)
+	pub fn disk_usage(&self) -> Result<u64> {
+		let sst_files = self.db.property_int_value("rocksdb.total-sst-files-size")?;
+		let mem_tables = self.db.property_int_value("rocksdb.cur-size-all-mem-tables")?;
+		Ok(sst_files.unwrap_or_default().saturating_add(mem_tables.unwrap_or_default()))
+	}
+
 	/// add an entry to the DB
 	#[cfg(test)]
 	pub fn put<K: Encode, V: Encode>(&mut self, key: K, value: V) -> Result<()> {
@@ -68,4 +68,36 @@ impl FetchBlocks<SignedBlock> for FetchBlocksMock {
 	) -> Option<LastSidechainBlock> {
 		None
 	}
+
+	fn fetch_blocks_from_number(
+		&self,
+		_from: BlockNumber,
+		_shard_identifier: &ShardIdentifierFor<SignedBlock>,
+	) -> Result<Vec<SignedBlock>> {
+		Ok(self.blocks_to_be_fetched.clone())
+	}
+	fn fetch_block(&self, block_hash: &BlockHash) -> Result<Option<SignedBlock>> {
+		Ok(self
+			.blocks_to_be_fetched
+			.iter()
+			.find(|b| b.block.hash() == *block_hash)
+			.cloned())
+	}
+
+	fn fetch_blocks_by_number(
+		&self,
+		from: BlockNumber,
+		count: u64,
+		_shard_identifier: &ShardIdentifierFor<SignedBlock>,
+	) -> Result<Vec<SignedBlock>> {
+		Ok(self
+			.blocks_to_be_fetched
+			.iter()
+			.filter(|b| {
+				b.block.header.block_number >= from
+					&& b.block.header.block_number < from.saturating_add(count)
+			})
+			.cloned()
+			.collect())
+	}
 }
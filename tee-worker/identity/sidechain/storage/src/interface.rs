@@ -21,6 +21,7 @@ use mockall::predicate::*;
 use mockall::*;
 
 use super::{
+	retention::{RetentionPolicy, RetentionStatus},
 	storage::{LastSidechainBlock, SidechainStorage},
 	Result,
 };
@@ -29,7 +30,10 @@ use its_primitives::{
 	types::{BlockHash, BlockNumber},
 };
 use parking_lot::RwLock;
-use std::path::PathBuf;
+use std::{
+	path::PathBuf,
+	time::{SystemTime, UNIX_EPOCH},
+};
 
 /// Lock wrapper around sidechain storage
 pub struct SidechainStorageLock<SignedBlock: SignedBlockT> {
@@ -56,6 +60,9 @@ pub trait BlockStorage<SignedBlock: SignedBlockT> {
 pub trait BlockPruner {
 	/// Prune all blocks except the newest n, where n = `number_of_blocks_to_keep`.
 	fn prune_blocks_except(&self, number_of_blocks_to_keep: u64);
+
+	/// Prune blocks according to the given retention policy and return the resulting retention status.
+	fn apply_retention_policy(&self, policy: &RetentionPolicy) -> RetentionStatus;
 }
 
 #[cfg_attr(test, automock)]
@@ -92,6 +99,26 @@ pub trait FetchBlocks<SignedBlock: SignedBlockT> {
 		block_number: BlockNumber,
 		shard_identifier: &ShardIdentifierFor<SignedBlock>,
 	) -> Option<LastSidechainBlock>;
+
+	/// Fetch all stored blocks from block number `from` (inclusive) until the last block.
+	fn fetch_blocks_from_number(
+		&self,
+		from: BlockNumber,
+		shard_identifier: &ShardIdentifierFor<SignedBlock>,
+	) -> Result<Vec<SignedBlock>>;
+
+	/// Fetch a block by its hash.
+	fn fetch_block(&self, block_hash: &BlockHash) -> Result<Option<SignedBlock>>;
+
+	/// Fetch up to `count` stored blocks, starting at block number `from` (inclusive).
+	///
+	/// Blocks which are no longer in storage are skipped.
+	fn fetch_blocks_by_number(
+		&self,
+		from: BlockNumber,
+		count: u64,
+		shard_identifier: &ShardIdentifierFor<SignedBlock>,
+	) -> Result<Vec<SignedBlock>>;
 }
 
 impl<SignedBlock: SignedBlockT> BlockStorage<SignedBlock> for SidechainStorageLock<SignedBlock> {
@@ -104,6 +131,30 @@ impl<SignedBlock: SignedBlockT> BlockPruner for SidechainStorageLock<SignedBlock
 	fn prune_blocks_except(&self, number_of_blocks_to_keep: BlockNumber) {
 		self.storage.write().prune_shards(number_of_blocks_to_keep);
 	}
+
+	fn apply_retention_policy(&self, policy: &RetentionPolicy) -> RetentionStatus {
+		let now = SystemTime::now()
+			.duration_since(UNIX_EPOCH)
+			.map(|d| d.as_millis() as u64)
+			.unwrap_or_default();
+		let mut pruned_blocks = 0u64;
+		if let RetentionPolicy::Prune(limits) = policy {
+			// compacting can take a while, block import must not wait for it
+			let compaction = self.storage.read().compaction();
+			pruned_blocks = self.storage.write().prune_by_limits(limits, now);
+			if pruned_blocks > 0 {
+				compaction.compact();
+			}
+			if let Some(max_disk_bytes) = limits.max_disk_bytes {
+				let pruned = self.storage.write().prune_to_disk_budget(max_disk_bytes);
+				if pruned > 0 {
+					pruned_blocks = pruned_blocks.saturating_add(pruned);
+					compaction.compact();
+				}
+			}
+		}
+		self.storage.read().retention_status(policy.is_archive(), pruned_blocks)
+	}
 }
 
 impl<SignedBlock: SignedBlockT> FetchBlocks<SignedBlock> for SidechainStorageLock<SignedBlock> {
@@ -151,4 +202,25 @@ impl<SignedBlock: SignedBlockT> FetchBlocks<SignedBlock> for SidechainStorageLoc
 			},
 		}
 	}
+
+	fn fetch_blocks_from_number(
+		&self,
+		from: BlockNumber,
+		shard_identifier: &ShardIdentifierFor<SignedBlock>,
+	) -> Result<Vec<SignedBlock>> {
+		self.storage.read().get_blocks_from_number(shard_identifier, from)
+	}
+
+	fn fetch_block(&self, block_hash: &BlockHash) -> Result<Option<SignedBlock>> {
+		self.storage.read().get_block(block_hash)
+	}
+
+	fn fetch_blocks_by_number(
+		&self,
+		from: BlockNumber,
+		count: u64,
+		shard_identifier: &ShardIdentifierFor<SignedBlock>,
+	) -> Result<Vec<SignedBlock>> {
+		self.storage.read().get_blocks_by_number(shard_identifier, from, count)
+	}
 }
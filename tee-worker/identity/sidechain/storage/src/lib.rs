@@ -17,7 +17,6 @@
 
 #![cfg_attr(test, feature(assert_matches))]
 
-use its_primitives::types::BlockNumber;
 use std::{
 	sync::Arc,
 	thread,
@@ -27,11 +26,18 @@ use std::{
 mod db;
 mod error;
 pub mod interface;
+mod retention;
 mod storage;
 
 #[cfg(test)]
 mod storage_tests_get_blocks_after;
 
+#[cfg(test)]
+mod storage_tests_get_blocks_by_number;
+
+#[cfg(test)]
+mod storage_tests_get_blocks_from_number;
+
 #[cfg(test)]
 mod storage_tests_get_blocks_in_range;
 
@@ -43,14 +49,20 @@ pub mod fetch_blocks_mock;
 
 pub use error::{Error, Result};
 pub use interface::{BlockPruner, BlockStorage, SidechainStorageLock};
-pub use storage::LastSidechainBlock;
+pub use retention::{PruningLimits, RetentionPolicy, RetentionStatus};
+pub use storage::{LastSidechainBlock, SIDECHAIN_STORAGE_VERSION};
 
-pub fn start_sidechain_pruning_loop<D>(
+/// Periodically applies the retention policy to the sidechain storage.
+///
+/// `report_status` is called with the retention status after every run, also in archive mode.
+pub fn start_sidechain_pruning_loop<D, F>(
 	storage: &Arc<D>,
 	purge_interval: u64,
-	purge_limit: BlockNumber,
+	retention_policy: RetentionPolicy,
+	report_status: F,
 ) where
 	D: BlockPruner,
+	F: Fn(&RetentionStatus),
 {
 	let interval_time = Duration::from_secs(purge_interval);
 	let mut interval_start = SystemTime::now();
@@ -59,7 +71,8 @@ pub fn start_sidechain_pruning_loop<D>(
 			if elapsed >= interval_time {
 				// update interval time
 				interval_start = SystemTime::now();
-				storage.prune_blocks_except(purge_limit);
+				let status = storage.apply_retention_policy(&retention_policy);
+				report_status(&status);
 			} else {
 				// sleep for the rest of the interval
 				let sleep_time = interval_time - elapsed;
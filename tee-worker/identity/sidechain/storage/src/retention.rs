@@ -0,0 +1,70 @@
+// Copyright 2020-2024 Trust Computing GmbH.
+// This file is part of Litentry.
+//
+// Litentry is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// Litentry is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with Litentry.  If not, see <https://www.gnu.org/licenses/>.
+
+use itp_settings::files::SIDECHAIN_PURGE_LIMIT;
+use its_primitives::types::BlockNumber;
+use std::time::Duration;
+
+/// Decides which sidechain blocks are kept by the pruning loop.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum RetentionPolicy {
+	/// Never prune any blocks, e.g. for indexer nodes that need the full sidechain history.
+	Archive,
+	/// Prune the oldest blocks as soon as they exceed any of the given limits.
+	///
+	/// The last block of a shard is always kept.
+	Prune(PruningLimits),
+}
+
+impl Default for RetentionPolicy {
+	fn default() -> Self {
+		RetentionPolicy::Prune(PruningLimits {
+			max_blocks: Some(SIDECHAIN_PURGE_LIMIT),
+			..Default::default()
+		})
+	}
+}
+
+impl RetentionPolicy {
+	pub fn is_archive(&self) -> bool {
+		matches!(self, RetentionPolicy::Archive)
+	}
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct PruningLimits {
+	/// Keep at most the newest n blocks of every shard.
+	pub max_blocks: Option<BlockNumber>,
+	/// Prune blocks whose timestamp is older than the given age.
+	pub max_age: Option<Duration>,
+	/// Prune the oldest blocks of every shard while the database is larger than the given number of bytes.
+	pub max_disk_bytes: Option<u64>,
+}
+
+/// Retention status of the sidechain storage, as reported after each pruning run.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct RetentionStatus {
+	/// whether the storage runs in archive mode
+	pub archive: bool,
+	/// number of blocks in storage, over all shards
+	pub stored_blocks: u64,
+	/// number of blocks pruned in the last run
+	pub pruned_blocks: u64,
+	/// timestamp (ms) of the oldest block in storage, over all shards
+	pub oldest_block_timestamp: Option<u64>,
+	/// estimated size of the database in bytes
+	pub disk_usage_bytes: u64,
+}
@@ -15,11 +15,18 @@
 
 */
 
-use super::{db::SidechainDB, Error, Result};
+use super::{
+	db::{DbCompaction, SidechainDB},
+	retention::{PruningLimits, RetentionPolicy, RetentionStatus},
+	Error, Result,
+};
 use codec::{Decode, Encode};
 use itp_settings::files::SIDECHAIN_STORAGE_PATH;
 use its_primitives::{
-	traits::{Block as BlockTrait, Header as HeaderTrait, SignedBlock as SignedBlockT},
+	traits::{
+		Block as BlockTrait, BlockData as BlockDataTrait, Header as HeaderTrait,
+		SignedBlock as SignedBlockT,
+	},
 	types::{BlockHash, BlockNumber},
 };
 use log::*;
@@ -32,6 +39,14 @@ use std::{collections::HashMap, fmt::Debug, path::PathBuf};
 const LAST_BLOCK_KEY: &[u8] = b"last_sidechainblock";
 /// key value of the stored shards vector
 const STORED_SHARDS_KEY: &[u8] = b"stored_shards";
+/// key value of the version of the stored blocks
+const STORAGE_VERSION_KEY: &[u8] = b"sidechain_storage_version";
+
+/// Version of the stored blocks. Stored blocks of a lower version can't be decoded anymore
+/// and are purged on load.
+///
+/// 1: the sidechain header commits to a state root.
+pub const SIDECHAIN_STORAGE_VERSION: u32 = 1;
 
 /// ShardIdentifier type
 type ShardIdentifierFor<B> =
@@ -76,9 +91,49 @@ impl<SignedBlock: SignedBlockT> SidechainStorage<SignedBlock> {
 				error!("Sidechain storage of shard {:?} is empty", shard);
 			}
 		}
+		storage.migrate()?;
 		Ok(storage)
 	}
 
+	/// Hard reset of a storage with a lower storage version: all its shards are purged.
+	///
+	/// Stored blocks of a lower version are not migrated. Their signed headers can't be
+	/// re-created with a state root without invalidating the author signature and the block
+	/// hash, so their history is dropped on every worker which is upgraded, and is not
+	/// available from peers afterwards either. The sidechain continues from the last block
+	/// kept in the state, see `its_state::migrate_last_block`.
+	///
+	/// The version is only written once all shards are purged, a failed purge is retried on
+	/// the next load.
+	fn migrate(&mut self) -> Result<()> {
+		// a storage without version and without shards is a new one
+		let version = match self.db.get::<_, u32>(STORAGE_VERSION_KEY)? {
+			Some(version) => version,
+			None if self.shards.is_empty() => SIDECHAIN_STORAGE_VERSION,
+			None => 0,
+		};
+		if version < SIDECHAIN_STORAGE_VERSION {
+			warn!(
+				"Dropping sidechain blocks of storage version {}, current version is {}",
+				version, SIDECHAIN_STORAGE_VERSION
+			);
+			for shard in self.shards.clone() {
+				// a shard without last block has no blocks to purge
+				if !self.last_blocks.contains_key(&shard) {
+					continue
+				}
+				self.purge_shard(&shard).map_err(|e| {
+					error!("Could not purge sidechain storage of shard {:?}: {:?}", shard, e);
+					e
+				})?;
+			}
+			self.shards.clear();
+			self.last_blocks.clear();
+			self.db.put(STORED_SHARDS_KEY, &self.shards)?;
+		}
+		self.db.put(STORAGE_VERSION_KEY, SIDECHAIN_STORAGE_VERSION)
+	}
+
 	/// gets all shards of currently loaded sidechain db
 	pub fn shards(&self) -> &Vec<ShardIdentifierFor<SignedBlock>> {
 		&self.shards
@@ -102,7 +157,6 @@ impl<SignedBlock: SignedBlockT> SidechainStorage<SignedBlock> {
 	}
 
 	/// gets the block of the given blockhash, if there is such a block
-	#[allow(unused)]
 	pub fn get_block(&self, block_hash: &BlockHash) -> Result<Option<SignedBlock>> {
 		self.db.get(block_hash)
 	}
@@ -165,6 +219,68 @@ impl<SignedBlock: SignedBlockT> SidechainStorage<SignedBlock> {
 			.collect())
 	}
 
+	/// Get the stored blocks of a shard from block number `from` (inclusive) until the last block.
+	///
+	/// Returns the blocks oldest first. Stops at the first block that is no longer in storage,
+	/// e.g. because it has been pruned.
+	pub fn get_blocks_from_number(
+		&self,
+		shard_identifier: &ShardIdentifierFor<SignedBlock>,
+		from: BlockNumber,
+	) -> Result<Vec<SignedBlock>> {
+		let last_block = self.get_last_block_of_shard(shard_identifier)?;
+
+		let mut blocks_to_return = Vec::<SignedBlock>::new();
+		let mut current_block_number = last_block.number;
+		while current_block_number >= from {
+			let block_hash = match self.get_block_hash(shard_identifier, current_block_number)? {
+				Some(block_hash) => block_hash,
+				None => break,
+			};
+			let block = self.get_block(&block_hash)?.ok_or(Error::FailedToFindParentBlock)?;
+			blocks_to_return.push(block);
+
+			if current_block_number == 0 {
+				break
+			}
+			current_block_number -= 1;
+		}
+
+		// Reverse because we iterate from newest to oldest, but result should be oldest first.
+		blocks_to_return.reverse();
+
+		Ok(blocks_to_return)
+	}
+
+	/// Get up to `count` stored blocks of a shard, starting at block number `from` (inclusive).
+	///
+	/// Returns the blocks oldest first. Blocks which are no longer in storage, e.g. because
+	/// they have been pruned, are skipped.
+	pub fn get_blocks_by_number(
+		&self,
+		shard_identifier: &ShardIdentifierFor<SignedBlock>,
+		from: BlockNumber,
+		count: u64,
+	) -> Result<Vec<SignedBlock>> {
+		let last_block_number = match self.last_block_of_shard(shard_identifier) {
+			Some(last_block) => last_block.number,
+			None => return Ok(Vec::new()),
+		};
+
+		let mut blocks_to_return = Vec::<SignedBlock>::new();
+		for block_number in
+			(from..from.saturating_add(count)).take_while(|n| *n <= last_block_number)
+		{
+			if let Some(block_hash) = self.get_block_hash(shard_identifier, block_number)? {
+				if let Some(block) = self.get_block(&block_hash)? {
+					blocks_to_return.push(block);
+				}
+			}
+		}
+
+		Ok(blocks_to_return)
+	}
+
 	/// Update sidechain storage with blocks.
 	///
 	/// Blocks are iterated through one by one. In case more than one block per shard is included,
@@ -254,6 +370,226 @@ impl<SignedBlock: SignedBlockT> SidechainStorage<SignedBlock> {
 		}
 	}
 
+	/// Prunes all shards according to the given retention policy, `now` being the current time in ms.
+	///
+	/// Compacts the DB after blocks have been pruned, so the freed disk space is reclaimed.
+	/// `SidechainStorageLock` does the same, but compacts without holding the storage lock.
+	pub fn apply_retention_policy(
+		&mut self,
+		policy: &RetentionPolicy,
+		now: u64,
+	) -> RetentionStatus {
+		let mut pruned_blocks = 0u64;
+		if let RetentionPolicy::Prune(limits) = policy {
+			pruned_blocks = self.prune_by_limits(limits, now);
+			if pruned_blocks > 0 {
+				self.compaction().compact();
+			}
+			if let Some(max_disk_bytes) = limits.max_disk_bytes {
+				let pruned = self.prune_to_disk_budget(max_disk_bytes);
+				if pruned > 0 {
+					pruned_blocks = pruned_blocks.saturating_add(pruned);
+					self.compaction().compact();
+				}
+			}
+		}
+		self.retention_status(policy.is_archive(), pruned_blocks)
+	}
+
+	/// Prunes the oldest blocks of all shards which exceed the block count or age limit,
+	/// `now` being the current time in ms.
+	///
+	/// Returns the number of pruned blocks, the DB is not compacted.
+	pub fn prune_by_limits(&mut self, limits: &PruningLimits, now: u64) -> u64 {
+		let mut pruned_blocks = 0u64;
+		for shard in self.shards().clone() {
+			match self.prune_shard_by_limits(&shard, limits, now) {
+				Ok(pruned) => pruned_blocks = pruned_blocks.saturating_add(pruned),
+				Err(e) => error!("Could not prune shard {:?} due to {:?}", shard, e),
+			}
+		}
+		pruned_blocks
+	}
+
+	/// Prunes the oldest blocks of all shards while the DB is larger than `max_disk_bytes`.
+	///
+	/// The disk usage only shrinks once the DB is compacted after earlier pruning.
+	/// Returns the number of pruned blocks, the DB is not compacted.
+	pub fn prune_to_disk_budget(&mut self, max_disk_bytes: u64) -> u64 {
+		self.try_prune_to_disk_budget(max_disk_bytes).unwrap_or_else(|e| {
+			error!("Could not prune sidechain storage to disk budget: {:?}", e);
+			0
+		})
+	}
+
+	/// Handle to compact the DB, which can be used without access to the storage.
+	pub fn compaction(&self) -> DbCompaction {
+		self.db.compaction()
+	}
+
+	/// Collects the retention status over all shards.
+	pub fn retention_status(&self, archive: bool, pruned_blocks: u64) -> RetentionStatus {
+		let mut status = RetentionStatus { archive, pruned_blocks, ..Default::default() };
+		for shard in self.shards() {
+			let (first, last) = match self.stored_block_range(shard) {
+				Ok(Some(range)) => range,
+				Ok(None) => continue,
+				Err(e) => {
+					error!("Could not get stored blocks of shard {:?} due to {:?}", shard, e);
+					continue
+				},
+			};
+			status.stored_blocks = status
+				.stored_blocks
+				.saturating_add(last.saturating_sub(first).saturating_add(1));
+			if let Ok(Some(timestamp)) = self.get_block_timestamp(shard, first) {
+				status.oldest_block_timestamp =
+					Some(status.oldest_block_timestamp.map_or(timestamp, |t| t.min(timestamp)));
+			}
+		}
+		status.disk_usage_bytes = self.db.disk_usage().unwrap_or_else(|e| {
+			error!("Could not get disk usage of sidechain storage: {:?}", e);
+			0
+		});
+		status
+	}
+
+	/// Gets the range (first, last) of block numbers of a shard which are still in storage.
+	///
+	/// Blocks are pruned from the oldest onwards, so the stored blocks of a shard always form
+	/// a contiguous range up to the last block.
+	pub fn stored_block_range(
+		&self,
+		shard: &ShardIdentifierFor<SignedBlock>,
+	) -> Result<Option<(BlockNumber, BlockNumber)>> {
+		let last_block_number = match self.last_block_of_shard(shard) {
+			Some(last_block) => last_block.number,
+			None => return Ok(None),
+		};
+		// binary search for the lowest block number still in storage
+		let (mut low, mut high) = (0, last_block_number);
+		while low < high {
+			let mid = low + (high - low) / 2;
+			if self.get_block_hash(shard, mid)?.is_some() {
+				high = mid;
+			} else {
+				low = mid + 1;
+			}
+		}
+		Ok(Some((high, last_block_number)))
+	}
+
+	/// Prunes the oldest blocks of a shard which exceed the block count or age limit.
+	///
+	/// Returns the number of pruned blocks.
+	fn prune_shard_by_limits(
+		&mut self,
+		shard: &ShardIdentifierFor<SignedBlock>,
+		limits: &PruningLimits,
+		now: u64,
+	) -> Result<u64> {
+		let (first, last) = match self.stored_block_range(shard)? {
+			Some(range) => range,
+			None => return Ok(0),
+		};
+		let mut keep_from = first;
+		if let Some(max_blocks) = limits.max_blocks {
+			keep_from = keep_from.max(last.saturating_add(1).saturating_sub(max_blocks));
+		}
+		if let Some(max_age) = limits.max_age {
+			let oldest_allowed = now.saturating_sub(max_age.as_millis() as u64);
+			keep_from =
+				keep_from.max(self.first_block_since(shard, first, last, oldest_allowed)?);
+		}
+		self.prune_shard_before(shard, first, keep_from.min(last))
+	}
+
+	/// Prunes the oldest blocks of every shard proportionally, such that the remaining blocks
+	/// are estimated to fit into the given disk budget.
+	///
+	/// Returns the number of pruned blocks.
+	fn try_prune_to_disk_budget(&mut self, max_disk_bytes: u64) -> Result<u64> {
+		let disk_usage = self.db.disk_usage()?;
+		if disk_usage <= max_disk_bytes {
+			return Ok(0)
+		}
+		let mut pruned_blocks = 0u64;
+		for shard in self.shards().clone() {
+			let (first, last) = match self.stored_block_range(&shard)? {
+				Some(range) => range,
+				None => continue,
+			};
+			let stored_blocks = last.saturating_sub(first).saturating_add(1);
+			let blocks_to_keep =
+				(stored_blocks as u128 * max_disk_bytes as u128 / disk_usage as u128) as u64;
+			let keep_from = last.saturating_add(1).saturating_sub(blocks_to_keep.max(1));
+			pruned_blocks =
+				pruned_blocks.saturating_add(self.prune_shard_before(&shard, first, keep_from)?);
+		}
+		Ok(pruned_blocks)
+	}
+
+	/// Gets the first block number within [first, last] whose timestamp is not older than
+	/// `oldest_allowed`, or `last` if there is no such block.
+	fn first_block_since(
+		&self,
+		shard: &ShardIdentifierFor<SignedBlock>,
+		first: BlockNumber,
+		last: BlockNumber,
+		oldest_allowed: u64,
+	) -> Result<BlockNumber> {
+		// block timestamps are increasing with the block number
+		let (mut low, mut high) = (first, last);
+		while low < high {
+			let mid = low + (high - low) / 2;
+			let is_recent = self
+				.get_block_timestamp(shard, mid)?
+				.map_or(false, |timestamp| timestamp >= oldest_allowed);
+			if is_recent {
+				high = mid;
+			} else {
+				low = mid + 1;
+			}
+		}
+		Ok(high)
+	}
+
+	/// Prunes all blocks of a shard with a block number in [first, keep_from).
+	///
+	/// Returns the number of pruned blocks.
+	fn prune_shard_before(
+		&mut self,
+		shard: &ShardIdentifierFor<SignedBlock>,
+		first: BlockNumber,
+		keep_from: BlockNumber,
+	) -> Result<u64> {
+		if keep_from <= first {
+			return Ok(0)
+		}
+		trace!("pruning sidechain blocks [{}, {}) for shard {:?}", first, keep_from, shard);
+		let mut batch = WriteBatch::default();
+		for block_number in first..keep_from {
+			if let Some(block_hash) = self.get_block_hash(shard, block_number)? {
+				self.delete_block(&mut batch, &block_hash, &block_number, shard);
+			}
+		}
+		self.db.write(batch)?;
+		Ok(keep_from - first)
+	}
+
+	/// gets the timestamp (ms) of the sidechain block of the given shard and block number, if there is such a block
+	fn get_block_timestamp(
+		&self,
+		shard: &ShardIdentifierFor<SignedBlock>,
+		block_number: BlockNumber,
+	) -> Result<Option<u64>> {
+		match self.get_block_hash(shard, block_number)? {
+			Some(block_hash) =>
+				Ok(self.get_block(&block_hash)?.map(|block| block.block().block_data().timestamp())),
+			None => Ok(None),
+		}
+	}
+
 	fn add_block_to_batch(
 		&mut self,
 		signed_block: &SignedBlock,
@@ -394,11 +730,13 @@ impl<SignedBlock: SignedBlockT> SidechainStorage<SignedBlock> {
 mod test {
 	use super::*;
 	use crate::test_utils::{
-		create_signed_block_with_shard as create_signed_block, create_temp_dir, get_storage,
+		create_signed_block_with_shard as create_signed_block,
+		create_signed_block_with_shard_and_timestamp, create_temp_dir, get_storage,
 	};
 	use itp_types::ShardIdentifier;
 	use its_primitives::{traits::SignedBlock as SignedBlockT, types::SignedBlock};
 	use sp_core::H256;
+	use std::time::Duration;
 
 	#[test]
 	fn load_shards_from_db_works() {
@@ -482,6 +820,31 @@ mod test {
 		}
 	}
 
+	#[test]
+	fn storage_of_lower_version_is_purged_on_load() {
+		// given
+		let temp_dir = create_temp_dir();
+		let shard = H256::from_low_u64_be(1);
+		let signed_block = create_signed_block(1, shard);
+		{
+			let mut sidechain_db = get_storage(temp_dir.path().to_path_buf());
+			sidechain_db.store_blocks(vec![signed_block.clone()]).unwrap();
+			sidechain_db.db.put(STORAGE_VERSION_KEY, 0u32).unwrap();
+		}
+
+		// when
+		let updated_sidechain_db = get_storage(temp_dir.path().to_path_buf());
+
+		// then
+		assert!(updated_sidechain_db.shards().is_empty());
+		assert!(updated_sidechain_db.last_block_of_shard(&shard).is_none());
+		assert!(updated_sidechain_db.get_block(&signed_block.hash()).unwrap().is_none());
+		assert_eq!(
+			updated_sidechain_db.db.get::<_, u32>(STORAGE_VERSION_KEY).unwrap(),
+			Some(SIDECHAIN_STORAGE_VERSION)
+		);
+	}
+
 	#[test]
 	fn add_last_block_works() {
 		let temp_dir = create_temp_dir();
@@ -1178,4 +1541,152 @@ mod test {
 			assert!(updated_sidechain_db.get_block(&block_two_s.hash()).unwrap().is_none());
 		}
 	}
+
+	#[test]
+	fn stored_block_range_works() {
+		let temp_dir = create_temp_dir();
+		let shard = H256::from_low_u64_be(1);
+		let blocks: Vec<_> = (1..=5).map(|n| create_signed_block(n, shard)).collect();
+		let mut sidechain_db = get_storage(temp_dir.path().to_path_buf());
+		sidechain_db.store_blocks(blocks).unwrap();
+		assert_eq!(sidechain_db.stored_block_range(&shard).unwrap(), Some((1, 5)));
+
+		sidechain_db.prune_shard_from_block_number(&shard, 2).unwrap();
+
+		assert_eq!(sidechain_db.stored_block_range(&shard).unwrap(), Some((3, 5)));
+		assert_eq!(sidechain_db.stored_block_range(&H256::from_low_u64_be(2)).unwrap(), None);
+	}
+
+	#[test]
+	fn apply_retention_policy_prunes_by_block_count() {
+		// given
+		let temp_dir = create_temp_dir();
+		let shard = H256::from_low_u64_be(1);
+		let blocks: Vec<_> = (1..=5).map(|n| create_signed_block(n, shard)).collect();
+		let mut sidechain_db = get_storage(temp_dir.path().to_path_buf());
+		sidechain_db.store_blocks(blocks.clone()).unwrap();
+		let policy =
+			RetentionPolicy::Prune(PruningLimits { max_blocks: Some(2), ..Default::default() });
+
+		// when
+		let status = sidechain_db.apply_retention_policy(&policy, 0);
+
+		// then
+		assert!(!status.archive);
+		assert_eq!(status.pruned_blocks, 3);
+		assert_eq!(status.stored_blocks, 2);
+		assert_eq!(sidechain_db.stored_block_range(&shard).unwrap(), Some((4, 5)));
+		assert!(sidechain_db.get_block(&blocks[2].hash()).unwrap().is_none());
+		assert_eq!(sidechain_db.get_block(&blocks[3].hash()).unwrap().unwrap(), blocks[3]);
+	}
+
+	#[test]
+	fn apply_retention_policy_always_keeps_last_block() {
+		// given
+		let temp_dir = create_temp_dir();
+		let shard = H256::from_low_u64_be(1);
+		let blocks: Vec<_> = (1..=3).map(|n| create_signed_block(n, shard)).collect();
+		let mut sidechain_db = get_storage(temp_dir.path().to_path_buf());
+		sidechain_db.store_blocks(blocks.clone()).unwrap();
+		let policy =
+			RetentionPolicy::Prune(PruningLimits { max_blocks: Some(0), ..Default::default() });
+
+		// when
+		let status = sidechain_db.apply_retention_policy(&policy, 0);
+
+		// then
+		assert_eq!(status.pruned_blocks, 2);
+		assert_eq!(status.stored_blocks, 1);
+		assert_eq!(sidechain_db.last_block_of_shard(&shard).unwrap().hash, blocks[2].hash());
+		assert_eq!(sidechain_db.get_block(&blocks[2].hash()).unwrap().unwrap(), blocks[2]);
+	}
+
+	#[test]
+	fn storage_lock_applies_retention_policy() {
+		use crate::interface::{BlockPruner, FetchBlocks, SidechainStorageLock};
+
+		// given
+		let temp_dir = create_temp_dir();
+		let shard = H256::from_low_u64_be(1);
+		let blocks: Vec<_> = (1..=5).map(|n| create_signed_block(n, shard)).collect();
+		get_storage(temp_dir.path().to_path_buf()).store_blocks(blocks.clone()).unwrap();
+		let storage =
+			SidechainStorageLock::<SignedBlock>::from_base_path(temp_dir.path().into()).unwrap();
+		let policy =
+			RetentionPolicy::Prune(PruningLimits { max_blocks: Some(2), ..Default::default() });
+
+		// when
+		let status = storage.apply_retention_policy(&policy);
+
+		// then
+		assert_eq!(status.pruned_blocks, 3);
+		assert_eq!(status.stored_blocks, 2);
+		assert_eq!(storage.fetch_block(&blocks[2].hash()).unwrap(), None);
+		assert_eq!(storage.fetch_block(&blocks[3].hash()).unwrap().unwrap(), blocks[3]);
+	}
+
+	#[test]
+	fn apply_retention_policy_prunes_by_age() {
+		// given
+		let temp_dir = create_temp_dir();
+		let shard = H256::from_low_u64_be(1);
+		let blocks: Vec<_> = (1..=5)
+			.map(|n| create_signed_block_with_shard_and_timestamp(n, shard, n * 1000))
+			.collect();
+		let mut sidechain_db = get_storage(temp_dir.path().to_path_buf());
+		sidechain_db.store_blocks(blocks).unwrap();
+		let policy = RetentionPolicy::Prune(PruningLimits {
+			max_age: Some(Duration::from_secs(3)),
+			..Default::default()
+		});
+
+		// when
+		let status = sidechain_db.apply_retention_policy(&policy, 6000);
+
+		// then
+		assert_eq!(status.pruned_blocks, 2);
+		assert_eq!(status.oldest_block_timestamp, Some(3000));
+		assert_eq!(sidechain_db.stored_block_range(&shard).unwrap(), Some((3, 5)));
+	}
+
+	#[test]
+	fn apply_retention_policy_prunes_to_disk_budget() {
+		// given
+		let temp_dir = create_temp_dir();
+		let shard = H256::from_low_u64_be(1);
+		let blocks: Vec<_> = (1..=5).map(|n| create_signed_block(n, shard)).collect();
+		let mut sidechain_db = get_storage(temp_dir.path().to_path_buf());
+		sidechain_db.store_blocks(blocks).unwrap();
+		let policy =
+			RetentionPolicy::Prune(PruningLimits { max_disk_bytes: Some(1), ..Default::default() });
+
+		// when
+		let status = sidechain_db.apply_retention_policy(&policy, 0);
+
+		// then
+		assert_eq!(status.pruned_blocks, 4);
+		assert_eq!(sidechain_db.stored_block_range(&shard).unwrap(), Some((5, 5)));
+	}
+
+	#[test]
+	fn apply_retention_policy_does_not_prune_in_archive_mode() {
+		// given
+		let temp_dir = create_temp_dir();
+		let shard = H256::from_low_u64_be(1);
+		let blocks: Vec<_> = (1..=5)
+			.map(|n| create_signed_block_with_shard_and_timestamp(n, shard, n * 1000))
+			.collect();
+		let mut sidechain_db = get_storage(temp_dir.path().to_path_buf());
+		sidechain_db.store_blocks(blocks).unwrap();
+
+		// when
+		let status = sidechain_db.apply_retention_policy(&RetentionPolicy::Archive, u64::MAX);
+
+		// then
+		assert!(status.archive);
+		assert_eq!(status.pruned_blocks, 0);
+		assert_eq!(status.stored_blocks, 5);
+		assert_eq!(status.oldest_block_timestamp, Some(1000));
+		assert_eq!(sidechain_db.stored_block_range(&shard).unwrap(), Some((1, 5)));
+	}
 }
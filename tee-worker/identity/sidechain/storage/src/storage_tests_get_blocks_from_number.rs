@@ -0,0 +1,63 @@
+// Copyright 2020-2024 Trust Computing GmbH.
+// This file is part of Litentry.
+//
+// Litentry is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// Litentry is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with Litentry.  If not, see <https://www.gnu.org/licenses/>.
+
+use crate::test_utils::{
+	create_signed_block_with_parenthash as create_signed_block, default_shard,
+	fill_storage_with_blocks, get_storage,
+};
+use itp_types::BlockHash;
+use its_primitives::traits::SignedBlock;
+
+#[test]
+fn get_blocks_from_number_works_for_regular_case() {
+	let block_1 = create_signed_block(1, BlockHash::default());
+	let block_2 = create_signed_block(2, block_1.hash());
+	let block_3 = create_signed_block(3, block_2.hash());
+	let block_4 = create_signed_block(4, block_3.hash());
+
+	let temp_dir =
+		fill_storage_with_blocks(vec![block_1, block_2.clone(), block_3, block_4.clone()]);
+
+	{
+		let updated_sidechain_db = get_storage(temp_dir.path().to_path_buf());
+		let blocks_2_to_4 =
+			updated_sidechain_db.get_blocks_from_number(&default_shard(), 2).unwrap();
+
+		assert_eq!(3, blocks_2_to_4.len());
+		assert_eq!(block_2.hash(), blocks_2_to_4.first().unwrap().hash());
+		assert_eq!(block_4.hash(), blocks_2_to_4.last().unwrap().hash());
+	}
+}
+
+#[test]
+fn get_blocks_from_number_stops_at_pruned_blocks() {
+	let block_1 = create_signed_block(1, BlockHash::default());
+	let block_2 = create_signed_block(2, block_1.hash());
+	let block_3 = create_signed_block(3, block_2.hash());
+	let block_4 = create_signed_block(4, block_3.hash());
+
+	let temp_dir = fill_storage_with_blocks(vec![block_1, block_2, block_3.clone(), block_4]);
+
+	{
+		let mut updated_sidechain_db = get_storage(temp_dir.path().to_path_buf());
+		updated_sidechain_db.prune_shard_from_block_number(&default_shard(), 2).unwrap();
+
+		let blocks = updated_sidechain_db.get_blocks_from_number(&default_shard(), 0).unwrap();
+
+		assert_eq!(2, blocks.len());
+		assert_eq!(block_3.hash(), blocks.first().unwrap().hash());
+	}
+}
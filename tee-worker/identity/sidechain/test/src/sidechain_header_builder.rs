@@ -27,6 +27,7 @@ pub struct SidechainHeaderBuilder {
 	shard_id: ShardIdentifier,
 	block_data_hash: H256,
 	next_finalization_block_number: u64,
+	state_root: H256,
 }
 
 impl Default for SidechainHeaderBuilder {
@@ -37,6 +38,7 @@ impl Default for SidechainHeaderBuilder {
 			shard_id: Default::default(),
 			block_data_hash: Default::default(),
 			next_finalization_block_number: 1,
+			state_root: Default::default(),
 		}
 	}
 }
@@ -49,6 +51,7 @@ impl SidechainHeaderBuilder {
 			shard_id: ShardIdentifier::random(),
 			block_data_hash: H256::random(),
 			next_finalization_block_number: 1,
+			state_root: H256::random(),
 		}
 	}
 
@@ -80,6 +83,11 @@ impl SidechainHeaderBuilder {
 		self
 	}
 
+	pub fn with_state_root(mut self, state_root: H256) -> Self {
+		self.state_root = state_root;
+		self
+	}
+
 	pub fn build(self) -> Header {
 		Header {
 			parent_hash: self.parent_hash,
@@ -87,6 +95,7 @@ impl SidechainHeaderBuilder {
 			shard_id: self.shard_id,
 			block_data_hash: self.block_data_hash,
 			next_finalization_block_number: self.next_finalization_block_number,
+			state_root: self.state_root,
 		}
 	}
 }